@@ -3,7 +3,7 @@ use llm::{Model, ModelArchitecture, InferenceSession, InferenceRequest, Inferenc
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::{info, warn, error, debug};
-use crate::secrets::{SecretMatch, SecretSeverity, SecretCategory, ValidationResult};
+use crate::secrets::{rotation_actions, SecretMatch, SecretSeverity, SecretCategory, ValidationResult};
 
 /// AI-powered triage agent for secret analysis
 pub struct AITriageAgent {
@@ -23,6 +23,9 @@ pub struct TriageResult {
     pub risk_factors: Vec<RiskFactor>,
     pub context_analysis: ContextAnalysis,
     pub confidence: f64,          // 0.0 - 1.0
+    /// Estimated model tokens the triage read and wrote, charged to the workspace AI quota
+    #[serde(default)]
+    pub tokens_used: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -236,10 +239,15 @@ impl AITriageAgent {
         let analysis = self.generate_ai_analysis(secret, &risk_factors, &context_analysis).await?;
         
         // Generate suggested actions
-        let suggested_actions = self.generate_suggested_actions(secret, &risk_factors, revocation_priority.clone()).await?;
+        let suggested_actions = self.generate_suggested_actions(secret, &risk_factors, revocation_priority.clone(), context).await?;
         
         // Calculate confidence
         let confidence = self.calculate_confidence(&risk_factors, validation_result);
+
+        let tokens_used = estimate_tokens(&secret.context)
+            + estimate_tokens(&secret.detector_name)
+            + estimate_tokens(&analysis)
+            + suggested_actions.iter().map(|action| estimate_tokens(action)).sum::<u64>();
         
         Ok(TriageResult {
             secret_hash: secret.hash.clone(),
@@ -251,6 +259,7 @@ impl AITriageAgent {
             risk_factors,
             context_analysis,
             confidence,
+            tokens_used,
         })
     }
 
@@ -549,6 +558,7 @@ impl AITriageAgent {
         secret: &SecretMatch,
         risk_factors: &[RiskFactor],
         priority: RevocationPriority,
+        context: &TriageContext,
     ) -> Result<Vec<String>> {
         let mut actions = Vec::new();
 
@@ -607,6 +617,9 @@ impl AITriageAgent {
             }
         }
 
+        // Provider scripts that rotate this kind of credential, for the leak's repository
+        actions.extend(rotation_actions(secret, Some(&context.repository_name)));
+
         Ok(actions)
     }
 
@@ -660,6 +673,11 @@ impl AITriageAgent {
     }
 }
 
+/// Rough token count of `text` for quota accounting: about four characters per token
+pub fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
 #[derive(Debug, Clone)]
 pub struct TriageContext {
     pub repository_name: String,
@@ -759,6 +777,10 @@ mod tests {
             end_position: 20,
             line_number: Some(42),
             filename: Some("config.env".to_string()),
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
             entropy: 4.5,
             severity: SecretSeverity::High,
             category: SecretCategory::CloudProvider,
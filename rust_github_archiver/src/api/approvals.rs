@@ -0,0 +1,209 @@
+// Two-person approval of dangerous actions: requests, approvals and rejections
+use axum::{
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::api::findings::{api_error, ApiError};
+use crate::api::openapi::ErrorBody;
+use crate::api::pagination::Page;
+use crate::api::state::AppState;
+use crate::auth::{ApprovalAction, ApprovalPolicy, ApprovalRequest, ApprovalStatus, User};
+use crate::core::AuditLog;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct NewApprovalRequest {
+    pub action: ApprovalAction,
+    /// Exactly what the command will be run on; `database export` and `database erase`
+    /// print it when they stop for want of an approval
+    pub target: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ApprovalQuery {
+    /// pending, approved, rejected or executed
+    status: Option<ApprovalStatus>,
+}
+
+fn classify(error: anyhow::Error) -> ApiError {
+    let message = error.to_string();
+    let status = if message.starts_with("No approval request") {
+        StatusCode::NOT_FOUND
+    } else if message.starts_with("Forbidden") {
+        StatusCode::FORBIDDEN
+    } else if message.starts_with("Cannot ") {
+        StatusCode::CONFLICT
+    } else if message.starts_with("Invalid ") {
+        StatusCode::BAD_REQUEST
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    api_error(status, message)
+}
+
+fn policy(state: &AppState) -> Result<ApprovalPolicy, ApiError> {
+    ApprovalPolicy::from_config(&state.config.security).map_err(classify)
+}
+
+fn audit(state: &AppState, action: &str, user: &User, request: &ApprovalRequest) {
+    let details = json!({
+        "request": request.id,
+        "action": request.action,
+        "target": request.target,
+        "requested_by": request.requested_by,
+        "decided_by": request.decided_by,
+    });
+    if let Err(e) = AuditLog::from_config(&state.config.logging).record(action, &user.username, details) {
+        tracing::warn!("Could not record {} in the audit log: {}", action, e);
+    }
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/approvals", tag = "approvals",
+    params(ApprovalQuery),
+    responses(
+        (status = 200, body = Page<ApprovalRequest>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody)
+    )
+)]
+pub async fn list_approvals(
+    State(state): State<AppState>,
+    Query(query): Query<ApprovalQuery>,
+) -> Result<Json<Page<ApprovalRequest>>, ApiError> {
+    let requests = state.user_manager.approval_requests(query.status).await.map_err(classify)?;
+    Ok(Json(Page::all(requests)))
+}
+
+/// Ask for a second person to approve an action, before running it with `--approval <id>`
+#[utoipa::path(
+    post, path = "/api/v1/approvals", tag = "approvals",
+    request_body = NewApprovalRequest,
+    responses(
+        (status = 201, body = ApprovalRequest),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody)
+    )
+)]
+pub async fn request_approval(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(new_request): Json<NewApprovalRequest>,
+) -> Result<(StatusCode, Json<ApprovalRequest>), ApiError> {
+    if !user.role_kind().can_annotate() {
+        return Err(api_error(StatusCode::FORBIDDEN, "Viewers cannot request dangerous actions"));
+    }
+    if new_request.target.trim().is_empty() {
+        return Err(api_error(StatusCode::BAD_REQUEST, "Invalid request: target is required"));
+    }
+    let request = state
+        .user_manager
+        .request_approval(new_request.action, new_request.target.trim(), &user.username, new_request.reason)
+        .await
+        .map_err(classify)?;
+    audit(&state, "approval_requested", &user, &request);
+    Ok((StatusCode::CREATED, Json(request)))
+}
+
+/// Approve a pending request. The approver must not be its requester and needs the role
+/// the approval policy names for the action.
+#[utoipa::path(
+    post, path = "/api/v1/approvals/{id}/approve", tag = "approvals",
+    params(("id" = i64, Path)),
+    responses(
+        (status = 200, body = ApprovalRequest),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "The requester, or a role that may not approve this action", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody),
+        (status = 409, description = "Already decided or expired", body = ErrorBody)
+    )
+)]
+pub async fn approve_request(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<i64>,
+) -> Result<Json<ApprovalRequest>, ApiError> {
+    let policy = policy(&state)?;
+    let request = state.user_manager.decide_approval(id, &user, true, &policy).await.map_err(classify)?;
+    audit(&state, "approval_granted", &user, &request);
+    Ok(Json(request))
+}
+
+#[utoipa::path(
+    post, path = "/api/v1/approvals/{id}/reject", tag = "approvals",
+    params(("id" = i64, Path)),
+    responses(
+        (status = 200, body = ApprovalRequest),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "The requester, or a role that may not approve this action", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody),
+        (status = 409, description = "Already decided or expired", body = ErrorBody)
+    )
+)]
+pub async fn reject_request(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<i64>,
+) -> Result<Json<ApprovalRequest>, ApiError> {
+    let policy = policy(&state)?;
+    let request = state.user_manager.decide_approval(id, &user, false, &policy).await.map_err(classify)?;
+    audit(&state, "approval_rejected", &user, &request);
+    Ok(Json(request))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Role;
+    use std::collections::HashMap;
+
+    fn new_request(target: &str) -> Json<NewApprovalRequest> {
+        Json(NewApprovalRequest { action: ApprovalAction::Erase, target: target.to_string(), reason: None })
+    }
+
+    #[tokio::test]
+    async fn test_requester_is_the_signed_in_user_and_cannot_approve() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = AppState::for_tests(dir.path());
+        state.config.security.approval_policy = HashMap::from([("erase".to_string(), "admin".to_string())]);
+        let (alice, bob) = (state.test_user("alice", Role::Admin).await, state.test_user("bob", Role::Admin).await);
+
+        let (status, Json(request)) =
+            request_approval(State(state.clone()), Extension(alice.clone()), new_request(" secrets.db: abc ")).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!((request.requested_by.as_str(), request.target.as_str()), ("alice", "secrets.db: abc"));
+
+        let error = approve_request(State(state.clone()), Extension(alice), Path(request.id)).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::FORBIDDEN);
+
+        let Json(approved) = approve_request(State(state.clone()), Extension(bob.clone()), Path(request.id)).await.unwrap();
+        assert_eq!((approved.status, approved.decided_by.as_deref()), (ApprovalStatus::Approved, Some("bob")));
+        let error = reject_request(State(state.clone()), Extension(bob), Path(request.id)).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::CONFLICT);
+
+        let Json(page) =
+            list_approvals(State(state), Query(ApprovalQuery { status: Some(ApprovalStatus::Approved) })).await.unwrap();
+        assert_eq!(page.data.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_viewers_and_empty_targets_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path());
+        let viewer = state.test_user("victor", Role::Viewer).await;
+        let analyst = state.test_user("anna", Role::Analyst).await;
+
+        let error = request_approval(State(state.clone()), Extension(viewer), new_request("secrets.db: abc")).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::FORBIDDEN);
+        let error = request_approval(State(state.clone()), Extension(analyst.clone()), new_request("  ")).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::BAD_REQUEST);
+        let error = approve_request(State(state), Extension(analyst), Path(42)).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::NOT_FOUND);
+    }
+}
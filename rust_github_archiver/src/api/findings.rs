@@ -0,0 +1,805 @@
+// Finding queries, saved searches, tags, notes and evidence attachments
+use axum::{
+    extract::{Extension, Multipart, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::api::openapi::ErrorBody;
+use crate::api::pagination::{decode_cursor, page_size, Page};
+use crate::api::state::AppState;
+use crate::auth::User;
+use crate::performance::{
+    guess_content_type, parse_as_of, CacheScope, FindingAnnotations, FindingAttachment, FindingNote, FindingStatus, SavedSearch, SecretDatabase, SecretQueryFilters, SecretRecord, SimilarFinding, TagCount,
+};
+use crate::secrets::{SecretCategory, SecretSeverity};
+use crate::sinks::AnnotationUpdate;
+
+pub(super) type ApiError = (StatusCode, Json<Value>);
+
+#[derive(Deserialize, ToSchema)]
+pub struct NoteRequest {
+    body: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct StatusRequest {
+    status: FindingStatus,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TagRequest {
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SavedSearchRequest {
+    name: String,
+    #[serde(default)]
+    filters: SecretQueryFilters,
+    #[serde(default)]
+    alert: bool,
+    webhook_url: Option<String>,
+}
+
+/// `?limit=100&cursor=...`
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageQuery {
+    limit: Option<u32>,
+    cursor: Option<String>,
+}
+
+/// Findings counted for `total_estimate`; beyond this the count is a lower bound
+const TOTAL_ESTIMATE_CAP: u32 = 100_000;
+
+/// Position of a finding in newest-first order
+type FindingCursor = (String, i64);
+
+#[derive(Serialize, ToSchema)]
+pub struct TaggedFinding {
+    #[serde(flatten)]
+    record: SecretRecord,
+    tags: Vec<String>,
+}
+
+/// Count matches for the first page only, so paging through millions of findings stays cheap
+fn estimate_total(db: &SecretDatabase, filters: &SecretQueryFilters, warnings: &mut Vec<String>) -> anyhow::Result<Option<i64>> {
+    if filters.after.is_some() {
+        return Ok(None);
+    }
+    let total = db.count_secrets(filters, TOTAL_ESTIMATE_CAP)?;
+    if total >= TOTAL_ESTIMATE_CAP as i64 {
+        warnings.push(format!("More than {} findings match; total_estimate is a lower bound", TOTAL_ESTIMATE_CAP));
+    }
+    Ok(Some(total))
+}
+
+/// Query string for `GET /api/v1/findings`; `tags` is comma-separated and every tag must match
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FindingQuery {
+    severity: Option<String>,
+    /// e.g. `cloud-provider` or `api-key`
+    category: Option<String>,
+    detector: Option<String>,
+    /// Glob over the file path, e.g. `*.env`
+    filename: Option<String>,
+    #[serde(default)]
+    verified: bool,
+    days: Option<u32>,
+    /// First day to include, `YYYY-MM-DD`
+    since: Option<NaiveDate>,
+    /// Last day to include, `YYYY-MM-DD`
+    until: Option<NaiveDate>,
+    limit: Option<u32>,
+    tags: Option<String>,
+    repo: Option<String>,
+    /// Include findings suppressed by the allowlist (flagged `suppressed`)
+    #[serde(default)]
+    suppressed: bool,
+    /// Findings with the severity and status they had at this time: `YYYY-MM-DD` (end of
+    /// day, UTC) or RFC 3339
+    as_of: Option<String>,
+    /// `cursor` from the previous page
+    cursor: Option<String>,
+}
+
+/// `?limit=20`
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SimilarQuery {
+    limit: Option<u32>,
+}
+
+pub(super) fn api_error(status: StatusCode, message: impl ToString) -> ApiError {
+    (status, Json(json!(ErrorBody { error: message.to_string() })))
+}
+
+fn classify(error: anyhow::Error) -> ApiError {
+    let message = error.to_string();
+    let status = if message.starts_with("No finding") || message.starts_with("No saved search") {
+        StatusCode::NOT_FOUND
+    } else if message.contains("must not be empty")
+        || message.contains("limit is")
+        || message.starts_with("Invalid ")
+        || message.starts_with("Tags must")
+    {
+        StatusCode::BAD_REQUEST
+    } else {
+        tracing::error!("Findings API error: {}", message);
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    api_error(status, message)
+}
+
+fn require_annotator(user: &User) -> Result<(), ApiError> {
+    if user.role_kind().can_annotate() {
+        Ok(())
+    } else {
+        Err(api_error(StatusCode::FORBIDDEN, "Viewers cannot change searches, tags, notes or attachments"))
+    }
+}
+
+/// Run a query on a pooled findings database connection, off the async workers (rusqlite blocks)
+pub(super) async fn with_db<T, F>(state: &AppState, f: F) -> Result<T, ApiError>
+where
+    T: Send + 'static,
+    F: FnOnce(&SecretDatabase) -> anyhow::Result<T> + Send + 'static,
+{
+    state.findings_db.interact(f).await.map_err(classify)
+}
+
+/// Forward new annotations to ticket sinks in the background; the API call has already succeeded
+fn publish(state: &AppState, update: AnnotationUpdate) {
+    if state.sinks.is_empty() {
+        return;
+    }
+    let sinks = state.sinks.clone();
+    tokio::spawn(async move {
+        let report = sinks.publish_annotations(&update).await;
+        if report.failed_batches > 0 {
+            tracing::warn!("Annotations for {} not delivered to every sink: {:?}", update.secret_hash, report.errors);
+        }
+    });
+}
+
+/// Newest first, in pages of `limit` (default 100, at most 1000)
+#[utoipa::path(
+    get, path = "/api/v1/findings", tag = "findings",
+    params(FindingQuery),
+    responses(
+        (status = 200, body = Page<TaggedFinding>),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody)
+    )
+)]
+pub async fn list_findings(
+    State(state): State<AppState>,
+    Query(query): Query<FindingQuery>,
+) -> Result<Json<Page<TaggedFinding>>, ApiError> {
+    let min_severity = query
+        .severity
+        .as_deref()
+        .map(str::parse::<SecretSeverity>)
+        .transpose()
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
+    let category = query
+        .category
+        .as_deref()
+        .map(str::parse::<SecretCategory>)
+        .transpose()
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
+    let as_of = query
+        .as_of
+        .as_deref()
+        .map(parse_as_of)
+        .transpose()
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
+    let mut warnings = Vec::new();
+    let limit = page_size(query.limit, 100, &mut warnings);
+    let filters = SecretQueryFilters {
+        min_severity,
+        category,
+        detector_name: query.detector,
+        filename: query.filename,
+        verified_only: query.verified,
+        last_n_days: query.days,
+        since: query.since,
+        until: query.until,
+        limit: Some(limit),
+        tags: query
+            .tags
+            .map(|tags| tags.split(',').filter(|t| !t.trim().is_empty()).map(str::to_string).collect())
+            .unwrap_or_default(),
+        repository: query.repo,
+        include_suppressed: query.suppressed,
+        after: decode_cursor::<FindingCursor>(query.cursor.as_deref())?,
+        as_of,
+    };
+
+    let page = with_db(&state, move |db| {
+        let mut findings = Vec::new();
+        for record in db.query_secrets(&filters)? {
+            let tags = db.finding_tags(&record.secret_hash)?;
+            findings.push(TaggedFinding { record, tags });
+        }
+        let total = estimate_total(db, &filters, &mut warnings)?;
+        Ok(Page::keyset(findings, limit, |f| (f.record.created_at.clone(), f.record.id))
+            .with_total_estimate(total)
+            .with_warnings(warnings))
+    })
+    .await?;
+    Ok(Json(page))
+}
+
+/// Findings most like this one, best first, for triaging a new kind of false positive
+#[utoipa::path(
+    get, path = "/api/v1/findings/{hash}/similar", tag = "findings",
+    params(("hash" = String, Path, description = "Finding hash"), SimilarQuery),
+    responses(
+        (status = 200, body = Page<SimilarFinding>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn similar_findings(
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+    Query(query): Query<SimilarQuery>,
+) -> Result<Json<Page<SimilarFinding>>, ApiError> {
+    let mut warnings = Vec::new();
+    let limit = page_size(query.limit, 20, &mut warnings);
+    let similar = with_db(&state, move |db| db.similar_findings(&hash, limit as usize)).await?;
+    Ok(Json(Page::all(similar).with_warnings(warnings)))
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/searches", tag = "searches",
+    responses(
+        (status = 200, body = Page<SavedSearch>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody)
+    )
+)]
+pub async fn list_saved_searches(State(state): State<AppState>) -> Result<Json<Page<SavedSearch>>, ApiError> {
+    Ok(Json(Page::all(with_db(&state, |db| db.list_saved_searches()).await?)))
+}
+
+/// Create or replace a saved search by name
+#[utoipa::path(
+    post, path = "/api/v1/searches", tag = "searches",
+    request_body = SavedSearchRequest,
+    responses(
+        (status = 201, body = SavedSearch),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody)
+    )
+)]
+pub async fn save_search(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(request): Json<SavedSearchRequest>,
+) -> Result<(StatusCode, Json<SavedSearch>), ApiError> {
+    require_annotator(&user)?;
+    let search = with_db(&state, move |db| {
+        db.save_search(&request.name, &user.username, &request.filters, request.alert, request.webhook_url.as_deref())
+    })
+    .await?;
+    Ok((StatusCode::CREATED, Json(search)))
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/searches/{name}/results", tag = "searches",
+    params(("name" = String, Path), PageQuery),
+    responses(
+        (status = 200, body = Page<SecretRecord>),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn run_saved_search(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<Page<SecretRecord>>, ApiError> {
+    let mut warnings = Vec::new();
+    let limit = page_size(query.limit, 100, &mut warnings);
+    let after = decode_cursor::<FindingCursor>(query.cursor.as_deref())?;
+
+    let page = with_db(&state, move |db| {
+        let first_page = after.is_none();
+        let records = db.run_saved_search_after(&name, Some(limit), after)?;
+        let total = match db.get_saved_search(&name)? {
+            Some(search) if first_page => estimate_total(db, &search.filters, &mut warnings)?,
+            _ => None,
+        };
+        Ok(Page::keyset(records, limit, |r| (r.created_at.clone(), r.id))
+            .with_total_estimate(total)
+            .with_warnings(warnings))
+    })
+    .await?;
+    Ok(Json(page))
+}
+
+#[utoipa::path(
+    delete, path = "/api/v1/searches/{name}", tag = "searches",
+    params(("name" = String, Path)),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn delete_saved_search(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    require_annotator(&user)?;
+    match with_db(&state, move |db| db.delete_saved_search(&name)).await? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(api_error(StatusCode::NOT_FOUND, "No such saved search")),
+    }
+}
+
+/// Tag facets across all findings
+#[utoipa::path(
+    get, path = "/api/v1/tags", tag = "tags",
+    responses(
+        (status = 200, body = Page<TagCount>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody)
+    )
+)]
+pub async fn list_tags(State(state): State<AppState>) -> Result<Json<Page<TagCount>>, ApiError> {
+    Ok(Json(Page::all(with_db(&state, |db| db.cached(CacheScope::Tags, "tag_counts", |db| db.tag_counts())).await?)))
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/findings/{hash}/tags", tag = "tags",
+    params(("hash" = String, Path, description = "Finding hash")),
+    responses(
+        (status = 200, body = Vec<String>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn get_finding_tags(
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+) -> Result<Json<Vec<String>>, ApiError> {
+    Ok(Json(with_db(&state, move |db| db.finding_tags(&hash)).await?))
+}
+
+#[utoipa::path(
+    post, path = "/api/v1/findings/{hash}/tags", tag = "tags",
+    params(("hash" = String, Path, description = "Finding hash")),
+    request_body = TagRequest,
+    responses(
+        (status = 200, description = "Tags now on the finding", body = Vec<String>),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn add_finding_tags(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(hash): Path<String>,
+    Json(request): Json<TagRequest>,
+) -> Result<Json<Vec<String>>, ApiError> {
+    require_annotator(&user)?;
+    Ok(Json(with_db(&state, move |db| db.tag_finding(&hash, &request.tags, &user.username)).await?))
+}
+
+#[utoipa::path(
+    delete, path = "/api/v1/findings/{hash}/tags/{tag}", tag = "tags",
+    params(("hash" = String, Path, description = "Finding hash"), ("tag" = String, Path)),
+    responses(
+        (status = 200, description = "Tags left on the finding", body = Vec<String>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn remove_finding_tag(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((hash, tag)): Path<(String, String)>,
+) -> Result<Json<Vec<String>>, ApiError> {
+    require_annotator(&user)?;
+    Ok(Json(with_db(&state, move |db| db.untag_finding(&hash, &[tag])).await?))
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/repos/{owner}/{name}/tags", tag = "tags",
+    params(("owner" = String, Path), ("name" = String, Path)),
+    responses(
+        (status = 200, body = Vec<String>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody)
+    )
+)]
+pub async fn get_repository_tags(
+    State(state): State<AppState>,
+    Path((owner, name)): Path<(String, String)>,
+) -> Result<Json<Vec<String>>, ApiError> {
+    let repository = format!("{}/{}", owner, name);
+    Ok(Json(with_db(&state, move |db| db.repository_tags(&repository)).await?))
+}
+
+#[utoipa::path(
+    post, path = "/api/v1/repos/{owner}/{name}/tags", tag = "tags",
+    params(("owner" = String, Path), ("name" = String, Path)),
+    request_body = TagRequest,
+    responses(
+        (status = 200, description = "Tags now on the repository", body = Vec<String>),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody)
+    )
+)]
+pub async fn add_repository_tags(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((owner, name)): Path<(String, String)>,
+    Json(request): Json<TagRequest>,
+) -> Result<Json<Vec<String>>, ApiError> {
+    require_annotator(&user)?;
+    let repository = format!("{}/{}", owner, name);
+    Ok(Json(with_db(&state, move |db| db.tag_repository(&repository, &request.tags, &user.username)).await?))
+}
+
+#[utoipa::path(
+    delete, path = "/api/v1/repos/{owner}/{name}/tags/{tag}", tag = "tags",
+    params(("owner" = String, Path), ("name" = String, Path), ("tag" = String, Path)),
+    responses(
+        (status = 200, description = "Tags left on the repository", body = Vec<String>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody)
+    )
+)]
+pub async fn remove_repository_tag(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((owner, name, tag)): Path<(String, String, String)>,
+) -> Result<Json<Vec<String>>, ApiError> {
+    require_annotator(&user)?;
+    let repository = format!("{}/{}", owner, name);
+    Ok(Json(with_db(&state, move |db| db.untag_repository(&repository, &[tag])).await?))
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/findings/{hash}/annotations", tag = "annotations",
+    params(("hash" = String, Path, description = "Finding hash")),
+    responses(
+        (status = 200, body = FindingAnnotations),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody)
+    )
+)]
+pub async fn list_annotations(
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let annotations = with_db(&state, move |db| db.annotations(&hash)).await?;
+    Ok(Json(json!(annotations)))
+}
+
+#[utoipa::path(
+    post, path = "/api/v1/findings/{hash}/notes", tag = "annotations",
+    params(("hash" = String, Path, description = "Finding hash")),
+    request_body = NoteRequest,
+    responses(
+        (status = 201, body = FindingNote),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn add_note(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(hash): Path<String>,
+    Json(request): Json<NoteRequest>,
+) -> Result<(StatusCode, Json<FindingNote>), ApiError> {
+    require_annotator(&user)?;
+
+    let secret_hash = hash.clone();
+    let note = with_db(&state, move |db| db.add_note(&secret_hash, &user.username, &request.body)).await?;
+    publish(&state, AnnotationUpdate { secret_hash: hash, notes: vec![note.clone()], files: Vec::new() });
+
+    Ok((StatusCode::CREATED, Json(note)))
+}
+
+#[utoipa::path(
+    put, path = "/api/v1/findings/{hash}/notes/{id}", tag = "annotations",
+    params(("hash" = String, Path, description = "Finding hash"), ("id" = i64, Path)),
+    request_body = NoteRequest,
+    responses(
+        (status = 204, description = "Updated"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn update_note(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((hash, note_id)): Path<(String, i64)>,
+    Json(request): Json<NoteRequest>,
+) -> Result<StatusCode, ApiError> {
+    require_annotator(&user)?;
+
+    match with_db(&state, move |db| db.update_note(&hash, note_id, &request.body)).await? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(api_error(StatusCode::NOT_FOUND, format!("No note {} on this finding", note_id))),
+    }
+}
+
+#[utoipa::path(
+    delete, path = "/api/v1/findings/{hash}/notes/{id}", tag = "annotations",
+    params(("hash" = String, Path, description = "Finding hash"), ("id" = i64, Path)),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn delete_note(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((hash, note_id)): Path<(String, i64)>,
+) -> Result<StatusCode, ApiError> {
+    require_annotator(&user)?;
+
+    match with_db(&state, move |db| db.delete_note(&hash, note_id)).await? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(api_error(StatusCode::NOT_FOUND, format!("No note {} on this finding", note_id))),
+    }
+}
+
+/// Mark a finding resolved or revoked (or open again); stats count it as remediated from now
+#[utoipa::path(
+    put, path = "/api/v1/findings/{hash}/status", tag = "findings",
+    params(("hash" = String, Path, description = "Finding hash")),
+    request_body = StatusRequest,
+    responses(
+        (status = 204, description = "Updated"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn set_finding_status(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(hash): Path<String>,
+    Json(request): Json<StatusRequest>,
+) -> Result<StatusCode, ApiError> {
+    require_annotator(&user)?;
+
+    let secret_hash = hash.clone();
+    match with_db(&state, move |db| db.set_finding_status(&secret_hash, request.status)).await? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(api_error(StatusCode::NOT_FOUND, format!("No finding with hash {}", hash))),
+    }
+}
+
+/// Multipart upload; the file goes in a field named `file`
+#[utoipa::path(
+    post, path = "/api/v1/findings/{hash}/attachments", tag = "annotations",
+    params(("hash" = String, Path, description = "Finding hash")),
+    request_body(content_type = "multipart/form-data", description = "The file in a field named `file`"),
+    responses(
+        (status = 201, body = FindingAttachment),
+        (status = 413, description = "Larger than 25 MB"),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn upload_attachment(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(hash): Path<String>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<FindingAttachment>), ApiError> {
+    require_annotator(&user)?;
+
+    let mut upload = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?
+    {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let filename = field.file_name().unwrap_or("attachment").to_string();
+        let content_type = field
+            .content_type()
+            .map(str::to_string)
+            .unwrap_or_else(|| guess_content_type(&filename).to_string());
+        let contents = field.bytes().await.map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
+        upload = Some((filename, content_type, contents.to_vec()));
+        break;
+    }
+    let (filename, content_type, contents) =
+        upload.ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "Missing multipart field `file`"))?;
+
+    let store = state.attachments.clone();
+    let secret_hash = hash.clone();
+    let stored_contents = contents.clone();
+    let attachment = with_db(&state, move |db| {
+        db.add_attachment(&store, &secret_hash, &filename, &content_type, &stored_contents, &user.username)
+    })
+    .await?;
+    publish(&state, AnnotationUpdate {
+        secret_hash: hash,
+        notes: Vec::new(),
+        files: vec![(attachment.clone(), contents)],
+    });
+
+    Ok((StatusCode::CREATED, Json(attachment)))
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/findings/{hash}/attachments/{sha256}", tag = "annotations",
+    params(("hash" = String, Path, description = "Finding hash"), ("sha256" = String, Path)),
+    responses(
+        (status = 200, description = "The file, with its stored content type"),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn download_attachment(
+    State(state): State<AppState>,
+    Path((hash, sha256)): Path<(String, String)>,
+) -> Result<Response, ApiError> {
+    let store = state.attachments.clone();
+    let (attachment, contents) = with_db(&state, move |db| {
+        let attachment = db
+            .get_attachment(&hash, &sha256)?
+            .ok_or_else(|| anyhow::anyhow!("No finding attachment {}", sha256))?;
+        let contents = store.get(&attachment.sha256)?;
+        Ok((attachment, contents))
+    })
+    .await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, attachment.content_type.clone()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", attachment.filename)),
+        ],
+        contents,
+    )
+        .into_response())
+}
+
+#[utoipa::path(
+    delete, path = "/api/v1/findings/{hash}/attachments/{sha256}", tag = "annotations",
+    params(("hash" = String, Path, description = "Finding hash"), ("sha256" = String, Path)),
+    responses(
+        (status = 204, description = "Detached"),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn delete_attachment(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path((hash, sha256)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    require_annotator(&user)?;
+
+    let store = state.attachments.clone();
+    let deleted = with_db(&state, move |db| {
+        let deleted = db.delete_attachment(&hash, &sha256)?;
+        if deleted {
+            store.prune(&db.referenced_attachments()?)?;
+        }
+        Ok(deleted)
+    })
+    .await?;
+
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(api_error(StatusCode::NOT_FOUND, "No such attachment on this finding"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Role;
+
+    async fn seed_finding(state: &AppState, hash: &str) {
+        let finding = crate::secrets::SecretMatch {
+            detector_name: "Generic Secret".to_string(),
+            matched_text: format!("secret-{}", hash),
+            start_position: 0,
+            end_position: 10,
+            line_number: Some(1),
+            filename: Some("config.yml".to_string()),
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
+            entropy: 4.0,
+            severity: SecretSeverity::High,
+            category: SecretCategory::Token,
+            context: String::new(),
+            verified: false,
+            hash: hash.to_string(),
+        };
+        state.findings_db.interact(move |db| db.bulk_insert_secrets(&[finding])).await.unwrap();
+    }
+
+    fn note(body: &str) -> Json<NoteRequest> {
+        Json(NoteRequest { body: body.to_string() })
+    }
+
+    #[tokio::test]
+    async fn test_notes_endpoints_add_list_and_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path());
+        seed_finding(&state, "aaa").await;
+        let analyst = state.test_user("anna", Role::Analyst).await;
+
+        let (status, Json(added)) =
+            add_note(State(state.clone()), Extension(analyst.clone()), Path("aaa".to_string()), note("Owner contacted"))
+                .await
+                .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!((added.author.as_str(), added.body.as_str()), ("anna", "Owner contacted"));
+
+        let Json(listed) = list_annotations(State(state.clone()), Path("aaa".to_string())).await.unwrap();
+        assert_eq!(listed["notes"].as_array().unwrap().len(), 1);
+        assert_eq!(listed["notes"][0]["body"], "Owner contacted");
+
+        let status = update_note(State(state.clone()), Extension(analyst.clone()), Path(("aaa".to_string(), added.id)), note("Rotated"))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let status =
+            delete_note(State(state.clone()), Extension(analyst.clone()), Path(("aaa".to_string(), added.id))).await.unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let error = delete_note(State(state.clone()), Extension(analyst), Path(("aaa".to_string(), added.id))).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::NOT_FOUND);
+
+        let Json(listed) = list_annotations(State(state), Path("aaa".to_string())).await.unwrap();
+        assert!(listed["notes"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_notes_endpoints_refuse_viewers_and_unknown_findings() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path());
+        seed_finding(&state, "aaa").await;
+        let viewer = state.test_user("victor", Role::Viewer).await;
+        let analyst = state.test_user("anna", Role::Analyst).await;
+
+        let error = add_note(State(state.clone()), Extension(viewer), Path("aaa".to_string()), note("mine")).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::FORBIDDEN);
+        let error =
+            add_note(State(state.clone()), Extension(analyst.clone()), Path("missing".to_string()), note("lost")).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::NOT_FOUND);
+        let error = add_note(State(state), Extension(analyst), Path("aaa".to_string()), note("  ")).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::BAD_REQUEST);
+    }
+}
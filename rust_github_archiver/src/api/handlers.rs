@@ -1,28 +1,57 @@
 // API handlers placeholder
 // API handlers implementation
-use axum::{extract::{Extension, State}, http::StatusCode, Json};
+use axum::{extract::{Extension, Path, Query, State}, http::StatusCode, response::{IntoResponse, Redirect, Response}, Json};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use utoipa::ToSchema;
 use chrono::Utc;
 use std::sync::Arc;
 
-use crate::auth::{User, UserManager, create_token};
+use crate::auth::{jwt, IssuedSession, Session, SessionId, User, UserManager};
+use crate::api::findings::{api_error, with_db, ApiError};
+use crate::api::openapi::ErrorBody;
+use crate::api::pagination::Page;
 use crate::api::state::AppState;
+use crate::core::enhanced_database::{ARCHIVE_MIGRATIONS, ARCHIVE_STORE};
+use crate::core::SchemaVersion;
+use crate::performance::{FINDINGS_MIGRATIONS, FINDINGS_STORE};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct LoginRequest {
     username: String,
     password: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct LoginResponse {
+    /// Bearer token for the other endpoints; short-lived, renew it with the refresh token
     token: String,
     user: UserInfo,
     expires_at: String,
+    session_id: String,
+    /// Single use: `POST /api/auth/refresh` returns a new one
+    refresh_token: String,
+    /// After this, sign in again
+    session_expires_at: String,
+    /// Every other endpoint answers 403 until the password is changed
+    password_change_required: bool,
 }
 
-#[derive(Serialize)]
+impl LoginResponse {
+    fn new(session: IssuedSession, user: User, password_change_required: bool) -> Self {
+        Self {
+            token: session.access_token,
+            user: user.into(),
+            expires_at: session.access_expires_at.to_rfc3339(),
+            session_id: session.session_id,
+            refresh_token: session.refresh_token,
+            session_expires_at: session.session_expires_at.to_rfc3339(),
+            password_change_required,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct UserInfo {
     id: String,
     username: String,
@@ -48,6 +77,53 @@ pub async fn health_check() -> Json<Value> {
     }))
 }
 
+/// Liveness plus the schema version of each store, so a deploy can confirm migrations ran
+#[utoipa::path(
+    get, path = "/healthz", tag = "health", security(()),
+    responses(
+        (status = 200, description = "Every store is reachable, with its schema version", body = serde_json::Value),
+        (status = 503, description = "A store could not be opened", body = serde_json::Value)
+    )
+)]
+pub async fn healthz(State(app_state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let mut schemas = Vec::new();
+    let mut errors = Vec::new();
+
+    match app_state.findings_db.interact(|db| db.schema_version()).await {
+        Ok(version) => schemas.push(SchemaVersion::new(FINDINGS_STORE, version, FINDINGS_MIGRATIONS)),
+        Err(e) => errors.push(format!("{}: {}", FINDINGS_STORE, e)),
+    }
+
+    let archive = app_state
+        .get_comprehensive_status()
+        .await
+        .ok()
+        .and_then(|status| status.database_health)
+        .and_then(|health| health.schema_version);
+    if let Some(version) = archive {
+        schemas.push(SchemaVersion::new(ARCHIVE_STORE, version, ARCHIVE_MIGRATIONS));
+    }
+
+    let code = if errors.is_empty() { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (
+        code,
+        Json(json!({
+            "status": if errors.is_empty() { "healthy" } else { "unhealthy" },
+            "timestamp": Utc::now().to_rfc3339(),
+            "schemas": schemas,
+            "errors": errors
+        })),
+    )
+}
+
+#[utoipa::path(
+    post, path = "/api/auth/login", tag = "auth", security(()),
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Tokens for a new session", body = LoginResponse),
+        (status = 401, description = "Wrong username or password", body = ErrorBody)
+    )
+)]
 pub async fn login(
     State(app_state): State<AppState>,
     Json(payload): Json<LoginRequest>,
@@ -71,8 +147,8 @@ pub async fn login(
         tracing::warn!("Failed to update last login for {}: {}", user.username, e);
     }
 
-    // Create JWT token
-    let token = create_token(&user.username).map_err(|_| {
+    let session = app_state.user_manager.issue_session(&user).await.map_err(|e| {
+        tracing::error!("Failed to start a session for {}: {}", user.username, e);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({
@@ -82,50 +158,270 @@ pub async fn login(
         )
     })?;
 
-    // Calculate expiration time (24 hours from now)
-    let expires_at = (Utc::now() + chrono::Duration::hours(24)).to_rfc3339();
+    let password_change_required = app_state.user_manager.password_change_required(&user);
+    Ok(Json(LoginResponse::new(session, user, password_change_required)))
+}
 
-    Ok(Json(LoginResponse {
-        token,
-        user: user.into(),
-        expires_at,
-    }))
+#[derive(Deserialize, ToSchema)]
+pub struct RefreshRequest {
+    refresh_token: String,
+}
+
+/// Renew the access token; the refresh token is replaced too
+#[utoipa::path(
+    post, path = "/api/auth/refresh", tag = "auth", security(()),
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "New tokens for the same session", body = LoginResponse),
+        (status = 401, description = "Unknown, used, revoked or expired refresh token", body = ErrorBody)
+    )
+)]
+pub async fn refresh_session(
+    State(app_state): State<AppState>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<Json<LoginResponse>, ApiError> {
+    let users = &app_state.user_manager;
+    let session = users
+        .refresh_session(&payload.refresh_token)
+        .await
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))?
+        .ok_or_else(|| api_error(StatusCode::UNAUTHORIZED, "Session ended; sign in again"))?;
+    let claims = jwt::verify_token(&session.access_token).map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    let user = users
+        .get_user(&claims.sub)
+        .await
+        .ok_or_else(|| api_error(StatusCode::UNAUTHORIZED, "Session ended; sign in again"))?;
+    let password_change_required = users.password_change_required(&user);
+    Ok(Json(LoginResponse::new(session, user, password_change_required)))
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct SsoLoginParams {
+    /// Path on this server to send the browser to after signing in; without it the callback
+    /// answers with JSON
+    return_to: Option<String>,
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct SsoCallbackParams {
+    code: Option<String>,
+    state: Option<String>,
+    /// Set by the identity provider when the sign-in failed or was cancelled
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+fn sso_provider(app_state: &AppState) -> Result<&Arc<crate::auth::OidcProvider>, ApiError> {
+    app_state.oidc.as_ref().ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Single sign-on is not configured"))
+}
+
+/// Start a single sign-on: redirects to the identity provider's sign-in page
+#[utoipa::path(
+    get, path = "/api/auth/oidc/login", tag = "auth", security(()),
+    params(SsoLoginParams),
+    responses(
+        (status = 303, description = "Redirect to the identity provider"),
+        (status = 400, description = "return_to is not a path on this server", body = ErrorBody),
+        (status = 404, description = "Single sign-on is not configured", body = ErrorBody),
+        (status = 502, description = "The identity provider could not be reached", body = ErrorBody)
+    )
+)]
+pub async fn oidc_login(State(app_state): State<AppState>, Query(params): Query<SsoLoginParams>) -> Result<Redirect, ApiError> {
+    let url = sso_provider(&app_state)?.authorization_url(params.return_to).await.map_err(|e| {
+        let status = if e.to_string().starts_with("Invalid ") { StatusCode::BAD_REQUEST } else { StatusCode::BAD_GATEWAY };
+        api_error(status, e)
+    })?;
+    Ok(Redirect::to(&url))
 }
 
-#[derive(Serialize)]
+/// Where the identity provider sends the browser back. Starts a session as `/api/auth/login`
+/// does; with `return_to`, redirects there with the tokens in the URL fragment.
+#[utoipa::path(
+    get, path = "/api/auth/oidc/callback", tag = "auth", security(()),
+    params(SsoCallbackParams),
+    responses(
+        (status = 200, description = "Tokens for a new session", body = LoginResponse),
+        (status = 303, description = "Redirect to return_to with the tokens in the fragment"),
+        (status = 401, description = "The sign-in failed, expired or was cancelled", body = ErrorBody),
+        (status = 403, description = "No role for the user's groups, or the account is disabled", body = ErrorBody),
+        (status = 404, description = "Single sign-on is not configured", body = ErrorBody)
+    )
+)]
+pub async fn oidc_callback(State(app_state): State<AppState>, Query(params): Query<SsoCallbackParams>) -> Result<Response, ApiError> {
+    let oidc = sso_provider(&app_state)?;
+    if let Some(error) = params.error {
+        return Err(api_error(StatusCode::UNAUTHORIZED, params.error_description.unwrap_or(error)));
+    }
+    let (Some(code), Some(state)) = (params.code, params.state) else {
+        return Err(api_error(StatusCode::UNAUTHORIZED, "The callback needs code and state"));
+    };
+    let login = oidc.complete_login(&code, &state).await.map_err(|e| {
+        let status = if e.to_string().starts_with("None of your groups") { StatusCode::FORBIDDEN } else { StatusCode::UNAUTHORIZED };
+        api_error(status, e)
+    })?;
+
+    let users = &app_state.user_manager;
+    let user = users
+        .sign_in_sso(&login.identity.subject, &login.identity.username, login.role)
+        .await
+        .map_err(|e| api_error(StatusCode::FORBIDDEN, e))?;
+    let session = users.issue_session(&user).await.map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    match login.return_to {
+        // The fragment never reaches a server, so the tokens stay out of access logs
+        Some(path) => Ok(Redirect::to(&format!(
+            "{}#token={}&refresh_token={}&session_id={}",
+            path, session.access_token, session.refresh_token, session.session_id
+        ))
+        .into_response()),
+        None => Ok(Json(LoginResponse::new(session, user, false)).into_response()),
+    }
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct AuthStatusResponse {
     authenticated: bool,
     user: Option<String>,
+    /// Whether `/api/auth/oidc/login` is available
+    sso_enabled: bool,
 }
 
-pub async fn auth_status(user: Option<Extension<User>>) -> Json<AuthStatusResponse> {
+#[utoipa::path(
+    get, path = "/api/auth/status", tag = "auth", security((), ("bearer" = [])),
+    responses((status = 200, body = AuthStatusResponse))
+)]
+pub async fn auth_status(State(app_state): State<AppState>, user: Option<Extension<User>>) -> Json<AuthStatusResponse> {
+    let sso_enabled = app_state.oidc.is_some();
     if let Some(Extension(user)) = user {
         Json(AuthStatusResponse {
             authenticated: true,
             user: Some(user.username.clone()),
+            sso_enabled,
         })
     } else {
         Json(AuthStatusResponse {
             authenticated: false,
             user: None,
+            sso_enabled,
         })
     }
 }
 
-pub async fn logout() -> Json<Value> {
-    // In a stateless JWT system, logout is handled client-side by discarding the token
-    // Server-side logout would require token blacklisting, which we're not implementing here
-    Json(json!({
+/// End the caller's session; its refresh token stops working and its access tokens are refused
+#[utoipa::path(
+    post, path = "/api/auth/logout", tag = "auth",
+    responses((status = 200, body = serde_json::Value),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody))
+)]
+pub async fn logout(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<User>,
+    session: Option<Extension<SessionId>>,
+) -> Result<Json<Value>, ApiError> {
+    // Identity provider tokens have no session here; they end when the provider says so
+    if let Some(Extension(SessionId(session))) = session {
+        app_state
+            .user_manager
+            .revoke_session(&user.username, &session)
+            .await
+            .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    }
+    Ok(Json(json!({
         "message": "Logged out successfully",
         "timestamp": Utc::now().to_rfc3339()
-    }))
+    })))
+}
+
+/// The caller's live sessions
+#[utoipa::path(
+    get, path = "/api/auth/sessions", tag = "auth",
+    responses((status = 200, body = Page<Session>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody))
+)]
+pub async fn list_sessions(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> Result<Json<Page<Session>>, ApiError> {
+    let sessions = app_state
+        .user_manager
+        .list_sessions(&user.username)
+        .await
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    Ok(Json(Page::all(sessions)))
+}
+
+/// Sign out one of the caller's sessions, for example on a lost laptop
+#[utoipa::path(
+    delete, path = "/api/auth/sessions/{id}", tag = "auth",
+    params(("id" = String, Path, description = "Session id")),
+    responses(
+        (status = 204, description = "Revoked"),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn revoke_session(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    match app_state.user_manager.revoke_session(&user.username, &id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(api_error(StatusCode::NOT_FOUND, "No such session")),
+        Err(e) => Err(api_error(StatusCode::INTERNAL_SERVER_ERROR, e)),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PasswordChange {
+    current_password: String,
+    new_password: String,
 }
 
+/// Change the caller's password. Every other session is signed out; this one stays.
+#[utoipa::path(
+    post, path = "/api/auth/password", tag = "auth",
+    request_body = PasswordChange,
+    responses(
+        (status = 204, description = "Changed"),
+        (status = 400, description = "The new password breaks a password rule", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Current password is wrong", body = ErrorBody)
+    )
+)]
+pub async fn change_password(
+    State(app_state): State<AppState>,
+    Extension(user): Extension<User>,
+    session: Option<Extension<SessionId>>,
+    Json(change): Json<PasswordChange>,
+) -> Result<StatusCode, ApiError> {
+    let keep_session = session.map(|Extension(SessionId(session))| session);
+    app_state
+        .user_manager
+        .change_password(&user.username, &change.current_password, &change.new_password, keep_session.as_deref())
+        .await
+        .map_err(|e| {
+            let status = if e.to_string().starts_with("Invalid ") { StatusCode::BAD_REQUEST } else { StatusCode::FORBIDDEN };
+            api_error(status, e)
+        })?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get, path = "/api/auth/user", tag = "auth",
+    responses((status = 200, body = UserInfo),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody))
+)]
 pub async fn user_info(Extension(user): Extension<User>) -> Json<UserInfo> {
     Json(user.into())
 }
 
 // Scraper control handlers
+#[utoipa::path(
+    post, path = "/api/start-scraper", tag = "scraper",
+    responses((status = 200, body = serde_json::Value),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody), (status = 500, description = "The scraper could not start"))
+)]
 pub async fn start_scraper(State(app_state): State<AppState>) -> Result<Json<Value>, StatusCode> {
     // Initialize main scraper if not already done
     if let Err(e) = app_state.initialize_main_scraper().await {
@@ -158,6 +454,11 @@ pub async fn start_scraper(State(app_state): State<AppState>) -> Result<Json<Val
     }
 }
 
+#[utoipa::path(
+    post, path = "/api/stop-scraper", tag = "scraper",
+    responses((status = 200, body = serde_json::Value),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody))
+)]
 pub async fn stop_scraper(State(app_state): State<AppState>) -> Result<Json<Value>, StatusCode> {
     match app_state.scraper_manager.stop() {
         Ok(()) => {
@@ -184,6 +485,11 @@ pub async fn stop_scraper(State(app_state): State<AppState>) -> Result<Json<Valu
     }
 }
 
+#[utoipa::path(
+    post, path = "/api/pause-scraper", tag = "scraper",
+    responses((status = 200, body = serde_json::Value),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody))
+)]
 pub async fn pause_scraper(State(app_state): State<AppState>) -> Result<Json<Value>, StatusCode> {
     match app_state.scraper_manager.pause() {
         Ok(()) => {
@@ -210,6 +516,11 @@ pub async fn pause_scraper(State(app_state): State<AppState>) -> Result<Json<Val
     }
 }
 
+#[utoipa::path(
+    post, path = "/api/resume-scraper", tag = "scraper",
+    responses((status = 200, body = serde_json::Value),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody))
+)]
 pub async fn resume_scraper(State(app_state): State<AppState>) -> Result<Json<Value>, StatusCode> {
     match app_state.scraper_manager.resume() {
         Ok(()) => {
@@ -236,6 +547,11 @@ pub async fn resume_scraper(State(app_state): State<AppState>) -> Result<Json<Va
     }
 }
 
+#[utoipa::path(
+    post, path = "/api/restart-scraper", tag = "scraper",
+    responses((status = 200, body = serde_json::Value),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody))
+)]
 pub async fn restart_scraper(State(app_state): State<AppState>) -> Result<Json<Value>, StatusCode> {
     match app_state.scraper_manager.restart() {
         Ok(()) => {
@@ -262,6 +578,10 @@ pub async fn restart_scraper(State(app_state): State<AppState>) -> Result<Json<V
     }
 }
 
+#[utoipa::path(
+    get, path = "/api/scraper/status", tag = "scraper", security(()),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn scraper_status(State(app_state): State<AppState>) -> Result<Json<Value>, StatusCode> {
     match app_state.scraper_manager.get_status() {
         Ok(status) => Ok(Json(json!({
@@ -286,6 +606,10 @@ pub async fn scraper_status(State(app_state): State<AppState>) -> Result<Json<Va
     }
 }
 
+#[utoipa::path(
+    get, path = "/api/status", tag = "scraper", security(()),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn system_status(State(app_state): State<AppState>) -> Result<Json<Value>, StatusCode> {
     // Get comprehensive status if main scraper is available
     match app_state.get_comprehensive_status().await {
@@ -298,7 +622,8 @@ pub async fn system_status(State(app_state): State<AppState>) -> Result<Json<Val
                 "uptime_seconds": status.uptime_seconds,
                 "total_files_processed": status.total_files_processed,
                 "total_events_processed": status.total_events_processed,
-                "total_errors": status.total_errors
+                "total_errors": status.total_errors,
+                "read_only": app_state.config.web.read_only
             });
 
             // Add database health if available
@@ -352,3 +677,25 @@ pub async fn system_status(State(app_state): State<AppState>) -> Result<Json<Val
         }
     }
 }
+
+/// The real-time monitor's last heartbeat: health, whether it is paused, and the GitHub
+/// rate limit with the delay until its next poll
+#[utoipa::path(
+    get, path = "/api/v1/monitor", tag = "scraper",
+    responses(
+        (status = 200, body = serde_json::Value),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "The monitor has not run", body = ErrorBody)
+    )
+)]
+pub async fn monitor_status(State(app_state): State<AppState>) -> Result<Json<Value>, ApiError> {
+    let heartbeats = with_db(&app_state, |db| db.heartbeats()).await?;
+    let monitor = heartbeats
+        .into_iter()
+        .find(|beat| beat.component == "monitor")
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "No monitor heartbeat recorded"))?;
+    let mut snapshot = monitor.snapshot;
+    snapshot["updated_at"] = json!(monitor.updated_at);
+    snapshot["age_seconds"] = json!(monitor.age_seconds);
+    Ok(Json(snapshot))
+}
@@ -0,0 +1,196 @@
+// Listener hardening: address allowlists and (mutual) TLS
+use anyhow::{anyhow, Context, Result};
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::RootCertStore;
+use serde_json::json;
+use std::fs::File;
+use std::io::BufReader;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tracing::warn;
+
+use crate::api::openapi::ErrorBody;
+use crate::core::ServerConfig;
+
+/// An address or CIDR range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IpRange {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpRange {
+    fn parse(entry: &str) -> Result<Self> {
+        let entry = entry.trim();
+        let (address, prefix) = match entry.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (entry, None),
+        };
+        let network: IpAddr = address.parse().map_err(|_| anyhow!("Invalid address in allowlist: {}", entry))?;
+        let bits = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|p| *p <= bits)
+                .ok_or_else(|| anyhow!("Invalid prefix length in allowlist: {}", entry))?,
+            None => bits,
+        };
+        Ok(Self { network: network.to_canonical(), prefix })
+    }
+
+    fn contains(&self, address: IpAddr) -> bool {
+        // IPv4 clients of a dual-stack socket arrive as ::ffff:a.b.c.d
+        match (self.network, address.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(address) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(address) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Who may connect to a listener. Empty allows everyone.
+#[derive(Debug, Clone, Default)]
+pub struct IpAllowlist {
+    ranges: Vec<IpRange>,
+}
+
+impl IpAllowlist {
+    pub fn parse(entries: &[String]) -> Result<Self> {
+        Ok(Self { ranges: entries.iter().map(|e| IpRange::parse(e)).collect::<Result<_>>()? })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn allows(&self, address: IpAddr) -> bool {
+        self.ranges.is_empty() || self.ranges.iter().any(|range| range.contains(address))
+    }
+}
+
+/// Refuses peers outside the allowlist with 403. A request without a peer address (one that
+/// did not come through `serve`) is refused too, unless the list is empty.
+pub async fn ip_allowlist(State(allowlist): State<Arc<IpAllowlist>>, request: Request, next: Next) -> Response {
+    if allowlist.is_empty() {
+        return next.run(request).await;
+    }
+    let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| addr.ip());
+    match peer {
+        Some(address) if allowlist.allows(address) => next.run(request).await,
+        peer => {
+            warn!("Refused API request from {}", peer.map_or("an unknown address".to_string(), |a| a.to_string()));
+            (StatusCode::FORBIDDEN, Json(json!(ErrorBody { error: "Address not allowed".to_string() }))).into_response()
+        }
+    }
+}
+
+fn read_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    let mut reader = BufReader::new(File::open(path).with_context(|| format!("Cannot open {}", path.display()))?);
+    let certificates = rustls_pemfile::certs(&mut reader).collect::<Result<Vec<_>, _>>()?;
+    if certificates.is_empty() {
+        return Err(anyhow!("No certificates in {}", path.display()));
+    }
+    Ok(certificates)
+}
+
+fn read_private_key(path: &Path) -> Result<PrivateKeyDer<'static>> {
+    let mut reader = BufReader::new(File::open(path).with_context(|| format!("Cannot open {}", path.display()))?);
+    rustls_pemfile::private_key(&mut reader)?.ok_or_else(|| anyhow!("No private key in {}", path.display()))
+}
+
+/// TLS settings for the listeners, or `None` to serve plain HTTP. With `client_ca`, the
+/// handshake fails for clients without a certificate issued by that CA.
+pub fn tls_config(server: &ServerConfig) -> Result<Option<RustlsConfig>> {
+    let (Some(cert), Some(key)) = (&server.tls_cert, &server.tls_key) else {
+        return Ok(None);
+    };
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = rustls::ServerConfig::builder_with_provider(provider.clone()).with_safe_default_protocol_versions()?;
+
+    let builder = match &server.client_ca {
+        Some(ca) => {
+            let mut roots = RootCertStore::empty();
+            for certificate in read_certificates(ca)? {
+                roots.add(certificate)?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider).build()?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut config = builder.with_single_cert(read_certificates(cert)?, read_private_key(key)?)?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(Some(RustlsConfig::from_config(Arc::new(config))))
+}
+
+/// Serve `app` on `addr` until it fails, over TLS when `tls` is set. Handlers and the
+/// allowlist see the peer address through `ConnectInfo`.
+pub async fn serve(addr: SocketAddr, app: Router, tls: Option<RustlsConfig>) -> Result<()> {
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+    match tls {
+        Some(tls) => axum_server::bind_rustls(addr, tls).serve(service).await?,
+        None => axum::serve(TcpListener::bind(addr).await?, service).await?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowlist(entries: &[&str]) -> IpAllowlist {
+        IpAllowlist::parse(&entries.iter().map(|e| e.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn test_allowlist_matches_ranges() {
+        let list = allowlist(&["10.0.0.0/8", "192.168.1.20", "fd00::/8"]);
+        assert!(list.allows("10.20.30.40".parse().unwrap()));
+        assert!(list.allows("192.168.1.20".parse().unwrap()));
+        assert!(!list.allows("192.168.1.21".parse().unwrap()));
+        assert!(list.allows("fd12:3456::1".parse().unwrap()));
+        assert!(!list.allows("2001:db8::1".parse().unwrap()));
+        // IPv4 peers on a dual-stack socket
+        assert!(list.allows("::ffff:10.1.2.3".parse().unwrap()));
+
+        assert!(allowlist(&[]).allows("203.0.113.9".parse().unwrap()));
+        assert!(allowlist(&["0.0.0.0/0"]).allows("203.0.113.9".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_allowlist_rejects_bad_entries() {
+        for entry in ["10.0.0.0/33", "example.com", "10.0.0.0/x", "::/129"] {
+            assert!(IpAllowlist::parse(&[entry.to_string()]).is_err(), "{} was accepted", entry);
+        }
+    }
+
+    #[test]
+    fn test_plain_http_without_certificates() {
+        assert!(tls_config(&ServerConfig::default()).unwrap().is_none());
+        let missing = ServerConfig {
+            tls_cert: Some("/nonexistent/cert.pem".into()),
+            tls_key: Some("/nonexistent/key.pem".into()),
+            ..Default::default()
+        };
+        assert!(tls_config(&missing).is_err());
+    }
+}
@@ -1,10 +1,230 @@
-// API middleware placeholder
+// API middleware: per-client rate limiting and caps on concurrent findings queries
 use axum::{
-    extract::Request,
-    http::StatusCode,
+    extract::{ConnectInfo, Extension, Request, State},
+    http::{header, Method, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
+    Json,
 };
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+use crate::api::openapi::ErrorBody;
+use crate::api::state::AppState;
+use crate::auth::jwt;
+use crate::core::config::WebConfig;
+
+/// Clients tracked before idle ones with a full bucket are forgotten
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Limits every client of the API is held to
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ApiLimits {
+    /// 0 when requests are not rate limited
+    pub requests_per_minute: u32,
+    /// Requests a client may send at once after being idle
+    pub burst: u32,
+    /// Findings queries a client may run at the same time; 0 when uncapped
+    pub max_concurrent_scans: usize,
+    /// Largest request body accepted; attachment uploads have their own limit
+    pub max_request_bytes: usize,
+}
+
+impl ApiLimits {
+    pub fn from_config(web: &WebConfig) -> Self {
+        Self {
+            requests_per_minute: web.rate_limit_per_minute,
+            burst: web.rate_limit_burst.max(1),
+            max_concurrent_scans: web.max_concurrent_scans,
+            max_request_bytes: web.max_request_size,
+        }
+    }
+}
+
+/// Who a request is counted against: `user:<id>` with a valid token, otherwise `ip:<address>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientKey(pub String);
+
+struct ClientState {
+    tokens: f64,
+    last_refill: Instant,
+    scans: usize,
+}
+
+/// One token bucket and one in-flight scan counter per client
+pub struct RateLimiter {
+    limits: ApiLimits,
+    clients: Mutex<HashMap<String, ClientState>>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: ApiLimits) -> Self {
+        Self { limits, clients: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn limits(&self) -> &ApiLimits {
+        &self.limits
+    }
+
+    fn refill_rate(&self) -> f64 {
+        self.limits.requests_per_minute as f64 / 60.0
+    }
+
+    /// Refill `key`'s bucket up to now and hand it to `f`
+    fn with_client<T>(&self, key: &str, f: impl FnOnce(&mut ClientState) -> T) -> T {
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let burst = self.limits.burst as f64;
+        if clients.len() >= MAX_TRACKED_CLIENTS && !clients.contains_key(key) {
+            let rate = self.refill_rate();
+            clients.retain(|_, c| c.scans > 0 || c.tokens + now.duration_since(c.last_refill).as_secs_f64() * rate < burst);
+        }
+        let client = clients
+            .entry(key.to_string())
+            .or_insert(ClientState { tokens: burst, last_refill: now, scans: 0 });
+        let refill = now.duration_since(client.last_refill).as_secs_f64() * self.refill_rate();
+        client.tokens = (client.tokens + refill).min(burst);
+        client.last_refill = now;
+        f(client)
+    }
+
+    /// Take one request from `key`'s bucket, or say how long until the next one is allowed
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        if self.limits.requests_per_minute == 0 {
+            return Ok(());
+        }
+        let rate = self.refill_rate();
+        self.with_client(key, |client| {
+            if client.tokens >= 1.0 {
+                client.tokens -= 1.0;
+                Ok(())
+            } else {
+                Err(Duration::from_secs_f64((1.0 - client.tokens) / rate))
+            }
+        })
+    }
+
+    /// A slot for one findings query, released when the permit is dropped; `None` at the cap
+    pub fn begin_scan(self: &Arc<Self>, key: &str) -> Option<ScanPermit> {
+        let cap = self.limits.max_concurrent_scans;
+        let admitted = self.with_client(key, |client| {
+            if cap > 0 && client.scans >= cap {
+                return false;
+            }
+            client.scans += 1;
+            true
+        });
+        admitted.then(|| ScanPermit { limiter: self.clone(), key: key.to_string() })
+    }
+
+    /// Requests left in `key`'s bucket and its findings queries in flight
+    pub fn usage(&self, key: &str) -> (u32, usize) {
+        self.with_client(key, |client| (client.tokens.floor() as u32, client.scans))
+    }
+}
+
+pub struct ScanPermit {
+    limiter: Arc<RateLimiter>,
+    key: String,
+}
+
+impl Drop for ScanPermit {
+    fn drop(&mut self) {
+        let mut clients = self.limiter.clients.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(client) = clients.get_mut(&self.key) {
+            client.scans = client.scans.saturating_sub(1);
+        }
+    }
+}
+
+/// Probes must keep working while a client is throttled
+fn is_exempt(path: &str) -> bool {
+    matches!(path, "/health" | "/healthz" | "/api/health")
+}
+
+/// Requests that scan the findings table rather than an index or a rollup
+fn is_scan(method: &Method, path: &str) -> bool {
+    *method == Method::GET
+        && (path == "/api/v1/findings" || (path.starts_with("/api/v1/searches/") && path.ends_with("/results")))
+}
+
+fn client_key(request: &Request) -> ClientKey {
+    let user = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .and_then(|token| jwt::verify_token(token).ok());
+    if let Some(claims) = user {
+        return ClientKey(format!("user:{}", claims.sub));
+    }
+    match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => ClientKey(format!("ip:{}", addr.ip())),
+        None => ClientKey("ip:unknown".to_string()),
+    }
+}
+
+fn too_many_requests(retry_after: Duration, error: &str) -> Response {
+    let seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, seconds.to_string())],
+        Json(json!(ErrorBody { error: format!("{}; retry in {} seconds", error, seconds) })),
+    )
+        .into_response()
+}
+
+/// Runs before authentication, so unauthenticated floods are throttled by address too
+pub async fn rate_limit(State(limiter): State<Arc<RateLimiter>>, mut request: Request, next: Next) -> Response {
+    if is_exempt(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let key = client_key(&request);
+    if let Err(wait) = limiter.check(&key.0) {
+        return too_many_requests(wait, "Rate limit exceeded");
+    }
+    // Held until the response is built
+    let _permit = if is_scan(request.method(), request.uri().path()) {
+        match limiter.begin_scan(&key.0) {
+            Some(permit) => Some(permit),
+            None => return too_many_requests(Duration::from_secs(1), "Too many findings queries in progress"),
+        }
+    } else {
+        None
+    };
+
+    request.extensions_mut().insert(key);
+    next.run(request).await
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LimitsStatus {
+    pub limits: ApiLimits,
+    /// The key this caller is counted against
+    pub client: String,
+    /// Requests left before the caller is throttled
+    pub remaining: u32,
+    pub scans_in_flight: usize,
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/limits", tag = "limits",
+    responses(
+        (status = 200, body = LimitsStatus),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 429, description = "Rate limited; see Retry-After", body = ErrorBody)
+    )
+)]
+pub async fn api_limits(State(state): State<AppState>, Extension(client): Extension<ClientKey>) -> Json<LimitsStatus> {
+    let (remaining, scans_in_flight) = state.limiter.usage(&client.0);
+    Json(LimitsStatus { limits: state.limiter.limits().clone(), client: client.0, remaining, scans_in_flight })
+}
 
 pub async fn cors_middleware(
     request: Request,
@@ -13,3 +233,50 @@ pub async fn cors_middleware(
     // TODO: Implement CORS middleware
     Ok(next.run(request).await)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(requests_per_minute: u32, burst: u32, max_concurrent_scans: usize) -> ApiLimits {
+        ApiLimits { requests_per_minute, burst, max_concurrent_scans, max_request_bytes: 1024 }
+    }
+
+    #[test]
+    fn test_bucket_allows_a_burst_then_throttles() {
+        let limiter = RateLimiter::new(limits(60, 3, 0));
+        for _ in 0..3 {
+            assert!(limiter.check("ip:10.0.0.1").is_ok());
+        }
+        let wait = limiter.check("ip:10.0.0.1").unwrap_err();
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+        // Other clients have their own bucket
+        assert!(limiter.check("user:alice").is_ok());
+        assert_eq!(limiter.usage("user:alice").0, 2);
+
+        let unlimited = RateLimiter::new(limits(0, 1, 0));
+        assert!((0..100).all(|_| unlimited.check("ip:10.0.0.1").is_ok()));
+    }
+
+    #[test]
+    fn test_scan_permits_are_capped_per_client() {
+        let limiter = Arc::new(RateLimiter::new(limits(60, 10, 2)));
+        let first = limiter.begin_scan("user:alice").unwrap();
+        let _second = limiter.begin_scan("user:alice").unwrap();
+        assert!(limiter.begin_scan("user:alice").is_none());
+        assert!(limiter.begin_scan("user:bob").is_some());
+
+        drop(first);
+        assert_eq!(limiter.usage("user:alice").1, 1);
+        assert!(limiter.begin_scan("user:alice").is_some());
+    }
+
+    #[test]
+    fn test_scans_are_findings_queries() {
+        assert!(is_scan(&Method::GET, "/api/v1/findings"));
+        assert!(is_scan(&Method::GET, "/api/v1/searches/aws-critical/results"));
+        assert!(!is_scan(&Method::GET, "/api/v1/findings/abc/tags"));
+        assert!(!is_scan(&Method::POST, "/api/v1/searches"));
+        assert!(is_exempt("/healthz"));
+    }
+}
@@ -3,9 +3,20 @@
 
 pub mod routes;
 pub mod handlers;
+pub mod approvals;
+pub mod listener;
+pub mod findings;
+pub mod openapi;
+pub mod organizations;
+pub mod pagination;
+pub mod quotas;
+pub mod repositories;
+pub mod scans;
+pub mod stats;
 pub mod middleware;
 pub mod server;
 pub mod state;
+pub mod users;
 
 // Re-export main components
 pub use server::*;
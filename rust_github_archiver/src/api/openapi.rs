@@ -0,0 +1,151 @@
+// OpenAPI document for the REST API, served at /api/openapi.json with Swagger UI at /api/docs
+use serde::Serialize;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi, ToSchema};
+
+use crate::api::{approvals, findings, handlers, middleware, organizations, quotas, repositories, scans, stats, users};
+
+/// Body of every API error: `{"error": "..."}`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "GitHub Secret Hunter API",
+        description = "Findings, saved searches, tags, repositories, organizations, triggered scans and scraper control. \
+            Sign in with `POST /api/auth/login` and send the token as `Authorization: Bearer <token>`; \
+            renew it with the refresh token at `POST /api/auth/refresh`. \
+            List endpoints return a `{data, cursor, total_estimate, warnings}` page; pass `cursor` back to get the next one. \
+            Requests are rate limited per client; a 429 carries `Retry-After`."
+    ),
+    paths(
+        handlers::healthz,
+        handlers::login,
+        handlers::refresh_session,
+        handlers::oidc_login,
+        handlers::oidc_callback,
+        handlers::logout,
+        handlers::auth_status,
+        handlers::user_info,
+        handlers::list_sessions,
+        handlers::revoke_session,
+        handlers::change_password,
+        handlers::start_scraper,
+        handlers::stop_scraper,
+        handlers::pause_scraper,
+        handlers::resume_scraper,
+        handlers::restart_scraper,
+        handlers::scraper_status,
+        handlers::system_status,
+        handlers::monitor_status,
+        findings::list_findings,
+        findings::similar_findings,
+        findings::set_finding_status,
+        findings::list_saved_searches,
+        findings::save_search,
+        findings::run_saved_search,
+        findings::delete_saved_search,
+        findings::list_tags,
+        findings::get_finding_tags,
+        findings::add_finding_tags,
+        findings::remove_finding_tag,
+        findings::get_repository_tags,
+        findings::add_repository_tags,
+        findings::remove_repository_tag,
+        findings::list_annotations,
+        findings::add_note,
+        findings::update_note,
+        findings::delete_note,
+        findings::upload_attachment,
+        findings::download_attachment,
+        findings::delete_attachment,
+        stats::stats_summary,
+        stats::top_detectors,
+        stats::top_repositories,
+        stats::findings_trend,
+        stats::validation_ratio,
+        stats::mttr,
+        repositories::list_repositories,
+        repositories::repository_facets,
+        repositories::get_repository,
+        organizations::list_organizations,
+        organizations::save_organization,
+        organizations::remove_organization,
+        organizations::organization_scorecard,
+        scans::trigger_scan,
+        scans::get_scan,
+        middleware::api_limits,
+        quotas::workspace_quota,
+        users::list_users,
+        users::create_user,
+        users::update_user,
+        users::reset_password,
+        users::revoke_user_sessions,
+        users::user_notification_preferences,
+        users::set_user_notification_preferences,
+        users::my_notification_preferences,
+        users::set_my_notification_preferences,
+        approvals::list_approvals,
+        approvals::request_approval,
+        approvals::approve_request,
+        approvals::reject_request,
+    ),
+    modifiers(&BearerAuth),
+    security(("bearer" = [])),
+    tags(
+        (name = "findings", description = "Query stored findings"),
+        (name = "searches", description = "Saved searches and their results"),
+        (name = "tags", description = "Finding and repository tags"),
+        (name = "annotations", description = "Notes and evidence attachments"),
+        (name = "stats", description = "Dashboard aggregations"),
+        (name = "repositories", description = "Synced repository metadata"),
+        (name = "organizations", description = "Monitored organizations and scorecards"),
+        (name = "scans", description = "Scans triggered by CI and SOAR, with result callbacks"),
+        (name = "scraper", description = "Scraper control and status"),
+        (name = "auth", description = "Sign-in, sessions and the current user"),
+        (name = "users", description = "User administration (admins only)"),
+        (name = "approvals", description = "Two-person approval of dangerous actions"),
+        (name = "limits", description = "Rate limits and the caller's usage"),
+        (name = "health", description = "Liveness and schema versions"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Declares the `bearer` scheme that the document's `security` requirement names
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_covers_the_findings_api() {
+        let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        assert_eq!(document["openapi"], "3.1.0");
+        for path in ["/api/v1/findings", "/api/v1/searches/{name}/results", "/api/v1/repos", "/api/auth/login"] {
+            assert!(document["paths"].get(path).is_some(), "{} is not documented", path);
+        }
+        assert!(document["paths"]["/api/v1/findings"]["get"]["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|parameter| parameter["name"] == "cursor"));
+        assert!(document["components"]["schemas"].get("SecretRecord").is_some());
+        assert_eq!(document["components"]["securitySchemes"]["bearer"]["scheme"], "bearer");
+        // Sign-in cannot require the token it hands out
+        assert_eq!(document["paths"]["/api/auth/login"]["post"]["security"], serde_json::json!([{}]));
+    }
+}
@@ -0,0 +1,128 @@
+// Monitored organization management and per-org health
+use axum::{
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::api::findings::{api_error, with_db, ApiError};
+use crate::api::openapi::ErrorBody;
+use crate::api::pagination::Page;
+use crate::api::state::AppState;
+use crate::auth::User;
+use crate::performance::{
+    render_scorecards_html, MonitoredOrganization, OrganizationHealth, OrganizationScorecard, OrganizationSettings, ScorecardPeriod,
+};
+
+fn require_admin(user: &User) -> Result<(), ApiError> {
+    if user.role_kind().can_manage_monitoring() {
+        Ok(())
+    } else {
+        Err(api_error(StatusCode::FORBIDDEN, "Only admins can change monitored organizations"))
+    }
+}
+
+/// Every monitored organization with its last event and findings this week
+#[utoipa::path(
+    get, path = "/api/v1/organizations", tag = "organizations",
+    responses(
+        (status = 200, body = Page<OrganizationHealth>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody)
+    )
+)]
+pub async fn list_organizations(State(state): State<AppState>) -> Result<Json<Page<OrganizationHealth>>, ApiError> {
+    Ok(Json(Page::all(with_db(&state, |db| db.organization_health()).await?)))
+}
+
+/// Add an organization or update its settings; the hunter picks changes up on its next start
+#[utoipa::path(
+    put, path = "/api/v1/organizations/{name}", tag = "organizations",
+    params(("name" = String, Path, description = "GitHub organization login")),
+    request_body = OrganizationSettings,
+    responses(
+        (status = 200, body = MonitoredOrganization),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody)
+    )
+)]
+pub async fn save_organization(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(name): Path<String>,
+    Json(settings): Json<OrganizationSettings>,
+) -> Result<Json<MonitoredOrganization>, ApiError> {
+    require_admin(&user)?;
+    Ok(Json(with_db(&state, move |db| db.save_organization(&name, &settings, &user.username)).await?))
+}
+
+#[utoipa::path(
+    delete, path = "/api/v1/organizations/{name}", tag = "organizations",
+    params(("name" = String, Path)),
+    responses(
+        (status = 204, description = "No longer monitored"),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn remove_organization(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(&user)?;
+    match with_db(&state, move |db| db.remove_organization(&name)).await? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err(api_error(StatusCode::NOT_FOUND, "Organization is not monitored")),
+    }
+}
+
+/// `?month=2026-09&format=html&limit=10`; the month defaults to the last complete one
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ScorecardQuery {
+    month: Option<String>,
+    format: Option<String>,
+    limit: Option<u32>,
+}
+
+/// Monthly exposure scorecard as JSON, or as a standalone HTML page with `format=html`
+#[utoipa::path(
+    get, path = "/api/v1/organizations/{name}/scorecard", tag = "organizations",
+    params(("name" = String, Path), ScorecardQuery),
+    responses(
+        (status = 200, content(
+            (OrganizationScorecard = "application/json"),
+            (String = "text/html"),
+        )),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn organization_scorecard(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<ScorecardQuery>,
+) -> Result<Response, ApiError> {
+    let period = match &query.month {
+        Some(month) => ScorecardPeriod::parse_month(month).map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?,
+        None => ScorecardPeriod::last_month(chrono::Utc::now().date_naive()),
+    };
+    let limit = query.limit.unwrap_or(10).clamp(1, 100);
+    let scorecard = with_db(&state, move |db| db.organization_scorecard(&name, period, limit)).await?;
+
+    match query.format.as_deref() {
+        None | Some("json") => Ok(Json(scorecard).into_response()),
+        Some("html") => {
+            let html = render_scorecards_html(&[scorecard])
+                .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+            Ok(Html(html).into_response())
+        }
+        Some(other) => Err(api_error(StatusCode::BAD_REQUEST, format!("Unknown format {}", other))),
+    }
+}
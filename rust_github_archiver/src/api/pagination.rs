@@ -0,0 +1,104 @@
+// Response envelope and keyset cursors shared by the list endpoints
+use axum::http::StatusCode;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{de::DeserializeOwned, Serialize};
+use utoipa::ToSchema;
+
+use crate::api::findings::{api_error, ApiError};
+
+/// Largest page any list endpoint returns
+pub const MAX_PAGE_SIZE: u32 = 1000;
+
+/// `{"data": [...], "cursor": "...", "total_estimate": 1234, "warnings": []}`.
+/// `cursor` is set when there may be more rows; pass it back as `?cursor=` for the next page.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub cursor: Option<String>,
+    pub total_estimate: Option<i64>,
+    pub warnings: Vec<String>,
+}
+
+impl<T> Page<T> {
+    /// A complete list that is never paginated
+    pub fn all(data: Vec<T>) -> Self {
+        let total = data.len() as i64;
+        Self { data, cursor: None, total_estimate: Some(total), warnings: Vec::new() }
+    }
+
+    /// One page of at most `limit` rows. A full page gets a cursor after its last row, so the
+    /// next request starts there even if rows are inserted in between.
+    pub fn keyset<K: Serialize>(data: Vec<T>, limit: u32, key: impl Fn(&T) -> K) -> Self {
+        let cursor = match data.last() {
+            Some(last) if data.len() as u32 >= limit => Some(encode_cursor(&key(last))),
+            _ => None,
+        };
+        Self { data, cursor, total_estimate: None, warnings: Vec::new() }
+    }
+
+    pub fn with_total_estimate(mut self, total: Option<i64>) -> Self {
+        self.total_estimate = total;
+        self
+    }
+
+    pub fn with_warnings(mut self, warnings: Vec<String>) -> Self {
+        self.warnings.extend(warnings);
+        self
+    }
+}
+
+/// URL-safe base64 of the key's JSON. Opaque to clients; its shape may change between releases.
+pub fn encode_cursor(key: &impl Serialize) -> String {
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(key).unwrap_or_default())
+}
+
+/// `None` for the first page; a cursor that does not decode to `K` is a 400
+pub fn decode_cursor<K: DeserializeOwned>(cursor: Option<&str>) -> Result<Option<K>, ApiError> {
+    let Some(cursor) = cursor.filter(|c| !c.is_empty()) else {
+        return Ok(None);
+    };
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .map(Some)
+        .ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "Invalid cursor"))
+}
+
+/// `requested` or `default`, capped at `MAX_PAGE_SIZE` with a warning when it was larger
+pub fn page_size(requested: Option<u32>, default: u32, warnings: &mut Vec<String>) -> u32 {
+    match requested {
+        Some(limit) if limit > MAX_PAGE_SIZE => {
+            warnings.push(format!("limit {} exceeds the maximum; returning {} rows per page", limit, MAX_PAGE_SIZE));
+            MAX_PAGE_SIZE
+        }
+        Some(limit) => limit.max(1),
+        None => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let key = ("2024-05-01 12:00:00".to_string(), 42i64);
+        let decoded: Option<(String, i64)> = decode_cursor(Some(&encode_cursor(&key))).unwrap();
+        assert_eq!(decoded, Some(key));
+        assert!(decode_cursor::<(String, i64)>(None).unwrap().is_none());
+        assert!(decode_cursor::<(String, i64)>(Some("not a cursor")).is_err());
+    }
+
+    #[test]
+    fn test_only_full_pages_get_a_cursor() {
+        let full = Page::keyset(vec![1, 2, 3], 3, |n| *n);
+        assert_eq!(decode_cursor::<i32>(full.cursor.as_deref()).unwrap(), Some(3));
+        assert!(Page::keyset(vec![1, 2], 3, |n| *n).cursor.is_none());
+
+        let mut warnings = Vec::new();
+        assert_eq!(page_size(Some(5000), 100, &mut warnings), MAX_PAGE_SIZE);
+        assert_eq!(page_size(None, 100, &mut warnings), 100);
+        assert_eq!(warnings.len(), 1);
+    }
+}
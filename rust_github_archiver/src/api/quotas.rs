@@ -0,0 +1,87 @@
+// Workspace quotas and the budget left in the current period
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::api::findings::{with_db, ApiError};
+use crate::api::openapi::ErrorBody;
+use crate::api::state::AppState;
+use crate::performance::QuotaReport;
+
+/// `?workspace=acme`
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct QuotaQuery {
+    /// Defaults to the server's own workspace
+    workspace: Option<String>,
+}
+
+/// Limits, usage and remaining budget of a workspace: GitHub API calls and AI tokens per
+/// UTC day, BigQuery bytes per UTC month, and stored findings
+#[utoipa::path(
+    get, path = "/api/v1/quota", tag = "limits", params(QuotaQuery),
+    responses((status = 200, body = QuotaReport),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody))
+)]
+pub async fn workspace_quota(
+    State(state): State<AppState>,
+    Query(query): Query<QuotaQuery>,
+) -> Result<Json<QuotaReport>, ApiError> {
+    let quotas = &state.config.quotas;
+    let workspace = query.workspace.unwrap_or_else(|| quotas.workspace.clone());
+    let limits = quotas.limits_for(&workspace).clone();
+    Ok(Json(with_db(&state, move |db| db.quota_report(&workspace, &limits)).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::QuotaLimits;
+    use crate::performance::{QuotaBudget, QuotaResource};
+
+    fn budget(report: &QuotaReport, resource: QuotaResource) -> QuotaBudget {
+        report.budgets.iter().find(|b| b.resource == resource).unwrap().clone()
+    }
+
+    #[tokio::test]
+    async fn test_workspace_quota_reports_each_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = AppState::for_tests(dir.path());
+        state.config.quotas.workspace = "home".to_string();
+        state.config.quotas.limits =
+            QuotaLimits { github_api_calls_per_day: 100, bigquery_bytes_per_month: 0, ai_tokens_per_day: 0, findings_stored: 0 };
+        state.config.quotas.workspaces.insert(
+            "acme".to_string(),
+            QuotaLimits { github_api_calls_per_day: 10, bigquery_bytes_per_month: 0, ai_tokens_per_day: 500, findings_stored: 0 },
+        );
+        state
+            .findings_db
+            .interact(|db| {
+                db.record_quota_usage("home", QuotaResource::GithubApiCalls, 30)?;
+                db.record_quota_usage("acme", QuotaResource::GithubApiCalls, 4)?;
+                db.record_quota_usage("acme", QuotaResource::AiTokens, 700)
+            })
+            .await
+            .unwrap();
+
+        let Json(home) = workspace_quota(State(state.clone()), Query(QuotaQuery { workspace: None })).await.unwrap();
+        assert_eq!(home.workspace, "home");
+        let calls = budget(&home, QuotaResource::GithubApiCalls);
+        assert_eq!((calls.limit, calls.used, calls.remaining), (100, 30, Some(70)));
+        assert!(calls.resets_at.is_some());
+
+        let Json(acme) = workspace_quota(State(state.clone()), Query(QuotaQuery { workspace: Some("acme".to_string()) })).await.unwrap();
+        assert_eq!(acme.workspace, "acme");
+        assert_eq!(budget(&acme, QuotaResource::GithubApiCalls).remaining, Some(6));
+        assert_eq!(budget(&acme, QuotaResource::AiTokens).remaining, Some(0), "usage past the limit leaves nothing");
+        assert_eq!(budget(&acme, QuotaResource::BigqueryBytes).remaining, None, "a zero limit is unlimited");
+
+        // A workspace without its own entry falls back to the default limits
+        let Json(other) = workspace_quota(State(state), Query(QuotaQuery { workspace: Some("other".to_string()) })).await.unwrap();
+        let calls = budget(&other, QuotaResource::GithubApiCalls);
+        assert_eq!((calls.limit, calls.used), (100, 0));
+    }
+}
@@ -0,0 +1,172 @@
+// Synced repository metadata for triage and dashboard facets
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::api::findings::{api_error, with_db, ApiError};
+use crate::api::openapi::ErrorBody;
+use crate::api::pagination::{decode_cursor, page_size, Page, MAX_PAGE_SIZE};
+use crate::api::state::AppState;
+use crate::performance::{CacheScope, RepositoryFacets, RepositoryFilters, RepositoryRecord};
+
+/// `cursor` from the previous page
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RepositoryCursorQuery {
+    cursor: Option<String>,
+}
+
+/// Repositories riskiest first; `?organization=&visibility=&language=&min_risk=&limit=100&cursor=`
+#[utoipa::path(
+    get, path = "/api/v1/repos", tag = "repositories",
+    params(RepositoryFilters, RepositoryCursorQuery),
+    responses(
+        (status = 200, body = Page<RepositoryRecord>),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody)
+    )
+)]
+pub async fn list_repositories(
+    State(state): State<AppState>,
+    Query(mut filters): Query<RepositoryFilters>,
+    Query(position): Query<RepositoryCursorQuery>,
+) -> Result<Json<Page<RepositoryRecord>>, ApiError> {
+    let mut warnings = Vec::new();
+    let limit = page_size(filters.limit, 100, &mut warnings);
+    filters.limit = Some(limit);
+    filters.after = decode_cursor(position.cursor.as_deref())?;
+
+    let repositories = with_db(&state, move |db| db.list_repositories(&filters)).await?;
+    Ok(Json(
+        Page::keyset(repositories, limit, |r| (r.risk_score, r.name.clone())).with_warnings(warnings),
+    ))
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/repos/{owner}/{name}", tag = "repositories",
+    params(("owner" = String, Path), ("name" = String, Path)),
+    responses(
+        (status = 200, body = RepositoryRecord),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn get_repository(
+    State(state): State<AppState>,
+    Path((owner, name)): Path<(String, String)>,
+) -> Result<Json<RepositoryRecord>, ApiError> {
+    let repository = format!("{}/{}", owner, name);
+    with_db(&state, move |db| db.repository(&repository))
+        .await?
+        .map(Json)
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Repository has not been seen"))
+}
+
+/// `?limit=20` caps the language and organization facets
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FacetQuery {
+    limit: Option<u32>,
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/repos/facets", tag = "repositories",
+    params(FacetQuery),
+    responses(
+        (status = 200, body = RepositoryFacets),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody)
+    )
+)]
+pub async fn repository_facets(
+    State(state): State<AppState>,
+    Query(query): Query<FacetQuery>,
+) -> Result<Json<RepositoryFacets>, ApiError> {
+    let limit = query.limit.unwrap_or(20).clamp(1, MAX_PAGE_SIZE);
+    Ok(Json(with_db(&state, move |db| {
+        db.cached(CacheScope::Repositories, &("facets", limit), |db| db.repository_facets(limit))
+    }).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::performance::RepositoryMetadata;
+
+    async fn seed(state: &AppState) {
+        let repositories =
+            [("acme/api", "public", "Go", 900), ("acme/web", "private", "TypeScript", 40), ("other/cli", "public", "Go", 5)];
+        let metadata: Vec<_> = repositories
+            .iter()
+            .map(|(name, visibility, language, stars)| RepositoryMetadata {
+                name: name.to_string(),
+                visibility: Some(visibility.to_string()),
+                star_count: Some(*stars),
+                language: Some(language.to_string()),
+                ..Default::default()
+            })
+            .collect();
+        state
+            .findings_db
+            .interact(move |db| metadata.iter().try_for_each(|m| db.save_repository_metadata(m).map(|_| ())))
+            .await
+            .unwrap();
+    }
+
+    fn page(filters: RepositoryFilters, cursor: Option<String>) -> (Query<RepositoryFilters>, Query<RepositoryCursorQuery>) {
+        (Query(filters), Query(RepositoryCursorQuery { cursor }))
+    }
+
+    #[tokio::test]
+    async fn test_list_repositories_filters_and_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path());
+        seed(&state).await;
+
+        let (filters, position) = page(RepositoryFilters { language: Some("go".to_string()), ..Default::default() }, None);
+        let Json(go) = list_repositories(State(state.clone()), filters, position).await.unwrap();
+        let mut names: Vec<_> = go.data.iter().map(|r| r.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, ["acme/api", "other/cli"]);
+
+        // Two pages of one, riskiest first, then nothing left
+        let acme = |organization: &str| RepositoryFilters {
+            organization: Some(organization.to_string()),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let (filters, position) = page(acme("ACME"), None);
+        let Json(first) = list_repositories(State(state.clone()), filters, position).await.unwrap();
+        assert_eq!(first.data.len(), 1);
+        let cursor = first.cursor.clone().expect("a full page has a cursor");
+        let (filters, position) = page(acme("acme"), Some(cursor));
+        let Json(second) = list_repositories(State(state.clone()), filters, position).await.unwrap();
+        assert_eq!(second.data.len(), 1);
+        assert_ne!(first.data[0].name, second.data[0].name);
+        assert!(first.data[0].risk_score >= second.data[0].risk_score);
+
+        let (filters, position) = page(RepositoryFilters::default(), Some("not a cursor".to_string()));
+        let error = list_repositories(State(state), filters, position).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_get_repository_and_facets() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path());
+        seed(&state).await;
+
+        let Json(repository) = get_repository(State(state.clone()), Path(("acme".to_string(), "web".to_string()))).await.unwrap();
+        assert_eq!((repository.organization.as_deref(), repository.star_count), (Some("acme"), Some(40)));
+        let error = get_repository(State(state.clone()), Path(("acme".to_string(), "gone".to_string()))).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::NOT_FOUND);
+
+        let Json(facets) = repository_facets(State(state), Query(FacetQuery { limit: Some(1) })).await.unwrap();
+        assert_eq!(facets.language.len(), 1);
+        assert_eq!((facets.language[0].value.as_str(), facets.language[0].repositories), ("Go", 2));
+        assert_eq!((facets.organization[0].value.as_str(), facets.organization[0].repositories), ("acme", 2));
+    }
+}
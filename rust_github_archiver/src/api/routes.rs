@@ -1,14 +1,39 @@
 // API routes implementation
-use axum::{Router, routing::{get, post}, middleware, response::Html};
+use axum::{Router, routing::{delete, get, patch, post, put}, middleware, response::{Html, Response}, extract::{DefaultBodyLimit, Request}, http::{Method, StatusCode}, Json};
+use serde_json::{json, Value};
 use std::sync::Arc;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 use crate::auth::{UserManager, auth_middleware, optional_auth_middleware};
 use crate::api::handlers::{
-    health_check, login, logout, user_info, auth_status,
+    health_check, healthz, login, logout, oidc_login, oidc_callback, user_info, auth_status, refresh_session, list_sessions, revoke_session, change_password,
     start_scraper, stop_scraper, pause_scraper, resume_scraper, 
-    restart_scraper, scraper_status, system_status
+    restart_scraper, scraper_status, system_status, monitor_status
 };
+use crate::api::findings::{
+    list_findings, similar_findings, set_finding_status, list_saved_searches, save_search, run_saved_search, delete_saved_search, list_tags, get_finding_tags, add_finding_tags, remove_finding_tag,
+    get_repository_tags, add_repository_tags, remove_repository_tag,
+    list_annotations, add_note, update_note, delete_note,
+    upload_attachment, download_attachment, delete_attachment
+};
+use crate::api::stats::{
+    stats_summary, top_detectors, top_repositories, findings_trend, validation_ratio, mttr
+};
+use crate::api::approvals::{list_approvals, request_approval, approve_request, reject_request};
+use crate::api::middleware::{api_limits, rate_limit};
+use crate::api::openapi::ApiDoc;
+use crate::api::quotas::workspace_quota;
+use crate::api::organizations::{list_organizations, save_organization, remove_organization, organization_scorecard};
+use crate::api::repositories::{list_repositories, get_repository, repository_facets};
+use crate::api::scans::{trigger_scan, get_scan};
 use crate::api::state::AppState;
+use crate::api::users::{
+    list_users, create_user, update_user, reset_password, revoke_user_sessions,
+    my_notification_preferences, set_my_notification_preferences, user_notification_preferences,
+    set_user_notification_preferences,
+};
+use crate::performance::MAX_ATTACHMENT_BYTES;
 
 // Handler to serve dashboard.html
 async fn serve_dashboard() -> Html<String> {
@@ -18,32 +43,167 @@ async fn serve_dashboard() -> Html<String> {
     }
 }
 
-pub fn create_routes(app_state: AppState) -> Router {
-    // Create protected routes that require authentication
-    let protected_routes = Router::new()
-        .route("/api/auth/logout", post(logout))
-        .route("/api/auth/user", get(user_info))
-        // Scraper control endpoints (protected)
+/// Requests served in read-only mode: anything that does not change state, plus the caller's
+/// own session, password and notification preferences, which live in the users database rather than the findings
+fn allowed_when_read_only(method: &Method, path: &str) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+        || matches!(path, "/api/auth/login" | "/api/auth/logout" | "/api/auth/refresh" | "/api/auth/password" | "/api/auth/notifications")
+        || (*method == Method::DELETE && path.starts_with("/api/auth/sessions/"))
+}
+
+async fn read_only_guard(request: Request, next: middleware::Next) -> Result<Response, (StatusCode, Json<Value>)> {
+    if !allowed_when_read_only(request.method(), request.uri().path()) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({
+                "error": "Read-only mode",
+                "message": "This server is running in read-only mode; changes are disabled"
+            })),
+        ));
+    }
+    Ok(next.run(request).await)
+}
+
+/// Scraper control endpoints; served by the admin listener instead when one is configured
+fn scraper_control_routes() -> Router<AppState> {
+    Router::new()
         .route("/api/start-scraper", post(start_scraper))
         .route("/api/stop-scraper", post(stop_scraper))
         .route("/api/pause-scraper", post(pause_scraper))
         .route("/api/resume-scraper", post(resume_scraper))
         .route("/api/restart-scraper", post(restart_scraper))
-        .layer(middleware::from_fn_with_state(app_state.user_manager.clone(), auth_middleware))
+}
+
+/// Applies the read-only guard, body limit and rate limits shared by both listeners
+fn harden(router: Router, app_state: &AppState) -> Router {
+    // Checked before authentication, so even admins cannot write to a read-only server
+    let router = if app_state.config.web.read_only {
+        router.layer(middleware::from_fn(read_only_guard))
+    } else {
+        router
+    };
+
+    // Outermost, so throttled clients never reach authentication or the database
+    router
+        .layer(DefaultBodyLimit::max(app_state.config.web.max_request_size))
+        .layer(middleware::from_fn_with_state(app_state.limiter.clone(), rate_limit))
+}
+
+/// Routes of the admin listener: sign-in, health and the scraper controls
+pub fn create_admin_routes(app_state: AppState) -> Router {
+    let protected_routes = scraper_control_routes()
+        .route("/api/auth/logout", post(logout))
+        .route("/api/auth/user", get(user_info))
+        .route("/api/auth/password", post(change_password))
+        .layer(middleware::from_fn_with_state(app_state.auth_state(), auth_middleware))
+        .with_state(app_state.clone());
+
+    let router = Router::new()
+        .route("/healthz", get(healthz))
+        .route("/api/auth/login", post(login))
+        .route("/api/auth/refresh", post(refresh_session))
+        .route("/api/auth/oidc/login", get(oidc_login))
+        .route("/api/auth/oidc/callback", get(oidc_callback))
+        .route("/api/status", get(system_status))
+        .route("/api/scraper/status", get(scraper_status))
+        .merge(protected_routes)
+        .with_state(app_state.clone());
+    harden(router, &app_state)
+}
+
+pub fn create_routes(app_state: AppState) -> Router {
+    // Scraper controls stay on the main listener unless an admin listener takes them
+    let scraper_controls = if app_state.config.server.admin_listen.is_some() {
+        Router::new()
+    } else {
+        scraper_control_routes()
+    };
+
+    // Create protected routes that require authentication
+    let protected_routes = scraper_controls
+        .route("/api/auth/logout", post(logout))
+        .route("/api/auth/user", get(user_info))
+        .route("/api/auth/password", post(change_password))
+        .route("/api/auth/sessions", get(list_sessions))
+        .route("/api/auth/sessions/:id", delete(revoke_session))
+        .route("/api/auth/notifications", get(my_notification_preferences).put(set_my_notification_preferences))
+        // User administration (admins only)
+        .route("/api/v1/users", get(list_users).post(create_user))
+        .route("/api/v1/users/:username", patch(update_user))
+        .route("/api/v1/users/:username/password", post(reset_password))
+        .route("/api/v1/users/:username/sessions", delete(revoke_user_sessions))
+        .route(
+            "/api/v1/users/:username/notifications",
+            get(user_notification_preferences).put(set_user_notification_preferences),
+        )
+        // Two-person approval of exports and erasure
+        .route("/api/v1/approvals", get(list_approvals).post(request_approval))
+        .route("/api/v1/approvals/:id/approve", post(approve_request))
+        .route("/api/v1/approvals/:id/reject", post(reject_request))
+        // The caller's rate limit and how much of it is left
+        .route("/api/v1/limits", get(api_limits))
+        // The workspace's quotas and what is left of them
+        .route("/api/v1/quota", get(workspace_quota))
+        // Real-time monitor health and the GitHub rate limit it is pacing itself by
+        .route("/api/v1/monitor", get(monitor_status))
+        // Monitored organizations (admins may change them)
+        .route("/api/v1/organizations", get(list_organizations))
+        .route("/api/v1/organizations/:name", put(save_organization).delete(remove_organization))
+        .route("/api/v1/organizations/:name/scorecard", get(organization_scorecard))
+        // Scans triggered by CI and SOAR (analysts and admins may queue them)
+        .route("/api/v1/scans", post(trigger_scan))
+        .route("/api/v1/scans/:id", get(get_scan))
+        // Dashboard aggregations, served from the rollup tables
+        .route("/api/v1/stats", get(stats_summary))
+        .route("/api/v1/stats/top-detectors", get(top_detectors))
+        .route("/api/v1/stats/top-repos", get(top_repositories))
+        .route("/api/v1/stats/trend", get(findings_trend))
+        .route("/api/v1/stats/validation-ratio", get(validation_ratio))
+        .route("/api/v1/stats/mttr", get(mttr))
+        // Finding queries, saved searches and tags
+        .route("/api/v1/findings", get(list_findings))
+        .route("/api/v1/findings/:hash/similar", get(similar_findings))
+        .route("/api/v1/findings/:hash/status", put(set_finding_status))
+        .route("/api/v1/searches", get(list_saved_searches).post(save_search))
+        .route("/api/v1/searches/:name", delete(delete_saved_search))
+        .route("/api/v1/searches/:name/results", get(run_saved_search))
+        .route("/api/v1/tags", get(list_tags))
+        .route("/api/v1/findings/:hash/tags", get(get_finding_tags).post(add_finding_tags))
+        .route("/api/v1/findings/:hash/tags/:tag", delete(remove_finding_tag))
+        .route("/api/v1/repos", get(list_repositories))
+        .route("/api/v1/repos/facets", get(repository_facets))
+        .route("/api/v1/repos/:owner/:name", get(get_repository))
+        .route("/api/v1/repos/:owner/:name/tags", get(get_repository_tags).post(add_repository_tags))
+        .route("/api/v1/repos/:owner/:name/tags/:tag", delete(remove_repository_tag))
+        // Finding notes and evidence (analysts and admins may write)
+        .route("/api/v1/findings/:hash/annotations", get(list_annotations))
+        .route("/api/v1/findings/:hash/notes", post(add_note))
+        .route("/api/v1/findings/:hash/notes/:id", put(update_note).delete(delete_note))
+        .route(
+            "/api/v1/findings/:hash/attachments",
+            // Leave room for multipart framing around the largest accepted file
+            post(upload_attachment).layer(DefaultBodyLimit::max(MAX_ATTACHMENT_BYTES + 64 * 1024)),
+        )
+        .route("/api/v1/findings/:hash/attachments/:sha256", get(download_attachment).delete(delete_attachment))
+        .layer(middleware::from_fn_with_state(app_state.auth_state(), auth_middleware))
         .with_state(app_state.clone());
 
     // Create auth status route with optional authentication
     let auth_status_route = Router::new()
         .route("/api/auth/status", get(auth_status))
-        .layer(middleware::from_fn_with_state(app_state.user_manager.clone(), optional_auth_middleware))
+        .layer(middleware::from_fn_with_state(app_state.auth_state(), optional_auth_middleware))
         .with_state(app_state.clone());
 
     // Combine public and protected routes
-    Router::new()
+    let router = Router::new()
         // Public routes
         .route("/health", get(health_check))
         .route("/api/health", get(health_check))
+        .route("/healthz", get(healthz))
         .route("/api/auth/login", post(login))
+        .route("/api/auth/refresh", post(refresh_session))
+        .route("/api/auth/oidc/login", get(oidc_login))
+        .route("/api/auth/oidc/callback", get(oidc_callback))
         // Status endpoints (public)
         .route("/api/status", get(system_status))
         .route("/api/scraper/status", get(scraper_status))
@@ -51,10 +211,32 @@ pub fn create_routes(app_state: AppState) -> Router {
         .route("/", get(serve_dashboard))
         .route("/dashboard", get(serve_dashboard))
         .route("/dashboard.html", get(serve_dashboard))
+        // OpenAPI document and Swagger UI (public, like the login it describes)
+        .merge(SwaggerUi::new("/api/docs").url("/api/openapi.json", ApiDoc::openapi()))
         // Merge auth status route with optional auth
         .merge(auth_status_route)
         // Merge protected routes
         .merge(protected_routes)
         // Add app state that includes user manager and scraper manager
-        .with_state(app_state)
+        .with_state(app_state.clone());
+
+    harden(router, &app_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_only_allows_queries_only() {
+        assert!(allowed_when_read_only(&Method::GET, "/api/v1/findings"));
+        assert!(allowed_when_read_only(&Method::POST, "/api/auth/login"));
+        assert!(!allowed_when_read_only(&Method::POST, "/api/v1/findings/abc/notes"));
+        assert!(!allowed_when_read_only(&Method::DELETE, "/api/v1/searches/mine"));
+        assert!(!allowed_when_read_only(&Method::POST, "/api/start-scraper"));
+        assert!(allowed_when_read_only(&Method::POST, "/api/auth/refresh"));
+        assert!(allowed_when_read_only(&Method::DELETE, "/api/auth/sessions/abc"));
+        assert!(!allowed_when_read_only(&Method::POST, "/api/v1/users"));
+        assert!(allowed_when_read_only(&Method::PUT, "/api/auth/notifications"));
+    }
 }
@@ -0,0 +1,120 @@
+// Scans triggered by CI systems, SOAR platforms and analysts, run by the hunter
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::api::findings::{api_error, with_db, ApiError};
+use crate::api::openapi::ErrorBody;
+use crate::api::state::AppState;
+use crate::auth::User;
+use crate::performance::{NewScanRequest, ScanRequest};
+
+/// Queue a scan of a repository, or of one commit of it. The running hunter picks it up
+/// within a minute and POSTs a signed summary to `callback_url` when it finishes.
+#[utoipa::path(
+    post, path = "/api/v1/scans", tag = "scans",
+    request_body = NewScanRequest,
+    responses(
+        (status = 202, description = "Queued", body = ScanRequest),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody)
+    )
+)]
+pub async fn trigger_scan(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(request): Json<NewScanRequest>,
+) -> Result<(StatusCode, Json<ScanRequest>), ApiError> {
+    if !user.role_kind().can_annotate() {
+        return Err(api_error(StatusCode::FORBIDDEN, "Viewers cannot trigger scans"));
+    }
+    let queued = with_db(&state, move |db| db.queue_scan_request(&request, &user.username)).await?;
+    Ok((StatusCode::ACCEPTED, Json(queued)))
+}
+
+/// A triggered scan's status, and its finding count once completed
+#[utoipa::path(
+    get, path = "/api/v1/scans/{id}", tag = "scans",
+    params(("id" = i64, Path)),
+    responses(
+        (status = 200, body = ScanRequest),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn get_scan(State(state): State<AppState>, Path(id): Path<i64>) -> Result<Json<ScanRequest>, ApiError> {
+    match with_db(&state, move |db| db.scan_request(id)).await? {
+        Some(request) => Ok(Json(request)),
+        None => Err(api_error(StatusCode::NOT_FOUND, "No such scan request")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Role;
+
+    fn new_scan(repository: &str, commit_sha: Option<&str>, callback_url: Option<&str>) -> Json<NewScanRequest> {
+        Json(NewScanRequest {
+            repository: repository.to_string(),
+            commit_sha: commit_sha.map(str::to_string),
+            callback_url: callback_url.map(str::to_string),
+            source: "ci".to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_trigger_and_follow_a_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path());
+        let analyst = state.test_user("alice", Role::Analyst).await;
+
+        let request = new_scan("acme/api", Some("0123abcd"), Some("https://ci.example.com/hook"));
+        let (status, Json(queued)) = trigger_scan(State(state.clone()), Extension(analyst), request).await.unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!((queued.status.as_str(), queued.requested_by.as_str(), queued.source.as_str()), ("queued", "alice", "ci"));
+
+        let id = queued.id;
+        let Json(fetched) = get_scan(State(state.clone()), Path(id)).await.unwrap();
+        assert_eq!(fetched.repository, "acme/api");
+        assert_eq!(fetched.commit_sha.as_deref(), Some("0123abcd"));
+        assert!(!fetched.is_finished());
+
+        state
+            .findings_db
+            .interact(move |db| {
+                db.claim_scan_request()?;
+                db.finish_scan_request(id, Ok(3))
+            })
+            .await
+            .unwrap();
+        let Json(finished) = get_scan(State(state), Path(id)).await.unwrap();
+        assert_eq!((finished.status.as_str(), finished.findings), ("completed", Some(3)));
+    }
+
+    #[tokio::test]
+    async fn test_trigger_scan_rejects_bad_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path());
+        let viewer = state.test_user("victor", Role::Viewer).await;
+        let analyst = state.test_user("alice", Role::Analyst).await;
+
+        let error = trigger_scan(State(state.clone()), Extension(viewer), new_scan("acme/api", None, None)).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::FORBIDDEN);
+
+        for request in [
+            new_scan("acme api", None, None),
+            new_scan("acme/api", Some("not-a-sha"), None),
+            new_scan("acme/api", None, Some("ftp://ci.example.com/hook")),
+        ] {
+            let error = trigger_scan(State(state.clone()), Extension(analyst.clone()), request).await.unwrap_err();
+            assert_eq!(error.0, StatusCode::BAD_REQUEST);
+        }
+
+        let error = get_scan(State(state), Path(42)).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::NOT_FOUND);
+    }
+}
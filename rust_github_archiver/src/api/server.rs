@@ -1,12 +1,13 @@
 // API server implementation
-use anyhow::Result;
-use axum::Router;
+use anyhow::{anyhow, Result};
+use axum::{middleware, Router};
 use std::net::SocketAddr;
-use tokio::net::TcpListener;
+use std::sync::Arc;
 use tracing::info;
 
 use crate::core::Config;
-use crate::api::routes::create_routes;
+use crate::api::listener::{ip_allowlist, serve, tls_config, IpAllowlist};
+use crate::api::routes::{create_admin_routes, create_routes};
 use crate::api::state::AppState;
 
 #[derive(Clone)]
@@ -15,21 +16,55 @@ pub struct ApiServer {
 }
 
 impl ApiServer {
-    pub fn new(config: Config) -> Self {
-        Self { 
-            app_state: AppState::new(config)
-        }
+    pub fn new(config: Config) -> Result<Self> {
+        Ok(Self {
+            app_state: AppState::new(config)?,
+        })
     }
 
     pub async fn start(&self) -> Result<()> {
-        let app = self.create_app();
-        
+        // Migrate the findings database before listening, so a schema from a newer build
+        // stops startup instead of failing every request. A read-only server only checks it.
+        let version = self.app_state.findings_db.interact(|db| db.schema_version()).await?;
+        info!(
+            "Findings database at schema version {} (up to {} pooled connections)",
+            version,
+            self.app_state.findings_db.status().max_size
+        );
+        let read_only = self.app_state.config.web.read_only;
+        if read_only {
+            info!("Read-only mode: changes through the API are disabled");
+        }
+
+        let server = &self.app_state.config.server;
+        let tls = tls_config(server)?;
+        let scheme = if tls.is_some() { "https" } else { "http" };
+        if server.client_ca.is_some() {
+            info!("Mutual TLS: clients must present a certificate issued by the configured CA");
+        }
+        let allowlist = IpAllowlist::parse(&server.allowed_ips)?;
+        let admin_allowlist = if server.admin_allowed_ips.is_empty() {
+            allowlist.clone()
+        } else {
+            IpAllowlist::parse(&server.admin_allowed_ips)?
+        };
+
+        let app = with_allowlist(self.create_app(), allowlist);
         let addr = SocketAddr::from(([0, 0, 0, 0], self.app_state.config.web.port));
-        info!("Server listening on {}", addr);
-        
-        let listener = TcpListener::bind(addr).await?;
-        axum::serve(listener, app).await?;
-        
+        info!("Server listening on {}://{}", scheme, addr);
+
+        match &server.admin_listen {
+            Some(admin_listen) => {
+                let admin_addr: SocketAddr = admin_listen
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid admin listen address {} (expected host:port)", admin_listen))?;
+                info!("Admin listener (scraper controls) on {}://{}", scheme, admin_addr);
+                let admin = with_allowlist(create_admin_routes(self.app_state.clone()), admin_allowlist);
+                tokio::try_join!(serve(addr, app, tls.clone()), serve(admin_addr, admin, tls))?;
+            }
+            None => serve(addr, app, tls).await?,
+        }
+
         Ok(())
     }
 
@@ -37,3 +72,8 @@ impl ApiServer {
         create_routes(self.app_state.clone())
     }
 }
+
+/// Outermost layer, so refused addresses are not even counted against a rate limit
+fn with_allowlist(router: Router, allowlist: IpAllowlist) -> Router {
+    router.layer(middleware::from_fn_with_state(Arc::new(allowlist), ip_allowlist))
+}
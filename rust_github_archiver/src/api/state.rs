@@ -1,8 +1,11 @@
+use crate::api::middleware::{ApiLimits, RateLimiter};
 use crate::core::Config;
 use crate::scraper::{ScraperManager, MainScraper};
-use crate::auth::UserManager;
+use crate::auth::{AuthState, OidcProvider, UserManager};
+use crate::performance::{AttachmentStore, SecretDatabasePool};
+use crate::sinks::{RedactionProfile, SinkDispatcher};
 use std::sync::{Arc, Mutex};
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 #[derive(Clone)]
 pub struct AppState {
@@ -10,16 +13,49 @@ pub struct AppState {
     pub scraper_manager: Arc<ScraperManager>,
     pub main_scraper: Arc<Mutex<Option<MainScraper>>>,
     pub user_manager: Arc<UserManager>,
+    /// Single sign-on provider, when one is configured
+    pub oidc: Option<Arc<OidcProvider>>,
+    /// Connections to the findings database, shared by every request
+    pub findings_db: SecretDatabasePool,
+    /// Evidence files for the findings database
+    pub attachments: AttachmentStore,
+    /// Ticket sinks that receive notes and evidence added through the API
+    pub sinks: Arc<SinkDispatcher>,
+    /// Per-client request buckets and findings query counts
+    pub limiter: Arc<RateLimiter>,
 }
 
 impl AppState {
-    pub fn new(config: Config) -> Self {
-        Self {
+    pub fn new(config: Config) -> Result<Self> {
+        let sinks = SinkDispatcher::from_config(&config.sinks, &config.http).unwrap_or_else(|e| {
+            tracing::warn!("Finding sinks disabled for the API: {}", e);
+            SinkDispatcher::new(Vec::new(), 1, RedactionProfile::HashOnly)
+        });
+
+        let web = &config.web;
+        let findings_db = SecretDatabasePool::new(&web.findings_database, web.read_only, web.findings_pool_size)
+            .with_context(|| format!("Cannot open the findings database {}", web.findings_database))?;
+
+        let user_manager = UserManager::open(&config.security.users_database, &config.security)
+            .with_context(|| format!("Cannot open the users database {}", config.security.users_database))?;
+        let oidc = OidcProvider::from_config(&config.oidc, &config.http).context("Invalid single sign-on (OIDC) settings")?;
+
+        Ok(Self {
+            findings_db,
+            attachments: AttachmentStore::beside_database(&config.web.findings_database),
             config: config.clone(),
             scraper_manager: Arc::new(ScraperManager::new()),
             main_scraper: Arc::new(Mutex::new(None)),
-            user_manager: Arc::new(UserManager::new()),
-        }
+            user_manager: Arc::new(user_manager),
+            oidc: oidc.map(Arc::new),
+            sinks: Arc::new(sinks),
+            limiter: Arc::new(RateLimiter::new(ApiLimits::from_config(web))),
+        })
+    }
+
+    /// State for the authentication middleware
+    pub fn auth_state(&self) -> AuthState {
+        AuthState { users: self.user_manager.clone(), oidc: self.oidc.clone() }
     }
 
     pub async fn initialize_main_scraper(&self) -> Result<()> {
@@ -54,3 +90,23 @@ impl AppState {
         })
     }
 }
+
+#[cfg(test)]
+impl AppState {
+    /// State over findings and users databases in `dir`, for handler tests
+    pub(crate) fn for_tests(dir: &std::path::Path) -> Self {
+        let mut config = Config::default();
+        config.web.findings_database = dir.join("secrets.db").display().to_string();
+        config.web.read_only = false;
+        config.security.users_database = dir.join("users.db").display().to_string();
+        config.security.admin_password = None;
+        config.security.approval_policy.clear();
+        config.logging.log_dir = dir.join("logs");
+        Self::new(config).unwrap()
+    }
+
+    /// An account with `role` and a password that passes the default rules
+    pub(crate) async fn test_user(&self, name: &str, role: crate::auth::Role) -> crate::auth::User {
+        self.user_manager.create_user(name, "a-long-enough-password", role, false).await.unwrap()
+    }
+}
@@ -0,0 +1,136 @@
+// Dashboard aggregations over the finding rollups, served from the query cache
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::api::findings::{with_db, ApiError};
+use crate::api::openapi::ErrorBody;
+use crate::api::state::AppState;
+use crate::performance::{CacheScope, Mttr, StatCount, StatsSummary, TrendPoint, ValidationRatio};
+
+const MAX_DAYS: u32 = 3650;
+const MAX_LIMIT: u32 = 100;
+
+/// `?days=30&limit=10`
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatsQuery {
+    days: Option<u32>,
+    limit: Option<u32>,
+}
+
+impl StatsQuery {
+    fn days(&self) -> u32 {
+        self.days.unwrap_or(30).min(MAX_DAYS)
+    }
+
+    fn limit(&self) -> u32 {
+        self.limit.unwrap_or(10).clamp(1, MAX_LIMIT)
+    }
+}
+
+/// Everything a dashboard needs in one call
+#[utoipa::path(
+    get, path = "/api/v1/stats", tag = "stats", params(StatsQuery),
+    responses((status = 200, body = StatsSummary),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody))
+)]
+pub async fn stats_summary(
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<StatsSummary>, ApiError> {
+    let (days, limit) = (query.days(), query.limit());
+    let result = with_db(&state, move |db| {
+        db.cached(CacheScope::Stats, &("summary", days, limit), |db| db.stats_summary(days, limit))
+    })
+    .await?;
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/stats/top-detectors", tag = "stats", params(StatsQuery),
+    responses((status = 200, body = Vec<StatCount>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody))
+)]
+pub async fn top_detectors(
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<Vec<StatCount>>, ApiError> {
+    let (days, limit) = (query.days(), query.limit());
+    let result = with_db(&state, move |db| {
+        db.cached(CacheScope::Stats, &("top_detectors", days, limit), |db| db.top_detectors(days, limit))
+    })
+    .await?;
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/stats/top-repos", tag = "stats", params(StatsQuery),
+    responses((status = 200, body = Vec<StatCount>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody))
+)]
+pub async fn top_repositories(
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<Vec<StatCount>>, ApiError> {
+    let (days, limit) = (query.days(), query.limit());
+    let result = with_db(&state, move |db| {
+        db.cached(CacheScope::Stats, &("top_repositories", days, limit), |db| db.top_repositories(days, limit))
+    })
+    .await?;
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/stats/trend", tag = "stats", params(StatsQuery),
+    responses((status = 200, body = Vec<TrendPoint>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody))
+)]
+pub async fn findings_trend(
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<Vec<TrendPoint>>, ApiError> {
+    let days = query.days();
+    let result = with_db(&state, move |db| {
+        db.cached(CacheScope::Stats, &("trend", days), |db| db.findings_trend(days))
+    })
+    .await?;
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/stats/validation-ratio", tag = "stats", params(StatsQuery),
+    responses((status = 200, body = ValidationRatio),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody))
+)]
+pub async fn validation_ratio(
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<ValidationRatio>, ApiError> {
+    let days = query.days();
+    let result = with_db(&state, move |db| {
+        db.cached(CacheScope::Stats, &("validation_ratio", days), |db| db.validation_ratio(days))
+    })
+    .await?;
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/stats/mttr", tag = "stats", params(StatsQuery),
+    responses((status = 200, body = Mttr),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody))
+)]
+pub async fn mttr(
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<Mttr>, ApiError> {
+    let days = query.days();
+    let result = with_db(&state, move |db| {
+        db.cached(CacheScope::Stats, &("mttr", days), |db| db.mttr(days))
+    })
+    .await?;
+    Ok(Json(result))
+}
@@ -0,0 +1,433 @@
+// User administration for admins: accounts, roles, password resets and sessions; and every
+// user's own notification preferences
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::api::findings::{api_error, ApiError};
+use crate::api::openapi::ErrorBody;
+use crate::api::pagination::Page;
+use crate::api::state::AppState;
+use crate::auth::{temporary_password, NotificationPreferences, Role, User};
+use crate::core::AuditLog;
+
+/// An account as admins see it; never the password hash
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserAccount {
+    pub username: String,
+    pub role: String,
+    pub is_active: bool,
+    pub must_change_password: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_login: Option<DateTime<Utc>>,
+    pub password_changed_at: DateTime<Utc>,
+}
+
+impl From<User> for UserAccount {
+    fn from(user: User) -> Self {
+        Self {
+            username: user.username,
+            role: user.role,
+            is_active: user.is_active,
+            must_change_password: user.must_change_password,
+            created_at: user.created_at,
+            last_login: user.last_login,
+            password_changed_at: user.password_changed_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct NewUser {
+    pub username: String,
+    /// admin, analyst or viewer
+    pub role: String,
+    /// Generated when omitted. Either way the user must change it at first sign-in.
+    pub password: Option<String>,
+}
+
+/// The account, and its password when the server generated it
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedUser {
+    pub user: UserAccount,
+    pub temporary_password: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UserUpdate {
+    pub role: Option<String>,
+    /// false disables the account and signs out its sessions
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct PasswordReset {
+    /// Generated when omitted
+    pub password: Option<String>,
+}
+
+fn require_admin(user: &User) -> Result<(), ApiError> {
+    if user.role_kind().can_manage_users() {
+        Ok(())
+    } else {
+        Err(api_error(StatusCode::FORBIDDEN, "Only admins can manage users"))
+    }
+}
+
+fn parse_role(name: &str) -> Result<Role, ApiError> {
+    Role::parse(name).ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "Invalid role: use admin, analyst or viewer"))
+}
+
+fn classify(error: anyhow::Error) -> ApiError {
+    let message = error.to_string();
+    let status = if message.starts_with("No user") {
+        StatusCode::NOT_FOUND
+    } else if message.ends_with("already exists") || message.starts_with("Cannot ") {
+        StatusCode::CONFLICT
+    } else if message.starts_with("Invalid ") {
+        StatusCode::BAD_REQUEST
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    api_error(status, message)
+}
+
+fn audit(state: &AppState, action: &str, admin: &User, details: serde_json::Value) {
+    if let Err(e) = AuditLog::from_config(&state.config.logging).record(action, &admin.username, details) {
+        tracing::warn!("Could not record {} in the audit log: {}", action, e);
+    }
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/users", tag = "users",
+    responses(
+        (status = 200, body = Page<UserAccount>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody)
+    )
+)]
+pub async fn list_users(State(state): State<AppState>, Extension(admin): Extension<User>) -> Result<Json<Page<UserAccount>>, ApiError> {
+    require_admin(&admin)?;
+    let users = state.user_manager.list_users().await.map_err(classify)?;
+    Ok(Json(Page::all(users.into_iter().map(UserAccount::from).collect())))
+}
+
+/// Add an account; its first sign-in can only change the password
+#[utoipa::path(
+    post, path = "/api/v1/users", tag = "users",
+    request_body = NewUser,
+    responses(
+        (status = 201, body = CreatedUser),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 409, description = "The username is taken", body = ErrorBody)
+    )
+)]
+pub async fn create_user(
+    State(state): State<AppState>,
+    Extension(admin): Extension<User>,
+    Json(new_user): Json<NewUser>,
+) -> Result<(StatusCode, Json<CreatedUser>), ApiError> {
+    require_admin(&admin)?;
+    let role = parse_role(&new_user.role)?;
+    let generated = new_user.password.is_none().then(temporary_password);
+    let password = new_user.password.or_else(|| generated.clone()).unwrap_or_default();
+    let user = state.user_manager.create_user(&new_user.username, &password, role, true).await.map_err(classify)?;
+    audit(&state, "user_created", &admin, json!({ "username": user.username, "role": user.role }));
+    Ok((StatusCode::CREATED, Json(CreatedUser { user: user.into(), temporary_password: generated })))
+}
+
+/// Change a user's role or disable them. The last active admin cannot be demoted or disabled.
+#[utoipa::path(
+    patch, path = "/api/v1/users/{username}", tag = "users",
+    params(("username" = String, Path)),
+    request_body = UserUpdate,
+    responses(
+        (status = 200, body = UserAccount),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody),
+        (status = 409, description = "Would leave no active admin", body = ErrorBody)
+    )
+)]
+pub async fn update_user(
+    State(state): State<AppState>,
+    Extension(admin): Extension<User>,
+    Path(username): Path<String>,
+    Json(update): Json<UserUpdate>,
+) -> Result<Json<UserAccount>, ApiError> {
+    require_admin(&admin)?;
+    let role = update.role.as_deref().map(parse_role).transpose()?;
+    let user = state.user_manager.update_user(&username, role, update.active).await.map_err(classify)?;
+    audit(&state, "user_updated", &admin, json!({ "username": user.username, "role": update.role, "active": update.active }));
+    Ok(Json(user.into()))
+}
+
+/// Set a new password the user must change, and sign out all of their sessions
+#[utoipa::path(
+    post, path = "/api/v1/users/{username}/password", tag = "users",
+    params(("username" = String, Path)),
+    request_body = PasswordReset,
+    responses(
+        (status = 200, body = CreatedUser),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Extension(admin): Extension<User>,
+    Path(username): Path<String>,
+    reset: Option<Json<PasswordReset>>,
+) -> Result<Json<CreatedUser>, ApiError> {
+    require_admin(&admin)?;
+    let Json(reset) = reset.unwrap_or_default();
+    let generated = reset.password.is_none().then(temporary_password);
+    let password = reset.password.or_else(|| generated.clone()).unwrap_or_default();
+    state.user_manager.reset_password(&username, &password).await.map_err(classify)?;
+    audit(&state, "password_reset", &admin, json!({ "username": username }));
+    let user = state.user_manager.get_user(&username).await.ok_or_else(|| api_error(StatusCode::NOT_FOUND, "No such user"))?;
+    Ok(Json(CreatedUser { user: user.into(), temporary_password: generated }))
+}
+
+/// Sign out every session of a user
+#[utoipa::path(
+    delete, path = "/api/v1/users/{username}/sessions", tag = "users",
+    params(("username" = String, Path)),
+    responses(
+        (status = 200, description = "How many sessions were revoked", body = serde_json::Value),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn revoke_user_sessions(
+    State(state): State<AppState>,
+    Extension(admin): Extension<User>,
+    Path(username): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    require_admin(&admin)?;
+    let revoked = state.user_manager.revoke_sessions(&username).await.map_err(classify)?;
+    audit(&state, "sessions_revoked", &admin, json!({ "username": username, "sessions": revoked }));
+    Ok(Json(json!({ "revoked": revoked })))
+}
+
+/// The caller's alert preferences: severities, organizations and email or Slack DM
+#[utoipa::path(
+    get, path = "/api/auth/notifications", tag = "auth",
+    responses(
+        (status = 200, body = NotificationPreferences),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody)
+    )
+)]
+pub async fn my_notification_preferences(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> Result<Json<NotificationPreferences>, ApiError> {
+    let preferences = state.user_manager.notification_preferences(&user.username).await.map_err(classify)?;
+    Ok(Json(preferences))
+}
+
+#[utoipa::path(
+    put, path = "/api/auth/notifications", tag = "auth",
+    request_body = NotificationPreferences,
+    responses(
+        (status = 200, body = NotificationPreferences),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody)
+    )
+)]
+pub async fn set_my_notification_preferences(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(preferences): Json<NotificationPreferences>,
+) -> Result<Json<NotificationPreferences>, ApiError> {
+    let saved = state.user_manager.set_notification_preferences(&user.username, preferences).await.map_err(classify)?;
+    Ok(Json(saved))
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/users/{username}/notifications", tag = "users",
+    params(("username" = String, Path)),
+    responses(
+        (status = 200, body = NotificationPreferences),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn user_notification_preferences(
+    State(state): State<AppState>,
+    Extension(admin): Extension<User>,
+    Path(username): Path<String>,
+) -> Result<Json<NotificationPreferences>, ApiError> {
+    require_admin(&admin)?;
+    let preferences = state.user_manager.notification_preferences(&username).await.map_err(classify)?;
+    Ok(Json(preferences))
+}
+
+/// Set where a user's alerts go, for example when they cannot sign in to do it themselves
+#[utoipa::path(
+    put, path = "/api/v1/users/{username}/notifications", tag = "users",
+    params(("username" = String, Path)),
+    request_body = NotificationPreferences,
+    responses(
+        (status = 200, body = NotificationPreferences),
+        (status = 400, description = "Invalid parameters", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role may not do this", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn set_user_notification_preferences(
+    State(state): State<AppState>,
+    Extension(admin): Extension<User>,
+    Path(username): Path<String>,
+    Json(preferences): Json<NotificationPreferences>,
+) -> Result<Json<NotificationPreferences>, ApiError> {
+    require_admin(&admin)?;
+    let saved = state.user_manager.set_notification_preferences(&username, preferences).await.map_err(classify)?;
+    audit(&state, "notification_preferences_updated", &admin, json!({ "username": username, "enabled": saved.enabled, "channels": saved.channels }));
+    Ok(Json(saved))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::NotificationChannel;
+
+    fn new_user(username: &str, role: &str, password: Option<&str>) -> Json<NewUser> {
+        Json(NewUser { username: username.to_string(), role: role.to_string(), password: password.map(str::to_string) })
+    }
+
+    #[tokio::test]
+    async fn test_admin_creates_lists_and_updates_users() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path());
+        let admin = state.test_user("root", Role::Admin).await;
+
+        let (status, Json(created)) = create_user(State(state.clone()), Extension(admin.clone()), new_user("alice", "analyst", None)).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created.user.role, "analyst");
+        assert!(created.user.must_change_password);
+        let generated = created.temporary_password.expect("a generated password");
+        assert!(state.user_manager.authenticate("alice", &generated).await.is_some());
+
+        let (_, Json(chosen)) =
+            create_user(State(state.clone()), Extension(admin.clone()), new_user("bob", "viewer", Some("another-long-password"))).await.unwrap();
+        assert!(chosen.temporary_password.is_none());
+
+        let Json(page) = list_users(State(state.clone()), Extension(admin.clone())).await.unwrap();
+        let mut names: Vec<_> = page.data.iter().map(|u| u.username.as_str()).collect();
+        names.sort();
+        assert_eq!(names, ["alice", "bob", "root"]);
+
+        let update = UserUpdate { role: Some("viewer".to_string()), active: Some(false) };
+        let Json(updated) = update_user(State(state.clone()), Extension(admin.clone()), Path("alice".to_string()), Json(update)).await.unwrap();
+        assert_eq!(updated.role, "viewer");
+        assert!(!updated.is_active);
+        assert!(state.user_manager.authenticate("alice", &generated).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_user_management_rejects_bad_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path());
+        let admin = state.test_user("root", Role::Admin).await;
+        let analyst = state.test_user("alice", Role::Analyst).await;
+
+        let error = list_users(State(state.clone()), Extension(analyst.clone())).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::FORBIDDEN);
+        let error = create_user(State(state.clone()), Extension(analyst), new_user("mallory", "admin", None)).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::FORBIDDEN);
+
+        let error = create_user(State(state.clone()), Extension(admin.clone()), new_user("carol", "owner", None)).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::BAD_REQUEST);
+        let error = create_user(State(state.clone()), Extension(admin.clone()), new_user("alice", "viewer", None)).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::CONFLICT);
+
+        let update = || Json(UserUpdate { role: None, active: Some(false) });
+        let error = update_user(State(state.clone()), Extension(admin.clone()), Path("nobody".to_string()), update()).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::NOT_FOUND);
+        let error = update_user(State(state.clone()), Extension(admin.clone()), Path("root".to_string()), update()).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::CONFLICT, "the last active admin stays");
+    }
+
+    #[tokio::test]
+    async fn test_reset_password_and_revoke_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path());
+        let admin = state.test_user("root", Role::Admin).await;
+        let alice = state.test_user("alice", Role::Analyst).await;
+        state.user_manager.issue_session(&alice).await.unwrap();
+        state.user_manager.issue_session(&alice).await.unwrap();
+
+        let Json(revoked) = revoke_user_sessions(State(state.clone()), Extension(admin.clone()), Path("alice".to_string())).await.unwrap();
+        assert_eq!(revoked["revoked"], 2);
+
+        let Json(reset) = reset_password(State(state.clone()), Extension(admin.clone()), Path("alice".to_string()), None).await.unwrap();
+        assert!(reset.user.must_change_password);
+        let password = reset.temporary_password.expect("a generated password");
+        assert!(state.user_manager.authenticate("alice", &password).await.is_some());
+        assert!(state.user_manager.authenticate("alice", "a-long-enough-password").await.is_none());
+
+        let error = reset_password(State(state.clone()), Extension(alice), Path("root".to_string()), None).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::FORBIDDEN);
+        let error = revoke_user_sessions(State(state.clone()), Extension(admin), Path("nobody".to_string())).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_notification_preferences() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path());
+        let admin = state.test_user("root", Role::Admin).await;
+        let alice = state.test_user("alice", Role::Analyst).await;
+
+        let Json(defaults) = my_notification_preferences(State(state.clone()), Extension(alice.clone())).await.unwrap();
+        assert!(!defaults.enabled);
+
+        let preferences = NotificationPreferences {
+            enabled: true,
+            channels: vec![NotificationChannel::Email],
+            organizations: vec!["Acme".to_string()],
+            email: Some("alice@example.com".to_string()),
+            ..Default::default()
+        };
+        let Json(saved) = set_my_notification_preferences(State(state.clone()), Extension(alice.clone()), Json(preferences)).await.unwrap();
+        assert_eq!(saved.organizations, ["acme"]);
+
+        let Json(seen) = user_notification_preferences(State(state.clone()), Extension(admin.clone()), Path("alice".to_string())).await.unwrap();
+        assert!(seen.enabled);
+        assert_eq!(seen.email.as_deref(), Some("alice@example.com"));
+
+        let slack = NotificationPreferences { enabled: true, channels: vec![NotificationChannel::Slack], ..Default::default() };
+        let error = set_user_notification_preferences(State(state.clone()), Extension(admin.clone()), Path("alice".to_string()), Json(slack))
+            .await
+            .unwrap_err();
+        assert_eq!(error.0, StatusCode::BAD_REQUEST);
+
+        let error = user_notification_preferences(State(state.clone()), Extension(alice), Path("root".to_string())).await.unwrap_err();
+        assert_eq!(error.0, StatusCode::FORBIDDEN);
+        let error = set_user_notification_preferences(
+            State(state.clone()),
+            Extension(admin),
+            Path("nobody".to_string()),
+            Json(NotificationPreferences::default()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.0, StatusCode::NOT_FOUND);
+    }
+}
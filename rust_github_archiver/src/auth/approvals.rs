@@ -0,0 +1,318 @@
+// Two-person approval: dangerous actions run only after someone other than the requester approves
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use crate::auth::roles::Role;
+use crate::auth::users::{parse_timestamp, timestamp, User, UserManager};
+use crate::core::config::SecurityConfig;
+
+/// Requests and approvals lapse this long after they were made
+pub const APPROVAL_VALID_HOURS: i64 = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalAction {
+    /// `database export`, which writes out every stored finding
+    Export,
+    /// `database erase`, which scrubs findings for good
+    Erase,
+}
+
+impl ApprovalAction {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "export" => Some(ApprovalAction::Export),
+            "erase" => Some(ApprovalAction::Erase),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalAction::Export => "export",
+            ApprovalAction::Erase => "erase",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+    /// Approved and carried out; an approval is good for one run
+    Executed,
+}
+
+impl ApprovalStatus {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "pending" => Some(ApprovalStatus::Pending),
+            "approved" => Some(ApprovalStatus::Approved),
+            "rejected" => Some(ApprovalStatus::Rejected),
+            "executed" => Some(ApprovalStatus::Executed),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalStatus::Pending => "pending",
+            ApprovalStatus::Approved => "approved",
+            ApprovalStatus::Rejected => "rejected",
+            ApprovalStatus::Executed => "executed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ApprovalRequest {
+    pub id: i64,
+    pub action: ApprovalAction,
+    /// What the action runs on, such as the database and finding hashes to erase
+    pub target: String,
+    pub reason: Option<String>,
+    pub requested_by: String,
+    pub requested_at: DateTime<Utc>,
+    pub status: ApprovalStatus,
+    /// Who approved or rejected the request
+    pub decided_by: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub executed_at: Option<DateTime<Utc>>,
+}
+
+impl ApprovalRequest {
+    /// When a pending request can no longer be approved, or an approval no longer used
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.decided_at.unwrap_or(self.requested_at) + Duration::hours(APPROVAL_VALID_HOURS)
+    }
+
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        let action: String = row.get(1)?;
+        let status: String = row.get(6)?;
+        Ok(Self {
+            id: row.get(0)?,
+            // Unknown actions come from a newer release; treat them as the strictest
+            action: ApprovalAction::parse(&action).unwrap_or(ApprovalAction::Erase),
+            target: row.get(2)?,
+            reason: row.get(3)?,
+            requested_by: row.get(4)?,
+            requested_at: parse_timestamp(row.get(5)?)?,
+            status: ApprovalStatus::parse(&status).unwrap_or(ApprovalStatus::Pending),
+            decided_by: row.get(7)?,
+            decided_at: row.get::<_, Option<String>>(8)?.map(parse_timestamp).transpose()?,
+            executed_at: row.get::<_, Option<String>>(9)?.map(parse_timestamp).transpose()?,
+        })
+    }
+}
+
+const REQUEST_COLUMNS: &str =
+    "id, action, target, reason, requested_by, requested_at, status, decided_by, decided_at, executed_at";
+
+/// Which actions need a second person, and the least role that person must have
+#[derive(Debug, Clone, Default)]
+pub struct ApprovalPolicy {
+    approvers: HashMap<ApprovalAction, Role>,
+}
+
+impl ApprovalPolicy {
+    pub fn from_config(security: &SecurityConfig) -> Result<Self> {
+        let mut approvers = HashMap::new();
+        for (action, role) in &security.approval_policy {
+            let action = ApprovalAction::parse(action)
+                .ok_or_else(|| anyhow!("Invalid approval policy: unknown action {}; use export or erase", action))?;
+            let role = Role::parse(role)
+                .ok_or_else(|| anyhow!("Invalid approval policy: unknown role {}; use admin, analyst or viewer", role))?;
+            approvers.insert(action, role);
+        }
+        Ok(Self { approvers })
+    }
+
+    pub fn requires_approval(&self, action: ApprovalAction) -> bool {
+        self.approvers.contains_key(&action)
+    }
+
+    /// The least role that may approve `action`; admins when the policy does not name one
+    pub fn approver_role(&self, action: ApprovalAction) -> Role {
+        self.approvers.get(&action).copied().unwrap_or(Role::Admin)
+    }
+}
+
+pub(super) fn initialize_approvals_schema(connection: &Connection) -> Result<()> {
+    connection.execute_batch(
+        "CREATE TABLE IF NOT EXISTS approval_requests (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            action TEXT NOT NULL,
+            target TEXT NOT NULL,
+            reason TEXT,
+            requested_by TEXT NOT NULL,
+            requested_at TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            decided_by TEXT,
+            decided_at TEXT,
+            executed_at TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_approval_requests_status ON approval_requests(status, requested_at);",
+    )?;
+    Ok(())
+}
+
+fn load_request(connection: &Connection, id: i64) -> Result<ApprovalRequest> {
+    connection
+        .query_row(&format!("SELECT {} FROM approval_requests WHERE id = ?", REQUEST_COLUMNS), params![id], ApprovalRequest::from_row)
+        .optional()?
+        .ok_or_else(|| anyhow!("No approval request {}", id))
+}
+
+impl UserManager {
+    /// File a request for a second person to approve `action` on `target`
+    pub async fn request_approval(
+        &self,
+        action: ApprovalAction,
+        target: &str,
+        requested_by: &str,
+        reason: Option<String>,
+    ) -> Result<ApprovalRequest> {
+        let (target, requested_by) = (target.to_string(), requested_by.to_string());
+        self.interact(move |connection, _| {
+            connection.execute(
+                "INSERT INTO approval_requests (action, target, reason, requested_by, requested_at) VALUES (?, ?, ?, ?, ?)",
+                params![action.as_str(), target, reason, requested_by, timestamp(Utc::now())],
+            )?;
+            load_request(connection, connection.last_insert_rowid())
+        })
+        .await
+    }
+
+    /// Requests, newest first, optionally only those with `status`
+    pub async fn approval_requests(&self, status: Option<ApprovalStatus>) -> Result<Vec<ApprovalRequest>> {
+        self.interact(move |connection, _| {
+            let mut stmt = connection.prepare(&format!(
+                "SELECT {} FROM approval_requests WHERE ?1 IS NULL OR status = ?1 ORDER BY id DESC",
+                REQUEST_COLUMNS
+            ))?;
+            let requests = stmt
+                .query_map(params![status.map(|s| s.as_str())], ApprovalRequest::from_row)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(requests)
+        })
+        .await
+    }
+
+    /// Approve or reject a pending request. The approver must not be the requester and
+    /// needs the role the policy names for the action.
+    pub async fn decide_approval(&self, id: i64, approver: &User, approve: bool, policy: &ApprovalPolicy) -> Result<ApprovalRequest> {
+        let (approver_name, approver_role) = (approver.username.clone(), approver.role_kind());
+        let policy = policy.clone();
+        self.interact(move |connection, _| {
+            let request = load_request(connection, id)?;
+            if request.status != ApprovalStatus::Pending {
+                return Err(anyhow!("Cannot decide approval request {}: it is already {}", id, request.status.as_str()));
+            }
+            if request.expires_at() < Utc::now() {
+                return Err(anyhow!("Cannot decide approval request {}: it expired at {}", id, request.expires_at().to_rfc3339()));
+            }
+            if request.requested_by.eq_ignore_ascii_case(&approver_name) {
+                return Err(anyhow!("Forbidden: a request must be approved by someone other than its requester"));
+            }
+            let needed = policy.approver_role(request.action);
+            if !approver_role.includes(needed) {
+                return Err(anyhow!("Forbidden: {} requests are approved by the {} role", request.action.as_str(), needed.as_str()));
+            }
+            let status = if approve { ApprovalStatus::Approved } else { ApprovalStatus::Rejected };
+            connection.execute(
+                "UPDATE approval_requests SET status = ?, decided_by = ?, decided_at = ? WHERE id = ?",
+                params![status.as_str(), approver_name, timestamp(Utc::now()), id],
+            )?;
+            load_request(connection, id)
+        })
+        .await
+    }
+
+    /// Use an approval to run `action` on `target`, which must be exactly what was approved.
+    /// Each approval works once.
+    pub async fn execute_approved(&self, id: i64, action: ApprovalAction, target: &str) -> Result<ApprovalRequest> {
+        let target = target.to_string();
+        self.interact(move |connection, _| {
+            let request = load_request(connection, id)?;
+            if request.status != ApprovalStatus::Approved {
+                return Err(anyhow!("Approval request {} is {}, not approved", id, request.status.as_str()));
+            }
+            if request.action != action || request.target != target {
+                return Err(anyhow!(
+                    "Approval request {} is for {} on {}, not {} on {}",
+                    id,
+                    request.action.as_str(),
+                    request.target,
+                    action.as_str(),
+                    target
+                ));
+            }
+            if request.expires_at() < Utc::now() {
+                return Err(anyhow!("The approval of request {} expired at {}", id, request.expires_at().to_rfc3339()));
+            }
+            // Conditional, so two runs racing for the same approval cannot both proceed
+            let claimed = connection.execute(
+                "UPDATE approval_requests SET status = 'executed', executed_at = ? WHERE id = ? AND status = 'approved'",
+                params![timestamp(Utc::now()), id],
+            )?;
+            if claimed == 0 {
+                return Err(anyhow!("Approval request {} has already been used", id));
+            }
+            load_request(connection, id)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> ApprovalPolicy {
+        let security = SecurityConfig {
+            admin_password: None,
+            approval_policy: HashMap::from([("erase".to_string(), "admin".to_string())]),
+            ..Default::default()
+        };
+        ApprovalPolicy::from_config(&security).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_second_person_must_approve() {
+        let users = UserManager::open(":memory:", &SecurityConfig { admin_password: None, ..Default::default() }).unwrap();
+        let alice = users.create_user("alice", "a-long-enough-password", Role::Admin, false).await.unwrap();
+        let bob = users.create_user("bob", "a-long-enough-password", Role::Admin, false).await.unwrap();
+        let carol = users.create_user("carol", "a-long-enough-password", Role::Analyst, false).await.unwrap();
+        let policy = policy();
+        assert!(policy.requires_approval(ApprovalAction::Erase));
+        assert!(!policy.requires_approval(ApprovalAction::Export));
+
+        let request = users.request_approval(ApprovalAction::Erase, "secrets.db: abc", "alice", None).await.unwrap();
+        assert!(users.decide_approval(request.id, &alice, true, &policy).await.is_err());
+        assert!(users.decide_approval(request.id, &carol, true, &policy).await.is_err());
+        assert!(users.execute_approved(request.id, ApprovalAction::Erase, "secrets.db: abc").await.is_err());
+
+        let approved = users.decide_approval(request.id, &bob, true, &policy).await.unwrap();
+        assert_eq!((approved.status, approved.decided_by.as_deref()), (ApprovalStatus::Approved, Some("bob")));
+        assert!(users.execute_approved(request.id, ApprovalAction::Erase, "secrets.db: abc,def").await.is_err());
+        let executed = users.execute_approved(request.id, ApprovalAction::Erase, "secrets.db: abc").await.unwrap();
+        assert_eq!(executed.status, ApprovalStatus::Executed);
+        assert!(users.execute_approved(request.id, ApprovalAction::Erase, "secrets.db: abc").await.is_err());
+    }
+
+    #[test]
+    fn test_policy_rejects_unknown_actions() {
+        let security = SecurityConfig {
+            admin_password: None,
+            approval_policy: HashMap::from([("revoke-everything".to_string(), "admin".to_string())]),
+            ..Default::default()
+        };
+        assert!(ApprovalPolicy::from_config(&security).is_err());
+    }
+}
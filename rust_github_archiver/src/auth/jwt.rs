@@ -11,16 +11,20 @@ use chrono::{Utc, Duration};
 pub struct Claims {
     pub sub: String, // subject (user id)
     pub exp: usize,  // expiration timestamp (seconds since epoch)
+    /// Session the token was issued for; the token dies with it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
 }
 
 
-/// Create a JWT token for a given user id, with expiration (default 24h)
-pub fn create_token(user_id: &str) -> Result<String> {
+/// Create a JWT access token for a user's session, valid for `lifetime`
+pub fn create_token(user_id: &str, session_id: &str, lifetime: Duration) -> Result<String> {
     let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "github-archive-scraper-jwt-secret-key".to_string());
-    let expiration = Utc::now() + Duration::hours(24);
+    let expiration = Utc::now() + lifetime;
     let claims = Claims {
         sub: user_id.to_string(),
         exp: expiration.timestamp() as usize,
+        sid: Some(session_id.to_string()),
     };
     encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes()))
         .map_err(|e| anyhow!("JWT encode error: {e}"))
@@ -9,11 +9,69 @@ use axum::{
 use serde_json::{json, Value};
 use std::sync::Arc;
 
-use crate::auth::{jwt, users::UserManager};
+use crate::auth::{jwt, oidc::OidcProvider, users::{User, UserManager}};
+
+/// The session a request's token was issued for. Absent for tokens from the identity
+/// provider, which are not tied to a session here.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionId(pub String);
+
+/// What the authentication middleware checks tokens against
+#[derive(Clone)]
+pub struct AuthState {
+    pub users: Arc<UserManager>,
+    /// Set when single sign-on is configured; its JWTs are then accepted as bearer tokens
+    pub oidc: Option<Arc<OidcProvider>>,
+}
+
+fn unauthorized(error: &str, message: &str) -> (StatusCode, Json<Value>) {
+    (StatusCode::UNAUTHORIZED, Json(json!({ "error": error, "message": message })))
+}
+
+/// The user a bearer token speaks for, and its session. Tokens this server signed must
+/// belong to a live session; with single sign-on, tokens the identity provider signed are
+/// validated against its keys and sign the user in as their groups allow.
+async fn authenticate_token(auth: &AuthState, token: &str) -> Result<(User, Option<String>), (StatusCode, Json<Value>)> {
+    let claims = match (jwt::verify_token(token), &auth.oidc) {
+        (Ok(claims), _) => claims,
+        (Err(_), Some(oidc)) => {
+            let (identity, role) = oidc.verify_bearer(token).await.map_err(|e| {
+                tracing::debug!("Bearer token rejected: {}", e);
+                unauthorized("Invalid token", "JWT token is invalid or expired")
+            })?;
+            let user = auth
+                .users
+                .sign_in_sso(&identity.subject, &identity.username, role)
+                .await
+                .map_err(|e| unauthorized("User not found", &e.to_string()))?;
+            return Ok((user, None));
+        }
+        (Err(_), None) => return Err(unauthorized("Invalid token", "JWT token is invalid or expired")),
+    };
+
+    // The token's session must still be live
+    let session = match claims.sid {
+        Some(sid) if auth.users.session_active(&sid, &claims.sub).await => sid,
+        _ => return Err(unauthorized("Session ended", "The session was revoked or has expired; sign in again")),
+    };
+
+    let user = auth
+        .users
+        .get_user(&claims.sub)
+        .await
+        .filter(|user| user.is_active)
+        .ok_or_else(|| unauthorized("User not found", "User associated with token not found or disabled"))?;
+    Ok((user, Some(session)))
+}
+
+/// What a user whose password must be changed may still do
+fn allowed_during_password_change(path: &str) -> bool {
+    matches!(path, "/api/auth/password" | "/api/auth/user" | "/api/auth/logout")
+}
 
 /// Authentication middleware that checks for valid JWT tokens
 pub async fn auth_middleware(
-    State(user_manager): State<Arc<UserManager>>,
+    State(auth): State<AuthState>,
     headers: HeaderMap,
     mut request: Request,
     next: Next,
@@ -46,33 +104,23 @@ pub async fn auth_middleware(
     // Extract the token
     let token = &auth_header[7..]; // Remove "Bearer " prefix
 
-    // Verify the JWT token
-    let claims = jwt::verify_token(token).map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
+    let (user, session) = authenticate_token(&auth, token).await?;
+
+    if auth.users.password_change_required(&user) && !allowed_during_password_change(request.uri().path()) {
+        return Err((
+            StatusCode::FORBIDDEN,
             Json(json!({
-                "error": "Invalid token",
-                "message": "JWT token is invalid or expired"
+                "error": "Password change required",
+                "message": "Change your password with POST /api/auth/password before continuing"
             })),
-        )
-    })?;
-
-    // Get user information
-    let user = user_manager
-        .get_user(&claims.sub)
-        .await
-        .ok_or_else(|| {
-            (
-                StatusCode::UNAUTHORIZED,
-                Json(json!({
-                    "error": "User not found",
-                    "message": "User associated with token not found"
-                })),
-            )
-        })?;
+        ));
+    }
 
     // Add user info to request extensions for use in handlers
     request.extensions_mut().insert(user);
+    if let Some(session) = session {
+        request.extensions_mut().insert(SessionId(session));
+    }
 
     // Continue to the next middleware/handler
     Ok(next.run(request).await)
@@ -80,20 +128,15 @@ pub async fn auth_middleware(
 
 /// Optional authentication middleware that doesn't fail if no token is provided
 pub async fn optional_auth_middleware(
-    State(user_manager): State<Arc<UserManager>>,
+    State(auth): State<AuthState>,
     headers: HeaderMap,
     mut request: Request,
     next: Next,
 ) -> Response {
     // Try to extract and verify token, but don't fail if it's missing
-    if let Some(auth_header) = headers.get("Authorization").and_then(|h| h.to_str().ok()) {
-        if auth_header.starts_with("Bearer ") {
-            let token = &auth_header[7..];
-            if let Ok(claims) = jwt::verify_token(token) {
-                if let Some(user) = user_manager.get_user(&claims.sub).await {
-                    request.extensions_mut().insert(user);
-                }
-            }
+    if let Some(token) = headers.get("Authorization").and_then(|h| h.to_str().ok()).and_then(|h| h.strip_prefix("Bearer ")) {
+        if let Ok((user, _)) = authenticate_token(&auth, token).await {
+            request.extensions_mut().insert(user);
         }
     }
 
@@ -1,9 +1,17 @@
 // Authentication module
+pub mod approvals;
 pub mod jwt;
+pub mod oidc;
+pub mod preferences;
+pub mod roles;
 pub mod users;
 pub mod middleware;
 
 // Re-export main types and functions
+pub use approvals::{ApprovalAction, ApprovalPolicy, ApprovalRequest, ApprovalStatus};
 pub use jwt::{create_token};
-pub use users::{User, UserManager};
-pub use middleware::{auth_middleware, optional_auth_middleware};
+pub use roles::Role;
+pub use users::{temporary_password, IssuedSession, Session, SessionPolicy, User, UserManager};
+pub use middleware::{auth_middleware, optional_auth_middleware, AuthState, SessionId};
+pub use oidc::{CompletedLogin, OidcIdentity, OidcProvider};
+pub use preferences::{AlertRecipient, NotificationChannel, NotificationPreferences};
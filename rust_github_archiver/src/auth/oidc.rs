@@ -0,0 +1,336 @@
+// Single sign-on through an OpenID Connect provider: authorization code flow with PKCE,
+// and validation of the provider's JWTs against its published keys
+use anyhow::{anyhow, Result};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::{OnceCell, RwLock};
+use tracing::{debug, info};
+
+use crate::auth::roles::Role;
+use crate::core::{build_client, offline, send_with_retry, HttpConfig, OidcConfig, RetryPolicy};
+
+const DISCOVERY_PATH: &str = "/.well-known/openid-configuration";
+
+/// How long a user has to finish signing in at the provider
+const LOGIN_TIMEOUT_MINUTES: i64 = 10;
+
+/// Least time between key set fetches when a token names an unknown key
+const JWKS_REFRESH_SECONDS: i64 = 60;
+
+/// Only asymmetric signatures: the provider's keys are public, so an HMAC "signed" with one
+/// would prove nothing
+const ALLOWED_ALGORITHMS: [Algorithm; 9] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+/// A sign-in sent to the provider and not yet back, keyed by its `state`
+struct PendingLogin {
+    verifier: String,
+    nonce: String,
+    return_to: Option<String>,
+    started_at: DateTime<Utc>,
+}
+
+/// Who the provider says signed in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OidcIdentity {
+    pub subject: String,
+    /// `preferred_username`, else `email`, else the subject
+    pub username: String,
+    pub groups: Vec<String>,
+}
+
+/// A finished sign-in: the identity, the role its groups map to, and where to send the browser
+#[derive(Debug, Clone)]
+pub struct CompletedLogin {
+    pub identity: OidcIdentity,
+    pub role: Role,
+    pub return_to: Option<String>,
+}
+
+pub struct OidcProvider {
+    config: OidcConfig,
+    role_mappings: HashMap<String, Role>,
+    default_role: Option<Role>,
+    http: Client,
+    retry_policy: RetryPolicy,
+    metadata: OnceCell<ProviderMetadata>,
+    keys: RwLock<(JwkSet, Option<DateTime<Utc>>)>,
+    pending: Mutex<HashMap<String, PendingLogin>>,
+}
+
+impl OidcProvider {
+    /// `None` when no issuer is configured. The provider is contacted at first use, not here.
+    pub fn from_config(config: &OidcConfig, http: &HttpConfig) -> Result<Option<Self>> {
+        if !config.enabled() {
+            return Ok(None);
+        }
+        let role = |name: &String| Role::parse(name).ok_or_else(|| anyhow!("Invalid OIDC role {}: use admin, analyst or viewer", name));
+        Ok(Some(Self {
+            role_mappings: config.role_mappings.iter().map(|(group, name)| Ok((group.clone(), role(name)?))).collect::<Result<_>>()?,
+            default_role: config.default_role.as_ref().map(role).transpose()?,
+            config: config.clone(),
+            http: build_client(http)?,
+            retry_policy: RetryPolicy::from_config(http),
+            metadata: OnceCell::new(),
+            keys: RwLock::new((JwkSet { keys: Vec::new() }, None)),
+            pending: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    async fn metadata(&self) -> Result<&ProviderMetadata> {
+        self.metadata
+            .get_or_try_init(|| async {
+                let issuer = self.config.issuer.as_deref().unwrap_or_default().trim_end_matches('/');
+                let url = format!("{}{}", issuer, DISCOVERY_PATH);
+                offline::policy().check_url(&url)?;
+                let response = send_with_retry(self.http.get(&url), &self.retry_policy).await?;
+                if !response.status().is_success() {
+                    return Err(anyhow!("OIDC discovery at {} failed: {}", url, response.status()));
+                }
+                let metadata: ProviderMetadata = response.json().await?;
+                // Tokens name the issuer exactly as discovery does; anything else is a misconfiguration
+                if metadata.issuer.trim_end_matches('/') != issuer {
+                    return Err(anyhow!("OIDC discovery names issuer {}, expected {}", metadata.issuer, issuer));
+                }
+                info!("Using OIDC provider {}", metadata.issuer);
+                Ok(metadata)
+            })
+            .await
+    }
+
+    /// The provider's sign-in page for a new login. `return_to` is where the callback sends
+    /// the browser afterwards; it must be a path on this server.
+    pub async fn authorization_url(&self, return_to: Option<String>) -> Result<String> {
+        if let Some(path) = &return_to {
+            if !path.starts_with('/') || path.starts_with("//") || path.contains('\\') {
+                return Err(anyhow!("Invalid return_to: use a path on this server"));
+            }
+        }
+        let metadata = self.metadata().await?;
+        let (state, nonce, verifier) = (random_string(), random_string(), random_string());
+        let challenge = pkce_challenge(&verifier);
+
+        let scope = self.config.scopes.join(" ");
+        let url = Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            [
+                ("response_type", "code"),
+                ("client_id", self.config.client_id.as_str()),
+                ("redirect_uri", self.config.redirect_uri.as_str()),
+                ("scope", scope.as_str()),
+                ("state", state.as_str()),
+                ("nonce", nonce.as_str()),
+                ("code_challenge", challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )?;
+
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let cutoff = Utc::now() - Duration::minutes(LOGIN_TIMEOUT_MINUTES);
+        pending.retain(|_, login| login.started_at > cutoff);
+        pending.insert(state, PendingLogin { verifier, nonce, return_to, started_at: Utc::now() });
+        Ok(url.to_string())
+    }
+
+    /// Finish a login at the callback: redeem the code with its PKCE verifier, validate the
+    /// ID token and map the user's groups to a role
+    pub async fn complete_login(&self, code: &str, state: &str) -> Result<CompletedLogin> {
+        let login = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(state)
+            .filter(|login| Utc::now() - login.started_at <= Duration::minutes(LOGIN_TIMEOUT_MINUTES))
+            .ok_or_else(|| anyhow!("Invalid or expired sign-in; start again"))?;
+
+        let metadata = self.metadata().await?;
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", login.verifier.as_str()),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+        let response = send_with_retry(self.http.post(&metadata.token_endpoint).form(&form), &self.retry_policy).await?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(anyhow!(
+                "The identity provider refused the sign-in: {}",
+                body["error_description"].as_str().or(body["error"].as_str()).unwrap_or(status.as_str())
+            ));
+        }
+        let id_token = body["id_token"].as_str().ok_or_else(|| anyhow!("The identity provider returned no ID token"))?;
+
+        let claims = self.verify(id_token, &self.config.client_id).await?;
+        if claims.get("nonce").and_then(Value::as_str) != Some(login.nonce.as_str()) {
+            return Err(anyhow!("ID token nonce does not match the sign-in"));
+        }
+        let identity = self.identity(&claims)?;
+        let role = self.role_for(&identity.groups)?;
+        Ok(CompletedLogin { identity, role, return_to: login.return_to })
+    }
+
+    /// Validate a token the provider issued for this API, as sent in `Authorization: Bearer`
+    pub async fn verify_bearer(&self, token: &str) -> Result<(OidcIdentity, Role)> {
+        let audience = self.config.audience.as_deref().unwrap_or(&self.config.client_id);
+        let claims = self.verify(token, audience).await?;
+        let identity = self.identity(&claims)?;
+        let role = self.role_for(&identity.groups)?;
+        Ok((identity, role))
+    }
+
+    /// Check signature, issuer, audience and expiry, and return the claims
+    async fn verify(&self, token: &str, audience: &str) -> Result<Map<String, Value>> {
+        let header = decode_header(token)?;
+        if !ALLOWED_ALGORITHMS.contains(&header.alg) {
+            return Err(anyhow!("Unsupported token algorithm {:?}", header.alg));
+        }
+        let kid = header.kid.ok_or_else(|| anyhow!("Token names no signing key"))?;
+        let key = self.signing_key(&kid).await?;
+
+        let metadata = self.metadata().await?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[audience]);
+        Ok(decode::<Map<String, Value>>(token, &key, &validation)?.claims)
+    }
+
+    /// The provider's key `kid`, refetching the key set when the provider has rotated keys
+    async fn signing_key(&self, kid: &str) -> Result<DecodingKey> {
+        {
+            let keys = self.keys.read().await;
+            if let Some(jwk) = keys.0.find(kid) {
+                return Ok(DecodingKey::from_jwk(jwk)?);
+            }
+            if keys.1.map_or(false, |fetched| Utc::now() - fetched < Duration::seconds(JWKS_REFRESH_SECONDS)) {
+                return Err(anyhow!("Unknown signing key {}", kid));
+            }
+        }
+
+        let mut keys = self.keys.write().await;
+        let jwks_uri = &self.metadata().await?.jwks_uri;
+        offline::policy().check_url(jwks_uri)?;
+        let response = send_with_retry(self.http.get(jwks_uri), &self.retry_policy).await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Fetching the OIDC key set failed: {}", response.status()));
+        }
+        *keys = (response.json().await?, Some(Utc::now()));
+        debug!("Fetched {} OIDC signing keys", keys.0.keys.len());
+        let jwk = keys.0.find(kid).ok_or_else(|| anyhow!("Unknown signing key {}", kid))?;
+        Ok(DecodingKey::from_jwk(jwk)?)
+    }
+
+    fn identity(&self, claims: &Map<String, Value>) -> Result<OidcIdentity> {
+        let claim = |name: &str| claims.get(name).and_then(Value::as_str).filter(|v| !v.is_empty());
+        let subject = claim("sub").ok_or_else(|| anyhow!("Token has no subject"))?;
+        // Azure AD sends a single group as a string in some configurations
+        let groups = match claims.get(&self.config.groups_claim) {
+            Some(Value::Array(groups)) => groups.iter().filter_map(Value::as_str).map(str::to_string).collect(),
+            Some(Value::String(group)) => vec![group.clone()],
+            _ => Vec::new(),
+        };
+        Ok(OidcIdentity {
+            subject: subject.to_string(),
+            username: claim("preferred_username").or(claim("email")).unwrap_or(subject).to_string(),
+            groups,
+        })
+    }
+
+    /// The most privileged role any of `groups` maps to, else the default role
+    pub fn role_for(&self, groups: &[String]) -> Result<Role> {
+        groups
+            .iter()
+            .filter_map(|group| self.role_mappings.get(group).copied())
+            .max_by_key(Role::privilege)
+            .or(self.default_role)
+            .ok_or_else(|| anyhow!("None of your groups may use this server"))
+    }
+}
+
+/// 32 random bytes, base64url encoded: 43 characters, as PKCE asks of verifiers
+fn random_string() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(mappings: &[(&str, &str)], default_role: Option<&str>) -> OidcProvider {
+        let config = OidcConfig {
+            issuer: Some("https://idp.example.com".to_string()),
+            client_id: "archiver".to_string(),
+            redirect_uri: "https://archiver.example.com/api/auth/oidc/callback".to_string(),
+            scopes: vec!["openid".to_string()],
+            groups_claim: "groups".to_string(),
+            role_mappings: mappings.iter().map(|(g, r)| (g.to_string(), r.to_string())).collect(),
+            default_role: default_role.map(str::to_string),
+            ..Default::default()
+        };
+        OidcProvider::from_config(&config, &HttpConfig::default()).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_pkce_challenge() {
+        // RFC 7636, appendix B
+        assert_eq!(pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"), "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+        assert_eq!(random_string().len(), 43);
+    }
+
+    #[test]
+    fn test_groups_map_to_the_highest_role() {
+        let oidc = provider(&[("secops", "analyst"), ("secops-admins", "admin")], None);
+        let groups = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(oidc.role_for(&groups(&["everyone", "secops", "secops-admins"])).unwrap(), Role::Admin);
+        assert_eq!(oidc.role_for(&groups(&["secops"])).unwrap(), Role::Analyst);
+        assert!(oidc.role_for(&groups(&["everyone"])).is_err());
+        assert_eq!(provider(&[], Some("viewer")).role_for(&[]).unwrap(), Role::Viewer);
+        assert!(OidcProvider::from_config(&OidcConfig::default(), &HttpConfig::default()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_identity_from_claims() {
+        let oidc = provider(&[], Some("viewer"));
+        let claims = serde_json::json!({ "sub": "00u1", "email": "carol@example.com", "groups": "secops" });
+        let identity = oidc.identity(claims.as_object().unwrap()).unwrap();
+        assert_eq!(identity.username, "carol@example.com");
+        assert_eq!(identity.groups, vec!["secops".to_string()]);
+        assert!(oidc.identity(serde_json::json!({ "email": "x@example.com" }).as_object().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,256 @@
+// Per-user alert preferences: which findings a user hears about, and how
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use utoipa::ToSchema;
+
+use crate::auth::users::UserManager;
+use crate::auth::Role;
+use crate::core::config::SecurityConfig;
+use crate::performance::normalize_organization;
+use crate::realtime::RealTimeSecretAlert;
+use crate::secrets::SecretSeverity;
+use crate::sinks::severity_rank;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationChannel {
+    Email,
+    /// A direct message to `slack_user_id`
+    Slack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct NotificationPreferences {
+    pub enabled: bool,
+    pub channels: Vec<NotificationChannel>,
+    /// Alerts with no finding at least this severe are skipped
+    pub min_severity: SecretSeverity,
+    /// Repository owners to hear about; empty means every organization
+    pub organizations: Vec<String>,
+    pub email: Option<String>,
+    /// Slack member ID, such as `U024BE7LH`
+    pub slack_user_id: Option<String>,
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            channels: Vec::new(),
+            min_severity: SecretSeverity::High,
+            organizations: Vec::new(),
+            email: None,
+            slack_user_id: None,
+        }
+    }
+}
+
+impl NotificationPreferences {
+    /// Check the addresses the chosen channels need and normalize organization names
+    pub fn validated(mut self) -> Result<Self> {
+        self.channels.sort();
+        self.channels.dedup();
+        if self.channels.contains(&NotificationChannel::Email)
+            && !self.email.as_deref().map_or(false, |email| email.contains('@') && !email.contains(char::is_whitespace))
+        {
+            return Err(anyhow!("Invalid preferences: the email channel needs an email address"));
+        }
+        if self.channels.contains(&NotificationChannel::Slack)
+            && !self.slack_user_id.as_deref().map_or(false, |id| {
+                id.len() >= 9 && id.starts_with(['U', 'W']) && id.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            })
+        {
+            return Err(anyhow!("Invalid preferences: the Slack channel needs a member ID such as U024BE7LH"));
+        }
+        if self.enabled && self.channels.is_empty() {
+            return Err(anyhow!("Invalid preferences: choose email, Slack or both"));
+        }
+        self.organizations = self
+            .organizations
+            .iter()
+            .map(|name| normalize_organization(name).map_err(|e| anyhow!("Invalid preferences: {}", e)))
+            .collect::<Result<_>>()?;
+        self.organizations.sort();
+        self.organizations.dedup();
+        Ok(self)
+    }
+
+    /// Whether this user should be told about `alert`
+    pub fn wants(&self, alert: &RealTimeSecretAlert) -> bool {
+        let owner = alert.repository.split('/').next().unwrap_or_default();
+        self.enabled
+            && alert.secrets_found.iter().any(|s| severity_rank(&s.severity) >= severity_rank(&self.min_severity))
+            && (self.organizations.is_empty() || self.organizations.iter().any(|org| org.eq_ignore_ascii_case(owner)))
+    }
+}
+
+/// A user whose preferences route alerts to them
+#[derive(Debug, Clone)]
+pub struct AlertRecipient {
+    pub username: String,
+    /// Decides how much of each secret the user's alerts show
+    pub role: Role,
+    pub preferences: NotificationPreferences,
+}
+
+pub(super) fn initialize_preferences_schema(connection: &Connection) -> Result<()> {
+    connection.execute_batch(
+        "CREATE TABLE IF NOT EXISTS notification_preferences (
+            user_id TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+            preferences TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );",
+    )?;
+    Ok(())
+}
+
+impl UserManager {
+    /// The user store for alert routing; `None` until the API has created one, so the
+    /// hunter does not leave an empty users database behind
+    pub fn open_for_alerts(security: &SecurityConfig) -> Result<Option<Self>> {
+        if !Path::new(&security.users_database).exists() {
+            return Ok(None);
+        }
+        let security = SecurityConfig { admin_password: None, ..security.clone() };
+        Self::open(&security.users_database, &security).map(Some)
+    }
+
+    /// A user's preferences; the defaults (nothing sent) when they have never saved any
+    pub async fn notification_preferences(&self, username: &str) -> Result<NotificationPreferences> {
+        let username = username.to_string();
+        self.interact(move |connection, _| {
+            let stored: Option<String> = connection
+                .query_row(
+                    "SELECT p.preferences FROM notification_preferences p JOIN users u ON u.id = p.user_id
+                    WHERE u.username = ?",
+                    params![username],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            match stored {
+                Some(json) => Ok(serde_json::from_str(&json)?),
+                None if find_user_id(connection, &username)?.is_some() => Ok(NotificationPreferences::default()),
+                None => Err(anyhow!("No user {}", username)),
+            }
+        })
+        .await
+    }
+
+    pub async fn set_notification_preferences(&self, username: &str, preferences: NotificationPreferences) -> Result<NotificationPreferences> {
+        let preferences = preferences.validated()?;
+        let username = username.to_string();
+        self.interact(move |connection, _| {
+            let user_id = find_user_id(connection, &username)?.ok_or_else(|| anyhow!("No user {}", username))?;
+            connection.execute(
+                "INSERT INTO notification_preferences (user_id, preferences, updated_at) VALUES (?, ?, ?)
+                ON CONFLICT(user_id) DO UPDATE SET preferences = excluded.preferences, updated_at = excluded.updated_at",
+                params![user_id, serde_json::to_string(&preferences)?, Utc::now().to_rfc3339()],
+            )?;
+            Ok(preferences)
+        })
+        .await
+    }
+
+    /// Active users who have alerts turned on
+    pub async fn alert_recipients(&self) -> Result<Vec<AlertRecipient>> {
+        self.interact(|connection, _| {
+            let mut stmt = connection.prepare(
+                "SELECT u.username, u.role, p.preferences FROM notification_preferences p JOIN users u ON u.id = p.user_id
+                WHERE u.is_active ORDER BY u.username",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+            })?;
+            let mut recipients = Vec::new();
+            for row in rows {
+                let (username, role, json) = row?;
+                let preferences: NotificationPreferences = serde_json::from_str(&json)?;
+                if preferences.enabled {
+                    recipients.push(AlertRecipient { username, role: Role::from_name(&role), preferences });
+                }
+            }
+            Ok(recipients)
+        })
+        .await
+    }
+}
+
+fn find_user_id(connection: &Connection, username: &str) -> Result<Option<String>> {
+    Ok(connection
+        .query_row("SELECT id FROM users WHERE username = ?", params![username], |row| row.get(0))
+        .optional()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::realtime::{AlertSeverity, RealTimeSecretMatch};
+
+    fn alert(repository: &str, severity: SecretSeverity) -> RealTimeSecretAlert {
+        RealTimeSecretAlert {
+            event_id: "1".to_string(),
+            repository: repository.to_string(),
+            commit_sha: "abc".to_string(),
+            secrets_found: vec![RealTimeSecretMatch {
+                detector_name: "GitHub Token".to_string(),
+                matched_text: "ghp_x".to_string(),
+                hash: "hmac-sha256:preferences".to_string(),
+                line_number: None,
+                filename: ".env".to_string(),
+                severity,
+            }],
+            alert_severity: AlertSeverity::High,
+            detection_time: Utc::now(),
+            triage_result: None,
+            spread: None,
+        }
+    }
+
+    #[test]
+    fn test_preferences_filter_alerts() {
+        let preferences = NotificationPreferences {
+            enabled: true,
+            channels: vec![NotificationChannel::Email],
+            email: Some("carol@example.com".to_string()),
+            organizations: vec!["Acme".to_string()],
+            ..Default::default()
+        }
+        .validated()
+        .unwrap();
+        assert_eq!(preferences.organizations, vec!["acme".to_string()]);
+        assert!(preferences.wants(&alert("ACME/api", SecretSeverity::Critical)));
+        assert!(!preferences.wants(&alert("acme/api", SecretSeverity::Medium)));
+        assert!(!preferences.wants(&alert("other/api", SecretSeverity::Critical)));
+
+        let slack_without_id = NotificationPreferences { enabled: true, channels: vec![NotificationChannel::Slack], ..Default::default() };
+        assert!(slack_without_id.validated().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_preferences_are_stored_per_user() {
+        let users = UserManager::open(":memory:", &SecurityConfig { admin_password: None, ..Default::default() }).unwrap();
+        users.create_user("carol", "a-long-enough-password", Role::Analyst, false).await.unwrap();
+        assert!(!users.notification_preferences("carol").await.unwrap().enabled);
+        assert!(users.notification_preferences("nobody").await.is_err());
+
+        let slack = NotificationPreferences {
+            enabled: true,
+            channels: vec![NotificationChannel::Slack],
+            slack_user_id: Some("U024BE7LH".to_string()),
+            min_severity: SecretSeverity::Critical,
+            ..Default::default()
+        };
+        users.set_notification_preferences("carol", slack).await.unwrap();
+        let recipients = users.alert_recipients().await.unwrap();
+        assert_eq!(recipients.len(), 1);
+        assert_eq!(recipients[0].preferences.slack_user_id.as_deref(), Some("U024BE7LH"));
+        assert_eq!(recipients[0].role, Role::Analyst);
+
+        users.update_user("carol", None, Some(false)).await.unwrap();
+        assert!(users.alert_recipients().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+// Role definitions for role-based access control
+use serde::{Deserialize, Serialize};
+
+use crate::auth::users::User;
+use crate::sinks::RedactionProfile;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Admin,
+    Analyst,
+    Viewer,
+}
+
+impl Role {
+    /// Parse a stored role name. Unknown roles get the least privilege.
+    pub fn from_name(name: &str) -> Self {
+        Self::parse(name).unwrap_or(Role::Viewer)
+    }
+
+    /// Parse a role given by an admin; unknown names are refused rather than downgraded
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "admin" => Some(Role::Admin),
+            "analyst" => Some(Role::Analyst),
+            "viewer" => Some(Role::Viewer),
+            _ => None,
+        }
+    }
+
+    /// Analysts and admins may write notes and upload evidence; viewers only read
+    pub fn can_annotate(&self) -> bool {
+        matches!(self, Role::Admin | Role::Analyst)
+    }
+
+    /// Only admins change what the hunter monitors
+    pub fn can_manage_monitoring(&self) -> bool {
+        matches!(self, Role::Admin)
+    }
+
+    /// Only admins add users, change roles and revoke other users' sessions
+    pub fn can_manage_users(&self) -> bool {
+        matches!(self, Role::Admin)
+    }
+
+    /// Admins outrank analysts, who outrank viewers
+    pub fn privilege(&self) -> u8 {
+        match self {
+            Role::Admin => 2,
+            Role::Analyst => 1,
+            Role::Viewer => 0,
+        }
+    }
+
+    /// Whether this role may do everything `other` may
+    pub fn includes(&self, other: Role) -> bool {
+        self.privilege() >= other.privilege()
+    }
+
+    /// How much of a secret this role may see: full values for admins, masked for analysts,
+    /// hashes for viewers. `floor` is the scanner-wide policy, which a role can only tighten.
+    pub fn redaction(&self, floor: RedactionProfile) -> RedactionProfile {
+        RedactionProfile::for_role(*self).at_least(floor)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Analyst => "analyst",
+            Role::Viewer => "viewer",
+        }
+    }
+}
+
+impl User {
+    /// Typed view of the user's role
+    pub fn role_kind(&self) -> Role {
+        Role::from_name(&self.role)
+    }
+
+    /// Redaction for secrets shown or sent to this user, decided by their own role
+    pub fn redaction(&self, floor: RedactionProfile) -> RedactionProfile {
+        self.role_kind().redaction(floor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redaction_follows_role() {
+        assert_eq!(Role::Admin.redaction(RedactionProfile::Full), RedactionProfile::Full);
+        assert_eq!(Role::Analyst.redaction(RedactionProfile::Full), RedactionProfile::PartialMask);
+        assert_eq!(Role::Viewer.redaction(RedactionProfile::Full), RedactionProfile::HashOnly);
+    }
+
+    #[test]
+    fn test_redaction_floor_is_never_loosened() {
+        assert_eq!(Role::Admin.redaction(RedactionProfile::PartialMask), RedactionProfile::PartialMask);
+        assert_eq!(Role::Admin.redaction(RedactionProfile::HashOnly), RedactionProfile::HashOnly);
+        assert_eq!(Role::Viewer.redaction(RedactionProfile::PartialMask), RedactionProfile::HashOnly);
+    }
+
+    fn user(role: &str) -> User {
+        let now = chrono::Utc::now();
+        User {
+            id: "1".to_string(),
+            username: "carol".to_string(),
+            password_hash: String::new(),
+            role: role.to_string(),
+            created_at: now,
+            last_login: None,
+            is_active: true,
+            must_change_password: false,
+            password_changed_at: now,
+            sso_subject: None,
+        }
+    }
+
+    #[test]
+    fn test_user_redaction_uses_stored_role() {
+        assert_eq!(user("Admin").redaction(RedactionProfile::Full), RedactionProfile::Full);
+        assert_eq!(user("analyst").redaction(RedactionProfile::Full), RedactionProfile::PartialMask);
+        // Unknown roles get the least privilege
+        assert_eq!(user("auditor").redaction(RedactionProfile::Full), RedactionProfile::HashOnly);
+    }
+}
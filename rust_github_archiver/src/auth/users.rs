@@ -1,80 +1,549 @@
-// User management implementation
-use anyhow::Result;
+// User management implementation: accounts and sessions in a SQLite user store
+use anyhow::{anyhow, Result};
 use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
-use argon2::password_hash::{rand_core::OsRng, SaltString};
+use argon2::password_hash::{rand_core::{OsRng, RngCore}, SaltString};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::auth::jwt;
+use crate::auth::roles::Role;
+use crate::core::config::SecurityConfig;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: String,
     pub username: String,
+    #[serde(skip_serializing, default)]
     pub password_hash: String,
     pub role: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub last_login: Option<chrono::DateTime<chrono::Utc>>,
     pub is_active: bool,
+    /// Set for passwords handed out by an admin; cleared when the user picks their own
+    pub must_change_password: bool,
+    pub password_changed_at: chrono::DateTime<chrono::Utc>,
+    /// Subject at the identity provider; single sign-on accounts have no password
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sso_subject: Option<String>,
+}
+
+/// A sign-in that can be refreshed until it expires or is revoked
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Session {
+    pub id: String,
+    pub username: String,
+    pub created_at: DateTime<Utc>,
+    /// Last sign-in or refresh
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Tokens handed out at sign-in and on every refresh. The refresh token is only shown here;
+/// the store keeps its hash.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct IssuedSession {
+    pub session_id: String,
+    pub access_token: String,
+    pub access_expires_at: DateTime<Utc>,
+    pub refresh_token: String,
+    pub session_expires_at: DateTime<Utc>,
+}
+
+/// Token lifetimes and password rules, from `SecurityConfig`
+#[derive(Debug, Clone)]
+pub struct SessionPolicy {
+    pub access_token_lifetime: Duration,
+    pub session_lifetime: Duration,
+    /// `None` never expires passwords
+    pub password_max_age: Option<Duration>,
+    pub min_password_length: usize,
+}
+
+impl SessionPolicy {
+    pub fn from_config(security: &SecurityConfig) -> Self {
+        Self {
+            access_token_lifetime: Duration::minutes(security.access_token_minutes.max(1) as i64),
+            session_lifetime: Duration::hours(security.session_duration_hours.max(1) as i64),
+            password_max_age: (security.password_max_age_days > 0).then(|| Duration::days(security.password_max_age_days as i64)),
+            min_password_length: security.min_password_length,
+        }
+    }
+}
+
+impl Default for SessionPolicy {
+    fn default() -> Self {
+        Self::from_config(&SecurityConfig::default())
+    }
+}
+
+const USER_COLUMNS: &str =
+    "id, username, password_hash, role, created_at, last_login, is_active, must_change_password, password_changed_at, sso_subject";
+
+/// Stored in place of a hash for single sign-on accounts, so password sign-in always fails
+const NO_PASSWORD: &str = "!";
+
+pub(super) fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+pub(super) fn parse_timestamp(value: String) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&value)
+        .map(|at| at.with_timezone(&Utc))
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+impl User {
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            username: row.get(1)?,
+            password_hash: row.get(2)?,
+            role: row.get(3)?,
+            created_at: parse_timestamp(row.get(4)?)?,
+            last_login: row.get::<_, Option<String>>(5)?.map(parse_timestamp).transpose()?,
+            is_active: row.get(6)?,
+            must_change_password: row.get(7)?,
+            password_changed_at: parse_timestamp(row.get(8)?)?,
+            sso_subject: row.get(9)?,
+        })
+    }
+}
+
+fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// A password for accounts an admin creates or resets; the user must replace it on first sign-in
+pub fn temporary_password() -> String {
+    random_token()[..20].to_string()
 }
 
 pub struct UserManager {
-    users: Arc<RwLock<HashMap<String, User>>>,
+    connection: Arc<Mutex<Connection>>,
+    policy: SessionPolicy,
 }
 
 impl UserManager {
-    pub fn new() -> Self {
-        let mut users = HashMap::new();
-        
-        // Create default admin user
-        let admin_password = std::env::var("ADMIN_PASSWORD").unwrap_or_else(|_| "admin123".to_string());
-        if let Ok(admin_user) = Self::create_user("admin", &admin_password, "admin") {
-            users.insert("admin".to_string(), admin_user);
+    /// Open the user store, creating it if needed. An empty store gets an `admin` account
+    /// when `security.admin_password` is set.
+    pub fn open(path: &str, security: &SecurityConfig) -> Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(
+            "PRAGMA foreign_keys = ON;
+            CREATE TABLE IF NOT EXISTS users (
+                id TEXT PRIMARY KEY,
+                username TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                password_hash TEXT NOT NULL,
+                role TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                last_login TEXT,
+                is_active BOOLEAN NOT NULL DEFAULT TRUE,
+                must_change_password BOOLEAN NOT NULL DEFAULT FALSE,
+                password_changed_at TEXT NOT NULL,
+                sso_subject TEXT
+            );
+            CREATE TABLE IF NOT EXISTS sessions (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                refresh_token_hash TEXT NOT NULL UNIQUE,
+                created_at TEXT NOT NULL,
+                last_used_at TEXT NOT NULL,
+                expires_at TEXT NOT NULL,
+                revoked_at TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_sessions_user ON sessions(user_id);",
+        )?;
+        // Stores created before single sign-on lack the column
+        let has_sso_subject = connection
+            .prepare("SELECT 1 FROM pragma_table_info('users') WHERE name = 'sso_subject'")?
+            .exists([])?;
+        if !has_sso_subject {
+            connection.execute_batch("ALTER TABLE users ADD COLUMN sso_subject TEXT")?;
         }
-        
-        Self {
-            users: Arc::new(RwLock::new(users)),
+        connection.execute_batch("CREATE UNIQUE INDEX IF NOT EXISTS idx_users_sso_subject ON users(sso_subject)")?;
+        super::preferences::initialize_preferences_schema(&connection)?;
+        super::approvals::initialize_approvals_schema(&connection)?;
+
+        let manager = Self { connection: Arc::new(Mutex::new(connection)), policy: SessionPolicy::from_config(security) };
+        let users: i64 = manager.lock().query_row("SELECT COUNT(*) FROM users", [], |row| row.get(0))?;
+        if users == 0 {
+            match &security.admin_password {
+                Some(password) => {
+                    // A password too short to set through the API can only be used to replace itself
+                    let weak = manager.check_password_rules(password).is_err();
+                    manager.insert_user("admin", password, Role::Admin, weak)?;
+                    info!("Created the admin account from ADMIN_PASSWORD");
+                }
+                None => warn!("No API users in {}; add one with `admin users add <name> --role admin`", path),
+            }
         }
+        Ok(manager)
+    }
+
+    pub fn policy(&self) -> &SessionPolicy {
+        &self.policy
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.connection.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Run `f` on the store off the async runtime
+    pub(super) async fn interact<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection, &SessionPolicy) -> Result<T> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        let policy = self.policy.clone();
+        tokio::task::spawn_blocking(move || {
+            let connection = connection.lock().unwrap_or_else(|e| e.into_inner());
+            f(&connection, &policy)
+        })
+        .await?
     }
 
     /// Authenticate a user with username and password
     pub async fn authenticate(&self, username: &str, password: &str) -> Option<User> {
-        let users = self.users.read().await;
-        if let Some(user) = users.get(username) {
-            if user.is_active && self.verify_password(password, &user.password_hash).unwrap_or(false) {
-                return Some(user.clone());
-            }
+        let user = self.get_user(username).await?;
+        if user.is_active && Self::verify_password(password, &user.password_hash).unwrap_or(false) {
+            return Some(user);
         }
         None
     }
 
     /// Get user by username
     pub async fn get_user(&self, username: &str) -> Option<User> {
-        let users = self.users.read().await;
-        users.get(username).cloned()
+        let username = username.to_string();
+        self.interact(move |connection, _| find_user(connection, &username))
+            .await
+            .unwrap_or_else(|e| {
+                warn!("User lookup failed: {}", e);
+                None
+            })
+    }
+
+    pub async fn list_users(&self) -> Result<Vec<User>> {
+        self.interact(|connection, _| {
+            let mut stmt = connection.prepare(&format!("SELECT {} FROM users ORDER BY username", USER_COLUMNS))?;
+            let rows = stmt.query_map([], User::from_row)?;
+            Ok(rows.collect::<rusqlite::Result<_>>()?)
+        })
+        .await
     }
 
     /// Update user's last login time
     pub async fn update_last_login(&self, username: &str) -> Result<()> {
-        let mut users = self.users.write().await;
-        if let Some(user) = users.get_mut(username) {
-            user.last_login = Some(chrono::Utc::now());
+        let username = username.to_string();
+        self.interact(move |connection, _| {
+            connection.execute(
+                "UPDATE users SET last_login = ? WHERE username = ?",
+                params![timestamp(Utc::now()), username],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Add an account. With `must_change_password`, the user can do nothing but change it.
+    pub async fn create_user(&self, username: &str, password: &str, role: Role, must_change_password: bool) -> Result<User> {
+        let username = username.trim();
+        if username.is_empty() || username.len() > 64 || !username.chars().all(|c| c.is_ascii_alphanumeric() || "-_.@".contains(c)) {
+            return Err(anyhow!("Invalid username: use letters, digits and - _ . @ (at most 64)"));
         }
-        Ok(())
+        if self.get_user(username).await.is_some() {
+            return Err(anyhow!("User {} already exists", username));
+        }
+        self.check_password_rules(password)?;
+        self.insert_user(username, password, role, must_change_password)
     }
 
-    /// Create a new user with hashed password
-    fn create_user(username: &str, password: &str, role: &str) -> Result<User> {
-        let password_hash = Self::hash_password(password)?;
-        Ok(User {
+    fn insert_user(&self, username: &str, password: &str, role: Role, must_change_password: bool) -> Result<User> {
+        let now = Utc::now();
+        let user = User {
             id: uuid::Uuid::new_v4().to_string(),
             username: username.to_string(),
-            password_hash,
-            role: role.to_string(),
-            created_at: chrono::Utc::now(),
+            password_hash: Self::hash_password(password)?,
+            role: role.as_str().to_string(),
+            created_at: now,
             last_login: None,
             is_active: true,
+            must_change_password,
+            password_changed_at: now,
+            sso_subject: None,
+        };
+        self.lock().execute(
+            &format!("INSERT INTO users ({}) VALUES (?, ?, ?, ?, ?, NULL, TRUE, ?, ?, NULL)", USER_COLUMNS),
+            params![user.id, user.username, user.password_hash, user.role, timestamp(now), must_change_password, timestamp(now)],
+        )?;
+        Ok(user)
+    }
+
+    /// Sign in a user the identity provider vouched for, adding their account on first sign-in.
+    /// The provider's groups decide the role every time; a disabled account stays disabled.
+    pub async fn sign_in_sso(&self, subject: &str, username: &str, role: Role) -> Result<User> {
+        let (subject, username) = (subject.to_string(), username.to_string());
+        self.interact(move |connection, _| {
+            let existing = connection
+                .query_row(
+                    &format!("SELECT {} FROM users WHERE sso_subject = ?", USER_COLUMNS),
+                    params![subject],
+                    User::from_row,
+                )
+                .optional()?;
+            let now = timestamp(Utc::now());
+            let id = match existing {
+                Some(user) if !user.is_active => return Err(anyhow!("Cannot sign in: the account {} is disabled", user.username)),
+                Some(user) => {
+                    connection.execute("UPDATE users SET role = ? WHERE id = ?", params![role.as_str(), user.id])?;
+                    user.id
+                }
+                None => {
+                    if find_user(connection, &username)?.is_some() {
+                        return Err(anyhow!("Cannot sign in as {}: a local account has that name", username));
+                    }
+                    let id = uuid::Uuid::new_v4().to_string();
+                    connection.execute(
+                        &format!("INSERT INTO users ({}) VALUES (?, ?, ?, ?, ?, NULL, TRUE, FALSE, ?, ?)", USER_COLUMNS),
+                        params![id, username, NO_PASSWORD, role.as_str(), now, now, subject],
+                    )?;
+                    info!("Added single sign-on account {} ({})", username, role.as_str());
+                    id
+                }
+            };
+            connection.execute("UPDATE users SET last_login = ? WHERE id = ?", params![now, id])?;
+            Ok(connection.query_row(&format!("SELECT {} FROM users WHERE id = ?", USER_COLUMNS), params![id], User::from_row)?)
+        })
+        .await
+    }
+
+    /// Change a user's role or enable/disable them. Disabling revokes their sessions. The last
+    /// active admin cannot be demoted or disabled.
+    pub async fn update_user(&self, username: &str, role: Option<Role>, active: Option<bool>) -> Result<User> {
+        let username = username.to_string();
+        self.interact(move |connection, _| {
+            let user = find_user(connection, &username)?.ok_or_else(|| anyhow!("No user {}", username))?;
+            let stays_admin = role.map_or(user.role_kind() == Role::Admin, |role| role == Role::Admin) && active.unwrap_or(user.is_active);
+            if user.role_kind() == Role::Admin && user.is_active && !stays_admin {
+                let admins: i64 = connection.query_row(
+                    "SELECT COUNT(*) FROM users WHERE role = 'admin' AND is_active",
+                    [],
+                    |row| row.get(0),
+                )?;
+                if admins <= 1 {
+                    return Err(anyhow!("Cannot demote or disable the last active admin"));
+                }
+            }
+
+            let tx = connection.unchecked_transaction()?;
+            if let Some(role) = role {
+                tx.execute("UPDATE users SET role = ? WHERE id = ?", params![role.as_str(), user.id])?;
+            }
+            if let Some(active) = active {
+                tx.execute("UPDATE users SET is_active = ? WHERE id = ?", params![active, user.id])?;
+                if !active {
+                    revoke_user_sessions(&tx, &user.id, None)?;
+                }
+            }
+            tx.commit()?;
+            find_user(connection, &username)?.ok_or_else(|| anyhow!("No user {}", username))
         })
+        .await
+    }
+
+    /// The user's own change: needs the current password, clears a forced rotation and signs
+    /// out every other session
+    pub async fn change_password(&self, username: &str, current: &str, new: &str, keep_session: Option<&str>) -> Result<()> {
+        let user = self.authenticate(username, current).await.ok_or_else(|| anyhow!("Current password is wrong"))?;
+        if current == new {
+            return Err(anyhow!("Invalid password: the new password must differ from the current one"));
+        }
+        self.check_password_rules(new)?;
+        self.set_password(user, new, false, keep_session.map(str::to_string)).await
+    }
+
+    /// An admin's reset: the user must change the new password and every session is revoked
+    pub async fn reset_password(&self, username: &str, password: &str) -> Result<()> {
+        let user = self.get_user(username).await.ok_or_else(|| anyhow!("No user {}", username))?;
+        if user.sso_subject.is_some() {
+            return Err(anyhow!("Cannot set a password for single sign-on account {}", username));
+        }
+        self.check_password_rules(password)?;
+        self.set_password(user, password, true, None).await
+    }
+
+    async fn set_password(&self, user: User, password: &str, must_change: bool, keep_session: Option<String>) -> Result<()> {
+        let hash = Self::hash_password(password)?;
+        self.interact(move |connection, _| {
+            let tx = connection.unchecked_transaction()?;
+            tx.execute(
+                "UPDATE users SET password_hash = ?, must_change_password = ?, password_changed_at = ? WHERE id = ?",
+                params![hash, must_change, timestamp(Utc::now()), user.id],
+            )?;
+            revoke_user_sessions(&tx, &user.id, keep_session.as_deref())?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    fn check_password_rules(&self, password: &str) -> Result<()> {
+        if password.chars().count() < self.policy.min_password_length {
+            return Err(anyhow!("Invalid password: use at least {} characters", self.policy.min_password_length));
+        }
+        Ok(())
+    }
+
+    /// Whether the user may only change their password: an admin handed it out, or it is
+    /// older than the configured maximum age
+    pub fn password_change_required(&self, user: &User) -> bool {
+        if user.sso_subject.is_some() {
+            return false;
+        }
+        user.must_change_password
+            || self.policy.password_max_age.map_or(false, |max_age| Utc::now() - user.password_changed_at > max_age)
+    }
+
+    /// Start a session for a signed-in user
+    pub async fn issue_session(&self, user: &User) -> Result<IssuedSession> {
+        let user = user.clone();
+        self.interact(move |connection, policy| {
+            let now = Utc::now();
+            let session_id = uuid::Uuid::new_v4().to_string();
+            let refresh_token = random_token();
+            let session_expires_at = now + policy.session_lifetime;
+            connection.execute(
+                "INSERT INTO sessions (id, user_id, refresh_token_hash, created_at, last_used_at, expires_at)
+                VALUES (?, ?, ?, ?, ?, ?)",
+                params![session_id, user.id, hash_refresh_token(&refresh_token), timestamp(now), timestamp(now), timestamp(session_expires_at)],
+            )?;
+            issued(&user, session_id, refresh_token, session_expires_at, policy)
+        })
+        .await
+    }
+
+    /// Swap a refresh token for a new access token and a new refresh token. The old refresh
+    /// token stops working. `None` when the token is unknown, revoked or expired, or its
+    /// user has been disabled.
+    pub async fn refresh_session(&self, refresh_token: &str) -> Result<Option<IssuedSession>> {
+        let token_hash = hash_refresh_token(refresh_token);
+        self.interact(move |connection, policy| {
+            let now = Utc::now();
+            let session: Option<(String, String, String)> = connection
+                .query_row(
+                    "SELECT s.id, u.username, s.expires_at FROM sessions s JOIN users u ON u.id = s.user_id
+                    WHERE s.refresh_token_hash = ? AND s.revoked_at IS NULL AND u.is_active",
+                    params![token_hash],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .optional()?;
+            let Some((session_id, username, expires_at)) = session else { return Ok(None) };
+            let expires_at = parse_timestamp(expires_at)?;
+            if expires_at <= now {
+                return Ok(None);
+            }
+
+            let user = find_user(connection, &username)?.ok_or_else(|| anyhow!("No user {}", username))?;
+            let refresh_token = random_token();
+            connection.execute(
+                "UPDATE sessions SET refresh_token_hash = ?, last_used_at = ? WHERE id = ?",
+                params![hash_refresh_token(&refresh_token), timestamp(now), session_id],
+            )?;
+            issued(&user, session_id, refresh_token, expires_at, policy).map(Some)
+        })
+        .await
+    }
+
+    /// Whether `session_id` belongs to `username` and is neither revoked nor expired
+    pub async fn session_active(&self, session_id: &str, username: &str) -> bool {
+        let (session_id, username) = (session_id.to_string(), username.to_string());
+        self.interact(move |connection, _| {
+            let active = connection
+                .query_row(
+                    "SELECT 1 FROM sessions s JOIN users u ON u.id = s.user_id
+                    WHERE s.id = ? AND u.username = ? AND s.revoked_at IS NULL AND s.expires_at > ?",
+                    params![session_id, username, timestamp(Utc::now())],
+                    |_| Ok(()),
+                )
+                .optional()?;
+            Ok(active.is_some())
+        })
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Session lookup failed: {}", e);
+            false
+        })
+    }
+
+    /// Live sessions of a user, most recently used first
+    pub async fn list_sessions(&self, username: &str) -> Result<Vec<Session>> {
+        let username = username.to_string();
+        self.interact(move |connection, _| {
+            let mut stmt = connection.prepare(
+                "SELECT s.id, u.username, s.created_at, s.last_used_at, s.expires_at
+                FROM sessions s JOIN users u ON u.id = s.user_id
+                WHERE u.username = ? AND s.revoked_at IS NULL AND s.expires_at > ?
+                ORDER BY s.last_used_at DESC",
+            )?;
+            let rows = stmt.query_map(params![username, timestamp(Utc::now())], |row| {
+                Ok(Session {
+                    id: row.get(0)?,
+                    username: row.get(1)?,
+                    created_at: parse_timestamp(row.get(2)?)?,
+                    last_used_at: parse_timestamp(row.get(3)?)?,
+                    expires_at: parse_timestamp(row.get(4)?)?,
+                })
+            })?;
+            Ok(rows.collect::<rusqlite::Result<_>>()?)
+        })
+        .await
+    }
+
+    /// Revoke one of `username`'s sessions; false if they have no such live session
+    pub async fn revoke_session(&self, username: &str, session_id: &str) -> Result<bool> {
+        let (username, session_id) = (username.to_string(), session_id.to_string());
+        self.interact(move |connection, _| {
+            let revoked = connection.execute(
+                "UPDATE sessions SET revoked_at = ?
+                WHERE id = ? AND revoked_at IS NULL AND user_id = (SELECT id FROM users WHERE username = ?)",
+                params![timestamp(Utc::now()), session_id, username],
+            )?;
+            Ok(revoked > 0)
+        })
+        .await
+    }
+
+    /// Revoke every session of `username`; returns how many were live
+    pub async fn revoke_sessions(&self, username: &str) -> Result<usize> {
+        let username = username.to_string();
+        self.interact(move |connection, _| {
+            let user = find_user(connection, &username)?.ok_or_else(|| anyhow!("No user {}", username))?;
+            revoke_user_sessions(connection, &user.id, None)
+        })
+        .await
+    }
+
+    /// Forget sessions that expired or were revoked more than a day ago
+    pub async fn prune_sessions(&self) -> Result<usize> {
+        self.interact(|connection, _| {
+            let cutoff = timestamp(Utc::now() - Duration::days(1));
+            Ok(connection.execute(
+                "DELETE FROM sessions WHERE expires_at < ? OR revoked_at < ?",
+                params![cutoff, cutoff],
+            )?)
+        })
+        .await
     }
 
     /// Hash a password using Argon2
@@ -87,10 +556,137 @@ impl UserManager {
     }
 
     /// Verify a password against a hash
-    fn verify_password(&self, password: &str, hash: &str) -> Result<bool> {
+    fn verify_password(password: &str, hash: &str) -> Result<bool> {
         let parsed_hash = PasswordHash::new(hash)
             .map_err(|e| anyhow::anyhow!("Invalid password hash: {}", e))?;
         let argon2 = Argon2::default();
         Ok(argon2.verify_password(password.as_bytes(), &parsed_hash).is_ok())
     }
 }
+
+fn find_user(connection: &Connection, username: &str) -> Result<Option<User>> {
+    Ok(connection
+        .query_row(
+            &format!("SELECT {} FROM users WHERE username = ?", USER_COLUMNS),
+            params![username],
+            User::from_row,
+        )
+        .optional()?)
+}
+
+fn revoke_user_sessions(connection: &Connection, user_id: &str, keep: Option<&str>) -> Result<usize> {
+    Ok(connection.execute(
+        "UPDATE sessions SET revoked_at = ? WHERE user_id = ? AND revoked_at IS NULL AND id IS NOT ?",
+        params![timestamp(Utc::now()), user_id, keep],
+    )?)
+}
+
+fn issued(
+    user: &User,
+    session_id: String,
+    refresh_token: String,
+    session_expires_at: DateTime<Utc>,
+    policy: &SessionPolicy,
+) -> Result<IssuedSession> {
+    // An access token never outlives its session
+    let access_expires_at = (Utc::now() + policy.access_token_lifetime).min(session_expires_at);
+    Ok(IssuedSession {
+        access_token: jwt::create_token(&user.username, &session_id, access_expires_at - Utc::now())?,
+        session_id,
+        access_expires_at,
+        refresh_token,
+        session_expires_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> UserManager {
+        let security = SecurityConfig { admin_password: Some("bootstrap-password".to_string()), ..Default::default() };
+        UserManager::open(":memory:", &security).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sessions_refresh_and_revoke() {
+        let users = manager();
+        let admin = users.authenticate("admin", "bootstrap-password").await.unwrap();
+        assert!(users.authenticate("admin", "wrong").await.is_none());
+
+        let session = users.issue_session(&admin).await.unwrap();
+        assert!(users.session_active(&session.session_id, "admin").await);
+        let claims = jwt::verify_token(&session.access_token).unwrap();
+        assert_eq!(claims.sid.as_deref(), Some(session.session_id.as_str()));
+
+        // Refresh tokens are single use
+        let refreshed = users.refresh_session(&session.refresh_token).await.unwrap().unwrap();
+        assert_eq!(refreshed.session_id, session.session_id);
+        assert!(users.refresh_session(&session.refresh_token).await.unwrap().is_none());
+
+        assert!(users.revoke_session("admin", &session.session_id).await.unwrap());
+        assert!(!users.session_active(&session.session_id, "admin").await);
+        assert!(users.refresh_session(&refreshed.refresh_token).await.unwrap().is_none());
+        assert!(users.list_sessions("admin").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_forced_password_rotation() {
+        let users = manager();
+        let analyst = users.create_user("alice", "temporary-pass", Role::Analyst, true).await.unwrap();
+        assert!(users.password_change_required(&analyst));
+        assert!(users.create_user("alice", "another-password", Role::Viewer, false).await.is_err());
+        assert!(users.create_user("bob", "short", Role::Viewer, false).await.is_err());
+
+        let first = users.issue_session(&analyst).await.unwrap();
+        let second = users.issue_session(&analyst).await.unwrap();
+        assert!(users.change_password("alice", "temporary-pass", "temporary-pass", None).await.is_err());
+        users.change_password("alice", "temporary-pass", "a-better-passphrase", Some(&first.session_id)).await.unwrap();
+
+        let alice = users.get_user("alice").await.unwrap();
+        assert!(!users.password_change_required(&alice));
+        // Changing the password signs out every other session
+        assert!(users.session_active(&first.session_id, "alice").await);
+        assert!(!users.session_active(&second.session_id, "alice").await);
+
+        users.reset_password("alice", "handed-out-password").await.unwrap();
+        assert!(users.password_change_required(&users.get_user("alice").await.unwrap()));
+        assert!(!users.session_active(&first.session_id, "alice").await);
+    }
+
+    #[tokio::test]
+    async fn test_last_admin_is_kept() {
+        let users = manager();
+        assert!(users.update_user("admin", Some(Role::Viewer), None).await.is_err());
+        assert!(users.update_user("admin", None, Some(false)).await.is_err());
+
+        users.create_user("root", "second-admin-pass", Role::Admin, false).await.unwrap();
+        let disabled = users.update_user("admin", None, Some(false)).await.unwrap();
+        assert!(!disabled.is_active);
+        assert!(users.authenticate("admin", "bootstrap-password").await.is_none());
+        assert_eq!(users.list_users().await.unwrap().len(), 2);
+
+        let empty = UserManager::open(":memory:", &SecurityConfig { admin_password: None, ..Default::default() }).unwrap();
+        assert!(empty.list_users().await.unwrap().is_empty());
+        let weak = UserManager::open(":memory:", &SecurityConfig { admin_password: Some("admin123".to_string()), ..Default::default() }).unwrap();
+        assert!(weak.password_change_required(&weak.get_user("admin").await.unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_sso_accounts() {
+        let users = manager();
+        let carol = users.sign_in_sso("okta|00u1", "carol@example.com", Role::Analyst).await.unwrap();
+        assert_eq!((carol.role.as_str(), carol.sso_subject.as_deref()), ("analyst", Some("okta|00u1")));
+        assert!(carol.last_login.is_some() && !users.password_change_required(&carol));
+        assert!(users.authenticate("carol@example.com", NO_PASSWORD).await.is_none());
+        assert!(users.reset_password("carol@example.com", "some-long-password").await.is_err());
+
+        // The provider's groups win at every sign-in
+        let promoted = users.sign_in_sso("okta|00u1", "carol@example.com", Role::Admin).await.unwrap();
+        assert_eq!((promoted.id, promoted.role.as_str()), (carol.id, "admin"));
+
+        assert!(users.sign_in_sso("okta|00u2", "admin", Role::Viewer).await.is_err());
+        users.update_user("carol@example.com", None, Some(false)).await.unwrap();
+        assert!(users.sign_in_sso("okta|00u1", "carol@example.com", Role::Admin).await.is_err());
+    }
+}
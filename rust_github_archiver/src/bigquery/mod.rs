@@ -1,14 +1,57 @@
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc, NaiveDate};
-use gcp_bigquery_client::{Client, model::query_request::QueryRequest};
+use futures::stream::{self, BoxStream, Stream, StreamExt, TryStreamExt};
+use gcp_bigquery_client::Client;
+use gcp_bigquery_client::model::get_query_results_parameters::GetQueryResultsParameters;
+use gcp_bigquery_client::model::job_reference::JobReference;
+use gcp_bigquery_client::model::query_request::QueryRequest;
+use gcp_bigquery_client::model::query_response::{QueryResponse, ResultSet};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 use tracing::{info, warn, error, debug};
 
+use crate::core::{offline, BigQueryConfig};
+use crate::performance::{QuotaResource, WorkspaceQuota};
+
+const TIB: f64 = (1u64 << 40) as f64;
+
+/// BigQuery refuses a query referencing more tables than this, counting each table a
+/// wildcard matches
+const MAX_TABLES_PER_QUERY: i64 = 1000;
+
+/// `before` of a push that created its branch
+pub(crate) const NULL_COMMIT: &str = "0000000000000000000000000000000000000000";
+
 /// BigQuery client for scanning GitHub Archive data
 pub struct BigQueryScanner {
     client: Client,
     project_id: String,
+    /// Monthly bytes budget; each query is dry-run first and refused when it would exceed it
+    quota: Option<WorkspaceQuota>,
+    /// Per-query bytes-billed cap and pricing
+    limits: BigQueryConfig,
+}
+
+/// What a query would process and cost, from a dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryEstimate {
+    pub bytes_processed: u64,
+    /// At on-demand pricing; cached results and flat-rate reservations cost less
+    pub estimated_cost_usd: f64,
+}
+
+impl QueryEstimate {
+    fn new(bytes_processed: u64, price_per_tib_usd: f64) -> Self {
+        Self { bytes_processed, estimated_cost_usd: bytes_processed as f64 / TIB * price_per_tib_usd }
+    }
+}
+
+impl std::fmt::Display for QueryEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let gib = self.bytes_processed as f64 / (1u64 << 30) as f64;
+        write!(f, "{:.2} GiB (about ${:.2})", gib, self.estimated_cost_usd)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -25,7 +68,86 @@ pub struct ZeroCommitEvent {
     pub ref_name: String,
 }
 
+/// A push whose commit list is complete, so `first_commit` is the oldest commit it added.
+/// GH Archive does not record parents: the push was forced, leaving `before_commit`
+/// dangling, when `first_commit`'s parent is not `before_commit`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForcePushEvent {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub repo_name: String,
+    pub repo_id: i64,
+    pub actor_login: String,
+    pub actor_id: i64,
+    pub before_commit: String,
+    pub after_commit: String,
+    pub first_commit: String,
+    pub ref_name: String,
+}
+
+impl ForcePushEvent {
+    /// Whether the push rewrote its branch, given the parents of `first_commit`
+    pub fn is_forced(&self, first_commit_parents: &[String]) -> bool {
+        !first_commit_parents.iter().any(|parent| *parent == self.before_commit)
+    }
+}
+
+/// A deleted branch and the last head pushed to it within the scanned range
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BranchDeletionEvent {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub repo_name: String,
+    pub repo_id: i64,
+    pub actor_login: String,
+    pub actor_id: i64,
+    pub ref_name: String,
+    pub head_commit: String,
+}
+
+/// An event that may have left a commit reachable only by its SHA
 #[derive(Debug, Serialize, Deserialize)]
+pub enum DanglingCommitEvent {
+    ZeroCommit(ZeroCommitEvent),
+    ForcePush(ForcePushEvent),
+    BranchDeletion(BranchDeletionEvent),
+}
+
+impl DanglingCommitEvent {
+    pub fn id(&self) -> &str {
+        match self {
+            Self::ZeroCommit(event) => &event.id,
+            Self::ForcePush(event) => &event.id,
+            Self::BranchDeletion(event) => &event.id,
+        }
+    }
+
+    pub fn repo_name(&self) -> &str {
+        match self {
+            Self::ZeroCommit(event) => &event.repo_name,
+            Self::ForcePush(event) => &event.repo_name,
+            Self::BranchDeletion(event) => &event.repo_name,
+        }
+    }
+
+    /// The commit the event may have left dangling
+    pub fn commit(&self) -> &str {
+        match self {
+            Self::ZeroCommit(event) => &event.before_commit,
+            Self::ForcePush(event) => &event.before_commit,
+            Self::BranchDeletion(event) => &event.head_commit,
+        }
+    }
+}
+
+/// Which events besides zero-commit pushes a historical scan mines for dangling commits
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DanglingCommitSources {
+    pub force_pushes: bool,
+    pub branch_deletions: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepositoryFilter {
     pub organizations: Vec<String>,
     pub users: Vec<String>,
@@ -42,9 +164,305 @@ impl Default for RepositoryFilter {
     }
 }
 
+impl RepositoryFilter {
+    /// Whether `repo_name` is in one of the filter's organizations, users or repositories,
+    /// ignoring case as GitHub does. An empty filter matches every repository.
+    pub fn matches(&self, repo_name: &str) -> bool {
+        if self.organizations.is_empty() && self.users.is_empty() && self.repositories.is_empty() {
+            return true;
+        }
+        let owner = repo_name.split('/').next().unwrap_or_default();
+        self.organizations.iter().chain(&self.users).any(|name| name.eq_ignore_ascii_case(owner))
+            || self.repositories.iter().any(|name| name.eq_ignore_ascii_case(repo_name))
+    }
+}
+
+/// `totalBytesProcessed` is a decimal string in BigQuery responses
+fn processed_bytes(total_bytes_processed: Option<&str>) -> u64 {
+    total_bytes_processed.and_then(|bytes| bytes.parse().ok()).unwrap_or(0)
+}
+
+/// First wait before polling a job that has not completed; doubled on every poll after
+const JOB_POLL_INITIAL: Duration = Duration::from_millis(500);
+
+/// Longest wait between polls of an incomplete job
+const JOB_POLL_MAX: Duration = Duration::from_secs(10);
+
+/// Where a paged query's results stand between pages
+#[derive(Debug, PartialEq)]
+enum ResultPages {
+    /// Not started: the query still has to be submitted
+    Query(String),
+    /// Submitted as `job_id`. `page_token` is unset until the job completes and the first
+    /// page arrives; `complete` records that its bytes were charged. `polls` counts the
+    /// fetches that found the job still running.
+    Job { job_id: String, location: Option<String>, page_token: Option<String>, complete: bool, polls: u32 },
+    Done,
+}
+
+impl ResultPages {
+    /// Where the results stand after a page: done once a complete job has no further page,
+    /// otherwise the job to fetch the next page, or poll again, from
+    fn after(complete: bool, page_token: Option<String>, job: Option<JobReference>, polls: u32) -> Result<Self> {
+        match (complete, page_token, job) {
+            (true, None, _) => Ok(Self::Done),
+            (_, page_token, Some(job)) => Ok(Self::Job {
+                job_id: job.job_id.ok_or_else(|| anyhow!("BigQuery returned a job reference without a job ID"))?,
+                location: job.location,
+                page_token,
+                complete,
+                polls: if complete { 0 } else { polls + 1 },
+            }),
+            (_, _, None) => Err(anyhow!("BigQuery returned more results but no job to fetch them from")),
+        }
+    }
+}
+
+/// How long to wait before the next poll of a job that was still running `polls` times
+fn job_poll_delay(polls: u32) -> Duration {
+    JOB_POLL_INITIAL.saturating_mul(1 << polls.saturating_sub(1).min(16)).min(JOB_POLL_MAX)
+}
+
+/// The zero-commit events on one page of results, leaving out pushes with no `before` commit
+fn zero_commit_events(response: QueryResponse) -> Result<Vec<ZeroCommitEvent>> {
+    let mut events = Vec::new();
+    let mut result_set = ResultSet::new_from_query_response(response);
+    while result_set.next_row() {
+        let event = ZeroCommitEvent {
+            id: result_set.get_string_by_name("id")?.unwrap_or_default(),
+            event_type: result_set.get_string_by_name("type")?.unwrap_or_default(),
+            created_at: result_set.get_datetime_by_name("created_at")?
+                .ok_or_else(|| anyhow!("Missing created_at field"))?
+                .and_utc(),
+            repo_name: result_set.get_string_by_name("repo_name")?.unwrap_or_default(),
+            repo_id: result_set.get_i64_by_name("repo_id")?.unwrap_or(0),
+            actor_login: result_set.get_string_by_name("actor_login")?.unwrap_or_default(),
+            actor_id: result_set.get_i64_by_name("actor_id")?.unwrap_or(0),
+            before_commit: result_set.get_string_by_name("before_commit")?.unwrap_or_default(),
+            after_commit: result_set.get_string_by_name("after_commit")?.unwrap_or_default(),
+            ref_name: result_set.get_string_by_name("ref")?.unwrap_or_default(),
+        };
+        if !event.before_commit.is_empty() && event.before_commit != NULL_COMMIT {
+            events.push(event);
+        }
+    }
+    Ok(events)
+}
+
+/// The force-push candidates on one page of results
+fn force_push_events(response: QueryResponse) -> Result<Vec<ForcePushEvent>> {
+    let mut events = Vec::new();
+    let mut result_set = ResultSet::new_from_query_response(response);
+    while result_set.next_row() {
+        events.push(ForcePushEvent {
+            id: result_set.get_string_by_name("id")?.unwrap_or_default(),
+            created_at: result_set.get_datetime_by_name("created_at")?
+                .ok_or_else(|| anyhow!("Missing created_at field"))?
+                .and_utc(),
+            repo_name: result_set.get_string_by_name("repo_name")?.unwrap_or_default(),
+            repo_id: result_set.get_i64_by_name("repo_id")?.unwrap_or(0),
+            actor_login: result_set.get_string_by_name("actor_login")?.unwrap_or_default(),
+            actor_id: result_set.get_i64_by_name("actor_id")?.unwrap_or(0),
+            before_commit: result_set.get_string_by_name("before_commit")?.unwrap_or_default(),
+            after_commit: result_set.get_string_by_name("after_commit")?.unwrap_or_default(),
+            first_commit: result_set.get_string_by_name("first_commit")?.unwrap_or_default(),
+            ref_name: result_set.get_string_by_name("ref")?.unwrap_or_default(),
+        });
+    }
+    Ok(events)
+}
+
+/// The branch deletions on one page of results
+fn branch_deletions(response: QueryResponse) -> Result<Vec<BranchDeletionEvent>> {
+    let mut events = Vec::new();
+    let mut result_set = ResultSet::new_from_query_response(response);
+    while result_set.next_row() {
+        events.push(BranchDeletionEvent {
+            id: result_set.get_string_by_name("id")?.unwrap_or_default(),
+            created_at: result_set.get_datetime_by_name("created_at")?
+                .ok_or_else(|| anyhow!("Missing created_at field"))?
+                .and_utc(),
+            repo_name: result_set.get_string_by_name("repo_name")?.unwrap_or_default(),
+            repo_id: result_set.get_i64_by_name("repo_id")?.unwrap_or(0),
+            actor_login: result_set.get_string_by_name("actor_login")?.unwrap_or_default(),
+            actor_id: result_set.get_i64_by_name("actor_id")?.unwrap_or(0),
+            ref_name: result_set.get_string_by_name("ref")?.unwrap_or_default(),
+            head_commit: result_set.get_string_by_name("head_commit")?.unwrap_or_default(),
+        });
+    }
+    Ok(events)
+}
+
+/// `repo.name` restricted to the organizations, users and repositories of `filter`, or
+/// `None` when it names none
+fn repository_clause(filter: &RepositoryFilter) -> Option<String> {
+    let quoted = |names: &[String]| {
+        names.iter()
+            .map(|name| format!("'{}'", name.replace("'", "''")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut repo_filters = Vec::new();
+    if !filter.organizations.is_empty() {
+        repo_filters.push(format!("SPLIT(repo.name, '/')[OFFSET(0)] IN ({})", quoted(&filter.organizations)));
+    }
+    if !filter.users.is_empty() {
+        repo_filters.push(format!("SPLIT(repo.name, '/')[OFFSET(0)] IN ({})", quoted(&filter.users)));
+    }
+    if !filter.repositories.is_empty() {
+        repo_filters.push(format!("repo.name IN ({})", quoted(&filter.repositories)));
+    }
+    (!repo_filters.is_empty()).then(|| format!("({})", repo_filters.join(" OR ")))
+}
+
+fn limit_clause(limit: Option<i64>) -> String {
+    limit.map(|l| format!("LIMIT {}", l)).unwrap_or_default()
+}
+
+/// SQL for pushes that may have been forced: pushes with a `before` commit whose commit
+/// list is complete (GH Archive keeps at most 20), so `commits[0]` is the first one added
+fn force_push_query(start_date: NaiveDate, end_date: NaiveDate, filter: &RepositoryFilter, limit: Option<i64>) -> String {
+    let tables = ArchiveTables::covering(start_date, end_date);
+    let mut where_clauses = vec![
+        tables.suffix_predicate,
+        "type = 'PushEvent'".to_string(),
+        "ARRAY_LENGTH(JSON_EXTRACT_ARRAY(payload, '$.commits')) > 0".to_string(),
+        "ARRAY_LENGTH(JSON_EXTRACT_ARRAY(payload, '$.commits')) = CAST(JSON_EXTRACT_SCALAR(payload, '$.size') AS INT64)".to_string(),
+        "JSON_EXTRACT_SCALAR(payload, '$.before') IS NOT NULL".to_string(),
+        format!("JSON_EXTRACT_SCALAR(payload, '$.before') NOT IN ('', '{}')", NULL_COMMIT),
+        format!("DATE(created_at) >= '{}'", start_date),
+        format!("DATE(created_at) <= '{}'", end_date),
+    ];
+    where_clauses.extend(repository_clause(filter));
+
+    format!(
+        r#"
+SELECT
+    id,
+    created_at,
+    repo.name as repo_name,
+    repo.id as repo_id,
+    actor.login as actor_login,
+    actor.id as actor_id,
+    JSON_EXTRACT_SCALAR(payload, '$.before') as before_commit,
+    JSON_EXTRACT_SCALAR(payload, '$.head') as after_commit,
+    JSON_EXTRACT_SCALAR(payload, '$.commits[0].sha') as first_commit,
+    JSON_EXTRACT_SCALAR(payload, '$.ref') as ref
+FROM {}
+WHERE {}
+ORDER BY created_at DESC
+{}
+        "#,
+        tables.wildcard,
+        where_clauses.join(" AND "),
+        limit_clause(limit)
+    )
+}
+
+/// SQL for deleted branches with the last head pushed to each before its deletion.
+/// DeleteEvents carry no SHA, so a branch with no push in the range is not found.
+fn branch_deletion_query(start_date: NaiveDate, end_date: NaiveDate, filter: &RepositoryFilter, limit: Option<i64>) -> String {
+    let tables = ArchiveTables::covering(start_date, end_date);
+    let common = {
+        let mut clauses = vec![
+            tables.suffix_predicate.clone(),
+            format!("DATE(created_at) >= '{}'", start_date),
+            format!("DATE(created_at) <= '{}'", end_date),
+        ];
+        clauses.extend(repository_clause(filter));
+        clauses.join(" AND ")
+    };
+
+    format!(
+        r#"
+WITH deletions AS (
+    SELECT
+        id,
+        created_at,
+        repo.name as repo_name,
+        repo.id as repo_id,
+        actor.login as actor_login,
+        actor.id as actor_id,
+        CONCAT('refs/heads/', JSON_EXTRACT_SCALAR(payload, '$.ref')) as ref
+    FROM {wildcard}
+    WHERE {common}
+        AND type = 'DeleteEvent'
+        AND JSON_EXTRACT_SCALAR(payload, '$.ref_type') = 'branch'
+),
+pushes AS (
+    SELECT
+        created_at,
+        repo.name as repo_name,
+        JSON_EXTRACT_SCALAR(payload, '$.ref') as ref,
+        JSON_EXTRACT_SCALAR(payload, '$.head') as head_commit
+    FROM {wildcard}
+    WHERE {common}
+        AND type = 'PushEvent'
+        AND JSON_EXTRACT_SCALAR(payload, '$.head') NOT IN ('', '{null_commit}')
+)
+SELECT
+    d.id,
+    d.created_at,
+    d.repo_name,
+    d.repo_id,
+    d.actor_login,
+    d.actor_id,
+    d.ref,
+    ARRAY_AGG(p.head_commit ORDER BY p.created_at DESC LIMIT 1)[OFFSET(0)] as head_commit
+FROM deletions d
+JOIN pushes p ON p.repo_name = d.repo_name AND p.ref = d.ref AND p.created_at <= d.created_at
+GROUP BY d.id, d.created_at, d.repo_name, d.repo_id, d.actor_login, d.actor_id, d.ref
+ORDER BY d.created_at DESC
+{limit}
+        "#,
+        wildcard = tables.wildcard,
+        common = common,
+        null_commit = NULL_COMMIT,
+        limit = limit_clause(limit)
+    )
+}
+
+/// The GH Archive tables a query over `start..=end` reads, with the `_TABLE_SUFFIX`
+/// predicate that prunes the wildcard to them. Only the matched tables are scanned and
+/// billed, so a 3-day window costs 3 days, not the months around it. Day tables are used
+/// unless the range spans more of them than one query may reference.
+#[derive(Debug, Clone, PartialEq)]
+struct ArchiveTables {
+    wildcard: &'static str,
+    suffix_predicate: String,
+}
+
+impl ArchiveTables {
+    fn covering(start: NaiveDate, end: NaiveDate) -> Self {
+        let (wildcard, format) = if (end - start).num_days() < MAX_TABLES_PER_QUERY {
+            ("`githubarchive.day.*`", "%Y%m%d")
+        } else {
+            ("`githubarchive.month.*`", "%Y%m")
+        };
+        Self {
+            wildcard,
+            suffix_predicate: format!("_TABLE_SUFFIX BETWEEN '{}' AND '{}'", start.format(format), end.format(format)),
+        }
+    }
+}
+
+/// Refuse a query estimated above `max_bytes_billed`; 0 means no limit
+fn check_max_bytes(estimate: &QueryEstimate, max_bytes_billed: u64) -> Result<()> {
+    if max_bytes_billed > 0 && estimate.bytes_processed > max_bytes_billed {
+        return Err(anyhow!(
+            "BigQuery query would process {}, over the {:.2} GiB max_bytes_billed limit (BIGQUERY_MAX_BYTES_BILLED); \
+             narrow the date range or filters",
+            estimate,
+            max_bytes_billed as f64 / (1u64 << 30) as f64
+        ));
+    }
+    Ok(())
+}
+
 impl BigQueryScanner {
     /// Create a new BigQuery scanner with service account authentication
     pub async fn new(service_account_key_path: &str, project_id: String) -> Result<Self> {
+        offline::policy().ensure_online("BigQuery scanning")?;
         info!("Initializing BigQuery client with project: {}", project_id);
         
         let client = Client::from_service_account_key_file(service_account_key_path).await
@@ -53,11 +471,14 @@ impl BigQueryScanner {
         Ok(Self {
             client,
             project_id,
+            quota: None,
+            limits: BigQueryConfig::default(),
         })
     }
 
     /// Create a new BigQuery scanner with application default credentials
     pub async fn new_with_default_credentials(project_id: String) -> Result<Self> {
+        offline::policy().ensure_online("BigQuery scanning")?;
         info!("Initializing BigQuery client with default credentials for project: {}", project_id);
         
         let client = Client::from_application_default_credentials().await
@@ -66,10 +487,78 @@ impl BigQueryScanner {
         Ok(Self {
             client,
             project_id,
+            quota: None,
+            limits: BigQueryConfig::default(),
         })
     }
 
-    /// Query GitHub Archive for zero-commit PushEvents
+    /// Charge the bytes processed by queries to a workspace budget
+    pub fn with_quota(mut self, quota: WorkspaceQuota) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    /// Refuse queries that would process more than `max_bytes_billed`
+    pub fn with_cost_limits(mut self, limits: BigQueryConfig) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// A standard SQL request for `query`, capped at `max_bytes_billed` so BigQuery fails
+    /// it instead of billing more should the estimate have been low
+    fn query_request(&self, query: String) -> QueryRequest {
+        let mut request = QueryRequest::new(query);
+        request.use_legacy_sql = Some(false);
+        if self.limits.max_bytes_billed > 0 {
+            request.maximum_bytes_billed = Some(self.limits.max_bytes_billed.to_string());
+        }
+        request
+    }
+
+    /// Bytes `query` would process and what that costs, from a dry run. A dry run is free.
+    pub async fn estimate(&self, query: &str) -> Result<QueryEstimate> {
+        let mut dry_run = self.query_request(query.to_string());
+        dry_run.dry_run = Some(true);
+        let response = self.client
+            .job()
+            .query(&self.project_id, dry_run)
+            .await
+            .map_err(|e| anyhow!("BigQuery dry run failed: {}", e))?;
+        Ok(QueryEstimate::new(processed_bytes(response.total_bytes_processed.as_deref()), self.limits.price_per_tib_usd))
+    }
+
+    /// Dry-run `query` and refuse it when it would process more than `max_bytes_billed` or
+    /// the remaining monthly budget
+    async fn check_bytes_budget(&self, query: &str) -> Result<()> {
+        let estimate = self.estimate(query).await?;
+        check_max_bytes(&estimate, self.limits.max_bytes_billed)?;
+
+        if let Some(quota) = &self.quota {
+            if let Some(remaining) = quota.remaining(QuotaResource::BigqueryBytes).await? {
+                if estimate.bytes_processed > remaining {
+                    return Err(anyhow!(
+                        "{}; the query would process {} bytes with {} left",
+                        quota.exceeded(QuotaResource::BigqueryBytes),
+                        estimate.bytes_processed,
+                        remaining
+                    ));
+                }
+            }
+        }
+        info!("BigQuery query will process {}", estimate);
+        Ok(())
+    }
+
+    /// Record the bytes a query processed against the monthly budget
+    async fn charge_bytes(&self, total_bytes_processed: Option<&str>) -> Result<()> {
+        match &self.quota {
+            Some(quota) => quota.record(QuotaResource::BigqueryBytes, processed_bytes(total_bytes_processed)).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Query GitHub Archive for zero-commit PushEvents. Every page of results is held in
+    /// memory; `stream_zero_commit_events` reads them one page at a time.
     pub async fn scan_zero_commit_events(
         &self,
         start_date: NaiveDate,
@@ -78,46 +567,156 @@ impl BigQueryScanner {
         limit: Option<i64>,
     ) -> Result<Vec<ZeroCommitEvent>> {
         info!("Scanning zero-commit events from {} to {}", start_date, end_date);
-        
+        let events: Vec<ZeroCommitEvent> =
+            self.stream_zero_commit_events(start_date, end_date, filter, limit).try_collect().await?;
+        info!("Found {} zero-commit events", events.len());
+        Ok(events)
+    }
+
+    /// Zero-commit PushEvents in result order, fetched `page_size` rows at a time as the
+    /// stream is read. The budget check runs before the first page; an error ends the stream.
+    pub fn stream_zero_commit_events<'a>(
+        &'a self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        filter: &RepositoryFilter,
+        limit: Option<i64>,
+    ) -> impl Stream<Item = Result<ZeroCommitEvent>> + Send + 'a {
         let query = self.build_zero_commit_query(start_date, end_date, filter, limit);
-        debug!("BigQuery SQL: {}", query);
-        
-        let mut query_request = QueryRequest::new(query);
-        query_request.max_results = limit.map(|l| l as u32);
-        query_request.use_legacy_sql = Some(false);
-        
-        let mut response = self.client
-            .job()
-            .query(&self.project_id, query_request)
-            .await
-            .map_err(|e| anyhow!("BigQuery query failed: {}", e))?;
-        
-        let mut events = Vec::new();
-        let mut result_set = gcp_bigquery_client::model::query_response::ResultSet::new_from_query_response(response);
-        
-        while result_set.next_row() {
-            let event = ZeroCommitEvent {
-                id: result_set.get_string_by_name("id")?.unwrap_or_default(),
-                event_type: result_set.get_string_by_name("type")?.unwrap_or_default(),
-                created_at: result_set.get_datetime_by_name("created_at")?
-                    .ok_or_else(|| anyhow!("Missing created_at field"))?
-                    .and_utc(),
-                repo_name: result_set.get_string_by_name("repo_name")?.unwrap_or_default(),
-                repo_id: result_set.get_i64_by_name("repo_id")?.unwrap_or(0),
-                actor_login: result_set.get_string_by_name("actor_login")?.unwrap_or_default(),
-                actor_id: result_set.get_i64_by_name("actor_id")?.unwrap_or(0),
-                before_commit: result_set.get_string_by_name("before_commit")?.unwrap_or_default(),
-                after_commit: result_set.get_string_by_name("after_commit")?.unwrap_or_default(),
-                ref_name: result_set.get_string_by_name("ref")?.unwrap_or_default(),
-            };
-            
-            if !event.before_commit.is_empty() && event.before_commit != "0000000000000000000000000000000000000000" {
-                events.push(event);
+        self.stream_rows(query, zero_commit_events)
+    }
+
+    /// Pushes that rewrote their branch, leaving the old head dangling. Candidates are read
+    /// from BigQuery; each `ForcePushEvent` is confirmed with `is_forced` once the parents of
+    /// its `first_commit` are fetched from GitHub.
+    pub async fn scan_force_push_events(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        filter: &RepositoryFilter,
+        limit: Option<i64>,
+    ) -> Result<Vec<ForcePushEvent>> {
+        info!("Scanning force-push candidates from {} to {}", start_date, end_date);
+        let events: Vec<ForcePushEvent> =
+            self.stream_force_push_events(start_date, end_date, filter, limit).try_collect().await?;
+        info!("Found {} force-push candidates", events.len());
+        Ok(events)
+    }
+
+    /// `scan_force_push_events`, read one page at a time
+    pub fn stream_force_push_events<'a>(
+        &'a self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        filter: &RepositoryFilter,
+        limit: Option<i64>,
+    ) -> impl Stream<Item = Result<ForcePushEvent>> + Send + 'a {
+        self.stream_rows(force_push_query(start_date, end_date, filter, limit), force_push_events)
+    }
+
+    /// Deleted branches with the last head pushed to them, which stays fetchable by SHA
+    pub async fn scan_branch_deletions(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        filter: &RepositoryFilter,
+        limit: Option<i64>,
+    ) -> Result<Vec<BranchDeletionEvent>> {
+        info!("Scanning branch deletions from {} to {}", start_date, end_date);
+        let events: Vec<BranchDeletionEvent> =
+            self.stream_branch_deletions(start_date, end_date, filter, limit).try_collect().await?;
+        info!("Found {} branch deletions", events.len());
+        Ok(events)
+    }
+
+    /// `scan_branch_deletions`, read one page at a time
+    pub fn stream_branch_deletions<'a>(
+        &'a self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        filter: &RepositoryFilter,
+        limit: Option<i64>,
+    ) -> impl Stream<Item = Result<BranchDeletionEvent>> + Send + 'a {
+        self.stream_rows(branch_deletion_query(start_date, end_date, filter, limit), branch_deletions)
+    }
+
+    /// The rows of `query` parsed by `rows`, fetched `page_size` at a time as the stream is
+    /// read. The budget check runs before the first page; an error ends the stream.
+    fn stream_rows<'a, T: Send + 'a>(
+        &'a self,
+        query: String,
+        rows: fn(QueryResponse) -> Result<Vec<T>>,
+    ) -> impl Stream<Item = Result<T>> + Send + 'a {
+        stream::try_unfold(ResultPages::Query(query), move |pages| self.next_page(pages, rows))
+            .map_ok(|page| stream::iter(page.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    /// Fetch the page `pages` points at, and where the results stand after it. A job that
+    /// has not completed yet returns an empty page; the next call backs off, then polls it again.
+    async fn next_page<T>(
+        &self,
+        pages: ResultPages,
+        rows: fn(QueryResponse) -> Result<Vec<T>>,
+    ) -> Result<Option<(Vec<T>, ResultPages)>> {
+        let (response, was_complete, polls) = match pages {
+            ResultPages::Done => return Ok(None),
+            ResultPages::Query(query) => {
+                debug!("BigQuery SQL: {}", query);
+                self.check_bytes_budget(&query).await?;
+                let mut request = self.query_request(query);
+                request.max_results = Some(self.limits.page_size);
+                let response = self.client
+                    .job()
+                    .query(&self.project_id, request)
+                    .await
+                    .map_err(|e| anyhow!("BigQuery query failed: {}", e))?;
+                (response, false, 0)
+            }
+            ResultPages::Job { job_id, location, page_token, complete, polls } => {
+                if !complete {
+                    let delay = job_poll_delay(polls);
+                    debug!("BigQuery job {} still running, polling again in {:?}", job_id, delay);
+                    tokio::time::sleep(delay).await;
+                }
+                let parameters = GetQueryResultsParameters {
+                    location,
+                    max_results: Some(self.limits.page_size),
+                    page_token,
+                    ..Default::default()
+                };
+                let response = self.client
+                    .job()
+                    .get_query_results(&self.project_id, &job_id, parameters)
+                    .await
+                    .map_err(|e| anyhow!("Failed to fetch BigQuery results of job {}: {}", job_id, e))?;
+                (QueryResponse::from(response), complete, polls)
             }
+        };
+
+        let complete = response.job_complete.unwrap_or(false);
+        if complete && !was_complete {
+            self.charge_bytes(response.total_bytes_processed.as_deref()).await?;
         }
-        
-        info!("Found {} zero-commit events", events.len());
-        Ok(events)
+        let job = response.job_reference.clone();
+        let page_token = response.page_token.clone();
+        let page = rows(response)?;
+
+        Ok(Some((page, ResultPages::after(complete, page_token, job, polls)?)))
+    }
+
+    /// Dry run of `scan_zero_commit_events`: what the query would process and cost,
+    /// without running it
+    pub async fn estimate_zero_commit_scan(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        filter: &RepositoryFilter,
+        limit: Option<i64>,
+    ) -> Result<QueryEstimate> {
+        let query = self.build_zero_commit_query(start_date, end_date, filter, limit);
+        debug!("BigQuery SQL (dry run): {}", query);
+        self.estimate(&query).await
     }
 
     /// Build the BigQuery SQL for finding zero-commit events
@@ -128,7 +727,9 @@ impl BigQueryScanner {
         filter: &RepositoryFilter,
         limit: Option<i64>,
     ) -> String {
+        let tables = ArchiveTables::covering(start_date, end_date);
         let mut where_clauses = vec![
+            tables.suffix_predicate,
             "type = 'PushEvent'".to_string(),
             "JSON_EXTRACT_ARRAY(payload, '$.commits') = []".to_string(), // Zero commits
             "JSON_EXTRACT_SCALAR(payload, '$.before') IS NOT NULL".to_string(),
@@ -138,44 +739,7 @@ impl BigQueryScanner {
             format!("DATE(created_at) <= '{}'", end_date),
         ];
 
-        // Add repository filters
-        if !filter.organizations.is_empty() || !filter.users.is_empty() || !filter.repositories.is_empty() {
-            let mut repo_filters = Vec::new();
-            
-            if !filter.organizations.is_empty() {
-                let orgs = filter.organizations.iter()
-                    .map(|org| format!("'{}'", org.replace("'", "''")))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                repo_filters.push(format!("SPLIT(repo.name, '/')[OFFSET(0)] IN ({})", orgs));
-            }
-            
-            if !filter.users.is_empty() {
-                let users = filter.users.iter()
-                    .map(|user| format!("'{}'", user.replace("'", "''")))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                repo_filters.push(format!("SPLIT(repo.name, '/')[OFFSET(0)] IN ({})", users));
-            }
-            
-            if !filter.repositories.is_empty() {
-                let repos = filter.repositories.iter()
-                    .map(|repo| format!("'{}'", repo.replace("'", "''")))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                repo_filters.push(format!("repo.name IN ({})", repos));
-            }
-            
-            if !repo_filters.is_empty() {
-                where_clauses.push(format!("({})", repo_filters.join(" OR ")));
-            }
-        }
-
-        let limit_clause = if let Some(l) = limit {
-            format!("LIMIT {}", l)
-        } else {
-            String::new()
-        };
+        where_clauses.extend(repository_clause(filter));
 
         format!(
             r#"
@@ -190,13 +754,14 @@ SELECT
     JSON_EXTRACT_SCALAR(payload, '$.before') as before_commit,
     JSON_EXTRACT_SCALAR(payload, '$.after') as after_commit,
     JSON_EXTRACT_SCALAR(payload, '$.ref') as ref
-FROM `githubarchive.month.*`
+FROM {}
 WHERE {}
 ORDER BY created_at DESC
 {}
             "#,
+            tables.wildcard,
             where_clauses.join(" AND "),
-            limit_clause
+            limit_clause(limit)
         )
     }
 
@@ -212,7 +777,7 @@ WHERE _TABLE_SUFFIX IS NOT NULL
 ORDER BY table_date DESC
         "#;
         
-        let query_request = QueryRequest::new(query.to_string());
+        let query_request = self.query_request(query.to_string());
         let mut response = self.client
             .job()
             .query(&self.project_id, query_request)
@@ -242,6 +807,7 @@ ORDER BY table_date DESC
     ) -> Result<HashMap<String, i64>> {
         info!("Getting PushEvent statistics from {} to {}", start_date, end_date);
         
+        let tables = ArchiveTables::covering(start_date, end_date);
         let query = format!(
             r#"
 SELECT 
@@ -250,20 +816,23 @@ SELECT
     COUNT(CASE WHEN JSON_EXTRACT_ARRAY(payload, '$.commits') != [] THEN 1 END) as normal_push_events,
     COUNT(DISTINCT repo.name) as unique_repositories,
     COUNT(DISTINCT actor.login) as unique_actors
-FROM `githubarchive.month.*`
-WHERE type = 'PushEvent'
+FROM {}
+WHERE {}
+    AND type = 'PushEvent'
     AND DATE(created_at) >= '{}'
     AND DATE(created_at) <= '{}'
             "#,
-            start_date, end_date
+            tables.wildcard, tables.suffix_predicate, start_date, end_date
         );
+        self.check_bytes_budget(&query).await?;
         
-        let query_request = QueryRequest::new(query);
+        let query_request = self.query_request(query);
         let mut response = self.client
             .job()
             .query(&self.project_id, query_request)
             .await
             .map_err(|e| anyhow!("Failed to query PushEvent stats: {}", e))?;
+        self.charge_bytes(response.total_bytes_processed.as_deref()).await?;
         
         let mut stats = HashMap::new();
         let mut result_set = gcp_bigquery_client::model::query_response::ResultSet::new_from_query_response(response);
@@ -296,6 +865,59 @@ WHERE type = 'PushEvent'
         self.scan_zero_commit_events(start_date, end_date, &filter, limit).await
     }
 
+    /// `scan_organization_zero_commits`, read one page at a time
+    pub fn stream_organization_zero_commits<'a>(
+        &'a self,
+        organization: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        limit: Option<i64>,
+    ) -> impl Stream<Item = Result<ZeroCommitEvent>> + Send + 'a {
+        let filter = RepositoryFilter {
+            organizations: vec![organization.to_string()],
+            ..Default::default()
+        };
+
+        self.stream_zero_commit_events(start_date, end_date, &filter, limit)
+    }
+
+    /// Every event of `organization` that may have left a dangling commit: zero-commit
+    /// pushes, then force-push candidates and branch deletions as `sources` enables them.
+    /// Each query runs once the previous one's results are read.
+    pub fn stream_organization_dangling_commits<'a>(
+        &'a self,
+        organization: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        sources: DanglingCommitSources,
+    ) -> impl Stream<Item = Result<DanglingCommitEvent>> + Send + 'a {
+        let filter = RepositoryFilter {
+            organizations: vec![organization.to_string()],
+            ..Default::default()
+        };
+
+        let mut streams: Vec<BoxStream<'a, Result<DanglingCommitEvent>>> = vec![
+            self.stream_zero_commit_events(start_date, end_date, &filter, None)
+                .map_ok(DanglingCommitEvent::ZeroCommit)
+                .boxed(),
+        ];
+        if sources.force_pushes {
+            streams.push(
+                self.stream_force_push_events(start_date, end_date, &filter, None)
+                    .map_ok(DanglingCommitEvent::ForcePush)
+                    .boxed(),
+            );
+        }
+        if sources.branch_deletions {
+            streams.push(
+                self.stream_branch_deletions(start_date, end_date, &filter, None)
+                    .map_ok(DanglingCommitEvent::BranchDeletion)
+                    .boxed(),
+            );
+        }
+        stream::iter(streams).flatten()
+    }
+
     /// Scan for zero-commit events by user
     pub async fn scan_user_zero_commits(
         &self,
@@ -327,7 +949,7 @@ WHERE type = 'PushEvent'
     pub fn extract_before_commits(events: &[ZeroCommitEvent]) -> Vec<String> {
         let mut commits: Vec<String> = events
             .iter()
-            .filter(|e| !e.before_commit.is_empty() && e.before_commit != "0000000000000000000000000000000000000000")
+            .filter(|e| !e.before_commit.is_empty() && e.before_commit != NULL_COMMIT)
             .map(|e| e.before_commit.clone())
             .collect();
         commits.sort();
@@ -341,6 +963,148 @@ mod tests {
     use super::*;
     use chrono::NaiveDate;
 
+    #[test]
+    fn test_estimate_cost_and_max_bytes_guard() {
+        let estimate = QueryEstimate::new(3 << 40, 6.25);
+        assert!((estimate.estimated_cost_usd - 18.75).abs() < 1e-9);
+        assert_eq!(estimate.to_string(), "3072.00 GiB (about $18.75)");
+
+        assert!(check_max_bytes(&estimate, 0).is_ok());
+        assert!(check_max_bytes(&estimate, 3 << 40).is_ok());
+        let refused = check_max_bytes(&estimate, 1 << 40).unwrap_err().to_string();
+        assert!(refused.contains("1024.00 GiB max_bytes_billed"));
+    }
+
+    #[test]
+    fn test_short_ranges_read_day_tables() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let short = ArchiveTables::covering(date(2024, 3, 30), date(2024, 4, 1));
+        assert_eq!(short.wildcard, "`githubarchive.day.*`");
+        assert_eq!(short.suffix_predicate, "_TABLE_SUFFIX BETWEEN '20240330' AND '20240401'");
+
+        let long = ArchiveTables::covering(date(2020, 1, 15), date(2024, 4, 1));
+        assert_eq!(long.wildcard, "`githubarchive.month.*`");
+        assert_eq!(long.suffix_predicate, "_TABLE_SUFFIX BETWEEN '202001' AND '202404'");
+    }
+
+    #[test]
+    fn test_force_push_and_branch_deletion_queries() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let filter = RepositoryFilter { organizations: vec!["acme".to_string()], ..Default::default() };
+
+        let force_pushes = force_push_query(date(2024, 3, 1), date(2024, 3, 3), &filter, Some(50));
+        assert!(force_pushes.contains("_TABLE_SUFFIX BETWEEN '20240301' AND '20240303'"));
+        assert!(force_pushes.contains("= CAST(JSON_EXTRACT_SCALAR(payload, '$.size') AS INT64)"));
+        assert!(force_pushes.contains("SPLIT(repo.name, '/')[OFFSET(0)] IN ('acme')"));
+        assert!(force_pushes.trim_end().ends_with("LIMIT 50"));
+
+        let deletions = branch_deletion_query(date(2024, 3, 1), date(2024, 3, 3), &filter, None);
+        assert!(deletions.contains("JSON_EXTRACT_SCALAR(payload, '$.ref_type') = 'branch'"));
+        assert_eq!(deletions.matches("IN ('acme')").count(), 2, "both pushes and deletions are filtered");
+        assert!(!deletions.contains("LIMIT"));
+
+        let push = ForcePushEvent {
+            id: "1".to_string(),
+            created_at: chrono::Utc::now(),
+            repo_name: "acme/api".to_string(),
+            repo_id: 1,
+            actor_login: "user1".to_string(),
+            actor_id: 1,
+            before_commit: "abc123".to_string(),
+            after_commit: "def456".to_string(),
+            first_commit: "def456".to_string(),
+            ref_name: "refs/heads/main".to_string(),
+        };
+        assert!(!push.is_forced(&["abc123".to_string()]));
+        assert!(push.is_forced(&["0a1b2c".to_string()]));
+        assert_eq!(DanglingCommitEvent::ForcePush(push).commit(), "abc123");
+    }
+
+    /// A results page in the shape the BigQuery REST API returns it
+    fn zero_commit_page(befores: &[&str]) -> QueryResponse {
+        let columns = ["id", "type", "created_at", "repo_name", "repo_id", "actor_login", "actor_id", "before_commit", "after_commit", "ref"];
+        let fields: Vec<_> = columns
+            .iter()
+            .map(|name| {
+                let kind = match *name {
+                    "created_at" => "TIMESTAMP",
+                    "repo_id" | "actor_id" => "INTEGER",
+                    _ => "STRING",
+                };
+                serde_json::json!({ "name": name, "type": kind })
+            })
+            .collect();
+        let rows: Vec<_> = befores
+            .iter()
+            .enumerate()
+            .map(|(i, before)| {
+                let values = [&i.to_string(), "PushEvent", "1709251200.0", "org/repo", "1", "user", "2", before, "def456", "refs/heads/main"];
+                serde_json::json!({ "f": values.iter().map(|v| serde_json::json!({ "v": v })).collect::<Vec<_>>() })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "jobComplete": true,
+            "schema": { "fields": fields },
+            "rows": rows,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_zero_commit_events_skip_pushes_without_before() {
+        let events = zero_commit_events(zero_commit_page(&["abc123", NULL_COMMIT, "", "xyz789"])).unwrap();
+        let befores: Vec<_> = events.iter().map(|e| e.before_commit.as_str()).collect();
+        assert_eq!(befores, ["abc123", "xyz789"]);
+        assert_eq!(events[1].id, "3");
+        assert_eq!(events[0].repo_name, "org/repo");
+        assert_eq!(events[0].actor_id, 2);
+
+        assert!(zero_commit_events(zero_commit_page(&[])).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_result_pages_follow_the_job() {
+        let job = || Some(JobReference { job_id: Some("job_1".to_string()), location: Some("US".to_string()), ..Default::default() });
+
+        // Still running: poll the job again, counting the polls
+        let running = ResultPages::after(false, None, job(), 0).unwrap();
+        assert_eq!(
+            running,
+            ResultPages::Job { job_id: "job_1".to_string(), location: Some("US".to_string()), page_token: None, complete: false, polls: 1 }
+        );
+        let ResultPages::Job { polls, .. } = ResultPages::after(false, None, job(), 1).unwrap() else { panic!("expected a job") };
+        assert_eq!(polls, 2);
+
+        // Complete with more pages: fetch the next one without waiting
+        let paging = ResultPages::after(true, Some("page_2".to_string()), job(), 2).unwrap();
+        assert_eq!(
+            paging,
+            ResultPages::Job {
+                job_id: "job_1".to_string(),
+                location: Some("US".to_string()),
+                page_token: Some("page_2".to_string()),
+                complete: true,
+                polls: 0,
+            }
+        );
+
+        // Complete with no token left: done, with or without a job reference
+        assert_eq!(ResultPages::after(true, None, job(), 0).unwrap(), ResultPages::Done);
+        assert_eq!(ResultPages::after(true, None, None, 0).unwrap(), ResultPages::Done);
+
+        assert!(ResultPages::after(false, None, None, 0).is_err());
+        assert!(ResultPages::after(false, None, Some(JobReference::default()), 0).is_err());
+    }
+
+    #[test]
+    fn test_job_poll_delay_backs_off() {
+        assert_eq!(job_poll_delay(1), Duration::from_millis(500));
+        assert_eq!(job_poll_delay(2), Duration::from_secs(1));
+        assert_eq!(job_poll_delay(3), Duration::from_secs(2));
+        assert_eq!(job_poll_delay(6), JOB_POLL_MAX);
+        assert_eq!(job_poll_delay(u32::MAX), JOB_POLL_MAX);
+    }
+
     #[test]
     fn test_repository_filter_default() {
         let filter = RepositoryFilter::default();
@@ -0,0 +1,354 @@
+pub mod service;
+
+pub use service::generate_canarytoken;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use rand::{rngs::OsRng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::performance::SecretDatabase;
+use crate::realtime::{AlertSeverity, RealTimeSecretAlert, RealTimeSecretMatch};
+use crate::secrets::{SecretMatch, SecretSeverity};
+
+/// Detector names of findings that are canaries start with this
+pub const CANARY_DETECTOR_PREFIX: &str = "CANARY";
+
+const BASE32: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const BASE62: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CanaryKind {
+    /// An AWS access key ID and secret access key
+    AwsKeys,
+    /// A classic GitHub personal access token
+    GithubToken,
+}
+
+impl CanaryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AwsKeys => "aws_keys",
+            Self::GithubToken => "github_token",
+        }
+    }
+}
+
+impl std::str::FromStr for CanaryKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "aws" | "aws_keys" => Ok(Self::AwsKeys),
+            "github" | "github_token" => Ok(Self::GithubToken),
+            other => Err(anyhow!("Unknown canary kind {} (expected aws or github)", other)),
+        }
+    }
+}
+
+impl fmt::Display for CanaryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Who generated a canary, and so who else sees it being used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CanaryProvider {
+    /// Generated here; only this scanner notices it
+    Local,
+    /// Generated by a Canarytokens server, which also alerts when the key is used against AWS
+    Canarytokens,
+}
+
+impl CanaryProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Canarytokens => "canarytokens",
+        }
+    }
+}
+
+impl std::str::FromStr for CanaryProvider {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "local" => Ok(Self::Local),
+            "canarytokens" => Ok(Self::Canarytokens),
+            other => Err(anyhow!("Unknown canary provider {} (expected local or canarytokens)", other)),
+        }
+    }
+}
+
+/// A decoy credential planted where a leak would expose it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryToken {
+    pub id: String,
+    pub kind: CanaryKind,
+    pub provider: CanaryProvider,
+    pub label: String,
+    /// AWS access key ID; `None` for single-value tokens
+    pub key_id: Option<String>,
+    pub secret: String,
+    /// The provider's id for the token, to manage it there
+    pub external_id: Option<String>,
+    pub created_at: String,
+    pub retired_at: Option<String>,
+    /// Where it was seeded
+    #[serde(default)]
+    pub locations: Vec<String>,
+    /// Times the scanner has seen it
+    #[serde(default)]
+    pub trips: i64,
+    pub last_tripped_at: Option<String>,
+}
+
+impl CanaryToken {
+    pub(crate) fn new(kind: CanaryKind, provider: CanaryProvider, label: &str, key_id: Option<String>, secret: String) -> Self {
+        Self {
+            id: Uuid::new_v4().simple().to_string()[..12].to_string(),
+            kind,
+            provider,
+            label: label.to_string(),
+            key_id,
+            secret,
+            external_id: None,
+            created_at: Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            retired_at: None,
+            locations: Vec::new(),
+            trips: 0,
+            last_tripped_at: None,
+        }
+    }
+
+    /// The values a scanner could match: the key ID and the secret
+    pub fn values(&self) -> impl Iterator<Item = &str> {
+        self.key_id.iter().map(String::as_str).chain(std::iter::once(self.secret.as_str()))
+    }
+
+    /// Whether a finding's matched text contains one of this canary's values
+    pub fn matches(&self, matched_text: &str) -> bool {
+        self.values().any(|value| !value.is_empty() && matched_text.contains(value))
+    }
+}
+
+/// `choose` samples by rejection, so every character of the alphabet is equally likely
+fn random_string(alphabet: &[u8], len: usize) -> String {
+    (0..len)
+        .map(|_| *alphabet.choose(&mut OsRng).expect("alphabets are not empty") as char)
+        .collect()
+}
+
+/// Generate a canary that only this scanner tracks. Its values have the shape of the
+/// real credential, so the built-in detectors find it.
+pub fn generate_local(kind: CanaryKind, label: &str) -> CanaryToken {
+    match kind {
+        CanaryKind::AwsKeys => CanaryToken::new(
+            kind,
+            CanaryProvider::Local,
+            label,
+            Some(format!("AKIA{}", random_string(BASE32, 16))),
+            random_string(BASE64, 40),
+        ),
+        CanaryKind::GithubToken => {
+            CanaryToken::new(kind, CanaryProvider::Local, label, None, format!("ghp_{}", random_string(BASE62, 36)))
+        }
+    }
+}
+
+/// How a canary is written into a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedFormat {
+    /// `NAME="value"` lines, as in a `.env` file or shell profile
+    Env,
+    /// An `~/.aws/credentials` profile
+    AwsCredentials,
+    /// One JSON object per line
+    Json,
+}
+
+impl std::str::FromStr for SeedFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "env" => Ok(Self::Env),
+            "aws" | "aws-credentials" | "ini" => Ok(Self::AwsCredentials),
+            "json" => Ok(Self::Json),
+            other => Err(anyhow!("Unknown seed format {} (expected env, aws-credentials or json)", other)),
+        }
+    }
+}
+
+/// The canary as text to plant in a file. Values are quoted so the secret key detector,
+/// which wants quotes, picks them up.
+pub fn seed_text(canary: &CanaryToken, format: SeedFormat) -> Result<String> {
+    let key_id = canary.key_id.as_deref().unwrap_or_default();
+    let text = match (canary.kind, format) {
+        (CanaryKind::AwsKeys, SeedFormat::Env) => {
+            format!("AWS_ACCESS_KEY_ID=\"{}\"\nAWS_SECRET_ACCESS_KEY=\"{}\"\n", key_id, canary.secret)
+        }
+        (CanaryKind::AwsKeys, SeedFormat::AwsCredentials) => format!(
+            "[{}]\naws_access_key_id = {}\naws_secret_access_key = \"{}\"\n",
+            canary.label.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "-"),
+            key_id,
+            canary.secret
+        ),
+        (CanaryKind::AwsKeys, SeedFormat::Json) => format!(
+            "{}\n",
+            serde_json::json!({ "aws_access_key_id": key_id, "aws_secret_access_key": canary.secret })
+        ),
+        (CanaryKind::GithubToken, SeedFormat::Env) => format!("GITHUB_TOKEN=\"{}\"\n", canary.secret),
+        (CanaryKind::GithubToken, SeedFormat::Json) => format!("{}\n", serde_json::json!({ "github_token": canary.secret })),
+        (CanaryKind::GithubToken, SeedFormat::AwsCredentials) => {
+            return Err(anyhow!("A GitHub token canary cannot go in an AWS credentials file"))
+        }
+    };
+    Ok(text)
+}
+
+/// Append the canary to `path`, creating the file if needed
+pub fn seed_file(canary: &CanaryToken, path: &Path, format: SeedFormat) -> Result<()> {
+    let text = seed_text(canary, format)?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    // Keep the canary on its own lines when the file does not end with a newline
+    let needs_newline = std::fs::read(path).map(|bytes| bytes.last().is_some_and(|b| *b != b'\n')).unwrap_or(false);
+    if needs_newline {
+        file.write_all(b"\n")?;
+    }
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+fn canary_detector(canary: &CanaryToken, detector_name: &str) -> String {
+    format!("{} {}: {}", CANARY_DETECTOR_PREFIX, canary.label, detector_name)
+}
+
+/// Flag the findings of an alert that are canaries, record each sighting and raise the
+/// alert to Critical. Returns the number of canary findings.
+pub fn trip_alert(db: &SecretDatabase, alert: &mut RealTimeSecretAlert) -> Result<usize> {
+    let canaries = db.active_canaries()?;
+    let mut tripped = 0;
+    for finding in alert.secrets_found.iter_mut() {
+        let Some(canary) = canaries.iter().find(|c| c.matches(&finding.matched_text)) else {
+            continue;
+        };
+        db.record_canary_trip(&canary.id, &alert.repository, Some(&alert.commit_sha), Some(&finding.filename))?;
+        finding.detector_name = canary_detector(canary, &finding.detector_name);
+        finding.severity = SecretSeverity::Critical;
+        tripped += 1;
+    }
+    if tripped > 0 {
+        alert.alert_severity = AlertSeverity::Critical;
+    }
+    Ok(tripped)
+}
+
+/// Record the canaries among a scan's findings and build a Critical alert for them.
+/// Suppressed findings count too: an allowlist rule must not hide a canary.
+pub fn trip_findings(db: &SecretDatabase, repository: &str, secrets: &[SecretMatch]) -> Result<Option<RealTimeSecretAlert>> {
+    let canaries = db.active_canaries()?;
+    if canaries.is_empty() {
+        return Ok(None);
+    }
+    let mut found = Vec::new();
+    let mut commit_sha = None;
+    for secret in secrets {
+        let Some(canary) = canaries.iter().find(|c| c.matches(&secret.matched_text)) else {
+            continue;
+        };
+        db.record_canary_trip(&canary.id, repository, secret.commit_sha.as_deref(), secret.filename.as_deref())?;
+        commit_sha = commit_sha.or(secret.commit_sha.clone());
+        found.push(RealTimeSecretMatch {
+            detector_name: canary_detector(canary, &secret.detector_name),
+            matched_text: secret.matched_text.clone(),
+            hash: secret.hash.clone(),
+            line_number: secret.line_number.map(|l| l as u32),
+            filename: secret.filename.clone().unwrap_or_default(),
+            severity: SecretSeverity::Critical,
+        });
+    }
+    if found.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(RealTimeSecretAlert {
+        event_id: format!("canary-{}", Uuid::new_v4()),
+        repository: repository.to_string(),
+        commit_sha: commit_sha.unwrap_or_default(),
+        secrets_found: found,
+        alert_severity: AlertSeverity::Critical,
+        detection_time: Utc::now(),
+        triage_result: None,
+        spread: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::SecretScanner;
+
+    #[test]
+    fn test_seeded_canaries_are_detected() {
+        let scanner = SecretScanner::new();
+        let aws = generate_local(CanaryKind::AwsKeys, "deploy bucket");
+        let text = seed_text(&aws, SeedFormat::Env).unwrap();
+        let found = scanner.scan_text(&text, Some(".env"));
+        assert!(found.iter().any(|m| m.detector_name == "AWS Access Key ID" && aws.matches(&m.matched_text)));
+
+        let github = generate_local(CanaryKind::GithubToken, "ci");
+        let found = scanner.scan_text(&seed_text(&github, SeedFormat::Env).unwrap(), Some(".env"));
+        assert!(found.iter().any(|m| github.matches(&m.matched_text)));
+        assert!(!github.matches("ghp_somethingelse"));
+        assert!(seed_text(&github, SeedFormat::AwsCredentials).is_err());
+    }
+
+    #[test]
+    fn test_random_strings_cover_the_alphabet_evenly() {
+        let sample = random_string(BASE62, 62 * 2000);
+        assert!(sample.bytes().all(|b| BASE62.contains(&b)));
+        for c in BASE62 {
+            // 2000 expected per character, give or take 45; reducing random bytes modulo 62
+            // would give the first 8 characters about 2420
+            let count = sample.bytes().filter(|b| b == c).count();
+            assert!((1780..=2220).contains(&count), "{} appeared {} times", *c as char, count);
+        }
+        assert_eq!(random_string(BASE32, 16).len(), 16);
+        assert_ne!(random_string(BASE64, 40), random_string(BASE64, 40));
+    }
+
+    #[test]
+    fn test_trip_alert_flags_only_canaries() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let canary = generate_local(CanaryKind::AwsKeys, "honeypot");
+        db.save_canary(&canary).unwrap();
+
+        let mut alert = crate::notifications::sample_alert();
+        alert.alert_severity = AlertSeverity::Medium;
+        let mut tripped = alert.secrets_found[0].clone();
+        tripped.matched_text = canary.key_id.clone().unwrap();
+        alert.secrets_found.push(tripped);
+
+        assert_eq!(trip_alert(&db, &mut alert).unwrap(), 1);
+        assert!(matches!(alert.alert_severity, AlertSeverity::Critical));
+        assert!(alert.secrets_found[1].detector_name.starts_with("CANARY honeypot"));
+        assert_eq!(alert.secrets_found[0].detector_name, "AWS Access Key ID");
+        assert_eq!(db.canary(&canary.id).unwrap().unwrap().trips, 1);
+
+        db.retire_canary(&canary.id).unwrap();
+        assert_eq!(trip_alert(&db, &mut alert).unwrap(), 0);
+    }
+}
@@ -27,9 +27,12 @@ impl CliApp {
             Some(("server", sub_matches)) => {
                 tokio::runtime::Runtime::new()?.block_on(app.run_server(sub_matches))
             }
-            Some(("scraper", sub_matches)) => {
-                tokio::runtime::Runtime::new()?.block_on(app.run_scraper(sub_matches))
-            }
+            Some(("scraper", sub_matches)) => match sub_matches.subcommand() {
+                Some(("gaps", gap_matches)) => {
+                    tokio::runtime::Runtime::new()?.block_on(app.check_gaps(gap_matches))
+                }
+                _ => tokio::runtime::Runtime::new()?.block_on(app.run_scraper(sub_matches)),
+            },
             Some(("process", sub_matches)) => {
                 tokio::runtime::Runtime::new()?.block_on(app.process_file(sub_matches))
             }
@@ -73,6 +76,12 @@ impl CliApp {
                             .help("Host to bind the server to")
                             .default_value("0.0.0.0")
                     )
+                    .arg(
+                        Arg::new("read-only")
+                            .long("read-only")
+                            .help("Refuse every change and open the findings database read-only (also WEB_READ_ONLY=true)")
+                            .action(clap::ArgAction::SetTrue)
+                    )
             )
             .subcommand(
                 Command::new("scraper")
@@ -91,6 +100,40 @@ impl CliApp {
                             .value_name("COUNT")
                             .help("Maximum number of files to process")
                     )
+                    .subcommand(
+                        Command::new("gaps")
+                            .about("Report missing GH Archive hours and queue them for backfill")
+                            .arg(
+                                Arg::new("since")
+                                    .long("since")
+                                    .value_name("YYYY-MM-DD")
+                                    .help("First day to check (default: earliest processed hour)")
+                            )
+                            .arg(
+                                Arg::new("until")
+                                    .long("until")
+                                    .value_name("YYYY-MM-DD")
+                                    .help("Last day to check, inclusive (default: two hours ago)")
+                            )
+                            .arg(
+                                Arg::new("no-queue")
+                                    .long("no-queue")
+                                    .help("Only report gaps, do not queue backfills")
+                                    .action(clap::ArgAction::SetTrue)
+                            )
+                            .arg(
+                                Arg::new("backfill")
+                                    .long("backfill")
+                                    .help("Download and process the queued hours now")
+                                    .action(clap::ArgAction::SetTrue)
+                            )
+                            .arg(
+                                Arg::new("json")
+                                    .long("json")
+                                    .help("Print the gap report as JSON")
+                                    .action(clap::ArgAction::SetTrue)
+                            )
+                    )
             )
             .subcommand(
                 Command::new("process")
@@ -144,14 +187,18 @@ impl CliApp {
         if let Some(host) = matches.get_one::<String>("host") {
             self.config.web.host = host.clone();
         }
+        if matches.get_flag("read-only") {
+            self.config.web.read_only = true;
+        }
 
         info!("Server configuration:");
         info!("  Host: {}", self.config.web.host);
         info!("  Port: {}", self.config.web.port);
         info!("  Database: {}:{}", self.config.database.host, self.config.database.port);
+        info!("  Read-only: {}", self.config.web.read_only);
 
         // Start the API server
-        let server = ApiServer::new(self.config.clone());
+        let server = ApiServer::new(self.config.clone())?;
         server.start().await?;
 
         Ok(())
@@ -206,6 +253,47 @@ impl CliApp {
         Ok(())
     }
 
+    async fn check_gaps(&mut self, matches: &ArgMatches) -> Result<()> {
+        let parse_day = |name: &str| -> Result<Option<chrono::NaiveDate>> {
+            matches
+                .get_one::<String>(name)
+                .map(|s| {
+                    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                        .map_err(|e| anyhow::anyhow!("Invalid --{} date {}: {}", name, s, e))
+                })
+                .transpose()
+        };
+        let start = parse_day("since")?.and_then(|d| d.and_hms_opt(0, 0, 0)).map(|t| t.and_utc());
+        let end = parse_day("until")?.and_then(|d| d.and_hms_opt(23, 0, 0)).map(|t| t.and_utc());
+
+        let mut main_scraper = MainScraper::new(self.config.clone())?;
+        main_scraper.initialize().await?;
+
+        let report = main_scraper.find_gaps(start, end).await?;
+        if matches.get_flag("json") {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            info!("Archive completeness {} to {}:", report.start, report.end);
+            info!("  Expected hours: {}", report.expected_hours);
+            info!("  Processed hours: {}", report.processed_hours);
+            info!("  Missing hours: {}", report.missing_hours);
+            for gap in &report.gaps {
+                info!("    {} .. {} ({} hours)", gap.start.format("%Y-%m-%d %H:00"), gap.end.format("%Y-%m-%d %H:00"), gap.hours);
+            }
+        }
+
+        if !report.is_complete() && !matches.get_flag("no-queue") {
+            main_scraper.queue_backfills(&report).await?;
+        }
+        if matches.get_flag("backfill") {
+            let summary = main_scraper.run_backfills(i64::MAX).await?;
+            info!("Backfilled {} hours ({} failed)", summary.processed, summary.failed);
+        }
+
+        main_scraper.shutdown().await?;
+        Ok(())
+    }
+
     async fn process_file(&mut self, matches: &ArgMatches) -> Result<()> {
         let filename = matches.get_one::<String>("file").unwrap();
         
@@ -354,6 +442,8 @@ impl CliApp {
         info!("Starting GitHub Archive Scraper v2.0.0 (Full Mode)");
         info!("This will run both the API server and the scraper");
 
+        let server = ApiServer::new(self.config.clone())?;
+
         // Initialize main scraper
         let mut main_scraper = MainScraper::new(self.config.clone())?;
         main_scraper.initialize().await?;
@@ -369,7 +459,6 @@ impl CliApp {
         };
 
         // Start API server
-        let server = ApiServer::new(self.config.clone());
         let server_handle = tokio::spawn(async move {
             if let Err(e) = server.start().await {
                 error!("Server error: {}", e);
@@ -0,0 +1,164 @@
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+
+use super::config::PrivacyConfig;
+use super::database::ValidatedEvent;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Payload objects whose `name` field is a person's name (commit authors, pushers, ...)
+const PERSON_OBJECTS: &[&str] = &["author", "committer", "pusher", "sender", "user", "actor"];
+
+/// Replaces actor logins, emails and names with keyed pseudonyms at ingest.
+/// The same input always maps to the same pseudonym under one key, so events can still be
+/// correlated internally without storing the original value.
+#[derive(Clone)]
+pub struct ActorAnonymizer {
+    key: Vec<u8>,
+}
+
+impl ActorAnonymizer {
+    pub fn new(key: &str) -> Result<Self> {
+        if key.len() < 16 {
+            return Err(anyhow!("Anonymization key must be at least 16 characters"));
+        }
+        Ok(Self { key: key.as_bytes().to_vec() })
+    }
+
+    /// Build an anonymizer when anonymization is enabled in the privacy settings
+    pub fn from_config(privacy: &PrivacyConfig) -> Result<Option<Self>> {
+        if !privacy.anonymize_actors {
+            return Ok(None);
+        }
+        Self::new(&privacy.anonymization_key).map(Some)
+    }
+
+    fn mac(&self, value: &[u8]) -> [u8; 32] {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(value);
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&mac.finalize().into_bytes());
+        digest
+    }
+
+    /// Stable pseudonym for a login, email or name. Case-insensitive, as GitHub logins are.
+    pub fn pseudonym(&self, value: &str) -> String {
+        let digest = self.mac(value.trim().to_lowercase().as_bytes());
+        format!("anon_{}", hex::encode(&digest[..12]))
+    }
+
+    /// Stable positive pseudonymous id for a numeric account id
+    pub fn pseudonym_id(&self, id: i64) -> i64 {
+        let digest = self.mac(&id.to_be_bytes());
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        (u64::from_be_bytes(bytes) >> 1) as i64
+    }
+
+    /// Anonymize the actor and every login/email/name in the payload and raw event.
+    /// Profile URLs embed the login, so they are dropped rather than hashed.
+    pub fn anonymize_event(&self, event: &mut ValidatedEvent) {
+        let actor = &mut event.actor;
+        actor.id = actor.id.map(|id| self.pseudonym_id(id));
+        actor.login = actor.login.as_deref().map(|login| self.pseudonym(login));
+        actor.display_login = actor.display_login.as_deref().map(|login| self.pseudonym(login));
+        actor.node_id = None;
+        actor.gravatar_id = None;
+        actor.url = None;
+        actor.avatar_url = None;
+        actor.html_url = None;
+        actor.followers_url = None;
+        actor.following_url = None;
+        actor.gists_url = None;
+        actor.starred_url = None;
+        actor.subscriptions_url = None;
+        actor.organizations_url = None;
+        actor.repos_url = None;
+        actor.events_url = None;
+        actor.received_events_url = None;
+
+        self.anonymize_value(&mut event.payload, None);
+        self.anonymize_value(&mut event.raw_event, None);
+    }
+
+    fn anonymize_value(&self, value: &mut Value, parent_key: Option<&str>) {
+        match value {
+            Value::Object(map) => {
+                let is_person = parent_key.map_or(false, |k| PERSON_OBJECTS.contains(&k));
+                for (key, child) in map.iter_mut() {
+                    match (key.as_str(), child) {
+                        ("email" | "login" | "display_login", Value::String(s)) => {
+                            *s = self.pseudonym(s);
+                        }
+                        ("name", Value::String(s)) if is_person => {
+                            *s = self.pseudonym(s);
+                        }
+                        ("id", Value::Number(n)) if is_person => {
+                            if let Some(id) = n.as_i64() {
+                                *n = self.pseudonym_id(id).into();
+                            }
+                        }
+                        (key, child) if is_person && (key.ends_with("url") || key == "gravatar_id" || key == "node_id") => {
+                            *child = Value::Null;
+                        }
+                        (key, child) => self.anonymize_value(child, Some(key)),
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.anonymize_value(item, parent_key);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const KEY: &str = "test-anonymization-key";
+
+    #[test]
+    fn test_pseudonyms_are_stable_and_keyed() {
+        let a = ActorAnonymizer::new(KEY).unwrap();
+        let b = ActorAnonymizer::new("another-anonymization-key").unwrap();
+
+        assert_eq!(a.pseudonym("Octocat"), a.pseudonym("octocat"));
+        assert_ne!(a.pseudonym("octocat"), b.pseudonym("octocat"));
+        assert!(a.pseudonym("octocat").starts_with("anon_"));
+        assert!(a.pseudonym_id(583231) >= 0);
+    }
+
+    #[test]
+    fn test_short_key_rejected() {
+        assert!(ActorAnonymizer::new("short").is_err());
+    }
+
+    #[test]
+    fn test_payload_emails_and_names_are_hashed() {
+        let anonymizer = ActorAnonymizer::new(KEY).unwrap();
+        let mut payload = json!({
+            "ref": "refs/heads/main",
+            "commits": [{
+                "sha": "abc123",
+                "author": { "name": "Mona Lisa", "email": "mona@example.com" },
+                "message": "fix"
+            }],
+            "pusher": { "name": "mona", "email": "mona@example.com" }
+        });
+
+        anonymizer.anonymize_value(&mut payload, None);
+        let serialized = payload.to_string();
+
+        assert!(!serialized.contains("mona@example.com"));
+        assert!(!serialized.contains("Mona Lisa"));
+        assert_eq!(payload["commits"][0]["sha"], "abc123");
+        assert_eq!(payload["commits"][0]["author"]["email"], payload["pusher"]["email"]);
+    }
+}
@@ -0,0 +1,70 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde_json::json;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::config::LoggingConfig;
+
+/// Append-only JSON-lines audit trail for administrative actions (purges, erasures, ...)
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self { path: path.as_ref().to_path_buf() }
+    }
+
+    pub fn from_config(logging: &LoggingConfig) -> Self {
+        Self::new(logging.audit_log_path())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one audit entry
+    pub fn record(&self, action: &str, actor: &str, details: serde_json::Value) -> Result<()> {
+        let entry = json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "action": action,
+            "actor": actor,
+            "details": details,
+        });
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open audit log {}", self.path.display()))?;
+        writeln!(file, "{}", entry)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_appends_json_lines() {
+        let path = std::env::temp_dir().join(format!("audit-{}.log", uuid::Uuid::new_v4()));
+        let audit = AuditLog::new(&path);
+
+        audit.record("purge", "scheduler", json!({ "findings_deleted": 2 })).unwrap();
+        audit.record("purge", "cli", json!({ "findings_deleted": 0 })).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = contents.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["action"], "purge");
+        assert_eq!(lines[1]["actor"], "cli");
+
+        std::fs::remove_file(&path).ok();
+    }
+}
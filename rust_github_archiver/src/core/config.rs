@@ -1,10 +1,14 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveTime, Utc};
 use config::{Config as ConfigBuilder, Environment, File};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::path::PathBuf;
 use tracing::{error, info, warn};
 
+use crate::sinks::RedactionProfile;
+
 /// Database configuration settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseConfig {
@@ -109,6 +113,46 @@ pub struct DownloadConfig {
     pub max_retries: u32,
     pub retry_delay: f64,
     pub batch_size: u32,
+    /// Bandwidth cap shared by all concurrent downloads; `None` is unlimited
+    #[serde(default)]
+    pub max_bytes_per_second: Option<u64>,
+    /// Local times downloads may start in, e.g. `22:00-06:00` to only backfill at night;
+    /// empty means any time
+    #[serde(default)]
+    pub download_windows: Vec<DownloadWindow>,
+}
+
+/// Whether `time` falls in the daily window from `start` (inclusive) to `end` (exclusive).
+/// A window whose end is before its start wraps past midnight, as in 22:00-06:00.
+pub fn in_daily_window(time: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start <= end {
+        time >= start && time < end
+    } else {
+        time >= start || time < end
+    }
+}
+
+/// A daily window between two local times; may wrap past midnight
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DownloadWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl std::str::FromStr for DownloadWindow {
+    type Err = anyhow::Error;
+
+    /// `HH:MM-HH:MM`
+    fn from_str(s: &str) -> Result<Self> {
+        let (start, end) = s
+            .split_once('-')
+            .with_context(|| format!("Download window {:?} is not HH:MM-HH:MM", s))?;
+        let parse = |t: &str| {
+            NaiveTime::parse_from_str(t.trim(), "%H:%M")
+                .with_context(|| format!("Invalid time {:?} in download window {:?}", t, s))
+        };
+        Ok(Self { start: parse(start)?, end: parse(end)? })
+    }
 }
 
 impl Default for DownloadConfig {
@@ -150,10 +194,1088 @@ impl Default for DownloadConfig {
                 .unwrap_or_else(|_| "500".to_string())
                 .parse()
                 .unwrap_or(500),
+            max_bytes_per_second: env::var("DOWNLOAD_RATE_LIMIT_KBPS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|&kbps| kbps > 0)
+                .map(|kbps| kbps * 1024),
+            download_windows: env::var("DOWNLOAD_WINDOWS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|w| !w.is_empty())
+                .filter_map(|w| match w.parse() {
+                    Ok(window) => Some(window),
+                    Err(e) => {
+                        warn!("Ignoring download window: {}", e);
+                        None
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Shared HTTP client configuration (timeouts, TLS, pooling and retries)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpConfig {
+    pub connect_timeout: u64,
+    pub request_timeout: u64,
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout: u64,
+    pub max_retries: u32,
+    pub retry_base_delay_ms: u64,
+    pub retry_max_delay_ms: u64,
+    pub ca_bundle_path: Option<PathBuf>,
+    pub accept_invalid_certs: bool,
+    pub user_agent: String,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: env::var("HTTP_CONNECT_TIMEOUT")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            request_timeout: env::var("HTTP_REQUEST_TIMEOUT")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            pool_max_idle_per_host: env::var("HTTP_POOL_MAX_IDLE")
+                .unwrap_or_else(|_| "16".to_string())
+                .parse()
+                .unwrap_or(16),
+            pool_idle_timeout: env::var("HTTP_POOL_IDLE_TIMEOUT")
+                .unwrap_or_else(|_| "90".to_string())
+                .parse()
+                .unwrap_or(90),
+            max_retries: env::var("HTTP_MAX_RETRIES")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3),
+            retry_base_delay_ms: env::var("HTTP_RETRY_BASE_DELAY_MS")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()
+                .unwrap_or(500),
+            retry_max_delay_ms: env::var("HTTP_RETRY_MAX_DELAY_MS")
+                .unwrap_or_else(|_| "30000".to_string())
+                .parse()
+                .unwrap_or(30000),
+            ca_bundle_path: env::var("HTTP_CA_BUNDLE").ok().map(PathBuf::from),
+            accept_invalid_certs: env::var("HTTP_ACCEPT_INVALID_CERTS")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase()
+                == "true",
+            user_agent: env::var("HTTP_USER_AGENT")
+                .unwrap_or_else(|_| "GitHubArchiver/2.0".to_string()),
+        }
+    }
+}
+
+/// External sinks that receive findings (DefectDojo, Splunk HEC, ...).
+/// Targets are only configurable from the config file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinksConfig {
+    pub batch_size: usize,
+    /// Role whose redaction profile applies to everything sent to sinks
+    #[serde(default = "default_sink_redaction_role")]
+    pub redaction_role: String,
+    pub targets: Vec<SinkTarget>,
+    /// Issue trackers that get one ticket per verified finding
+    #[serde(default)]
+    pub tickets: Vec<TicketTarget>,
+}
+
+impl Default for SinksConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: env::var("SINK_BATCH_SIZE")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100),
+            redaction_role: default_sink_redaction_role(),
+            targets: Vec::new(),
+            tickets: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkTarget {
+    /// DefectDojo `import-scan` API using the Generic Findings Import format
+    DefectDojo {
+        url: String,
+        api_key: String,
+        engagement_id: u64,
+        #[serde(default = "default_defectdojo_test_title")]
+        test_title: String,
+        #[serde(default)]
+        minimum_severity: Option<String>,
+    },
+    /// Splunk HTTP Event Collector
+    SplunkHec {
+        url: String,
+        token: String,
+        #[serde(default)]
+        index: Option<String>,
+        #[serde(default = "default_splunk_source")]
+        source: String,
+        #[serde(default = "default_splunk_sourcetype")]
+        sourcetype: String,
+    },
+    /// MISP event push for validated findings
+    Misp {
+        url: String,
+        api_key: String,
+        /// MISP distribution level (0 = organisation only)
+        #[serde(default)]
+        distribution: u8,
+    },
+    /// Elasticsearch or OpenSearch `_bulk` indexer for findings and alerts
+    Elasticsearch {
+        url: String,
+        #[serde(default = "default_elasticsearch_index")]
+        index: String,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+        #[serde(default)]
+        api_key: Option<String>,
+    },
+}
+
+/// Where tickets for verified findings are opened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TicketTarget {
+    /// Jira Cloud or Server, through the v2 REST API with an API token
+    Jira {
+        url: String,
+        email: String,
+        api_token: String,
+        project: String,
+        #[serde(default = "default_jira_issue_type")]
+        issue_type: String,
+        /// Least severe finding to open a ticket for (default High)
+        #[serde(default)]
+        minimum_severity: Option<String>,
+    },
+    /// Issues in a (private) triage repository
+    GithubIssues {
+        /// `owner/name`
+        repository: String,
+        token: String,
+        #[serde(default)]
+        labels: Vec<String>,
+        /// For GitHub Enterprise; defaults to https://api.github.com
+        #[serde(default)]
+        api_url: Option<String>,
+        #[serde(default)]
+        minimum_severity: Option<String>,
+    },
+}
+
+/// Retention windows for the findings database, in days. Zero disables a rule.
+/// Findings under legal hold are never purged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Clear raw context hashes after this many days
+    pub context_days: u32,
+    /// Clear stored secret value hashes of validated findings after this many days
+    pub validated_secret_days: u32,
+    /// Delete resolved or revoked findings this many days after their last update
+    pub resolved_finding_days: u32,
+    /// Hours between scheduled purge runs (0 disables the scheduled job)
+    pub purge_interval_hours: u64,
+    /// Apply `finding_action` to any finding this many days after it was found
+    #[serde(default = "default_retention_finding_days")]
+    pub finding_days: u32,
+    #[serde(default = "default_retention_finding_action")]
+    pub finding_action: RetentionAction,
+    /// Delete unverified Low severity findings after this many days
+    #[serde(default = "default_retention_low_unverified_days")]
+    pub low_unverified_days: u32,
+    /// Delete triage results, notes, attachments, tags and search matches left behind by
+    /// findings that no longer exist
+    #[serde(default = "default_retention_orphans")]
+    pub orphans: bool,
+    /// Per-category overrides of `finding_days` and `finding_action`, keyed by category
+    /// (`cloud_provider`, `token`, ...)
+    #[serde(default)]
+    pub categories: BTreeMap<String, CategoryRetention>,
+}
+
+/// What an age-based retention rule does to an expired finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionAction {
+    Delete,
+    /// Scrub it the way an erasure does, keeping it in the statistics
+    Anonymize,
+}
+
+impl std::str::FromStr for RetentionAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "delete" => Ok(RetentionAction::Delete),
+            "anonymize" | "anonymise" => Ok(RetentionAction::Anonymize),
+            _ => Err(anyhow!("Unknown retention action {}; use delete or anonymize", s)),
+        }
+    }
+}
+
+/// Retention for the findings of one category
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryRetention {
+    /// 0 keeps the category's findings indefinitely
+    pub days: u32,
+    /// Defaults to the policy's `finding_action`
+    #[serde(default)]
+    pub action: Option<RetentionAction>,
+}
+
+fn default_retention_finding_days() -> u32 {
+    env::var("RETENTION_FINDING_DAYS").ok().and_then(|v| v.parse().ok()).unwrap_or(0)
+}
+
+fn default_retention_finding_action() -> RetentionAction {
+    env::var("RETENTION_FINDING_ACTION").ok().and_then(|v| v.parse().ok()).unwrap_or(RetentionAction::Anonymize)
+}
+
+fn default_retention_low_unverified_days() -> u32 {
+    env::var("RETENTION_LOW_UNVERIFIED_DAYS").ok().and_then(|v| v.parse().ok()).unwrap_or(0)
+}
+
+fn default_retention_orphans() -> bool {
+    env::var("RETENTION_PURGE_ORPHANS").map(|v| v != "false").unwrap_or(true)
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            context_days: env::var("RETENTION_CONTEXT_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            validated_secret_days: env::var("RETENTION_VALIDATED_SECRET_DAYS")
+                .unwrap_or_else(|_| "90".to_string())
+                .parse()
+                .unwrap_or(90),
+            resolved_finding_days: env::var("RETENTION_RESOLVED_FINDING_DAYS")
+                .unwrap_or_else(|_| "365".to_string())
+                .parse()
+                .unwrap_or(365),
+            purge_interval_hours: env::var("RETENTION_PURGE_INTERVAL_HOURS")
+                .unwrap_or_else(|_| "24".to_string())
+                .parse()
+                .unwrap_or(24),
+            finding_days: default_retention_finding_days(),
+            finding_action: default_retention_finding_action(),
+            low_unverified_days: default_retention_low_unverified_days(),
+            orphans: default_retention_orphans(),
+            categories: BTreeMap::new(),
+        }
+    }
+}
+
+/// Scheduled online maintenance of the findings and archive databases
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceConfig {
+    /// Hours between maintenance runs (0 disables the scheduled job)
+    pub interval_hours: u64,
+    /// Free pages returned to the file system per run by `PRAGMA incremental_vacuum`
+    pub vacuum_pages: u64,
+    /// Rows ingested since the last ANALYZE that trigger another one
+    pub analyze_after_rows: u64,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            interval_hours: env::var("MAINTENANCE_INTERVAL_HOURS")
+                .unwrap_or_else(|_| "6".to_string())
+                .parse()
+                .unwrap_or(6),
+            vacuum_pages: env::var("MAINTENANCE_VACUUM_PAGES")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .unwrap_or(1000),
+            analyze_after_rows: env::var("MAINTENANCE_ANALYZE_AFTER_ROWS")
+                .unwrap_or_else(|_| "50000".to_string())
+                .parse()
+                .unwrap_or(50000),
+        }
+    }
+}
+
+/// Usage limits of one workspace; 0 leaves a resource unlimited
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaLimits {
+    /// GitHub API requests per UTC day
+    pub github_api_calls_per_day: u64,
+    /// Bytes processed by BigQuery scans per UTC calendar month
+    pub bigquery_bytes_per_month: u64,
+    /// AI triage tokens per UTC day
+    pub ai_tokens_per_day: u64,
+    /// Findings kept in the findings database
+    pub findings_stored: u64,
+}
+
+impl Default for QuotaLimits {
+    fn default() -> Self {
+        Self {
+            github_api_calls_per_day: env::var("QUOTA_GITHUB_CALLS_PER_DAY")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            bigquery_bytes_per_month: env::var("QUOTA_BIGQUERY_BYTES_PER_MONTH")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            ai_tokens_per_day: env::var("QUOTA_AI_TOKENS_PER_DAY")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            findings_stored: env::var("QUOTA_FINDINGS_STORED")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Which workspace this process bills its usage to, and each workspace's limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaConfig {
+    pub workspace: String,
+    /// Limits of workspaces without an entry in `workspaces`
+    #[serde(default)]
+    pub limits: QuotaLimits,
+    /// Per-workspace limits; only configurable from the config file
+    #[serde(default)]
+    pub workspaces: HashMap<String, QuotaLimits>,
+}
+
+impl Default for QuotaConfig {
+    fn default() -> Self {
+        Self {
+            workspace: env::var("WORKSPACE").unwrap_or_else(|_| "default".to_string()),
+            limits: QuotaLimits::default(),
+            workspaces: HashMap::new(),
+        }
+    }
+}
+
+impl QuotaConfig {
+    pub fn limits_for(&self, workspace: &str) -> &QuotaLimits {
+        self.workspaces.get(workspace).unwrap_or(&self.limits)
+    }
+}
+
+/// Canarytokens service that `canary create --provider canarytokens` generates decoy
+/// credentials with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryConfig {
+    pub service_url: String,
+    /// The service itself alerts this address when a canary is used; it needs an email
+    /// address or a webhook
+    pub alert_email: Option<String>,
+    pub alert_webhook: Option<String>,
+}
+
+impl Default for CanaryConfig {
+    fn default() -> Self {
+        Self {
+            service_url: env::var("CANARYTOKENS_URL").unwrap_or_else(|_| "https://canarytokens.org".to_string()),
+            alert_email: env::var("CANARY_ALERT_EMAIL").ok().filter(|e| !e.is_empty()),
+            alert_webhook: env::var("CANARY_ALERT_WEBHOOK").ok().filter(|w| !w.is_empty()),
+        }
+    }
+}
+
+/// Cost guards for BigQuery queries over GH Archive, whose `month.*` tables hold terabytes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BigQueryConfig {
+    /// A query that would process more bytes than this is refused before it runs, and
+    /// BigQuery is told to fail it rather than bill more; 0 means no limit
+    pub max_bytes_billed: u64,
+    /// On-demand price in USD per TiB processed, for cost estimates
+    pub price_per_tib_usd: f64,
+    /// Rows fetched per result page; large scans are read a page at a time
+    #[serde(default = "default_bigquery_page_size")]
+    pub page_size: u32,
+}
+
+impl Default for BigQueryConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes_billed: env::var("BIGQUERY_MAX_BYTES_BILLED").ok().and_then(|v| v.parse().ok()).unwrap_or(0),
+            price_per_tib_usd: env::var("BIGQUERY_PRICE_PER_TIB_USD").ok().and_then(|v| v.parse().ok()).unwrap_or(6.25),
+            page_size: default_bigquery_page_size(),
+        }
+    }
+}
+
+/// Short-lived results of dashboard aggregates and facets, shared by every open dashboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryCacheConfig {
+    /// Seconds a cached result is served before it is recomputed, even when nothing changed
+    /// (0 disables the cache)
+    pub ttl_seconds: u64,
+    /// Results kept at once; the oldest is dropped to make room
+    pub max_entries: usize,
+}
+
+impl Default for QueryCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_seconds: env::var("QUERY_CACHE_TTL_SECONDS").ok().and_then(|v| v.parse().ok()).unwrap_or(30),
+            max_entries: env::var("QUERY_CACHE_MAX_ENTRIES").ok().and_then(|v| v.parse().ok()).unwrap_or(256),
+        }
+    }
+}
+
+/// Forecast of database and cache growth against free disk space
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskForecastConfig {
+    /// Minutes between disk usage samples (0 disables forecasting)
+    pub sample_interval_minutes: u64,
+    /// Hours of samples the growth rate is fitted over
+    pub window_hours: u64,
+    /// Warn when the disk is projected to fill within this many days
+    pub warn_days: f64,
+    /// Run a retention purge and an incremental vacuum when the warning fires
+    pub auto_purge: bool,
+}
+
+impl Default for DiskForecastConfig {
+    fn default() -> Self {
+        Self {
+            sample_interval_minutes: env::var("DISK_FORECAST_INTERVAL_MINUTES")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            window_hours: env::var("DISK_FORECAST_WINDOW_HOURS")
+                .unwrap_or_else(|_| "72".to_string())
+                .parse()
+                .unwrap_or(72),
+            warn_days: env::var("DISK_FORECAST_WARN_DAYS")
+                .unwrap_or_else(|_| "7".to_string())
+                .parse()
+                .unwrap_or(7.0),
+            auto_purge: env::var("DISK_FORECAST_AUTO_PURGE")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Privacy settings for the archive store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivacyConfig {
+    /// Replace actor logins, emails and names with keyed pseudonyms at ingest
+    pub anonymize_actors: bool,
+    /// HMAC key for pseudonyms; keep it stable or correlation across ingests breaks
+    pub anonymization_key: String,
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        Self {
+            anonymize_actors: env::var("ARCHIVE_ANONYMIZE_ACTORS")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase()
+                == "true",
+            anonymization_key: env::var("ARCHIVE_ANONYMIZATION_KEY").unwrap_or_default(),
+        }
+    }
+}
+
+/// Which GH Archive events are written to Postgres. Restricting types and payload fields
+/// cuts storage sharply for deployments that only hunt secrets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngestConfig {
+    /// Event types to store (e.g. `PushEvent`); empty stores every type
+    pub event_types: Vec<String>,
+    /// Payload fields kept per event type as dotted paths (`commits.sha`); types not listed
+    /// keep their full payload. Only configurable from the config file.
+    pub payload_fields: HashMap<String, Vec<String>>,
+    /// Keep the original event JSON next to the parsed columns
+    pub store_raw_event: bool,
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self {
+            event_types: env::var("INGEST_EVENT_TYPES")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            payload_fields: HashMap::new(),
+            store_raw_event: env::var("INGEST_STORE_RAW_EVENT")
+                .unwrap_or_else(|_| "true".to_string())
+                .to_lowercase()
+                == "true",
+        }
+    }
+}
+
+/// Scratch space for repository clones: one directory per scan, removed when the scan ends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanWorkspaceConfig {
+    /// Directory the per-scan directories are created in
+    pub root: PathBuf,
+    /// A scan whose directory grows past this many MB is stopped; 0 means no cap
+    pub max_scan_mb: u64,
+    /// New scans are refused while all workspaces together use this many MB; 0 means no cap
+    pub max_total_mb: u64,
+}
+
+impl Default for ScanWorkspaceConfig {
+    fn default() -> Self {
+        Self {
+            root: env::var("SCAN_WORKSPACE_ROOT")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| super::paths::cache_dir().join("workspaces")),
+            max_scan_mb: env::var("SCAN_WORKSPACE_MAX_MB").ok().and_then(|v| v.parse().ok()).unwrap_or(4096),
+            max_total_mb: env::var("SCAN_WORKSPACE_TOTAL_MB").ok().and_then(|v| v.parse().ok()).unwrap_or(0),
         }
     }
 }
 
+/// How a compressed column's values are stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageCodec {
+    /// Plain text
+    None,
+    /// Fast enough for rows read back within seconds
+    Lz4,
+    /// Smaller, for rows mostly kept around
+    Zstd,
+}
+
+impl std::str::FromStr for StorageCodec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "none" | "off" => Ok(StorageCodec::None),
+            "lz4" => Ok(StorageCodec::Lz4),
+            "zstd" => Ok(StorageCodec::Zstd),
+            _ => Err(anyhow!("Unknown storage codec {}; use none, lz4 or zstd", s)),
+        }
+    }
+}
+
+/// Language of alert, digest and report text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+    De,
+}
+
+impl Locale {
+    /// BCP 47 language tag
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+            Locale::De => "de",
+        }
+    }
+}
+
+impl std::str::FromStr for Locale {
+    type Err = anyhow::Error;
+
+    /// Accepts a region or encoding suffix, as in `de-AT` or `es_ES.UTF-8`
+    fn from_str(s: &str) -> Result<Self> {
+        let language = s.split(['-', '_', '.']).next().unwrap_or_default().to_lowercase();
+        match language.as_str() {
+            "en" => Ok(Locale::En),
+            "es" => Ok(Locale::Es),
+            "de" => Ok(Locale::De),
+            _ => Err(anyhow!("Unsupported locale {}; use en, es or de", s)),
+        }
+    }
+}
+
+/// An accessibility adjustment to CLI, GUI and notification output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputProfile {
+    /// Leave out emoji and Slack emoji codes
+    NoEmoji,
+    /// Severity and status colors from the Okabe-Ito palette, told apart with any color vision
+    ColorblindSafe,
+    /// Plain ASCII symbols and no terminal colors, for screen readers and old terminals; implies `no-emoji`
+    Ascii,
+}
+
+impl std::str::FromStr for OutputProfile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "no-emoji" => Ok(OutputProfile::NoEmoji),
+            "colorblind-safe" | "colorblind" => Ok(OutputProfile::ColorblindSafe),
+            "ascii" | "plain-ascii" => Ok(OutputProfile::Ascii),
+            _ => Err(anyhow!("Unknown output profile {}; use no-emoji, colorblind-safe or ascii", s)),
+        }
+    }
+}
+
+/// How severities and status markers are drawn; the profiles combine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputConfig {
+    pub profiles: Vec<OutputProfile>,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            profiles: env::var("OUTPUT_PROFILE")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|s| s.parse().ok())
+                .collect(),
+        }
+    }
+}
+
+/// Compression of large payload columns in the findings database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageCompressionConfig {
+    /// Store every column as plain text when false; compressed rows stay readable
+    pub enabled: bool,
+    /// Values shorter than this many bytes are stored as they are
+    pub min_bytes: usize,
+    pub zstd_level: i32,
+    /// Codec per `table.column`, overriding that column's default
+    #[serde(default)]
+    pub columns: BTreeMap<String, StorageCodec>,
+}
+
+impl Default for StorageCompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: env::var("STORAGE_COMPRESSION").map(|v| v != "false").unwrap_or(true),
+            min_bytes: env::var("STORAGE_COMPRESSION_MIN_BYTES").ok().and_then(|v| v.parse().ok()).unwrap_or(256),
+            zstd_level: env::var("STORAGE_ZSTD_LEVEL").ok().and_then(|v| v.parse().ok()).unwrap_or(3),
+            columns: BTreeMap::new(),
+        }
+    }
+}
+
+/// Air-gapped operation: every outbound request fails unless its host is explicitly allowed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfflineConfig {
+    pub enabled: bool,
+    /// GitHub Enterprise Server host (e.g. `github.corp.example`) used instead of api.github.com
+    pub github_host: Option<String>,
+    /// Further internal hosts that may be reached (sink endpoints, update mirror, archive mirror)
+    pub allowed_hosts: Vec<String>,
+}
+
+impl Default for OfflineConfig {
+    fn default() -> Self {
+        let allowed_hosts = env::var("OFFLINE_ALLOWED_HOSTS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        Self {
+            enabled: env::var("OFFLINE_MODE")
+                .unwrap_or_else(|_| "false".to_string())
+                .to_lowercase()
+                == "true",
+            github_host: env::var("OFFLINE_GITHUB_HOST").ok().filter(|h| !h.is_empty()),
+            allowed_hosts,
+        }
+    }
+}
+
+/// Hardening for the API listener: HTTPS with optional client certificates, address
+/// allowlists, and a second listener that alone serves the scraper controls
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// PEM certificate chain; with `tls_key` the API is served over HTTPS only
+    pub tls_cert: Option<PathBuf>,
+    pub tls_key: Option<PathBuf>,
+    /// PEM CA bundle; clients must then present a certificate it issued (mutual TLS)
+    pub client_ca: Option<PathBuf>,
+    /// Addresses or CIDR ranges allowed to connect, e.g. `10.0.0.0/8`; empty allows any
+    pub allowed_ips: Vec<String>,
+    /// `host:port` for the admin listener; when set, the scraper controls are served there
+    /// and nowhere else
+    pub admin_listen: Option<String>,
+    /// Allowlist for the admin listener; empty falls back to `allowed_ips`
+    pub admin_allowed_ips: Vec<String>,
+}
+
+impl ServerConfig {
+    pub fn from_env() -> Self {
+        let path = |name: &str| env::var(name).ok().filter(|v| !v.is_empty()).map(PathBuf::from);
+        let list = |name: &str| -> Vec<String> {
+            env::var(name)
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        };
+
+        Self {
+            tls_cert: path("API_TLS_CERT"),
+            tls_key: path("API_TLS_KEY"),
+            client_ca: path("API_CLIENT_CA"),
+            allowed_ips: list("API_ALLOWED_IPS"),
+            admin_listen: env::var("API_ADMIN_LISTEN").ok().filter(|v| !v.is_empty()),
+            admin_allowed_ips: list("API_ADMIN_ALLOWED_IPS"),
+        }
+    }
+}
+
+fn default_server_config() -> ServerConfig {
+    ServerConfig::from_env()
+}
+
+/// Single sign-on through an OpenID Connect provider such as Okta or Azure AD. Disabled
+/// unless `issuer` is set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OidcConfig {
+    /// Issuer URL; its `/.well-known/openid-configuration` names the other endpoints
+    pub issuer: Option<String>,
+    pub client_id: String,
+    /// Confidential clients only; public clients rely on PKCE alone
+    pub client_secret: Option<String>,
+    /// Where the provider sends users back: this server's `/api/auth/oidc/callback`
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    /// ID token claim listing the user's groups
+    pub groups_claim: String,
+    /// IdP group to role (admin, analyst or viewer); a member of several groups gets the
+    /// most privileged role
+    pub role_mappings: HashMap<String, String>,
+    /// Role for users in no mapped group; without it they cannot sign in
+    pub default_role: Option<String>,
+    /// Audience required of IdP access tokens sent as bearer tokens; defaults to `client_id`
+    pub audience: Option<String>,
+}
+
+impl OidcConfig {
+    pub fn from_env() -> Self {
+        let scopes = env::var("OIDC_SCOPES").unwrap_or_else(|_| "openid profile email".to_string());
+        let role_mappings = env::var("OIDC_ROLE_MAPPINGS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|mapping| mapping.split_once('='))
+            .map(|(group, role)| (group.trim().to_string(), role.trim().to_string()))
+            .filter(|(group, _)| !group.is_empty())
+            .collect();
+
+        Self {
+            issuer: env::var("OIDC_ISSUER").ok().filter(|v| !v.is_empty()),
+            client_id: env::var("OIDC_CLIENT_ID").unwrap_or_default(),
+            client_secret: env::var("OIDC_CLIENT_SECRET").ok().filter(|v| !v.is_empty()),
+            redirect_uri: env::var("OIDC_REDIRECT_URI").unwrap_or_default(),
+            scopes: scopes.split([' ', ',']).filter(|s| !s.is_empty()).map(str::to_string).collect(),
+            groups_claim: env::var("OIDC_GROUPS_CLAIM").unwrap_or_else(|_| "groups".to_string()),
+            role_mappings,
+            default_role: env::var("OIDC_DEFAULT_ROLE").ok().filter(|v| !v.is_empty()),
+            audience: env::var("OIDC_AUDIENCE").ok().filter(|v| !v.is_empty()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.issuer.is_some()
+    }
+}
+
+fn default_oidc_config() -> OidcConfig {
+    OidcConfig::from_env()
+}
+
+/// Slack and email alert channels; message bodies come from the notification templates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    /// Slack incoming webhook URL
+    pub slack_webhook_url: Option<String>,
+    /// Slack bot token (`xoxb-…`, `chat:write` scope) for direct messages to users who asked
+    /// for Slack alerts
+    #[serde(default)]
+    pub slack_bot_token: Option<String>,
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub email_from: Option<String>,
+    pub email_to: Vec<String>,
+    /// Alert webhooks; only configurable from the config file
+    #[serde(default)]
+    pub webhooks: Vec<WebhookTarget>,
+    /// Slack, Discord and Teams webhooks that get formatted messages; `DISCORD_WEBHOOK_URL`
+    /// and `TEAMS_WEBHOOK_URL` add one each
+    #[serde(default)]
+    pub chat: Vec<ChatTarget>,
+    /// PagerDuty services and Opsgenie teams paged for Critical findings; `PAGERDUTY_ROUTING_KEY`
+    /// and `OPSGENIE_API_KEY` add one each
+    #[serde(default)]
+    pub incidents: Vec<IncidentTarget>,
+    /// Quiet hours and maintenance windows; only configurable from the config file
+    #[serde(default)]
+    pub quiet_windows: Vec<QuietWindow>,
+    /// Send non-Critical email alerts as one hourly or daily digest instead of one message each
+    #[serde(default = "default_email_digest")]
+    pub email_digest: Option<DigestSchedule>,
+    /// Local hour (0-23) the daily digest is sent
+    #[serde(default = "default_email_digest_hour")]
+    pub email_digest_hour: u32,
+    /// Email a summary of each completed scan to `email_to`
+    #[serde(default = "default_email_scan_reports")]
+    pub email_scan_reports: bool,
+    /// Directory with `slack.tera`, `email_subject.tera`, `email_body.tera`, `webhook.tera` and other template
+    /// overrides; defaults to `templates` in the config directory
+    pub template_dir: Option<PathBuf>,
+    /// Language of alert, digest and report text
+    #[serde(default = "default_notification_locale")]
+    pub locale: Locale,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        let email_to = env::var("ALERT_EMAIL_TO")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        Self {
+            slack_webhook_url: env::var("SLACK_WEBHOOK_URL").ok().filter(|u| !u.is_empty()),
+            slack_bot_token: env::var("SLACK_BOT_TOKEN").ok().filter(|t| !t.is_empty()),
+            smtp_host: env::var("SMTP_HOST").ok().filter(|h| !h.is_empty()),
+            smtp_port: env::var("SMTP_PORT")
+                .unwrap_or_else(|_| "587".to_string())
+                .parse()
+                .unwrap_or(587),
+            smtp_username: env::var("SMTP_USERNAME").ok().filter(|u| !u.is_empty()),
+            smtp_password: env::var("SMTP_PASSWORD").ok().filter(|p| !p.is_empty()),
+            email_from: env::var("ALERT_EMAIL_FROM").ok().filter(|f| !f.is_empty()),
+            email_to,
+            webhooks: Vec::new(),
+            chat: [(ChatPlatform::Discord, "DISCORD_WEBHOOK_URL"), (ChatPlatform::Teams, "TEAMS_WEBHOOK_URL")]
+                .into_iter()
+                .filter_map(|(platform, var)| {
+                    let url = env::var(var).ok().filter(|u| !u.is_empty())?;
+                    Some(ChatTarget { platform, url, ..ChatTarget::default() })
+                })
+                .collect(),
+            incidents: [(IncidentProvider::PagerDuty, "PAGERDUTY_ROUTING_KEY"), (IncidentProvider::Opsgenie, "OPSGENIE_API_KEY")]
+                .into_iter()
+                .filter_map(|(provider, var)| {
+                    let key = env::var(var).ok().filter(|k| !k.is_empty())?;
+                    let api_url = match provider {
+                        IncidentProvider::PagerDuty => None,
+                        IncidentProvider::Opsgenie => env::var("OPSGENIE_API_URL").ok().filter(|u| !u.is_empty()),
+                    };
+                    Some(IncidentTarget { provider, key, name: None, api_url })
+                })
+                .collect(),
+            quiet_windows: Vec::new(),
+            email_digest: default_email_digest(),
+            email_digest_hour: default_email_digest_hour(),
+            email_scan_reports: default_email_scan_reports(),
+            template_dir: env::var("NOTIFICATION_TEMPLATE_DIR").ok().map(PathBuf::from),
+            locale: default_notification_locale(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookTarget {
+    pub url: String,
+    /// Bodies are signed with this secret in `X-Hub-Signature-256`
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Extra subscriptions besides secret alerts, e.g. `health` for monitor health alerts
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatPlatform {
+    /// Block Kit message
+    Slack,
+    /// Embeds
+    Discord,
+    /// Adaptive Card, for Teams workflow and incoming webhooks
+    Teams,
+}
+
+/// A chat webhook that gets alerts formatted for its platform. The first alert after a
+/// quiet spell goes out at once; alerts arriving within `batch_seconds` of a message are
+/// batched into the next one, so an alert storm becomes a few summary messages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatTarget {
+    pub platform: ChatPlatform,
+    pub url: String,
+    /// Shown in delivery logs as `chat:<name>`; defaults to the platform
+    #[serde(default)]
+    pub name: Option<String>,
+    /// `low`, `medium`, `high` or `critical`; alerts below it are not sent here
+    #[serde(default)]
+    pub min_severity: Option<String>,
+    #[serde(default = "default_chat_batch_seconds")]
+    pub batch_seconds: u64,
+    /// `0` for no limit
+    #[serde(default = "default_chat_messages_per_minute")]
+    pub max_messages_per_minute: u32,
+}
+
+impl Default for ChatTarget {
+    fn default() -> Self {
+        Self {
+            platform: ChatPlatform::Slack,
+            url: String::new(),
+            name: None,
+            min_severity: env::var("CHAT_MIN_SEVERITY").ok().filter(|s| !s.is_empty()),
+            batch_seconds: default_chat_batch_seconds(),
+            max_messages_per_minute: default_chat_messages_per_minute(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IncidentProvider {
+    PagerDuty,
+    Opsgenie,
+}
+
+/// A PagerDuty service or Opsgenie team that gets an incident for each secret in a Critical
+/// alert, or in an alert whose triage says to revoke it immediately
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentTarget {
+    pub provider: IncidentProvider,
+    /// PagerDuty Events API v2 routing key, or Opsgenie API integration key
+    pub key: String,
+    /// Shown in delivery logs as `incident:<name>`; defaults to the provider
+    #[serde(default)]
+    pub name: Option<String>,
+    /// API base URL, e.g. `https://api.eu.opsgenie.com` for Opsgenie's EU instance
+    #[serde(default)]
+    pub api_url: Option<String>,
+}
+
+/// While a window is active, non-Critical alerts on its channels are held and sent
+/// as one digest once it ends. Critical alerts are always delivered immediately.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuietWindow {
+    /// Every day between two local times; may wrap past midnight (e.g. 22:00-07:00)
+    Daily {
+        start: NaiveTime,
+        end: NaiveTime,
+        /// `slack`, `email` or `webhook`; empty means every channel
+        #[serde(default)]
+        channels: Vec<String>,
+    },
+    /// One-off maintenance window
+    Maintenance {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        #[serde(default)]
+        channels: Vec<String>,
+    },
+}
+
+/// How often the email digest of non-Critical alerts is sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestSchedule {
+    Hourly,
+    Daily,
+}
+
+fn default_email_digest() -> Option<DigestSchedule> {
+    match env::var("ALERT_EMAIL_DIGEST").unwrap_or_default().to_lowercase().as_str() {
+        "hourly" => Some(DigestSchedule::Hourly),
+        "daily" => Some(DigestSchedule::Daily),
+        _ => None,
+    }
+}
+
+fn default_email_digest_hour() -> u32 {
+    env::var("ALERT_EMAIL_DIGEST_HOUR").unwrap_or_else(|_| "8".to_string()).parse().unwrap_or(8)
+}
+
+fn default_email_scan_reports() -> bool {
+    env::var("ALERT_EMAIL_SCAN_REPORTS").map(|v| v == "true" || v == "1").unwrap_or(false)
+}
+
+fn default_bigquery_page_size() -> u32 {
+    env::var("BIGQUERY_PAGE_SIZE").ok().and_then(|v| v.parse().ok()).filter(|&n| n > 0).unwrap_or(10_000)
+}
+
+fn default_notification_locale() -> Locale {
+    env::var("NOTIFICATION_LOCALE").ok().and_then(|v| v.parse().ok()).unwrap_or_default()
+}
+
+fn default_sink_redaction_role() -> String {
+    env::var("SINK_REDACTION_ROLE").unwrap_or_else(|_| "analyst".to_string())
+}
+
+fn default_redaction() -> RedactionProfile {
+    RedactionProfile::Full
+}
+
+fn default_chat_batch_seconds() -> u64 {
+    env::var("CHAT_BATCH_SECONDS").unwrap_or_else(|_| "30".to_string()).parse().unwrap_or(30)
+}
+
+fn default_chat_messages_per_minute() -> u32 {
+    env::var("CHAT_MESSAGES_PER_MINUTE").unwrap_or_else(|_| "10".to_string()).parse().unwrap_or(10)
+}
+
+fn default_defectdojo_test_title() -> String {
+    "GitHub Secret Hunter".to_string()
+}
+
+fn default_jira_issue_type() -> String {
+    "Bug".to_string()
+}
+
+fn default_splunk_source() -> String {
+    "github_archiver".to_string()
+}
+
+fn default_splunk_sourcetype() -> String {
+    "github_archiver:finding".to_string()
+}
+
+fn default_elasticsearch_index() -> String {
+    "github-archiver-findings".to_string()
+}
+
 /// Resource monitoring and limits
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceConfig {
@@ -277,6 +1399,52 @@ pub struct WebConfig {
     pub cors_origins: Vec<String>,
     pub max_request_size: usize,
     pub request_timeout: u64,
+    /// Findings database served by the `/api/v1/findings` endpoints
+    #[serde(default = "default_findings_database")]
+    pub findings_database: String,
+    /// Query-only mode for analysts: requests that change anything are refused and the
+    /// findings database (which may be a replica) is opened read-only
+    #[serde(default = "default_read_only")]
+    pub read_only: bool,
+    /// Connections the API keeps open to the findings database
+    #[serde(default = "default_findings_pool_size")]
+    pub findings_pool_size: usize,
+    /// Requests each client (signed-in user, or address) may make per minute; 0 disables the limit
+    #[serde(default = "default_rate_limit_per_minute")]
+    pub rate_limit_per_minute: u32,
+    /// Requests a client may send in a burst before the per-minute rate applies
+    #[serde(default = "default_rate_limit_burst")]
+    pub rate_limit_burst: u32,
+    /// Findings queries each client may run at once; 0 disables the cap
+    #[serde(default = "default_max_concurrent_scans")]
+    pub max_concurrent_scans: usize,
+}
+
+fn default_findings_database() -> String {
+    env::var("FINDINGS_DATABASE").unwrap_or_else(|_| "secrets.db".to_string())
+}
+
+fn default_read_only() -> bool {
+    env::var("WEB_READ_ONLY").map(|v| v.to_lowercase() == "true").unwrap_or(false)
+}
+
+fn default_findings_pool_size() -> usize {
+    env::var("FINDINGS_DB_POOL_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(crate::performance::DEFAULT_POOL_SIZE)
+}
+
+fn default_rate_limit_per_minute() -> u32 {
+    env::var("API_RATE_LIMIT_PER_MINUTE").ok().and_then(|v| v.parse().ok()).unwrap_or(600)
+}
+
+fn default_rate_limit_burst() -> u32 {
+    env::var("API_RATE_LIMIT_BURST").ok().and_then(|v| v.parse().ok()).unwrap_or(60)
+}
+
+fn default_max_concurrent_scans() -> usize {
+    env::var("API_MAX_CONCURRENT_SCANS").ok().and_then(|v| v.parse().ok()).unwrap_or(4)
 }
 
 impl Default for WebConfig {
@@ -306,6 +1474,12 @@ impl Default for WebConfig {
                 .unwrap_or_else(|_| "30".to_string())
                 .parse()
                 .unwrap_or(30),
+            findings_database: default_findings_database(),
+            read_only: default_read_only(),
+            findings_pool_size: default_findings_pool_size(),
+            rate_limit_per_minute: default_rate_limit_per_minute(),
+            rate_limit_burst: default_rate_limit_burst(),
+            max_concurrent_scans: default_max_concurrent_scans(),
         }
     }
 }
@@ -320,13 +1494,59 @@ impl WebConfig {
 /// Security configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecurityConfig {
-    pub admin_password: String,
+    /// Password of the `admin` account created in an empty user store; without it, the
+    /// first account is added with `admin users add`
+    #[serde(default)]
+    pub admin_password: Option<String>,
     pub secret_key: String,
     pub jwt_secret: String,
+    /// How long a session can be refreshed before its user must sign in again
     pub session_duration_hours: u64,
     pub max_failed_attempts: u32,
     pub lockout_duration_minutes: u64,
     pub require_2fa: bool,
+    /// SQLite file holding API users and their sessions
+    #[serde(default = "default_users_database")]
+    pub users_database: String,
+    /// Lifetime of the bearer tokens handed out at sign-in and refresh
+    #[serde(default = "default_access_token_minutes")]
+    pub access_token_minutes: u64,
+    /// Days before a password must be changed; 0 never expires passwords
+    #[serde(default = "default_password_max_age_days")]
+    pub password_max_age_days: u64,
+    #[serde(default = "default_min_password_length")]
+    pub min_password_length: usize,
+    /// Actions that need a second person's approval, each mapped to the least role that may
+    /// give it: `export` and `erase`. Empty unless `APPROVAL_POLICY` is set, in which case
+    /// nothing needs approval.
+    #[serde(default = "default_approval_policy")]
+    pub approval_policy: HashMap<String, String>,
+}
+
+fn default_users_database() -> String {
+    env::var("API_USERS_DATABASE").unwrap_or_else(|_| "users.db".to_string())
+}
+
+fn default_access_token_minutes() -> u64 {
+    env::var("API_ACCESS_TOKEN_MINUTES").ok().and_then(|v| v.parse().ok()).unwrap_or(60)
+}
+
+fn default_password_max_age_days() -> u64 {
+    env::var("PASSWORD_MAX_AGE_DAYS").ok().and_then(|v| v.parse().ok()).unwrap_or(0)
+}
+
+fn default_min_password_length() -> usize {
+    env::var("PASSWORD_MIN_LENGTH").ok().and_then(|v| v.parse().ok()).unwrap_or(12)
+}
+
+fn default_approval_policy() -> HashMap<String, String> {
+    env::var("APPROVAL_POLICY")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|rule| rule.split_once('='))
+        .map(|(action, role)| (action.trim().to_lowercase(), role.trim().to_string()))
+        .filter(|(action, _)| !action.is_empty())
+        .collect()
 }
 
 impl Default for SecurityConfig {
@@ -334,8 +1554,7 @@ impl Default for SecurityConfig {
         use uuid::Uuid;
 
         Self {
-            admin_password: env::var("ADMIN_PASSWORD")
-                .unwrap_or_else(|_| "admin123".to_string()),
+            admin_password: env::var("ADMIN_PASSWORD").ok().filter(|p| !p.is_empty()),
             secret_key: env::var("SECRET_KEY")
                 .unwrap_or_else(|_| Uuid::new_v4().to_string()),
             jwt_secret: env::var("JWT_SECRET")
@@ -356,6 +1575,11 @@ impl Default for SecurityConfig {
                 .unwrap_or_else(|_| "false".to_string())
                 .to_lowercase()
                 == "true",
+            users_database: default_users_database(),
+            access_token_minutes: default_access_token_minutes(),
+            password_max_age_days: default_password_max_age_days(),
+            min_password_length: default_min_password_length(),
+            approval_policy: default_approval_policy(),
         }
     }
 }
@@ -366,10 +1590,51 @@ pub struct Config {
     pub database: DatabaseConfig,
     pub github: GitHubConfig,
     pub download: DownloadConfig,
+    pub http: HttpConfig,
+    #[serde(default)]
+    pub sinks: SinksConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    #[serde(default)]
+    pub disk_forecast: DiskForecastConfig,
+    #[serde(default)]
+    pub quotas: QuotaConfig,
+    #[serde(default)]
+    pub canary: CanaryConfig,
+    #[serde(default)]
+    pub privacy: PrivacyConfig,
+    #[serde(default)]
+    pub ingest: IngestConfig,
+    #[serde(default)]
+    pub offline: OfflineConfig,
+    #[serde(default)]
+    pub scan_workspaces: ScanWorkspaceConfig,
+    #[serde(default)]
+    pub bigquery: BigQueryConfig,
+    #[serde(default)]
+    pub storage_compression: StorageCompressionConfig,
+    #[serde(default)]
+    pub query_cache: QueryCacheConfig,
+    #[serde(default)]
+    pub output: OutputConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    /// Least redaction of secrets in everything the scanner outputs; `SECRET_REDACTION`
+    /// overrides it (see `Config::redaction`)
+    #[serde(default = "default_redaction")]
+    pub redaction: RedactionProfile,
     pub resources: ResourceConfig,
     pub logging: LoggingConfig,
     pub web: WebConfig,
+    /// API listener TLS, allowlists and admin binding
+    #[serde(default = "default_server_config")]
+    pub server: ServerConfig,
     pub security: SecurityConfig,
+    /// Single sign-on for the API and dashboard
+    #[serde(default = "default_oidc_config")]
+    pub oidc: OidcConfig,
 }
 
 impl Default for Config {
@@ -378,15 +1643,39 @@ impl Default for Config {
             database: DatabaseConfig::default(),
             github: GitHubConfig::default(),
             download: DownloadConfig::default(),
+            http: HttpConfig::default(),
+            sinks: SinksConfig::default(),
+            retention: RetentionConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+            disk_forecast: DiskForecastConfig::default(),
+            quotas: QuotaConfig::default(),
+            canary: CanaryConfig::default(),
+            privacy: PrivacyConfig::default(),
+            ingest: IngestConfig::default(),
+            offline: OfflineConfig::default(),
+            scan_workspaces: ScanWorkspaceConfig::default(),
+            bigquery: BigQueryConfig::default(),
+            storage_compression: StorageCompressionConfig::default(),
+            query_cache: QueryCacheConfig::default(),
+            output: OutputConfig::default(),
+            notifications: NotificationsConfig::default(),
+            redaction: default_redaction(),
             resources: ResourceConfig::default(),
             logging: LoggingConfig::default(),
             web: WebConfig::default(),
+            server: ServerConfig::from_env(),
             security: SecurityConfig::default(),
+            oidc: OidcConfig::from_env(),
         }
     }
 }
 
 impl Config {
+    /// The `redaction` setting, or `SECRET_REDACTION` when that is set
+    pub fn redaction(&self) -> RedactionProfile {
+        RedactionProfile::from_env_or(self.redaction)
+    }
+
     /// Create a new configuration from environment variables and optional config file
     pub fn new(config_file: Option<&str>) -> Result<Self> {
         let mut config = Config::default();
@@ -456,6 +1745,40 @@ impl Config {
             errors.push("Invalid web port");
         }
 
+        if self.server.tls_cert.is_some() != self.server.tls_key.is_some() {
+            errors.push("Server TLS needs both tls_cert and tls_key");
+        }
+        if self.server.client_ca.is_some() && self.server.tls_cert.is_none() {
+            errors.push("Server client_ca requires tls_cert and tls_key");
+        }
+        let tls_files = [&self.server.tls_cert, &self.server.tls_key, &self.server.client_ca];
+        if tls_files.iter().any(|file| file.as_ref().map_or(false, |path| !path.exists())) {
+            errors.push("Server TLS file does not exist");
+        }
+
+        // Validate security configuration
+        if self.security.access_token_minutes == 0 || self.security.session_duration_hours == 0 {
+            errors.push("Access token and session lifetimes must be positive");
+        }
+        if self.security.min_password_length < 8 {
+            errors.push("Minimum password length must be at least 8");
+        }
+        let known_role = |role: &String| matches!(role.to_lowercase().as_str(), "admin" | "analyst" | "viewer");
+        if !self.security.approval_policy.iter().all(|(action, role)| matches!(action.as_str(), "export" | "erase") && known_role(role)) {
+            errors.push("Approval policy entries must map export or erase to admin, analyst or viewer");
+        }
+        if self.oidc.enabled() {
+            if self.oidc.client_id.is_empty() || self.oidc.redirect_uri.is_empty() {
+                errors.push("OIDC needs a client_id and redirect_uri");
+            }
+            if !self.oidc.scopes.iter().any(|scope| scope == "openid") {
+                errors.push("OIDC scopes must include openid");
+            }
+            if !self.oidc.role_mappings.values().chain(&self.oidc.default_role).all(known_role) {
+                errors.push("OIDC roles must be admin, analyst or viewer");
+            }
+        }
+
         // Validate download configuration
         if self.download.max_concurrent_downloads == 0 {
             errors.push("Max concurrent downloads must be positive");
@@ -464,6 +1787,35 @@ impl Config {
             errors.push("Batch size must be positive");
         }
 
+        // Validate HTTP configuration
+        if self.http.request_timeout == 0 || self.http.connect_timeout == 0 {
+            errors.push("HTTP timeouts must be positive");
+        }
+        if self.http.retry_base_delay_ms > self.http.retry_max_delay_ms {
+            errors.push("HTTP retry base delay must not exceed max delay");
+        }
+        if let Some(ca_bundle) = &self.http.ca_bundle_path {
+            if !ca_bundle.exists() {
+                errors.push("HTTP CA bundle file does not exist");
+            }
+        }
+
+        // Validate sink configuration
+        if self.sinks.batch_size == 0 {
+            errors.push("Sink batch size must be positive");
+        }
+
+        // Validate privacy configuration
+        if self.privacy.anonymize_actors && self.privacy.anonymization_key.len() < 16 {
+            errors.push("Actor anonymization requires an anonymization key of at least 16 characters");
+        }
+
+        // Validate offline configuration
+        let bare_host = |h: &String| !h.is_empty() && !h.contains("://") && !h.contains('/');
+        if !self.offline.github_host.iter().chain(&self.offline.allowed_hosts).all(bare_host) {
+            errors.push("Offline hosts must be bare host names without scheme or path");
+        }
+
         if !errors.is_empty() {
             let error_msg = format!("Configuration validation failed: {}", errors.join(", "));
             error!("{}", error_msg);
@@ -534,6 +1886,15 @@ mod tests {
         assert!(config.is_authenticated());
     }
 
+    #[test]
+    fn test_download_window_parsing() {
+        let window: DownloadWindow = "22:00-06:30".parse().unwrap();
+        assert_eq!(window.start, NaiveTime::from_hms_opt(22, 0, 0).unwrap());
+        assert_eq!(window.end, NaiveTime::from_hms_opt(6, 30, 0).unwrap());
+        assert!("22:00".parse::<DownloadWindow>().is_err());
+        assert!("25:00-06:00".parse::<DownloadWindow>().is_err());
+    }
+
     #[test]
     fn test_resource_limits() {
         let config = ResourceConfig::default();
@@ -559,4 +1920,14 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_redaction_from_config_file() {
+        let mut document = serde_json::to_value(Config::default()).unwrap();
+        document.as_object_mut().unwrap().remove("redaction");
+        assert_eq!(serde_json::from_value::<Config>(document.clone()).unwrap().redaction, RedactionProfile::Full);
+
+        document["redaction"] = serde_json::json!("hash_only");
+        assert_eq!(serde_json::from_value::<Config>(document).unwrap().redaction, RedactionProfile::HashOnly);
+    }
 }
@@ -4,9 +4,12 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::{postgres::PgPoolOptions, PgPool, Row};
 use std::collections::HashMap;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
+use super::anonymize::ActorAnonymizer;
 use super::config::Config;
+use super::ingest::IngestFilter;
+use super::migrations::{self, Migration};
 
 /// Database health status
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,11 +120,20 @@ pub struct OrgData {
     pub site_admin: Option<bool>,
 }
 
+/// Store name of the `github_events` tables in `schema_migrations`
+pub const EVENTS_STORE: &str = "github_events";
+
+/// The baseline is the schema in `get_schema_sql`; later changes are added here
+pub const EVENTS_MIGRATIONS: &[Migration] = &[Migration { version: 1, description: "baseline schema", statements: &[] }];
+
 /// Professional PostgreSQL database manager with connection pooling
 #[derive(Clone)]
 pub struct Database {
     pool: PgPool,
     config: Config,
+    anonymizer: Option<ActorAnonymizer>,
+    ingest: Option<IngestFilter>,
+    schema_version: i32,
 }
 
 impl Database {
@@ -129,6 +141,14 @@ impl Database {
     pub async fn new(config: Config) -> Result<Self> {
         let connection_string = &config.database.connection_string();
         let max_attempts = 3;
+        let anonymizer = ActorAnonymizer::from_config(&config.privacy)?;
+        if anonymizer.is_some() {
+            info!("Actor anonymization enabled: logins and emails are stored as keyed hashes");
+        }
+        let ingest = IngestFilter::from_config(&config.ingest);
+        if ingest.is_some() {
+            info!("Ingest filter enabled: event types {:?}", config.ingest.event_types);
+        }
 
         for attempt in 1..=max_attempts {
             match PgPoolOptions::new()
@@ -139,11 +159,11 @@ impl Database {
                 .await
             {
                 Ok(pool) => {
-                    let db = Database { pool, config };
+                    let mut db = Database { pool, config, anonymizer, ingest, schema_version: 0 };
                     
                     // Verify connection and initialize schema
                     db.verify_connection().await?;
-                    db.initialize_schema().await?;
+                    db.schema_version = db.initialize_schema().await?;
                     
                     info!("Database connected successfully (attempt {})", attempt);
                     return Ok(db);
@@ -177,8 +197,12 @@ impl Database {
         Ok(())
     }
 
-    /// Initialize database schema if needed
-    async fn initialize_schema(&self) -> Result<()> {
+    /// Initialize database schema if needed and apply pending migrations
+    async fn initialize_schema(&self) -> Result<i32> {
+        // Refuse a database written by a newer build before touching it
+        let stored = migrations::postgres_version(&self.pool, EVENTS_STORE).await?;
+        migrations::pending(EVENTS_STORE, stored, EVENTS_MIGRATIONS)?;
+
         let schema_commands = self.get_schema_commands();
         
         for command in schema_commands {
@@ -190,8 +214,14 @@ impl Database {
             }
         }
         
-        info!("Database schema initialized");
-        Ok(())
+        let version = migrations::migrate_postgres(&self.pool, EVENTS_STORE, EVENTS_MIGRATIONS).await?;
+        info!("Database schema initialized (version {})", version);
+        Ok(version)
+    }
+
+    /// Schema version applied when the connection was made
+    pub fn schema_version(&self) -> i32 {
+        self.schema_version
     }
 
     /// Comprehensive database health check
@@ -275,6 +305,19 @@ impl Database {
             return Ok(0);
         }
 
+        // Drop event types this deployment does not store
+        let total = events.len();
+        let events: Vec<serde_json::Value> = match &self.ingest {
+            Some(filter) => events.into_iter().filter(|event| filter.accepts(event)).collect(),
+            None => events,
+        };
+        if events.len() < total {
+            debug!("Ingest filter skipped {} of {} events in {}", total - events.len(), total, filename);
+        }
+        if events.is_empty() {
+            return Ok(0);
+        }
+
         // Validate events
         let validated_events: Vec<ValidatedEvent> = events
             .into_iter()
@@ -654,7 +697,7 @@ impl Database {
             site_admin: org_obj.get("site_admin").and_then(|v| v.as_bool()),
         });
 
-        Some(ValidatedEvent {
+        let mut validated = ValidatedEvent {
             id,
             event_type,
             created_at,
@@ -665,7 +708,17 @@ impl Database {
             payload: event.get("payload").unwrap_or(&serde_json::Value::Null).clone(),
             raw_event: event.clone(),
             api_source: "github_archive".to_string(),
-        })
+        };
+
+        if let Some(ingest) = &self.ingest {
+            ingest.apply(&mut validated);
+        }
+
+        if let Some(anonymizer) = &self.anonymizer {
+            anonymizer.anonymize_event(&mut validated);
+        }
+
+        Some(validated)
     }
 
     fn parse_datetime(&self, date_str: &str) -> Option<DateTime<Utc>> {
@@ -4,9 +4,12 @@ use serde::{Serialize, Deserialize};
 use anyhow::{Result, anyhow};
 use tracing::{info, warn, error, debug};
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use crate::core::Config;
+use crate::core::migrations::{self, Migration};
+use crate::core::slow_queries::{explainable, slow_query_threshold, SlowQuery};
+use crate::core::{Config, IngestFilter};
 use crate::scraper::{GitHubEvent, EventBatch};
 
 #[derive(Debug, Clone, Serialize)]
@@ -16,6 +19,8 @@ pub struct DatabaseHealth {
     pub active_queries: u32,
     pub cache_hit_ratio: f64,
     pub error_message: Option<String>,
+    /// Schema version applied when the manager connected
+    pub schema_version: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -40,20 +45,64 @@ pub struct ProcessedFile {
     pub processing_time_seconds: f64,
 }
 
+/// Store name of the archive tables in `schema_migrations`
+pub const ARCHIVE_STORE: &str = "archive";
+
+/// The baseline is the schema `initialize_schema` creates; later changes are added here
+pub const ARCHIVE_MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, description: "baseline schema", statements: &[] },
+    Migration {
+        version: 2,
+        description: "backfill queue",
+        statements: &[r#"
+            CREATE TABLE IF NOT EXISTS backfill_queue (
+                filename VARCHAR(255) PRIMARY KEY,
+                queued_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                attempts INTEGER DEFAULT 0,
+                last_error TEXT
+            )
+        "#],
+    },
+    Migration {
+        version: 3,
+        description: "slow query log",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS slow_queries (
+                id BIGSERIAL PRIMARY KEY,
+                sql TEXT NOT NULL,
+                duration_ms DOUBLE PRECISION NOT NULL,
+                plan TEXT,
+                recorded_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+            )
+        "#,
+            "CREATE INDEX IF NOT EXISTS idx_slow_queries_recorded_at ON slow_queries(recorded_at)",
+        ],
+    },
+];
+
 pub struct DatabaseManager {
     pool: Option<Pool<Postgres>>,
     config: Config,
     connection_attempts: u32,
     max_connection_attempts: u32,
+    schema_version: Option<i32>,
+    /// Events inserted since `events` was last analyzed
+    rows_since_analyze: AtomicU64,
+    ingest: Option<IngestFilter>,
 }
 
 impl DatabaseManager {
     pub fn new(config: Config) -> Self {
+        let ingest = IngestFilter::from_config(&config.ingest);
         Self {
             pool: None,
             config,
             connection_attempts: 0,
             max_connection_attempts: 3,
+            schema_version: None,
+            rows_since_analyze: AtomicU64::new(0),
+            ingest,
         }
     }
 
@@ -67,7 +116,7 @@ impl DatabaseManager {
                 Ok(pool) => {
                     self.pool = Some(pool);
                     self.verify_connection().await?;
-                    self.initialize_schema().await?;
+                    self.schema_version = Some(self.initialize_schema().await?);
                     info!("Database connected successfully (attempt {})", attempt + 1);
                     return Ok(());
                 }
@@ -126,9 +175,14 @@ impl DatabaseManager {
         Ok(())
     }
 
-    async fn initialize_schema(&self) -> Result<()> {
+    /// Create the baseline tables and apply pending migrations, returning the schema version
+    async fn initialize_schema(&self) -> Result<i32> {
         let pool = self.pool.as_ref().ok_or_else(|| anyhow!("No database connection"))?;
-        
+
+        // Refuse a database written by a newer build before touching it
+        let stored = migrations::postgres_version(pool, ARCHIVE_STORE).await?;
+        migrations::pending(ARCHIVE_STORE, stored, ARCHIVE_MIGRATIONS)?;
+
         info!("Initializing database schema...");
 
         // Create events table
@@ -232,8 +286,14 @@ impl DatabaseManager {
             }
         }
 
-        info!("Database schema initialized successfully");
-        Ok(())
+        let version = migrations::migrate_postgres(pool, ARCHIVE_STORE, ARCHIVE_MIGRATIONS).await?;
+        info!("Database schema initialized successfully (version {})", version);
+        Ok(version)
+    }
+
+    /// Schema version applied by `connect`
+    pub fn schema_version(&self) -> Option<i32> {
+        self.schema_version
     }
 
     pub async fn insert_events_batch(&self, events: &[GitHubEvent], source_file: &str) -> Result<u64> {
@@ -247,8 +307,24 @@ impl DatabaseManager {
 
         let mut tx = pool.begin().await?;
         let mut inserted_count = 0u64;
+        let mut skipped = 0usize;
 
         for event in events {
+            // Same ingest settings as `Database::insert_events_batch`
+            let mut stored = event.clone();
+            if let Some(filter) = &self.ingest {
+                if !filter.accepts_type(&event.event_type) {
+                    skipped += 1;
+                    continue;
+                }
+                stored.payload = event.payload.as_ref().map(|payload| filter.project_payload(&event.event_type, payload));
+            }
+            let raw_data = match &self.ingest {
+                Some(filter) if !filter.stores_raw_event() => None,
+                _ => Some(serde_json::to_value(&stored)?),
+            };
+            let event = &stored;
+
             // Parse created_at timestamp
             let created_at = event.created_at.as_ref()
                 .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
@@ -292,7 +368,7 @@ impl DatabaseManager {
             .bind(event.public)
             .bind(created_at)
             .bind(source_file)
-            .bind(serde_json::to_value(event)?)
+            .bind(raw_data)
             .execute(&mut *tx)
             .await {
                 Ok(result) => {
@@ -307,11 +383,75 @@ impl DatabaseManager {
         }
 
         tx.commit().await?;
+        if skipped > 0 {
+            debug!("Ingest filter skipped {} of {} events in {}", skipped, events.len(), source_file);
+        }
         debug!("Successfully inserted {} events", inserted_count);
-        
+
+        if let Err(e) = self.analyze_after_ingest(inserted_count).await {
+            warn!("Failed to analyze the events table: {}", e);
+        }
         Ok(inserted_count)
     }
 
+    /// ANALYZE `events` once `maintenance.analyze_after_rows` events were inserted since the
+    /// last time, so the planner sees a large ingest before autovacuum gets to it
+    async fn analyze_after_ingest(&self, inserted: u64) -> Result<bool> {
+        let threshold = self.config.maintenance.analyze_after_rows;
+        let total = self.rows_since_analyze.fetch_add(inserted, Ordering::SeqCst) + inserted;
+        if threshold == 0 || total < threshold {
+            return Ok(false);
+        }
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("No database connection"))?;
+
+        self.rows_since_analyze.store(0, Ordering::SeqCst);
+        let started = Instant::now();
+        sqlx::query("ANALYZE events").execute(pool).await?;
+        info!("Analyzed events after {} inserts in {:.1}s", total, started.elapsed().as_secs_f64());
+        Ok(true)
+    }
+
+    /// Autovacuum tuning suggestions for tables whose dead rows or unanalyzed changes
+    /// outgrow what the default thresholds handle
+    pub async fn maintenance_hints(&self) -> Result<Vec<String>> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("No database connection"))?;
+
+        let rows = sqlx::query(
+            "SELECT relname::text AS table_name, n_live_tup, n_dead_tup, n_mod_since_analyze,
+                (last_autovacuum IS NULL AND last_vacuum IS NULL) AS never_vacuumed
+            FROM pg_stat_user_tables WHERE schemaname = current_schema() ORDER BY n_dead_tup DESC"
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut hints = Vec::new();
+        for row in &rows {
+            let table: String = row.get("table_name");
+            let live: i64 = row.get("n_live_tup");
+            let dead: i64 = row.get("n_dead_tup");
+            let modified: i64 = row.get("n_mod_since_analyze");
+            let never_vacuumed: bool = row.get("never_vacuumed");
+            if live < 10_000 {
+                continue;
+            }
+            if dead * 10 > live {
+                hints.push(format!(
+                    "{} has {} dead rows ({:.0}% of live ones): ALTER TABLE {} SET (autovacuum_vacuum_scale_factor = 0.02, autovacuum_vacuum_cost_limit = 1000);",
+                    table, dead, dead as f64 * 100.0 / live as f64, table
+                ));
+            } else if never_vacuumed && dead > 0 {
+                hints.push(format!("{} has never been vacuumed: VACUUM (ANALYZE) {};", table, table));
+            }
+            if modified * 5 > live {
+                hints.push(format!(
+                    "{} changed {} rows since its last ANALYZE: ALTER TABLE {} SET (autovacuum_analyze_scale_factor = 0.01);",
+                    table, modified, table
+                ));
+            }
+        }
+        Ok(hints)
+    }
+
     pub async fn mark_file_processed(
         &self,
         filename: &str,
@@ -349,16 +489,110 @@ impl DatabaseManager {
     pub async fn is_file_processed(&self, filename: &str, etag: Option<&str>) -> Result<bool> {
         let pool = self.pool.as_ref().ok_or_else(|| anyhow!("No database connection"))?;
 
+        let sql = "SELECT COUNT(*) as count FROM processed_files WHERE filename = $1 AND (etag = $2 OR $2 IS NULL)";
+        let started = Instant::now();
+        let row = sqlx::query(sql)
+            .bind(filename)
+            .bind(etag)
+            .fetch_one(pool)
+            .await?;
+        self.note_query(sql, started.elapsed()).await;
+
+        let count: i64 = row.get("count");
+        Ok(count > 0)
+    }
+
+    /// Filenames of every successfully processed archive file
+    pub async fn processed_filenames(&self) -> Result<Vec<String>> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("No database connection"))?;
+
+        let rows = sqlx::query("SELECT filename FROM processed_files WHERE status = 'completed'")
+            .fetch_all(pool)
+            .await?;
+
+        Ok(rows.iter().map(|row| row.get("filename")).collect())
+    }
+
+    /// Queue archive files for download; already queued files are left alone.
+    /// Returns how many were newly queued.
+    pub async fn queue_backfills(&self, filenames: &[String]) -> Result<u64> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("No database connection"))?;
+
+        let mut tx = pool.begin().await?;
+        let mut queued = 0u64;
+        for filename in filenames {
+            let result = sqlx::query("INSERT INTO backfill_queue (filename) VALUES ($1) ON CONFLICT (filename) DO NOTHING")
+                .bind(filename)
+                .execute(&mut *tx)
+                .await?;
+            queued += result.rows_affected();
+        }
+        tx.commit().await?;
+
+        Ok(queued)
+    }
+
+    /// Oldest queued backfills first, skipping files that already failed `max_attempts` times
+    pub async fn pending_backfills(&self, limit: i64, max_attempts: i32) -> Result<Vec<String>> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("No database connection"))?;
+
+        let sql = "SELECT filename FROM backfill_queue WHERE attempts < $1 ORDER BY queued_at, filename LIMIT $2";
+        let started = Instant::now();
+        let rows = sqlx::query(sql)
+            .bind(max_attempts)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?;
+        self.note_query(sql, started.elapsed()).await;
+
+        Ok(rows.iter().map(|row| row.get("filename")).collect())
+    }
+
+    /// Remove a backfill from the queue once processed, or record why it failed
+    pub async fn finish_backfill(&self, filename: &str, error: Option<&str>) -> Result<()> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("No database connection"))?;
+
+        match error {
+            None => {
+                sqlx::query("DELETE FROM backfill_queue WHERE filename = $1")
+                    .bind(filename)
+                    .execute(pool)
+                    .await?;
+            }
+            Some(error) => {
+                sqlx::query("UPDATE backfill_queue SET attempts = attempts + 1, last_error = $2 WHERE filename = $1")
+                    .bind(filename)
+                    .bind(error)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Queued backfills still to try, and those that gave up after `max_attempts`
+    pub async fn backfill_queue_depth(&self, max_attempts: i32) -> Result<(i64, i64)> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("No database connection"))?;
+
         let row = sqlx::query(
-            "SELECT COUNT(*) as count FROM processed_files WHERE filename = $1 AND (etag = $2 OR $2 IS NULL)"
+            "SELECT COUNT(*) FILTER (WHERE attempts < $1) AS pending, COUNT(*) FILTER (WHERE attempts >= $1) AS failed
+            FROM backfill_queue"
         )
-        .bind(filename)
-        .bind(etag)
+        .bind(max_attempts)
         .fetch_one(pool)
         .await?;
 
-        let count: i64 = row.get("count");
-        Ok(count > 0)
+        Ok((row.get("pending"), row.get("failed")))
+    }
+
+    /// On-disk size of the connected database in bytes
+    pub async fn database_size(&self) -> Result<i64> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("No database connection"))?;
+
+        let size: i64 = sqlx::query_scalar("SELECT pg_database_size(current_database())")
+            .fetch_one(pool)
+            .await?;
+        Ok(size)
     }
 
     pub async fn get_health_status(&self) -> Result<DatabaseHealth> {
@@ -369,6 +603,7 @@ impl DatabaseManager {
                 active_queries: 0,
                 cache_hit_ratio: 0.0,
                 error_message: Some("No database connection".to_string()),
+                schema_version: self.schema_version,
             };
         });
 
@@ -385,6 +620,7 @@ impl DatabaseManager {
                         active_queries: 0, // Would need more complex querying to get this
                         cache_hit_ratio: 0.0, // Would need PostgreSQL stats to calculate this
                         error_message: None,
+                        schema_version: self.schema_version,
                     }),
                     Err(e) => Ok(DatabaseHealth {
                         is_connected: false,
@@ -392,6 +628,7 @@ impl DatabaseManager {
                         active_queries: 0,
                         cache_hit_ratio: 0.0,
                         error_message: Some(e.to_string()),
+                        schema_version: self.schema_version,
                     }),
                 }
             }
@@ -401,6 +638,7 @@ impl DatabaseManager {
                 active_queries: 0,
                 cache_hit_ratio: 0.0,
                 error_message: Some(e.to_string()),
+                schema_version: self.schema_version,
             }),
         }
     }
@@ -409,28 +647,16 @@ impl DatabaseManager {
         let pool = self.pool.as_ref().ok_or_else(|| anyhow!("No database connection"))?;
 
         // Get total events
-        let total_events_row = sqlx::query("SELECT COUNT(*) as count FROM events")
-            .fetch_one(pool)
-            .await?;
-        let total_events: i64 = total_events_row.get("count");
+        let total_events = self.count(pool, "SELECT COUNT(*) as count FROM events").await?;
 
         // Get unique actors
-        let unique_actors_row = sqlx::query("SELECT COUNT(DISTINCT actor_id) as count FROM events WHERE actor_id IS NOT NULL")
-            .fetch_one(pool)
-            .await?;
-        let unique_actors: i64 = unique_actors_row.get("count");
+        let unique_actors = self.count(pool, "SELECT COUNT(DISTINCT actor_id) as count FROM events WHERE actor_id IS NOT NULL").await?;
 
         // Get unique repositories
-        let unique_repos_row = sqlx::query("SELECT COUNT(DISTINCT repo_id) as count FROM events WHERE repo_id IS NOT NULL")
-            .fetch_one(pool)
-            .await?;
-        let unique_repos: i64 = unique_repos_row.get("count");
+        let unique_repos = self.count(pool, "SELECT COUNT(DISTINCT repo_id) as count FROM events WHERE repo_id IS NOT NULL").await?;
 
         // Get event types
-        let event_types_row = sqlx::query("SELECT COUNT(DISTINCT event_type) as count FROM events")
-            .fetch_one(pool)
-            .await?;
-        let event_types: i64 = event_types_row.get("count");
+        let event_types = self.count(pool, "SELECT COUNT(DISTINCT event_type) as count FROM events").await?;
 
         // Calculate quality score (simplified)
         let quality_score = if total_events > 0 {
@@ -443,16 +669,10 @@ impl DatabaseManager {
         // Get integrity issues (simplified)
         let mut integrity_issues = HashMap::new();
         
-        let null_actors_row = sqlx::query("SELECT COUNT(*) as count FROM events WHERE actor_id IS NULL")
-            .fetch_one(pool)
-            .await?;
-        let null_actors: i64 = null_actors_row.get("count");
+        let null_actors = self.count(pool, "SELECT COUNT(*) as count FROM events WHERE actor_id IS NULL").await?;
         integrity_issues.insert("null_actors".to_string(), null_actors as u64);
 
-        let null_repos_row = sqlx::query("SELECT COUNT(*) as count FROM events WHERE repo_id IS NULL")
-            .fetch_one(pool)
-            .await?;
-        let null_repos: i64 = null_repos_row.get("count");
+        let null_repos = self.count(pool, "SELECT COUNT(*) as count FROM events WHERE repo_id IS NULL").await?;
         integrity_issues.insert("null_repos".to_string(), null_repos as u64);
 
         // Processing stats
@@ -461,10 +681,9 @@ impl DatabaseManager {
 
         // Recent activity (last 24 hours)
         let mut recent_activity = HashMap::new();
-        let recent_events_row = sqlx::query(
-            "SELECT COUNT(*) as count FROM events WHERE processed_at > NOW() - INTERVAL '24 hours'"
-        ).fetch_one(pool).await?;
-        let recent_events: i64 = recent_events_row.get("count");
+        let recent_events = self
+            .count(pool, "SELECT COUNT(*) as count FROM events WHERE processed_at > NOW() - INTERVAL '24 hours'")
+            .await?;
         recent_activity.insert("events_24h".to_string(), recent_events as u64);
 
         Ok(QualityMetrics {
@@ -479,6 +698,109 @@ impl DatabaseManager {
         })
     }
 
+    /// A parameterless `SELECT COUNT(*) as count`, timed for the slow query log
+    async fn count(&self, pool: &Pool<Postgres>, sql: &str) -> Result<i64> {
+        let started = Instant::now();
+        let row = sqlx::query(sql).fetch_one(pool).await?;
+        self.note_query(sql, started.elapsed()).await;
+        Ok(row.get("count"))
+    }
+
+    /// Record a statement that took longer than `SLOW_QUERY_MS` in the slow query log with
+    /// its plan. Failing to explain or record it never fails the caller.
+    async fn note_query(&self, sql: &str, elapsed: Duration) {
+        let (Some(threshold), Some(pool)) = (slow_query_threshold(), self.pool.as_ref()) else {
+            return;
+        };
+        if elapsed < threshold || !explainable(sql) {
+            return;
+        }
+
+        let duration_ms = elapsed.as_secs_f64() * 1000.0;
+        warn!("Slow query ({:.0} ms): {}", duration_ms, sql);
+        let plan = match Self::explain(pool, sql).await {
+            Ok(plan) => Some(plan),
+            Err(e) => {
+                debug!("Could not explain slow query: {}", e);
+                None
+            }
+        };
+        if let Err(e) = sqlx::query("INSERT INTO slow_queries (sql, duration_ms, plan) VALUES ($1, $2, $3)")
+            .bind(sql)
+            .bind(duration_ms)
+            .bind(plan)
+            .execute(pool)
+            .await
+        {
+            debug!("Failed to record slow query: {}", e);
+        }
+    }
+
+    /// `EXPLAIN (ANALYZE, BUFFERS)` for parameterless reads, which runs them again. Statements
+    /// with parameters get the generic plan (PostgreSQL 16 and later), and writes are only
+    /// ever planned, never run.
+    async fn explain(pool: &Pool<Postgres>, sql: &str) -> Result<String> {
+        let upper = sql.trim_start().to_ascii_uppercase();
+        let read = (upper.starts_with("SELECT") || upper.starts_with("WITH"))
+            && !["INSERT ", "UPDATE ", "DELETE "].iter().any(|verb| upper.contains(verb));
+        let explain = if sql.contains("$1") {
+            "EXPLAIN (GENERIC_PLAN)"
+        } else if read {
+            "EXPLAIN (ANALYZE, BUFFERS)"
+        } else {
+            "EXPLAIN"
+        };
+        let lines: Vec<String> = sqlx::query_scalar(&format!("{} {}", explain, sql)).fetch_all(pool).await?;
+        Ok(lines.join("\n"))
+    }
+
+    /// Most recent slow statements, newest first
+    pub async fn slow_queries(&self, limit: i64) -> Result<Vec<SlowQuery>> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("No database connection"))?;
+
+        let rows = sqlx::query(
+            "SELECT id, sql, duration_ms, plan,
+                to_char(recorded_at AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS') AS recorded_at
+            FROM slow_queries ORDER BY recorded_at DESC, id DESC LIMIT $1"
+        )
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| SlowQuery {
+                id: row.get("id"),
+                backend: "postgres".to_string(),
+                sql: row.get("sql"),
+                duration_ms: row.get("duration_ms"),
+                plan: row.get("plan"),
+                recorded_at: row.get("recorded_at"),
+            })
+            .collect())
+    }
+
+    /// `(table, leading column)` of every index on the archive tables
+    pub async fn indexed_columns(&self) -> Result<HashSet<(String, String)>> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("No database connection"))?;
+
+        let rows = sqlx::query(
+            "SELECT t.relname::text AS table_name, a.attname::text AS column_name
+            FROM pg_index i
+            JOIN pg_class t ON t.oid = i.indrelid
+            JOIN pg_namespace n ON n.oid = t.relnamespace
+            JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = i.indkey[0]
+            WHERE n.nspname = current_schema()"
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| (row.get::<String, _>("table_name"), row.get::<String, _>("column_name")))
+            .collect())
+    }
+
     pub async fn disconnect(&mut self) -> Result<()> {
         if let Some(pool) = &self.pool {
             pool.close().await;
@@ -0,0 +1,264 @@
+use anyhow::{anyhow, Context, Result};
+use reqwest::{Certificate, Client, Method, RequestBuilder, Response, StatusCode};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::core::config::HttpConfig;
+use crate::core::offline;
+
+/// Header a caller sets on a POST or PATCH the server deduplicates, which makes it safe to retry
+pub const IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+
+/// Exponential backoff policy shared by all outbound HTTP calls
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::from_config(&HttpConfig::default())
+    }
+}
+
+impl RetryPolicy {
+    /// Build a retry policy from the shared HTTP configuration
+    pub fn from_config(config: &HttpConfig) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            base_delay: Duration::from_millis(config.retry_base_delay_ms),
+            max_delay: Duration::from_millis(config.retry_max_delay_ms),
+        }
+    }
+
+    /// Delay before the given retry attempt (1-based), doubling each time and capped at `max_delay`
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Whether a response status is worth retrying
+    pub fn is_retryable_status(status: StatusCode) -> bool {
+        status == StatusCode::TOO_MANY_REQUESTS
+            || status == StatusCode::REQUEST_TIMEOUT
+            || status.is_server_error()
+    }
+
+    /// Whether sending a request with `method` twice has the same effect as sending it once
+    pub fn is_idempotent(method: &Method) -> bool {
+        matches!(*method, Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS | Method::TRACE)
+    }
+}
+
+/// Build a client from the shared HTTP configuration
+pub fn build_client(config: &HttpConfig) -> Result<Client> {
+    build_client_with(config, None, None)
+}
+
+/// Build a client with a module-specific request timeout
+pub fn build_client_with_timeout(config: &HttpConfig, timeout_secs: u64) -> Result<Client> {
+    build_client_with(config, Some(Duration::from_secs(timeout_secs)), None)
+}
+
+/// Build a client, optionally overriding the request timeout and user agent
+pub fn build_client_with(
+    config: &HttpConfig,
+    timeout: Option<Duration>,
+    user_agent: Option<&str>,
+) -> Result<Client> {
+    let mut builder = Client::builder()
+        .connect_timeout(Duration::from_secs(config.connect_timeout))
+        .timeout(timeout.unwrap_or_else(|| Duration::from_secs(config.request_timeout)))
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout))
+        .user_agent(user_agent.unwrap_or(&config.user_agent));
+
+    if let Some(ca_bundle_path) = &config.ca_bundle_path {
+        let pem = std::fs::read(ca_bundle_path)
+            .with_context(|| format!("Failed to read CA bundle {}", ca_bundle_path.display()))?;
+        for certificate in Certificate::from_pem_bundle(&pem)
+            .map_err(|e| anyhow!("Invalid CA bundle {}: {}", ca_bundle_path.display(), e))?
+        {
+            builder = builder.add_root_certificate(certificate);
+        }
+        debug!("Loaded custom CA bundle from {}", ca_bundle_path.display());
+    }
+
+    if config.accept_invalid_certs {
+        warn!("TLS certificate verification is disabled for outbound HTTP clients");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    offline::restrict(builder)
+        .build()
+        .map_err(|e| anyhow!("Failed to create HTTP client: {}", e))
+}
+
+/// Send a request, retrying transport errors and retryable statuses with exponential backoff.
+/// Honours `Retry-After` (seconds) when the server provides it.
+///
+/// Only idempotent methods, and requests carrying an `Idempotency-Key`, are retried after a
+/// timeout or a 5xx: the first attempt may have taken effect, and a repeated POST would open a
+/// second ticket or incident. Other requests are retried only when the server never took them,
+/// that is when the connection failed or the answer was `429 Too Many Requests`.
+pub async fn send_with_retry(request: RequestBuilder, policy: &RetryPolicy) -> Result<Response> {
+    send(request, policy, false).await
+}
+
+/// `send_with_retry` for a POST that is safe to repeat because it only reads, such as a GraphQL
+/// query or a git fetch, or because the server keys what it writes, such as documents indexed by id
+pub async fn send_idempotent_with_retry(request: RequestBuilder, policy: &RetryPolicy) -> Result<Response> {
+    send(request, policy, true).await
+}
+
+async fn send(request: RequestBuilder, policy: &RetryPolicy, idempotent: bool) -> Result<Response> {
+    let mut idempotent = idempotent;
+    // Blocked hosts fail straight away instead of being retried as connect errors
+    if let Some(Ok(built)) = request.try_clone().map(|r| r.build()) {
+        offline::policy().check_url(built.url().as_str())?;
+        idempotent |= RetryPolicy::is_idempotent(built.method()) || built.headers().contains_key(IDEMPOTENCY_KEY);
+    }
+
+    let mut attempt = 0;
+
+    loop {
+        let current = request
+            .try_clone()
+            .ok_or_else(|| anyhow!("Request body cannot be cloned for retry"))?;
+
+        match current.send().await {
+            Ok(response)
+                if attempt < policy.max_retries
+                    && (response.status() == StatusCode::TOO_MANY_REQUESTS
+                        || idempotent && RetryPolicy::is_retryable_status(response.status())) =>
+            {
+                attempt += 1;
+                let delay = retry_after(&response).unwrap_or_else(|| policy.delay_for_attempt(attempt));
+                warn!(
+                    "HTTP {} from {}, retrying attempt {} after {:?}",
+                    response.status(),
+                    response.url(),
+                    attempt,
+                    delay
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Ok(response) => return Ok(response),
+            Err(e) if attempt < policy.max_retries && (e.is_connect() || idempotent && (e.is_timeout() || e.is_request())) => {
+                attempt += 1;
+                let delay = policy.delay_for_attempt(attempt);
+                warn!("HTTP request failed ({}), retrying attempt {} after {:?}", e, attempt, delay);
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(anyhow!("HTTP request failed after {} retries: {}", attempt, e)),
+        }
+    }
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let policy = test_policy();
+        assert_eq!(policy.delay_for_attempt(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_for_attempt(2), Duration::from_millis(200));
+        assert_eq!(policy.delay_for_attempt(4), Duration::from_millis(800));
+        assert_eq!(policy.delay_for_attempt(5), Duration::from_millis(1000));
+        assert_eq!(policy.delay_for_attempt(40), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(RetryPolicy::is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(RetryPolicy::is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(!RetryPolicy::is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!RetryPolicy::is_retryable_status(StatusCode::OK));
+    }
+
+    #[test]
+    fn test_idempotent_methods() {
+        assert!(RetryPolicy::is_idempotent(&Method::GET));
+        assert!(RetryPolicy::is_idempotent(&Method::PUT));
+        assert!(RetryPolicy::is_idempotent(&Method::DELETE));
+        assert!(!RetryPolicy::is_idempotent(&Method::POST));
+        assert!(!RetryPolicy::is_idempotent(&Method::PATCH));
+    }
+
+    /// A server answering every request with `status`; returns its URL and how many requests it got
+    async fn failing_server(status: StatusCode) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let hits = std::sync::Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let app = axum::Router::new().fallback(move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                axum::http::StatusCode::from_u16(status.as_u16()).unwrap()
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, hits)
+    }
+
+    #[tokio::test]
+    async fn test_only_idempotent_requests_are_retried_after_server_errors() {
+        use std::sync::atomic::Ordering;
+        let policy = RetryPolicy { max_retries: 2, base_delay: Duration::from_millis(1), max_delay: Duration::from_millis(1) };
+        let client = Client::new();
+        let (url, hits) = failing_server(StatusCode::BAD_GATEWAY).await;
+
+        let response = send_with_retry(client.get(&url), &policy).await.unwrap();
+        assert_eq!((response.status(), hits.swap(0, Ordering::SeqCst)), (StatusCode::BAD_GATEWAY, 3));
+
+        send_with_retry(client.post(&url).body("ticket"), &policy).await.unwrap();
+        assert_eq!(hits.swap(0, Ordering::SeqCst), 1, "a POST that may have taken effect is sent once");
+
+        send_with_retry(client.post(&url).header(IDEMPOTENCY_KEY, "finding-1").body("ticket"), &policy).await.unwrap();
+        assert_eq!(hits.swap(0, Ordering::SeqCst), 3);
+        send_idempotent_with_retry(client.post(&url).body("{\"query\": \"{ viewer { login } }\"}"), &policy).await.unwrap();
+        assert_eq!(hits.swap(0, Ordering::SeqCst), 3);
+
+        let (url, hits) = failing_server(StatusCode::TOO_MANY_REQUESTS).await;
+        send_with_retry(client.post(&url).body("ticket"), &policy).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 3, "a rate-limited POST was never taken");
+    }
+
+    #[test]
+    fn test_build_client_from_default_config() {
+        let config = HttpConfig::default();
+        assert!(build_client(&config).is_ok());
+        assert!(build_client_with_timeout(&config, 180).is_ok());
+    }
+
+    #[test]
+    fn test_missing_ca_bundle_fails() {
+        let config = HttpConfig {
+            ca_bundle_path: Some("/nonexistent/ca.pem".into()),
+            ..HttpConfig::default()
+        };
+        assert!(build_client(&config).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+use super::config::IngestConfig;
+use super::database::ValidatedEvent;
+
+/// Drops unwanted event types before they reach Postgres and trims the payload of the
+/// rest to the configured fields, so secret-hunting deployments only store what they query.
+#[derive(Debug, Clone)]
+pub struct IngestFilter {
+    event_types: HashSet<String>,
+    payload_fields: HashMap<String, Vec<String>>,
+    store_raw_event: bool,
+}
+
+impl IngestFilter {
+    /// Build a filter when the ingest settings restrict anything; `None` stores events unchanged
+    pub fn from_config(ingest: &IngestConfig) -> Option<Self> {
+        if ingest.event_types.is_empty() && ingest.payload_fields.is_empty() && ingest.store_raw_event {
+            return None;
+        }
+        Some(Self {
+            event_types: ingest.event_types.iter().map(|t| t.trim().to_string()).collect(),
+            payload_fields: ingest.payload_fields.clone(),
+            store_raw_event: ingest.store_raw_event,
+        })
+    }
+
+    pub fn accepts_type(&self, event_type: &str) -> bool {
+        self.event_types.is_empty() || self.event_types.contains(event_type)
+    }
+
+    /// Whether a raw archive event should be stored at all
+    pub fn accepts(&self, event: &Value) -> bool {
+        event.get("type").and_then(Value::as_str).map_or(false, |t| self.accepts_type(t))
+    }
+
+    /// The payload of an `event_type` event as stored: only the configured fields, if any
+    pub fn project_payload(&self, event_type: &str, payload: &Value) -> Value {
+        match self.payload_fields.get(event_type) {
+            Some(fields) => project(payload, fields),
+            None => payload.clone(),
+        }
+    }
+
+    pub fn stores_raw_event(&self) -> bool {
+        self.store_raw_event
+    }
+
+    /// Project the payload (in both the payload column and the raw event) and drop the
+    /// raw event if it is not kept
+    pub fn apply(&self, event: &mut ValidatedEvent) {
+        if self.payload_fields.contains_key(&event.event_type) {
+            event.payload = self.project_payload(&event.event_type, &event.payload);
+            if let Some(raw_payload) = event.raw_event.get_mut("payload") {
+                *raw_payload = event.payload.clone();
+            }
+        }
+        if !self.store_raw_event {
+            event.raw_event = Value::Null;
+        }
+    }
+}
+
+/// Keep only the dotted `paths` of `value`. Arrays are projected element by element,
+/// so `commits.sha` keeps the sha of every commit.
+pub fn project(value: &Value, paths: &[String]) -> Value {
+    let split: Vec<Vec<&str>> = paths.iter().map(|p| p.split('.').collect()).collect();
+    project_paths(value, &split)
+}
+
+fn project_paths(value: &Value, paths: &[Vec<&str>]) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut projected = Map::new();
+            for (key, child) in map {
+                let matching: Vec<&Vec<&str>> = paths.iter().filter(|p| p.first() == Some(&key.as_str())).collect();
+                if matching.is_empty() {
+                    continue;
+                }
+                if matching.iter().any(|p| p.len() == 1) {
+                    projected.insert(key.clone(), child.clone());
+                } else {
+                    let tails: Vec<Vec<&str>> = matching.iter().map(|p| p[1..].to_vec()).collect();
+                    projected.insert(key.clone(), project_paths(child, &tails));
+                }
+            }
+            Value::Object(projected)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|item| project_paths(item, paths)).collect()),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_project_keeps_nested_and_array_fields() {
+        let payload = json!({
+            "ref": "refs/heads/main",
+            "before": "abc",
+            "size": 2,
+            "commits": [
+                {"sha": "1", "message": "one", "author": {"name": "a", "email": "a@x"}},
+                {"sha": "2", "message": "two", "author": {"name": "b", "email": "b@x"}}
+            ]
+        });
+        let fields = vec!["ref".to_string(), "before".to_string(), "commits.sha".to_string(), "commits.author.email".to_string()];
+
+        assert_eq!(
+            project(&payload, &fields),
+            json!({
+                "ref": "refs/heads/main",
+                "before": "abc",
+                "commits": [
+                    {"sha": "1", "author": {"email": "a@x"}},
+                    {"sha": "2", "author": {"email": "b@x"}}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_filter_by_event_type() {
+        let config = IngestConfig {
+            event_types: vec!["PushEvent".to_string(), "PublicEvent".to_string()],
+            payload_fields: HashMap::new(),
+            store_raw_event: true,
+        };
+        let filter = IngestFilter::from_config(&config).unwrap();
+        assert!(filter.accepts(&json!({"type": "PushEvent"})));
+        assert!(!filter.accepts(&json!({"type": "WatchEvent"})));
+        assert!(!filter.accepts(&json!({})));
+
+        let unrestricted = IngestConfig { event_types: Vec::new(), ..config };
+        assert!(IngestFilter::from_config(&unrestricted).is_none());
+    }
+
+    #[test]
+    fn test_partial_ingest_config_and_payload_projection() {
+        let config: IngestConfig = serde_json::from_value(json!({
+            "event_types": ["PushEvent"],
+            "payload_fields": {"PushEvent": ["ref"]}
+        }))
+        .unwrap();
+        assert!(config.store_raw_event || std::env::var("INGEST_STORE_RAW_EVENT").is_ok());
+
+        let filter = IngestFilter::from_config(&config).unwrap();
+        assert!(filter.accepts_type("PushEvent") && !filter.accepts_type("WatchEvent"));
+        let payload = json!({"ref": "refs/heads/main", "commits": []});
+        assert_eq!(filter.project_payload("PushEvent", &payload), json!({"ref": "refs/heads/main"}));
+        assert_eq!(filter.project_payload("CreateEvent", &payload), payload);
+    }
+}
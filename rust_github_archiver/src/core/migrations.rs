@@ -0,0 +1,128 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::info;
+
+/// One forward step of a store's schema. Steps are applied in order and never edited once
+/// released; a change to the schema is a new step.
+#[derive(Debug)]
+pub struct Migration {
+    pub version: i32,
+    pub description: &'static str,
+    pub statements: &'static [&'static str],
+}
+
+/// Stored schema version of one store next to the newest version this build knows about
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaVersion {
+    pub store: String,
+    pub version: i32,
+    pub supported: i32,
+}
+
+impl SchemaVersion {
+    pub fn new(store: &str, version: i32, migrations: &[Migration]) -> Self {
+        Self { store: store.to_string(), version, supported: latest_version(migrations) }
+    }
+}
+
+pub fn latest_version(migrations: &[Migration]) -> i32 {
+    migrations.last().map_or(0, |m| m.version)
+}
+
+/// Migrations newer than `stored`. A database written by a newer build is refused rather
+/// than opened, since this build would not know what its tables mean.
+pub fn pending<'a>(store: &str, stored: i32, migrations: &'a [Migration]) -> Result<&'a [Migration]> {
+    let latest = latest_version(migrations);
+    if stored > latest {
+        return Err(anyhow!(
+            "The {} database is at schema version {} but this build only supports up to version {}; \
+             run a newer release or restore a backup taken with this one",
+            store,
+            stored,
+            latest
+        ));
+    }
+    let start = migrations.iter().position(|m| m.version > stored).unwrap_or(migrations.len());
+    Ok(&migrations[start..])
+}
+
+const MIGRATIONS_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS schema_migrations (
+        store VARCHAR(50) NOT NULL,
+        version INTEGER NOT NULL,
+        description TEXT NOT NULL,
+        applied_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        PRIMARY KEY (store, version)
+    )
+"#;
+
+/// Highest migration applied to `store`; 0 for a database that predates migrations
+pub async fn postgres_version(pool: &PgPool, store: &str) -> Result<i32> {
+    sqlx::query(MIGRATIONS_TABLE).execute(pool).await?;
+    let version: Option<i32> = sqlx::query_scalar("SELECT MAX(version) FROM schema_migrations WHERE store = $1")
+        .bind(store)
+        .fetch_one(pool)
+        .await?;
+    Ok(version.unwrap_or(0))
+}
+
+/// Apply the pending migrations of `store` in one transaction and return the resulting
+/// version. The advisory lock keeps two processes starting together from racing.
+pub async fn migrate_postgres(pool: &PgPool, store: &str, migrations: &[Migration]) -> Result<i32> {
+    sqlx::query(MIGRATIONS_TABLE).execute(pool).await?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(store)
+        .execute(&mut *tx)
+        .await?;
+    let stored: Option<i32> = sqlx::query_scalar("SELECT MAX(version) FROM schema_migrations WHERE store = $1")
+        .bind(store)
+        .fetch_one(&mut *tx)
+        .await?;
+    let stored = stored.unwrap_or(0);
+
+    let pending = pending(store, stored, migrations)?;
+    for migration in pending {
+        info!("Migrating {} schema to version {}: {}", store, migration.version, migration.description);
+        for statement in migration.statements {
+            sqlx::query(statement).execute(&mut *tx).await.map_err(|e| {
+                anyhow!("Migration {} of the {} schema failed: {}", migration.version, store, e)
+            })?;
+        }
+        sqlx::query("INSERT INTO schema_migrations (store, version, description) VALUES ($1, $2, $3)")
+            .bind(store)
+            .bind(migration.version)
+            .bind(migration.description)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(pending.last().map_or(stored, |m| m.version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIGRATIONS: &[Migration] = &[
+        Migration { version: 1, description: "baseline", statements: &[] },
+        Migration { version: 2, description: "add table", statements: &["CREATE TABLE t (id INTEGER)"] },
+    ];
+
+    #[test]
+    fn test_pending_migrations() {
+        assert_eq!(pending("archive", 0, MIGRATIONS).unwrap().len(), 2);
+        assert_eq!(pending("archive", 1, MIGRATIONS).unwrap()[0].version, 2);
+        assert!(pending("archive", 2, MIGRATIONS).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_downgrade_is_refused() {
+        let error = pending("archive", 3, MIGRATIONS).unwrap_err().to_string();
+        assert!(error.contains("schema version 3"));
+        assert!(error.contains("up to version 2"));
+    }
+}
@@ -1,9 +1,28 @@
+pub mod anonymize;
+pub mod audit;
 pub mod config;
 pub mod database;
 pub mod enhanced_database;
+pub mod http_client;
+pub mod ingest;
+pub mod migrations;
+pub mod offline;
+pub mod output;
+pub mod paths;
 pub mod resource_monitor;
+pub mod slow_queries;
+pub mod scan_workspace;
 
-pub use config::Config;
+pub use anonymize::ActorAnonymizer;
+pub use audit::AuditLog;
+pub use config::{in_daily_window, BigQueryConfig, CanaryConfig, CategoryRetention, ChatPlatform, ChatTarget, Config, DigestSchedule, DiskForecastConfig, DownloadWindow, HttpConfig, IncidentProvider, IncidentTarget, IngestConfig, Locale, LoggingConfig, MaintenanceConfig, NotificationsConfig, OfflineConfig, OidcConfig, OutputConfig, OutputProfile, PrivacyConfig, QueryCacheConfig, QuietWindow, QuotaConfig, QuotaLimits, RetentionAction, RetentionConfig, ScanWorkspaceConfig, ServerConfig, SinkTarget, SinksConfig, StorageCodec, StorageCompressionConfig, TicketTarget, WebhookTarget};
 pub use database::Database;
 pub use enhanced_database::{DatabaseManager, DatabaseHealth, QualityMetrics, ProcessedFile};
-pub use resource_monitor::{ResourceMonitor, ResourceStatus, ResourceLimits, CleanupResult};
+pub use ingest::IngestFilter;
+pub use migrations::{Migration, SchemaVersion};
+pub use offline::OfflinePolicy;
+pub use output::OutputStyle;
+pub use http_client::{RetryPolicy, build_client, build_client_with_timeout, send_idempotent_with_retry, send_with_retry, IDEMPOTENCY_KEY};
+pub use resource_monitor::{ResourceMonitor, ResourceStatus, ResourceLimits, CleanupResult, DiskForecast, DiskSample};
+pub use slow_queries::{slow_query_threshold, IndexSuggestion, SlowQuery, SlowQueryReport};
+pub use scan_workspace::{ScanWorkspace, ScanWorkspaceManager};
@@ -0,0 +1,202 @@
+use anyhow::{anyhow, Result};
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::{ClientBuilder, Proxy, Url};
+use std::net::IpAddr;
+use std::sync::{Arc, OnceLock};
+use tracing::{error, info, warn};
+
+use super::config::OfflineConfig;
+
+const PUBLIC_GITHUB_API: &str = "https://api.github.com";
+const PUBLIC_GITHUB_WEB: &str = "https://github.com";
+
+/// Blocked requests are routed to this proxy, whose name the offline resolver refuses to
+/// resolve; requests to IP literals never reach a resolver, so the proxy is what stops them.
+const BLOCKED_PROXY: &str = "http://blocked.offline.invalid";
+
+static POLICY: OnceLock<OfflinePolicy> = OnceLock::new();
+
+/// Which hosts the process may talk to. Outside offline mode everything is allowed.
+#[derive(Debug, Clone)]
+pub struct OfflinePolicy {
+    enabled: bool,
+    github_host: Option<String>,
+    allowed_hosts: Vec<String>,
+}
+
+impl OfflinePolicy {
+    pub fn new(config: &OfflineConfig) -> Self {
+        let normalize = |h: &String| h.trim().trim_end_matches('.').to_lowercase();
+        let github_host = config.github_host.as_ref().map(normalize);
+        let allowed_hosts = config.allowed_hosts.iter().map(normalize).chain(github_host.clone()).collect();
+
+        Self {
+            enabled: config.enabled,
+            github_host,
+            allowed_hosts,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Loopback is always reachable; anything else must be listed exactly (no wildcards)
+    pub fn is_host_allowed(&self, host: &str) -> bool {
+        if !self.enabled {
+            return true;
+        }
+
+        let host = host.trim_start_matches('[').trim_end_matches(']').trim_end_matches('.').to_lowercase();
+        if host == "localhost" || host.parse::<IpAddr>().map_or(false, |ip| ip.is_loopback()) {
+            return true;
+        }
+        self.allowed_hosts.iter().any(|allowed| *allowed == host)
+    }
+
+    /// Fail closed for a URL whose host is not allowed
+    pub fn check_url(&self, url: &str) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let parsed = Url::parse(url).map_err(|e| anyhow!("Offline mode: cannot parse URL {}: {}", url, e))?;
+        match parsed.host_str() {
+            Some(host) if self.is_host_allowed(host) => Ok(()),
+            Some(host) => Err(self.blocked(host)),
+            None => Err(anyhow!("Offline mode: URL {} has no host", url)),
+        }
+    }
+
+    /// Refuse to start a component that only talks to public services
+    pub fn ensure_online(&self, component: &str) -> Result<()> {
+        if self.enabled {
+            error!("Offline mode: refusing to start {}", component);
+            return Err(anyhow!("Offline mode: {} needs external network access and is disabled", component));
+        }
+        Ok(())
+    }
+
+    /// REST API root: the GitHub Enterprise host in offline mode, api.github.com otherwise
+    pub fn github_api_base(&self) -> Result<String> {
+        if !self.enabled {
+            return Ok(PUBLIC_GITHUB_API.to_string());
+        }
+        self.github_host
+            .as_ref()
+            .map(|host| format!("https://{}/api/v3", host))
+            .ok_or_else(|| anyhow!("Offline mode: set OFFLINE_GITHUB_HOST to reach a GitHub Enterprise instance"))
+    }
+
+    /// Web and smart-HTTP git root, matching `github_api_base`
+    pub fn github_web_base(&self) -> Result<String> {
+        if !self.enabled {
+            return Ok(PUBLIC_GITHUB_WEB.to_string());
+        }
+        self.github_host
+            .as_ref()
+            .map(|host| format!("https://{}", host))
+            .ok_or_else(|| anyhow!("Offline mode: set OFFLINE_GITHUB_HOST to reach a GitHub Enterprise instance"))
+    }
+
+    fn blocked(&self, host: &str) -> anyhow::Error {
+        error!("Offline mode: blocked outbound request to {}", host);
+        anyhow!("Offline mode: outbound requests to {} are not allowed", host)
+    }
+}
+
+/// Install the process-wide policy. Must run before any HTTP client is built;
+/// the policy cannot change afterwards, so a config reload never reopens the network.
+pub fn install(config: &OfflineConfig) {
+    let policy = OfflinePolicy::new(config);
+    if policy.enabled {
+        info!("✈️  Offline mode: outbound requests limited to {:?} and loopback", policy.allowed_hosts);
+    }
+    if POLICY.set(policy).is_err() {
+        warn!("Offline policy was already initialised; ignoring the new settings");
+    }
+}
+
+/// The active policy, read from the environment if `install` was never called
+pub fn policy() -> &'static OfflinePolicy {
+    POLICY.get_or_init(|| OfflinePolicy::new(&OfflineConfig::default()))
+}
+
+/// Apply the policy to a client under construction: system proxies are ignored and
+/// every connection to a disallowed host fails before a socket is opened.
+pub(crate) fn restrict(builder: ClientBuilder) -> ClientBuilder {
+    if !policy().enabled() {
+        return builder;
+    }
+
+    builder
+        .no_proxy()
+        .proxy(Proxy::custom(|url| {
+            let host = url.host_str().unwrap_or_default();
+            if policy().is_host_allowed(host) {
+                None
+            } else {
+                error!("Offline mode: blocked outbound request to {}", host);
+                Some(BLOCKED_PROXY)
+            }
+        }))
+        .dns_resolver(Arc::new(OfflineResolver))
+}
+
+/// Resolves allowed hosts through the system resolver and refuses everything else,
+/// so no DNS query for a blocked name ever leaves the machine
+struct OfflineResolver;
+
+impl Resolve for OfflineResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            if !policy().is_host_allowed(&host) {
+                return Err(policy().blocked(&host).into());
+            }
+            let addrs: Vec<_> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offline_policy() -> OfflinePolicy {
+        OfflinePolicy::new(&OfflineConfig {
+            enabled: true,
+            github_host: Some("GitHub.Corp.Example".to_string()),
+            allowed_hosts: vec!["splunk.corp.example".to_string()],
+        })
+    }
+
+    #[test]
+    fn test_only_listed_hosts_and_loopback_allowed() {
+        let policy = offline_policy();
+
+        assert!(policy.check_url("https://github.corp.example/api/v3/events").is_ok());
+        assert!(policy.check_url("https://splunk.corp.example:8088/services/collector").is_ok());
+        assert!(policy.check_url("http://127.0.0.1:9200/findings").is_ok());
+        assert!(policy.check_url("http://[::1]:8080/").is_ok());
+        assert!(policy.check_url("https://api.github.com/events").is_err());
+        assert!(policy.check_url("https://evil.splunk.corp.example/").is_err());
+        assert!(policy.check_url("http://203.0.113.7/").is_err());
+        assert!(policy.ensure_online("BigQuery").is_err());
+    }
+
+    #[test]
+    fn test_github_api_base() {
+        assert_eq!(offline_policy().github_api_base().unwrap(), "https://github.corp.example/api/v3");
+        assert_eq!(offline_policy().github_web_base().unwrap(), "https://github.corp.example");
+
+        let no_ghe = OfflinePolicy::new(&OfflineConfig { enabled: true, github_host: None, allowed_hosts: vec![] });
+        assert!(no_ghe.github_api_base().is_err());
+
+        let online = OfflinePolicy::new(&OfflineConfig { enabled: false, github_host: None, allowed_hosts: vec![] });
+        assert_eq!(online.github_api_base().unwrap(), PUBLIC_GITHUB_API);
+        assert!(online.check_url("https://api.github.com/events").is_ok());
+    }
+}
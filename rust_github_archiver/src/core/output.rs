@@ -0,0 +1,227 @@
+use std::borrow::Cow;
+use std::io::{self, Write};
+use serde::Serialize;
+use std::sync::OnceLock;
+use tracing::warn;
+
+use super::config::{OutputConfig, OutputProfile};
+
+static STYLE: OnceLock<OutputStyle> = OnceLock::new();
+
+/// Severity colors, Low to Critical
+const STANDARD_SEVERITY_COLORS: [[u8; 3]; 4] = [[0x4A, 0x90, 0xE2], [0xF2, 0xC9, 0x4C], [0xF2, 0x99, 0x4A], [0xE0, 0x1E, 0x5A]];
+/// Okabe-Ito blue, yellow, orange and vermillion, which stay distinct without red-green vision
+const COLORBLIND_SEVERITY_COLORS: [[u8; 3]; 4] = [[0x00, 0x72, 0xB2], [0xF0, 0xE4, 0x42], [0xE6, 0x9F, 0x00], [0xD5, 0x5E, 0x00]];
+
+/// Symbols written as ASCII under the `ascii` profile
+const ASCII_SYMBOLS: &[(char, &str)] = &[
+    ('…', "..."),
+    ('•', "*"),
+    ('·', "-"),
+    ('—', "-"),
+    ('–', "-"),
+    ('→', "->"),
+    ('▶', ">"),
+    ('✓', "[ok]"),
+    ('✗', "[x]"),
+    ('─', "-"),
+    ('└', "`"),
+    ('│', "|"),
+    ('“', "\""),
+    ('”', "\""),
+    ('‘', "'"),
+    ('’', "'"),
+];
+
+/// Emoji and pictographs, with the joiners and presentation selectors that build them
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x2300..=0x23FF | 0xFE0F | 0x200D
+    ) && !ASCII_SYMBOLS.iter().any(|(symbol, _)| *symbol == c)
+}
+
+/// How CLI, GUI and notification output draws severities and status markers. Notification
+/// templates see it as `output`, with `emoji`, `ascii` and `colorblind_safe`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct OutputStyle {
+    emoji: bool,
+    ascii: bool,
+    colorblind_safe: bool,
+}
+
+impl OutputStyle {
+    pub fn new(config: &OutputConfig) -> Self {
+        let has = |profile| config.profiles.contains(&profile);
+        let ascii = has(OutputProfile::Ascii);
+        Self {
+            emoji: !ascii && !has(OutputProfile::NoEmoji),
+            ascii,
+            colorblind_safe: has(OutputProfile::ColorblindSafe),
+        }
+    }
+
+    pub fn emoji(&self) -> bool {
+        self.emoji
+    }
+
+    pub fn ascii(&self) -> bool {
+        self.ascii
+    }
+
+    /// Terminal colors; off for plain ASCII output
+    pub fn ansi(&self) -> bool {
+        !self.ascii
+    }
+
+    /// Color of a severity ranked as `severity_rank` ranks it, 0 for Low to 3 for Critical
+    pub fn severity_color(&self, rank: u8) -> [u8; 3] {
+        let palette = if self.colorblind_safe { &COLORBLIND_SEVERITY_COLORS } else { &STANDARD_SEVERITY_COLORS };
+        palette[usize::from(rank.min(3))]
+    }
+
+    /// Color of a passed or failed check, such as a verified or invalid secret
+    pub fn status_color(&self, ok: bool) -> [u8; 3] {
+        match (ok, self.colorblind_safe) {
+            (true, false) => [0x2D, 0xA4, 0x4E],
+            (false, false) => [0xE0, 0x1E, 0x5A],
+            (true, true) => [0x00, 0x72, 0xB2],
+            (false, true) => [0xD5, 0x5E, 0x00],
+        }
+    }
+
+    /// `text` with its emoji left out unless emoji are on, and its typographic symbols
+    /// spelled in ASCII under the `ascii` profile. Letters are kept, accents included.
+    pub fn text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let drops_emoji = !self.emoji && text.chars().any(is_emoji);
+        let replaces = self.ascii && text.chars().any(|c| ASCII_SYMBOLS.iter().any(|(symbol, _)| *symbol == c));
+        if !drops_emoji && !replaces {
+            return Cow::Borrowed(text);
+        }
+
+        let mut styled = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if drops_emoji && is_emoji(c) {
+                // An emoji leading a line or word takes the spaces after it along
+                if styled.is_empty() || styled.ends_with(char::is_whitespace) {
+                    while chars.next_if(|next| *next == ' ' || is_emoji(*next)).is_some() {}
+                }
+                continue;
+            }
+            match ASCII_SYMBOLS.iter().find(|(symbol, _)| *symbol == c).filter(|_| self.ascii) {
+                Some((_, ascii)) => styled.push_str(ascii),
+                None => styled.push(c),
+            }
+        }
+        Cow::Owned(styled)
+    }
+
+    /// Emoji codes such as `:warning:` in Slack text, or nothing when emoji are off
+    pub fn slack_emoji<'a>(&self, code: &'a str) -> &'a str {
+        if self.emoji {
+            code
+        } else {
+            ""
+        }
+    }
+}
+
+/// Install the process-wide style from the configuration
+pub fn install(config: &OutputConfig) {
+    if STYLE.set(OutputStyle::new(config)).is_err() {
+        warn!("The output profile was already configured; ignoring the new settings");
+    }
+}
+
+/// The active style. Until `install` runs it comes from the environment, so log lines
+/// written while the configuration loads are styled too.
+pub fn style() -> &'static OutputStyle {
+    static FROM_ENV: OnceLock<OutputStyle> = OnceLock::new();
+    STYLE.get().unwrap_or_else(|| FROM_ENV.get_or_init(|| OutputStyle::new(&OutputConfig::default())))
+}
+
+/// Writes log lines through the active style. Under the `ascii` profile the ANSI color
+/// codes of the log formatter are removed too.
+pub struct StyledWriter<W> {
+    inner: W,
+}
+
+impl<W> StyledWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+}
+
+/// `text` without ANSI escape sequences
+fn strip_ansi(text: &str) -> Cow<'_, str> {
+    if !text.contains('\x1b') {
+        return Cow::Borrowed(text);
+    }
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            plain.push(c);
+            continue;
+        }
+        // CSI sequences end at their first letter
+        if chars.next() == Some('[') {
+            for next in chars.by_ref() {
+                if next.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        }
+    }
+    Cow::Owned(plain)
+}
+
+impl<W: Write> Write for StyledWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let style = style();
+        match std::str::from_utf8(buf) {
+            Ok(line) if !style.emoji() => {
+                let styled = style.text(line);
+                let styled = if style.ascii { strip_ansi(&styled).into_owned() } else { styled.into_owned() };
+                self.inner.write_all(styled.as_bytes())?;
+            }
+            _ => self.inner.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style(profiles: &[OutputProfile]) -> OutputStyle {
+        OutputStyle::new(&OutputConfig { profiles: profiles.to_vec() })
+    }
+
+    #[test]
+    fn test_profiles_restyle_text() {
+        let line = "🔍 Scanning acme/api • ✈️  offline… ✓ done";
+        assert_eq!(style(&[]).text(line), line);
+        assert_eq!(style(&[OutputProfile::NoEmoji]).text(line), "Scanning acme/api • offline… ✓ done");
+        assert_eq!(style(&[OutputProfile::Ascii]).text(line), "Scanning acme/api * offline... [ok] done");
+        // Translated text keeps its letters
+        assert_eq!(style(&[OutputProfile::Ascii]).text("Crítica: Schweregrad über"), "Crítica: Schweregrad über");
+        assert_eq!(strip_ansi("\x1b[32m INFO\x1b[0m ready"), " INFO ready");
+    }
+
+    #[test]
+    fn test_colorblind_palette() {
+        let standard = style(&[]);
+        let safe = style(&[OutputProfile::ColorblindSafe, OutputProfile::NoEmoji]);
+        assert_eq!(standard.severity_color(3), [0xE0, 0x1E, 0x5A]);
+        assert_eq!(safe.severity_color(3), [0xD5, 0x5E, 0x00]);
+        assert_ne!(safe.status_color(true), safe.status_color(false));
+        assert!(!safe.emoji() && safe.ansi());
+        assert!(!style(&[OutputProfile::Ascii]).ansi());
+    }
+}
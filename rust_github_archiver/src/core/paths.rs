@@ -0,0 +1,59 @@
+use directories::ProjectDirs;
+use std::path::{Path, PathBuf};
+
+/// Per-platform application directories:
+/// - Linux: `~/.local/share/gitarchiver`, `~/.cache/gitarchiver`, `~/.config/gitarchiver`
+/// - macOS: `~/Library/Application Support/com.Bit-Loop.GitArchiver` (data and config), `~/Library/Caches/...`
+/// - Windows: `%APPDATA%\Bit-Loop\GitArchiver\data`, `%LOCALAPPDATA%\Bit-Loop\GitArchiver\cache`,
+///   `%APPDATA%\Bit-Loop\GitArchiver\config`
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("com", "Bit-Loop", "GitArchiver")
+}
+
+/// Where persistent data (databases, logs) lives when no explicit path is given.
+/// Falls back to the working directory for accounts without a home directory.
+pub fn data_dir() -> PathBuf {
+    project_dirs()
+        .map(|dirs| dirs.data_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Where disposable caches live
+pub fn cache_dir() -> PathBuf {
+    project_dirs()
+        .map(|dirs| dirs.cache_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from(".").join("cache"))
+}
+
+/// Where user-editable configuration (e.g. notification templates) lives
+pub fn config_dir() -> PathBuf {
+    project_dirs()
+        .map(|dirs| dirs.config_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from(".").join("config"))
+}
+
+/// Anchor a relative path at `base`; absolute paths are returned unchanged.
+/// Services start in a system directory (e.g. `C:\Windows\System32`), so relative
+/// paths from the command line must not be resolved against the working directory.
+pub fn resolve_in(base: &Path, path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base.join(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_in_keeps_absolute_paths() {
+        let base = std::env::temp_dir();
+        let absolute = base.join("secrets.db");
+
+        assert_eq!(resolve_in(Path::new("/elsewhere"), &absolute), absolute);
+        assert_eq!(resolve_in(&base, "secrets.db"), base.join("secrets.db"));
+    }
+}
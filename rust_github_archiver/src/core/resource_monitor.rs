@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 use chrono::{DateTime, Utc};
 use tokio::time;
@@ -64,10 +66,37 @@ impl Default for ResourceLimits {
     }
 }
 
+/// Samples spanning less than this are too noisy to project from
+const MIN_FORECAST_SPAN_SECS: i64 = 3600;
+
+/// Size of the tracked files and the free space left on their disk at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskSample {
+    pub timestamp: DateTime<Utc>,
+    /// Combined size of the tracked databases and caches
+    pub tracked_bytes: u64,
+    pub free_bytes: u64,
+}
+
+/// Growth of the tracked files projected against the free space left
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskForecast {
+    pub tracked_bytes: u64,
+    pub free_bytes: u64,
+    /// Least-squares fit over the sample window; negative after a purge
+    pub growth_bytes_per_day: f64,
+    /// Days until the disk fills at that rate; `None` while the files are not growing
+    pub days_remaining: Option<f64>,
+    pub samples: usize,
+    pub warning: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
 pub struct ResourceMonitor {
     limits: ResourceLimits,
     emergency_mode: bool,
     last_cpu_measurement: Option<(Instant, f64)>,
+    disk_samples: VecDeque<DiskSample>,
 }
 
 impl ResourceMonitor {
@@ -76,7 +105,46 @@ impl ResourceMonitor {
             limits,
             emergency_mode: false,
             last_cpu_measurement: None,
+            disk_samples: VecDeque::new(),
+        }
+    }
+
+    /// Record the size of `paths` (files or directories) and the free space on the disk
+    /// holding the first of them, keeping the samples taken within `window`
+    pub fn sample_disk_usage(&mut self, paths: &[PathBuf], window: Duration) -> Result<DiskSample> {
+        let sample = DiskSample {
+            timestamp: Utc::now(),
+            tracked_bytes: paths.iter().map(|path| path_size(path)).sum(),
+            free_bytes: free_space(paths.first().map(PathBuf::as_path).unwrap_or(Path::new(".")))?,
+        };
+
+        let cutoff = sample.timestamp - chrono::Duration::from_std(window)?;
+        while self.disk_samples.front().is_some_and(|s| s.timestamp < cutoff) {
+            self.disk_samples.pop_front();
         }
+        self.disk_samples.push_back(sample.clone());
+        Ok(sample)
+    }
+
+    /// Project the sampled growth against the free space. `None` until the samples span an hour.
+    pub fn disk_forecast(&self, warn_days: f64) -> Option<DiskForecast> {
+        let first = self.disk_samples.front()?;
+        let last = self.disk_samples.back()?;
+        if (last.timestamp - first.timestamp).num_seconds() < MIN_FORECAST_SPAN_SECS {
+            return None;
+        }
+
+        let growth_bytes_per_day = growth_per_day(self.disk_samples.iter());
+        let days_remaining = (growth_bytes_per_day > 0.0).then(|| last.free_bytes as f64 / growth_bytes_per_day);
+        Some(DiskForecast {
+            tracked_bytes: last.tracked_bytes,
+            free_bytes: last.free_bytes,
+            growth_bytes_per_day,
+            days_remaining,
+            samples: self.disk_samples.len(),
+            warning: days_remaining.is_some_and(|days| days < warn_days),
+            timestamp: last.timestamp,
+        })
     }
 
     pub async fn get_resource_status(&mut self) -> Result<ResourceStatus> {
@@ -246,10 +314,14 @@ impl ResourceMonitor {
 
     async fn cleanup_temp_files(&self) -> Result<u64> {
         let mut count = 0;
-        let temp_dirs = ["./tmp", "./temp", "./gharchive_data/tmp"];
-
-        for temp_dir in &temp_dirs {
-            let path = std::path::Path::new(temp_dir);
+        let temp_dirs = [
+            std::path::PathBuf::from("tmp"),
+            std::path::PathBuf::from("temp"),
+            std::path::Path::new("gharchive_data").join("tmp"),
+            super::paths::cache_dir().join("tmp"),
+        ];
+
+        for path in &temp_dirs {
             if !path.exists() {
                 continue;
             }
@@ -263,6 +335,9 @@ impl ResourceMonitor {
             }
         }
 
+        // Clones left behind by scans whose process died; running scans keep theirs
+        count += super::scan_workspace::manager().sweep_abandoned()? as u64;
+
         Ok(count)
     }
 
@@ -276,6 +351,54 @@ impl ResourceMonitor {
     }
 }
 
+/// Slope of tracked bytes over time, in bytes per day
+fn growth_per_day<'a>(samples: impl Iterator<Item = &'a DiskSample> + Clone) -> f64 {
+    let origin = match samples.clone().next() {
+        Some(first) => first.timestamp,
+        None => return 0.0,
+    };
+    let points: Vec<(f64, f64)> = samples
+        .map(|s| ((s.timestamp - origin).num_seconds() as f64 / 86400.0, s.tracked_bytes as f64))
+        .collect();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if variance == 0.0 {
+        0.0
+    } else {
+        covariance / variance
+    }
+}
+
+/// Size of a file, or of everything below a directory; 0 when it does not exist
+fn path_size(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|entry| path_size(&entry.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Space available on the disk mounted deepest above `path`
+fn free_space(path: &Path) -> Result<u64> {
+    let path = std::fs::canonicalize(path)
+        .or_else(|_| std::fs::canonicalize(path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."))))?;
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+        .ok_or_else(|| anyhow::anyhow!("No mounted disk holds {}", path.display()))
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct CleanupResult {
     pub actions_taken: Vec<String>,
@@ -283,3 +406,75 @@ pub struct CleanupResult {
     pub success: bool,
     pub timestamp: DateTime<Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(hours: i64, tracked_bytes: u64) -> DiskSample {
+        DiskSample {
+            timestamp: Utc::now() - chrono::Duration::hours(48) + chrono::Duration::hours(hours),
+            tracked_bytes,
+            free_bytes: 10_000,
+        }
+    }
+
+    #[test]
+    fn test_disk_forecast_projects_growth() {
+        let mut monitor = ResourceMonitor::new(ResourceLimits::default());
+        monitor.disk_samples.push_back(sample(0, 1_000));
+        assert!(monitor.disk_forecast(7.0).is_none());
+
+        // 1000 bytes a day against 10000 free
+        monitor.disk_samples.extend([sample(12, 1_500), sample(24, 2_000)]);
+        let forecast = monitor.disk_forecast(7.0).unwrap();
+        assert!((forecast.growth_bytes_per_day - 1_000.0).abs() < 1.0);
+        assert!((forecast.days_remaining.unwrap() - 10.0).abs() < 0.1);
+        assert!(!forecast.warning);
+        assert!(monitor.disk_forecast(14.0).unwrap().warning);
+
+        // Shrinking after a purge never warns
+        monitor.disk_samples.push_back(sample(36, 100));
+        monitor.disk_samples.push_back(sample(48, 50));
+        assert!(monitor.disk_forecast(14.0).unwrap().days_remaining.is_none());
+    }
+
+    #[test]
+    fn test_disk_forecast_needs_an_hour_of_samples() {
+        let mut monitor = ResourceMonitor::new(ResourceLimits::default());
+        assert!(monitor.disk_forecast(7.0).is_none());
+        monitor.disk_samples.extend([sample(0, 1_000), sample(0, 5_000)]);
+        assert!(monitor.disk_forecast(7.0).is_none(), "samples taken together say nothing about growth");
+        monitor.disk_samples.push_back(sample(1, 9_000));
+        assert_eq!(monitor.disk_forecast(7.0).unwrap().samples, 3);
+    }
+
+    #[test]
+    fn test_disk_forecast_without_growth_never_fills() {
+        let mut monitor = ResourceMonitor::new(ResourceLimits::default());
+        monitor.disk_samples.extend([sample(0, 4_000), sample(12, 4_000), sample(24, 4_000)]);
+        let flat = monitor.disk_forecast(f64::MAX).unwrap();
+        assert_eq!(flat.growth_bytes_per_day, 0.0);
+        assert!(flat.days_remaining.is_none());
+        assert!(!flat.warning);
+
+        let mut monitor = ResourceMonitor::new(ResourceLimits::default());
+        monitor.disk_samples.extend([sample(0, 4_000), sample(12, 3_000), sample(24, 2_000)]);
+        let shrinking = monitor.disk_forecast(f64::MAX).unwrap();
+        assert!((shrinking.growth_bytes_per_day + 2_000.0).abs() < 1.0);
+        assert!(shrinking.days_remaining.is_none());
+        assert!(!shrinking.warning);
+        assert_eq!((shrinking.tracked_bytes, shrinking.free_bytes), (2_000, 10_000));
+    }
+
+    #[test]
+    fn test_disk_forecast_fits_noisy_growth() {
+        // A dip in between does not stop the fit from seeing about 1700 bytes a day
+        let mut monitor = ResourceMonitor::new(ResourceLimits::default());
+        monitor.disk_samples.extend([sample(0, 1_000), sample(12, 2_500), sample(24, 2_000), sample(36, 4_000)]);
+        let forecast = monitor.disk_forecast(7.0).unwrap();
+        assert!((forecast.growth_bytes_per_day - 1_700.0).abs() < 1.0);
+        assert!((forecast.days_remaining.unwrap() - 10_000.0 / 1_700.0).abs() < 0.01);
+        assert!(forecast.warning);
+    }
+}
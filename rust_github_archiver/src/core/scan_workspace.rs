@@ -0,0 +1,242 @@
+use anyhow::{anyhow, Context, Result};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use super::config::ScanWorkspaceConfig;
+
+/// Held locked by the owning process for as long as its workspace lives
+const LOCK_FILE: &str = ".workspace.lock";
+
+/// A workspace directory without a lock file this old was abandoned while being created
+const LOCK_GRACE: Duration = Duration::from_secs(60);
+
+const MB: u64 = 1024 * 1024;
+
+static MANAGER: OnceLock<ScanWorkspaceManager> = OnceLock::new();
+
+/// Bytes in the files under `path`; symlinks are counted as links, not followed
+fn directory_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.path().symlink_metadata() {
+            Ok(metadata) if metadata.is_dir() => directory_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Hands out one scratch directory per scan under a common root, within size caps
+#[derive(Debug, Clone)]
+pub struct ScanWorkspaceManager {
+    root: PathBuf,
+    max_scan_bytes: u64,
+    max_total_bytes: u64,
+}
+
+impl ScanWorkspaceManager {
+    pub fn new(config: &ScanWorkspaceConfig) -> Self {
+        Self {
+            root: config.root.clone(),
+            max_scan_bytes: config.max_scan_mb.saturating_mul(MB),
+            max_total_bytes: config.max_total_mb.saturating_mul(MB),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Bytes used by every workspace under the root, live or abandoned
+    pub fn used_bytes(&self) -> u64 {
+        directory_size(&self.root)
+    }
+
+    /// Create a workspace for one scan; `label` names it, e.g. `clone`. Refused while the
+    /// root is over its total cap, after abandoned workspaces have been removed.
+    pub fn allocate(&self, label: &str) -> Result<ScanWorkspace> {
+        std::fs::create_dir_all(&self.root)
+            .with_context(|| format!("Failed to create scan workspace root {}", self.root.display()))?;
+        if self.max_total_bytes > 0 && self.used_bytes() >= self.max_total_bytes {
+            self.sweep_abandoned()?;
+            let used = self.used_bytes();
+            if used >= self.max_total_bytes {
+                return Err(anyhow!(
+                    "Scan workspaces under {} use {} MB, at the {} MB cap; wait for running scans to finish",
+                    self.root.display(),
+                    used / MB,
+                    self.max_total_bytes / MB
+                ));
+            }
+        }
+
+        let path = self.root.join(format!("{}-{}", label, uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir(&path).with_context(|| format!("Failed to create scan workspace {}", path.display()))?;
+        let lock = OpenOptions::new().create(true).write(true).truncate(false).open(path.join(LOCK_FILE))?;
+        if let Err(e) = lock.try_lock() {
+            std::fs::remove_dir_all(&path).ok();
+            return Err(anyhow!("Failed to lock scan workspace {}: {:?}", path.display(), e));
+        }
+        debug!("Allocated scan workspace {}", path.display());
+        Ok(ScanWorkspace { path, max_bytes: self.max_scan_bytes, lock: Some(lock) })
+    }
+
+    /// Remove workspaces whose process died without cleaning up (killed, out of memory,
+    /// power loss). A live workspace's lock is held, so it is never removed.
+    pub fn sweep_abandoned(&self) -> Result<usize> {
+        let Ok(entries) = std::fs::read_dir(&self.root) else {
+            return Ok(0);
+        };
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let abandoned = match File::open(path.join(LOCK_FILE)) {
+                Ok(lock) => match lock.try_lock() {
+                    Ok(()) => true,
+                    Err(TryLockError::WouldBlock) => false,
+                    Err(TryLockError::Error(e)) => {
+                        warn!("Cannot check scan workspace {}: {}", path.display(), e);
+                        false
+                    }
+                },
+                // Died between creating the directory and its lock file, unless it is being
+                // created right now
+                Err(_) => entry
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .map(|modified| modified.elapsed().unwrap_or_default() > LOCK_GRACE)
+                    .unwrap_or(false),
+            };
+            if abandoned {
+                match std::fs::remove_dir_all(&path) {
+                    Ok(()) => removed += 1,
+                    Err(e) => warn!("Failed to remove abandoned scan workspace {}: {}", path.display(), e),
+                }
+            }
+        }
+        if removed > 0 {
+            info!("Removed {} abandoned scan workspaces from {}", removed, self.root.display());
+        }
+        Ok(removed)
+    }
+}
+
+/// One scan's scratch directory. It is removed when dropped, which includes a panic
+/// unwinding through the scan and a cancelled task dropping its future.
+#[derive(Debug)]
+pub struct ScanWorkspace {
+    path: PathBuf,
+    max_bytes: u64,
+    lock: Option<File>,
+}
+
+impl ScanWorkspace {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The scan's size cap in bytes; 0 means none
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    pub fn used_bytes(&self) -> u64 {
+        directory_size(&self.path)
+    }
+
+    /// An error once the workspace has grown past its cap
+    pub fn check_size(&self) -> Result<()> {
+        if self.max_bytes == 0 {
+            return Ok(());
+        }
+        let used = self.used_bytes();
+        if used > self.max_bytes {
+            return Err(anyhow!(
+                "Scan workspace {} uses {} MB, over the {} MB cap (SCAN_WORKSPACE_MAX_MB)",
+                self.path.display(),
+                used / MB,
+                self.max_bytes / MB
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ScanWorkspace {
+    fn drop(&mut self) {
+        // Windows cannot delete a file that is still open
+        drop(self.lock.take());
+        match std::fs::remove_dir_all(&self.path) {
+            Ok(()) => debug!("Removed scan workspace {}", self.path.display()),
+            Err(e) => warn!("Failed to remove scan workspace {}: {}", self.path.display(), e),
+        }
+    }
+}
+
+/// Install the process-wide manager and remove workspaces abandoned by earlier runs
+pub fn install(config: &ScanWorkspaceConfig) {
+    if MANAGER.set(ScanWorkspaceManager::new(config)).is_err() {
+        warn!("Scan workspaces were already configured; ignoring the new settings");
+    }
+    if let Err(e) = manager().sweep_abandoned() {
+        warn!("Failed to clean up abandoned scan workspaces: {}", e);
+    }
+}
+
+/// The active manager, configured from the environment if `install` was never called
+pub fn manager() -> &'static ScanWorkspaceManager {
+    MANAGER.get_or_init(|| ScanWorkspaceManager::new(&ScanWorkspaceConfig::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager_in(root: &Path, max_scan_mb: u64) -> ScanWorkspaceManager {
+        ScanWorkspaceManager::new(&ScanWorkspaceConfig { root: root.to_path_buf(), max_scan_mb, max_total_mb: 0 })
+    }
+
+    #[test]
+    fn test_workspace_is_removed_on_drop_and_panic() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager_in(dir.path(), 1);
+
+        let workspace = manager.allocate("clone").unwrap();
+        let path = workspace.path().to_path_buf();
+        std::fs::write(path.join("pack"), vec![0u8; 1024]).unwrap();
+        workspace.check_size().unwrap();
+        std::fs::write(path.join("big"), vec![0u8; 2 * MB as usize]).unwrap();
+        assert!(workspace.check_size().is_err());
+        drop(workspace);
+        assert!(!path.exists());
+
+        let unwound = std::panic::catch_unwind(|| {
+            let _workspace = manager.allocate("clone").unwrap();
+            panic!("scan failed");
+        });
+        assert!(unwound.is_err());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_sweep_keeps_live_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager_in(dir.path(), 0);
+        let live = manager.allocate("clone").unwrap();
+        let abandoned = dir.path().join("clone-abandoned");
+        std::fs::create_dir(&abandoned).unwrap();
+        File::create(abandoned.join(LOCK_FILE)).unwrap();
+
+        assert_eq!(manager.sweep_abandoned().unwrap(), 1);
+        assert!(live.path().exists() && !abandoned.exists());
+    }
+}
@@ -0,0 +1,275 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Statements slower than this many milliseconds are recorded with their plan; 0 turns
+/// recording off
+pub const SLOW_QUERY_MS_ENV: &str = "SLOW_QUERY_MS";
+const DEFAULT_SLOW_QUERY_MS: u64 = 250;
+
+/// `SLOW_QUERY_MS`, or 250 ms; `None` when recording is off
+pub fn slow_query_threshold() -> Option<Duration> {
+    static THRESHOLD: OnceLock<u64> = OnceLock::new();
+    let ms = *THRESHOLD.get_or_init(|| {
+        std::env::var(SLOW_QUERY_MS_ENV).ok().and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_SLOW_QUERY_MS)
+    });
+    (ms > 0).then(|| Duration::from_millis(ms))
+}
+
+/// Whether a statement is worth explaining: queries and writes, not pragmas, transactions
+/// or the recording itself
+pub fn explainable(sql: &str) -> bool {
+    let sql = sql.trim_start().to_ascii_uppercase();
+    ["SELECT", "WITH", "INSERT", "UPDATE", "DELETE"].iter().any(|verb| sql.starts_with(verb))
+        && !sql.contains("SLOW_QUERIES")
+}
+
+/// One statement that took longer than the threshold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlowQuery {
+    pub id: i64,
+    /// `sqlite` or `postgres`
+    pub backend: String,
+    pub sql: String,
+    pub duration_ms: f64,
+    /// `EXPLAIN QUERY PLAN` or `EXPLAIN ANALYZE` output; unset when the statement could not be explained
+    pub plan: Option<String>,
+    pub recorded_at: String,
+}
+
+/// An index that would let a slow statement avoid a full table scan or a sort
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexSuggestion {
+    pub table: String,
+    pub columns: Vec<String>,
+    pub statement: String,
+    pub reason: String,
+}
+
+/// Slow statements with the same text, summarized for `database slow-queries`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlowQueryReport {
+    pub backend: String,
+    pub sql: String,
+    pub count: usize,
+    pub max_ms: f64,
+    pub mean_ms: f64,
+    pub last_seen: String,
+    pub plan: Option<String>,
+    pub suggestions: Vec<IndexSuggestion>,
+}
+
+/// Group slow statements by backend and text, slowest first, with index suggestions for
+/// the latest plan of each. `indexed` holds the `(table, leading column)` of existing indexes.
+pub fn summarize(queries: &[SlowQuery], indexed: &HashSet<(String, String)>) -> Vec<SlowQueryReport> {
+    let mut groups: BTreeMap<(String, String), Vec<&SlowQuery>> = BTreeMap::new();
+    for query in queries {
+        let sql = query.sql.split_whitespace().collect::<Vec<_>>().join(" ");
+        groups.entry((query.backend.clone(), sql)).or_default().push(query);
+    }
+
+    let mut reports: Vec<SlowQueryReport> = groups
+        .into_iter()
+        .map(|((backend, sql), runs)| {
+            let latest = runs.iter().max_by(|a, b| a.recorded_at.cmp(&b.recorded_at)).copied();
+            let plan = latest.and_then(|q| q.plan.clone());
+            SlowQueryReport {
+                suggestions: plan.as_deref().map(|plan| suggest_indexes(&sql, plan, indexed)).unwrap_or_default(),
+                count: runs.len(),
+                max_ms: runs.iter().map(|q| q.duration_ms).fold(0.0, f64::max),
+                mean_ms: runs.iter().map(|q| q.duration_ms).sum::<f64>() / runs.len() as f64,
+                last_seen: latest.map(|q| q.recorded_at.clone()).unwrap_or_default(),
+                backend,
+                sql,
+                plan,
+            }
+        })
+        .collect();
+    reports.sort_by(|a, b| (b.max_ms * b.count as f64).total_cmp(&(a.max_ms * a.count as f64)));
+    reports
+}
+
+/// Tables named in `FROM` and `JOIN`, keyed by alias (and by their own name)
+fn table_aliases(sql: &str) -> HashMap<String, String> {
+    static TABLES: OnceLock<Regex> = OnceLock::new();
+    let re = TABLES.get_or_init(|| {
+        Regex::new(r"(?i)\b(?:FROM|JOIN|UPDATE|INTO)\s+([A-Za-z_][A-Za-z0-9_]*)(?:\s+(?:AS\s+)?([A-Za-z_][A-Za-z0-9_]*))?").unwrap()
+    });
+    const KEYWORDS: &[&str] = &["WHERE", "JOIN", "LEFT", "INNER", "ON", "GROUP", "ORDER", "LIMIT", "SET", "VALUES", "USING"];
+
+    let mut aliases = HashMap::new();
+    for captures in re.captures_iter(sql) {
+        let table = captures[1].to_lowercase();
+        aliases.insert(table.clone(), table.clone());
+        if let Some(alias) = captures.get(2) {
+            if !KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(alias.as_str())) {
+                aliases.insert(alias.as_str().to_lowercase(), table);
+            }
+        }
+    }
+    aliases
+}
+
+/// Columns of `table` compared in `text` (a WHERE clause or a plan's filter), equality first
+fn filtered_columns(text: &str, table: &str, aliases: &HashMap<String, String>) -> Vec<String> {
+    static CASTS: OnceLock<Regex> = OnceLock::new();
+    static COMPARISONS: OnceLock<Regex> = OnceLock::new();
+    // Postgres filters cast their operands, as in `(actor_login)::text = 'x'::text`
+    let casts = CASTS.get_or_init(|| {
+        Regex::new(r"::(?:character varying|double precision|timestamp with time zone|[A-Za-z_][A-Za-z0-9_]*)(?:\[\])?").unwrap()
+    });
+    let text = casts.replace_all(text, "");
+    let re = COMPARISONS.get_or_init(|| {
+        Regex::new(r"(?i)(?:\b([A-Za-z_][A-Za-z0-9_]*)\.)?\b([A-Za-z_][A-Za-z0-9_]*)\)?\s*(=|<>|!=|<=|>=|<|>|\bIN\b|\bLIKE\b|\bIS\b|\bBETWEEN\b|~~)").unwrap()
+    });
+    let single_table = aliases.values().collect::<HashSet<_>>().len() <= 1;
+
+    let (mut equal, mut other) = (Vec::new(), Vec::new());
+    for captures in re.captures_iter(&text) {
+        let belongs = match captures.get(1) {
+            Some(alias) => aliases.get(&alias.as_str().to_lowercase()).is_some_and(|t| t == table),
+            None => single_table,
+        };
+        let column = captures[2].to_lowercase();
+        if !belongs || ["and", "or", "not", "null", "select", "where"].contains(&column.as_str()) {
+            continue;
+        }
+        let list = if &captures[3] == "=" || captures[3].eq_ignore_ascii_case("IN") { &mut equal } else { &mut other };
+        if !list.contains(&column) {
+            list.push(column);
+        }
+    }
+    equal.extend(other.into_iter().filter(|c| !equal.contains(c)).collect::<Vec<_>>());
+    equal
+}
+
+fn suggestion(table: &str, columns: Vec<String>, reason: String) -> IndexSuggestion {
+    IndexSuggestion {
+        statement: format!("CREATE INDEX IF NOT EXISTS idx_{}_{} ON {}({})", table, columns.join("_"), table, columns.join(", ")),
+        table: table.to_string(),
+        columns,
+        reason,
+    }
+}
+
+/// Indexes that would turn the full scans or temporary sorts in `plan` into index lookups.
+/// Reads SQLite's `EXPLAIN QUERY PLAN` (`SCAN t`, `USE TEMP B-TREE FOR ORDER BY`) and
+/// Postgres' `EXPLAIN` (`Seq Scan on t` with its `Filter:`) output. Columns come from the
+/// plan's filter when it has one and from the statement's WHERE clause otherwise; tables
+/// that already have an index led by the first column are left out.
+pub fn suggest_indexes(sql: &str, plan: &str, indexed: &HashSet<(String, String)>) -> Vec<IndexSuggestion> {
+    static SQLITE_SCAN: OnceLock<Regex> = OnceLock::new();
+    static POSTGRES_SCAN: OnceLock<Regex> = OnceLock::new();
+    static ORDER_BY: OnceLock<Regex> = OnceLock::new();
+    let sqlite_scan = SQLITE_SCAN.get_or_init(|| Regex::new(r"^\s*(?:\|--|`--)?\s*SCAN (?:TABLE )?([A-Za-z_][A-Za-z0-9_]*)(.*)$").unwrap());
+    let postgres_scan = POSTGRES_SCAN.get_or_init(|| Regex::new(r"Seq Scan on ([A-Za-z_][A-Za-z0-9_]*)(?: ([A-Za-z_][A-Za-z0-9_]*))?").unwrap());
+    let order_by = ORDER_BY.get_or_init(|| {
+        Regex::new(r"(?i)\bORDER BY\s+(?:([A-Za-z_][A-Za-z0-9_]*)\.)?([A-Za-z_][A-Za-z0-9_]*)").unwrap()
+    });
+
+    let sql = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    let sql = sql.as_str();
+    let aliases = table_aliases(sql);
+    let where_clause = sql
+        .to_ascii_uppercase()
+        .find(" WHERE ")
+        .map(|start| &sql[start..])
+        .unwrap_or_default();
+    let lines: Vec<&str> = plan.lines().collect();
+    let mut suggestions: Vec<IndexSuggestion> = Vec::new();
+    let mut push = |table: &str, columns: Vec<String>, reason: String| {
+        let columns: Vec<String> = columns.into_iter().take(3).collect();
+        let Some(first) = columns.first() else {
+            return;
+        };
+        if indexed.contains(&(table.to_string(), first.clone())) || suggestions.iter().any(|s| s.table == table && s.columns == columns) {
+            return;
+        }
+        suggestions.push(suggestion(table, columns, reason));
+    };
+
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(captures) = sqlite_scan.captures(line) {
+            if captures[2].contains("INDEX") {
+                continue;
+            }
+            let name = captures[1].to_lowercase();
+            let table = aliases.get(&name).cloned().unwrap_or(name);
+            let columns = filtered_columns(where_clause, &table, &aliases);
+            push(&table, columns, format!("full scan of {}", table));
+        } else if let Some(captures) = postgres_scan.captures(line) {
+            let table = captures[1].to_lowercase();
+            let mut scan_aliases = aliases.clone();
+            if let Some(alias) = captures.get(2) {
+                scan_aliases.insert(alias.as_str().to_lowercase(), table.clone());
+            }
+            // The filter is on the lines under this node, before the next one
+            let filter: String = lines[i + 1..]
+                .iter()
+                .take_while(|l| !l.contains("->"))
+                .filter_map(|l| l.trim().strip_prefix("Filter:"))
+                .collect();
+            let source = if filter.is_empty() { where_clause } else { filter.as_str() };
+            let columns = filtered_columns(source, &table, &scan_aliases);
+            push(&table, columns, format!("sequential scan of {}", table));
+        } else if line.contains("USE TEMP B-TREE FOR ORDER BY") {
+            if let Some(captures) = order_by.captures(sql) {
+                let table = match captures.get(1) {
+                    Some(alias) => aliases.get(&alias.as_str().to_lowercase()).cloned(),
+                    None if aliases.values().collect::<HashSet<_>>().len() == 1 => aliases.values().next().cloned(),
+                    None => None,
+                };
+                if let Some(table) = table {
+                    push(&table, vec![captures[2].to_lowercase()], format!("sort of {} for ORDER BY", table));
+                }
+            }
+        }
+    }
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqlite_scan_suggests_where_columns() {
+        let sql = "SELECT s.secret_hash FROM secrets s JOIN commits c ON c.id = s.commit_id WHERE s.detector_name = ? AND s.created_at > ?";
+        let plan = "SCAN s\nSEARCH c USING INTEGER PRIMARY KEY (rowid=?)";
+        let suggestions = suggest_indexes(sql, plan, &HashSet::new());
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].statement, "CREATE INDEX IF NOT EXISTS idx_secrets_detector_name_created_at ON secrets(detector_name, created_at)");
+
+        let indexed = HashSet::from([("secrets".to_string(), "detector_name".to_string())]);
+        assert!(suggest_indexes(sql, plan, &indexed).is_empty());
+        assert!(suggest_indexes(sql, "SEARCH s USING INDEX idx_secrets_detector (detector_name=?)", &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn test_postgres_seq_scan_uses_the_filter() {
+        let sql = "SELECT COUNT(*) as count FROM events WHERE actor_login = $1";
+        let plan = "Aggregate  (cost=1.0..1.0 rows=1)\n  ->  Seq Scan on events  (cost=0.00..1.00 rows=1)\n        Filter: ((actor_login)::text = 'octocat'::text)\n        Rows Removed by Filter: 9999";
+        let suggestions = suggest_indexes(sql, plan, &HashSet::new());
+        assert_eq!(suggestions[0].columns, vec!["actor_login".to_string()]);
+        assert_eq!(suggestions[0].reason, "sequential scan of events");
+    }
+
+    #[test]
+    fn test_reports_group_repeated_statements() {
+        let query = |id, ms: f64| SlowQuery {
+            id,
+            backend: "sqlite".to_string(),
+            sql: "SELECT * FROM secrets\n  WHERE severity = ?".to_string(),
+            duration_ms: ms,
+            plan: Some("SCAN secrets".to_string()),
+            recorded_at: format!("2024-01-0{} 00:00:00", id),
+        };
+        let reports = summarize(&[query(1, 300.0), query(2, 500.0)], &HashSet::new());
+        assert_eq!(reports.len(), 1);
+        assert_eq!((reports[0].count, reports[0].max_ms, reports[0].mean_ms), (2, 500.0, 400.0));
+        assert_eq!(reports[0].sql, "SELECT * FROM secrets WHERE severity = ?");
+        assert_eq!(reports[0].suggestions[0].columns, vec!["severity".to_string()]);
+    }
+}
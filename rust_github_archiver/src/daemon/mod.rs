@@ -0,0 +1,257 @@
+use anyhow::{anyhow, Context, Result};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+
+#[cfg(windows)]
+pub mod windows;
+
+static SHUTDOWN: OnceLock<Notify> = OnceLock::new();
+
+/// Ask the running hunt to drain and exit (used by service control handlers)
+pub fn request_shutdown() {
+    SHUTDOWN.get_or_init(Notify::new).notify_one();
+}
+
+/// Resolves once `request_shutdown` has been called
+pub async fn shutdown_requested() {
+    SHUTDOWN.get_or_init(Notify::new).notified().await
+}
+
+/// Detach from the controlling terminal (double fork + setsid).
+/// Must run before the tokio runtime starts: forking a multi-threaded process only keeps
+/// the calling thread. The working directory is kept so relative database paths still resolve.
+#[cfg(unix)]
+pub fn daemonize(log_path: Option<&Path>) -> Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    // Open everything before forking so errors still reach the terminal
+    let stdin = File::open("/dev/null")?;
+    let output = match log_path {
+        Some(path) => OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open daemon log {}", path.display()))?,
+        None => OpenOptions::new().write(true).open("/dev/null")?,
+    };
+
+    unsafe {
+        match libc::fork() {
+            -1 => return Err(std::io::Error::last_os_error().into()),
+            0 => {}
+            _ => libc::_exit(0),
+        }
+
+        if libc::setsid() == -1 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        // Second fork so the daemon can never reacquire a controlling terminal
+        match libc::fork() {
+            -1 => return Err(std::io::Error::last_os_error().into()),
+            0 => {}
+            _ => libc::_exit(0),
+        }
+
+        libc::umask(0o027);
+        libc::dup2(stdin.as_raw_fd(), libc::STDIN_FILENO);
+        libc::dup2(output.as_raw_fd(), libc::STDOUT_FILENO);
+        libc::dup2(output.as_raw_fd(), libc::STDERR_FILENO);
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn daemonize(_log_path: Option<&Path>) -> Result<()> {
+    Err(anyhow!("--daemon is only supported on Unix"))
+}
+
+fn try_lock(file: &File, path: &Path, what: &str) -> Result<()> {
+    match file.try_lock() {
+        Ok(()) => Ok(()),
+        Err(TryLockError::WouldBlock) => Err(anyhow!(
+            "{} {} is locked: another instance is already running",
+            what,
+            path.display()
+        )),
+        Err(TryLockError::Error(e)) => Err(e).with_context(|| format!("Failed to lock {}", path.display())),
+    }
+}
+
+/// PID file held under an exclusive lock for the life of the process; removed on drop
+pub struct PidFile {
+    path: PathBuf,
+    _file: File,
+}
+
+impl PidFile {
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&path)
+            .with_context(|| format!("Failed to open PID file {}", path.display()))?;
+
+        try_lock(&file, &path, "PID file")?;
+        file.set_len(0)?;
+        writeln!(file, "{}", std::process::id())?;
+        file.sync_all()?;
+
+        debug!("Wrote PID {} to {}", std::process::id(), path.display());
+        Ok(Self { path, _file: file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            warn!("Failed to remove PID file {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Exclusive lock on `<database>.lock` so two hunters never write the same database.
+/// The lock is released by the OS when the process exits, even on a crash.
+pub struct InstanceLock {
+    path: PathBuf,
+    _file: File,
+}
+
+impl InstanceLock {
+    pub fn acquire(database_path: &str) -> Result<Self> {
+        let path = PathBuf::from(format!("{}.lock", database_path));
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&path)
+            .with_context(|| format!("Failed to open lock file {}", path.display()))?;
+
+        try_lock(&file, &path, "Database lock")?;
+        Ok(Self { path, _file: file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Options for the generated systemd unit
+#[derive(Debug, Clone)]
+pub struct SystemdUnit {
+    pub description: String,
+    pub exec_path: PathBuf,
+    pub args: Vec<String>,
+    pub working_directory: PathBuf,
+    pub user: Option<String>,
+    pub environment_file: Option<PathBuf>,
+}
+
+impl SystemdUnit {
+    /// Render the unit. systemd supervises the process itself, so the service runs in the
+    /// foreground; SIGHUP reloads the config and SIGTERM drains before exit.
+    pub fn render(&self) -> String {
+        let exec_start = std::iter::once(self.exec_path.display().to_string())
+            .chain(self.args.iter().map(|a| systemd_quote(a)))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut service = vec![
+            "Type=simple".to_string(),
+            format!("ExecStart={}", exec_start),
+            "ExecReload=/bin/kill -HUP $MAINPID".to_string(),
+            "KillSignal=SIGTERM".to_string(),
+            "TimeoutStopSec=60".to_string(),
+            "Restart=on-failure".to_string(),
+            "RestartSec=10".to_string(),
+            format!("WorkingDirectory={}", self.working_directory.display()),
+        ];
+        if let Some(user) = &self.user {
+            service.push(format!("User={}", user));
+        }
+        if let Some(env_file) = &self.environment_file {
+            service.push(format!("EnvironmentFile={}", env_file.display()));
+        }
+
+        format!(
+            "[Unit]\nDescription={}\nAfter=network-online.target\nWants=network-online.target\n\n[Service]\n{}\n\n[Install]\nWantedBy=multi-user.target\n",
+            self.description,
+            service.join("\n")
+        )
+    }
+
+    pub fn install(&self, unit_path: &Path) -> Result<()> {
+        std::fs::write(unit_path, self.render())
+            .with_context(|| format!("Failed to write unit file {}", unit_path.display()))
+    }
+}
+
+fn systemd_quote(arg: &str) -> String {
+    if arg.chars().any(|c| c.is_whitespace() || c == '"' || c == '\\') {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        arg.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}-{}", name, uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_second_instance_lock_fails() {
+        let db = temp_path("secrets.db");
+        let db = db.to_str().unwrap();
+
+        let first = InstanceLock::acquire(db).unwrap();
+        assert!(InstanceLock::acquire(db).is_err());
+        drop(first);
+        assert!(InstanceLock::acquire(db).is_ok());
+
+        std::fs::remove_file(format!("{}.lock", db)).ok();
+    }
+
+    #[test]
+    fn test_pid_file_written_and_removed() {
+        let path = temp_path("hunter.pid");
+        {
+            let _pid = PidFile::create(&path).unwrap();
+            let contents = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(contents.trim(), std::process::id().to_string());
+        }
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_unit_rendering() {
+        let unit = SystemdUnit {
+            description: "GitHub Secret Hunter".to_string(),
+            exec_path: PathBuf::from("/usr/local/bin/github_archiver"),
+            args: vec!["hunt".to_string(), "--realtime".to_string(), "--database".to_string(), "/var/lib/hunter/secrets db".to_string()],
+            working_directory: PathBuf::from("/var/lib/hunter"),
+            user: Some("hunter".to_string()),
+            environment_file: None,
+        };
+
+        let rendered = unit.render();
+        assert!(rendered.contains("ExecStart=/usr/local/bin/github_archiver hunt --realtime --database \"/var/lib/hunter/secrets db\""));
+        assert!(rendered.contains("ExecReload=/bin/kill -HUP $MAINPID"));
+        assert!(rendered.contains("User=hunter"));
+        assert!(!rendered.contains("EnvironmentFile"));
+    }
+}
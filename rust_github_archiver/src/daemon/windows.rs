@@ -0,0 +1,296 @@
+use anyhow::{anyhow, Context, Result};
+use std::ffi::{OsStr, OsString};
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context as LayerContext, Layer};
+use windows_service::service::{
+    ServiceAccess, ServiceControl, ServiceControlAccept, ServiceErrorControl, ServiceExitCode,
+    ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+};
+use windows_service::service_control_handler::{self, ServiceControlHandlerResult};
+use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+use windows_service::{define_windows_service, service_dispatcher};
+use windows_sys::Win32::System::EventLog::{
+    DeregisterEventSource, RegisterEventSourceW, ReportEventW, EVENTLOG_ERROR_TYPE,
+    EVENTLOG_INFORMATION_TYPE, EVENTLOG_WARNING_TYPE,
+};
+
+use super::request_shutdown;
+
+pub const SERVICE_NAME: &str = "GitHubSecretHunter";
+const SERVICE_DISPLAY_NAME: &str = "GitHub Secret Hunter";
+const SERVICE_TYPE: ServiceType = ServiceType::OWN_PROCESS;
+
+/// Body of the service, run on the dispatcher thread once the SCM has started us
+type ServiceRunner = Box<dyn FnOnce() -> Result<()> + Send>;
+
+static RUNNER: OnceLock<Mutex<Option<ServiceRunner>>> = OnceLock::new();
+static RUNNING_AS_SERVICE: AtomicBool = AtomicBool::new(false);
+
+pub fn is_running_as_service() -> bool {
+    RUNNING_AS_SERVICE.load(Ordering::SeqCst)
+}
+
+/// Register the service with the SCM. It is started as `<exe> service run -- <args>`.
+pub fn install(args: Vec<String>) -> Result<()> {
+    let manager = ServiceManager::local_computer(
+        None::<&str>,
+        ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE,
+    )?;
+
+    let mut launch_arguments: Vec<OsString> = vec!["service".into(), "run".into(), "--".into()];
+    launch_arguments.extend(args.into_iter().map(OsString::from));
+
+    let info = ServiceInfo {
+        name: OsString::from(SERVICE_NAME),
+        display_name: OsString::from(SERVICE_DISPLAY_NAME),
+        service_type: SERVICE_TYPE,
+        start_type: ServiceStartType::AutoStart,
+        error_control: ServiceErrorControl::Normal,
+        executable_path: std::env::current_exe()?,
+        launch_arguments,
+        dependencies: vec![],
+        account_name: None, // LocalSystem
+        account_password: None,
+    };
+
+    let service = manager
+        .create_service(&info, ServiceAccess::CHANGE_CONFIG)
+        .context("Failed to create service (is the shell elevated?)")?;
+    service.set_description("Monitors GitHub for leaked secrets")?;
+
+    register_event_source()?;
+    Ok(())
+}
+
+pub fn uninstall() -> Result<()> {
+    let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    let service = manager.open_service(SERVICE_NAME, ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::DELETE)?;
+
+    if service.query_status()?.current_state != ServiceState::Stopped {
+        service.stop()?;
+    }
+    service.delete()?;
+    Ok(())
+}
+
+/// Hand control to the service dispatcher; blocks until the service stops.
+/// Only works when the process was started by the SCM.
+pub fn run(runner: ServiceRunner) -> Result<()> {
+    RUNNER.get_or_init(|| Mutex::new(None)).lock().unwrap().replace(runner);
+    RUNNING_AS_SERVICE.store(true, Ordering::SeqCst);
+    service_dispatcher::start(SERVICE_NAME, ffi_service_main)
+        .map_err(|e| anyhow!("Failed to start service dispatcher (not started by the SCM?): {}", e))
+}
+
+define_windows_service!(ffi_service_main, service_main);
+
+fn service_main(_arguments: Vec<OsString>) {
+    if let Err(e) = run_service() {
+        tracing::error!("Service failed: {}", e);
+    }
+}
+
+fn run_service() -> Result<()> {
+    let status_handle = service_control_handler::register(SERVICE_NAME, move |control| match control {
+        ServiceControl::Stop | ServiceControl::Shutdown => {
+            request_shutdown();
+            ServiceControlHandlerResult::NoError
+        }
+        ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+        _ => ServiceControlHandlerResult::NotImplemented,
+    })?;
+
+    let set_state = |state: ServiceState, accept: ServiceControlAccept, exit_code: u32| {
+        status_handle.set_service_status(ServiceStatus {
+            service_type: SERVICE_TYPE,
+            current_state: state,
+            controls_accepted: accept,
+            exit_code: ServiceExitCode::Win32(exit_code),
+            checkpoint: 0,
+            wait_hint: Duration::from_secs(30),
+            process_id: None,
+        })
+    };
+
+    set_state(ServiceState::Running, ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN, 0)?;
+
+    let runner = RUNNER
+        .get()
+        .and_then(|slot| slot.lock().unwrap().take())
+        .ok_or_else(|| anyhow!("Service runner was not registered"))?;
+    let result = runner();
+
+    let exit_code = if result.is_ok() { 0 } else { 1 };
+    set_state(ServiceState::Stopped, ServiceControlAccept::empty(), exit_code)?;
+    result
+}
+
+fn wide(s: impl AsRef<OsStr>) -> Vec<u16> {
+    s.as_ref().encode_wide().chain(std::iter::once(0)).collect()
+}
+
+/// Register the event source so Event Viewer renders our messages. The generic .NET message
+/// file maps every event id to `%1`, so no message resources need to be compiled in.
+fn register_event_source() -> Result<()> {
+    let key = format!(
+        r"HKLM\SYSTEM\CurrentControlSet\Services\EventLog\Application\{}",
+        SERVICE_NAME
+    );
+    let status = std::process::Command::new("reg")
+        .args(["add", &key, "/v", "EventMessageFile", "/t", "REG_EXPAND_SZ", "/d"])
+        .arg(r"%SystemRoot%\Microsoft.NET\Framework64\v4.0.30319\EventLogMessages.dll")
+        .arg("/f")
+        .status()?;
+    if !status.success() {
+        return Err(anyhow!("Failed to register event log source {}", SERVICE_NAME));
+    }
+    Ok(())
+}
+
+/// Forwards warnings and errors (and info, when verbose) to the Windows Application event log
+pub struct EventLogLayer {
+    /// Event source handle, stored as an integer so the layer is Send + Sync
+    handle: isize,
+    min_level: Level,
+}
+
+impl EventLogLayer {
+    pub fn new(source: &str, min_level: Level) -> Result<Self> {
+        let source = wide(source);
+        let handle = unsafe { RegisterEventSourceW(std::ptr::null(), source.as_ptr()) };
+        if handle.is_null() {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(Self { handle: handle as isize, min_level })
+    }
+
+    /// Event log layer when running under the SCM, `None` otherwise
+    pub fn for_service(verbose: bool) -> Option<Self> {
+        if !is_running_as_service() {
+            return None;
+        }
+        Self::new(SERVICE_NAME, if verbose { Level::INFO } else { Level::WARN }).ok()
+    }
+
+    fn report(&self, level: &Level, message: &str) {
+        let event_type = match *level {
+            Level::ERROR => EVENTLOG_ERROR_TYPE,
+            Level::WARN => EVENTLOG_WARNING_TYPE,
+            _ => EVENTLOG_INFORMATION_TYPE,
+        };
+        let text = wide(message);
+        let strings = [text.as_ptr()];
+
+        unsafe {
+            ReportEventW(
+                self.handle as _,
+                event_type,
+                0,
+                0,
+                std::ptr::null_mut(),
+                1,
+                0,
+                strings.as_ptr(),
+                std::ptr::null(),
+            );
+        }
+    }
+}
+
+impl Drop for EventLogLayer {
+    fn drop(&mut self) {
+        unsafe {
+            DeregisterEventSource(self.handle as _);
+        }
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: Vec<String>,
+}
+
+impl MessageVisitor {
+    /// `target: message (field=value, ...)`, as written to the event log
+    fn into_message(self, target: &str) -> String {
+        let mut message = format!("{}: {}", target, self.message);
+        if !self.fields.is_empty() {
+            message.push_str(&format!(" ({})", self.fields.join(", ")));
+        }
+        message
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.push(format!("{}={:?}", field.name(), value));
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for EventLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        let level = event.metadata().level();
+        // Level ordering is by verbosity: ERROR < WARN < INFO
+        if *level > self.min_level {
+            return;
+        }
+
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        self.report(level, &visitor.into_message(event.metadata().target()));
+    }
+}
+
+/// Service processes start in the system directory; move to the data directory so
+/// relative paths (database, logs, PID files) stay in one predictable place.
+pub fn enter_data_dir(data_dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(data_dir)?;
+    std::env::set_current_dir(data_dir)
+        .with_context(|| format!("Failed to enter data directory {}", data_dir.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wide_strings_are_nul_terminated() {
+        assert_eq!(wide("ok"), vec![b'o' as u16, b'k' as u16, 0]);
+        assert_eq!(wide(""), vec![0]);
+    }
+
+    #[test]
+    fn test_event_log_message_format() {
+        let visitor = MessageVisitor { message: "Scan failed".to_string(), fields: Vec::new() };
+        assert_eq!(visitor.into_message("github_archiver::realtime"), "github_archiver::realtime: Scan failed");
+
+        let visitor = MessageVisitor {
+            message: "Scan failed".to_string(),
+            fields: vec!["repository=\"acme/api\"".to_string(), "attempt=2".to_string()],
+        };
+        assert_eq!(visitor.into_message("hunter"), "hunter: Scan failed (repository=\"acme/api\", attempt=2)");
+    }
+
+    #[test]
+    fn test_enter_data_dir_creates_it() {
+        let previous = std::env::current_dir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let data_dir = dir.path().join("Bit-Loop").join("GitArchiver");
+
+        enter_data_dir(&data_dir).unwrap();
+        let entered = std::env::current_dir().unwrap();
+        std::env::set_current_dir(previous).unwrap();
+        assert_eq!(entered.canonicalize().unwrap(), data_dir.canonicalize().unwrap());
+    }
+}
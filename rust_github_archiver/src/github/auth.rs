@@ -0,0 +1,249 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+
+use crate::core::{build_client, offline, send_with_retry, HttpConfig, RetryPolicy};
+
+/// GitHub App to authenticate as; takes precedence over `GITHUB_TOKEN`
+pub const GITHUB_APP_ID_ENV: &str = "GITHUB_APP_ID";
+/// The App's PEM private key, or a path to it
+pub const GITHUB_APP_PRIVATE_KEY_ENV: &str = "GITHUB_APP_PRIVATE_KEY";
+/// Installation to act as; required when the App is installed on more than one account
+pub const GITHUB_APP_INSTALLATION_ID_ENV: &str = "GITHUB_APP_INSTALLATION_ID";
+
+/// GitHub App JWTs may live at most ten minutes
+const APP_JWT_LIFETIME_SECS: i64 = 540;
+/// Installation tokens last an hour; a new one is fetched when this little is left
+const REFRESH_MARGIN_MINUTES: i64 = 5;
+
+static APP: OnceLock<Option<Arc<GitHubApp>>> = OnceLock::new();
+
+#[derive(Serialize)]
+struct AppClaims<'a> {
+    iat: i64,
+    exp: i64,
+    iss: &'a str,
+}
+
+struct InstallationToken {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// A GitHub App installation. Its access token is fetched on first use and refreshed
+/// shortly before it expires, so long-running jobs never hold a stale one.
+pub struct GitHubApp {
+    app_id: String,
+    key: EncodingKey,
+    installation_id: Mutex<Option<u64>>,
+    current: Mutex<Option<InstallationToken>>,
+    api_url: String,
+    http: Client,
+    retry_policy: RetryPolicy,
+}
+
+impl GitHubApp {
+    pub fn new(app_id: String, private_key_pem: &str, installation_id: Option<u64>) -> Result<Self> {
+        let http_config = HttpConfig::default();
+        Ok(Self {
+            app_id,
+            key: EncodingKey::from_rsa_pem(private_key_pem.as_bytes()).context("Invalid GitHub App private key")?,
+            installation_id: Mutex::new(installation_id),
+            current: Mutex::new(None),
+            api_url: offline::policy().github_api_base()?.trim_end_matches('/').to_string(),
+            http: build_client(&http_config)?,
+            retry_policy: RetryPolicy::from_config(&http_config),
+        })
+    }
+
+    /// The App from `GITHUB_APP_ID`, `GITHUB_APP_PRIVATE_KEY` and `GITHUB_APP_INSTALLATION_ID`,
+    /// or `None` when no App is configured
+    pub fn from_env() -> Result<Option<Self>> {
+        let Ok(app_id) = std::env::var(GITHUB_APP_ID_ENV) else {
+            return Ok(None);
+        };
+        let key = std::env::var(GITHUB_APP_PRIVATE_KEY_ENV)
+            .map_err(|_| anyhow!("{} is set but {} is not", GITHUB_APP_ID_ENV, GITHUB_APP_PRIVATE_KEY_ENV))?;
+        let private_key = if key.contains("-----BEGIN") {
+            key
+        } else {
+            std::fs::read_to_string(&key).with_context(|| format!("Failed to read GitHub App private key {}", key))?
+        };
+        let installation_id = match std::env::var(GITHUB_APP_INSTALLATION_ID_ENV) {
+            Ok(id) => Some(id.parse().map_err(|_| anyhow!("Invalid {} {}", GITHUB_APP_INSTALLATION_ID_ENV, id))?),
+            Err(_) => None,
+        };
+        Ok(Some(Self::new(app_id, &private_key, installation_id)?))
+    }
+
+    /// Short-lived JWT the App authenticates its own endpoints with
+    pub fn jwt(&self) -> Result<String> {
+        let now = Utc::now().timestamp();
+        // Backdated against clock drift, as GitHub recommends
+        let claims = AppClaims { iat: now - 60, exp: now + APP_JWT_LIFETIME_SECS, iss: &self.app_id };
+        Ok(encode(&Header::new(Algorithm::RS256), &claims, &self.key)?)
+    }
+
+    pub fn app_id(&self) -> &str {
+        &self.app_id
+    }
+
+    pub fn encoding_key(&self) -> &EncodingKey {
+        &self.key
+    }
+
+    async fn get(&self, path: &str, jwt: &str) -> Result<Value> {
+        let request = self.http.get(format!("{}{}", self.api_url, path));
+        self.send(request, jwt).await
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder, jwt: &str) -> Result<Value> {
+        let request = request.bearer_auth(jwt).header("Accept", "application/vnd.github+json");
+        let response = send_with_retry(request, &self.retry_policy).await?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(anyhow!("GitHub App authentication failed with {}: {}", status, body));
+        }
+        Ok(body)
+    }
+
+    /// The configured installation, or the App's only one
+    pub async fn installation_id(&self) -> Result<u64> {
+        let mut installation_id = self.installation_id.lock().await;
+        if let Some(id) = *installation_id {
+            return Ok(id);
+        }
+        let installations = self.get("/app/installations", &self.jwt()?).await?;
+        let ids: Vec<u64> = installations.as_array().into_iter().flatten().filter_map(|i| i["id"].as_u64()).collect();
+        let id = match ids.as_slice() {
+            [id] => *id,
+            [] => return Err(anyhow!("GitHub App {} is not installed anywhere", self.app_id)),
+            _ => {
+                return Err(anyhow!(
+                    "GitHub App {} has {} installations; set {}",
+                    self.app_id,
+                    ids.len(),
+                    GITHUB_APP_INSTALLATION_ID_ENV
+                ))
+            }
+        };
+        *installation_id = Some(id);
+        Ok(id)
+    }
+
+    /// A current installation access token
+    pub async fn token(&self) -> Result<String> {
+        let mut current = self.current.lock().await;
+        if let Some(token) = current.as_ref() {
+            if token.expires_at - Utc::now() > Duration::minutes(REFRESH_MARGIN_MINUTES) {
+                return Ok(token.token.clone());
+            }
+        }
+
+        let installation_id = self.installation_id().await?;
+        let path = format!("/app/installations/{}/access_tokens", installation_id);
+        let body = self.send(self.http.post(format!("{}{}", self.api_url, path)), &self.jwt()?).await?;
+        let token = body["token"].as_str().ok_or_else(|| anyhow!("GitHub did not return an installation token"))?;
+        let expires_at = body["expires_at"]
+            .as_str()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| at.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc::now() + Duration::hours(1));
+        if current.is_none() {
+            info!("Authenticating to GitHub as App {} (installation {})", self.app_id, installation_id);
+        }
+        debug!("Refreshed GitHub App installation token, valid until {}", expires_at);
+        *current = Some(InstallationToken { token: token.to_string(), expires_at });
+        Ok(token.to_string())
+    }
+}
+
+/// The process-wide GitHub App from the environment, shared so every client reuses one
+/// installation token
+pub fn configured_app() -> Result<Option<Arc<GitHubApp>>> {
+    if let Some(app) = APP.get() {
+        return Ok(app.clone());
+    }
+    let app = GitHubApp::from_env()?.map(Arc::new);
+    Ok(APP.get_or_init(|| app).clone())
+}
+
+/// What GitHub clients authenticate with: a configured GitHub App, a personal access token,
+/// or nothing (public data only, at the anonymous rate limit)
+#[derive(Clone, Default)]
+pub enum GitHubCredentials {
+    #[default]
+    Anonymous,
+    Token(String),
+    App(Arc<GitHubApp>),
+}
+
+impl GitHubCredentials {
+    /// The configured GitHub App when there is one, else `token` (usually `GITHUB_TOKEN`).
+    /// A broken App configuration is reported and falls back to `token`.
+    pub fn resolve(token: Option<String>) -> Self {
+        match configured_app() {
+            Ok(Some(app)) => return Self::App(app),
+            Ok(None) => {}
+            Err(e) => tracing::error!("GitHub App authentication disabled: {}", e),
+        }
+        match token.filter(|t| !t.is_empty()) {
+            Some(token) => Self::Token(token),
+            None => Self::Anonymous,
+        }
+    }
+
+    /// `resolve` with `GITHUB_TOKEN`
+    pub fn from_env() -> Self {
+        Self::resolve(std::env::var("GITHUB_TOKEN").ok())
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        matches!(self, Self::Anonymous)
+    }
+
+    /// The token to send now, refreshing an App installation token when it is about to expire
+    pub async fn token(&self) -> Result<Option<String>> {
+        match self {
+            Self::Anonymous => Ok(None),
+            Self::Token(token) => Ok(Some(token.clone())),
+            Self::App(app) => Ok(Some(app.token().await?)),
+        }
+    }
+}
+
+impl From<String> for GitHubCredentials {
+    fn from(token: String) -> Self {
+        Self::resolve(Some(token))
+    }
+}
+
+impl From<Option<String>> for GitHubCredentials {
+    fn from(token: Option<String>) -> Self {
+        Self::resolve(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_token_credentials_without_an_app() {
+        if std::env::var(GITHUB_APP_ID_ENV).is_ok() {
+            return;
+        }
+        assert!(GitHubCredentials::from(String::new()).is_anonymous());
+        assert_eq!(GitHubCredentials::from(None).token().await.unwrap(), None);
+        let credentials = GitHubCredentials::from("ghp_example".to_string());
+        assert_eq!(credentials.token().await.unwrap().as_deref(), Some("ghp_example"));
+        assert!(GitHubApp::new("1".to_string(), "not a key", None).is_err());
+    }
+}
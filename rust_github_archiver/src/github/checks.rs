@@ -0,0 +1,160 @@
+use anyhow::{anyhow, Result};
+use reqwest::{Client, RequestBuilder};
+use serde_json::{json, Value};
+use tracing::info;
+
+use super::auth::{GitHubCredentials, GITHUB_APP_ID_ENV, GITHUB_APP_PRIVATE_KEY_ENV};
+use crate::core::{build_client, offline, send_with_retry, HttpConfig, RetryPolicy};
+use crate::secrets::{check_run_annotations, SecretMatch, SecretSeverity};
+use crate::sinks::{severity_label, severity_rank};
+
+pub const CHECK_RUN_NAME: &str = "GitArchiver secret scan";
+
+/// Most annotations the Checks API takes per request; the rest go in follow-up updates
+const ANNOTATIONS_PER_REQUEST: usize = 50;
+
+/// `success` unless an active finding is at or above `fail_on`
+pub fn check_run_conclusion(findings: &[SecretMatch], fail_on: &SecretSeverity) -> &'static str {
+    let failing = findings.iter().any(|f| !f.suppressed && severity_rank(&f.severity) >= severity_rank(fail_on));
+    if failing {
+        "failure"
+    } else {
+        "success"
+    }
+}
+
+fn findings_title(count: usize) -> String {
+    match count {
+        0 => "No secrets found".to_string(),
+        1 => "1 secret found".to_string(),
+        count => format!("{} secrets found", count),
+    }
+}
+
+/// Markdown summary of the check run: findings per severity and the threshold applied
+pub fn check_run_summary(findings: &[SecretMatch], fail_on: &SecretSeverity) -> String {
+    let active: Vec<&SecretMatch> = findings.iter().filter(|f| !f.suppressed).collect();
+    if active.is_empty() {
+        return "No secrets found.".to_string();
+    }
+    let mut lines = vec![
+        format!("{}. The check fails on {} or higher.", findings_title(active.len()), severity_label(fail_on)),
+        String::new(),
+        "| Severity | Findings |".to_string(),
+        "| --- | --- |".to_string(),
+    ];
+    for severity in [SecretSeverity::Critical, SecretSeverity::High, SecretSeverity::Medium, SecretSeverity::Low] {
+        let count = active.iter().filter(|f| severity_rank(&f.severity) == severity_rank(&severity)).count();
+        if count > 0 {
+            lines.push(format!("| {} | {} |", severity_label(&severity), count));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Posts scan results as a completed GitHub check run with inline annotations
+pub struct CheckRunPublisher {
+    credentials: GitHubCredentials,
+    api_url: String,
+    client: Client,
+    retry_policy: RetryPolicy,
+}
+
+impl CheckRunPublisher {
+    pub fn new(credentials: GitHubCredentials) -> Result<Self> {
+        let http = HttpConfig::default();
+        Ok(Self {
+            credentials,
+            api_url: offline::policy().github_api_base()?.trim_end_matches('/').to_string(),
+            client: build_client(&http)?,
+            retry_policy: RetryPolicy::from_config(&http),
+        })
+    }
+
+    async fn send(&self, request: RequestBuilder, token: &str) -> Result<Value> {
+        let request = request.bearer_auth(token).header("Accept", "application/vnd.github+json");
+        let response = send_with_retry(request, &self.retry_policy).await?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(anyhow!("GitHub returned {}: {}", status, body));
+        }
+        Ok(body)
+    }
+
+    /// Create a completed check run on `head_sha` of `repository` with one annotation per
+    /// finding, failing when a finding is at or above `fail_on`. Returns the check run's URL.
+    pub async fn publish(&self, repository: &str, head_sha: &str, findings: &[SecretMatch], fail_on: &SecretSeverity) -> Result<String> {
+        // Check runs can only be created by GitHub Apps, which includes the `GITHUB_TOKEN`
+        // of a GitHub Actions run
+        let token = self.credentials.token().await?.ok_or_else(|| {
+            anyhow!("Posting a check run needs GITHUB_TOKEN or {} and {}", GITHUB_APP_ID_ENV, GITHUB_APP_PRIVATE_KEY_ENV)
+        })?;
+
+        let conclusion = check_run_conclusion(findings, fail_on);
+        let title = findings_title(findings.iter().filter(|f| !f.suppressed).count());
+        let summary = check_run_summary(findings, fail_on);
+        let annotations = check_run_annotations(findings);
+        let mut batches = annotations.chunks(ANNOTATIONS_PER_REQUEST);
+
+        let url = format!("{}/repos/{}/check-runs", self.api_url, repository);
+        let body = json!({
+            "name": CHECK_RUN_NAME,
+            "head_sha": head_sha,
+            "status": "completed",
+            "conclusion": conclusion,
+            "output": { "title": title, "summary": summary, "annotations": batches.next().unwrap_or_default() },
+        });
+        let created = self.send(self.client.post(&url).json(&body), &token).await?;
+        let id = created["id"].as_u64().ok_or_else(|| anyhow!("GitHub did not return a check run id"))?;
+
+        // Further annotations are appended by updating the run's output
+        for batch in batches {
+            let body = json!({ "output": { "title": title, "summary": summary, "annotations": batch } });
+            self.send(self.client.patch(format!("{}/{}", url, id)).json(&body), &token).await?;
+        }
+
+        let html_url = created["html_url"].as_str().unwrap_or_default().to_string();
+        info!("Posted check run {} ({}, {} annotations) on {}@{}", id, conclusion, annotations.len(), repository, head_sha);
+        Ok(html_url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::SecretCategory;
+
+    fn finding(severity: SecretSeverity, suppressed: bool) -> SecretMatch {
+        SecretMatch {
+            detector_name: "GitHub Token".to_string(),
+            matched_text: "ghp_********".to_string(),
+            start_position: 0,
+            end_position: 40,
+            line_number: Some(4),
+            filename: Some(".github/workflows/deploy.yml".to_string()),
+            commit_sha: None,
+            commit_author: None,
+            suppressed,
+            suppressed_by: None,
+            entropy: 4.5,
+            severity,
+            category: SecretCategory::Token,
+            context: String::new(),
+            verified: false,
+            hash: "beef".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_conclusion_follows_threshold() {
+        let findings = [finding(SecretSeverity::Medium, false), finding(SecretSeverity::Critical, true)];
+        assert_eq!(check_run_conclusion(&findings, &SecretSeverity::High), "success");
+        assert_eq!(check_run_conclusion(&findings, &SecretSeverity::Medium), "failure");
+        assert_eq!(check_run_conclusion(&[], &SecretSeverity::Low), "success");
+
+        let summary = check_run_summary(&findings, &SecretSeverity::High);
+        assert!(summary.starts_with("1 secret found. The check fails on High or higher."));
+        assert!(summary.contains("| Medium | 1 |") && !summary.contains("Critical"));
+    }
+}
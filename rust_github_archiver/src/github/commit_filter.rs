@@ -0,0 +1,213 @@
+use anyhow::{anyhow, Result};
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+use tracing::{debug, info};
+
+use crate::performance::SecretDatabase;
+
+/// Outcome of a previous commit check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommitCheckStatus {
+    /// Commit is reachable through the API
+    Exists,
+    /// Commit could not be retrieved (garbage collected or never pushed)
+    Dangling,
+    /// Full commit content was fetched and scanned
+    Fetched,
+}
+
+impl CommitCheckStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommitCheckStatus::Exists => "exists",
+            CommitCheckStatus::Dangling => "dangling",
+            CommitCheckStatus::Fetched => "fetched",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "exists" => Ok(CommitCheckStatus::Exists),
+            "dangling" => Ok(CommitCheckStatus::Dangling),
+            "fetched" => Ok(CommitCheckStatus::Fetched),
+            other => Err(anyhow!("Unknown commit check status: {}", other)),
+        }
+    }
+}
+
+/// Fixed-size bloom filter using double hashing
+#[derive(Debug, Clone)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    /// Size the filter for `expected_items` at the given false positive rate
+    pub fn with_rate(expected_items: usize, false_positive_rate: f64) -> Self {
+        let n = expected_items.max(1) as f64;
+        let p = false_positive_rate.clamp(1e-9, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+
+        let num_bits = (-(n * p.ln()) / (ln2 * ln2)).ceil().max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / n) * ln2).round().clamp(1.0, 16.0) as u32;
+
+        Self {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            num_hashes,
+        }
+    }
+
+    pub fn insert(&mut self, item: &str) {
+        let indexes: Vec<u64> = self.indexes(item).collect();
+        for index in indexes {
+            self.bits[(index / 64) as usize] |= 1 << (index % 64);
+        }
+    }
+
+    /// `false` means the item was definitely never inserted
+    pub fn might_contain(&self, item: &str) -> bool {
+        self.indexes(item)
+            .all(|index| self.bits[(index / 64) as usize] & (1 << (index % 64)) != 0)
+    }
+
+    fn indexes(&self, item: &str) -> impl Iterator<Item = u64> + '_ {
+        let h1 = Self::hash_with_seed(item, 0x51_7c_c1_b7);
+        let h2 = Self::hash_with_seed(item, 0x27_22_0a_95) | 1;
+        (0..self.num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % self.num_bits)
+    }
+
+    fn hash_with_seed(item: &str, seed: u64) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        seed.hash(&mut hasher);
+        item.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Index of commits that have already been checked, backed by the `checked_commits` table.
+/// The bloom filter answers "never seen" without touching the database; positives are
+/// confirmed through a small LRU and then the database itself.
+pub struct CheckedCommitIndex {
+    filter: BloomFilter,
+    recent: LruCache<String, CommitCheckStatus>,
+}
+
+impl CheckedCommitIndex {
+    pub fn new(expected_items: usize, recent_capacity: usize) -> Self {
+        Self {
+            filter: BloomFilter::with_rate(expected_items, 0.01),
+            recent: LruCache::new(NonZeroUsize::new(recent_capacity.max(1)).unwrap()),
+        }
+    }
+
+    /// Build the index from every commit already recorded in the database
+    pub fn load(database: &SecretDatabase, recent_capacity: usize) -> Result<Self> {
+        let checked = database.load_checked_commit_keys()?;
+        // Leave headroom so the false positive rate holds as new commits arrive
+        let mut index = Self::new((checked.len() * 2).max(1_000_000), recent_capacity);
+        for (repository, commit_sha) in &checked {
+            index.filter.insert(&Self::key(repository, commit_sha));
+        }
+
+        info!("Loaded {} previously checked commits into bloom filter", checked.len());
+        Ok(index)
+    }
+
+    /// Previous check result for a commit, if any
+    pub fn lookup(
+        &mut self,
+        database: &SecretDatabase,
+        repository: &str,
+        commit_sha: &str,
+    ) -> Result<Option<CommitCheckStatus>> {
+        let key = Self::key(repository, commit_sha);
+        if !self.filter.might_contain(&key) {
+            return Ok(None);
+        }
+
+        if let Some(status) = self.recent.get(&key) {
+            return Ok(Some(*status));
+        }
+
+        let status = database.get_commit_check(repository, commit_sha)?;
+        if let Some(status) = status {
+            self.recent.put(key, status);
+        } else {
+            debug!("Bloom filter false positive for {}@{}", repository, commit_sha);
+        }
+        Ok(status)
+    }
+
+    /// Persist a check result and add it to the filter
+    pub fn record(
+        &mut self,
+        database: &SecretDatabase,
+        repository: &str,
+        commit_sha: &str,
+        status: CommitCheckStatus,
+    ) -> Result<()> {
+        database.record_commit_check(repository, commit_sha, status)?;
+
+        let key = Self::key(repository, commit_sha);
+        self.filter.insert(&key);
+        self.recent.put(key, status);
+        Ok(())
+    }
+
+    fn key(repository: &str, commit_sha: &str) -> String {
+        format!("{}@{}", repository.to_lowercase(), commit_sha.to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bloom_filter_has_no_false_negatives() {
+        let mut filter = BloomFilter::with_rate(1000, 0.01);
+        for i in 0..1000 {
+            filter.insert(&format!("repo@{:040x}", i));
+        }
+        for i in 0..1000 {
+            assert!(filter.might_contain(&format!("repo@{:040x}", i)));
+        }
+    }
+
+    #[test]
+    fn test_bloom_filter_false_positive_rate() {
+        let mut filter = BloomFilter::with_rate(1000, 0.01);
+        for i in 0..1000 {
+            filter.insert(&format!("inserted-{}", i));
+        }
+        let false_positives = (0..10_000)
+            .filter(|i| filter.might_contain(&format!("absent-{}", i)))
+            .count();
+        assert!(false_positives < 300, "too many false positives: {}", false_positives);
+    }
+
+    #[test]
+    fn test_index_round_trip_through_database() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let mut index = CheckedCommitIndex::new(100, 10);
+
+        assert_eq!(index.lookup(&db, "owner/repo", "abc123").unwrap(), None);
+        index.record(&db, "owner/repo", "abc123", CommitCheckStatus::Dangling).unwrap();
+        assert_eq!(
+            index.lookup(&db, "Owner/Repo", "ABC123").unwrap(),
+            Some(CommitCheckStatus::Dangling)
+        );
+
+        let mut reloaded = CheckedCommitIndex::load(&db, 10).unwrap();
+        assert_eq!(
+            reloaded.lookup(&db, "owner/repo", "abc123").unwrap(),
+            Some(CommitCheckStatus::Dangling)
+        );
+    }
+}
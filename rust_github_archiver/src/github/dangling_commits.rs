@@ -1,17 +1,26 @@
 use anyhow::{anyhow, Result};
-use octocrab::{Octocrab, models::Repository};
+use octocrab::{Octocrab, models::{AppId, InstallationId, Repository}};
 use redis::{Client as RedisClient, Connection, Commands};
 use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
 use tracing::{info, warn, error, debug};
 use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+use crate::core::offline;
+use super::auth::GitHubCredentials;
+use super::etag_cache::{CachedGitHubClient, EtagCache, RateLimited};
+
+/// Number of conditional-request entries kept in memory per fetcher
+const ETAG_CACHE_CAPACITY: usize = 10_000;
 
 /// GitHub API client for fetching dangling commits
 pub struct DanglingCommitFetcher {
     github: Octocrab,
     redis: Option<RedisClient>,
     rate_limiter: RateLimiter,
+    metadata: CachedGitHubClient,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -122,10 +131,26 @@ impl DanglingCommitFetcher {
     pub async fn new(github_token: &str, redis_url: Option<&str>) -> Result<Self> {
         info!("Initializing dangling commit fetcher");
         
-        let github = Octocrab::builder()
-            .personal_token(github_token.to_string())
-            .build()
-            .map_err(|e| anyhow!("Failed to create GitHub client: {}", e))?;
+        let credentials = GitHubCredentials::from(github_token.to_string());
+        // octocrab has its own HTTP stack, so point it at the host the offline policy allows
+        let builder = Octocrab::builder()
+            .base_uri(offline::policy().github_api_base()?)
+            .map_err(|e| anyhow!("Invalid GitHub API base URL: {}", e))?;
+        let github = match &credentials {
+            // octocrab refreshes the installation token itself
+            GitHubCredentials::App(app) => {
+                let app_id = app.app_id().parse().map_err(|_| anyhow!("Invalid GitHub App id {}", app.app_id()))?;
+                builder
+                    .app(AppId(app_id), app.encoding_key().clone())
+                    .build()
+                    .map_err(|e| anyhow!("Failed to create GitHub client: {}", e))?
+                    .installation(InstallationId(app.installation_id().await?))
+            }
+            _ => builder
+                .personal_token(github_token.to_string())
+                .build()
+                .map_err(|e| anyhow!("Failed to create GitHub client: {}", e))?,
+        };
         
         let redis = if let Some(url) = redis_url {
             Some(RedisClient::open(url)
@@ -133,11 +158,15 @@ impl DanglingCommitFetcher {
         } else {
             None
         };
+
+        let etag_cache = Arc::new(EtagCache::new(ETAG_CACHE_CAPACITY, redis_url)?);
+        let metadata = CachedGitHubClient::new(credentials, etag_cache)?;
         
         Ok(Self {
             github,
             redis,
             rate_limiter: RateLimiter::default(),
+            metadata,
         })
     }
 
@@ -338,39 +367,44 @@ impl DanglingCommitFetcher {
         Ok(())
     }
 
-    /// Check if a commit exists without fetching full data
+    /// Check if a commit exists without fetching full data.
+    /// Uses conditional requests so repeated checks are answered with `304 Not Modified`.
     pub async fn commit_exists(
         &mut self,
         repository: &str,
         commit_sha: &str,
     ) -> Result<bool> {
-        let parts: Vec<&str> = repository.split('/').collect();
-        if parts.len() != 2 {
+        if repository.split('/').count() != 2 {
             return Err(anyhow!("Invalid repository format: {}", repository));
         }
-        let (owner, repo) = (parts[0], parts[1]);
 
         self.rate_limiter.wait_if_needed().await?;
 
-        match self.github.repos(owner, repo).commits(commit_sha).get().await {
-            Ok(_) => {
-                self.rate_limiter.requests_remaining -= 1;
-                Ok(true)
-            }
-            Err(octocrab::Error::GitHub { source, .. }) => {
-                match source.status_code.as_u16() {
-                    404 => Ok(false),
-                    403 | 429 => {
-                        self.rate_limiter.requests_remaining = 0;
-                        Err(anyhow!("GitHub API rate limited"))
-                    }
-                    _ => Err(anyhow!("GitHub API error: {}", source.status_code))
-                }
+        match self.metadata.commit_exists(repository, commit_sha).await {
+            Ok(exists) => Ok(exists),
+            Err(e) if e.is::<RateLimited>() => {
+                self.rate_limiter.requests_remaining = 0;
+                Err(anyhow!("GitHub API rate limited"))
             }
             Err(e) => Err(anyhow!("Failed to check commit existence: {}", e))
         }
     }
 
+    /// Fetch repository metadata, revalidated with ETags
+    pub async fn repository_metadata(&self, repository: &str) -> Result<Option<serde_json::Value>> {
+        self.metadata.get_repository(repository).await
+    }
+
+    /// Fetch user metadata, revalidated with ETags
+    pub async fn user_metadata(&self, login: &str) -> Result<Option<serde_json::Value>> {
+        self.metadata.get_user(login).await
+    }
+
+    /// Conditional request cache statistics
+    pub fn etag_cache_stats(&self) -> super::etag_cache::EtagCacheStats {
+        self.metadata.cache_stats()
+    }
+
     /// Get current rate limit status
     pub fn get_rate_limit_status(&self) -> (i32, Duration) {
         let remaining_time = self.rate_limiter.reset_time.saturating_duration_since(Instant::now());
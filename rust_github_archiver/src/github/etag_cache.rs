@@ -0,0 +1,350 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use lru::LruCache;
+use redis::{Client as RedisClient, Commands};
+use reqwest::{header, Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+use super::auth::GitHubCredentials;
+use crate::core::{build_client, offline, send_with_retry, HttpConfig, RetryPolicy};
+
+/// A cached GitHub API response keyed by request path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: serde_json::Value,
+    pub fetched_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EtagCacheStats {
+    pub not_modified_hits: u64,
+    pub misses: u64,
+    pub stored: u64,
+}
+
+/// ETag-aware response cache. Entries live in an in-memory LRU and are optionally
+/// mirrored to Redis so they survive restarts and can be shared between workers.
+pub struct EtagCache {
+    entries: Mutex<LruCache<String, CachedResponse>>,
+    redis: Option<RedisClient>,
+    stats: Mutex<EtagCacheStats>,
+}
+
+impl EtagCache {
+    pub fn new(capacity: usize, redis_url: Option<&str>) -> Result<Self> {
+        let redis = match redis_url {
+            Some(url) => Some(RedisClient::open(url)
+                .map_err(|e| anyhow!("Failed to connect to Redis: {}", e))?),
+            None => None,
+        };
+
+        Ok(Self {
+            entries: Mutex::new(LruCache::new(
+                NonZeroUsize::new(capacity.max(1)).unwrap(),
+            )),
+            redis,
+            stats: Mutex::new(EtagCacheStats::default()),
+        })
+    }
+
+    /// Look up a cached response, falling back to Redis on a local miss
+    pub fn get(&self, key: &str) -> Option<CachedResponse> {
+        if let Some(entry) = self.entries.lock().unwrap().get(key).cloned() {
+            return Some(entry);
+        }
+
+        let redis_client = self.redis.as_ref()?;
+        let mut conn = redis_client.get_connection().ok()?;
+        let json: Option<String> = conn.get(Self::redis_key(key)).ok()?;
+        let entry: CachedResponse = serde_json::from_str(&json?).ok()?;
+        self.entries.lock().unwrap().put(key.to_string(), entry.clone());
+        Some(entry)
+    }
+
+    /// Store a response that carried a validator (ETag or Last-Modified)
+    pub fn put(&self, key: &str, entry: CachedResponse) {
+        if entry.etag.is_none() && entry.last_modified.is_none() {
+            return;
+        }
+
+        if let Some(redis_client) = &self.redis {
+            match (redis_client.get_connection(), serde_json::to_string(&entry)) {
+                (Ok(mut conn), Ok(json)) => {
+                    // Keep for 7 days; revalidation keeps the entry fresh anyway
+                    let result: redis::RedisResult<()> = conn.set_ex(Self::redis_key(key), json, 7 * 86400);
+                    if let Err(e) = result {
+                        warn!("Failed to persist ETag cache entry {}: {}", key, e);
+                    }
+                }
+                (Err(e), _) => warn!("Redis connection failed: {}", e),
+                (_, Err(e)) => warn!("Failed to serialize ETag cache entry: {}", e),
+            }
+        }
+
+        self.entries.lock().unwrap().put(key.to_string(), entry);
+        self.stats.lock().unwrap().stored += 1;
+    }
+
+    pub fn invalidate(&self, key: &str) {
+        self.entries.lock().unwrap().pop(key);
+        if let Some(redis_client) = &self.redis {
+            if let Ok(mut conn) = redis_client.get_connection() {
+                let _: redis::RedisResult<()> = conn.del(Self::redis_key(key));
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> EtagCacheStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    fn record_not_modified(&self) {
+        self.stats.lock().unwrap().not_modified_hits += 1;
+    }
+
+    fn record_miss(&self) {
+        self.stats.lock().unwrap().misses += 1;
+    }
+
+    fn redis_key(key: &str) -> String {
+        format!("etag:{}", key)
+    }
+}
+
+/// Returned when GitHub answers with 403/429 so callers can back off
+#[derive(Debug)]
+pub struct RateLimited;
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GitHub API rate limited")
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Thin GitHub REST client that sends conditional requests for metadata lookups.
+/// A `304 Not Modified` answer is served from the cache and does not count
+/// against the authenticated rate limit.
+pub struct CachedGitHubClient {
+    http: Client,
+    api_base_url: String,
+    credentials: GitHubCredentials,
+    cache: Arc<EtagCache>,
+    retry_policy: RetryPolicy,
+}
+
+impl CachedGitHubClient {
+    /// `token` is used unless a GitHub App is configured; see `GitHubCredentials::resolve`
+    pub fn new(token: impl Into<GitHubCredentials>, cache: Arc<EtagCache>) -> Result<Self> {
+        let http_config = HttpConfig::default();
+        Ok(Self {
+            http: build_client(&http_config)?,
+            api_base_url: offline::policy().github_api_base()?,
+            credentials: token.into(),
+            cache,
+            retry_policy: RetryPolicy::from_config(&http_config),
+        })
+    }
+
+    pub fn cache_stats(&self) -> EtagCacheStats {
+        self.cache.stats()
+    }
+
+    /// Fetch repository metadata (`GET /repos/{owner}/{repo}`)
+    pub async fn get_repository(&self, repository: &str) -> Result<Option<serde_json::Value>> {
+        self.get_json(&format!("/repos/{}", repository)).await
+    }
+
+    /// Every repository of an organization, forks and archived ones included
+    /// (`GET /orgs/{org}/repos?type=all`, 100 per page). `None` if there is no such organization.
+    pub async fn organization_repositories(&self, organization: &str) -> Result<Option<Vec<serde_json::Value>>> {
+        const PER_PAGE: usize = 100;
+        let mut repositories = Vec::new();
+        for page in 1.. {
+            let path = format!("/orgs/{}/repos?type=all&per_page={}&page={}", organization, PER_PAGE, page);
+            let Some(body) = self.get_json(&path).await? else {
+                return Ok(None);
+            };
+            let listed = body.as_array().cloned().unwrap_or_default();
+            let last = listed.len() < PER_PAGE;
+            repositories.extend(listed);
+            if last {
+                break;
+            }
+        }
+        Ok(Some(repositories))
+    }
+
+    /// Fetch a user or organization profile (`GET /users/{login}`)
+    pub async fn get_user(&self, login: &str) -> Result<Option<serde_json::Value>> {
+        self.get_json(&format!("/users/{}", login)).await
+    }
+
+    /// Check whether a commit is reachable through the API
+    pub async fn commit_exists(&self, repository: &str, commit_sha: &str) -> Result<bool> {
+        Ok(self
+            .get_json(&format!("/repos/{}/commits/{}", repository, commit_sha))
+            .await?
+            .is_some())
+    }
+
+    /// Contributors of a repository, anonymous ones included. Asks for one per page and reads
+    /// the page count from the `Link` header, so any repository costs a single request; the
+    /// answer is not cached because ETag hits do not carry the header. `None` if there is no
+    /// such repository.
+    pub async fn contributor_count(&self, repository: &str) -> Result<Option<i64>> {
+        let url = format!("{}/repos/{}/contributors?per_page=1&anon=1", self.api_base_url, repository);
+        let mut request = self.http.get(&url).header(header::ACCEPT, "application/vnd.github+json");
+        if let Some(token) = self.credentials.token().await? {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let response = send_with_retry(request, &self.retry_policy).await?;
+
+        match response.status() {
+            // Empty repositories have no contributors list
+            StatusCode::NO_CONTENT => Ok(Some(0)),
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                if let Some(pages) = header_string(&response, header::LINK).as_deref().and_then(last_page) {
+                    return Ok(Some(pages));
+                }
+                let body: serde_json::Value = response.json().await?;
+                Ok(Some(body.as_array().map_or(0, |listed| listed.len() as i64)))
+            }
+            StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS => Err(RateLimited.into()),
+            status => Err(anyhow!("GitHub API error {} for contributors of {}", status, repository)),
+        }
+    }
+
+    /// Conditional GET returning `None` for 404/422 responses
+    pub async fn get_json(&self, path: &str) -> Result<Option<serde_json::Value>> {
+        let cached = self.cache.get(path);
+        let url = format!("{}{}", self.api_base_url, path);
+
+        let mut request = self.http
+            .get(&url)
+            .header(header::ACCEPT, "application/vnd.github+json");
+        if let Some(token) = self.credentials.token().await? {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        if let Some(entry) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            } else if let Some(last_modified) = &entry.last_modified {
+                request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = send_with_retry(request, &self.retry_policy).await?;
+
+        match response.status() {
+            StatusCode::NOT_MODIFIED => {
+                let entry = cached.ok_or_else(|| anyhow!("304 received for uncached path {}", path))?;
+                debug!("ETag cache hit for {}", path);
+                self.cache.record_not_modified();
+                Ok(Some(entry.body))
+            }
+            StatusCode::NOT_FOUND | StatusCode::UNPROCESSABLE_ENTITY => {
+                self.cache.invalidate(path);
+                Ok(None)
+            }
+            status if status.is_success() => {
+                self.cache.record_miss();
+                let etag = header_string(&response, header::ETAG);
+                let last_modified = header_string(&response, header::LAST_MODIFIED);
+                let body: serde_json::Value = response.json().await?;
+
+                self.cache.put(path, CachedResponse {
+                    etag,
+                    last_modified,
+                    body: body.clone(),
+                    fetched_at: Utc::now(),
+                });
+
+                Ok(Some(body))
+            }
+            StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS => Err(RateLimited.into()),
+            status => Err(anyhow!("GitHub API error {} for {}", status, path)),
+        }
+    }
+}
+
+fn header_string(response: &reqwest::Response, name: header::HeaderName) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string())
+}
+
+/// `page` of the `rel="last"` link, i.e. the number of pages
+fn last_page(link: &str) -> Option<i64> {
+    link.split(',')
+        .find(|part| part.contains("rel=\"last\""))
+        .and_then(|part| part.split(['?', '&', '>']).find_map(|pair| pair.trim().strip_prefix("page=")))
+        .and_then(|page| page.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_page_from_link_header() {
+        let link = "<https://api.github.com/repositories/1/contributors?per_page=1&anon=1&page=2>; rel=\"next\", \
+            <https://api.github.com/repositories/1/contributors?per_page=1&anon=1&page=437>; rel=\"last\"";
+        assert_eq!(last_page(link), Some(437));
+        assert_eq!(last_page("<https://api.github.com/x?page=1>; rel=\"prev\""), None);
+    }
+
+    fn entry(etag: Option<&str>) -> CachedResponse {
+        CachedResponse {
+            etag: etag.map(|s| s.to_string()),
+            last_modified: None,
+            body: serde_json::json!({"full_name": "owner/repo"}),
+            fetched_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_put_and_get() {
+        let cache = EtagCache::new(10, None).unwrap();
+        cache.put("/repos/owner/repo", entry(Some("\"abc\"")));
+
+        let cached = cache.get("/repos/owner/repo").unwrap();
+        assert_eq!(cached.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(cache.stats().stored, 1);
+    }
+
+    #[test]
+    fn test_entries_without_validators_are_not_stored() {
+        let cache = EtagCache::new(10, None).unwrap();
+        cache.put("/users/octocat", entry(None));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_lru_eviction_and_invalidate() {
+        let cache = EtagCache::new(1, None).unwrap();
+        cache.put("/a", entry(Some("1")));
+        cache.put("/b", entry(Some("2")));
+        assert!(cache.get("/a").is_none());
+
+        cache.invalidate("/b");
+        assert!(cache.get("/b").is_none());
+    }
+}
@@ -0,0 +1,216 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use reqwest::{header, Client, StatusCode};
+use serde_json::{json, Map, Value};
+use tracing::{debug, warn};
+
+use super::auth::GitHubCredentials;
+use super::etag_cache::RateLimited;
+use crate::core::{build_client, offline, send_idempotent_with_retry, HttpConfig, RetryPolicy};
+use crate::performance::RepositoryMetadata;
+
+/// Repositories per GraphQL query; each is an aliased `repository` field
+pub const GRAPHQL_BATCH: usize = 50;
+
+/// Largest page GitHub serves for a connection
+const PAGE_SIZE: usize = 100;
+
+const REPOSITORY_FIELDS: &str = "nameWithOwner visibility isPrivate isFork isArchived stargazerCount \
+    primaryLanguage { name } pushedAt";
+
+/// GitHub GraphQL client for metadata that would take one REST call per repository.
+/// Always needs a token; GitHub does not serve GraphQL anonymously.
+pub struct GitHubGraphQLClient {
+    http: Client,
+    endpoint: String,
+    credentials: GitHubCredentials,
+    retry_policy: RetryPolicy,
+}
+
+impl GitHubGraphQLClient {
+    pub fn new(token: impl Into<GitHubCredentials>) -> Result<Self> {
+        let credentials = token.into();
+        if credentials.is_anonymous() {
+            return Err(anyhow!("The GitHub GraphQL API requires a token"));
+        }
+        let http_config = HttpConfig::default();
+        Ok(Self {
+            http: build_client(&http_config)?,
+            endpoint: graphql_endpoint(&offline::policy().github_api_base()?),
+            credentials,
+            retry_policy: RetryPolicy::from_config(&http_config),
+        })
+    }
+
+    /// Metadata for every `owner/name` in `repositories`, `GRAPHQL_BATCH` per request.
+    /// Repositories GitHub does not return are marked unavailable; malformed names are skipped.
+    pub async fn repositories(&self, repositories: &[String]) -> Result<Vec<RepositoryMetadata>> {
+        let mut metadata = Vec::with_capacity(repositories.len());
+        let valid: Vec<(&str, &str, &String)> = repositories
+            .iter()
+            .filter_map(|full| match full.split_once('/') {
+                Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => Some((owner, name, full)),
+                _ => {
+                    warn!("Skipping malformed repository name {:?}", full);
+                    None
+                }
+            })
+            .collect();
+
+        for batch in valid.chunks(GRAPHQL_BATCH) {
+            let (query, variables) = repositories_query(batch.iter().map(|(owner, name, _)| (*owner, *name)));
+            let data = self.query(&query, variables).await?;
+            for (index, (_, _, full)) in batch.iter().enumerate() {
+                match &data[format!("r{}", index)] {
+                    Value::Null => metadata.push(RepositoryMetadata::unavailable(full)),
+                    repository => metadata.push(repository_metadata(full, repository)),
+                }
+            }
+            debug!("Fetched metadata for {} repositories over GraphQL", batch.len());
+        }
+        Ok(metadata)
+    }
+
+    /// Every repository of an organization, forks and archived ones included, a page of 100
+    /// per request
+    pub async fn organization_repositories(&self, organization: &str) -> Result<Vec<RepositoryMetadata>> {
+        let query = format!(
+            "query($login: String!, $after: String) {{ organization(login: $login) {{ \
+            repositories(first: {PAGE_SIZE}, after: $after, orderBy: {{field: NAME, direction: ASC}}) {{ \
+            pageInfo {{ hasNextPage endCursor }} nodes {{ {REPOSITORY_FIELDS} }} }} }} }}"
+        );
+        let mut repositories = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let data = self.query(&query, json!({ "login": organization, "after": after })).await?;
+            if data["organization"].is_null() {
+                return Err(anyhow!("No organization {} on GitHub", organization));
+            }
+            let connection = &data["organization"]["repositories"];
+            for node in connection["nodes"].as_array().into_iter().flatten() {
+                let name = node["nameWithOwner"].as_str().unwrap_or_default();
+                repositories.push(repository_metadata(name, node));
+            }
+            match (connection["pageInfo"]["hasNextPage"].as_bool(), connection["pageInfo"]["endCursor"].as_str()) {
+                (Some(true), Some(cursor)) => after = Some(cursor.to_string()),
+                _ => break,
+            }
+        }
+        debug!("Listed {} repositories of {} over GraphQL", repositories.len(), organization);
+        Ok(repositories)
+    }
+
+    /// Run a query and return its `data`. Per-field errors such as `NOT_FOUND` leave that
+    /// field `null` and are not failures; a rate limit is reported as `RateLimited`.
+    async fn query(&self, query: &str, variables: Value) -> Result<Value> {
+        let token = self.credentials.token().await?.unwrap_or_default();
+        let request = self
+            .http
+            .post(&self.endpoint)
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .json(&json!({ "query": query, "variables": variables }));
+        let response = send_idempotent_with_retry(request, &self.retry_policy).await?;
+
+        match response.status() {
+            StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS => return Err(RateLimited.into()),
+            status if !status.is_success() => return Err(anyhow!("GitHub GraphQL API error {}", status)),
+            _ => {}
+        }
+        let body: Value = response.json().await?;
+        let errors = body["errors"].as_array().cloned().unwrap_or_default();
+        if errors.iter().any(|e| e["type"] == "RATE_LIMITED") {
+            return Err(RateLimited.into());
+        }
+        match body.get("data").filter(|data| !data.is_null()) {
+            Some(data) => Ok(data.clone()),
+            None => Err(anyhow!(
+                "GitHub GraphQL query failed: {}",
+                errors.iter().filter_map(|e| e["message"].as_str()).collect::<Vec<_>>().join("; ")
+            )),
+        }
+    }
+}
+
+/// `https://api.github.com/graphql`, or `/api/graphql` on GitHub Enterprise
+fn graphql_endpoint(api_base: &str) -> String {
+    match api_base.strip_suffix("/api/v3") {
+        Some(host) => format!("{}/api/graphql", host),
+        None => format!("{}/graphql", api_base),
+    }
+}
+
+/// One aliased `repository` field per name, with owners and names passed as variables
+fn repositories_query<'a>(repositories: impl Iterator<Item = (&'a str, &'a str)>) -> (String, Value) {
+    let mut declarations = Vec::new();
+    let mut fields = Vec::new();
+    let mut variables = Map::new();
+    for (index, (owner, name)) in repositories.enumerate() {
+        declarations.push(format!("$o{index}: String!, $n{index}: String!"));
+        fields.push(format!("r{index}: repository(owner: $o{index}, name: $n{index}) {{ {REPOSITORY_FIELDS} }}"));
+        variables.insert(format!("o{}", index), json!(owner));
+        variables.insert(format!("n{}", index), json!(name));
+    }
+    let query = format!("query({}) {{ {} }}", declarations.join(", "), fields.join(" "));
+    (query, Value::Object(variables))
+}
+
+/// GraphQL has no contributor count (`mentionableUsers` also counts collaborators who never
+/// committed); it is left empty for `CachedGitHubClient::contributor_count`
+fn repository_metadata(name: &str, repository: &Value) -> RepositoryMetadata {
+    let visibility = repository["visibility"].as_str().map(str::to_lowercase).or_else(|| {
+        repository["isPrivate"].as_bool().map(|private| if private { "private" } else { "public" }.to_string())
+    });
+    RepositoryMetadata {
+        name: repository["nameWithOwner"].as_str().unwrap_or(name).to_string(),
+        visibility,
+        star_count: repository["stargazerCount"].as_i64(),
+        language: repository["primaryLanguage"]["name"].as_str().map(str::to_string),
+        archived: repository["isArchived"].as_bool().unwrap_or(false),
+        last_activity: repository["pushedAt"]
+            .as_str()
+            .and_then(|pushed| DateTime::parse_from_rfc3339(pushed).ok())
+            .map(|pushed| pushed.with_timezone(&Utc)),
+        contributor_count: None,
+        is_fork: repository["isFork"].as_bool(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_binds_names_as_variables() {
+        let (query, variables) = repositories_query([("acme", "api"), ("acme", "web\" }")].into_iter());
+        assert!(query.starts_with("query($o0: String!, $n0: String!, $o1: String!, $n1: String!)"));
+        assert!(query.contains("r1: repository(owner: $o1, name: $n1)"));
+        assert!(!query.contains("web"));
+        assert_eq!(variables["n1"], "web\" }");
+        assert_eq!(graphql_endpoint("https://api.github.com"), "https://api.github.com/graphql");
+        assert_eq!(graphql_endpoint("https://github.corp.example/api/v3"), "https://github.corp.example/api/graphql");
+    }
+
+    #[test]
+    fn test_repository_metadata_from_graphql() {
+        let node = json!({
+            "nameWithOwner": "Acme/Payments",
+            "visibility": "PUBLIC",
+            "isPrivate": false,
+            "isFork": true,
+            "isArchived": false,
+            "stargazerCount": 1200,
+            "primaryLanguage": { "name": "Go" },
+            "pushedAt": "2024-05-01T12:00:00Z",
+            "mentionableUsers": { "totalCount": 37 }
+        });
+        let metadata = repository_metadata("acme/payments", &node);
+        assert_eq!(metadata.name, "Acme/Payments");
+        assert_eq!(metadata.visibility.as_deref(), Some("public"));
+        assert_eq!((metadata.star_count, metadata.contributor_count, metadata.is_fork), (Some(1200), None, Some(true)));
+        assert_eq!(metadata.language.as_deref(), Some("Go"));
+
+        let bare = repository_metadata("acme/api", &json!({ "isPrivate": true, "primaryLanguage": null }));
+        assert_eq!(bare.visibility.as_deref(), Some("private"));
+        assert!(bare.language.is_none() && bare.contributor_count.is_none());
+    }
+}
@@ -1,3 +1,15 @@
+pub mod auth;
+pub mod checks;
+pub mod commit_filter;
 pub mod dangling_commits;
+pub mod etag_cache;
+pub mod graphql;
+pub mod recovery;
 
+pub use auth::{configured_app, GitHubApp, GitHubCredentials, GITHUB_APP_ID_ENV, GITHUB_APP_INSTALLATION_ID_ENV, GITHUB_APP_PRIVATE_KEY_ENV};
+pub use checks::{check_run_conclusion, check_run_summary, CheckRunPublisher, CHECK_RUN_NAME};
+pub use commit_filter::{BloomFilter, CheckedCommitIndex, CommitCheckStatus};
 pub use dangling_commits::{DanglingCommitFetcher, CommitInfo, CommitAuthor, CommitStats, CommitFile};
+pub use etag_cache::{CachedGitHubClient, CachedResponse, EtagCache, EtagCacheStats};
+pub use graphql::GitHubGraphQLClient;
+pub use recovery::{CandidateSource, CommitObject, DanglingCommitRecovery, RecoveredCommit};
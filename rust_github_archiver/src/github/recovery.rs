@@ -0,0 +1,529 @@
+use anyhow::{anyhow, bail, Result};
+use flate2::{Decompress, FlushDecompress, Status};
+use reqwest::{header, Client, StatusCode};
+use ring::digest::{digest, SHA1_FOR_LEGACY_USE_ONLY};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use tracing::{debug, info, warn};
+
+use super::auth::GitHubCredentials;
+use super::etag_cache::RateLimited;
+use crate::core::{build_client, offline, send_idempotent_with_retry, send_with_retry, HttpConfig, RetryPolicy};
+
+/// Shortest prefix worth expanding; GitHub itself abbreviates to 7
+pub const MIN_PREFIX_LEN: usize = 7;
+
+/// REST lookups one recovery may spend splitting ambiguous prefixes
+pub const DEFAULT_API_BUDGET: usize = 64;
+
+/// Ref tips sent as `have` lines, so the pack leaves out what the branches already hold
+const MAX_HAVES: usize = 32;
+
+/// Largest upload-pack response read for one commit
+const MAX_PACK_BYTES: usize = 16 * 1024 * 1024;
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/// How a candidate full SHA was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateSource {
+    /// A branch, tag or pull request ref points at it
+    RefAdvertisement,
+    /// GitHub's commit API resolved the prefix (or a longer one) to it
+    CommitApi,
+}
+
+/// A commit object as git stores it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommitObject {
+    pub tree: String,
+    pub parents: Vec<String>,
+    /// `Name <email> timestamp tz`
+    pub author: String,
+    pub committer: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveredCommit {
+    pub sha: String,
+    pub repository: String,
+    pub source: CandidateSource,
+    /// The ref that points at this commit; `None` means nothing advertises it, so it is
+    /// dangling or only reachable from another ref's history
+    pub advertised_ref: Option<String>,
+    /// The commit as upload-pack served it; `None` when the server refused to send it
+    pub object: Option<CommitObject>,
+}
+
+enum PrefixLookup {
+    Resolved(String),
+    Ambiguous,
+    NotFound,
+}
+
+/// Recovers commits the Events API no longer serves from the abbreviated SHAs left in
+/// events, refs and logs. Candidates come from the repository's ref advertisement and from
+/// GitHub's commit API, which resolves unique prefixes (dangling commits included) and is
+/// re-asked with one more hex digit when a prefix is ambiguous. Each candidate is then
+/// fetched over `git upload-pack` with a `want` for it and `have`s for the ref tips, which
+/// confirms it exists and returns the commit object itself.
+pub struct DanglingCommitRecovery {
+    http: Client,
+    api_base_url: String,
+    git_base_url: String,
+    credentials: GitHubCredentials,
+    retry_policy: RetryPolicy,
+    api_budget: usize,
+}
+
+impl DanglingCommitRecovery {
+    pub fn new(token: impl Into<GitHubCredentials>) -> Result<Self> {
+        let http_config = HttpConfig::default();
+        let policy = offline::policy();
+        Ok(Self {
+            http: build_client(&http_config)?,
+            api_base_url: policy.github_api_base()?,
+            git_base_url: policy.github_web_base()?,
+            credentials: token.into(),
+            retry_policy: RetryPolicy::from_config(&http_config),
+            api_budget: DEFAULT_API_BUDGET,
+        })
+    }
+
+    pub fn with_api_budget(mut self, requests: usize) -> Self {
+        self.api_budget = requests.max(1);
+        self
+    }
+
+    /// Every commit in `repository` whose SHA starts with `prefix`
+    pub async fn recover(&self, repository: &str, prefix: &str) -> Result<Vec<RecoveredCommit>> {
+        let prefix = normalize_prefix(prefix)?;
+        if repository.split('/').count() != 2 {
+            return Err(anyhow!("Invalid repository format: {}", repository));
+        }
+        info!("Recovering commits {}… in {}", prefix, repository);
+
+        // Private repositories without a token, or git access turned off, leave the API alone
+        let refs = self.list_refs(repository).await.unwrap_or_else(|e| {
+            warn!("Could not list refs of {}: {}", repository, e);
+            Vec::new()
+        });
+
+        let mut candidates: BTreeMap<String, (CandidateSource, Option<String>)> = BTreeMap::new();
+        for (sha, name) in &refs {
+            if sha.starts_with(&prefix) {
+                candidates.entry(sha.clone()).or_insert((CandidateSource::RefAdvertisement, Some(name.clone())));
+            }
+        }
+        for sha in self.expand_prefix(repository, &prefix).await? {
+            candidates.entry(sha).or_insert((CandidateSource::CommitApi, None));
+        }
+
+        let haves: Vec<&str> = refs.iter().map(|(sha, _)| sha.as_str()).collect();
+        let mut recovered = Vec::new();
+        for (sha, (source, advertised_ref)) in candidates {
+            let object = match self.fetch_commit_object(repository, &sha, &haves).await {
+                Ok(object) => object,
+                Err(e) => {
+                    warn!("upload-pack would not serve {} from {}: {}", sha, repository, e);
+                    None
+                }
+            };
+            recovered.push(RecoveredCommit { sha, repository: repository.to_string(), source, advertised_ref, object });
+        }
+
+        info!("Recovered {} commit(s) for {}… in {}", recovered.len(), prefix, repository);
+        Ok(recovered)
+    }
+
+    /// Full SHAs the commit API resolves `prefix` to, splitting ambiguous prefixes until
+    /// each is unique or the request budget runs out
+    async fn expand_prefix(&self, repository: &str, prefix: &str) -> Result<Vec<String>> {
+        let mut found = Vec::new();
+        let mut pending = VecDeque::from([prefix.to_string()]);
+        let mut budget = self.api_budget;
+
+        while let Some(candidate) = pending.pop_front() {
+            if budget == 0 {
+                warn!(
+                    "API budget spent with {} ambiguous prefix(es) of {} left in {}",
+                    pending.len() + 1,
+                    prefix,
+                    repository
+                );
+                break;
+            }
+            budget -= 1;
+
+            match self.lookup_prefix(repository, &candidate).await? {
+                PrefixLookup::Resolved(sha) => found.push(sha),
+                PrefixLookup::NotFound => {}
+                PrefixLookup::Ambiguous if candidate.len() < 40 => {
+                    debug!("{} is ambiguous in {}, trying one more digit", candidate, repository);
+                    pending.extend(HEX.iter().map(|&digit| format!("{}{}", candidate, digit as char)));
+                }
+                PrefixLookup::Ambiguous => {}
+            }
+        }
+
+        found.sort();
+        found.dedup();
+        Ok(found)
+    }
+
+    /// `GET /repos/{repo}/commits/{prefix}` with the SHA media type, which answers with
+    /// the bare full SHA
+    async fn lookup_prefix(&self, repository: &str, prefix: &str) -> Result<PrefixLookup> {
+        let url = format!("{}/repos/{}/commits/{}", self.api_base_url, repository, prefix);
+        let mut request = self.http.get(&url).header(header::ACCEPT, "application/vnd.github.sha");
+        if let Some(token) = self.credentials.token().await? {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+
+        let response = send_with_retry(request, &self.retry_policy).await?;
+        match response.status() {
+            status if status.is_success() => {
+                let sha = response.text().await?.trim().to_lowercase();
+                if sha.len() == 40 && sha.starts_with(prefix) && sha.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    Ok(PrefixLookup::Resolved(sha))
+                } else {
+                    Err(anyhow!("Unexpected commit API answer for {}: {}", prefix, sha))
+                }
+            }
+            StatusCode::NOT_FOUND => Ok(PrefixLookup::NotFound),
+            StatusCode::UNPROCESSABLE_ENTITY | StatusCode::CONFLICT => {
+                let message = response.text().await.unwrap_or_default();
+                if message.to_lowercase().contains("ambiguous") {
+                    Ok(PrefixLookup::Ambiguous)
+                } else {
+                    Ok(PrefixLookup::NotFound)
+                }
+            }
+            StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS => Err(RateLimited.into()),
+            status => Err(anyhow!("GitHub API error {} resolving {} in {}", status, prefix, repository)),
+        }
+    }
+
+    /// Protocol v2 `ls-refs`: every advertised ref as `(sha, name)`, tags peeled to their commit
+    async fn list_refs(&self, repository: &str) -> Result<Vec<(String, String)>> {
+        let mut body = Vec::new();
+        pkt_line(&mut body, "command=ls-refs\n");
+        body.extend_from_slice(b"0001");
+        pkt_line(&mut body, "peel\n");
+        pkt_line(&mut body, "ref-prefix refs/\n");
+        body.extend_from_slice(b"0000");
+
+        let response = self.upload_pack(repository, body).await?;
+        let mut refs = Vec::new();
+        for line in pkt_lines(&response)?.into_iter().flatten() {
+            check_err(line)?;
+            let line = std::str::from_utf8(line)?.trim_end();
+            let mut fields = line.split(' ');
+            let (Some(sha), Some(name)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            let commit = fields.find_map(|f| f.strip_prefix("peeled:")).unwrap_or(sha);
+            // `unborn HEAD` and other lines without an object id
+            if commit.len() == 40 && commit.bytes().all(|b| b.is_ascii_hexdigit()) {
+                refs.push((commit.to_string(), name.to_string()));
+            }
+        }
+        Ok(refs)
+    }
+
+    /// Protocol v2 `fetch` of one commit without its trees and blobs. `None` when the pack
+    /// does not contain it (it was stored as a delta, which this reader does not resolve).
+    async fn fetch_commit_object(&self, repository: &str, sha: &str, haves: &[&str]) -> Result<Option<CommitObject>> {
+        let mut body = Vec::new();
+        pkt_line(&mut body, "command=fetch\n");
+        body.extend_from_slice(b"0001");
+        pkt_line(&mut body, "no-progress\n");
+        pkt_line(&mut body, "ofs-delta\n");
+        pkt_line(&mut body, "filter tree:0\n");
+        pkt_line(&mut body, &format!("want {}\n", sha));
+        // A have equal to the want would leave it out of the pack
+        for have in haves.iter().filter(|have| **have != sha).take(MAX_HAVES) {
+            pkt_line(&mut body, &format!("have {}\n", have));
+        }
+        pkt_line(&mut body, "done\n");
+        body.extend_from_slice(b"0000");
+
+        let response = self.upload_pack(repository, body).await?;
+        let pack = demux_packfile(&response)?;
+        Ok(parse_pack_commits(&pack)?.into_iter().find(|(id, _)| id == sha).map(|(_, commit)| commit))
+    }
+
+    async fn upload_pack(&self, repository: &str, body: Vec<u8>) -> Result<Vec<u8>> {
+        let url = format!("{}/{}.git/git-upload-pack", self.git_base_url, repository);
+        let mut request = self
+            .http
+            .post(&url)
+            .header(header::CONTENT_TYPE, "application/x-git-upload-pack-request")
+            .header(header::ACCEPT, "application/x-git-upload-pack-result")
+            .header("Git-Protocol", "version=2")
+            .body(body);
+        if let Some(token) = self.credentials.token().await? {
+            request = request.basic_auth("x-access-token", Some(token));
+        }
+
+        let response = send_idempotent_with_retry(request, &self.retry_policy).await?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!("upload-pack for {} returned {}", repository, status);
+        }
+        let bytes = response.bytes().await?;
+        if bytes.len() > MAX_PACK_BYTES {
+            bail!("upload-pack response for {} exceeds {} bytes", repository, MAX_PACK_BYTES);
+        }
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Lowercase hex of at least `MIN_PREFIX_LEN` digits
+fn normalize_prefix(prefix: &str) -> Result<String> {
+    let prefix = prefix.trim().to_lowercase();
+    if prefix.len() < MIN_PREFIX_LEN || prefix.len() > 40 || !prefix.bytes().all(|b| b.is_ascii_hexdigit()) {
+        bail!("A commit prefix must be {} to 40 hex digits, got {:?}", MIN_PREFIX_LEN, prefix);
+    }
+    Ok(prefix)
+}
+
+fn pkt_line(buffer: &mut Vec<u8>, line: &str) {
+    buffer.extend_from_slice(format!("{:04x}", line.len() + 4).as_bytes());
+    buffer.extend_from_slice(line.as_bytes());
+}
+
+/// Split a pkt-line stream; flush, delimiter and response-end packets come back as `None`
+fn pkt_lines(data: &[u8]) -> Result<Vec<Option<&[u8]>>> {
+    let mut lines = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        if rest.len() < 4 {
+            bail!("Truncated pkt-line");
+        }
+        let length = usize::from_str_radix(std::str::from_utf8(&rest[..4])?, 16)?;
+        if length < 4 {
+            lines.push(None);
+            rest = &rest[4..];
+            continue;
+        }
+        if rest.len() < length {
+            bail!("Truncated pkt-line");
+        }
+        lines.push(Some(&rest[4..length]));
+        rest = &rest[length..];
+    }
+    Ok(lines)
+}
+
+fn check_err(line: &[u8]) -> Result<()> {
+    match line.strip_prefix(b"ERR ") {
+        Some(message) => Err(anyhow!("upload-pack: {}", String::from_utf8_lossy(message).trim_end())),
+        None => Ok(()),
+    }
+}
+
+/// Pack bytes from the `packfile` section, sideband channel 1
+fn demux_packfile(response: &[u8]) -> Result<Vec<u8>> {
+    let mut pack = Vec::new();
+    let mut in_pack = false;
+    for line in pkt_lines(response)?.into_iter().flatten() {
+        check_err(line)?;
+        if !in_pack {
+            in_pack = line == b"packfile\n";
+            continue;
+        }
+        match line.split_first() {
+            Some((1, data)) => pack.extend_from_slice(data),
+            Some((2, _)) => {}
+            Some((3, message)) => bail!("upload-pack: {}", String::from_utf8_lossy(message).trim_end()),
+            _ => bail!("Unexpected packet in packfile section"),
+        }
+    }
+    if !in_pack {
+        bail!("upload-pack sent no packfile");
+    }
+    Ok(pack)
+}
+
+/// Inflate one zlib stream from the start of `data`; returns the bytes and how much was read
+fn inflate(data: &[u8], size: usize) -> Result<(Vec<u8>, usize)> {
+    let mut inflater = Decompress::new(true);
+    // One spare byte lets the stream end be read once the object is complete
+    let mut output = Vec::with_capacity(size + 1);
+    loop {
+        let consumed = inflater.total_in() as usize;
+        let produced = output.len();
+        match inflater.decompress_vec(&data[consumed..], &mut output, FlushDecompress::Finish)? {
+            Status::StreamEnd => return Ok((output, inflater.total_in() as usize)),
+            _ if inflater.total_in() as usize == consumed && output.len() == produced => {
+                if output.len() == output.capacity() {
+                    output.reserve(4096);
+                } else {
+                    bail!("Truncated object in pack");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Commit objects in a pack as `(sha, commit)`. Other objects and deltas are skipped.
+fn parse_pack_commits(pack: &[u8]) -> Result<Vec<(String, CommitObject)>> {
+    if pack.len() < 12 || &pack[..4] != b"PACK" {
+        bail!("Not a pack");
+    }
+    let count = u32::from_be_bytes([pack[8], pack[9], pack[10], pack[11]]);
+    let mut offset = 12;
+    let mut commits = Vec::new();
+
+    for _ in 0..count {
+        let byte = |at: usize| pack.get(at).copied().ok_or_else(|| anyhow!("Truncated pack"));
+        let mut c = byte(offset)?;
+        offset += 1;
+        let kind = (c >> 4) & 7;
+        let mut size = (c & 15) as usize;
+        let mut shift = 4;
+        while c & 0x80 != 0 {
+            if shift > 57 {
+                bail!("Object size overflows in pack");
+            }
+            c = byte(offset)?;
+            offset += 1;
+            size |= ((c & 0x7f) as usize) << shift;
+            shift += 7;
+        }
+        match kind {
+            // OFS_DELTA: base offset as a varint
+            6 => {
+                while byte(offset)? & 0x80 != 0 {
+                    offset += 1;
+                }
+                offset += 1;
+            }
+            // REF_DELTA: base object id
+            7 => offset += 20,
+            _ => {}
+        }
+
+        let (data, consumed) = inflate(pack.get(offset..).ok_or_else(|| anyhow!("Truncated pack"))?, size)?;
+        offset += consumed;
+        if kind == 1 {
+            let mut object = format!("commit {}\0", data.len()).into_bytes();
+            object.extend_from_slice(&data);
+            let sha = hex::encode(digest(&SHA1_FOR_LEGACY_USE_ONLY, &object));
+            commits.push((sha, parse_commit(&String::from_utf8_lossy(&data))));
+        }
+    }
+    Ok(commits)
+}
+
+fn parse_commit(text: &str) -> CommitObject {
+    let (headers, message) = text.split_once("\n\n").unwrap_or((text, ""));
+    let mut commit = CommitObject {
+        tree: String::new(),
+        parents: Vec::new(),
+        author: String::new(),
+        committer: String::new(),
+        message: message.to_string(),
+    };
+    // Continuation lines (signatures) start with a space and match no header
+    for line in headers.lines() {
+        if let Some(tree) = line.strip_prefix("tree ") {
+            commit.tree = tree.to_string();
+        } else if let Some(parent) = line.strip_prefix("parent ") {
+            commit.parents.push(parent.to_string());
+        } else if let Some(author) = line.strip_prefix("author ") {
+            commit.author = author.to_string();
+        } else if let Some(committer) = line.strip_prefix("committer ") {
+            commit.committer = committer.to_string();
+        }
+    }
+    commit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    const COMMIT: &str = "tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\
+        parent 1111111111111111111111111111111111111111\n\
+        author Dev <dev@acme.test> 1700000000 +0000\n\
+        committer Dev <dev@acme.test> 1700000000 +0000\n\
+        \n\
+        Remove leaked key\n";
+
+    fn pack_of(objects: &[(u8, &[u8])]) -> Vec<u8> {
+        let mut pack = b"PACK".to_vec();
+        pack.extend_from_slice(&2u32.to_be_bytes());
+        pack.extend_from_slice(&(objects.len() as u32).to_be_bytes());
+        for (kind, data) in objects {
+            let mut size = data.len();
+            let mut c = (kind << 4) | (size & 15) as u8;
+            size >>= 4;
+            while size > 0 {
+                pack.push(c | 0x80);
+                c = (size & 0x7f) as u8;
+                size >>= 7;
+            }
+            pack.push(c);
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(data).unwrap();
+            pack.extend(encoder.finish().unwrap());
+        }
+        pack
+    }
+
+    #[test]
+    fn test_prefixes_are_normalized() {
+        assert_eq!(normalize_prefix(" ABC1234 ").unwrap(), "abc1234");
+        assert!(normalize_prefix("abc12").is_err());
+        assert!(normalize_prefix("abc123z").is_err());
+    }
+
+    #[test]
+    fn test_pkt_lines_round_trip() {
+        let mut stream = Vec::new();
+        pkt_line(&mut stream, "command=ls-refs\n");
+        stream.extend_from_slice(b"0001");
+        pkt_line(&mut stream, "peel\n");
+        stream.extend_from_slice(b"0000");
+        assert_eq!(&stream[..4], b"0014");
+        assert_eq!(
+            pkt_lines(&stream).unwrap(),
+            vec![Some(&b"command=ls-refs\n"[..]), None, Some(&b"peel\n"[..]), None]
+        );
+        assert!(pkt_lines(b"00ffshort").is_err());
+    }
+
+    #[test]
+    fn test_commits_are_read_from_a_sideband_pack() {
+        let pack = pack_of(&[(1, COMMIT.as_bytes()), (3, b"not a commit")]);
+        let mut response = Vec::new();
+        pkt_line(&mut response, "packfile\n");
+        for chunk in pack.chunks(50) {
+            let mut data = vec![1u8];
+            data.extend_from_slice(chunk);
+            response.extend_from_slice(format!("{:04x}", data.len() + 4).as_bytes());
+            response.extend(data);
+        }
+        response.extend_from_slice(b"0000");
+
+        let commits = parse_pack_commits(&demux_packfile(&response).unwrap()).unwrap();
+        assert_eq!(commits.len(), 1);
+        let (sha, commit) = &commits[0];
+        let mut object = format!("commit {}\0", COMMIT.len()).into_bytes();
+        object.extend_from_slice(COMMIT.as_bytes());
+        assert_eq!(*sha, hex::encode(digest(&SHA1_FOR_LEGACY_USE_ONLY, &object)));
+        assert_eq!(commit.parents, vec!["1111111111111111111111111111111111111111".to_string()]);
+        assert_eq!(commit.message, "Remove leaked key\n");
+
+        let mut refused = Vec::new();
+        pkt_line(&mut refused, "ERR upload-pack: not our ref deadbeef\n");
+        assert!(demux_packfile(&refused).unwrap_err().to_string().contains("not our ref"));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod code_view;
 pub mod secrets_ninja;
 
 pub use secrets_ninja::{SecretsNinjaApp, launch_secrets_ninja, load_secrets_data};
@@ -4,10 +4,14 @@ use iced::{
     alignment::{Horizontal, Vertical},
     Color, Subscription,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use crate::core::{output, AuditLog, LoggingConfig};
+use crate::performance::{CacheScope, FindingAnnotations, FindingNote, SecretDatabase, SecretQueryFilters, TagCount};
 use crate::secrets::{SecretMatch, SecretSeverity, SecretCategory, ValidationResult};
+use crate::sinks::{severity_rank, RedactionProfile};
+use super::code_view;
 
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -22,6 +26,14 @@ pub enum Message {
     ExportResults,
     ShowChart(ChartType),
     RefreshData,
+    LoadAnnotations(String, FindingAnnotations), // secret hash
+    NoteDraftChanged(String, String),            // secret hash, draft
+    AddNote(String),                             // secret hash
+    NoteSaved(String, Result<FindingNote, String>),
+    LoadTagFacets(Vec<TagCount>),
+    FilterByTag(String),
+    LoadTaggedFindings(String, HashSet<String>), // tag, secret hashes carrying it
+    ToggleReveal(String),                        // secret hash
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -56,6 +68,23 @@ pub struct SecretsNinjaApp {
     // UI State
     expanded_details: std::collections::HashSet<String>,
     current_chart: Option<ChartType>,
+
+    // Analyst notes, keyed by secret hash; saved to the findings database when one is attached
+    database_path: Option<String>,
+    /// Opened read-only (e.g. a replica); notes cannot be added
+    read_only: bool,
+    annotations: HashMap<String, FindingAnnotations>,
+    note_drafts: HashMap<String, String>,
+
+    // Tag facets from the findings database; the selected tag narrows the list
+    tag_facets: Vec<TagCount>,
+    tag_filter: Option<String>,
+    tagged_hashes: HashSet<String>,
+
+    // Secrets shown in clear in the context viewer; masked otherwise
+    revealed_secrets: HashSet<String>,
+    /// `SECRET_REDACTION`, applied as secrets are loaded; only `full` allows revealing them
+    redaction: RedactionProfile,
     
     // Statistics
     stats: SecretsStatistics,
@@ -89,6 +118,15 @@ impl Application for SecretsNinjaApp {
                 sort_field: SortField::Severity,
                 expanded_details: std::collections::HashSet::new(),
                 current_chart: None,
+                database_path: None,
+                read_only: false,
+                annotations: HashMap::new(),
+                note_drafts: HashMap::new(),
+                tag_facets: Vec::new(),
+                tag_filter: None,
+                tagged_hashes: HashSet::new(),
+                revealed_secrets: HashSet::new(),
+                redaction: RedactionProfile::from_env(),
                 stats: SecretsStatistics::default(),
             },
             Command::none(),
@@ -102,7 +140,7 @@ impl Application for SecretsNinjaApp {
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
             Message::LoadSecrets(secrets) => {
-                self.secrets = secrets;
+                self.secrets = self.redaction.redact_matches(&secrets);
                 self.update_statistics();
                 self.apply_filters();
                 Command::none()
@@ -137,10 +175,12 @@ impl Application for SecretsNinjaApp {
             Message::ToggleDetails(hash) => {
                 if self.expanded_details.contains(&hash) {
                     self.expanded_details.remove(&hash);
+                    self.revealed_secrets.remove(&hash);
+                    Command::none()
                 } else {
-                    self.expanded_details.insert(hash);
+                    self.expanded_details.insert(hash.clone());
+                    self.load_annotations(hash)
                 }
-                Command::none()
             }
             Message::ValidateSecret(_hash) => {
                 // This would trigger validation in the background
@@ -156,6 +196,61 @@ impl Application for SecretsNinjaApp {
             }
             Message::RefreshData => {
                 // This would reload data from the database
+                self.load_tag_facets()
+            }
+            Message::LoadAnnotations(hash, annotations) => {
+                self.annotations.insert(hash, annotations);
+                Command::none()
+            }
+            Message::NoteDraftChanged(hash, draft) => {
+                self.note_drafts.insert(hash, draft);
+                Command::none()
+            }
+            Message::AddNote(hash) => {
+                let body = self.note_drafts.get(&hash).cloned().unwrap_or_default();
+                if body.trim().is_empty() {
+                    return Command::none();
+                }
+                self.save_note(hash, body)
+            }
+            Message::NoteSaved(hash, result) => {
+                match result {
+                    Ok(note) => {
+                        self.note_drafts.remove(&hash);
+                        self.annotations.entry(hash).or_default().notes.push(note);
+                    }
+                    Err(e) => tracing::error!("Failed to save note on {}: {}", hash, e),
+                }
+                Command::none()
+            }
+            Message::LoadTagFacets(facets) => {
+                self.tag_facets = facets;
+                Command::none()
+            }
+            Message::FilterByTag(tag) => {
+                // Selecting the active tag again clears the filter
+                if self.tag_filter.as_deref() == Some(tag.as_str()) {
+                    self.tag_filter = None;
+                    self.tagged_hashes.clear();
+                    self.apply_filters();
+                    return Command::none();
+                }
+                self.tag_filter = Some(tag.clone());
+                self.load_tagged_findings(tag)
+            }
+            Message::LoadTaggedFindings(tag, hashes) => {
+                // Ignore results for a tag that is no longer selected
+                if self.tag_filter.as_deref() == Some(tag.as_str()) {
+                    self.tagged_hashes = hashes;
+                    self.apply_filters();
+                }
+                Command::none()
+            }
+            Message::ToggleReveal(hash) => {
+                if !self.revealed_secrets.remove(&hash) {
+                    record_reveal(&hash);
+                    self.revealed_secrets.insert(hash);
+                }
                 Command::none()
             }
         }
@@ -191,7 +286,7 @@ impl Application for SecretsNinjaApp {
 
 impl SecretsNinjaApp {
     fn create_header(&self) -> Element<Message> {
-        let title = text("🥷 Secrets Ninja")
+        let title = text(label("🥷 Secrets Ninja"))
             .size(24)
             .color(Color::from_rgb(0.9, 0.1, 0.1));
 
@@ -199,10 +294,10 @@ impl SecretsNinjaApp {
             .size(14)
             .color(Color::from_rgb(0.7, 0.7, 0.7));
 
-        let refresh_button = button("🔄 Refresh")
+        let refresh_button = button(text(label("🔄 Refresh")))
             .on_press(Message::RefreshData);
 
-        let export_button = button("📁 Export")
+        let export_button = button(text(label("📁 Export")))
             .on_press(Message::ExportResults);
 
         row![
@@ -247,28 +342,40 @@ impl SecretsNinjaApp {
         let search_input = text_input("Search secrets...", &self.search_text)
             .on_input(Message::SearchTextChanged);
 
-        row![
+        let filter_row = row![
             text("Filters:"),
             severity_filter,
             category_filter,
             search_input
         ]
         .spacing(10)
-        .align_items(iced::Alignment::Center)
-        .into()
+        .align_items(iced::Alignment::Center);
+
+        if self.tag_facets.is_empty() {
+            return filter_row.into();
+        }
+
+        let mut facets = row![text("Tags:").size(12)].spacing(5).align_items(iced::Alignment::Center);
+        for facet in &self.tag_facets {
+            let selected = self.tag_filter.as_deref() == Some(facet.tag.as_str());
+            let label = format!("{}{} ({})", if selected { label("✓ ") } else { String::new() }, facet.tag, facet.findings);
+            facets = facets.push(button(text(label).size(11)).on_press(Message::FilterByTag(facet.tag.clone())));
+        }
+
+        column![filter_row, facets].spacing(5).into()
     }
 
     fn create_statistics_panel(&self) -> Element<Message> {
         let total_text = text(format!("Total Secrets: {}", self.stats.total_secrets));
         let verified_text = text(format!("Verified: {}", self.stats.verified_secrets));
         
-        let severity_chart_button = button("📊 Severity Chart")
+        let severity_chart_button = button(text(label("📊 Severity Chart")))
             .on_press(Message::ShowChart(ChartType::SeverityDistribution));
         
-        let category_chart_button = button("📈 Category Chart")
+        let category_chart_button = button(text(label("📈 Category Chart")))
             .on_press(Message::ShowChart(ChartType::CategoryDistribution));
         
-        let timeline_chart_button = button("📉 Timeline")
+        let timeline_chart_button = button(text(label("📉 Timeline")))
             .on_press(Message::ShowChart(ChartType::TimelineLeak));
 
         let severity_breakdown = self.create_severity_breakdown();
@@ -301,12 +408,7 @@ impl SecretsNinjaApp {
                 0.0
             };
             
-            let color = match severity {
-                SecretSeverity::Critical => Color::from_rgb(0.9, 0.1, 0.1),
-                SecretSeverity::High => Color::from_rgb(0.9, 0.5, 0.1),
-                SecretSeverity::Medium => Color::from_rgb(0.9, 0.9, 0.1),
-                SecretSeverity::Low => Color::from_rgb(0.1, 0.9, 0.1),
-            };
+            let color = severity_color(severity);
             
             let severity_text = text(format!("{:?}: {} ({:.1}%)", severity, count, percentage))
                 .color(color);
@@ -353,12 +455,7 @@ impl SecretsNinjaApp {
     }
 
     fn create_secret_item(&self, secret: &SecretMatch) -> Element<Message> {
-        let severity_color = match secret.severity {
-            SecretSeverity::Critical => Color::from_rgb(0.9, 0.1, 0.1),
-            SecretSeverity::High => Color::from_rgb(0.9, 0.5, 0.1),
-            SecretSeverity::Medium => Color::from_rgb(0.9, 0.9, 0.1),
-            SecretSeverity::Low => Color::from_rgb(0.1, 0.9, 0.1),
-        };
+        let severity_color = severity_color(&secret.severity);
 
         let detector_name = text(&secret.detector_name)
             .size(16)
@@ -378,16 +475,16 @@ impl SecretsNinjaApp {
 
         let validation_status = if let Some(validation) = self.validation_results.get(&secret.hash) {
             if validation.is_valid {
-                text("✅ Verified")
+                text(label("✅ Verified"))
                     .size(10)
-                    .color(Color::from_rgb(0.1, 0.9, 0.1))
+                    .color(rgb(output::style().status_color(true)))
             } else {
-                text("❌ Invalid")
+                text(label("❌ Invalid"))
                     .size(10)
-                    .color(Color::from_rgb(0.9, 0.1, 0.1))
+                    .color(rgb(output::style().status_color(false)))
             }
         } else {
-            text("🔍 Validate")
+            text(label("🔍 Validate"))
                 .size(10)
                 .color(Color::from_rgb(0.5, 0.5, 0.5))
         };
@@ -395,11 +492,11 @@ impl SecretsNinjaApp {
         let validate_button = button(validation_status)
             .on_press(Message::ValidateSecret(secret.hash.clone()));
 
-        let details_button = button(if self.expanded_details.contains(&secret.hash) {
-            "🔽 Hide Details"
+        let details_button = button(text(if self.expanded_details.contains(&secret.hash) {
+            label("🔽 Hide Details")
         } else {
-            "🔼 Show Details"
-        })
+            label("🔼 Show Details")
+        }))
         .on_press(Message::ToggleDetails(secret.hash.clone()));
 
         let main_row = row![
@@ -426,9 +523,16 @@ impl SecretsNinjaApp {
     }
 
     fn create_secret_details(&self, secret: &SecretMatch) -> Element<Message> {
-        let matched_text = text(&secret.matched_text)
+        let revealed = self.revealed_secrets.contains(&secret.hash);
+        let shown = if revealed { secret.matched_text.clone() } else { code_view::mask_secret(&secret.matched_text) };
+        let matched_text = text(shown)
+            .font(iced::Font::MONOSPACE)
             .size(12)
             .color(Color::from_rgb(0.9, 0.9, 0.9));
+        let mut reveal_button = button(text(label(if revealed { "🙈 Hide" } else { "👁 Reveal" })).size(10));
+        if self.redaction == RedactionProfile::Full {
+            reveal_button = reveal_button.on_press(Message::ToggleReveal(secret.hash.clone()));
+        }
 
         let entropy_text = text(format!("Entropy: {:.2}", secret.entropy))
             .size(10)
@@ -442,9 +546,13 @@ impl SecretsNinjaApp {
             text("")
         };
 
-        let context_text = text(&secret.context)
-            .size(10)
-            .color(Color::from_rgb(0.6, 0.6, 0.6));
+        let context_view = code_view::context_view(
+            &secret.context,
+            secret.filename.as_deref(),
+            secret.line_number,
+            &secret.matched_text,
+            revealed,
+        );
 
         let validation_details = if let Some(validation) = self.validation_results.get(&secret.hash) {
             column![
@@ -472,17 +580,203 @@ impl SecretsNinjaApp {
 
         column![
             text("Matched Text:").size(12),
-            matched_text,
+            row![matched_text, reveal_button].spacing(10).align_items(iced::Alignment::Center),
             row![entropy_text, line_text].spacing(10),
             text("Context:").size(12),
-            context_text,
-            validation_details
+            context_view,
+            validation_details,
+            self.create_notes_panel(&secret.hash)
         ]
         .spacing(5)
         .padding(10)
         .into()
     }
 
+    fn create_notes_panel(&self, hash: &str) -> Element<Message> {
+        let mut panel = column![text("Notes:").size(12)].spacing(4);
+
+        if let Some(annotations) = self.annotations.get(hash) {
+            for note in &annotations.notes {
+                panel = panel.push(
+                    column![
+                        text(format!("{} · {}", note.author, note.created_at))
+                            .size(10)
+                            .color(Color::from_rgb(0.5, 0.5, 0.9)),
+                        text(&note.body).size(11),
+                    ]
+                    .spacing(2),
+                );
+            }
+            for attachment in &annotations.attachments {
+                panel = panel.push(
+                    text(label(&format!("📎 {} ({} bytes, {})", attachment.filename, attachment.size_bytes, attachment.content_type)))
+                        .size(10)
+                        .color(Color::from_rgb(0.7, 0.7, 0.7)),
+                );
+            }
+        }
+
+        if self.read_only {
+            return panel.push(text("Read-only: notes cannot be added").size(10)).into();
+        }
+
+        let owned_hash = hash.to_string();
+        let draft = self.note_drafts.get(hash).map(String::as_str).unwrap_or("");
+        let input = text_input("Add a note (markdown)...", draft)
+            .on_input(move |text| Message::NoteDraftChanged(owned_hash.clone(), text))
+            .on_submit(Message::AddNote(hash.to_string()));
+        let add_button = button(text(label("📝 Add Note"))).on_press(Message::AddNote(hash.to_string()));
+
+        panel.push(row![input, add_button].spacing(5)).into()
+    }
+
+    /// Fetch notes and attachments for a finding from the attached database
+    fn load_annotations(&self, hash: String) -> Command<Message> {
+        let Some(path) = self.database_path.clone() else {
+            return Command::none();
+        };
+        let read_only = self.read_only;
+
+        Command::perform(
+            async move {
+                let lookup = hash.clone();
+                let annotations = tokio::task::spawn_blocking(move || {
+                    SecretDatabase::open(&path, read_only).and_then(|db| db.annotations(&lookup))
+                })
+                .await;
+                (hash, annotations)
+            },
+            |(hash, result)| {
+                let annotations = match result {
+                    Ok(Ok(annotations)) => annotations,
+                    Ok(Err(e)) => {
+                        tracing::error!("Failed to load notes for {}: {}", hash, e);
+                        FindingAnnotations::default()
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to load notes for {}: {}", hash, e);
+                        FindingAnnotations::default()
+                    }
+                };
+                Message::LoadAnnotations(hash, annotations)
+            },
+        )
+    }
+
+    /// Persist a note when a database is attached; otherwise keep it for this session only
+    fn save_note(&self, hash: String, body: String) -> Command<Message> {
+        if self.read_only {
+            return Command::perform(async move { hash }, |hash| {
+                Message::NoteSaved(hash, Err("the findings database is open read-only".to_string()))
+            });
+        }
+
+        let author = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "analyst".to_string());
+
+        let Some(path) = self.database_path.clone() else {
+            let note = FindingNote {
+                id: 0,
+                secret_hash: hash.clone(),
+                author,
+                body,
+                created_at: Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                updated_at: None,
+            };
+            return Command::perform(async move { (hash, note) }, |(hash, note)| Message::NoteSaved(hash, Ok(note)));
+        };
+
+        Command::perform(
+            async move {
+                let secret_hash = hash.clone();
+                let saved = tokio::task::spawn_blocking(move || {
+                    SecretDatabase::new(&path).and_then(|db| db.add_note(&secret_hash, &author, &body))
+                })
+                .await;
+                let saved = match saved {
+                    Ok(result) => result.map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                (hash, saved)
+            },
+            |(hash, saved)| Message::NoteSaved(hash, saved),
+        )
+    }
+
+    /// Tag counts for the facet bar, from the query cache while no tags or findings changed
+    fn load_tag_facets(&self) -> Command<Message> {
+        let Some(path) = self.database_path.clone() else {
+            return Command::none();
+        };
+        let read_only = self.read_only;
+
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    SecretDatabase::open(&path, read_only)?.cached(CacheScope::Tags, "tag_counts", |db| db.tag_counts())
+                })
+                .await
+            },
+            |result| match result {
+                Ok(Ok(facets)) => Message::LoadTagFacets(facets),
+                Ok(Err(e)) => {
+                    tracing::error!("Failed to load tags: {}", e);
+                    Message::LoadTagFacets(Vec::new())
+                }
+                Err(e) => {
+                    tracing::error!("Failed to load tags: {}", e);
+                    Message::LoadTagFacets(Vec::new())
+                }
+            },
+        )
+    }
+
+    /// Hashes of findings matching a tag, directly or through their repository
+    fn load_tagged_findings(&self, tag: String) -> Command<Message> {
+        let Some(path) = self.database_path.clone() else {
+            return Command::none();
+        };
+        let read_only = self.read_only;
+
+        Command::perform(
+            async move {
+                let filters = SecretQueryFilters {
+                    min_severity: None,
+                    category: None,
+                    detector_name: None,
+                    filename: None,
+                    verified_only: false,
+                    last_n_days: None,
+                    since: None,
+                    until: None,
+                    limit: None,
+                    tags: vec![tag.clone()],
+                    repository: None,
+                    include_suppressed: false,
+                    after: None,
+                    as_of: None,
+                };
+                let hashes = tokio::task::spawn_blocking(move || SecretDatabase::open(&path, read_only)?.query_secrets(&filters)).await;
+                (tag, hashes)
+            },
+            |(tag, result)| {
+                let hashes = match result {
+                    Ok(Ok(records)) => records.into_iter().map(|record| record.secret_hash).collect(),
+                    Ok(Err(e)) => {
+                        tracing::error!("Failed to load findings tagged {}: {}", tag, e);
+                        HashSet::new()
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to load findings tagged {}: {}", tag, e);
+                        HashSet::new()
+                    }
+                };
+                Message::LoadTaggedFindings(tag, hashes)
+            },
+        )
+    }
+
     fn apply_filters(&mut self) {
         self.filtered_secrets = self.secrets
             .iter()
@@ -501,6 +795,11 @@ impl SecretsNinjaApp {
                     }
                 }
 
+                // Tag filter
+                if self.tag_filter.is_some() && !self.tagged_hashes.contains(&secret.hash) {
+                    return false;
+                }
+
                 // Search text filter
                 if !self.search_text.is_empty() {
                     let search_lower = self.search_text.to_lowercase();
@@ -585,20 +884,46 @@ impl SecretsNinjaApp {
     }
 }
 
-// Implement Display for enum types to make them work with pick_list
-impl std::fmt::Display for SecretSeverity {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+impl SecretsNinjaApp {
+    /// Read and write notes in this findings database
+    pub fn with_database(mut self, path: impl Into<String>) -> Self {
+        self.database_path = Some(path.into());
+        self
     }
-}
 
-impl std::fmt::Display for SecretCategory {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+    /// Open the attached database read-only and disable note editing, for analysts
+    /// browsing a replica or the operational store
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
     }
 }
 
 /// Launch the Secrets Ninja GUI
+/// Revealing a secret in clear is audited like other access to raw findings
+/// A label drawn in the active output profile
+fn label(text: &str) -> String {
+    output::style().text(text).into_owned()
+}
+
+fn rgb([r, g, b]: [u8; 3]) -> Color {
+    Color::from_rgb8(r, g, b)
+}
+
+fn severity_color(severity: &SecretSeverity) -> Color {
+    rgb(output::style().severity_color(severity_rank(severity)))
+}
+
+fn record_reveal(hash: &str) {
+    let analyst = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "analyst".to_string());
+    let audit = AuditLog::from_config(&LoggingConfig::default());
+    if let Err(e) = audit.record("reveal_secret", &analyst, serde_json::json!({ "secret_hash": hash })) {
+        tracing::error!("Failed to audit secret reveal for {}: {}", hash, e);
+    }
+}
+
 pub fn launch_secrets_ninja() -> iced::Result {
     SecretsNinjaApp::run(Settings::default())
 }
@@ -615,6 +940,10 @@ pub fn load_secrets_data(secrets: Vec<SecretMatch>, validations: Vec<ValidationR
         sort_field: SortField::Severity,
         expanded_details: std::collections::HashSet::new(),
         current_chart: None,
+        database_path: None,
+        read_only: false,
+        annotations: HashMap::new(),
+        note_drafts: HashMap::new(),
         stats: SecretsStatistics::default(),
     };
 
@@ -642,6 +971,10 @@ mod tests {
             end_position: 15,
             line_number: Some(42),
             filename: Some("test.env".to_string()),
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
             entropy: 4.5,
             severity: SecretSeverity::High,
             category: SecretCategory::ApiKey,
@@ -1,25 +1,55 @@
 use anyhow::{anyhow, Result};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn, error, debug};
 use uuid::Uuid;
 
-use crate::bigquery::BigQueryScanner;
-use crate::github::DanglingCommitFetcher;
-use crate::secrets::{SecretScanner, SecretValidator, SecretMatch};
+use crate::auth::UserManager;
+use crate::bigquery::{BigQueryScanner, DanglingCommitEvent, DanglingCommitSources};
+use crate::scraper::{DownloadConfig, LocalArchiveScanner};
+use crate::canary::trip_findings;
+use crate::github::{CheckedCommitIndex, CommitCheckStatus, DanglingCommitFetcher, GitHubCredentials};
+use crate::secrets::{Allowlist, SecretScanner, SecretValidator, SecretMatch, DEFAULT_IGNORE_FILE};
 #[cfg(feature = "ai")]
 use crate::ai::{AITriageAgent, TriageResult, TriageContext};
-use crate::realtime::GitHubEventMonitor;
-use crate::performance::{PerformanceEngine, SecretDatabase};
+use crate::realtime::{ChatNotifier, CommitActivity, EventFilter, EventQueue, GitHubEventMonitor, IncidentNotifier, LeaderElector, OrgActivity};
+use crate::performance::{
+    deliver_search_alerts, spawn_disk_forecast_job, QuotaResource, WorkspaceQuota, spawn_maintenance_job, spawn_purge_job, PerformanceEngine, ScanCheckpoint, SecretDatabase,
+};
+use crate::core::{AuditLog, BigQueryConfig, DiskForecastConfig, HttpConfig, QuotaConfig, LoggingConfig, MaintenanceConfig, NotificationsConfig, RetentionConfig, SinksConfig};
+use crate::core::config::SecurityConfig;
+use crate::notifications::Notifier;
+use crate::sinks::{RedactionProfile, SinkDispatcher, TicketFiler, TicketTriage};
 #[cfg(feature = "gui")]
 use crate::gui::SecretsNinjaApp;
 
+pub mod organization;
+pub mod repository;
+pub mod repository_sync;
+pub mod scan_requests;
+pub mod status;
+pub mod synthetic;
+
+pub use organization::{list_organization_repositories, OrganizationScanOptions, RepositoryBreakdown};
+pub use repository::{RepositoryScan, RepositoryScanOptions};
+pub use repository_sync::{
+    refresh_repository, spawn_repository_sync_job, sync_repositories, sync_repositories_graphql, RepositorySyncReport,
+};
+pub use scan_requests::{ScanCallback, SCAN_CALLBACK_SECRET_ENV};
+pub use status::{collect_status, SystemStatus};
+pub use synthetic::{synthetic_finding, SyntheticRun, SYNTHETIC_TAG};
+
 /// Comprehensive GitHub secret hunting platform
 pub struct GitHubSecretHunter {
-    pub bigquery_scanner: BigQueryScanner,
+    pub bigquery_scanner: Option<Arc<BigQueryScanner>>,
+    /// Reads the historical scan from GH Archive hourly files instead of BigQuery
+    pub local_archive: Option<Arc<LocalArchiveScanner>>,
     pub commit_fetcher: DanglingCommitFetcher,
     pub secret_scanner: SecretScanner,
     pub secret_validator: SecretValidator,
@@ -28,8 +58,26 @@ pub struct GitHubSecretHunter {
     pub event_monitor: GitHubEventMonitor,
     pub performance_engine: PerformanceEngine,
     pub database: SecretDatabase,
+    pub checked_commits: CheckedCommitIndex,
+    pub sinks: SinkDispatcher,
+    /// Opens Jira tickets or GitHub issues for verified findings
+    pub tickets: TicketFiler,
+    /// Budgets of the workspace this hunter bills its usage to
+    pub quota: WorkspaceQuota,
+    /// Shared with the event monitor; also emails scan reports
+    notifier: Arc<Notifier>,
     pub config: HunterConfig,
     pub state: Arc<RwLock<HunterState>>,
+    purge_job: Option<tokio::task::JoinHandle<()>>,
+    maintenance_job: Option<tokio::task::JoinHandle<()>>,
+    disk_forecast_job: Option<tokio::task::JoinHandle<()>>,
+    activity_job: Option<tokio::task::JoinHandle<()>>,
+    repository_sync_job: Option<tokio::task::JoinHandle<()>>,
+    heartbeat_job: Option<tokio::task::JoinHandle<()>>,
+    monitor_job: Option<tokio::task::JoinHandle<()>>,
+    /// Cancelled by `stop_hunting` to stop the monitor, activity flush and heartbeat
+    shutdown: CancellationToken,
+    resume_scan_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +90,26 @@ pub struct HunterConfig {
     pub webhook_endpoints: Vec<String>,
     pub scanning_options: ScanningOptions,
     pub performance_options: PerformanceOptions,
+    #[serde(default)]
+    pub sinks: SinksConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    #[serde(default)]
+    pub disk_forecast: DiskForecastConfig,
+    #[serde(default)]
+    pub quotas: QuotaConfig,
+    /// Bytes-billed cap and pricing for the historical BigQuery scan
+    #[serde(default)]
+    pub bigquery: BigQueryConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    /// Least redaction of secrets in everything the hunter outputs: scan reports, alerts,
+    /// sinks and tickets (`SECRET_REDACTION`, default `full`). Per-output roles such as
+    /// `ALERT_REDACTION_ROLE` can only redact more.
+    #[serde(default = "RedactionProfile::from_env")]
+    pub redaction: RedactionProfile,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +122,65 @@ pub struct ScanningOptions {
     pub minimum_entropy_threshold: f64,
     pub scan_historical_events: bool,
     pub historical_days_back: u32,
+    /// Coordinate with other instances through Redis so only the leader polls
+    pub enable_leader_election: bool,
+    /// Directory (or single file) of gitleaks-style TOML/YAML detector rules merged with the built-ins
+    #[serde(default)]
+    pub rules_path: Option<PathBuf>,
+    /// `.secretsignore`-style file of suppression rules, merged with those stored in the
+    /// database. `.secretsignore` in the working directory is used when this is not set.
+    #[serde(default)]
+    pub allowlist_path: Option<PathBuf>,
+    /// Hours between repository metadata syncs; 0 disables them
+    #[serde(default = "default_repository_sync_interval")]
+    pub repository_sync_interval_hours: u64,
+    /// Poll `/orgs/{org}/events` for each monitored organization instead of the public firehose
+    #[serde(default = "default_poll_organization_endpoints")]
+    pub poll_organization_endpoints: bool,
+    /// Seconds `stop_hunting` waits for in-flight event scans before aborting them
+    #[serde(default = "default_shutdown_drain_secs")]
+    pub shutdown_drain_secs: u64,
+    /// Also mine pushes that rewrote their branch. Each candidate costs a GitHub API call to
+    /// confirm, so this is off unless `MINE_FORCE_PUSHES` is set.
+    #[serde(default = "default_mine_force_pushes")]
+    pub mine_force_pushes: bool,
+    /// Also mine deleted branches for their last pushed head
+    #[serde(default = "default_mine_branch_deletions")]
+    pub mine_branch_deletions: bool,
+    /// Run the historical scan over GH Archive hourly files kept here, downloading missing
+    /// hours, instead of querying BigQuery. Only zero-commit pushes are mined this way.
+    #[serde(default = "default_local_archive_dir")]
+    pub local_archive_dir: Option<PathBuf>,
+}
+
+/// `REPOSITORY_SYNC_INTERVAL_HOURS`, or 6
+pub fn default_repository_sync_interval() -> u64 {
+    std::env::var("REPOSITORY_SYNC_INTERVAL_HOURS").ok().and_then(|v| v.parse().ok()).unwrap_or(6)
+}
+
+/// `SHUTDOWN_DRAIN_SECS`, or 30
+pub fn default_shutdown_drain_secs() -> u64 {
+    std::env::var("SHUTDOWN_DRAIN_SECS").ok().and_then(|v| v.parse().ok()).unwrap_or(30)
+}
+
+/// `GHARCHIVE_DIR`, or none to use BigQuery
+pub fn default_local_archive_dir() -> Option<PathBuf> {
+    std::env::var("GHARCHIVE_DIR").ok().filter(|dir| !dir.is_empty()).map(PathBuf::from)
+}
+
+/// `MINE_FORCE_PUSHES`, or false
+pub fn default_mine_force_pushes() -> bool {
+    std::env::var("MINE_FORCE_PUSHES").map(|v| v == "true" || v == "1").unwrap_or(false)
+}
+
+/// `MINE_BRANCH_DELETIONS`, or true
+pub fn default_mine_branch_deletions() -> bool {
+    std::env::var("MINE_BRANCH_DELETIONS").map(|v| v == "true" || v == "1").unwrap_or(true)
+}
+
+/// `MONITOR_ORG_ENDPOINTS`, or false
+pub fn default_poll_organization_endpoints() -> bool {
+    std::env::var("MONITOR_ORG_ENDPOINTS").map(|v| v == "true" || v == "1").unwrap_or(false)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,6 +219,34 @@ pub struct ScanningReport {
     pub performance_metrics: crate::performance::ProcessingMetrics,
     pub recommendations: Vec<String>,
     pub status: ScanStatus,
+    /// One entry per repository of an organization scan
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub repositories: Vec<RepositoryBreakdown>,
+}
+
+impl ScanningReport {
+    pub(crate) fn new(scan_id: Uuid, scan_type: ScanType, target: &str) -> Self {
+        Self {
+            scan_id,
+            started_at: Utc::now(),
+            completed_at: None,
+            scan_type,
+            target: target.to_string(),
+            secrets_found: Vec::new(),
+            #[cfg(feature = "ai")]
+            triage_results: Vec::new(),
+            performance_metrics: crate::performance::ProcessingMetrics {
+                total_processed: 0,
+                cache_hit_rate: 0.0,
+                average_processing_time_ms: 0.0,
+                throughput_per_second: 0.0,
+                memory_usage_mb: 0.0,
+            },
+            recommendations: Vec::new(),
+            status: ScanStatus::Running,
+            repositories: Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -100,6 +255,7 @@ pub enum ScanType {
     RealtimeMonitoring,
     ManualRepository,
     ScheduledScan,
+    Organization,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -112,17 +268,40 @@ pub enum ScanStatus {
 
 impl GitHubSecretHunter {
     /// Create a new comprehensive secret hunter
-    pub async fn new(config: HunterConfig) -> Result<Self> {
+    pub async fn new(mut config: HunterConfig) -> Result<Self> {
         info!("Initializing GitHub Secret Hunter with config: {:?}", config);
 
-        // Initialize BigQuery scanner
-        let bigquery_scanner = BigQueryScanner::new(&config.gcp_project_id).await?;
+        // GitHub, BigQuery and AI usage is metered against the workspace budgets
+        let quota = WorkspaceQuota::new(&config.quotas, &config.database_path)?;
+
+        // The historical scan reads local GH Archive files when a directory is configured
+        let local_archive = match &config.scanning_options.local_archive_dir {
+            Some(dir) if config.scanning_options.enable_bigquery_scanning => {
+                Some(Arc::new(LocalArchiveScanner::new(dir, DownloadConfig::default())?))
+            }
+            _ => None,
+        };
+
+        // Initialize BigQuery scanner only when it will be used; it needs Google Cloud access
+        let bigquery_scanner = if config.scanning_options.enable_bigquery_scanning && local_archive.is_none() {
+            Some(Arc::new(
+                BigQueryScanner::new(&config.gcp_project_id)
+                    .await?
+                    .with_quota(quota.clone())
+                    .with_cost_limits(config.bigquery.clone()),
+            ))
+        } else {
+            None
+        };
 
         // Initialize GitHub commit fetcher
         let commit_fetcher = DanglingCommitFetcher::new(config.github_token.clone());
 
         // Initialize secret scanner
-        let secret_scanner = SecretScanner::new();
+        let mut secret_scanner = SecretScanner::new();
+        if let Some(rules) = &config.scanning_options.rules_path {
+            secret_scanner.load_detectors_from_dir(rules)?.log();
+        }
 
         // Initialize secret validator
         let secret_validator = SecretValidator::new();
@@ -143,7 +322,28 @@ impl GitHubSecretHunter {
         let ai_triage_agent = None;
 
         // Initialize real-time event monitor
-        let mut event_monitor = GitHubEventMonitor::new();
+        let notifier = Arc::new(Notifier::from_config(&config.notifications, &HttpConfig::default())?);
+        let mut event_monitor = GitHubEventMonitor::new()?
+            .with_notifier(notifier.clone())
+            .with_chat_notifiers(ChatNotifier::from_targets(&config.notifications.chat)?)
+            .with_incident_notifiers(IncidentNotifier::from_targets(&config.notifications.incidents, &HttpConfig::default())?)
+            .with_alert_log(config.database_path.clone())
+            .with_canaries(config.database_path.clone())
+            .with_event_queue(EventQueue::open(config.redis_url.as_deref(), &config.database_path)?)
+            .with_quota(quota.clone())
+            .with_redaction_floor(config.redaction);
+        event_monitor.add_webhook_targets(&config.notifications.webhooks).await?;
+        if let Some(users) = UserManager::open_for_alerts(&SecurityConfig::default())? {
+            event_monitor = event_monitor.with_user_alerts(Arc::new(users));
+        }
+        if let Some(indexer) = SinkDispatcher::alert_indexer(&config.sinks, &HttpConfig::default())? {
+            event_monitor = event_monitor.with_alert_indexer(indexer);
+        }
+        if config.scanning_options.enable_leader_election {
+            let redis_url = config.redis_url.as_deref()
+                .ok_or_else(|| anyhow!("Leader election requires a Redis URL"))?;
+            event_monitor = event_monitor.with_leader_election(LeaderElector::from_env(redis_url)?);
+        }
         #[cfg(feature = "ai")]
         if let Some(ai_agent) = &ai_triage_agent {
             // Note: This would need proper ownership handling in practice
@@ -156,6 +356,38 @@ impl GitHubSecretHunter {
         // Initialize database
         let database = SecretDatabase::new(&config.database_path)?;
 
+        // Organizations managed from the desktop app or API join those given on the command line
+        for organization in database.list_organizations()?.into_iter().filter(|org| org.enabled) {
+            let monitored = &mut config.scanning_options.organizations_to_monitor;
+            if !monitored.iter().any(|org| org.eq_ignore_ascii_case(&organization.name)) {
+                monitored.push(organization.name);
+            }
+        }
+        // With organizations to monitor, events from everywhere else are skipped
+        let organizations = &config.scanning_options.organizations_to_monitor;
+        if !organizations.is_empty() {
+            event_monitor = event_monitor
+                .with_event_filter(EventFilter::default().with_owners(organizations, &[])?)
+                .with_target_endpoints(config.scanning_options.poll_organization_endpoints);
+        }
+        let checked_commits = CheckedCommitIndex::load(&database, config.performance_options.cache_size)?;
+
+        // Suppression rules from the ignore file and the database
+        let mut allowlist = match &config.scanning_options.allowlist_path {
+            Some(path) => Allowlist::from_file(path)?,
+            None if std::path::Path::new(DEFAULT_IGNORE_FILE).is_file() => Allowlist::from_file(DEFAULT_IGNORE_FILE)?,
+            None => Allowlist::new(),
+        };
+        allowlist.merge(database.load_allowlist()?);
+        if !allowlist.is_empty() {
+            info!("Loaded {} allowlist rules", allowlist.len());
+        }
+        secret_scanner.set_allowlist(allowlist);
+
+        // Initialize external finding sinks
+        let sinks = SinkDispatcher::from_config(&config.sinks, &HttpConfig::default())?.with_redaction_floor(config.redaction);
+        let tickets = TicketFiler::from_config(&config.sinks, &HttpConfig::default())?.with_redaction_floor(config.redaction);
+
         // Initialize state
         let state = Arc::new(RwLock::new(HunterState {
             is_running: false,
@@ -171,6 +403,7 @@ impl GitHubSecretHunter {
 
         Ok(Self {
             bigquery_scanner,
+            local_archive,
             commit_fetcher,
             secret_scanner,
             secret_validator,
@@ -179,11 +412,39 @@ impl GitHubSecretHunter {
             event_monitor,
             performance_engine,
             database,
+            checked_commits,
+            sinks,
+            tickets,
+            quota,
+            notifier,
             config,
             state,
+            purge_job: None,
+            maintenance_job: None,
+            disk_forecast_job: None,
+            activity_job: None,
+            repository_sync_job: None,
+            heartbeat_job: None,
+            monitor_job: None,
+            shutdown: CancellationToken::new(),
+            resume_scan_id: None,
         })
     }
 
+    /// Continue the interrupted historical scan `scan_id` instead of starting a new one
+    /// the next time hunting starts
+    pub fn resume_scan(&mut self, scan_id: &str) -> Result<()> {
+        let checkpoint = self.database.resumable_checkpoint(scan_id)?;
+        info!(
+            "Resuming scan {}: {} of {} organizations done",
+            scan_id,
+            checkpoint.completed_organizations.len(),
+            checkpoint.organizations.len()
+        );
+        self.resume_scan_id = Some(checkpoint.scan_id);
+        Ok(())
+    }
+
     /// Start comprehensive secret hunting
     pub async fn start_hunting(&mut self) -> Result<()> {
         info!("Starting comprehensive GitHub secret hunting");
@@ -194,15 +455,85 @@ impl GitHubSecretHunter {
             state.is_running = true;
             state.started_at = Some(Utc::now());
         }
+        if self.shutdown.is_cancelled() {
+            // Started again after `stop_hunting`
+            self.shutdown = CancellationToken::new();
+        }
 
         // Start real-time monitoring if enabled
         if self.config.scanning_options.enable_realtime_monitoring {
-            let event_monitor = self.event_monitor.clone();
-            tokio::spawn(async move {
-                if let Err(e) = event_monitor.start_monitoring().await {
-                    error!("Real-time monitoring failed: {}", e);
-                }
-            });
+            if self.monitor_job.is_none() {
+                let event_monitor = self.event_monitor.clone();
+                let shutdown = self.shutdown.clone();
+                self.monitor_job = Some(tokio::spawn(async move {
+                    if let Err(e) = event_monitor.start_monitoring(shutdown).await {
+                        error!("Real-time monitoring failed: {}", e);
+                    }
+                }));
+            }
+
+            if self.activity_job.is_none() {
+                self.activity_job = Some(spawn_activity_flush(
+                    self.event_monitor.org_activity(),
+                    self.event_monitor.repository_activity(),
+                    self.event_monitor.commit_activity(),
+                    self.config.database_path.clone(),
+                    self.shutdown.clone(),
+                ));
+            }
+        }
+
+        // Keep visibility, stars, activity and risk of every repository seen up to date
+        if self.repository_sync_job.is_none() {
+            match spawn_repository_sync_job(
+                self.config.database_path.clone(),
+                self.config.github_token.clone(),
+                self.config.redis_url.clone(),
+                self.config.scanning_options.repository_sync_interval_hours,
+            ) {
+                Ok(job) => self.repository_sync_job = job,
+                Err(e) => warn!("Repository metadata sync disabled: {}", e),
+            }
+        }
+
+        // Schedule retention purges against the findings database
+        if self.purge_job.is_none() {
+            self.purge_job = spawn_purge_job(
+                self.config.database_path.clone(),
+                self.config.retention.clone(),
+                AuditLog::from_config(&LoggingConfig::default()),
+            );
+        }
+
+        // Incremental vacuum and ANALYZE, deferred while a scan or event batch is in progress
+        if self.maintenance_job.is_none() {
+            self.maintenance_job = spawn_maintenance_job(self.config.database_path.clone(), self.config.maintenance.clone());
+        }
+
+        // Warn before the database and caches fill the disk of an unattended hunt
+        if self.disk_forecast_job.is_none() {
+            self.disk_forecast_job = spawn_disk_forecast_job(
+                self.config.database_path.clone(),
+                self.config.disk_forecast.clone(),
+                self.config.retention.clone(),
+                self.config.maintenance.clone(),
+                AuditLog::from_config(&LoggingConfig::default()),
+            );
+        }
+
+        // Let `status` report on this hunter from another process
+        if self.heartbeat_job.is_none() {
+            let monitor = self
+                .config
+                .scanning_options
+                .enable_realtime_monitoring
+                .then(|| self.event_monitor.clone());
+            self.heartbeat_job = Some(spawn_heartbeat(
+                self.state.clone(),
+                monitor,
+                self.config.database_path.clone(),
+                self.shutdown.clone(),
+            ));
         }
 
         // Run historical BigQuery scan if enabled
@@ -214,39 +545,34 @@ impl GitHubSecretHunter {
         Ok(())
     }
 
-    /// Run BigQuery historical scan
+    /// Run BigQuery historical scan. Progress is checkpointed after every event batch,
+    /// so an interrupted scan can be continued with `hunt --resume <scan_id>`.
     async fn run_bigquery_scan(&mut self) -> Result<ScanningReport> {
-        let scan_id = Uuid::new_v4();
-        info!("Starting BigQuery historical scan with ID: {}", scan_id);
-
-        let mut report = ScanningReport {
-            scan_id,
-            started_at: Utc::now(),
-            completed_at: None,
-            scan_type: ScanType::BigQueryHistorical,
-            target: "GitHub Archive".to_string(),
-            secrets_found: Vec::new(),
-            #[cfg(feature = "ai")]
-            triage_results: Vec::new(),
-            performance_metrics: crate::performance::ProcessingMetrics {
-                total_processed: 0,
-                cache_hit_rate: 0.0,
-                average_processing_time_ms: 0.0,
-                throughput_per_second: 0.0,
-                memory_usage_mb: 0.0,
-            },
-            recommendations: Vec::new(),
-            status: ScanStatus::Running,
+        let mut checkpoint = match self.resume_scan_id.take() {
+            Some(scan_id) => self.database.resumable_checkpoint(&scan_id)?,
+            None => ScanCheckpoint::new(
+                &Uuid::new_v4().to_string(),
+                self.config.scanning_options.organizations_to_monitor.clone(),
+            ),
         };
+        let scan_id = Uuid::parse_str(&checkpoint.scan_id)?;
+        self.database.save_checkpoint(&checkpoint)?;
+        info!(
+            "Starting BigQuery historical scan with ID: {} (resume with `hunt --resume {}`)",
+            scan_id, scan_id
+        );
+
+        let mut report = ScanningReport::new(scan_id, ScanType::BigQueryHistorical, "GitHub Archive");
 
-        // Scan each organization
-        for org in &self.config.scanning_options.organizations_to_monitor {
+        // Scan each organization not finished by an earlier run of this scan
+        for org in checkpoint.remaining_organizations() {
             info!("Scanning organization: {}", org);
 
-            match self.scan_organization_historical(org).await {
-                Ok(mut org_secrets) => {
-                    info!("Found {} secrets for organization: {}", org_secrets.len(), org);
-                    report.secrets_found.append(&mut org_secrets);
+            match self.scan_organization_historical(&org, &mut checkpoint, &mut report).await {
+                Ok(found) => {
+                    info!("Found {} secrets for organization: {}", found, org);
+                    checkpoint.finish_organization(&org);
+                    self.database.save_checkpoint(&checkpoint)?;
                 }
                 Err(e) => {
                     error!("Failed to scan organization {}: {}", org, e);
@@ -254,36 +580,6 @@ impl GitHubSecretHunter {
             }
         }
 
-        // Run AI triage on found secrets
-        #[cfg(feature = "ai")]
-        if self.config.scanning_options.enable_ai_triage && !report.secrets_found.is_empty() {
-            info!("Running AI triage on {} secrets", report.secrets_found.len());
-            
-            if let Some(ai_agent) = &mut self.ai_triage_agent {
-                for secret in &report.secrets_found {
-                    let context = TriageContext {
-                        repository_name: secret.filename.clone().unwrap_or_default(),
-                        organization: None,
-                        is_public_repository: true,
-                        recent_activity: true,
-                        contributor_count: None,
-                        star_count: None,
-                    };
-
-                    match ai_agent.triage_secret(secret, None, &context).await {
-                        #[cfg(feature = "ai")]
-                        Ok(triage) => report.triage_results.push(triage),
-                        Err(e) => warn!("AI triage failed for secret {}: {}", secret.hash, e),
-                    }
-                }
-            }
-        }
-
-        // Store secrets in database
-        if !report.secrets_found.is_empty() {
-            self.database.bulk_insert_secrets(&report.secrets_found)?;
-        }
-
         // Update state
         {
             let mut state = self.state.write().await;
@@ -291,34 +587,116 @@ impl GitHubSecretHunter {
             state.total_secrets_found += report.secrets_found.len() as u64;
         }
 
+        // Organizations that failed stay open so a resume retries them
+        checkpoint.completed = checkpoint.remaining_organizations().is_empty();
+        self.database.save_checkpoint(&checkpoint)?;
+
         report.completed_at = Some(Utc::now());
-        report.status = ScanStatus::Completed;
+        report.status = if checkpoint.completed { ScanStatus::Completed } else { ScanStatus::Failed };
+        self.email_scan_report(&report).await;
 
         info!("BigQuery scan completed. Found {} secrets", report.secrets_found.len());
         Ok(report)
     }
 
-    /// Scan a specific organization's historical data
-    async fn scan_organization_historical(&mut self, organization: &str) -> Result<Vec<SecretMatch>> {
-        let mut all_secrets = Vec::new();
+    /// Scan a specific organization's historical data, resuming after the checkpoint's
+    /// last processed event. Returns the number of secrets found.
+    async fn scan_organization_historical(
+        &mut self,
+        organization: &str,
+        checkpoint: &mut ScanCheckpoint,
+        report: &mut ScanningReport,
+    ) -> Result<usize> {
+        let mut found = 0;
 
-        // Get zero-commit events from BigQuery
-        let events = self.bigquery_scanner.scan_zero_commit_events(
-            Some(organization),
-            self.config.scanning_options.historical_days_back,
-        ).await?;
+        // Get zero-commit events from local GH Archive files or BigQuery
+        let local_archive = self.local_archive.clone();
+        let bigquery_scanner = self.bigquery_scanner.clone();
+        let days_back = self.database.list_organizations()?
+            .into_iter()
+            .find(|org| org.name.eq_ignore_ascii_case(organization))
+            .and_then(|org| org.historical_days_back)
+            .unwrap_or(self.config.scanning_options.historical_days_back);
+        // A resumed organization keeps its original range so the event order is unchanged
+        let today = Utc::now().date_naive();
+        let (start_date, end_date) =
+            checkpoint.begin_organization(organization, (today - Duration::days(days_back as i64), today));
+        // Events are fetched a page at a time as the batches below consume them
+        let mut events: BoxStream<'_, Result<DanglingCommitEvent>> = match (&local_archive, &bigquery_scanner) {
+            (Some(archive), _) => archive
+                .stream_organization_zero_commits(organization, start_date, end_date, None)
+                .map_ok(DanglingCommitEvent::ZeroCommit)
+                .boxed(),
+            (None, Some(bigquery)) => {
+                let sources = DanglingCommitSources {
+                    force_pushes: self.config.scanning_options.mine_force_pushes,
+                    branch_deletions: self.config.scanning_options.mine_branch_deletions,
+                };
+                bigquery.stream_organization_dangling_commits(organization, start_date, end_date, sources).boxed()
+            }
+            (None, None) => return Err(anyhow!("BigQuery scanning is not enabled")),
+        };
 
-        info!("Found {} zero-commit events for {}", events.len(), organization);
+        // On resume, the events up to the last processed one are held until it turns up, in
+        // case the result set changed and it never does
+        let mut held = Vec::new();
+        if let Some(last) = checkpoint.last_event_id.clone() {
+            while let Some(event) = events.try_next().await? {
+                let is_last = event.id() == last;
+                held.push(event);
+                if is_last {
+                    break;
+                }
+            }
+        }
+        let resume_at = checkpoint.resume_position(held.iter().map(|e| e.id())).min(held.len());
+        if resume_at > 0 {
+            info!("Skipping {} events of {} already processed before the interruption", resume_at, organization);
+        }
+        let events = stream::iter(held.split_off(resume_at).into_iter().map(Ok)).chain(events);
 
         // Process events in batches for performance
         let batch_size = self.config.performance_options.batch_size;
-        for batch in events.chunks(batch_size) {
+        let mut batches = Box::pin(events.try_chunks(batch_size.max(1)));
+        let mut scanned = 0;
+        while let Some(batch) = batches.try_next().await.map_err(|e| e.1)? {
             let mut batch_secrets = Vec::new();
+            scanned += batch.len();
+
+            for event in &batch {
+                let (repository, commit) = (event.repo_name(), event.commit());
+
+                // Skip commits already checked by a previous or duplicate event
+                if let Some(status) = self.checked_commits.lookup(&self.database, repository, commit)? {
+                    debug!("Skipping commit {} (already {})", commit, status.as_str());
+                    continue;
+                }
+
+                // Out of API budget the scan stops here; the checkpoint lets it resume tomorrow
+                self.quota.consume(QuotaResource::GithubApiCalls, 1).await?;
+
+                // A push is only forced when its first commit does not build on `before`
+                if let DanglingCommitEvent::ForcePush(push) = event {
+                    match self.commit_fetcher.fetch_commit(repository, &push.first_commit).await {
+                        Ok(Some(first)) if push.is_forced(&first.parents) => {
+                            debug!("Push {} to {} rewrote {}", push.id, repository, push.ref_name);
+                            self.quota.consume(QuotaResource::GithubApiCalls, 1).await?;
+                        }
+                        Ok(_) => continue,
+                        Err(e) => {
+                            debug!("Could not confirm force push {}: {}", push.id, e);
+                            continue;
+                        }
+                    }
+                }
 
-            for event in batch {
                 // Try to fetch the dangling commit
-                match self.commit_fetcher.fetch_commit(&event.repository, &event.before_commit).await {
-                    Ok(commit_data) => {
+                match self.commit_fetcher.fetch_commit(repository, commit).await {
+                    Ok(None) => {
+                        self.checked_commits.record(&self.database, repository, commit, CommitCheckStatus::Dangling)?;
+                    }
+                    Ok(Some(commit_data)) => {
+                        self.checked_commits.record(&self.database, repository, commit, CommitCheckStatus::Fetched)?;
                         // Scan commit for secrets
                         match self.secret_scanner.scan_text(&commit_data).await {
                             Ok(mut secrets) => {
@@ -326,61 +704,221 @@ impl GitHubSecretHunter {
                                 secrets.retain(|s| s.entropy >= self.config.scanning_options.minimum_entropy_threshold);
                                 batch_secrets.extend(secrets);
                             }
-                            Err(e) => warn!("Failed to scan commit {}: {}", event.before_commit, e),
+                            Err(e) => warn!("Failed to scan commit {}: {}", commit, e),
                         }
                     }
                     Err(e) => {
-                        debug!("Could not fetch commit {} (likely dangling): {}", event.before_commit, e);
+                        debug!("Could not fetch commit {} (likely dangling): {}", commit, e);
                     }
                 }
             }
 
-            all_secrets.extend(batch_secrets);
+            // Findings are fully handled before the checkpoint moves past their batch
+            found += batch_secrets.len();
+            checkpoint.findings += batch_secrets.len() as u64;
+            self.process_scan_findings(batch_secrets, report).await?;
+
+            checkpoint.events_processed += batch.len() as u64;
+            checkpoint.last_event_id = batch.last().map(|e| e.id().to_string());
+            self.database.save_checkpoint(checkpoint)?;
         }
 
-        Ok(all_secrets)
+        info!("Scanned {} BigQuery events for {}", scanned, organization);
+        Ok(found)
     }
 
-    /// Scan a specific repository manually
-    pub async fn scan_repository(&mut self, repository: &str) -> Result<ScanningReport> {
+    /// Triage, store and publish the findings of one scan batch
+    async fn process_scan_findings(&mut self, secrets: Vec<SecretMatch>, report: &mut ScanningReport) -> Result<()> {
+        if secrets.is_empty() {
+            return Ok(());
+        }
+
+        // Canaries are alerted on even when the findings quota refuses the batch
+        if let Some(alert) = trip_findings(&self.database, &report.target, &secrets)? {
+            error!("🐤 CANARY TRIPPED: {} canary credentials seen in {}", alert.secrets_found.len(), report.target);
+            if let Err(e) = self.event_monitor.send_alert(alert).await {
+                error!("Failed to send canary alert for {}: {}", report.target, e);
+            }
+        }
+
+        // Refuse the whole batch rather than store part of it; nothing is triaged or sent either
+        let findings_limit = self.quota.limit(QuotaResource::FindingsStored);
+        if findings_limit > 0 {
+            let stored = self.database.quota_used(self.quota.workspace(), QuotaResource::FindingsStored)?;
+            if stored + self.database.new_findings(&secrets)? as u64 > findings_limit {
+                return Err(self.quota.exceeded(QuotaResource::FindingsStored));
+            }
+        }
+
+        // Run AI triage on found secrets
+        #[cfg(feature = "ai")]
+        if self.config.scanning_options.enable_ai_triage {
+            if let Some(ai_agent) = &mut self.ai_triage_agent {
+                // Repository scans know their repository; enrich with its synced metadata
+                let mut repository = if report.target.contains('/') {
+                    self.database.repository(&report.target).unwrap_or_else(|e| {
+                        warn!("Repository metadata unavailable for {}: {}", report.target, e);
+                        None
+                    })
+                } else {
+                    None
+                };
+                // Syncs do not count contributors; fetch them once and keep them with the rest
+                let missing_contributors = repository.as_ref().map_or(true, |r| r.contributor_count.is_none());
+                if report.target.contains('/') && missing_contributors && !self.config.github_credentials().is_anonymous() {
+                    match refresh_repository(&self.database, &self.config.github_token, &report.target).await {
+                        Ok(record) => repository = Some(record),
+                        Err(e) => debug!("Could not fetch repository metadata for {}: {}", report.target, e),
+                    }
+                }
+                for secret in secrets.iter().filter(|s| !s.suppressed) {
+                    if self.quota.remaining(QuotaResource::AiTokens).await? == Some(0) {
+                        warn!("{}; the remaining findings are stored untriaged", self.quota.exceeded(QuotaResource::AiTokens));
+                        break;
+                    }
+                    let context = match &repository {
+                        Some(repository) => TriageContext {
+                            repository_name: repository.name.clone(),
+                            organization: repository.organization.clone(),
+                            is_public_repository: repository.is_public(),
+                            recent_activity: repository.recently_active(Utc::now()),
+                            contributor_count: repository.contributor_count.map(|count| count.max(0) as usize),
+                            star_count: repository.star_count.map(|stars| stars.max(0) as usize),
+                        },
+                        None => TriageContext {
+                            repository_name: secret.filename.clone().unwrap_or_default(),
+                            organization: None,
+                            is_public_repository: true,
+                            recent_activity: true,
+                            contributor_count: None,
+                            star_count: None,
+                        },
+                    };
+
+                    match ai_agent.triage_secret(secret, None, &context).await {
+                        Ok(triage) => {
+                            self.quota.record(QuotaResource::AiTokens, triage.tokens_used).await?;
+                            report.triage_results.push(triage);
+                        }
+                        Err(e) => warn!("AI triage failed for secret {}: {}", secret.hash, e),
+                    }
+                }
+            }
+        }
+
+        // Store secrets in database
+        self.database.bulk_insert_secrets(&secrets)?;
+        if let Err(e) = deliver_search_alerts(&self.database, &self.config.webhook_endpoints).await {
+            warn!("Saved search alerts not delivered: {}", e);
+        }
+
+        // Suppressed findings are stored, flagged, but not sent anywhere
+        let active: Vec<SecretMatch> = secrets.iter().filter(|s| !s.suppressed).cloned().collect();
+        if !self.sinks.is_empty() && !active.is_empty() {
+            let delivery = self.sinks.publish(&active).await;
+            if delivery.failed_batches > 0 {
+                warn!("{} sink batches failed to deliver", delivery.failed_batches);
+            }
+        }
+        if !self.tickets.is_empty() && !active.is_empty() {
+            #[cfg(feature = "ai")]
+            let triage: Vec<TicketTriage> = report.triage_results.iter().map(TicketTriage::from).collect();
+            #[cfg(not(feature = "ai"))]
+            let triage: Vec<TicketTriage> = Vec::new();
+            let filed = self.tickets.file(&self.database, &active, &triage).await;
+            if !filed.errors.is_empty() {
+                warn!("{} tickets not opened: {}", filed.errors.len(), filed.errors.join("; "));
+            }
+        }
+
+        // Reports are printed and serialized; they carry secrets only as far as the policy allows
+        report.secrets_found.extend(self.config.redaction.redact_matches(&secrets));
+        Ok(())
+    }
+
+    /// Clone a repository and scan every blob in its history; see `RepositoryScanOptions`
+    pub async fn scan_repository(&mut self, repository: &str, options: &RepositoryScanOptions) -> Result<ScanningReport> {
         let scan_id = Uuid::new_v4();
         info!("Starting manual repository scan: {} (ID: {})", repository, scan_id);
 
-        let mut report = ScanningReport {
-            scan_id,
-            started_at: Utc::now(),
-            completed_at: None,
-            scan_type: ScanType::ManualRepository,
-            target: repository.to_string(),
-            secrets_found: Vec::new(),
-            #[cfg(feature = "ai")]
-            triage_results: Vec::new(),
-            performance_metrics: crate::performance::ProcessingMetrics {
-                total_processed: 0,
-                cache_hit_rate: 0.0,
-                average_processing_time_ms: 0.0,
-                throughput_per_second: 0.0,
-                memory_usage_mb: 0.0,
-            },
-            recommendations: Vec::new(),
-            status: ScanStatus::Running,
-        };
+        let mut report = ScanningReport::new(scan_id, ScanType::ManualRepository, repository);
+
+        let target = repository.to_string();
+        // Resolved now: an App installation token is only valid for an hour
+        let token = self.config.github_credentials().token().await?.unwrap_or_default();
+        let scanner = self.secret_scanner.clone();
+        let scan_options = options.clone();
+        let scan = tokio::task::spawn_blocking(move || {
+            repository::clone_and_scan(&target, &token, &scanner, &scan_options)
+        })
+        .await??;
+
+        info!(
+            "Scanned {} commits ({} unreachable), {} blobs ({} skipped) in {}: {} findings",
+            scan.commits_scanned,
+            scan.unreachable_commits,
+            scan.blobs_scanned,
+            scan.blobs_skipped,
+            repository,
+            scan.findings.len()
+        );
+
+        let elapsed = (Utc::now() - report.started_at).num_milliseconds().max(1) as f64;
+        report.performance_metrics.total_processed = scan.blobs_scanned;
+        report.performance_metrics.average_processing_time_ms = elapsed / scan.blobs_scanned.max(1) as f64;
+        report.performance_metrics.throughput_per_second = scan.blobs_scanned as f64 * 1000.0 / elapsed;
+        if options.depth.is_some() && scan.findings.is_empty() {
+            report.recommendations.push("Shallow scan found nothing; rerun without --depth to cover the full history".to_string());
+        }
+
+        self.process_scan_findings(scan.findings, &mut report).await?;
 
-        // Implementation would scan the specific repository
-        // For now, return empty results
-        
         report.completed_at = Some(Utc::now());
         report.status = ScanStatus::Completed;
+        self.email_scan_report(&report).await;
 
         Ok(report)
     }
 
+    /// Email the report of a finished scan when `email_scan_reports` is on. A failed
+    /// delivery is logged; the scan itself still succeeded.
+    async fn email_scan_report(&self, report: &ScanningReport) {
+        if !self.notifier.scan_reports_enabled() {
+            return;
+        }
+        if let Err(e) = self.notifier.send_scan_report(report).await {
+            error!("Failed to email the report of scan {}: {}", report.scan_id, e);
+        }
+    }
+
     /// Get current hunting status
     pub async fn get_status(&self) -> HunterState {
         self.state.read().await.clone()
     }
 
-    /// Stop hunting operations
+    /// Apply reloaded sink and retention settings without restarting the hunt
+    pub fn reload_config(&mut self, sinks: SinksConfig, retention: RetentionConfig) -> Result<()> {
+        self.sinks = SinkDispatcher::from_config(&sinks, &HttpConfig::default())?.with_redaction_floor(self.config.redaction);
+        self.tickets = TicketFiler::from_config(&sinks, &HttpConfig::default())?.with_redaction_floor(self.config.redaction);
+        self.config.sinks = sinks;
+        self.config.retention = retention;
+
+        if let Some(job) = self.purge_job.take() {
+            job.abort();
+            self.purge_job = spawn_purge_job(
+                self.config.database_path.clone(),
+                self.config.retention.clone(),
+                AuditLog::from_config(&LoggingConfig::default()),
+            );
+        }
+
+        info!("Hunter configuration reloaded");
+        Ok(())
+    }
+
+    /// Stop hunting operations. The monitor finishes scanning the events it has already
+    /// fetched, for up to `shutdown_drain_secs`, and is aborted after that; then buffered
+    /// activity and the final heartbeat are written to the database.
     pub async fn stop_hunting(&mut self) -> Result<()> {
         info!("Stopping GitHub Secret Hunter");
 
@@ -390,8 +928,54 @@ impl GitHubSecretHunter {
             state.is_running = false;
         }
 
-        // Stop real-time monitoring
-        // Implementation would stop the monitoring task
+        self.shutdown.cancel();
+        let drain = std::time::Duration::from_secs(self.config.scanning_options.shutdown_drain_secs);
+        let deadline = tokio::time::Instant::now() + drain;
+        let jobs = [
+            ("Real-time monitor", self.monitor_job.take()),
+            ("Activity flush", self.activity_job.take()),
+            ("Heartbeat", self.heartbeat_job.take()),
+        ];
+        for (name, job) in jobs {
+            let Some(mut job) = job else {
+                continue;
+            };
+            if tokio::time::timeout_at(deadline, &mut job).await.is_err() {
+                warn!("{} did not stop within {}s, aborting it", name, drain.as_secs());
+                job.abort();
+            }
+        }
+
+        if let Some(job) = self.purge_job.take() {
+            job.abort();
+        }
+        if let Some(job) = self.maintenance_job.take() {
+            job.abort();
+        }
+        if let Some(job) = self.disk_forecast_job.take() {
+            job.abort();
+        }
+        if let Some(job) = self.repository_sync_job.take() {
+            job.abort();
+        }
+
+        // Whatever the monitor recorded since the last flush
+        if self.config.scanning_options.enable_realtime_monitoring {
+            if let Err(e) = flush_activity(
+                &self.event_monitor.org_activity(),
+                &self.event_monitor.repository_activity(),
+                &self.event_monitor.commit_activity(),
+                self.config.database_path.clone(),
+            )
+            .await
+            {
+                warn!("Failed to record organization activity: {}", e);
+            }
+        }
+        let state = serde_json::to_value(&*self.state.read().await)?;
+        if let Err(e) = self.database.record_heartbeat("hunter", &state) {
+            warn!("Failed to record final hunter heartbeat: {}", e);
+        }
 
         info!("GitHub Secret Hunter stopped");
         Ok(())
@@ -404,10 +988,19 @@ impl GitHubSecretHunter {
         // Query recent secrets from database
         let filters = crate::performance::SecretQueryFilters {
             min_severity: Some(crate::secrets::SecretSeverity::Medium),
+            category: None,
             detector_name: None,
+            filename: None,
             verified_only: false,
             last_n_days: Some(7),
+            since: None,
+            until: None,
             limit: Some(100),
+            tags: Vec::new(),
+            repository: None,
+            include_suppressed: false,
+            after: None,
+            as_of: None,
         };
         
         let recent_secrets = self.database.query_secrets(&filters)?;
@@ -435,6 +1028,104 @@ impl GitHubSecretHunter {
     }
 }
 
+/// Copy real-time activity per organization and repository into the database once a
+/// minute, so the monitoring screen can show when each organization was last heard from,
+/// the repository sync knows which repositories to fetch and scorecards can count commits
+fn spawn_activity_flush(
+    activity: OrgActivity,
+    repository_activity: OrgActivity,
+    commit_activity: CommitActivity,
+    database_path: String,
+    shutdown: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            tokio::select! {
+                // `stop_hunting` flushes once more after the monitor has stopped
+                _ = shutdown.cancelled() => return,
+                _ = ticker.tick() => {}
+            }
+
+            if let Err(e) = flush_activity(&activity, &repository_activity, &commit_activity, database_path.clone()).await {
+                warn!("Failed to record organization activity: {}", e);
+            }
+        }
+    })
+}
+
+/// Move the activity buffered by the monitor into the database
+async fn flush_activity(
+    activity: &OrgActivity,
+    repository_activity: &OrgActivity,
+    commit_activity: &CommitActivity,
+    database_path: String,
+) -> Result<()> {
+    let seen = std::mem::take(&mut *activity.write().await);
+    let repositories = std::mem::take(&mut *repository_activity.write().await);
+    let commits = std::mem::take(&mut *commit_activity.write().await);
+    if seen.is_empty() && repositories.is_empty() && commits.is_empty() {
+        return Ok(());
+    }
+    tokio::task::spawn_blocking(move || {
+        let database = SecretDatabase::new(&database_path)?;
+        database.record_organization_activity(&seen)?;
+        database.record_repository_activity(&repositories)?;
+        database.record_commit_activity(&commits)
+    })
+    .await
+    .map_err(|e| anyhow!("Organization activity task panicked: {}", e))?
+}
+
+/// Write the hunter state (and the monitor's health when it runs) to the database every
+/// 30 seconds for `status`
+fn spawn_heartbeat(
+    state: Arc<RwLock<HunterState>>,
+    monitor: Option<GitHubEventMonitor>,
+    database_path: String,
+    shutdown: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(30));
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = ticker.tick() => {}
+            }
+
+            let mut beats = vec![("hunter", serde_json::to_value(&*state.read().await).unwrap_or_default())];
+            if let Some(monitor) = &monitor {
+                let (status, health) = monitor.health_report().await;
+                beats.push((
+                    "monitor",
+                    serde_json::json!({
+                        "status": status,
+                        "paused": monitor.is_paused(),
+                        "health": health,
+                        "rate_limit": monitor.rate_limit().await,
+                        "queue": monitor.queue_depth().await.ok(),
+                    }),
+                ));
+            }
+
+            let path = database_path.clone();
+            let result = tokio::task::spawn_blocking(move || -> Result<()> {
+                let database = SecretDatabase::new(&path)?;
+                for (component, snapshot) in &beats {
+                    database.record_heartbeat(component, snapshot)?;
+                }
+                Ok(())
+            })
+            .await;
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => warn!("Failed to record hunter heartbeat: {}", e),
+                Err(e) => warn!("Hunter heartbeat task panicked: {}", e),
+            }
+        }
+    })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DashboardData {
     pub state: HunterState,
@@ -463,6 +1154,15 @@ impl Default for HunterConfig {
                 minimum_entropy_threshold: 3.0,
                 scan_historical_events: true,
                 historical_days_back: 30,
+                enable_leader_election: false,
+                rules_path: None,
+                allowlist_path: None,
+                repository_sync_interval_hours: default_repository_sync_interval(),
+                poll_organization_endpoints: default_poll_organization_endpoints(),
+                shutdown_drain_secs: default_shutdown_drain_secs(),
+                mine_force_pushes: default_mine_force_pushes(),
+                mine_branch_deletions: default_mine_branch_deletions(),
+                local_archive_dir: default_local_archive_dir(),
             },
             performance_options: PerformanceOptions {
                 parallel_workers: num_cpus::get(),
@@ -472,10 +1172,25 @@ impl Default for HunterConfig {
                 enable_caching: true,
                 enable_deduplication: true,
             },
+            sinks: SinksConfig::default(),
+            retention: RetentionConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+            disk_forecast: DiskForecastConfig::default(),
+            quotas: QuotaConfig::default(),
+            bigquery: BigQueryConfig::default(),
+            notifications: NotificationsConfig::default(),
+            redaction: RedactionProfile::from_env(),
         }
     }
 }
 
+impl HunterConfig {
+    /// The configured GitHub App, or `github_token`
+    pub fn github_credentials(&self) -> GitHubCredentials {
+        GitHubCredentials::from(self.github_token.clone())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
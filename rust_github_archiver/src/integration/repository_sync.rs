@@ -0,0 +1,210 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Duration};
+use tracing::{error, info, warn};
+
+use crate::github::etag_cache::RateLimited;
+use crate::github::{CachedGitHubClient, EtagCache, GitHubCredentials, GitHubGraphQLClient};
+use crate::github::graphql::GRAPHQL_BATCH;
+use crate::performance::{RepositoryMetadata, RepositoryRecord, SecretDatabase, UNAVAILABLE};
+
+/// Repositories fetched per run. Unchanged ones are answered with `304 Not Modified`,
+/// which does not count against the rate limit.
+pub const SYNC_BATCH: u32 = 500;
+
+const ETAG_CACHE_CAPACITY: usize = 10_000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepositorySyncReport {
+    pub synced: usize,
+    /// GitHub answered 404: deleted, renamed or no longer visible to the token
+    pub unavailable: usize,
+    pub failed: usize,
+    /// The run stopped early; the rest is picked up by the next one
+    pub rate_limited: bool,
+}
+
+/// Fetch metadata for up to `limit` repositories not synced in the last `stale_hours`
+/// and upsert it, with a fresh risk score, into the repositories table
+pub async fn sync_repositories(
+    database_path: &str,
+    client: &CachedGitHubClient,
+    stale_hours: u32,
+    limit: u32,
+) -> Result<RepositorySyncReport> {
+    let due = repositories_due(database_path, stale_hours, limit).await?;
+
+    let mut report = RepositorySyncReport::default();
+    let mut fetched = Vec::with_capacity(due.len());
+    for name in due {
+        match client.get_repository(&name).await {
+            Ok(Some(repository)) => fetched.push(RepositoryMetadata::from_github(&name, &repository)),
+            Ok(None) => {
+                report.unavailable += 1;
+                fetched.push(RepositoryMetadata::unavailable(&name));
+            }
+            Err(e) if e.is::<RateLimited>() => {
+                warn!("Repository sync rate limited after {} repositories", fetched.len());
+                report.rate_limited = true;
+                break;
+            }
+            Err(e) => {
+                warn!("Failed to fetch metadata for {}: {}", name, e);
+                report.failed += 1;
+            }
+        }
+    }
+
+    report.synced = save_metadata(database_path, fetched).await?;
+    Ok(report)
+}
+
+/// `sync_repositories` over GraphQL: 50 repositories per request, and forks, which the REST
+/// sync leaves empty. Needs a token.
+pub async fn sync_repositories_graphql(
+    database_path: &str,
+    client: &GitHubGraphQLClient,
+    stale_hours: u32,
+    limit: u32,
+) -> Result<RepositorySyncReport> {
+    let due = repositories_due(database_path, stale_hours, limit).await?;
+
+    let mut report = RepositorySyncReport::default();
+    let mut fetched = Vec::with_capacity(due.len());
+    for batch in due.chunks(GRAPHQL_BATCH) {
+        match client.repositories(batch).await {
+            Ok(metadata) => {
+                report.unavailable += metadata.iter().filter(|m| m.visibility.as_deref() == Some(UNAVAILABLE)).count();
+                report.failed += batch.len() - metadata.len();
+                fetched.extend(metadata);
+            }
+            Err(e) if e.is::<RateLimited>() => {
+                warn!("Repository sync rate limited after {} repositories", fetched.len());
+                report.rate_limited = true;
+                break;
+            }
+            Err(e) => {
+                warn!("Failed to fetch metadata for {} repositories: {}", batch.len(), e);
+                report.failed += batch.len();
+            }
+        }
+    }
+
+    report.synced = save_metadata(database_path, fetched).await?;
+    Ok(report)
+}
+
+async fn repositories_due(database_path: &str, stale_hours: u32, limit: u32) -> Result<Vec<String>> {
+    let path = database_path.to_string();
+    tokio::task::spawn_blocking(move || SecretDatabase::new(&path)?.repositories_due_for_sync(stale_hours, limit)).await?
+}
+
+async fn save_metadata(database_path: &str, fetched: Vec<RepositoryMetadata>) -> Result<usize> {
+    let path = database_path.to_string();
+    tokio::task::spawn_blocking(move || -> Result<usize> {
+        let database = SecretDatabase::new(&path)?;
+        for metadata in &fetched {
+            database.save_repository_metadata(metadata)?;
+        }
+        Ok(fetched.len())
+    })
+    .await?
+}
+
+/// Metadata of one repository over GraphQL, saved before it is returned
+pub async fn refresh_repository(database: &SecretDatabase, github_token: &str, name: &str) -> Result<RepositoryRecord> {
+    let client = GitHubGraphQLClient::new(github_token.to_string())?;
+    let mut metadata = client
+        .repositories(&[name.to_string()])
+        .await?
+        .pop()
+        .ok_or_else(|| anyhow!("Invalid repository name: {}", name))?;
+    if metadata.visibility.as_deref() != Some(UNAVAILABLE) {
+        let rest = CachedGitHubClient::new(github_token.to_string(), Arc::new(EtagCache::new(1, None)?))?;
+        metadata.contributor_count = rest.contributor_count(&metadata.name).await?;
+    }
+    database.save_repository_metadata(&metadata)
+}
+
+/// Spawn the periodic repository metadata sync, over GraphQL when there is a token.
+/// Returns `None` when the interval is zero.
+pub fn spawn_repository_sync_job(
+    database_path: String,
+    github_token: String,
+    redis_url: Option<String>,
+    interval_hours: u64,
+) -> Result<Option<JoinHandle<()>>> {
+    if interval_hours == 0 {
+        return Ok(None);
+    }
+    let credentials = GitHubCredentials::from(github_token);
+    let graphql = if credentials.is_anonymous() { None } else { Some(GitHubGraphQLClient::new(credentials.clone())?) };
+    // Redis keeps the ETags across restarts, so a restarted hunter still gets 304s
+    let cache = Arc::new(EtagCache::new(ETAG_CACHE_CAPACITY, redis_url.as_deref())?);
+    let client = CachedGitHubClient::new(credentials, cache)?;
+    let stale_hours = u32::try_from(interval_hours).unwrap_or(u32::MAX);
+
+    Ok(Some(tokio::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(interval_hours * 3600));
+        loop {
+            ticker.tick().await;
+
+            let synced = match &graphql {
+                Some(graphql) => sync_repositories_graphql(&database_path, graphql, stale_hours, SYNC_BATCH).await,
+                None => sync_repositories(&database_path, &client, stale_hours, SYNC_BATCH).await,
+            };
+            match synced {
+                Ok(report) => info!(
+                    "Repository sync updated {} repositories ({} unavailable, {} failed)",
+                    report.synced, report.unavailable, report.failed
+                ),
+                Err(e) => error!("Repository sync failed: {}", e),
+            }
+        }
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_due_repositories_are_saved_and_not_due_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.db").to_string_lossy().to_string();
+        let seen = HashMap::from([
+            ("acme/api".to_string(), Utc::now()),
+            ("acme/gone".to_string(), Utc::now()),
+            ("not-a-repository".to_string(), Utc::now()),
+        ]);
+        SecretDatabase::new(&path).unwrap().record_repository_activity(&seen).unwrap();
+
+        let mut due = repositories_due(&path, 24, 10).await.unwrap();
+        due.sort();
+        assert_eq!(due, ["acme/api", "acme/gone"]);
+        assert_eq!(repositories_due(&path, 24, 1).await.unwrap().len(), 1);
+
+        let github = serde_json::json!({ "full_name": "acme/api", "private": true, "stargazers_count": 3, "fork": true });
+        let fetched = vec![RepositoryMetadata::from_github("acme/api", &github), RepositoryMetadata::unavailable("acme/gone")];
+        assert_eq!(save_metadata(&path, fetched).await.unwrap(), 2);
+        assert!(repositories_due(&path, 24, 10).await.unwrap().is_empty());
+
+        let database = SecretDatabase::new(&path).unwrap();
+        let api = database.repository("acme/api").unwrap().unwrap();
+        assert_eq!((api.visibility.as_deref(), api.is_fork), (Some("private"), Some(true)));
+        assert!(api.synced_at.is_some());
+        let gone = database.repository("acme/gone").unwrap().unwrap();
+        assert_eq!(gone.visibility.as_deref(), Some(UNAVAILABLE));
+    }
+
+    #[test]
+    fn test_sync_report_serialization() {
+        let report = RepositorySyncReport { synced: 3, unavailable: 1, failed: 0, rate_limited: true };
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json, serde_json::json!({ "synced": 3, "unavailable": 1, "failed": 0, "rate_limited": true }));
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use tracing::{error, info, warn};
+
+use super::{GitHubSecretHunter, RepositoryScanOptions};
+use crate::core::{build_client, offline, send_with_retry, HttpConfig, RetryPolicy};
+use crate::performance::ScanRequest;
+use crate::secrets::SecretMatch;
+use crate::sinks::severity_label;
+
+/// Shared secret callbacks are signed with, as `X-Hub-Signature-256: sha256=<hex>`
+pub const SCAN_CALLBACK_SECRET_ENV: &str = "SCAN_CALLBACK_SECRET";
+
+/// Body POSTed to a scan request's callback URL. It counts findings but never carries
+/// them; the receiver fetches details from the findings API.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanCallback {
+    pub id: i64,
+    pub repository: String,
+    pub commit_sha: Option<String>,
+    pub source: String,
+    pub status: String,
+    pub findings: Option<i64>,
+    /// Active findings per severity
+    pub severities: BTreeMap<String, usize>,
+    pub error: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+impl ScanCallback {
+    pub fn new(request: &ScanRequest, findings: &[SecretMatch]) -> Self {
+        let mut severities = BTreeMap::new();
+        for finding in findings.iter().filter(|f| !f.suppressed) {
+            *severities.entry(severity_label(&finding.severity).to_lowercase()).or_insert(0) += 1;
+        }
+        Self {
+            id: request.id,
+            repository: request.repository.clone(),
+            commit_sha: request.commit_sha.clone(),
+            source: request.source.clone(),
+            status: request.status.clone(),
+            findings: request.findings,
+            severities,
+            error: request.error.clone(),
+            finished_at: request.finished_at.clone(),
+        }
+    }
+}
+
+/// `sha256=<hex>` HMAC of `body`, the scheme GitHub webhooks use
+pub fn callback_signature(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+async fn post_callback(url: &str, callback: &ScanCallback) -> Result<()> {
+    offline::policy().check_url(url)?;
+    let http = HttpConfig::default();
+    let body = serde_json::to_vec(callback)?;
+    let mut request = build_client(&http)?
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("User-Agent", "GitHubArchiver/2.0");
+    if let Ok(secret) = std::env::var(SCAN_CALLBACK_SECRET_ENV) {
+        request = request.header("X-Hub-Signature-256", callback_signature(&secret, &body));
+    }
+    let response = send_with_retry(request.body(body), &RetryPolicy::from_config(&http)).await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("Callback returned {}", response.status()));
+    }
+    Ok(())
+}
+
+impl GitHubSecretHunter {
+    /// Run every queued scan request in turn and notify its callback. Findings go through
+    /// the same store, triage, sink and ticket path as any repository scan. Returns how
+    /// many requests ran.
+    pub async fn run_scan_requests(&mut self) -> Result<usize> {
+        let mut ran = 0;
+        while let Some(request) = self.database.claim_scan_request()? {
+            info!(
+                "Running scan request {} from {} ({}): {}{}",
+                request.id,
+                request.source,
+                request.requested_by,
+                request.repository,
+                request.commit_sha.as_ref().map_or(String::new(), |sha| format!("@{}", sha))
+            );
+            let options = RepositoryScanOptions { commit: request.commit_sha.clone(), ..Default::default() };
+            let (outcome, findings) = match self.scan_repository(&request.repository, &options).await {
+                Ok(report) => {
+                    let active = report.secrets_found.iter().filter(|f| !f.suppressed).count();
+                    (Ok(active), report.secrets_found)
+                }
+                Err(e) => {
+                    error!("Scan request {} failed: {}", request.id, e);
+                    (Err(e.to_string()), Vec::new())
+                }
+            };
+            let finished = self.database.finish_scan_request(request.id, outcome)?;
+            ran += 1;
+
+            let Some(url) = &finished.callback_url else { continue };
+            let callback_status = match post_callback(url, &ScanCallback::new(&finished, &findings)).await {
+                Ok(()) => "delivered".to_string(),
+                Err(e) => {
+                    warn!("Callback for scan request {} not delivered: {}", finished.id, e);
+                    e.to_string()
+                }
+            };
+            self.database.set_scan_callback_status(finished.id, &callback_status)?;
+        }
+        Ok(ran)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callback_signature_matches_github_scheme() {
+        // Example from GitHub's webhook validation documentation
+        assert_eq!(
+            callback_signature("It's a Secret to Everybody", b"Hello, World!"),
+            "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"
+        );
+    }
+}
@@ -0,0 +1,438 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::core::enhanced_database::{ARCHIVE_MIGRATIONS, ARCHIVE_STORE};
+use crate::core::{build_client_with_timeout, offline, Config, DatabaseManager, SchemaVersion};
+use crate::github::GitHubCredentials;
+use crate::performance::{ComponentHeartbeat, ScanCheckpoint, SecretDatabase, FINDINGS_MIGRATIONS, FINDINGS_STORE};
+use crate::scraper::MAX_BACKFILL_ATTEMPTS;
+
+/// Heartbeats are written every 30 seconds; older than this means the process is gone
+pub const STALE_HEARTBEAT_SECS: i64 = 120;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitStatus {
+    pub limit: i64,
+    pub remaining: i64,
+    pub reset_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseSize {
+    pub name: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueuedJobs {
+    /// Archive hours waiting for a backfill download
+    pub backfills: Option<i64>,
+    /// Backfills that failed too often and are no longer retried
+    pub failed_backfills: Option<i64>,
+    /// Saved-search alerts not delivered yet
+    pub search_alerts: Option<i64>,
+}
+
+/// One view over the scraper, the hunter and the databases. Every section comes from the
+/// API server, the databases or GitHub, never from logs; sections that could not be read
+/// are `None` and explained in `errors`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemStatus {
+    pub generated_at: DateTime<Utc>,
+    /// `/api/status` of the API server
+    pub scraper: Option<Value>,
+    pub hunter: Option<ComponentHeartbeat>,
+    pub monitor: Option<ComponentHeartbeat>,
+    /// Last run of the scheduled database maintenance
+    pub maintenance: Option<ComponentHeartbeat>,
+    /// Latest disk growth forecast of a running hunter
+    pub disk: Option<ComponentHeartbeat>,
+    pub last_bigquery_scan: Option<ScanCheckpoint>,
+    pub database_sizes: Vec<DatabaseSize>,
+    pub schema_versions: Vec<SchemaVersion>,
+    pub rate_limit: Option<RateLimitStatus>,
+    pub queued_jobs: QueuedJobs,
+    pub unresolved_critical: Option<i64>,
+    pub errors: Vec<String>,
+}
+
+impl SystemStatus {
+    fn new() -> Self {
+        Self {
+            generated_at: Utc::now(),
+            scraper: None,
+            hunter: None,
+            monitor: None,
+            maintenance: None,
+            disk: None,
+            last_bigquery_scan: None,
+            database_sizes: Vec::new(),
+            schema_versions: Vec::new(),
+            rate_limit: None,
+            queued_jobs: QueuedJobs::default(),
+            unresolved_critical: None,
+            errors: Vec::new(),
+        }
+    }
+
+    fn record<T>(&mut self, source: &str, result: Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.errors.push(format!("{}: {}", source, e));
+                None
+            }
+        }
+    }
+}
+
+/// Gather the status of every component. Unreachable sources are reported, not fatal.
+pub async fn collect_status(config: &Config, findings_database: &str, api_url: &str) -> SystemStatus {
+    let mut status = SystemStatus::new();
+
+    let scraper = fetch_scraper_status(config, api_url).await;
+    status.scraper = status.record("API server", scraper);
+
+    read_findings_database(&mut status, findings_database);
+
+    let postgres = tokio::time::timeout(Duration::from_secs(10), read_archive_database(config))
+        .await
+        .unwrap_or_else(|_| Err(anyhow!("connection timed out")));
+    if let Some((size, version, pending, failed)) = status.record("Archive database", postgres) {
+        status.database_sizes.push(DatabaseSize { name: "archive (postgres)".to_string(), bytes: size });
+        status.schema_versions.push(SchemaVersion::new(ARCHIVE_STORE, version, ARCHIVE_MIGRATIONS));
+        status.queued_jobs.backfills = Some(pending);
+        status.queued_jobs.failed_backfills = Some(failed);
+    }
+
+    let rate_limit = fetch_rate_limit(config).await;
+    status.rate_limit = status.record("GitHub rate limit", rate_limit);
+
+    status
+}
+
+async fn fetch_scraper_status(config: &Config, api_url: &str) -> Result<Value> {
+    let url = format!("{}/api/status", api_url.trim_end_matches('/'));
+    offline::policy().check_url(&url)?;
+    let client = build_client_with_timeout(&config.http, 5)?;
+    let response = client.get(&url).send().await?.error_for_status()?;
+    Ok(response.json().await?)
+}
+
+fn read_findings_database(status: &mut SystemStatus, path: &str) {
+    if !Path::new(path).exists() {
+        status.errors.push(format!("Findings database: {} does not exist", path));
+        return;
+    }
+
+    let bytes = ["", "-wal"]
+        .iter()
+        .filter_map(|suffix| std::fs::metadata(format!("{}{}", path, suffix)).ok())
+        .map(|m| m.len())
+        .sum();
+    status.database_sizes.push(DatabaseSize { name: format!("findings ({})", path), bytes });
+    let attachments = directory_size(Path::new(&format!("{}.attachments", path)));
+    if attachments > 0 {
+        status.database_sizes.push(DatabaseSize { name: "attachments".to_string(), bytes: attachments });
+    }
+
+    let database = match SecretDatabase::new(path) {
+        Ok(database) => database,
+        Err(e) => {
+            status.errors.push(format!("Findings database: {}", e));
+            return;
+        }
+    };
+
+    if let Some(version) = status.record("Schema version", database.schema_version()) {
+        status.schema_versions.push(SchemaVersion::new(FINDINGS_STORE, version, FINDINGS_MIGRATIONS));
+    }
+    if let Some(heartbeats) = status.record("Heartbeats", database.heartbeats()) {
+        for heartbeat in heartbeats {
+            match heartbeat.component.as_str() {
+                "hunter" => status.hunter = Some(heartbeat),
+                "monitor" => status.monitor = Some(heartbeat),
+                "maintenance" => status.maintenance = Some(heartbeat),
+                "disk" => status.disk = Some(heartbeat),
+                _ => {}
+            }
+        }
+    }
+    status.last_bigquery_scan = status.record("Scan checkpoints", database.latest_checkpoint()).flatten();
+    status.queued_jobs.search_alerts = status.record("Saved searches", database.pending_search_alert_count());
+    status.unresolved_critical = status.record("Findings", database.unresolved_count("Critical"));
+}
+
+/// Size and schema version of the archive database, and pending and failed backfills
+async fn read_archive_database(config: &Config) -> Result<(u64, i32, i64, i64)> {
+    let mut database = DatabaseManager::new(config.clone());
+    database.connect().await?;
+    let size = database.database_size().await?;
+    let version = database.schema_version().unwrap_or(0);
+    let (pending, failed) = database.backfill_queue_depth(MAX_BACKFILL_ATTEMPTS).await?;
+    Ok((size.max(0) as u64, version, pending, failed))
+}
+
+/// `/rate_limit` does not count against the limit itself
+async fn fetch_rate_limit(config: &Config) -> Result<RateLimitStatus> {
+    let url = format!("{}/rate_limit", offline::policy().github_api_base()?);
+    offline::policy().check_url(&url)?;
+    let client = build_client_with_timeout(&config.http, 10)?;
+    let mut request = client.get(&url).header("Accept", "application/vnd.github+json");
+    if let Some(token) = GitHubCredentials::from(config.github.token.clone()).token().await? {
+        request = request.header("Authorization", format!("token {}", token));
+    }
+    let body: Value = request.send().await?.error_for_status()?.json().await?;
+    parse_rate_limit(&body)
+}
+
+fn parse_rate_limit(body: &Value) -> Result<RateLimitStatus> {
+    let core = &body["resources"]["core"];
+    let field = |name: &str| core[name].as_i64().ok_or_else(|| anyhow!("rate limit response has no core.{}", name));
+    Ok(RateLimitStatus {
+        limit: field("limit")?,
+        remaining: field("remaining")?,
+        reset_at: Utc
+            .timestamp_opt(field("reset")?, 0)
+            .single()
+            .ok_or_else(|| anyhow!("invalid rate limit reset time"))?,
+    })
+}
+
+fn directory_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(m) if m.is_dir() => directory_size(&entry.path()),
+            Ok(m) => m.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn heartbeat_age(heartbeat: &ComponentHeartbeat) -> String {
+    if heartbeat.age_seconds > STALE_HEARTBEAT_SECS {
+        format!("not running (last heartbeat {})", heartbeat.updated_at)
+    } else {
+        format!("updated {}s ago", heartbeat.age_seconds)
+    }
+}
+
+impl fmt::Display for SystemStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Status at {}", self.generated_at.format("%Y-%m-%d %H:%M:%S UTC"))?;
+
+        writeln!(f, "\nScraper")?;
+        match &self.scraper {
+            Some(scraper) => {
+                writeln!(f, "  State:            {}", scraper["status"].as_str().unwrap_or("unknown"))?;
+                writeln!(f, "  Files processed:  {}", scraper["total_files_processed"])?;
+                writeln!(f, "  Events processed: {}", scraper["total_events_processed"])?;
+                writeln!(f, "  Errors:           {}", scraper["total_errors"])?;
+            }
+            None => writeln!(f, "  API server not reachable")?,
+        }
+
+        writeln!(f, "\nHunter")?;
+        match &self.hunter {
+            Some(hunter) => {
+                let running = hunter.snapshot["is_running"].as_bool().unwrap_or(false);
+                let state = if running && hunter.age_seconds <= STALE_HEARTBEAT_SECS { "running" } else { "stopped" };
+                writeln!(f, "  State:            {} ({})", state, heartbeat_age(hunter))?;
+                writeln!(f, "  Secrets found:    {}", hunter.snapshot["total_secrets_found"])?;
+            }
+            None => writeln!(f, "  No heartbeat recorded")?,
+        }
+        match &self.monitor {
+            Some(monitor) => {
+                let health = match &monitor.snapshot["status"] {
+                    Value::String(s) => s.clone(),
+                    Value::Object(o) => o
+                        .iter()
+                        .next()
+                        .map(|(state, detail)| format!("{}: {}", state, detail["reason"].as_str().unwrap_or("")))
+                        .unwrap_or_default(),
+                    _ => "unknown".to_string(),
+                };
+                let paused = if monitor.snapshot["paused"].as_bool().unwrap_or(false) { ", paused" } else { "" };
+                writeln!(f, "  Monitor:          {}{} ({})", health, paused, heartbeat_age(monitor))?;
+                if let Some(last) = monitor.snapshot["health"]["last_event_received"].as_str() {
+                    writeln!(f, "  Last event:       {}", last)?;
+                }
+                let rate_limit = &monitor.snapshot["rate_limit"];
+                if let (Some(remaining), Some(limit)) = (rate_limit["remaining"].as_u64(), rate_limit["limit"].as_u64()) {
+                    writeln!(
+                        f,
+                        "  Rate limit:       {}/{} left, resets {}, next poll in {}s",
+                        remaining,
+                        limit,
+                        rate_limit["reset_at"].as_str().unwrap_or("unknown"),
+                        rate_limit["next_poll_secs"]
+                    )?;
+                }
+                let queue = &monitor.snapshot["queue"];
+                if let Some(pending) = queue["pending"].as_u64() {
+                    writeln!(
+                        f,
+                        "  Event queue:      {} pending, {} in flight, {} dead-lettered",
+                        pending, queue["in_flight"], queue["dead_letters"]
+                    )?;
+                }
+            }
+            None => writeln!(f, "  Monitor:          not running")?,
+        }
+
+        writeln!(f, "\nLast BigQuery scan")?;
+        match &self.last_bigquery_scan {
+            Some(scan) => {
+                let state = if scan.completed { "completed" } else { "interrupted or running" };
+                writeln!(f, "  {} ({}), updated {}", scan.scan_id, state, scan.updated_at)?;
+                writeln!(
+                    f,
+                    "  {}/{} organizations, {} findings",
+                    scan.completed_organizations.len(),
+                    scan.organizations.len(),
+                    scan.findings
+                )?;
+            }
+            None => writeln!(f, "  None")?,
+        }
+
+        writeln!(f, "\nDatabases")?;
+        for size in &self.database_sizes {
+            writeln!(f, "  {:<28} {}", size.name, format_bytes(size.bytes))?;
+        }
+        for schema in &self.schema_versions {
+            writeln!(f, "  {:<28} schema v{} (this build: v{})", schema.store, schema.version, schema.supported)?;
+        }
+        if let Some(maintenance) = &self.maintenance {
+            let report = &maintenance.snapshot;
+            match report["deferred"].as_str() {
+                Some(reason) => writeln!(f, "  Maintenance:      deferred, {} ({})", reason, maintenance.updated_at)?,
+                None => writeln!(
+                    f,
+                    "  Maintenance:      {} pages reclaimed{} ({})",
+                    report["vacuumed_pages"],
+                    if report["analyzed"].as_bool().unwrap_or(false) { ", analyzed" } else { "" },
+                    maintenance.updated_at
+                )?,
+            }
+        }
+        if let Some(disk) = &self.disk {
+            let forecast = &disk.snapshot;
+            let growth = forecast["growth_bytes_per_day"].as_f64().unwrap_or_default();
+            let runway = match forecast["days_remaining"].as_f64() {
+                Some(days) => format!("full in {:.1} days", days),
+                None => "not growing".to_string(),
+            };
+            writeln!(
+                f,
+                "  Disk:             {} free, {}/day, {}{} ({})",
+                format_bytes(forecast["free_bytes"].as_u64().unwrap_or_default()),
+                format_bytes(growth.max(0.0) as u64),
+                runway,
+                if forecast["warning"].as_bool().unwrap_or(false) { " [WARNING]" } else { "" },
+                disk.updated_at
+            )?;
+        }
+
+        writeln!(f, "\nGitHub rate limit")?;
+        match &self.rate_limit {
+            Some(limit) => writeln!(
+                f,
+                "  {}/{} remaining, resets {}",
+                limit.remaining,
+                limit.limit,
+                limit.reset_at.format("%H:%M:%S UTC")
+            )?,
+            None => writeln!(f, "  Unknown")?,
+        }
+
+        let count = |value: Option<i64>| value.map_or("-".to_string(), |v| v.to_string());
+        writeln!(f, "\nQueued jobs")?;
+        writeln!(
+            f,
+            "  Backfills:        {} ({} given up)",
+            count(self.queued_jobs.backfills),
+            count(self.queued_jobs.failed_backfills)
+        )?;
+        writeln!(f, "  Search alerts:    {}", count(self.queued_jobs.search_alerts))?;
+
+        writeln!(f, "\nUnresolved Critical findings: {}", count(self.unresolved_critical))?;
+
+        if !self.errors.is_empty() {
+            writeln!(f, "\nUnavailable")?;
+            for error in &self.errors {
+                writeln!(f, "  {}", error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_rate_limit() {
+        let body = json!({"resources": {"core": {"limit": 5000, "remaining": 4321, "reset": 1700000000}}});
+        let limit = parse_rate_limit(&body).unwrap();
+        assert_eq!((limit.limit, limit.remaining), (5000, 4321));
+        assert_eq!(limit.reset_at.timestamp(), 1700000000);
+        assert!(parse_rate_limit(&json!({"message": "Bad credentials"})).is_err());
+    }
+
+    #[test]
+    fn test_status_reads_findings_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.db").to_string_lossy().to_string();
+        let database = SecretDatabase::new(&path).unwrap();
+        database.record_heartbeat("hunter", &json!({"is_running": true, "total_secrets_found": 3})).unwrap();
+        drop(database);
+
+        let mut status = SystemStatus::new();
+        read_findings_database(&mut status, &path);
+        assert!(status.errors.is_empty(), "{:?}", status.errors);
+        assert_eq!(status.unresolved_critical, Some(0));
+        assert_eq!(status.queued_jobs.search_alerts, Some(0));
+        assert!(status.hunter.is_some() && status.monitor.is_none());
+        assert_eq!(status.schema_versions[0].version, crate::performance::SCHEMA_VERSION);
+        assert!(status.to_string().contains("running (updated"));
+
+        let mut missing = SystemStatus::new();
+        read_findings_database(&mut missing, &dir.path().join("none.db").to_string_lossy());
+        assert_eq!(missing.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+}
@@ -1,18 +1,23 @@
 pub mod api;
 pub mod auth;
 pub mod bigquery;
+pub mod canary;
 pub mod cli;
 pub mod core;
+pub mod daemon;
 pub mod github;
+pub mod notifications;
 #[cfg(feature = "gui")]
 pub mod gui;
 pub mod scraper;
 pub mod secrets;
+pub mod sinks;
 #[cfg(feature = "ai")]
 pub mod ai;
 pub mod realtime;
 pub mod performance;
 pub mod integration;
+pub mod update;
 
 pub use bigquery::BigQueryScanner;
 pub use github::DanglingCommitFetcher;
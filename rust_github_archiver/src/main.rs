@@ -11,10 +11,34 @@ use github_archiver::{
     PerformanceEngine,
     SecretDatabase,
     SecretsNinjaApp,
+    auth::{temporary_password, ApprovalAction, ApprovalPolicy, ApprovalStatus, Role, UserManager},
+    bigquery::RepositoryFilter,
+    canary::{generate_canarytoken, generate_local, seed_file, seed_text, CanaryKind, CanaryProvider, CanaryToken, SeedFormat},
+    core::{config::SecurityConfig, offline, output, scan_workspace, slow_queries::summarize, AuditLog, DatabaseManager},
+    github::{
+        CachedGitHubClient, CandidateSource, CheckRunPublisher, DanglingCommitRecovery, EtagCache, GitHubCredentials, GitHubGraphQLClient,
+    },
+    integration::{sync_repositories, sync_repositories_graphql, OrganizationScanOptions, RepositoryScanOptions},
+    daemon::{daemonize, shutdown_requested, InstanceLock, PidFile, SystemdUnit},
+    notifications::{i18n, resend_alert, sample_alert, verify::endpoint_label, verify_endpoints, AlertTarget, Notifier},
+    performance::{
+        compression, create_backup, query_cache, deliver_search_alerts, guess_content_type, parse_as_of, read_backup, read_manifest, restore_backup, restore_staging, run_purge,
+        render_scorecards_html, AttachmentStore, BackupLocation, DatasetOptions, ExportFormat, FindingStatus, NewScanRequest, RepositoryFilters, ScorecardPeriod,
+        SecretDatabasePool, WorkspaceQuota, BACKUP_KEY_ENV, DEFAULT_POOL_SIZE,
+    },
+    realtime::{ChatNotifier, EventFilter, EventQueue, IncidentNotifier, LeaderElector},
+    scraper::ArchiveSecretScanner,
+    secrets::{
+        rdjson_report, Allowlist, AllowlistEntry, AllowlistKind, FindingImporter, ImportFormat, SecretCategory, SecretSeverity,
+        DEFAULT_IGNORE_FILE,
+    },
+    sinks::{build_stix_bundle, severity_rank, AnnotationUpdate, IntelFinding, RedactionProfile, SinkDispatcher},
+    update::{spawn_version_notice, SelfUpdater, UpdateConfig, CURRENT_VERSION},
 };
 use std::path::PathBuf;
-use tracing::{info, error};
-use tracing_subscriber;
+use std::sync::Arc;
+use tracing::{info, error, warn};
+use tracing_subscriber::prelude::*;
 
 #[derive(Parser)]
 #[command(name = "github-secret-hunter")]
@@ -32,6 +56,11 @@ struct Cli {
     /// Configuration file path
     #[arg(short, long)]
     config: Option<PathBuf>,
+
+    /// Air-gapped mode: block every outbound request except OFFLINE_GITHUB_HOST,
+    /// OFFLINE_ALLOWED_HOSTS and loopback
+    #[arg(long)]
+    offline: bool,
 }
 
 #[derive(Subcommand)]
@@ -56,9 +85,42 @@ enum Commands {
     
     /// Database operations
     Database(DatabaseArgs),
+
+    /// Import findings from third-party scanners
+    Import(ImportArgs),
+
+    /// Scan downloaded GH Archive hourly files for secrets in push event commit messages
+    ScanArchive(ScanArchiveArgs),
     
     /// Performance testing and optimization
     Perf(PerfArgs),
+
+    /// Check for and install a newer signed release
+    SelfUpdate(SelfUpdateArgs),
+
+    /// Install the hunter as a system service
+    Service(ServiceArgs),
+
+    /// Preview and test alert notifications
+    Notify(NotifyArgs),
+
+    /// Show scraper, hunter, database and queue status in one view
+    Status(StatusArgs),
+
+    /// List sent alerts and re-send them to a channel
+    Alerts(AlertsArgs),
+
+    /// Generate decoy credentials, seed them and alert when they show up
+    Canary(CanaryArgs),
+
+    /// Recover dangling commits from abbreviated SHAs
+    RecoverCommits(RecoverCommitsArgs),
+
+    /// API administration: users and their sessions
+    Admin(AdminArgs),
+
+    /// Export derived data sets
+    Export(ExportArgs),
 }
 
 #[derive(Args)]
@@ -71,6 +133,15 @@ struct HuntArgs {
     #[arg(long)]
     bigquery: bool,
 
+    /// Continue an interrupted BigQuery scan from its last checkpoint
+    #[arg(long, value_name = "SCAN_ID", requires = "bigquery")]
+    resume: Option<String>,
+
+    /// Read the historical scan from GH Archive hourly files kept in DIR, downloading missing
+    /// hours, instead of BigQuery (default: GHARCHIVE_DIR)
+    #[arg(long, value_name = "DIR", requires = "bigquery")]
+    gharchive_dir: Option<PathBuf>,
+
     /// Enable real-time monitoring
     #[arg(long)]
     realtime: bool,
@@ -83,23 +154,158 @@ struct HuntArgs {
     #[arg(long)]
     model_path: Option<String>,
 
+    /// Run as part of an HA pair, polling only while holding the Redis leader lease
+    #[arg(long)]
+    leader_election: bool,
+
+    /// Directory of gitleaks-style TOML/YAML detector rules to merge with the built-ins
+    #[arg(long, value_name = "DIR")]
+    rules: Option<PathBuf>,
+
+    /// Suppression rules to apply (default: .secretsignore in the working directory)
+    #[arg(long, value_name = "FILE")]
+    allowlist: Option<PathBuf>,
+
+    /// Hours between repository metadata syncs, 0 disables them (default: REPOSITORY_SYNC_INTERVAL_HOURS or 6)
+    #[arg(long, value_name = "HOURS")]
+    repository_sync_hours: Option<u64>,
+
+    /// Seconds to wait on shutdown for in-flight event scans before aborting them (default: SHUTDOWN_DRAIN_SECS or 30)
+    #[arg(long, value_name = "SECS")]
+    drain_timeout: Option<u64>,
+
     /// Database path
     #[arg(short, long, default_value = "secrets.db")]
     database: String,
+
+    /// Detach from the terminal and run in the background (Unix only)
+    #[arg(long)]
+    daemon: bool,
+
+    /// PID file to write; defaults to github-secret-hunter.pid in daemon mode
+    #[arg(long)]
+    pid_file: Option<PathBuf>,
+
+    /// Where daemon stdout/stderr go (default: discarded)
+    #[arg(long, requires = "daemon")]
+    log_file: Option<PathBuf>,
+
+    /// Periodically push a labeled synthetic finding through store, triage, alert and ticket
+    #[arg(long)]
+    inject_synthetic: bool,
+
+    /// Minutes between synthetic findings
+    #[arg(long, default_value = "60", requires = "inject_synthetic")]
+    synthetic_interval: u64,
 }
 
 #[derive(Args)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct ScanArgs {
+    #[command(subcommand)]
+    operation: Option<ScanOps>,
+
     /// Target (organization/repository)
-    target: String,
+    #[arg(required = true)]
+    target: Option<String>,
 
     /// Scan type
     #[arg(short, long, default_value = "repository")]
     scan_type: String,
 
-    /// Output format
+    /// Output format: json, yaml, annotations (reviewdog rdjson diagnostics) or text
     #[arg(short, long, default_value = "json")]
     output: String,
+
+    /// Only fetch the last N commits (default: full history)
+    #[arg(long)]
+    depth: Option<u32>,
+
+    /// Skip blobs larger than this many bytes
+    #[arg(long, default_value_t = github_archiver::integration::repository::DEFAULT_MAX_BLOB_BYTES)]
+    max_file_size: usize,
+
+    /// Also walk every reflog entry and dangling commit, not just branches and tags
+    #[arg(long)]
+    history: bool,
+
+    /// Skip commits authored before this date (YYYY-MM-DD)
+    #[arg(long)]
+    since: Option<chrono::NaiveDate>,
+
+    /// Directory of gitleaks-style TOML/YAML detector rules to merge with the built-ins
+    #[arg(long, value_name = "DIR")]
+    rules: Option<PathBuf>,
+
+    /// Suppression rules to apply (default: .secretsignore in the working directory)
+    #[arg(long, value_name = "FILE")]
+    allowlist: Option<PathBuf>,
+
+    /// Organization scans: leave out forks
+    #[arg(long)]
+    skip_forks: bool,
+
+    /// Organization scans: leave out archived repositories
+    #[arg(long)]
+    skip_archived: bool,
+
+    /// Organization scans: repositories to clone and scan at once (default: one per CPU)
+    #[arg(long)]
+    concurrency: Option<usize>,
+
+    /// Exit with an error when an active finding is at or above this severity (low, medium,
+    /// high, critical), for gating CI
+    #[arg(long, value_name = "SEVERITY")]
+    fail_on: Option<SecretSeverity>,
+
+    /// Repository scans: post the results as a GitHub check run on --head-sha, with inline
+    /// annotations, failing on --fail-on (default: high)
+    #[arg(long)]
+    check_run: bool,
+
+    /// Commit the check run is attached to (default: $GITHUB_SHA)
+    #[arg(long, requires = "check_run")]
+    head_sha: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum ScanOps {
+    /// Queue a scan for the running hunter and optionally have a callback URL notified
+    /// when its results are ready
+    Trigger {
+        /// Repository to scan (owner/repo or a clone URL)
+        repository: String,
+        /// Scan only the changes this commit introduced
+        #[arg(long)]
+        commit: Option<String>,
+        /// URL POSTed a JSON summary when the scan finishes
+        #[arg(long)]
+        callback: Option<String>,
+        /// System asking for the scan, e.g. webhook, ci or soar
+        #[arg(long, default_value = "cli")]
+        source: String,
+        /// Findings database the hunter polls (default: FINDINGS_DATABASE or secrets.db)
+        #[arg(short, long)]
+        database: Option<String>,
+        /// Wait for the scan to finish and exit with an error if it failed
+        #[arg(long)]
+        wait: bool,
+    },
+}
+
+#[derive(Args)]
+struct StatusArgs {
+    /// Findings database (default: FINDINGS_DATABASE or secrets.db)
+    #[arg(short, long)]
+    database: Option<String>,
+
+    /// API server to ask for scraper progress (default: WEB_HOST and WEB_PORT)
+    #[arg(long)]
+    api_url: Option<String>,
+
+    /// Print JSON instead of text
+    #[arg(long)]
+    json: bool,
 }
 
 #[derive(Args)]
@@ -111,6 +317,10 @@ struct GuiArgs {
     /// Theme
     #[arg(short, long, default_value = "dark")]
     theme: String,
+
+    /// Browse without changing anything: the database is opened read-only
+    #[arg(long)]
+    read_only: bool,
 }
 
 #[derive(Args)]
@@ -126,6 +336,10 @@ struct BigQueryArgs {
     /// Days back to scan
     #[arg(short, long, default_value = "30")]
     days: u32,
+
+    /// Report the bytes the query would process and its estimated cost without running it
+    #[arg(long)]
+    dry_run: bool,
 }
 
 #[derive(Args)]
@@ -138,9 +352,47 @@ struct MonitorArgs {
     #[arg(short, long)]
     webhook: Option<String>,
 
-    /// Poll interval in seconds
+    /// Shortest poll interval in seconds; polling slows down as the GitHub rate limit runs low
     #[arg(long, default_value = "10")]
     interval: u64,
+
+    /// Poll only while holding the Redis leader lease (requires REDIS_URL)
+    #[arg(long)]
+    leader_election: bool,
+
+    /// Only process these event types (push, pull-request, issue-comment, release); repeatable
+    #[arg(long = "include-event", value_name = "TYPE")]
+    include_events: Vec<String>,
+
+    /// Skip these event types; repeatable
+    #[arg(long = "exclude-event", value_name = "TYPE")]
+    exclude_events: Vec<String>,
+
+    /// Only alert on findings of at least this severity (low, medium, high, critical)
+    #[arg(long)]
+    min_severity: Option<String>,
+
+    /// Only process repositories matching these `owner/name` patterns (`*` wildcard); repeatable
+    #[arg(long = "repo", value_name = "PATTERN")]
+    repo_patterns: Vec<String>,
+
+    /// Also process repositories owned by these user accounts; repeatable
+    #[arg(long = "user", value_name = "LOGIN")]
+    users: Vec<String>,
+
+    /// Poll each organization's and repository's own events endpoint instead of the public
+    /// firehose, which only carries a sample of events
+    #[arg(long)]
+    target_endpoints: bool,
+
+    /// `poll` the Events API, or receive organization and repository `webhook` deliveries
+    /// signed with GITHUB_WEBHOOK_SECRET
+    #[arg(long, default_value = "poll")]
+    mode: String,
+
+    /// Address the webhook receiver listens on (webhook mode)
+    #[arg(long, default_value = "0.0.0.0:8080")]
+    listen: String,
 }
 
 #[derive(Args)]
@@ -175,202 +427,1720 @@ enum DatabaseOps {
         path: String,
         #[arg(short, long)]
         limit: Option<u32>,
+        #[command(flatten)]
+        filters: FindingFilterArgs,
+        /// Include findings suppressed by the allowlist
+        #[arg(long)]
+        include_suppressed: bool,
+    },
+
+    /// Count findings per severity and status as they stood at a past date, e.g. a quarter end
+    Snapshot {
+        path: String,
+        /// YYYY-MM-DD (end of day UTC) or an RFC 3339 time
+        #[arg(long)]
+        as_of: String,
+        /// Print the counts as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show every recorded severity and status change of a finding
+    History {
+        path: String,
+        /// Secret hash of the finding
+        hash: String,
+    },
+
+    /// List findings like a given one (same context, similar path, same detector), best first
+    Similar {
+        path: String,
+        /// Secret hash of the finding
+        hash: String,
+        #[arg(long, default_value = "20")]
+        limit: usize,
+        /// Print the findings as JSON
+        #[arg(long)]
+        json: bool,
     },
     
     /// Optimize database
     Optimize { path: String },
+
+    /// Run the online maintenance the hunter schedules: payload compression, incremental
+    /// vacuum, ANALYZE after large ingests and a WAL checkpoint
+    Maintain {
+        path: String,
+        /// Run even while a scan or event batch is in progress
+        #[arg(long)]
+        force: bool,
+        /// Also print autovacuum tuning hints for the archive (PostgreSQL) database from the --config file
+        #[arg(long)]
+        archive: bool,
+    },
+
+    /// Compress payload rows stored as plain text, e.g. written before compression was on
+    Compress {
+        path: String,
+        /// Rows per transaction
+        #[arg(long, default_value_t = 500)]
+        batch_size: usize,
+    },
     
     /// Export data
     Export { 
         path: String,
+        /// File to write, or - for standard output
         #[arg(short, long)]
         output: String,
+        /// Output format (json, jsonl, csv, parquet, stix)
+        #[arg(short, long, default_value = "json")]
+        format: String,
+        #[command(flatten)]
+        filters: FindingFilterArgs,
+        /// Approved request to run under, when APPROVAL_POLICY covers exports
+        #[arg(long)]
+        approval: Option<i64>,
     },
-}
-
-#[derive(Args)]
-struct PerfArgs {
-    /// Performance test type
-    #[command(subcommand)]
-    test: PerfTests,
-}
 
-#[derive(Subcommand)]
-enum PerfTests {
-    /// Benchmark secret scanning
-    Scan {
-        #[arg(short, long, default_value = "1000")]
-        secrets: usize,
-        
-        #[arg(short, long, default_value = "4")]
-        workers: usize,
-    },
-    
-    /// Benchmark database operations
-    Database {
-        #[arg(short, long, default_value = "secrets.db")]
+    /// Place a finding under legal hold (exempt from retention and erasure)
+    Hold {
         path: String,
+        /// Secret hash of the finding
+        hash: String,
+        #[arg(short, long)]
+        reason: String,
+        /// Who requested the hold
+        #[arg(long, default_value = "cli")]
+        by: String,
     },
-    
-    /// Generate performance report
-    Report {
-        #[arg(short, long, default_value = "report.json")]
-        output: String,
+
+    /// Release a legal hold
+    Release { path: String, hash: String },
+
+    /// List findings under legal hold
+    Holds { path: String },
+
+    /// Add an allowlist rule and suppress the stored findings it matches
+    Suppress {
+        path: String,
+        /// Rule kind: regex, path, hash or detector
+        kind: AllowlistKind,
+        /// Regex, path glob, finding hash or detector name
+        pattern: String,
+        #[arg(short, long)]
+        reason: Option<String>,
+        #[arg(long, default_value = "cli")]
+        by: String,
     },
-}
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    /// Remove an allowlist rule and reactivate the findings it suppressed
+    Unsuppress { path: String, id: i64 },
 
-    // Initialize logging
-    let log_level = if cli.verbose { "debug" } else { "info" };
-    tracing_subscriber::fmt()
-        .with_env_filter(format!("github_archiver={}", log_level))
-        .init();
+    /// List the allowlist rules stored in the database
+    Allowlist { path: String },
 
-    info!("🔍 GitHub Secret Hunter v2.0.0 starting...");
+    /// Rewrap md5 fingerprints and SHA-256 finding hashes from earlier releases with the
+    /// fingerprint pepper
+    RehashFingerprints { path: String },
 
-    match cli.command {
-        Commands::Hunt(args) => run_comprehensive_hunt(args).await,
-        Commands::Scan(args) => run_scan(args).await,
-        Commands::Gui(args) => run_gui(args).await,
-        Commands::BigQuery(args) => run_bigquery_scan(args).await,
-        Commands::Monitor(args) => run_realtime_monitor(args).await,
-        Commands::Triage(args) => run_ai_triage(args).await,
-        Commands::Database(args) => run_database_ops(args).await,
-        Commands::Perf(args) => run_performance_tests(args).await,
-    }
-}
+    /// Merge the findings, triage results and commits of another hunter's database into
+    /// this one, deduplicating on the secret hash
+    Import {
+        path: String,
+        /// Database to merge from; it is only read
+        other: String,
+        /// Print the counts as JSON
+        #[arg(long)]
+        json: bool,
+    },
 
-async fn run_comprehensive_hunt(args: HuntArgs) -> Result<()> {
-    info!("🚀 Starting comprehensive GitHub secret hunting");
+    /// Encrypt a plaintext findings database in place with FINDINGS_DB_KEY (needs a
+    /// build with --features sqlcipher)
+    MigrateEncrypt { path: String },
 
-    let config = HunterConfig {
-        gcp_project_id: std::env::var("GCP_PROJECT_ID").unwrap_or_default(),
-        github_token: std::env::var("GITHUB_TOKEN").unwrap_or_default(),
-        redis_url: std::env::var("REDIS_URL").ok(),
-        database_path: args.database,
-        ai_model_path: args.model_path,
-        webhook_endpoints: Vec::new(),
-        scanning_options: github_archiver::integration::ScanningOptions {
-            enable_bigquery_scanning: args.bigquery,
-            enable_realtime_monitoring: args.realtime,
-            enable_ai_triage: args.ai_triage,
-            enable_secret_validation: true,
-            organizations_to_monitor: args.organizations,
-            minimum_entropy_threshold: 3.0,
-            scan_historical_events: true,
-            historical_days_back: 30,
-        },
-        performance_options: github_archiver::integration::PerformanceOptions {
-            parallel_workers: num_cpus::get(),
-            cache_size: 10000,
-            batch_size: 100,
-            rate_limit_per_hour: 5000,
-            enable_caching: true,
-            enable_deduplication: true,
-        },
-    };
+    /// Apply the retention policy (contexts, validated secrets, resolved, expired and low
+    /// severity unverified findings, orphaned triage and analyst data)
+    Purge {
+        path: String,
+        /// Show what would be purged without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
 
-    let mut hunter = GitHubSecretHunter::new(config).await?;
-    hunter.start_hunting().await?;
+    /// Scrub secret material for the given findings, keeping anonymized statistics
+    Erase {
+        path: String,
+        /// Secret hashes of the findings to erase
+        #[arg(required = true)]
+        hashes: Vec<String>,
+        /// Who requested the erasure
+        #[arg(long, default_value = "cli")]
+        by: String,
+        /// Approved request to run under, when APPROVAL_POLICY covers erasure
+        #[arg(long)]
+        approval: Option<i64>,
+    },
 
-    // Keep running until interrupted
-    info!("Secret hunting started. Press Ctrl+C to stop...");
-    tokio::signal::ctrl_c().await?;
-    
-    hunter.stop_hunting().await?;
-    info!("Secret hunting stopped");
+    /// Add a markdown note to a finding
+    Note {
+        path: String,
+        /// Secret hash of the finding
+        hash: String,
+        /// Note body (markdown)
+        #[arg(short, long)]
+        body: String,
+        #[arg(long, default_value = "cli")]
+        by: String,
+    },
 
-    Ok(())
-}
+    /// Attach an evidence file to a finding
+    Attach {
+        path: String,
+        /// Secret hash of the finding
+        hash: String,
+        file: PathBuf,
+        /// MIME type; guessed from the extension when omitted
+        #[arg(long)]
+        content_type: Option<String>,
+        #[arg(long, default_value = "cli")]
+        by: String,
+    },
 
-async fn run_scan(args: ScanArgs) -> Result<()> {
-    info!("🔍 Scanning target: {}", args.target);
+    /// Show the notes and attachments on a finding
+    Annotations {
+        path: String,
+        /// Secret hash of the finding
+        hash: String,
+    },
 
-    match args.scan_type.as_str() {
-        "repository" => {
-            let config = HunterConfig::default();
-            let mut hunter = GitHubSecretHunter::new(config).await?;
-            let report = hunter.scan_repository(&args.target).await?;
-            
-            match args.output.as_str() {
-                "json" => println!("{}", serde_json::to_string_pretty(&report)?),
-                "yaml" => println!("{}", serde_yaml::to_string(&report)?),
-                _ => info!("Scan completed: {} secrets found", report.secrets_found.len()),
-            }
-        }
-        _ => {
-            error!("Unsupported scan type: {}", args.scan_type);
-        }
-    }
+    /// Mark a finding resolved or revoked, or open again; MTTR is measured to this
+    Status {
+        path: String,
+        /// Secret hash of the finding
+        hash: String,
+        /// open, resolved or revoked
+        status: FindingStatus,
+    },
 
-    Ok(())
-}
+    /// Add or remove tags on a finding
+    Tag {
+        path: String,
+        /// Secret hash of the finding
+        hash: String,
+        #[arg(required = true)]
+        tags: Vec<String>,
+        /// Remove the tags instead of adding them
+        #[arg(long)]
+        remove: bool,
+        #[arg(long, default_value = "cli")]
+        by: String,
+    },
 
-async fn run_gui(_args: GuiArgs) -> Result<()> {
-    info!("🎨 Launching Secrets Ninja GUI");
-    
-    // This would launch the Iced GUI application
-    info!("GUI application would launch here");
-    info!("Use: iced::run(SecretsNinjaApp::new(), Settings::default())");
-    
-    Ok(())
-}
+    /// Add or remove tags on a repository; they apply to all of its findings
+    TagRepo {
+        path: String,
+        /// Repository as owner/name
+        repository: String,
+        #[arg(required = true)]
+        tags: Vec<String>,
+        /// Remove the tags instead of adding them
+        #[arg(long)]
+        remove: bool,
+        #[arg(long, default_value = "cli")]
+        by: String,
+    },
 
-async fn run_bigquery_scan(args: BigQueryArgs) -> Result<()> {
-    info!("📊 Running BigQuery historical scan");
+    /// List tags in use with their finding counts
+    Tags { path: String },
 
-    let scanner = BigQueryScanner::new(&args.project).await?;
-    let events = scanner.scan_zero_commit_events(
-        args.organization.as_deref(),
-        args.days,
-    ).await?;
+    /// Save a named query, optionally alerting on new findings that match it
+    SaveSearch {
+        path: String,
+        name: String,
+        /// Minimum severity (low, medium, high, critical)
+        #[arg(long)]
+        severity: Option<String>,
+        /// Only this category, e.g. cloud-provider or api-key
+        #[arg(long)]
+        category: Option<SecretCategory>,
+        #[arg(long)]
+        detector: Option<String>,
+        /// Only findings whose file path matches this glob, e.g. '*.env'
+        #[arg(long)]
+        filename: Option<String>,
+        #[arg(long)]
+        verified: bool,
+        /// Only findings from the last N days
+        #[arg(long)]
+        days: Option<u32>,
+        /// Only findings from this day on (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+        /// Only findings up to and including this day (YYYY-MM-DD)
+        #[arg(long)]
+        until: Option<chrono::NaiveDate>,
+        /// Owner or owner/name
+        #[arg(long)]
+        repo: Option<String>,
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+        /// Notify when a newly inserted finding matches
+        #[arg(long)]
+        alert: bool,
+        /// Alert webhook; defaults to the hunter's webhook endpoints
+        #[arg(long)]
+        webhook: Option<String>,
+        #[arg(long, default_value = "cli")]
+        by: String,
+    },
 
-    info!("Found {} zero-commit events", events.len());
-    for event in events.iter().take(10) {
-        info!("Event: {} -> {} ({})", event.repository, event.before_commit, event.created_at);
-    }
+    /// List saved searches
+    Searches { path: String },
 
-    Ok(())
-}
+    /// Run a saved search
+    RunSearch {
+        path: String,
+        name: String,
+        #[arg(short, long)]
+        limit: Option<u32>,
+    },
 
-async fn run_realtime_monitor(args: MonitorArgs) -> Result<()> {
-    info!("⚡ Starting real-time GitHub event monitoring");
+    /// Delete a saved search
+    DeleteSearch { path: String, name: String },
 
-    let monitor = GitHubEventMonitor::new();
-    
-    // Add webhook if provided
-    if let Some(webhook_url) = args.webhook {
-        monitor.add_webhook_endpoint(
-            webhook_url,
-            None,
-            vec!["push".to_string()],
-        ).await?;
-    }
+    /// Rebuild the dashboard rollups from the findings tables
+    Rollup {
+        path: String,
+        /// Days to rebuild; older days keep their counts
+        #[arg(long, default_value_t = github_archiver::performance::ROLLUP_WINDOW_DAYS)]
+        days: u32,
+    },
 
-    // Start monitoring
-    monitor.start_monitoring().await?;
+    /// Fetch GitHub metadata for repositories seen in events or findings and update their risk scores.
+    /// Uses the GraphQL API, which also returns contributor counts, when GITHUB_TOKEN is set.
+    SyncRepos {
+        path: String,
+        /// Refetch repositories last synced more than this many hours ago
+        #[arg(long, default_value_t = 24)]
+        stale_hours: u32,
+        #[arg(short, long, default_value_t = 500)]
+        limit: u32,
+    },
 
-    Ok(())
-}
+    /// List synced repositories, riskiest first
+    Repos {
+        path: String,
+        #[arg(long)]
+        organization: Option<String>,
+        /// public, private, internal or unavailable
+        #[arg(long)]
+        visibility: Option<String>,
+        #[arg(long)]
+        min_risk: Option<f64>,
+        #[arg(short, long, default_value_t = 20)]
+        limit: u32,
+    },
 
-async fn run_ai_triage(args: TriageArgs) -> Result<()> {
-    info!("🤖 Running AI triage on existing secrets");
+    /// Monthly exposure scorecard per organization for security leadership
+    Scorecard {
+        path: String,
+        /// Organizations to report on (default: every monitored organization)
+        #[arg(long = "org")]
+        organizations: Vec<String>,
+        /// Month as YYYY-MM (default: last month)
+        #[arg(long)]
+        month: Option<String>,
+        /// json or html
+        #[arg(short, long, default_value = "json")]
+        format: String,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Repositories and teams listed per organization
+        #[arg(short, long, default_value_t = 10)]
+        limit: u32,
+    },
 
-    let database = SecretDatabase::new(&args.database)?;
-    let filters = github_archiver::performance::SecretQueryFilters {
-        min_severity: Some(match args.min_severity.as_str() {
-            "critical" => github_archiver::secrets::SecretSeverity::Critical,
+    /// Print dashboard statistics from the rollups
+    Stats {
+        path: String,
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+        #[arg(short, long, default_value_t = 10)]
+        limit: u32,
+    },
+
+    /// Write a consistent snapshot of the database and its attachments
+    Backup {
+        path: String,
+        /// File path, s3://bucket/key or gs://bucket/object
+        destination: String,
+        /// Encrypt with the passphrase in BACKUP_ENCRYPTION_KEY
+        #[arg(long)]
+        encrypt: bool,
+    },
+
+    /// List events that failed processing too often, or put them back on the event queue
+    DeadLetters {
+        path: String,
+        #[arg(short, long, default_value_t = 50)]
+        limit: usize,
+        /// Dead letters to requeue, by id
+        #[arg(long)]
+        requeue: Vec<i64>,
+    },
+
+    /// Slow statements recorded above SLOW_QUERY_MS, with their plans and suggested indexes
+    SlowQueries {
+        path: String,
+        /// Recorded statements to read, newest first
+        #[arg(short, long, default_value_t = 500)]
+        limit: usize,
+        /// Also read the archive (PostgreSQL) database's slow query log from the --config file
+        #[arg(long)]
+        archive: bool,
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Restore a backup made with `database backup`
+    Restore {
+        /// Findings database path, or a postgres:// URL to copy the tables into its `findings` schema
+        path: String,
+        /// File path, s3://bucket/key or gs://bucket/object
+        source: String,
+        /// Replace an existing database
+        #[arg(long)]
+        force: bool,
+        /// Only print the backup's manifest
+        #[arg(long)]
+        inspect: bool,
+    },
+}
+
+/// Finding filters shared by `database query` and `database export`
+#[derive(Args)]
+struct FindingFilterArgs {
+    /// Minimum severity (low, medium, high, critical)
+    #[arg(long)]
+    severity: Option<SecretSeverity>,
+    /// Only this category, e.g. cloud-provider or api-key
+    #[arg(long)]
+    category: Option<SecretCategory>,
+    /// Only findings of this detector, e.g. 'AWS Access Key ID'
+    #[arg(long)]
+    detector: Option<String>,
+    /// Only findings whose file path matches this glob, e.g. '*.env'
+    #[arg(long)]
+    filename: Option<String>,
+    /// Only findings from the last N days
+    #[arg(long)]
+    days: Option<u32>,
+    /// Only findings from this day on (YYYY-MM-DD)
+    #[arg(long)]
+    since: Option<chrono::NaiveDate>,
+    /// Only findings up to and including this day (YYYY-MM-DD)
+    #[arg(long)]
+    until: Option<chrono::NaiveDate>,
+    /// Only findings carrying this tag (repeatable; all must match)
+    #[arg(short, long = "tag")]
+    tags: Vec<String>,
+    /// Only findings in this owner or owner/name repository
+    #[arg(long)]
+    repo: Option<String>,
+    /// Findings with the severity and status they had at this date (YYYY-MM-DD, end of day UTC) or RFC 3339 time
+    #[arg(long)]
+    as_of: Option<String>,
+}
+
+impl FindingFilterArgs {
+    fn into_filters(self, limit: Option<u32>, include_suppressed: bool) -> Result<github_archiver::performance::SecretQueryFilters> {
+        Ok(github_archiver::performance::SecretQueryFilters {
+            min_severity: self.severity,
+            category: self.category,
+            detector_name: self.detector,
+            filename: self.filename,
+            verified_only: false,
+            last_n_days: self.days,
+            since: self.since,
+            until: self.until,
+            limit,
+            tags: self.tags,
+            repository: self.repo,
+            include_suppressed,
+            after: None,
+            as_of: self.as_of.as_deref().map(parse_as_of).transpose()?,
+        })
+    }
+}
+
+#[derive(Args)]
+struct ImportArgs {
+    /// Report format (trufflehog, gitleaks, detect-secrets)
+    #[arg(short, long)]
+    format: String,
+
+    /// Report file to import
+    file: String,
+
+    /// Database path
+    #[arg(short, long, default_value = "secrets.db")]
+    database: String,
+}
+
+#[derive(Args)]
+struct ScanArchiveArgs {
+    /// GH Archive `.json.gz` files, or directories containing them
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+
+    /// Database path
+    #[arg(short, long, default_value = "secrets.db")]
+    database: String,
+
+    /// Print findings instead of storing them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Directory of gitleaks-style TOML/YAML detector rules to merge with the built-ins
+    #[arg(long, value_name = "DIR")]
+    rules: Option<PathBuf>,
+
+    /// Suppression rules to apply (default: .secretsignore in the working directory)
+    #[arg(long, value_name = "FILE")]
+    allowlist: Option<PathBuf>,
+
+    /// Seconds between progress lines
+    #[arg(long, default_value_t = 5)]
+    progress_secs: u64,
+}
+
+#[derive(Args)]
+struct SelfUpdateArgs {
+    /// Release channel (stable, beta); defaults to UPDATE_CHANNEL
+    #[arg(long)]
+    channel: Option<String>,
+
+    /// Only report whether an update is available
+    #[arg(long)]
+    check: bool,
+}
+
+#[derive(Args)]
+struct ServiceArgs {
+    #[command(subcommand)]
+    operation: ServiceOps,
+}
+
+#[derive(Subcommand)]
+enum ServiceOps {
+    /// Write a systemd unit (Linux) or register a Windows service that runs this binary
+    Install {
+        /// Unit file to write
+        #[arg(long, default_value = "/etc/systemd/system/github-secret-hunter.service")]
+        unit_path: PathBuf,
+
+        /// User the service runs as
+        #[arg(long)]
+        user: Option<String>,
+
+        /// EnvironmentFile with GITHUB_TOKEN, REDIS_URL, ...
+        #[arg(long)]
+        env_file: Option<PathBuf>,
+
+        /// Arguments for the service, e.g. `-- hunt --realtime` (default: `hunt`)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Remove the Windows service
+    #[cfg(windows)]
+    Uninstall,
+
+    /// Entry point used by the Windows service control manager
+    #[cfg(windows)]
+    #[command(hide = true)]
+    Run {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+}
+
+#[derive(Args)]
+struct NotifyArgs {
+    #[command(subcommand)]
+    operation: NotifyOps,
+}
+
+#[derive(Subcommand)]
+enum NotifyOps {
+    /// Render a sample alert with the configured templates and send it
+    Test {
+        /// Channel to test: slack, email, webhook or all
+        #[arg(long, default_value = "all")]
+        channel: String,
+
+        /// Extra webhook URL for the webhook channel, besides those in the config file
+        #[arg(long)]
+        webhook: Option<String>,
+
+        /// Secret used to sign the webhook body (X-Hub-Signature-256)
+        #[arg(long)]
+        webhook_secret: Option<String>,
+
+        /// Print the rendered messages without sending anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Send a test alert to every configured endpoint and report delivery and latency
+    Verify {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Args)]
+struct RecoverCommitsArgs {
+    /// Repository as owner/name
+    repository: String,
+
+    /// SHA prefixes of at least 7 hex digits, e.g. from push events or a reflog
+    #[arg(required = true)]
+    prefixes: Vec<String>,
+
+    /// Commit API lookups per prefix for splitting ambiguous prefixes
+    #[arg(long, default_value_t = github_archiver::github::recovery::DEFAULT_API_BUDGET)]
+    api_budget: usize,
+
+    /// Print JSON instead of text
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+struct ExportArgs {
+    /// Findings database (default: FINDINGS_DATABASE or secrets.db)
+    #[arg(short, long, global = true)]
+    database: Option<String>,
+
+    #[command(subcommand)]
+    operation: ExportOps,
+}
+
+#[derive(Subcommand)]
+enum ExportOps {
+    /// Labeled findings for training classifiers: features and TP/FP labels, no secrets or paths
+    Dataset {
+        /// File to write, or - for standard output
+        #[arg(short, long)]
+        output: String,
+        /// Output format (parquet, csv, jsonl)
+        #[arg(short, long, default_value = "parquet")]
+        format: String,
+        /// Share of the examples put in the test split
+        #[arg(long, default_value_t = 0.2)]
+        test_fraction: f64,
+        /// Seed of the example IDs and the train/test split
+        #[arg(long, default_value = "gitarchiver")]
+        seed: String,
+        /// Approved request to run under, when APPROVAL_POLICY covers exports
+        #[arg(long)]
+        approval: Option<i64>,
+    },
+}
+
+#[derive(Args)]
+struct AdminArgs {
+    /// Users database (default: API_USERS_DATABASE or users.db)
+    #[arg(short, long, global = true)]
+    database: Option<String>,
+
+    #[command(subcommand)]
+    operation: AdminOps,
+}
+
+#[derive(Subcommand)]
+enum AdminOps {
+    /// Add, list and disable API users, reset passwords and revoke sessions
+    #[command(subcommand)]
+    Users(UserOps),
+
+    /// Two-person approval requests for exports and erasure
+    #[command(subcommand)]
+    Approvals(ApprovalOps),
+}
+
+#[derive(Subcommand)]
+enum ApprovalOps {
+    /// List approval requests, newest first
+    List {
+        /// pending, approved, rejected or executed
+        #[arg(long)]
+        status: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum UserOps {
+    /// List users with their role and state
+    List,
+
+    /// Add a user. Without --password-stdin a temporary password is printed, which the
+    /// user must change at first sign-in.
+    Add {
+        username: String,
+        /// admin, analyst or viewer
+        #[arg(long, default_value = "viewer")]
+        role: String,
+        /// Read the password from the first line of standard input
+        #[arg(long)]
+        password_stdin: bool,
+    },
+
+    /// Change a user's role
+    SetRole { username: String, role: String },
+
+    /// Disable a user and revoke their sessions
+    Disable { username: String },
+
+    /// Re-enable a disabled user
+    Enable { username: String },
+
+    /// Set a password the user must change at next sign-in, and revoke their sessions
+    ResetPassword {
+        username: String,
+        #[arg(long)]
+        password_stdin: bool,
+    },
+
+    /// Sign out every session of a user
+    RevokeSessions { username: String },
+}
+
+#[derive(Args)]
+struct AlertsArgs {
+    /// Findings database (default: FINDINGS_DATABASE or secrets.db)
+    #[arg(short, long, global = true)]
+    database: Option<String>,
+
+    #[command(subcommand)]
+    operation: AlertsOps,
+}
+
+#[derive(Subcommand)]
+enum AlertsOps {
+    /// List recent alerts with their delivery counts
+    List {
+        /// Only alerts with a failed delivery
+        #[arg(long)]
+        failed: bool,
+        #[arg(short, long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Show an alert and every delivery attempt
+    Show { id: i64 },
+
+    /// Send a past alert again, by default to the channels whose last attempt failed
+    Resend {
+        id: i64,
+        /// slack[:channel], email[:addr,addr], webhook[:url] or chat:<name>; repeatable
+        #[arg(long = "to", value_name = "CHANNEL")]
+        targets: Vec<AlertTarget>,
+    },
+}
+
+#[derive(Args)]
+struct CanaryArgs {
+    /// Findings database (default: FINDINGS_DATABASE or secrets.db)
+    #[arg(short, long, global = true)]
+    database: Option<String>,
+
+    #[command(subcommand)]
+    operation: CanaryOps,
+}
+
+#[derive(Subcommand)]
+enum CanaryOps {
+    /// Generate a canary and start watching for it
+    Create {
+        /// aws or github
+        #[arg(long, default_value = "aws")]
+        kind: CanaryKind,
+        /// local, or canarytokens to also be alerted when the AWS keys are used
+        #[arg(long, default_value = "local")]
+        provider: CanaryProvider,
+        /// What the canary guards; shown in its alerts
+        #[arg(long)]
+        label: String,
+        /// Also append it to these files; repeatable
+        #[arg(long = "seed", value_name = "PATH")]
+        seed: Vec<PathBuf>,
+        /// env, aws-credentials or json
+        #[arg(long, default_value = "env")]
+        format: SeedFormat,
+    },
+
+    /// Append a canary to a file, or record where it was planted by hand
+    Seed {
+        id: String,
+        location: String,
+        /// env, aws-credentials or json
+        #[arg(long, default_value = "env")]
+        format: SeedFormat,
+        /// Only record the location; write nothing
+        #[arg(long)]
+        record_only: bool,
+    },
+
+    /// List canaries, where they were seeded and how often they were seen
+    List,
+
+    /// Show a canary's values and every sighting
+    Show { id: String },
+
+    /// Stop alerting on a canary
+    Retire { id: String },
+}
+
+#[derive(Args)]
+struct PerfArgs {
+    /// Performance test type
+    #[command(subcommand)]
+    test: PerfTests,
+}
+
+#[derive(Subcommand)]
+enum PerfTests {
+    /// Benchmark secret scanning
+    Scan {
+        #[arg(short, long, default_value = "1000")]
+        secrets: usize,
+        
+        #[arg(short, long, default_value = "4")]
+        workers: usize,
+
+        /// Also store the findings in this database, through a connection pool
+        #[arg(long)]
+        database: Option<String>,
+
+        /// Pooled connections when --database is given
+        #[arg(long, default_value_t = DEFAULT_POOL_SIZE)]
+        pool_size: usize,
+    },
+    
+    /// Benchmark database operations
+    Database {
+        #[arg(short, long, default_value = "secrets.db")]
+        path: String,
+    },
+    
+    /// Generate performance report
+    Report {
+        #[arg(short, long, default_value = "report.json")]
+        output: String,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    // Detach before the runtime exists; forking a multi-threaded process is unsafe
+    if let Commands::Hunt(args) = &cli.command {
+        if args.daemon {
+            daemonize(args.log_file.as_deref())?;
+        }
+    }
+
+    #[cfg(windows)]
+    if let Commands::Service(ServiceArgs { operation: ServiceOps::Run { args } }) = cli.command {
+        return run_windows_service(args);
+    }
+
+    tokio::runtime::Runtime::new()?.block_on(run(cli))
+}
+
+/// Started by the SCM as `service run -- <args>`; runs the inner command under the dispatcher
+#[cfg(windows)]
+fn run_windows_service(args: Vec<String>) -> Result<()> {
+    use github_archiver::daemon::windows;
+
+    // The SCM starts us in System32; keep relative paths (database, logs) in the data dir
+    windows::enter_data_dir(&github_archiver::core::paths::data_dir())?;
+    let cli = Cli::try_parse_from(std::iter::once("github_archiver".to_string()).chain(args))?;
+    windows::run(Box::new(move || tokio::runtime::Runtime::new()?.block_on(run(cli))))
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    init_logging(cli.verbose);
+
+    info!("🔍 GitHub Secret Hunter v2.0.0 starting...");
+
+    // Before any command builds an HTTP client or clones a repository
+    let file_config = load_file_config(cli.config.as_ref())?;
+    let mut offline_config = file_config.offline;
+    offline_config.enabled |= cli.offline;
+    offline::install(&offline_config);
+    scan_workspace::install(&file_config.scan_workspaces);
+    compression::install(&file_config.storage_compression);
+    query_cache::install(&file_config.query_cache);
+    i18n::install(file_config.notifications.locale);
+    output::install(&file_config.output);
+
+    match cli.command {
+        Commands::Hunt(args) => run_comprehensive_hunt(args, cli.config).await,
+        Commands::Scan(args) => run_scan(args, cli.config).await,
+        Commands::Gui(args) => run_gui(args).await,
+        Commands::BigQuery(args) => run_bigquery_scan(args, cli.config).await,
+        Commands::Monitor(args) => run_realtime_monitor(args, cli.config).await,
+        Commands::Triage(args) => run_ai_triage(args).await,
+        Commands::Database(args) => run_database_ops(args, cli.config).await,
+        Commands::Import(args) => run_import(args).await,
+        Commands::ScanArchive(args) => run_scan_archive(args, cli.config).await,
+        Commands::Perf(args) => run_performance_tests(args).await,
+        Commands::SelfUpdate(args) => run_self_update(args).await,
+        Commands::Service(args) => run_service_ops(args),
+        Commands::Notify(args) => run_notify_ops(args, cli.config).await,
+        Commands::Status(args) => run_status(args, cli.config).await,
+        Commands::Alerts(args) => run_alerts(args, cli.config).await,
+        Commands::Canary(args) => run_canary(args, cli.config).await,
+        Commands::RecoverCommits(args) => run_recover_commits(args).await,
+        Commands::Admin(args) => run_admin(args, cli.config).await,
+        Commands::Export(args) => run_export(args, cli.config).await,
+    }
+}
+
+async fn run_comprehensive_hunt(args: HuntArgs, config_path: Option<PathBuf>) -> Result<()> {
+    info!("🚀 Starting comprehensive GitHub secret hunting");
+    spawn_version_notice(UpdateConfig::default());
+
+    let _instance_lock = InstanceLock::acquire(&args.database)?;
+    let pid_file = match args.pid_file.clone() {
+        Some(path) => Some(path),
+        None if args.daemon => Some(PathBuf::from("github-secret-hunter.pid")),
+        None => None,
+    };
+    let _pid_file = pid_file.map(PidFile::create).transpose()?;
+
+    let file_config = load_file_config(config_path.as_ref())?;
+    let synthetic_interval = args
+        .inject_synthetic
+        .then(|| std::time::Duration::from_secs(args.synthetic_interval.max(1) * 60));
+
+    let config = HunterConfig {
+        gcp_project_id: std::env::var("GCP_PROJECT_ID").unwrap_or_default(),
+        github_token: std::env::var("GITHUB_TOKEN").unwrap_or_default(),
+        redis_url: std::env::var("REDIS_URL").ok(),
+        database_path: args.database,
+        ai_model_path: args.model_path,
+        webhook_endpoints: Vec::new(),
+        scanning_options: github_archiver::integration::ScanningOptions {
+            enable_bigquery_scanning: args.bigquery,
+            enable_realtime_monitoring: args.realtime,
+            enable_ai_triage: args.ai_triage,
+            enable_secret_validation: !offline::policy().enabled(),
+            organizations_to_monitor: args.organizations,
+            minimum_entropy_threshold: 3.0,
+            scan_historical_events: true,
+            historical_days_back: 30,
+            enable_leader_election: args.leader_election,
+            rules_path: args.rules,
+            allowlist_path: args.allowlist,
+            repository_sync_interval_hours: args
+                .repository_sync_hours
+                .unwrap_or_else(github_archiver::integration::default_repository_sync_interval),
+            poll_organization_endpoints: github_archiver::integration::default_poll_organization_endpoints(),
+            shutdown_drain_secs: args
+                .drain_timeout
+                .unwrap_or_else(github_archiver::integration::default_shutdown_drain_secs),
+            mine_force_pushes: github_archiver::integration::default_mine_force_pushes(),
+            mine_branch_deletions: github_archiver::integration::default_mine_branch_deletions(),
+            local_archive_dir: args.gharchive_dir.or_else(github_archiver::integration::default_local_archive_dir),
+        },
+        performance_options: github_archiver::integration::PerformanceOptions {
+            parallel_workers: num_cpus::get(),
+            cache_size: 10000,
+            batch_size: 100,
+            rate_limit_per_hour: 5000,
+            enable_caching: true,
+            enable_deduplication: true,
+        },
+        sinks: file_config.sinks,
+        retention: file_config.retention,
+        maintenance: file_config.maintenance,
+        disk_forecast: file_config.disk_forecast,
+        quotas: file_config.quotas,
+        bigquery: file_config.bigquery,
+        redaction: file_config.redaction(),
+        notifications: file_config.notifications,
+    };
+
+    let mut hunter = GitHubSecretHunter::new(config).await?;
+    if let Some(scan_id) = &args.resume {
+        hunter.resume_scan(scan_id)?;
+    }
+    hunter.start_hunting().await?;
+
+    // Keep running until interrupted
+    info!("Secret hunting started. Press Ctrl+C to stop...");
+    wait_for_shutdown(&mut hunter, config_path.as_ref(), synthetic_interval).await?;
+    
+    hunter.stop_hunting().await?;
+    info!("Secret hunting stopped");
+
+    Ok(())
+}
+
+/// Ticker for `--inject-synthetic`; the first tick fires immediately so a broken
+/// alert path shows up at startup rather than an interval later
+fn synthetic_ticker(interval: Option<std::time::Duration>) -> tokio::time::Interval {
+    let mut ticker = tokio::time::interval(interval.unwrap_or(std::time::Duration::from_secs(3600)));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    ticker
+}
+
+async fn inject_synthetic(hunter: &mut GitHubSecretHunter) {
+    if let Err(e) = hunter.inject_synthetic_finding().await {
+        error!("Synthetic finding injection failed: {}", e);
+    }
+}
+
+/// How often a hunt checks the findings database for scans queued by `scan trigger` or
+/// `POST /api/v1/scans`
+const SCAN_REQUEST_POLL: std::time::Duration = std::time::Duration::from_secs(30);
+
+fn scan_request_ticker() -> tokio::time::Interval {
+    let mut ticker = tokio::time::interval(SCAN_REQUEST_POLL);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    ticker
+}
+
+async fn run_scan_requests(hunter: &mut GitHubSecretHunter) {
+    if let Err(e) = hunter.run_scan_requests().await {
+        error!("Triggered scans not run: {}", e);
+    }
+}
+
+/// Block until SIGTERM or Ctrl+C; SIGHUP re-reads the `--config` file
+#[cfg(unix)]
+async fn wait_for_shutdown(
+    hunter: &mut GitHubSecretHunter,
+    config_path: Option<&PathBuf>,
+    synthetic_interval: Option<std::time::Duration>,
+) -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = signal(SignalKind::hangup())?;
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut synthetic = synthetic_ticker(synthetic_interval);
+    let mut scan_requests = scan_request_ticker();
+
+    loop {
+        tokio::select! {
+            _ = synthetic.tick(), if synthetic_interval.is_some() => inject_synthetic(hunter).await,
+            _ = scan_requests.tick() => run_scan_requests(hunter).await,
+            _ = hangup.recv() => {
+                info!("SIGHUP received, reloading configuration");
+                match load_file_config(config_path) {
+                    Ok(config) => {
+                        if let Err(e) = hunter.reload_config(config.sinks, config.retention) {
+                            error!("Failed to apply reloaded configuration: {}", e);
+                        }
+                    }
+                    Err(e) => error!("Configuration reload failed, keeping current settings: {}", e),
+                }
+            }
+            _ = terminate.recv() => {
+                info!("SIGTERM received, draining");
+                return Ok(());
+            }
+            _ = shutdown_requested() => return Ok(()),
+            result = tokio::signal::ctrl_c() => return Ok(result?),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_shutdown(
+    hunter: &mut GitHubSecretHunter,
+    _config_path: Option<&PathBuf>,
+    synthetic_interval: Option<std::time::Duration>,
+) -> Result<()> {
+    let mut synthetic = synthetic_ticker(synthetic_interval);
+    let mut scan_requests = scan_request_ticker();
+
+    loop {
+        tokio::select! {
+            _ = synthetic.tick(), if synthetic_interval.is_some() => inject_synthetic(hunter).await,
+            _ = scan_requests.tick() => run_scan_requests(hunter).await,
+            _ = shutdown_requested() => {
+                info!("Service stop requested, draining");
+                return Ok(());
+            }
+            result = tokio::signal::ctrl_c() => return Ok(result?),
+        }
+    }
+}
+
+fn run_service_ops(args: ServiceArgs) -> Result<()> {
+    match args.operation {
+        #[cfg(windows)]
+        ServiceOps::Install { args, .. } => {
+            let args = if args.is_empty() { vec!["hunt".to_string()] } else { args };
+            github_archiver::daemon::windows::install(args)?;
+            println!(
+                "Registered service {}; data is kept in {}",
+                github_archiver::daemon::windows::SERVICE_NAME,
+                github_archiver::core::paths::data_dir().display()
+            );
+            println!("Start it with: sc start {}", github_archiver::daemon::windows::SERVICE_NAME);
+        }
+        #[cfg(windows)]
+        ServiceOps::Uninstall => {
+            github_archiver::daemon::windows::uninstall()?;
+            println!("Removed service {}", github_archiver::daemon::windows::SERVICE_NAME);
+        }
+        #[cfg(windows)]
+        ServiceOps::Run { .. } => {
+            return Err(anyhow::anyhow!("`service run` is started by the service control manager"));
+        }
+        #[cfg(not(windows))]
+        ServiceOps::Install { unit_path, user, env_file, args } => {
+            let unit = SystemdUnit {
+                description: "GitHub Secret Hunter".to_string(),
+                exec_path: std::env::current_exe()?,
+                args: if args.is_empty() { vec!["hunt".to_string()] } else { args },
+                working_directory: std::env::current_dir()?,
+                user,
+                environment_file: env_file,
+            };
+            unit.install(&unit_path)?;
+
+            let name = unit_path.file_name().and_then(|n| n.to_str()).unwrap_or("github-secret-hunter.service");
+            println!("Wrote {}", unit_path.display());
+            println!("Enable it with: systemctl daemon-reload && systemctl enable --now {}", name);
+        }
+    }
+
+    Ok(())
+}
+
+fn init_logging(verbose: bool) {
+    let log_level = if verbose { "debug" } else { "info" };
+    let registry = tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(format!("github_archiver={}", log_level)))
+        // Logs go to stderr so `--output json|yaml|annotations` can be piped, drawn in the output profile
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(output::style().ansi())
+                .with_writer(|| output::StyledWriter::new(std::io::stderr())),
+        );
+
+    // Services have no console; mirror warnings and errors into the Application event log
+    #[cfg(windows)]
+    let registry = registry.with(github_archiver::daemon::windows::EventLogLayer::for_service(verbose));
+
+    registry.init();
+}
+
+/// Settings from the `--config` file, falling back to environment defaults
+fn load_file_config(config_path: Option<&PathBuf>) -> Result<github_archiver::core::Config> {
+    match config_path {
+        Some(path) => github_archiver::core::Config::new(path.to_str()),
+        None => Ok(github_archiver::core::Config::default()),
+    }
+}
+
+async fn run_scan(args: ScanArgs, config_path: Option<PathBuf>) -> Result<()> {
+    if let Some(operation) = args.operation {
+        return run_scan_ops(operation, config_path).await;
+    }
+    let target = args.target.unwrap_or_default();
+    info!("🔍 Scanning target: {}", target);
+
+    let options = RepositoryScanOptions {
+        depth: args.depth,
+        max_blob_bytes: args.max_file_size,
+        history: args.history,
+        since: args.since.map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc()),
+        commit: None,
+    };
+    let redaction = load_file_config(config_path.as_ref())?.redaction();
+    match args.scan_type.as_str() {
+        "repository" => {
+            let mut config = HunterConfig::default();
+            config.redaction = redaction;
+            config.scanning_options.rules_path = args.rules;
+            config.scanning_options.allowlist_path = args.allowlist;
+            let mut hunter = GitHubSecretHunter::new(config).await?;
+            let report = hunter.scan_repository(&target, &options).await?;
+            
+            match args.output.as_str() {
+                "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+                "yaml" => println!("{}", serde_yaml::to_string(&report)?),
+                "annotations" => println!("{}", serde_json::to_string_pretty(&rdjson_report(&report.secrets_found))?),
+                _ => info!("Scan completed: {} secrets found", report.secrets_found.len()),
+            }
+
+            if args.check_run {
+                let head_sha = match args.head_sha.clone().or_else(|| std::env::var("GITHUB_SHA").ok()) {
+                    Some(sha) => sha,
+                    None => return Err(anyhow::anyhow!("--check-run needs --head-sha or GITHUB_SHA")),
+                };
+                let fail_on = args.fail_on.clone().unwrap_or(SecretSeverity::High);
+                let publisher = CheckRunPublisher::new(GitHubCredentials::from_env())?;
+                let url = publisher.publish(&target, &head_sha, &report.secrets_found, &fail_on).await?;
+                info!("✅ Check run posted: {}", url);
+            }
+            fail_on_findings(&report.secrets_found, args.fail_on.as_ref())?;
+        }
+        "organization" => {
+            if args.check_run {
+                return Err(anyhow::anyhow!("--check-run is only supported for repository scans"));
+            }
+            let mut config = HunterConfig::default();
+            config.redaction = redaction;
+            config.scanning_options.rules_path = args.rules;
+            config.scanning_options.allowlist_path = args.allowlist;
+            let mut hunter = GitHubSecretHunter::new(config).await?;
+            let options = OrganizationScanOptions {
+                repository: options,
+                include_forks: !args.skip_forks,
+                include_archived: !args.skip_archived,
+                concurrency: args.concurrency.unwrap_or(0),
+            };
+            let report = hunter.scan_organization(&target, &options).await?;
+
+            match args.output.as_str() {
+                "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+                "yaml" => println!("{}", serde_yaml::to_string(&report)?),
+                "annotations" => println!("{}", serde_json::to_string_pretty(&rdjson_report(&report.secrets_found))?),
+                _ => {
+                    for repository in &report.repositories {
+                        println!(
+                            "{:<50} {:<9} {:>6} commits {:>4} findings {:>4} duplicates{}",
+                            repository.repository,
+                            format!("{:?}", repository.status),
+                            repository.commits_scanned,
+                            repository.findings,
+                            repository.duplicates,
+                            repository.error.as_ref().map_or(String::new(), |e| format!("  {}", e)),
+                        );
+                    }
+                    info!(
+                        "Scan completed: {} repositories, {} secrets found",
+                        report.repositories.len(),
+                        report.secrets_found.len()
+                    );
+                }
+            }
+            fail_on_findings(&report.secrets_found, args.fail_on.as_ref())?;
+        }
+        _ => {
+            error!("Unsupported scan type: {}", args.scan_type);
+        }
+    }
+
+    Ok(())
+}
+
+/// How often `scan trigger --wait` checks whether the hunter finished the scan
+const SCAN_REQUEST_WAIT_POLL: std::time::Duration = std::time::Duration::from_secs(5);
+
+async fn run_scan_ops(operation: ScanOps, config_path: Option<PathBuf>) -> Result<()> {
+    match operation {
+        ScanOps::Trigger { repository, commit, callback, source, database, wait } => {
+            let database = match database {
+                Some(database) => database,
+                None => load_file_config(config_path.as_ref())?.web.findings_database,
+            };
+            let db = SecretDatabase::new(&database)?;
+            let requested_by = std::env::var("USER").unwrap_or_else(|_| "cli".to_string());
+            let new_request = NewScanRequest { repository, commit_sha: commit, callback_url: callback, source };
+            let mut request = db.queue_scan_request(&new_request, &requested_by)?;
+            info!("Queued scan request {}; the running hunter picks it up", request.id);
+
+            while wait && !request.is_finished() {
+                tokio::time::sleep(SCAN_REQUEST_WAIT_POLL).await;
+                request = db.scan_request(request.id)?.ok_or_else(|| anyhow::anyhow!("Scan request {} was removed", request.id))?;
+            }
+            println!("{}", serde_json::to_string_pretty(&request)?);
+            if request.status == "failed" {
+                return Err(anyhow::anyhow!(
+                    "Scan request {} failed: {}",
+                    request.id,
+                    request.error.as_deref().unwrap_or("unknown error")
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// CI gate: an error, and so a non-zero exit, when an active finding is at or above `fail_on`
+fn fail_on_findings(findings: &[github_archiver::SecretMatch], fail_on: Option<&SecretSeverity>) -> Result<()> {
+    let Some(fail_on) = fail_on else {
+        return Ok(());
+    };
+    let failing = findings.iter().filter(|f| !f.suppressed && severity_rank(&f.severity) >= severity_rank(fail_on)).count();
+    if failing > 0 {
+        return Err(anyhow::anyhow!("{} findings at or above {:?} severity", failing, fail_on));
+    }
+    Ok(())
+}
+
+async fn run_status(args: StatusArgs, config_path: Option<PathBuf>) -> Result<()> {
+    let config = load_file_config(config_path.as_ref())?;
+    let database = args.database.unwrap_or_else(|| config.web.findings_database.clone());
+    let api_url = args.api_url.unwrap_or_else(|| {
+        let host = match config.web.host.as_str() {
+            "0.0.0.0" | "::" => "127.0.0.1",
+            host => host,
+        };
+        format!("http://{}:{}", host, config.web.port)
+    });
+
+    let status = github_archiver::integration::collect_status(&config, &database, &api_url).await;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+    } else {
+        print!("{}", status);
+    }
+    Ok(())
+}
+
+/// The first line of standard input, or a generated temporary password when `from_stdin` is false
+fn password_input(from_stdin: bool) -> Result<(String, bool)> {
+    if !from_stdin {
+        return Ok((temporary_password(), true));
+    }
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok((line.trim_end_matches(['\r', '\n']).to_string(), false))
+}
+
+fn parse_role(name: &str) -> Result<Role> {
+    Role::parse(name).ok_or_else(|| anyhow::anyhow!("Unknown role {}: use admin, analyst or viewer", name))
+}
+
+async fn run_admin(args: AdminArgs, config_path: Option<PathBuf>) -> Result<()> {
+    let file_config = load_file_config(config_path.as_ref())?;
+    let database = args.database.unwrap_or_else(|| file_config.security.users_database.clone());
+    // Only `add` bootstraps accounts; opening the store must not create one from ADMIN_PASSWORD
+    let security = SecurityConfig { admin_password: None, ..file_config.security.clone() };
+    let users = UserManager::open(&database, &security)?;
+    let audit = AuditLog::from_config(&file_config.logging);
+
+    let operation = match args.operation {
+        AdminOps::Users(operation) => operation,
+        AdminOps::Approvals(ApprovalOps::List { status }) => {
+            let status = status
+                .map(|name| {
+                    ApprovalStatus::parse(&name)
+                        .ok_or_else(|| anyhow::anyhow!("Unknown status {}: use pending, approved, rejected or executed", name))
+                })
+                .transpose()?;
+            for request in users.approval_requests(status).await? {
+                println!(
+                    "#{:<5} {:<7} {:<9} requested by {} at {}{}",
+                    request.id,
+                    request.action.as_str(),
+                    request.status.as_str(),
+                    request.requested_by,
+                    request.requested_at.to_rfc3339(),
+                    request.decided_by.map_or(String::new(), |by| format!(", decided by {}", by)),
+                );
+                println!("       {}", request.target);
+            }
+            return Ok(());
+        }
+    };
+    match operation {
+        UserOps::List => {
+            for user in users.list_users().await? {
+                println!(
+                    "{:<24} {:<8} {:<9} last login {}{}",
+                    user.username,
+                    user.role,
+                    if user.is_active { "active" } else { "disabled" },
+                    user.last_login.map_or("never".to_string(), |at| at.to_rfc3339()),
+                    if users.password_change_required(&user) { "  (password change required)" } else { "" },
+                );
+            }
+        }
+        UserOps::Add { username, role, password_stdin } => {
+            let role = parse_role(&role)?;
+            let (password, generated) = password_input(password_stdin)?;
+            let user = users.create_user(&username, &password, role, generated).await?;
+            audit.record("user_created", "cli", serde_json::json!({ "username": user.username, "role": user.role }))?;
+            println!("Added {} ({})", user.username, user.role);
+            if generated {
+                println!("Temporary password: {}", password);
+                println!("It must be changed at first sign-in.");
+            }
+        }
+        UserOps::SetRole { username, role } => {
+            let user = users.update_user(&username, Some(parse_role(&role)?), None).await?;
+            audit.record("user_updated", "cli", serde_json::json!({ "username": user.username, "role": user.role }))?;
+            println!("{} is now {}", user.username, user.role);
+        }
+        UserOps::Disable { username } => {
+            let user = users.update_user(&username, None, Some(false)).await?;
+            audit.record("user_updated", "cli", serde_json::json!({ "username": user.username, "active": false }))?;
+            println!("Disabled {} and revoked their sessions", user.username);
+        }
+        UserOps::Enable { username } => {
+            let user = users.update_user(&username, None, Some(true)).await?;
+            audit.record("user_updated", "cli", serde_json::json!({ "username": user.username, "active": true }))?;
+            println!("Enabled {}", user.username);
+        }
+        UserOps::ResetPassword { username, password_stdin } => {
+            let (password, generated) = password_input(password_stdin)?;
+            users.reset_password(&username, &password).await?;
+            audit.record("password_reset", "cli", serde_json::json!({ "username": username }))?;
+            if generated {
+                println!("Temporary password for {}: {}", username, password);
+            }
+            println!("{} must change the password at next sign-in; their sessions were revoked", username);
+        }
+        UserOps::RevokeSessions { username } => {
+            let revoked = users.revoke_sessions(&username).await?;
+            audit.record("sessions_revoked", "cli", serde_json::json!({ "username": username, "sessions": revoked }))?;
+            println!("Revoked {} sessions of {}", revoked, username);
+        }
+    }
+    Ok(())
+}
+
+async fn run_recover_commits(args: RecoverCommitsArgs) -> Result<()> {
+    let token = std::env::var("GITHUB_TOKEN").ok();
+    let recovery = DanglingCommitRecovery::new(token)?.with_api_budget(args.api_budget);
+
+    let mut recovered = Vec::new();
+    for prefix in &args.prefixes {
+        recovered.extend(recovery.recover(&args.repository, prefix).await?);
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&recovered)?);
+        return Ok(());
+    }
+    if recovered.is_empty() {
+        println!("No commits found for {} in {}", args.prefixes.join(", "), args.repository);
+    }
+    for commit in &recovered {
+        let summary = commit.object.as_ref().map_or("(object not served)", |o| o.message.lines().next().unwrap_or(""));
+        println!(
+            "{}  {:<8}  {}  {}",
+            commit.sha,
+            match commit.source {
+                CandidateSource::RefAdvertisement => "ref",
+                CandidateSource::CommitApi => "api",
+            },
+            commit.advertised_ref.as_deref().unwrap_or("unreferenced"),
+            summary
+        );
+    }
+    Ok(())
+}
+
+async fn run_export(args: ExportArgs, config_path: Option<PathBuf>) -> Result<()> {
+    let database = match args.database {
+        Some(database) => database,
+        None => load_file_config(config_path.as_ref())?.web.findings_database,
+    };
+
+    match args.operation {
+        ExportOps::Dataset { output, format, test_fraction, seed, approval } => {
+            let export_format = format.parse::<ExportFormat>()?;
+            let target = format!("dataset of {} as {} to {}", database, format, output);
+            require_approval(config_path.as_ref(), ApprovalAction::Export, &target, approval).await?;
+            let db = SecretDatabase::new(&database)?;
+
+            let out: Box<dyn std::io::Write + Send> = if output == "-" {
+                Box::new(std::io::stdout())
+            } else {
+                Box::new(std::fs::File::create(&output)?)
+            };
+            let mut out = std::io::BufWriter::new(out);
+            let report = db.export_dataset(&DatasetOptions { test_fraction, seed }, export_format, &mut out)?;
+            std::io::Write::flush(&mut out)?;
+            info!(
+                "Dataset exported: {} examples ({} TP, {} FP; {} train, {} test), {} unlabeled findings left out",
+                report.examples(),
+                report.true_positives,
+                report.false_positives,
+                report.train,
+                report.test,
+                report.unlabeled
+            );
+        }
+    }
+    Ok(())
+}
+
+async fn run_alerts(args: AlertsArgs, config_path: Option<PathBuf>) -> Result<()> {
+    let database = match args.database {
+        Some(database) => database,
+        None => load_file_config(config_path.as_ref())?.web.findings_database,
+    };
+    let db = SecretDatabase::new(&database)?;
+
+    match args.operation {
+        AlertsOps::List { failed, limit } => {
+            for alert in db.list_alerts(limit, failed)? {
+                println!(
+                    "[{}] {} {} in {} ({} secrets): {} delivered, {} failed",
+                    alert.id, alert.created_at, alert.severity, alert.repository, alert.secrets, alert.delivered, alert.failed
+                );
+            }
+        }
+        AlertsOps::Show { id } => {
+            let stored = db.alert(id)?.ok_or_else(|| anyhow::anyhow!("No alert {}", id))?;
+            println!("{}", serde_json::to_string_pretty(&stored)?);
+        }
+        AlertsOps::Resend { id, targets } => {
+            let stored = db.alert(id)?.ok_or_else(|| anyhow::anyhow!("No alert {}", id))?;
+            let targets = if targets.is_empty() { AlertTarget::failed_in(&stored.deliveries) } else { targets };
+            if targets.is_empty() {
+                println!("Every channel of alert {} was delivered; pass --to to send it elsewhere", id);
+                return Ok(());
+            }
+
+            let (notifier, monitor) = notification_channels(config_path.as_ref()).await?;
+            let mut failed = 0;
+            for target in &targets {
+                let delivery = resend_alert(&notifier, &monitor, &stored.alert, target).await;
+                db.record_deliveries(id, &delivery, true)?;
+                for channel in &delivery.delivered_to {
+                    println!("{}", output::style().text(&format!("✅ {}", channel)));
+                }
+                for error in &delivery.errors {
+                    println!("{}", output::style().text(&format!("❌ {}", error)));
+                }
+                failed += delivery.errors.len();
+            }
+            if failed > 0 {
+                return Err(anyhow::anyhow!("{} deliveries of alert {} failed", failed, id));
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run_canary(args: CanaryArgs, config_path: Option<PathBuf>) -> Result<()> {
+    let file_config = load_file_config(config_path.as_ref())?;
+    let database = args.database.unwrap_or_else(|| file_config.web.findings_database.clone());
+    let db = SecretDatabase::new(&database)?;
+
+    let seed = |canary: &CanaryToken, path: &std::path::Path, format: SeedFormat| -> Result<()> {
+        seed_file(canary, path, format)?;
+        let location = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        db.add_canary_location(&canary.id, &location.display().to_string())?;
+        println!("{}", output::style().text(&format!("🌱 Seeded {} into {}", canary.id, location.display())));
+        Ok(())
+    };
+
+    match args.operation {
+        CanaryOps::Create { kind, provider, label, seed: paths, format } => {
+            let canary = match provider {
+                CanaryProvider::Local => generate_local(kind, &label),
+                CanaryProvider::Canarytokens => {
+                    generate_canarytoken(&file_config.canary, &file_config.http, kind, &label).await?
+                }
+            };
+            db.save_canary(&canary)?;
+            let line = format!("🐤 Canary {} ({}, {})", canary.id, canary.kind, canary.provider.as_str());
+            println!("{}", output::style().text(&line));
+            print!("{}", seed_text(&canary, SeedFormat::Env)?);
+            for path in &paths {
+                seed(&canary, path, format)?;
+            }
+        }
+        CanaryOps::Seed { id, location, format, record_only } => {
+            let canary = db.canary(&id)?.ok_or_else(|| anyhow::anyhow!("No canary {}", id))?;
+            if record_only {
+                db.add_canary_location(&canary.id, &location)?;
+                println!("Recorded {} for canary {}", location, canary.id);
+            } else {
+                seed(&canary, std::path::Path::new(&location), format)?;
+            }
+        }
+        CanaryOps::List => {
+            for canary in db.list_canaries()? {
+                println!(
+                    "[{}] {} {} \"{}\": {} locations, {} sightings{}{}",
+                    canary.id,
+                    canary.kind,
+                    canary.provider.as_str(),
+                    canary.label,
+                    canary.locations.len(),
+                    canary.trips,
+                    canary.last_tripped_at.map(|at| format!(", last {}", at)).unwrap_or_default(),
+                    if canary.retired_at.is_some() { " (retired)" } else { "" },
+                );
+            }
+        }
+        CanaryOps::Show { id } => {
+            let canary = db.canary(&id)?.ok_or_else(|| anyhow::anyhow!("No canary {}", id))?;
+            let trips = db.canary_trips(&id)?;
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "canary": canary, "sightings": trips }))?);
+        }
+        CanaryOps::Retire { id } => {
+            db.retire_canary(&id)?;
+            println!("Canary {} retired; it no longer raises alerts", id);
+        }
+    }
+    Ok(())
+}
+
+async fn run_gui(args: GuiArgs) -> Result<()> {
+    info!("🎨 Launching Secrets Ninja GUI");
+    if args.read_only {
+        // Fail early when the database cannot be served read-only (missing or needs migrating)
+        SecretDatabase::open_read_only(&args.database)?;
+        info!("Read-only mode: {} is opened read-only and notes are disabled", args.database);
+    }
+    
+    // This would launch the Iced GUI application
+    info!("GUI application would launch here");
+    info!("Use: iced::run(SecretsNinjaApp::new(), Settings::default())");
+    
+    Ok(())
+}
+
+async fn run_bigquery_scan(args: BigQueryArgs, config_path: Option<PathBuf>) -> Result<()> {
+    info!("📊 Running BigQuery historical scan");
+
+    let file_config = load_file_config(config_path.as_ref())?;
+    let max_bytes_billed = file_config.bigquery.max_bytes_billed;
+    let scanner = BigQueryScanner::new_with_default_credentials(args.project)
+        .await?
+        .with_cost_limits(file_config.bigquery);
+    let end_date = chrono::Utc::now().date_naive();
+    let start_date = end_date - chrono::Duration::days(args.days as i64);
+    let filter = RepositoryFilter { organizations: args.organization.into_iter().collect(), ..Default::default() };
+
+    if args.dry_run {
+        let estimate = scanner.estimate_zero_commit_scan(start_date, end_date, &filter, None).await?;
+        info!("🧮 Scanning {} to {} would process {}", start_date, end_date, estimate);
+        if max_bytes_billed > 0 && estimate.bytes_processed > max_bytes_billed {
+            warn!("The scan would be refused: it is over BIGQUERY_MAX_BYTES_BILLED ({} bytes)", max_bytes_billed);
+        }
+        return Ok(());
+    }
+
+    let events = scanner.scan_zero_commit_events(start_date, end_date, &filter, None).await?;
+    info!("Found {} zero-commit events", events.len());
+    for event in events.iter().take(10) {
+        info!("Event: {} -> {} ({})", event.repo_name, event.before_commit, event.created_at);
+    }
+
+    Ok(())
+}
+
+async fn run_realtime_monitor(args: MonitorArgs, config_path: Option<PathBuf>) -> Result<()> {
+    info!("⚡ Starting real-time GitHub event monitoring");
+    spawn_version_notice(UpdateConfig::default());
+
+    let min_severity = args.min_severity.as_deref().map(str::parse).transpose()?;
+    let filter = EventFilter::new(&args.include_events, &args.exclude_events, min_severity, args.repo_patterns)?
+        .with_owners(&args.organizations, &args.users)?;
+    if args.target_endpoints && !filter.has_targets() {
+        return Err(anyhow::anyhow!("--target-endpoints needs --organizations, --user or --repo"));
+    }
+    let webhook_mode = match args.mode.as_str() {
+        "poll" => false,
+        "webhook" => true,
+        other => return Err(anyhow::anyhow!("Unknown monitor mode {:?}; use poll or webhook", other)),
+    };
+    if webhook_mode && (args.target_endpoints || args.leader_election) {
+        return Err(anyhow::anyhow!("--target-endpoints and --leader-election only apply to poll mode"));
+    }
+
+    let file_config = load_file_config(config_path.as_ref())?;
+    let notifier = Notifier::from_config(&file_config.notifications, &file_config.http)?;
+    let mut monitor = GitHubEventMonitor::new()?
+        .with_event_filter(filter)
+        .with_target_endpoints(args.target_endpoints)
+        .with_poll_interval(std::time::Duration::from_secs(args.interval))
+        .with_notifier(Arc::new(notifier))
+        .with_chat_notifiers(ChatNotifier::from_targets(&file_config.notifications.chat)?)
+        .with_incident_notifiers(IncidentNotifier::from_targets(&file_config.notifications.incidents, &file_config.http)?)
+        .with_alert_log(file_config.web.findings_database.clone())
+        .with_canaries(file_config.web.findings_database.clone())
+        .with_event_queue(EventQueue::open(
+            std::env::var("REDIS_URL").ok().as_deref(),
+            &file_config.web.findings_database,
+        )?)
+        .with_quota(WorkspaceQuota::new(&file_config.quotas, &file_config.web.findings_database)?)
+        .with_redaction_floor(file_config.redaction());
+    monitor.add_webhook_targets(&file_config.notifications.webhooks).await?;
+    if let Some(users) = UserManager::open_for_alerts(&file_config.security)? {
+        monitor = monitor.with_user_alerts(Arc::new(users));
+    }
+    if args.leader_election {
+        let redis_url = std::env::var("REDIS_URL")
+            .map_err(|_| anyhow::anyhow!("--leader-election requires REDIS_URL"))?;
+        monitor = monitor.with_leader_election(LeaderElector::from_env(&redis_url)?);
+    }
+    
+    // Add webhook if provided
+    if let Some(webhook_url) = args.webhook {
+        monitor.add_webhook_endpoint(
+            webhook_url,
+            None,
+            vec!["push".to_string()],
+        ).await?;
+    }
+
+    // Start monitoring; Ctrl+C finishes the current batch of events and stops
+    let shutdown = tokio_util::sync::CancellationToken::new();
+    let stop = shutdown.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            info!("Stopping the monitor");
+            stop.cancel();
+        }
+    });
+    if webhook_mode {
+        let listen: std::net::SocketAddr = args.listen.parse()?;
+        let secret = std::env::var(github_archiver::realtime::WEBHOOK_SECRET_ENV).unwrap_or_default();
+        Arc::new(monitor).serve_webhooks(listen, &secret, shutdown).await?;
+    } else {
+        monitor.start_monitoring(shutdown).await?;
+    }
+
+    Ok(())
+}
+
+async fn run_ai_triage(args: TriageArgs) -> Result<()> {
+    info!("🤖 Running AI triage on existing secrets");
+
+    let database = SecretDatabase::new(&args.database)?;
+    let filters = github_archiver::performance::SecretQueryFilters {
+        min_severity: Some(match args.min_severity.as_str() {
+            "critical" => github_archiver::secrets::SecretSeverity::Critical,
             "high" => github_archiver::secrets::SecretSeverity::High,
             "medium" => github_archiver::secrets::SecretSeverity::Medium,
             _ => github_archiver::secrets::SecretSeverity::Low,
         }),
+        category: None,
         detector_name: None,
+        filename: None,
         verified_only: false,
         last_n_days: Some(7),
+        since: None,
+        until: None,
         limit: Some(100),
+        tags: Vec::new(),
+        repository: None,
+        include_suppressed: false,
+        after: None,
+        as_of: None,
     };
 
     let secrets = database.query_secrets(&filters)?;
@@ -397,51 +2167,829 @@ async fn run_ai_triage(args: TriageArgs) -> Result<()> {
     Ok(())
 }
 
-async fn run_database_ops(args: DatabaseArgs) -> Result<()> {
+async fn run_import(args: ImportArgs) -> Result<()> {
+    let format: ImportFormat = args.format.parse()?;
+    info!("📥 Importing {} findings from {}", format.as_str(), args.file);
+
+    let report = FindingImporter::new().import_file(format, &args.file)?;
+    let database = SecretDatabase::new(&args.database)?;
+    database.bulk_insert_secrets(&report.matches)?;
+    deliver_search_alerts(&database, &[]).await?;
+
+    println!("Imported {} findings ({} skipped)", report.matches.len(), report.skipped);
+    if !report.unmapped_detectors.is_empty() {
+        println!("Unmapped detectors: {}", report.unmapped_detectors.join(", "));
+    }
+
+    Ok(())
+}
+
+async fn run_scan_archive(args: ScanArchiveArgs, config_path: Option<PathBuf>) -> Result<()> {
+    let files = ArchiveSecretScanner::collect_files(&args.inputs)?;
+    if files.is_empty() {
+        return Err(anyhow::anyhow!("No .json.gz archives found"));
+    }
+    info!("📦 Scanning {} GH Archive files", files.len());
+
+    let database = if args.dry_run { None } else { Some(SecretDatabase::new(&args.database)?) };
+    let mut scanner = SecretScanner::new();
+    if let Some(rules) = &args.rules {
+        scanner.load_detectors_from_dir(rules)?.log();
+    }
+    let mut allowlist = match &args.allowlist {
+        Some(path) => Allowlist::from_file(path)?,
+        None if std::path::Path::new(DEFAULT_IGNORE_FILE).is_file() => Allowlist::from_file(DEFAULT_IGNORE_FILE)?,
+        None => Allowlist::new(),
+    };
+    if let Some(db) = &database {
+        allowlist.merge(db.load_allowlist()?);
+    }
+    scanner.set_allowlist(allowlist);
+    let redaction = load_file_config(config_path.as_ref())?.redaction();
+
+    let stats = ArchiveSecretScanner::new(&scanner)
+        .with_progress_interval(std::time::Duration::from_secs(args.progress_secs.max(1)))
+        .scan_files(&files, |batch| {
+            match &database {
+                Some(db) => db.bulk_insert_secrets(&batch)?,
+                None => {
+                    for found in batch.iter().filter(|found| !found.suppressed) {
+                        println!("{}", serde_json::to_string(&redaction.redact_match(found))?);
+                    }
+                }
+            }
+            Ok(())
+        })?;
+
+    if let Some(db) = &database {
+        deliver_search_alerts(db, &[]).await?;
+    }
+    println!(
+        "Scanned {} files: {} events, {} push events, {} commits, {} findings ({} suppressed, {} malformed lines)",
+        stats.files,
+        stats.events,
+        stats.push_events,
+        stats.commits_scanned,
+        stats.findings,
+        stats.suppressed,
+        stats.malformed_lines
+    );
+    Ok(())
+}
+
+async fn run_notify_ops(args: NotifyArgs, config_path: Option<PathBuf>) -> Result<()> {
+    match args.operation {
+        NotifyOps::Test { channel, webhook, webhook_secret, dry_run } => {
+            let (notifier, monitor) = notification_channels(config_path.as_ref()).await?;
+            if let Some(url) = webhook {
+                monitor.add_webhook_endpoint(url, webhook_secret, Vec::new()).await?;
+            }
+            let alert = sample_alert();
+
+            let channels = match channel.as_str() {
+                "all" => vec!["slack", "email", "webhook"],
+                "slack" | "email" | "webhook" => vec![channel.as_str()],
+                other => {
+                    return Err(anyhow::anyhow!("Unknown channel {:?}; use slack, email, webhook or all", other));
+                }
+            };
+
+            let mut failed = 0;
+            for name in channels {
+                let configured = match name {
+                    "slack" => {
+                        println!("{}\n{}\n", output::style().text("── Slack ──"), notifier.templates().slack_text(&alert)?);
+                        notifier.slack_enabled()
+                    }
+                    "email" => {
+                        let (subject, body) = notifier.templates().email(&alert)?;
+                        println!("{}\nSubject: {}\n\n{}", output::style().text("── Email ──"), subject, body);
+                        notifier.email_enabled()
+                    }
+                    _ => {
+                        let payload = notifier.templates().webhook_payload(&alert)?;
+                        println!("{}\n{}\n", output::style().text("── Webhook ──"), serde_json::to_string_pretty(&payload)?);
+                        !monitor.active_webhooks().await.is_empty()
+                    }
+                };
+
+                if dry_run {
+                    continue;
+                }
+                if !configured {
+                    if channel == "all" {
+                        println!("{}: not configured, skipped", name);
+                        continue;
+                    }
+                    return Err(anyhow::anyhow!("The {} channel is not configured", name));
+                }
+
+                let result = match name {
+                    "slack" => notifier.send_slack(&alert).await,
+                    "email" => notifier.send_email(&alert).await,
+                    _ => monitor.send_webhooks(&alert).await.into_iter().map(|(_, result)| result).collect(),
+                };
+                match result {
+                    Ok(()) => println!("{}: sent", name),
+                    Err(e) => {
+                        println!("{}: failed: {}", name, e);
+                        failed += 1;
+                    }
+                }
+            }
+
+            if failed > 0 {
+                return Err(anyhow::anyhow!("{} notification channel(s) failed", failed));
+            }
+        }
+        NotifyOps::Verify { json } => {
+            let (notifier, monitor) = notification_channels(config_path.as_ref()).await?;
+            let checks = verify_endpoints(&notifier, &monitor).await;
+            if checks.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "No notification endpoints are configured (SLACK_WEBHOOK_URL, SMTP_HOST, or webhooks in the config file)"
+                ));
+            }
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&checks)?);
+            } else {
+                println!("{:<8} {:<45} {:<7} {:<10} {:>8}", "CHANNEL", "ENDPOINT", "SIGNED", "RESULT", "LATENCY");
+                for check in &checks {
+                    println!(
+                        "{:<8} {:<45} {:<7} {:<10} {:>5} ms",
+                        check.channel,
+                        check.endpoint,
+                        if check.channel != "webhook" { "-" } else if check.signed { "yes" } else { "NO" },
+                        if check.delivered { "delivered" } else { "FAILED" },
+                        check.latency_ms
+                    );
+                    if let Some(error) = &check.error {
+                        println!("{}", output::style().text(&format!("         └─ {}", error)));
+                    }
+                }
+            }
+
+            let failed = checks.iter().filter(|c| !c.delivered).count();
+            if failed > 0 {
+                return Err(anyhow::anyhow!("{} of {} notification endpoint(s) failed", failed, checks.len()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Notifier and webhook endpoints from the `notifications` section of the `--config` file
+async fn notification_channels(config_path: Option<&PathBuf>) -> Result<(Arc<Notifier>, GitHubEventMonitor)> {
+    let file_config = load_file_config(config_path)?;
+    let notifier = Arc::new(Notifier::from_config(&file_config.notifications, &file_config.http)?);
+    let monitor = GitHubEventMonitor::new()?
+        .with_notifier(notifier.clone())
+        .with_chat_notifiers(ChatNotifier::from_targets(&file_config.notifications.chat)?);
+    monitor.add_webhook_targets(&file_config.notifications.webhooks).await?;
+    Ok((notifier, monitor))
+}
+
+async fn run_self_update(args: SelfUpdateArgs) -> Result<()> {
+    let mut config = UpdateConfig::default();
+    if let Some(channel) = args.channel {
+        config.channel = channel.parse()?;
+    }
+
+    info!("🔄 Checking the {} channel for updates (running {})", config.channel.as_str(), CURRENT_VERSION);
+    let updater = SelfUpdater::new(config)?;
+
+    let Some(release) = updater.check().await? else {
+        println!("Already running the latest version ({})", CURRENT_VERSION);
+        return Ok(());
+    };
+
+    println!("Version {} is available (running {})", release.version, CURRENT_VERSION);
+    if args.check {
+        return Ok(());
+    }
+
+    let path = updater.install(&release).await?;
+    println!("Installed {} at {}. Restart running instances to pick it up.", release.version, path.display());
+    Ok(())
+}
+
+/// Let `action` go ahead only under an approved request when the approval policy covers it.
+/// Requests are filed through the API, where the requester is the signed-in user; the CLI
+/// has no identity of its own to file them under.
+async fn require_approval(
+    config_path: Option<&PathBuf>,
+    action: ApprovalAction,
+    target: &str,
+    approval: Option<i64>,
+) -> Result<()> {
+    let file_config = load_file_config(config_path)?;
+    if !ApprovalPolicy::from_config(&file_config.security)?.requires_approval(action) {
+        return Ok(());
+    }
+    let Some(id) = approval else {
+        return Err(anyhow::anyhow!(
+            "{} needs a second person's approval. File a request while signed in with \
+            POST /api/v1/approvals {}, have another user approve it, then rerun with --approval <id>",
+            action.as_str(),
+            serde_json::json!({ "action": action, "target": target })
+        ));
+    };
+    let security = SecurityConfig { admin_password: None, ..file_config.security.clone() };
+    let users = UserManager::open(&security.users_database, &security)?;
+    let audit = AuditLog::from_config(&file_config.logging);
+    let request = users.execute_approved(id, action, target).await?;
+    audit.record(
+        "approved_action_executed",
+        &request.requested_by,
+        serde_json::json!({
+            "request": request.id,
+            "action": action,
+            "target": target,
+            "requested_by": request.requested_by,
+            "approved_by": request.decided_by,
+        }),
+    )?;
+    info!("Running {} under request #{}, approved by {}", action.as_str(), id, request.decided_by.unwrap_or_default());
+    Ok(())
+}
+
+async fn run_database_ops(args: DatabaseArgs, config_path: Option<PathBuf>) -> Result<()> {
     match args.operation {
         DatabaseOps::Init { path } => {
             info!("🗄️ Initializing database: {}", path);
             let _db = SecretDatabase::new(&path)?;
             info!("Database initialized successfully");
         }
-        DatabaseOps::Query { path, limit } => {
+        DatabaseOps::Query { path, limit, filters, include_suppressed } => {
             info!("🔍 Querying database: {}", path);
             let db = SecretDatabase::new(&path)?;
-            let filters = github_archiver::performance::SecretQueryFilters {
-                min_severity: None,
-                detector_name: None,
-                verified_only: false,
-                last_n_days: None,
-                limit,
-            };
+            let filters = filters.into_filters(limit, include_suppressed)?;
             let secrets = db.query_secrets(&filters)?;
             info!("Found {} secrets", secrets.len());
             for secret in secrets.iter().take(5) {
                 info!("  - {} ({})", secret.detector_name, secret.severity);
             }
         }
+        DatabaseOps::Snapshot { path, as_of, json } => {
+            let db = SecretDatabase::new(&path)?;
+            let snapshot = db.snapshot(parse_as_of(&as_of)?)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&snapshot)?);
+            } else {
+                println!("Findings as of {}: {}", snapshot.as_of, snapshot.total);
+                for count in &snapshot.counts {
+                    println!("  {:<10} {:<12} {}", count.severity, count.status, count.findings);
+                }
+            }
+        }
+        DatabaseOps::History { path, hash } => {
+            let db = SecretDatabase::new(&path)?;
+            for version in db.finding_history(&hash)? {
+                let state = if version.deleted {
+                    "deleted".to_string()
+                } else if version.suppressed {
+                    format!("{} {} (suppressed)", version.severity, version.status)
+                } else {
+                    format!("{} {}", version.severity, version.status)
+                };
+                println!("{}  {}", version.valid_from, state);
+            }
+        }
+        DatabaseOps::Similar { path, hash, limit, json } => {
+            let db = SecretDatabase::new(&path)?;
+            let similar = db.similar_findings(&hash, limit)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&similar)?);
+            } else if similar.is_empty() {
+                println!("No findings like {}", hash);
+            } else {
+                for entry in &similar {
+                    let finding = &entry.finding;
+                    println!(
+                        "{:.2}  {}  {}  {}:{}  ({})",
+                        entry.score,
+                        finding.secret_hash,
+                        finding.detector_name,
+                        finding.filename.as_deref().unwrap_or("-"),
+                        finding.line_number.map(|l| l.to_string()).unwrap_or_default(),
+                        entry.reasons.join(", ")
+                    );
+                }
+            }
+        }
         DatabaseOps::Optimize { path } => {
             info!("⚡ Optimizing database: {}", path);
             let engine = PerformanceEngine::new();
             engine.optimize_database(&path).await?;
             info!("Database optimization completed");
         }
-        DatabaseOps::Export { path, output } => {
-            info!("📤 Exporting database: {} -> {}", path, output);
-            // Would implement export functionality
-            info!("Export completed");
+        DatabaseOps::Compress { path, batch_size } => {
+            let db = SecretDatabase::new(&path)?;
+            info!("🗜️ Compressing payload columns in {}", path);
+            for column in db.compress_existing_rows(batch_size.max(1), None)? {
+                let saved = column.bytes_before.saturating_sub(column.bytes_after);
+                info!(
+                    "  - {} ({:?}): {} rows, {} KB saved",
+                    column.column,
+                    column.codec,
+                    column.rows,
+                    saved / 1024
+                );
+            }
+            info!("Run `database maintain` or `database optimize` to return the freed pages to the file system");
+        }
+        DatabaseOps::Maintain { path, force, archive } => {
+            let file_config = load_file_config(config_path.as_ref())?;
+            let db = SecretDatabase::new(&path)?;
+            let report = db.run_maintenance(&file_config.maintenance, force)?;
+            if let Some(reason) = &report.deferred {
+                return Err(anyhow::anyhow!("Maintenance deferred: {}; use --force to run anyway", reason));
+            }
+            db.record_heartbeat("maintenance", &serde_json::to_value(&report)?)?;
+            info!(
+                "🧹 Reclaimed {} of {} free pages, {} findings since the last ANALYZE{}",
+                report.vacuumed_pages,
+                report.free_pages,
+                report.rows_since_analyze,
+                if report.analyzed { " (analyzed)" } else { "" }
+            );
+            if !report.incremental_vacuum_enabled {
+                warn!("Incremental vacuum is off for {}; run `database optimize` once to enable it", path);
+            }
+
+            if archive {
+                let mut manager = DatabaseManager::new(file_config);
+                manager.connect().await?;
+                let hints = manager.maintenance_hints().await?;
+                manager.disconnect().await?;
+                if hints.is_empty() {
+                    info!("No autovacuum tuning needed for the archive database");
+                }
+                for hint in hints {
+                    println!("{}", hint);
+                }
+            }
+        }
+        DatabaseOps::Export { path, output, format, filters, approval } => {
+            let export_format = (format != "stix").then(|| format.parse::<ExportFormat>()).transpose()?;
+            let target = format!("{} as {} to {}", path, format, output);
+            require_approval(config_path.as_ref(), ApprovalAction::Export, &target, approval).await?;
+            info!("📤 Exporting database: {} -> {} ({})", path, output, format);
+            let db = SecretDatabase::new(&path)?;
+            // Records carry the flag, so exports keep suppressed findings apart; STIX shares
+            // only verified, active findings
+            let mut filters = filters.into_filters(None, format != "stix")?;
+            filters.verified_only = format == "stix";
+
+            let out: Box<dyn std::io::Write + Send> = if output == "-" {
+                Box::new(std::io::stdout())
+            } else {
+                Box::new(std::fs::File::create(&output)?)
+            };
+            let mut out = std::io::BufWriter::new(out);
+            let exported = match export_format {
+                Some(export_format) => db.export_findings(&filters, export_format, &mut out)?,
+                None => {
+                    let findings: Vec<IntelFinding> = db.query_secrets(&filters)?.iter().map(IntelFinding::from).collect();
+                    serde_json::to_writer_pretty(&mut out, &build_stix_bundle(&findings))?;
+                    findings.len()
+                }
+            };
+            std::io::Write::flush(&mut out)?;
+            info!("Export completed: {} records", exported);
+        }
+        DatabaseOps::Hold { path, hash, reason, by } => {
+            let db = SecretDatabase::new(&path)?;
+            db.place_legal_hold(&hash, &reason, &by)?;
+            info!("⚖️ Legal hold placed on {}", hash);
+        }
+        DatabaseOps::Release { path, hash } => {
+            let db = SecretDatabase::new(&path)?;
+            if db.release_legal_hold(&hash)? {
+                info!("Legal hold released on {}", hash);
+            } else {
+                info!("{} was not under legal hold", hash);
+            }
+        }
+        DatabaseOps::Holds { path } => {
+            let db = SecretDatabase::new(&path)?;
+            let holds = db.list_legal_holds()?;
+            info!("{} findings under legal hold", holds.len());
+            for hold in &holds {
+                info!("  - {} by {} at {}: {}", hold.secret_hash, hold.placed_by, hold.placed_at, hold.reason);
+            }
+        }
+        DatabaseOps::Suppress { path, kind, pattern, reason, by } => {
+            let db = SecretDatabase::new(&path)?;
+            let entry = AllowlistEntry { reason, ..AllowlistEntry::new(kind, pattern) };
+            let (id, suppressed) = db.add_allowlist_entry(&entry, &by)?;
+            info!("🙈 Allowlist rule {} ({}) suppressed {} stored findings", id, entry.label(), suppressed);
+        }
+        DatabaseOps::Unsuppress { path, id } => {
+            let db = SecretDatabase::new(&path)?;
+            let reactivated = db.remove_allowlist_entry(id)?;
+            info!("Allowlist rule {} removed, {} findings reactivated", id, reactivated);
+        }
+        DatabaseOps::Allowlist { path } => {
+            let db = SecretDatabase::new(&path)?;
+            let entries = db.allowlist_entries()?;
+            info!("{} allowlist rules", entries.len());
+            for stored in &entries {
+                info!(
+                    "  - [{}] {} by {} at {}{}",
+                    stored.id,
+                    stored.entry.label(),
+                    stored.created_by,
+                    stored.created_at,
+                    stored.entry.reason.as_deref().map(|r| format!(": {}", r)).unwrap_or_default()
+                );
+            }
+        }
+        DatabaseOps::RehashFingerprints { path } => {
+            let db = SecretDatabase::new(&path)?;
+            let legacy = db.legacy_fingerprint_count()?;
+            if legacy == 0 {
+                info!("No unkeyed fingerprints or finding hashes left to rewrap");
+            } else {
+                let upgraded = db.upgrade_legacy_fingerprints()?;
+                info!("Rewrapped the fingerprints of {} findings", upgraded);
+            }
+        }
+        DatabaseOps::Import { path, other, json } => {
+            let db = SecretDatabase::new(&path)?;
+            let report = db.merge_from(&other)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                info!("📥 Imported {} into {}", other, path);
+                for (table, counts) in [
+                    ("findings", report.secrets),
+                    ("triage results", report.triage_results),
+                    ("commits", report.commits),
+                ] {
+                    info!("  - {}: {} new, {} updated, {} duplicates", table, counts.new, counts.updated, counts.duplicates);
+                }
+            }
+        }
+        DatabaseOps::MigrateEncrypt { path } => {
+            SecretDatabase::migrate_encrypt(&path)?;
+            info!("🔒 {} is encrypted; set the same key wherever it is opened", path);
+        }
+        DatabaseOps::Purge { path, dry_run } => {
+            let file_config = load_file_config(config_path.as_ref())?;
+            let audit = AuditLog::from_config(&file_config.logging);
+            let report = run_purge(&path, &file_config.retention, dry_run, &audit, "cli")?;
+
+            let verb = if dry_run { "Would purge" } else { "Purged" };
+            info!("🗑️ {} {} rows from {}", verb, report.total(), path);
+            info!("  - contexts cleared: {}", report.contexts_cleared);
+            info!("  - validated secret values cleared: {}", report.validated_secrets_cleared);
+            info!("  - resolved findings deleted: {}", report.resolved_findings_deleted);
+            info!("  - expired findings deleted: {}", report.expired_findings_deleted);
+            info!("  - expired findings anonymized: {}", report.expired_findings_anonymized);
+            for (category, count) in &report.expired_by_category {
+                info!("      {}: {}", category, count);
+            }
+            info!("  - unverified Low findings deleted: {}", report.low_unverified_deleted);
+            info!("  - orphaned triage results and analyst data deleted: {}", report.orphans_deleted);
+            info!("  - kept under legal hold: {}", report.held_skipped);
+        }
+        DatabaseOps::Erase { path, hashes, by, approval } => {
+            let mut sorted = hashes.clone();
+            sorted.sort();
+            sorted.dedup();
+            let target = format!("{}: {}", path, sorted.join(","));
+            require_approval(config_path.as_ref(), ApprovalAction::Erase, &target, approval).await?;
+            info!("🧹 Erasing {} findings from {}", hashes.len(), path);
+            let db = SecretDatabase::new(&path)?;
+            let report = db.erase_findings(&hashes, &by)?;
+            AttachmentStore::beside_database(&path).prune(&db.referenced_attachments()?)?;
+            info!("Erased {} findings", report.erased);
+            for hash in &report.on_legal_hold {
+                error!("  - {} skipped: under legal hold", hash);
+            }
+            for hash in &report.not_found {
+                info!("  - {} not found", hash);
+            }
+        }
+        DatabaseOps::Note { path, hash, body, by } => {
+            let db = SecretDatabase::new(&path)?;
+            let note = db.add_note(&hash, &by, &body)?;
+            info!("📝 Added note {} to {}", note.id, hash);
+            publish_annotations(config_path.as_ref(), AnnotationUpdate {
+                secret_hash: hash,
+                notes: vec![note],
+                files: Vec::new(),
+            }).await?;
+        }
+        DatabaseOps::Attach { path, hash, file, content_type, by } => {
+            let db = SecretDatabase::new(&path)?;
+            let contents = std::fs::read(&file)?;
+            let filename = file.file_name().and_then(|n| n.to_str()).unwrap_or("attachment");
+            let content_type = content_type.unwrap_or_else(|| guess_content_type(filename).to_string());
+
+            let store = AttachmentStore::beside_database(&path);
+            let attachment = db.add_attachment(&store, &hash, filename, &content_type, &contents, &by)?;
+            info!("📎 Attached {} ({} bytes, sha256 {}) to {}", attachment.filename, attachment.size_bytes, attachment.sha256, hash);
+            publish_annotations(config_path.as_ref(), AnnotationUpdate {
+                secret_hash: hash,
+                notes: Vec::new(),
+                files: vec![(attachment, contents)],
+            }).await?;
+        }
+        DatabaseOps::Annotations { path, hash } => {
+            let db = SecretDatabase::new(&path)?;
+            println!("{}", serde_json::to_string_pretty(&db.annotations(&hash)?)?);
+        }
+        DatabaseOps::Status { path, hash, status } => {
+            let db = SecretDatabase::new(&path)?;
+            if !db.set_finding_status(&hash, status)? {
+                return Err(anyhow::anyhow!("No finding with hash {}", hash));
+            }
+            info!("✅ Marked {} {:?}", hash, status);
+        }
+        DatabaseOps::Tag { path, hash, tags, remove, by } => {
+            let db = SecretDatabase::new(&path)?;
+            let current = if remove {
+                db.untag_finding(&hash, &tags)?
+            } else {
+                db.tag_finding(&hash, &tags, &by)?
+            };
+            info!("🏷️ {} tags: {}", hash, current.join(", "));
+        }
+        DatabaseOps::TagRepo { path, repository, tags, remove, by } => {
+            let db = SecretDatabase::new(&path)?;
+            let current = if remove {
+                db.untag_repository(&repository, &tags)?
+            } else {
+                db.tag_repository(&repository, &tags, &by)?
+            };
+            info!("🏷️ {} tags: {}", repository, current.join(", "));
+        }
+        DatabaseOps::Tags { path } => {
+            let db = SecretDatabase::new(&path)?;
+            for count in db.tag_counts()? {
+                println!("{:>6}  {}", count.findings, count.tag);
+            }
+        }
+        DatabaseOps::SaveSearch {
+            path, name, severity, category, detector, filename, verified, days, since, until, repo, tags, alert, webhook, by,
+        } => {
+            let db = SecretDatabase::new(&path)?;
+            let filters = github_archiver::performance::SecretQueryFilters {
+                min_severity: severity.as_deref().map(str::parse).transpose()?,
+                category,
+                detector_name: detector,
+                filename,
+                verified_only: verified,
+                last_n_days: days,
+                since,
+                until,
+                limit: None,
+                tags,
+                repository: repo,
+                include_suppressed: false,
+                after: None,
+                as_of: None,
+            };
+            let search = db.save_search(&name, &by, &filters, alert, webhook.as_deref())?;
+            info!("🔎 Saved search {:?}{}", search.name, if search.alert { " (alerting)" } else { "" });
+        }
+        DatabaseOps::Searches { path } => {
+            let db = SecretDatabase::new(&path)?;
+            println!("{}", serde_json::to_string_pretty(&db.list_saved_searches()?)?);
+        }
+        DatabaseOps::RunSearch { path, name, limit } => {
+            let db = SecretDatabase::new(&path)?;
+            let secrets = db.run_saved_search(&name, limit)?;
+            info!("Found {} secrets", secrets.len());
+            for secret in &secrets {
+                println!("{}  {:<8}  {}  {}", secret.secret_hash, secret.severity, secret.detector_name, secret.filename.as_deref().unwrap_or("-"));
+            }
+        }
+        DatabaseOps::DeleteSearch { path, name } => {
+            let db = SecretDatabase::new(&path)?;
+            if !db.delete_saved_search(&name)? {
+                return Err(anyhow::anyhow!("No saved search named {}", name));
+            }
+            info!("Deleted saved search {:?}", name);
+        }
+        DatabaseOps::Rollup { path, days } => {
+            let db = SecretDatabase::new(&path)?;
+            let rows = db.refresh_rollups(days)?;
+            info!("📊 Rebuilt {} rollup rows for the last {} days", rows, days);
+        }
+        DatabaseOps::SyncRepos { path, stale_hours, limit } => {
+            let credentials = GitHubCredentials::from_env();
+            let report = if credentials.is_anonymous() {
+                let cache = Arc::new(EtagCache::new(10_000, std::env::var("REDIS_URL").ok().as_deref())?);
+                let client = CachedGitHubClient::new(credentials, cache)?;
+                sync_repositories(&path, &client, stale_hours, limit).await?
+            } else {
+                sync_repositories_graphql(&path, &GitHubGraphQLClient::new(credentials)?, stale_hours, limit).await?
+            };
+            info!(
+                "🔄 Synced {} repositories ({} unavailable, {} failed{})",
+                report.synced,
+                report.unavailable,
+                report.failed,
+                if report.rate_limited { ", stopped by the rate limit" } else { "" }
+            );
+        }
+        DatabaseOps::Repos { path, organization, visibility, min_risk, limit } => {
+            let db = SecretDatabase::new(&path)?;
+            let filters = RepositoryFilters { organization, visibility, language: None, min_risk, limit: Some(limit), after: None };
+            for repository in db.list_repositories(&filters)? {
+                println!(
+                    "{:<50} {:>5} {:<11} {:>7} stars  last activity {}",
+                    repository.name,
+                    repository.risk_score.map(|risk| format!("{:.2}", risk)).unwrap_or_else(|| "-".to_string()),
+                    repository.visibility.as_deref().unwrap_or("unknown"),
+                    repository.star_count.unwrap_or(0),
+                    repository.last_activity.as_deref().unwrap_or("never"),
+                );
+            }
+        }
+        DatabaseOps::Scorecard { path, organizations, month, format, output, limit } => {
+            let db = SecretDatabase::new(&path)?;
+            let period = match month {
+                Some(month) => ScorecardPeriod::parse_month(&month)?,
+                None => ScorecardPeriod::last_month(chrono::Utc::now().date_naive()),
+            };
+            let organizations = if organizations.is_empty() {
+                db.list_organizations()?.into_iter().map(|org| org.name).collect()
+            } else {
+                organizations
+            };
+            if organizations.is_empty() {
+                return Err(anyhow::anyhow!("No organizations given with --org and none are monitored"));
+            }
+
+            let scorecards = organizations
+                .iter()
+                .map(|org| db.organization_scorecard(org, period, limit))
+                .collect::<Result<Vec<_>>>()?;
+            let rendered = match format.as_str() {
+                "json" => serde_json::to_string_pretty(&scorecards)?,
+                "html" => render_scorecards_html(&scorecards)?,
+                other => return Err(anyhow::anyhow!("Unknown scorecard format {} (expected json or html)", other)),
+            };
+            match output {
+                Some(file) => {
+                    std::fs::write(&file, rendered)?;
+                    info!("📈 Wrote {} scorecards for {} to {}", scorecards.len(), period.start.format("%Y-%m"), file.display());
+                }
+                None => println!("{}", rendered),
+            }
+        }
+        DatabaseOps::Stats { path, days, limit } => {
+            let db = SecretDatabase::new(&path)?;
+            println!("{}", serde_json::to_string_pretty(&db.stats_summary(days, limit)?)?);
+        }
+        DatabaseOps::Backup { path, destination, encrypt } => {
+            let location: BackupLocation = destination.parse()?;
+            let passphrase = if encrypt {
+                Some(std::env::var(BACKUP_KEY_ENV).map_err(|_| anyhow::anyhow!("--encrypt needs {}", BACKUP_KEY_ENV))?)
+            } else {
+                None
+            };
+            // Remote backups are written beside the database first, then streamed up
+            let (file, remote) = match &location {
+                BackupLocation::File(file) => (file.clone(), false),
+                _ => (PathBuf::from(format!("{}.backup-{}", path, uuid::Uuid::new_v4().simple())), true),
+            };
+            let manifest = create_backup(&path, passphrase.as_deref(), &file)?;
+            let size = std::fs::metadata(&file)?.len();
+            if remote {
+                let uploaded = location.upload(&file).await;
+                std::fs::remove_file(&file).ok();
+                uploaded?;
+            }
+            info!(
+                "💾 Backed up {} (schema {}, {} tables, {} attachments, {} bytes{}) to {}",
+                path,
+                manifest.schema_version,
+                manifest.tables.len(),
+                manifest.attachments,
+                size,
+                if manifest.encryption.is_some() { ", encrypted" } else { "" },
+                destination
+            );
+        }
+        DatabaseOps::DeadLetters { path, limit, requeue } => {
+            let db = SecretDatabase::new(&path)?;
+            if !requeue.is_empty() {
+                for id in requeue {
+                    if db.requeue_dead_letter(id)? {
+                        info!("📥 Requeued dead letter {}", id);
+                    } else {
+                        warn!("No dead letter {}", id);
+                    }
+                }
+                return Ok(());
+            }
+            let depth = db.event_queue_depth()?;
+            info!("Event queue: {} pending, {} in flight, {} dead-lettered", depth.pending, depth.in_flight, depth.dead_letters);
+            for letter in db.dead_letters(limit)? {
+                println!(
+                    "[{}] {} {} for {} after {} attempts at {}: {}",
+                    letter.id,
+                    letter.event_id,
+                    letter.event_type,
+                    letter.repository,
+                    letter.attempts,
+                    letter.failed_at,
+                    letter.last_error.as_deref().unwrap_or("")
+                );
+            }
+        }
+        DatabaseOps::SlowQueries { path, limit, archive, json } => {
+            let db = SecretDatabase::new(&path)?;
+            let mut reports = summarize(&db.slow_queries(limit)?, &db.indexed_columns()?);
+            if archive {
+                let mut manager = DatabaseManager::new(load_file_config(config_path.as_ref())?);
+                manager.connect().await?;
+                reports.extend(summarize(&manager.slow_queries(limit as i64).await?, &manager.indexed_columns().await?));
+                manager.disconnect().await?;
+                reports.sort_by(|a, b| (b.max_ms * b.count as f64).total_cmp(&(a.max_ms * a.count as f64)));
+            }
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&reports)?);
+                return Ok(());
+            }
+            if reports.is_empty() {
+                println!("No slow queries recorded");
+            }
+            for report in &reports {
+                println!(
+                    "[{}] {}x, max {:.0} ms, mean {:.0} ms, last {}",
+                    report.backend, report.count, report.max_ms, report.mean_ms, report.last_seen
+                );
+                println!("  {}", report.sql);
+                if let Some(plan) = &report.plan {
+                    for line in plan.lines() {
+                        println!("    {}", line);
+                    }
+                }
+                for suggestion in &report.suggestions {
+                    println!("  suggested ({}): {};", suggestion.reason, suggestion.statement);
+                }
+                println!();
+            }
+        }
+        DatabaseOps::Restore { path, source, force, inspect } => {
+            let location: BackupLocation = source.parse()?;
+            let staging = restore_staging(&path);
+            let scratch = PathBuf::from(format!("{}.download", staging.display()));
+            let file = location.download(&scratch).await?;
+            let restored = async {
+                if inspect {
+                    println!("{}", serde_json::to_string_pretty(&read_manifest(&file)?)?);
+                    return Ok(());
+                }
+                let passphrase = std::env::var(BACKUP_KEY_ENV).ok();
+                let (manifest, contents) = read_backup(&file, passphrase.as_deref(), &staging)?;
+                restore_backup(&manifest, &contents, &path, force).await?;
+                // A Postgres URL may carry a password
+                let target = if path.contains("://") { endpoint_label(&path) } else { path.clone() };
+                info!("♻️ Restored {} from {} (taken {})", target, source, manifest.created_at);
+                anyhow::Ok(())
+            }
+            .await;
+            std::fs::remove_file(&scratch).ok();
+            restored?;
         }
     }
 
     Ok(())
 }
 
+/// Forward new notes/evidence to the ticket sinks from the `--config` file
+async fn publish_annotations(config_path: Option<&PathBuf>, update: AnnotationUpdate) -> Result<()> {
+    let file_config = load_file_config(config_path)?;
+    let sinks = SinkDispatcher::from_config(&file_config.sinks, &file_config.http)?;
+    if sinks.is_empty() {
+        return Ok(());
+    }
+
+    let report = sinks.publish_annotations(&update).await;
+    for error in &report.errors {
+        error!("  - {}", error);
+    }
+    Ok(())
+}
+
 async fn run_performance_tests(args: PerfArgs) -> Result<()> {
     match args.test {
-        PerfTests::Scan { secrets, workers } => {
+        PerfTests::Scan { secrets, workers, database, pool_size } => {
             info!("🚀 Benchmarking secret scanning: {} secrets, {} workers", secrets, workers);
             
-            let engine = PerformanceEngine::new();
+            let mut engine = PerformanceEngine::new();
+            if let Some(path) = &database {
+                engine = engine.with_database(SecretDatabasePool::new(path, false, pool_size)?);
+            }
             let test_secrets = generate_test_secrets(secrets);
             
             let request = github_archiver::performance::BatchProcessingRequest {
@@ -504,6 +3052,10 @@ fn generate_test_secrets(count: usize) -> Vec<github_archiver::SecretMatch> {
             end_position: 20,
             line_number: Some(i as u32 + 1),
             filename: Some(format!("test_{}.env", i % 5)),
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
             entropy: 3.5 + (i % 3) as f64,
             severity: match i % 4 {
                 0 => github_archiver::secrets::SecretSeverity::Critical,
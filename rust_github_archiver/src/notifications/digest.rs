@@ -0,0 +1,124 @@
+use chrono::{DateTime, Duration, DurationRound, Local, NaiveTime, TimeZone, Utc};
+use std::sync::Mutex;
+use tracing::info;
+
+use crate::core::DigestSchedule;
+use crate::realtime::{AlertSeverity, RealTimeSecretAlert};
+
+/// When the digest after `now` is due: the next top of the hour, or the next `hour`:00 local time
+pub fn next_digest_at(schedule: DigestSchedule, hour: u32, now: DateTime<Utc>) -> DateTime<Utc> {
+    match schedule {
+        DigestSchedule::Hourly => now.duration_trunc(Duration::hours(1)).unwrap_or(now) + Duration::hours(1),
+        DigestSchedule::Daily => {
+            let time = NaiveTime::from_hms_opt(hour.min(23), 0, 0).unwrap_or_default();
+            let local = now.with_timezone(&Local);
+            let mut date = local.date_naive();
+            if local.time() >= time {
+                date = date.succ_opt().unwrap_or(date);
+            }
+            // A skipped hour on a DST change falls back to the same time in UTC
+            Local
+                .from_local_datetime(&date.and_time(time))
+                .earliest()
+                .map(|at| at.with_timezone(&Utc))
+                .unwrap_or_else(|| date.and_time(time).and_utc())
+        }
+    }
+}
+
+/// Non-Critical email alerts collected for the hourly or daily digest
+pub struct EmailDigest {
+    schedule: DigestSchedule,
+    hour: u32,
+    pending: Mutex<Vec<RealTimeSecretAlert>>,
+    next_due: Mutex<DateTime<Utc>>,
+}
+
+impl EmailDigest {
+    pub fn new(schedule: DigestSchedule, hour: u32, now: DateTime<Utc>) -> Self {
+        Self {
+            schedule,
+            hour,
+            pending: Mutex::new(Vec::new()),
+            next_due: Mutex::new(next_digest_at(schedule, hour, now)),
+        }
+    }
+
+    pub fn schedule(&self) -> DigestSchedule {
+        self.schedule
+    }
+
+    pub fn next_due(&self) -> DateTime<Utc> {
+        *self.next_due.lock().unwrap()
+    }
+
+    /// Collect `alert` for the digest. Critical alerts are never collected.
+    pub fn hold(&self, alert: &RealTimeSecretAlert) -> bool {
+        if matches!(alert.alert_severity, AlertSeverity::Critical) {
+            return false;
+        }
+        info!("Email digest: holding {:?} alert for {}", alert.alert_severity, alert.repository);
+        self.pending.lock().unwrap().push(alert.clone());
+        true
+    }
+
+    /// The collected alerts once the digest is due. An empty period sends nothing but still
+    /// moves the schedule on.
+    pub fn take_due(&self, now: DateTime<Utc>) -> Option<Vec<RealTimeSecretAlert>> {
+        let mut next_due = self.next_due.lock().unwrap();
+        if now < *next_due {
+            return None;
+        }
+        *next_due = next_digest_at(self.schedule, self.hour, now);
+        let alerts = std::mem::take(&mut *self.pending.lock().unwrap());
+        (!alerts.is_empty()).then_some(alerts)
+    }
+
+    /// Put alerts back after a failed delivery so the next digest includes them
+    pub fn restore(&self, alerts: Vec<RealTimeSecretAlert>) {
+        let mut pending = self.pending.lock().unwrap();
+        let newer = std::mem::replace(&mut *pending, alerts);
+        pending.extend(newer);
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::sample_alert;
+
+    fn alert(severity: AlertSeverity) -> RealTimeSecretAlert {
+        RealTimeSecretAlert { alert_severity: severity, ..sample_alert() }
+    }
+
+    #[test]
+    fn test_next_digest_at() {
+        let now = DateTime::parse_from_rfc3339("2024-03-01T09:30:00Z").unwrap().with_timezone(&Utc);
+        let hourly = next_digest_at(DigestSchedule::Hourly, 8, now);
+        assert_eq!(hourly.to_rfc3339(), "2024-03-01T10:00:00+00:00");
+
+        let daily = next_digest_at(DigestSchedule::Daily, 8, now).with_timezone(&Local);
+        assert_eq!(daily.time(), NaiveTime::from_hms_opt(8, 0, 0).unwrap());
+        assert!(daily > now && daily <= now + Duration::days(1));
+    }
+
+    #[test]
+    fn test_digest_collects_until_due() {
+        let now = Utc::now();
+        let digest = EmailDigest::new(DigestSchedule::Hourly, 8, now);
+        assert!(digest.hold(&alert(AlertSeverity::Medium)));
+        assert!(!digest.hold(&alert(AlertSeverity::Critical)));
+        assert!(digest.take_due(now).is_none());
+
+        let due = digest.next_due();
+        assert_eq!(digest.take_due(due).unwrap().len(), 1);
+        assert_eq!(digest.pending_count(), 0);
+        assert_eq!(digest.next_due(), due + Duration::hours(1));
+        // Nothing collected: nothing to send
+        assert!(digest.take_due(due + Duration::hours(1)).is_none());
+    }
+}
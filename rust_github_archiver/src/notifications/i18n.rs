@@ -0,0 +1,169 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use tracing::warn;
+use unic_langid::LanguageIdentifier;
+
+use crate::core::{Locale, NotificationsConfig};
+
+static ACTIVE: OnceLock<Locale> = OnceLock::new();
+
+/// Alert, digest and report text in one language. Messages missing from a translation
+/// fall back to English.
+pub struct Translations {
+    locale: Locale,
+    /// The locale's bundle first, then English
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+fn source(locale: Locale) -> &'static str {
+    match locale {
+        Locale::En => include_str!("locales/en.ftl"),
+        Locale::Es => include_str!("locales/es.ftl"),
+        Locale::De => include_str!("locales/de.ftl"),
+    }
+}
+
+fn bundle(locale: Locale) -> FluentBundle<FluentResource> {
+    let language: LanguageIdentifier = locale.tag().parse().expect("locale tags are valid");
+    let mut bundle = FluentBundle::new_concurrent(vec![language]);
+    // Isolation marks around values would end up in plain-text emails and chat messages
+    bundle.set_use_isolating(false);
+    let resource = FluentResource::try_new(source(locale).to_string()).expect("built-in translations parse");
+    bundle.add_resource(resource).expect("built-in translations have unique message ids");
+    bundle
+}
+
+impl Translations {
+    fn new(locale: Locale) -> Self {
+        let mut bundles = vec![bundle(locale)];
+        if locale != Locale::En {
+            bundles.push(bundle(Locale::En));
+        }
+        Self { locale, bundles }
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    /// The message `id` with its `{ $name }` placeables filled in from `args`
+    pub fn format(&self, id: &str, args: &[(&str, FluentValue)]) -> String {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, value.clone());
+        }
+        self.format_args(id, &fluent_args)
+    }
+
+    fn format_args(&self, id: &str, args: &FluentArgs) -> String {
+        for bundle in &self.bundles {
+            let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) else {
+                continue;
+            };
+            let mut errors = Vec::new();
+            let text = bundle.format_pattern(pattern, Some(args), &mut errors);
+            if !errors.is_empty() {
+                warn!("Formatting the {} message {} failed: {:?}", self.locale.tag(), id, errors);
+            }
+            return text.into_owned();
+        }
+        warn!("No {} message {}", self.locale.tag(), id);
+        id.to_string()
+    }
+
+    /// The `t(id="...", name=value, ...)` function of the notification templates. Null
+    /// values are passed as `none`, so selectors on them take their default variant.
+    pub fn template_function(&self, args: &HashMap<String, tera::Value>) -> tera::Result<tera::Value> {
+        let id = args
+            .get("id")
+            .and_then(|id| id.as_str())
+            .ok_or_else(|| tera::Error::msg("t() needs the message id, as in t(id=\"alert-title\")"))?;
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args.iter().filter(|(name, _)| name.as_str() != "id") {
+            let value = match value {
+                tera::Value::Number(number) => match number.as_i64() {
+                    Some(integer) => FluentValue::from(integer),
+                    None => FluentValue::from(number.as_f64().unwrap_or_default()),
+                },
+                tera::Value::String(text) => FluentValue::from(text.clone()),
+                tera::Value::Null => FluentValue::from("none"),
+                other => FluentValue::from(other.to_string()),
+            };
+            fluent_args.set(name.clone(), value);
+        }
+        Ok(tera::Value::String(self.format_args(id, &fluent_args)))
+    }
+}
+
+/// The built-in translations for `locale`
+pub fn translations(locale: Locale) -> &'static Translations {
+    static EN: OnceLock<Translations> = OnceLock::new();
+    static ES: OnceLock<Translations> = OnceLock::new();
+    static DE: OnceLock<Translations> = OnceLock::new();
+    let cell = match locale {
+        Locale::En => &EN,
+        Locale::Es => &ES,
+        Locale::De => &DE,
+    };
+    cell.get_or_init(|| Translations::new(locale))
+}
+
+/// Install the process-wide output locale from the configuration
+pub fn install(locale: Locale) {
+    if ACTIVE.set(locale).is_err() {
+        warn!("The notification locale was already configured; ignoring the new setting");
+    }
+}
+
+/// The active locale, configured from the environment if `install` was never called
+pub fn locale() -> Locale {
+    *ACTIVE.get_or_init(|| NotificationsConfig::default().locale)
+}
+
+/// The message `id` in the active locale
+pub fn text(id: &str, args: &[(&str, FluentValue)]) -> String {
+    translations(locale()).format(id, args)
+}
+
+/// A severity or status as the active locale names it
+pub fn severity(level: impl std::fmt::Debug) -> String {
+    text("severity", &[("level", format!("{:?}", level).into())])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every translation parses and has every English message
+    #[test]
+    fn test_translations_are_complete() {
+        let ids: Vec<&str> = source(Locale::En)
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_lowercase()))
+            .filter_map(|line| line.split_once(" =").map(|(id, _)| id))
+            .collect();
+        assert!(ids.len() > 20);
+        for locale in [Locale::Es, Locale::De] {
+            let translated = &translations(locale).bundles[0];
+            for id in &ids {
+                assert!(translated.has_message(id), "{} is missing from {}", id, locale.tag());
+            }
+        }
+    }
+
+    #[test]
+    fn test_plurals_and_fallback() {
+        let args = |count: i64| [("count", FluentValue::from(count)), ("repository", FluentValue::from("acme/api"))];
+        let english = translations(Locale::En);
+        assert_eq!(english.format("email-alert-subject", &args(1)), "1 secret detected in acme/api");
+        assert_eq!(english.format("email-alert-subject", &args(3)), "3 secrets detected in acme/api");
+
+        let german = translations(Locale::De);
+        assert_eq!(german.format("email-alert-subject", &args(3)), "3 Secrets in acme/api entdeckt");
+        assert_eq!(german.format("severity", &[("level", "Critical".into())]), "Kritisch");
+        assert_eq!(translations(Locale::Es).format("severity", &[("level", "High".into())]), "Alta");
+        assert_eq!(german.format("no-such-message", &[]), "no-such-message");
+    }
+}
@@ -0,0 +1,177 @@
+# Texte für Alarme, Zusammenfassungen und Berichte; siehe en.ftl
+
+## Allgemein
+
+severity = { $level ->
+    [Critical] Kritisch
+    [High] Hoch
+    [Medium] Mittel
+    [Low] Niedrig
+   *[other] { $level }
+}
+scan-status = { $status ->
+    [Running] Läuft
+    [Completed] Abgeschlossen
+    [Failed] Fehlgeschlagen
+    [Cancelled] Abgebrochen
+   *[other] { $status }
+}
+column-detector = Detektor
+column-severity = Schweregrad
+column-file = Datei
+column-match = Treffer
+column-commit = Commit
+column-repository = Repository
+column-findings = Funde
+column-detected = Entdeckt
+heading-findings = Funde
+heading-repositories = Repositories
+heading-recommendations = Empfehlungen
+word-commit = Commit
+unknown-file = unbekannte Datei
+finding-in-file = { $detector } ({ $severity }) in { $file }
+finding-line-number = , Zeile { $line }
+and-more = und { $count } weitere
+and-more-alerts = und { $count } { $count ->
+    [one] weiterer Alarm
+   *[other] weitere Alarme
+}
+time-range = { $from } bis { $until }
+detected-at = Entdeckt am { $time }
+view-commit = Commit ansehen
+
+## Echtzeit-Alarme
+
+slack-alert-title = *Secret-Alarm ({ $severity })* in { $repository }
+slack-alert-commit = { $count } { $count ->
+    [one] Secret
+   *[other] Secrets
+} in Commit { $commit }, entdeckt am { $time }
+slack-alert-spread = { $occurrences }-mal gesehen in { $files } { $files ->
+    [one] Datei
+   *[other] Dateien
+} in { $repositories } { $repositories ->
+    [one] Repository
+   *[other] Repositories
+} seit { $since }
+email-alert-subject = { $count } { $count ->
+    [one] Secret
+   *[other] Secrets
+} in { $repository } entdeckt
+email-alert-intro = GitHub Secret Hunter hat { $count } { $count ->
+    [one] Secret
+   *[other] Secrets
+} in { $repository } entdeckt.
+email-alert-severity = Schweregrad: { $severity }
+email-alert-commit = Commit:      { $url }
+email-alert-detected = Entdeckt:    { $time }
+email-alert-event = Ereignis:    { $event }
+email-alert-spread = Verbreitung: { $occurrences }-mal gefunden in { $files } { $files ->
+    [one] Datei
+   *[other] Dateien
+} in { $repositories } { $repositories ->
+    [one] Repository
+   *[other] Repositories
+}, zuerst gesehen { $first }, zuletzt gesehen { $last }
+html-alert-headline = { $count } { $count ->
+    [one] Secret
+   *[other] Secrets
+} entdeckt in
+html-alert-spread = { $occurrences }-mal gefunden in { $files } { $files ->
+    [one] Datei
+   *[other] Dateien
+} in { $repositories } { $repositories ->
+    [one] Repository
+   *[other] Repositories
+}, zuerst gesehen { $first }, zuletzt gesehen { $last }.
+alert-advice = Widerrufen Sie offengelegte Zugangsdaten, bevor Sie den Fund prüfen.
+alert-event = Ereignis { $event }
+chat-alert-title = { $severity }: { $count } { $count ->
+    [one] Secret
+   *[other] Secrets
+} in { $repository }
+chat-alert-section = { $repository } { $severity } in { $commit }
+chat-alert-spread = { $occurrences }-mal gesehen in { $repositories } { $repositories ->
+    [one] Repository
+   *[other] Repositories
+}
+chat-batch-title = { $alerts } Alarme, { $secrets } { $secrets ->
+    [one] Secret
+   *[other] Secrets
+}, in { $repositories } { $repositories ->
+    [one] Repository
+   *[other] Repositories
+}
+
+## Zusammenfassungen
+
+slack-digest-title = *Alarm-Zusammenfassung*: { $count } { $count ->
+    [one] Alarm
+   *[other] Alarme
+} während der Ruhezeit zurückgehalten ({ $since } bis { $until })
+slack-digest-line = { $severity }: { $repository }, { $count } { $count ->
+    [one] Secret
+   *[other] Secrets
+}
+digest-subject = [Zusammenfassung] { $count } { $count ->
+    [one] Secret-Alarm
+   *[other] Secret-Alarme
+} { $schedule ->
+    [hourly] in der letzten Stunde
+    [daily] am letzten Tag
+   *[other] während der Ruhezeit zurückgehalten
+}
+digest-headline = { $count } { $count ->
+    [one] Secret-Alarm
+   *[other] Secret-Alarme
+} { $schedule ->
+    [hourly] in der letzten Stunde
+    [daily] am letzten Tag
+   *[other] während der Ruhezeit zurückgehalten
+}
+digest-intro = { $count } { $count ->
+    [one] Alarm
+   *[other] Alarme
+} mit { $findings } { $findings ->
+    [one] Fund
+   *[other] Funden
+} { $schedule ->
+    [hourly] wurden für die stündliche Zusammenfassung gesammelt
+    [daily] wurden für die tägliche Zusammenfassung gesammelt
+   *[other] wurden zurückgehalten
+} zwischen { $since } und { $until }.
+digest-findings = { $count } { $count ->
+    [one] Fund
+   *[other] Funde
+} zwischen { $since } und { $until }.
+digest-footer = Kritische Alarme werden nie zurückgehalten und wurden sofort zugestellt.
+
+## Scan-Berichte
+
+report-subject = [Scan { $status }] { $count } { $count ->
+    [one] Secret
+   *[other] Secrets
+} in { $target } gefunden
+report-headline = { $count } { $count ->
+    [one] Secret
+   *[other] Secrets
+} in { $target } gefunden
+report-intro = { $scan_type }-Scan von { $target }: { $status }.
+report-scan-type = { $scan_type }-Scan
+report-started = Gestartet:     { $time }
+report-completed = Abgeschlossen: { $time }
+report-not-completed = Abgeschlossen: nicht abgeschlossen
+report-scan = Scan:          { $scan }
+report-found = { $count } { $count ->
+    [one] Secret
+   *[other] Secrets
+} gefunden{ $suppressed ->
+    [0] {""}
+   *[other] , { $suppressed } weitere von der Allowlist unterdrückt
+}.
+report-suppressed = { $count } unterdrückt
+repository-findings = { $count } { $count ->
+    [one] Fund
+   *[other] Funde
+} ({ $status })
+report-scan-id = Scan { $scan }
@@ -0,0 +1,168 @@
+# Alert, digest and report text. Templates call these with t(id="...", name=value);
+# see the README for adding a language.
+
+## Shared
+
+# `level` is Low, Medium, High or Critical
+severity = { $level }
+# `status` is Running, Completed, Failed or Cancelled
+scan-status = { $status }
+column-detector = Detector
+column-severity = Severity
+column-file = File
+column-match = Match
+column-commit = Commit
+column-repository = Repository
+column-findings = Findings
+column-detected = Detected
+heading-findings = Findings
+heading-repositories = Repositories
+heading-recommendations = Recommendations
+word-commit = commit
+unknown-file = unknown file
+finding-in-file = { $detector } ({ $severity }) in { $file }
+finding-line-number = , line { $line }
+and-more = and { $count } more
+and-more-alerts = and { $count } more { $count ->
+    [one] alert
+   *[other] alerts
+}
+time-range = { $from } to { $until }
+detected-at = Detected { $time }
+view-commit = View commit
+
+## Real-time alerts
+
+slack-alert-title = *{ $severity } secret alert* in { $repository }
+slack-alert-commit = { $count } { $count ->
+    [one] secret
+   *[other] secrets
+} in commit { $commit }, detected { $time }
+slack-alert-spread = Seen { $occurrences } times in { $files } { $files ->
+    [one] file
+   *[other] files
+} across { $repositories } { $repositories ->
+    [one] repository
+   *[other] repositories
+} since { $since }
+email-alert-subject = { $count } { $count ->
+    [one] secret
+   *[other] secrets
+} detected in { $repository }
+email-alert-intro = GitHub Secret Hunter detected { $count } { $count ->
+    [one] secret
+   *[other] secrets
+} in { $repository }.
+email-alert-severity = Severity: { $severity }
+email-alert-commit = Commit:   { $url }
+email-alert-detected = Detected: { $time }
+email-alert-event = Event:    { $event }
+email-alert-spread = Spread:   found { $occurrences } times in { $files } { $files ->
+    [one] file
+   *[other] files
+} across { $repositories } { $repositories ->
+    [one] repository
+   *[other] repositories
+}, first seen { $first }, last seen { $last }
+html-alert-headline = { $count } { $count ->
+    [one] secret
+   *[other] secrets
+} detected in
+html-alert-spread = Found { $occurrences } times in { $files } { $files ->
+    [one] file
+   *[other] files
+} across { $repositories } { $repositories ->
+    [one] repository
+   *[other] repositories
+}, first seen { $first }, last seen { $last }.
+alert-advice = Rotate any exposed credential before reviewing the finding.
+alert-event = Event { $event }
+chat-alert-title = { $severity }: { $count } { $count ->
+    [one] secret
+   *[other] secrets
+} in { $repository }
+chat-alert-section = { $repository } { $severity } in { $commit }
+chat-alert-spread = Seen { $occurrences } times across { $repositories } { $repositories ->
+    [one] repository
+   *[other] repositories
+}
+chat-batch-title = { $alerts } alerts, { $secrets } { $secrets ->
+    [one] secret
+   *[other] secrets
+}, in { $repositories } { $repositories ->
+    [one] repository
+   *[other] repositories
+}
+
+## Digests; `schedule` is hourly, daily, or none for alerts held during quiet hours
+
+slack-digest-title = *Alert digest*: { $count } { $count ->
+    [one] alert
+   *[other] alerts
+} held during quiet hours ({ $since } to { $until })
+slack-digest-line = { $severity }: { $repository }, { $count } { $count ->
+    [one] secret
+   *[other] secrets
+}
+digest-subject = [Digest] { $count } secret { $count ->
+    [one] alert
+   *[other] alerts
+} { $schedule ->
+    [hourly] in the last hour
+    [daily] in the last day
+   *[other] held during quiet hours
+}
+digest-headline = { $count } secret { $count ->
+    [one] alert
+   *[other] alerts
+} { $schedule ->
+    [hourly] in the last hour
+    [daily] in the last day
+   *[other] held during quiet hours
+}
+digest-intro = { $count } { $count ->
+    [one] alert
+   *[other] alerts
+} with { $findings } { $findings ->
+    [one] finding
+   *[other] findings
+} were { $schedule ->
+    [hourly] collected for the hourly digest
+    [daily] collected for the daily digest
+   *[other] held
+} between { $since } and { $until }.
+digest-findings = { $count } { $count ->
+    [one] finding
+   *[other] findings
+} between { $since } and { $until }.
+digest-footer = Critical alerts are never held and were delivered immediately.
+
+## Scan reports
+
+report-subject = [Scan { $status }] { $count } { $count ->
+    [one] secret
+   *[other] secrets
+} found in { $target }
+report-headline = { $count } { $count ->
+    [one] secret
+   *[other] secrets
+} found in { $target }
+report-intro = { $scan_type } scan of { $target }: { $status }.
+report-scan-type = { $scan_type } scan
+report-started = Started:   { $time }
+report-completed = Completed: { $time }
+report-not-completed = Completed: not completed
+report-scan = Scan:      { $scan }
+report-found = { $count } { $count ->
+    [one] secret
+   *[other] secrets
+} found{ $suppressed ->
+    [0] {""}
+   *[other] , { $suppressed } more suppressed by the allowlist
+}.
+report-suppressed = { $count } suppressed
+repository-findings = { $count } { $count ->
+    [one] finding
+   *[other] findings
+} ({ $status })
+report-scan-id = Scan { $scan }
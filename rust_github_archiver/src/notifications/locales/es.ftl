@@ -0,0 +1,177 @@
+# Textos de alertas, resúmenes e informes; véase en.ftl
+
+## Compartidos
+
+severity = { $level ->
+    [Critical] Crítica
+    [High] Alta
+    [Medium] Media
+    [Low] Baja
+   *[other] { $level }
+}
+scan-status = { $status ->
+    [Running] En curso
+    [Completed] Completado
+    [Failed] Fallido
+    [Cancelled] Cancelado
+   *[other] { $status }
+}
+column-detector = Detector
+column-severity = Gravedad
+column-file = Archivo
+column-match = Coincidencia
+column-commit = Commit
+column-repository = Repositorio
+column-findings = Hallazgos
+column-detected = Detectado
+heading-findings = Hallazgos
+heading-repositories = Repositorios
+heading-recommendations = Recomendaciones
+word-commit = commit
+unknown-file = archivo desconocido
+finding-in-file = { $detector } ({ $severity }) en { $file }
+finding-line-number = , línea { $line }
+and-more = y { $count } más
+and-more-alerts = y { $count } { $count ->
+    [one] alerta más
+   *[other] alertas más
+}
+time-range = { $from } a { $until }
+detected-at = Detectado el { $time }
+view-commit = Ver commit
+
+## Alertas en tiempo real
+
+slack-alert-title = *Alerta de secreto ({ $severity })* en { $repository }
+slack-alert-commit = { $count } { $count ->
+    [one] secreto
+   *[other] secretos
+} en el commit { $commit }, detectado el { $time }
+slack-alert-spread = Visto { $occurrences } veces en { $files } { $files ->
+    [one] archivo
+   *[other] archivos
+} de { $repositories } { $repositories ->
+    [one] repositorio
+   *[other] repositorios
+} desde { $since }
+email-alert-subject = { $count } { $count ->
+    [one] secreto detectado
+   *[other] secretos detectados
+} en { $repository }
+email-alert-intro = GitHub Secret Hunter detectó { $count } { $count ->
+    [one] secreto
+   *[other] secretos
+} en { $repository }.
+email-alert-severity = Gravedad:    { $severity }
+email-alert-commit = Commit:      { $url }
+email-alert-detected = Detectado:   { $time }
+email-alert-event = Evento:      { $event }
+email-alert-spread = Propagación: encontrado { $occurrences } veces en { $files } { $files ->
+    [one] archivo
+   *[other] archivos
+} de { $repositories } { $repositories ->
+    [one] repositorio
+   *[other] repositorios
+}, visto por primera vez el { $first }, por última vez el { $last }
+html-alert-headline = { $count } { $count ->
+    [one] secreto detectado
+   *[other] secretos detectados
+} en
+html-alert-spread = Encontrado { $occurrences } veces en { $files } { $files ->
+    [one] archivo
+   *[other] archivos
+} de { $repositories } { $repositories ->
+    [one] repositorio
+   *[other] repositorios
+}, visto por primera vez el { $first }, por última vez el { $last }.
+alert-advice = Revoque cualquier credencial expuesta antes de revisar el hallazgo.
+alert-event = Evento { $event }
+chat-alert-title = { $severity }: { $count } { $count ->
+    [one] secreto
+   *[other] secretos
+} en { $repository }
+chat-alert-section = { $repository } { $severity } en { $commit }
+chat-alert-spread = Visto { $occurrences } veces en { $repositories } { $repositories ->
+    [one] repositorio
+   *[other] repositorios
+}
+chat-batch-title = { $alerts } alertas, { $secrets } { $secrets ->
+    [one] secreto
+   *[other] secretos
+}, en { $repositories } { $repositories ->
+    [one] repositorio
+   *[other] repositorios
+}
+
+## Resúmenes
+
+slack-digest-title = *Resumen de alertas*: { $count } { $count ->
+    [one] alerta retenida
+   *[other] alertas retenidas
+} durante las horas de silencio ({ $since } a { $until })
+slack-digest-line = { $severity }: { $repository }, { $count } { $count ->
+    [one] secreto
+   *[other] secretos
+}
+digest-subject = [Resumen] { $count } { $count ->
+    [one] alerta de secretos
+   *[other] alertas de secretos
+} { $schedule ->
+    [hourly] en la última hora
+    [daily] en el último día
+   *[other] retenidas durante las horas de silencio
+}
+digest-headline = { $count } { $count ->
+    [one] alerta de secretos
+   *[other] alertas de secretos
+} { $schedule ->
+    [hourly] en la última hora
+    [daily] en el último día
+   *[other] retenidas durante las horas de silencio
+}
+digest-intro = { $count } { $count ->
+    [one] alerta
+   *[other] alertas
+} con { $findings } { $findings ->
+    [one] hallazgo
+   *[other] hallazgos
+} { $schedule ->
+    [hourly] recopiladas para el resumen horario
+    [daily] recopiladas para el resumen diario
+   *[other] retenidas
+} entre { $since } y { $until }.
+digest-findings = { $count } { $count ->
+    [one] hallazgo
+   *[other] hallazgos
+} entre { $since } y { $until }.
+digest-footer = Las alertas críticas nunca se retienen y se enviaron de inmediato.
+
+## Informes de escaneo
+
+report-subject = [Escaneo { $status }] { $count } { $count ->
+    [one] secreto encontrado
+   *[other] secretos encontrados
+} en { $target }
+report-headline = { $count } { $count ->
+    [one] secreto encontrado
+   *[other] secretos encontrados
+} en { $target }
+report-intro = Escaneo { $scan_type } de { $target }: { $status }.
+report-scan-type = Escaneo { $scan_type }
+report-started = Inicio:     { $time }
+report-completed = Fin:        { $time }
+report-not-completed = Fin:        sin completar
+report-scan = Escaneo:    { $scan }
+report-found = { $count } { $count ->
+    [one] secreto encontrado
+   *[other] secretos encontrados
+}{ $suppressed ->
+    [0] {""}
+   *[other] , { $suppressed } más suprimidos por la lista de permitidos
+}.
+report-suppressed = { $count } suprimidos
+repository-findings = { $count } { $count ->
+    [one] hallazgo
+   *[other] hallazgos
+} ({ $status })
+report-scan-id = Escaneo { $scan }
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Local, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::info;
+
+use crate::core::{in_daily_window, QuietWindow};
+use crate::realtime::{AlertSeverity, RealTimeSecretAlert};
+
+impl QuietWindow {
+    pub fn channels(&self) -> &[String] {
+        match self {
+            QuietWindow::Daily { channels, .. } | QuietWindow::Maintenance { channels, .. } => channels,
+        }
+    }
+
+    /// A user's own channel, such as `email:carol@example.com`, is quiet when its kind is
+    pub fn applies_to(&self, channel: &str) -> bool {
+        let kind = channel.split_once(':').map_or(channel, |(kind, _)| kind);
+        let channels = self.channels();
+        channels.is_empty() || channels.iter().any(|c| c.eq_ignore_ascii_case(kind))
+    }
+
+    /// Daily windows are compared in local time
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        match self {
+            QuietWindow::Daily { start, end, .. } => in_daily_window(now.with_timezone(&Local).time(), *start, *end),
+            QuietWindow::Maintenance { start, end, .. } => now >= *start && now < *end,
+        }
+    }
+}
+
+/// Non-Critical alerts held per channel while a quiet window is active
+pub struct DigestQueue {
+    windows: Vec<QuietWindow>,
+    held: Mutex<HashMap<String, Vec<RealTimeSecretAlert>>>,
+}
+
+impl DigestQueue {
+    pub fn new(windows: Vec<QuietWindow>) -> Self {
+        Self {
+            windows,
+            held: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_quiet(&self, channel: &str, now: DateTime<Utc>) -> bool {
+        self.windows.iter().any(|w| w.applies_to(channel) && w.is_active(now))
+    }
+
+    /// Hold `alert` for `channel` if the channel is quiet. Critical alerts are never held.
+    pub fn hold(&self, channel: &str, alert: &RealTimeSecretAlert, now: DateTime<Utc>) -> bool {
+        if matches!(alert.alert_severity, AlertSeverity::Critical) || !self.is_quiet(channel, now) {
+            return false;
+        }
+        info!("Quiet window: holding {:?} alert for {} on {}", alert.alert_severity, alert.repository, channel);
+        self.held.lock().unwrap().entry(channel.to_string()).or_default().push(alert.clone());
+        true
+    }
+
+    /// Remove and return the held alerts of every channel whose quiet window has ended
+    pub fn take_due(&self, now: DateTime<Utc>) -> Vec<(String, Vec<RealTimeSecretAlert>)> {
+        let mut held = self.held.lock().unwrap();
+        let due: Vec<String> = held
+            .iter()
+            .filter(|(channel, alerts)| !alerts.is_empty() && !self.is_quiet(channel, now))
+            .map(|(channel, _)| channel.clone())
+            .collect();
+        due.into_iter()
+            .filter_map(|channel| held.remove(&channel).map(|alerts| (channel, alerts)))
+            .collect()
+    }
+
+    pub fn held_count(&self) -> usize {
+        self.held.lock().unwrap().values().map(Vec::len).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::sample_alert;
+    use chrono::{Duration, NaiveTime};
+
+    fn alert(severity: AlertSeverity) -> RealTimeSecretAlert {
+        RealTimeSecretAlert { alert_severity: severity, ..sample_alert() }
+    }
+
+    #[test]
+    fn test_daily_window_wraps_midnight() {
+        let at = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        assert!(in_daily_window(at(23), at(22), at(7)));
+        assert!(in_daily_window(at(3), at(22), at(7)));
+        assert!(!in_daily_window(at(7), at(22), at(7)));
+        assert!(in_daily_window(at(12), at(9), at(17)));
+    }
+
+    #[test]
+    fn test_maintenance_holds_until_window_ends() {
+        let start = Utc::now();
+        let queue = DigestQueue::new(vec![QuietWindow::Maintenance {
+            start,
+            end: start + Duration::hours(1),
+            channels: vec!["slack".to_string()],
+        }]);
+        let during = start + Duration::minutes(10);
+        // Users' own channels follow the window for their kind
+        assert!(queue.is_quiet("slack:@U024BE7LH", during));
+        assert!(!queue.is_quiet("email:carol@example.com", during));
+
+        assert!(queue.hold("slack", &alert(AlertSeverity::High), during));
+        assert!(!queue.hold("email", &alert(AlertSeverity::High), during));
+        // Critical always goes out
+        assert!(!queue.hold("slack", &alert(AlertSeverity::Critical), during));
+
+        assert!(queue.take_due(during).is_empty());
+        let due = queue.take_due(start + Duration::hours(2));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].0, "slack");
+        assert_eq!(due[0].1.len(), 1);
+        assert_eq!(queue.held_count(), 0);
+    }
+}
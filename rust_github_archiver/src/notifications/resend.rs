@@ -0,0 +1,208 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use reqwest::Url;
+use std::fmt;
+use uuid::Uuid;
+
+use super::verify::endpoint_label;
+use super::Notifier;
+use crate::performance::DeliveryRecord;
+use crate::realtime::{AlertDelivery, GitHubEventMonitor, RealTimeSecretAlert, WebhookEndpoint};
+
+/// Where `alerts resend` delivers a past alert: `slack[:channel]`, `email[:addr,addr]` or
+/// `webhook[:url or endpoint label]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlertTarget {
+    /// The Slack webhook, optionally posting to another channel than its default
+    Slack(Option<String>),
+    /// The configured recipients, or these instead
+    Email(Vec<String>),
+    /// Every configured webhook, those matching a URL or endpoint label, or an unconfigured URL
+    Webhook(Option<String>),
+    /// A configured chat target by name, as logged in deliveries (`chat:<name>`)
+    Chat(String),
+}
+
+impl std::str::FromStr for AlertTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, rest) = match s.split_once(':') {
+            Some((kind, rest)) => (kind, Some(rest.trim()).filter(|rest| !rest.is_empty())),
+            None => (s, None),
+        };
+        match kind.trim().to_lowercase().as_str() {
+            "slack" => Ok(AlertTarget::Slack(rest.map(|channel| {
+                if channel.starts_with('#') || channel.starts_with('@') {
+                    channel.to_string()
+                } else {
+                    format!("#{}", channel)
+                }
+            }))),
+            "email" => Ok(AlertTarget::Email(
+                rest.map(|list| list.split(',').map(|a| a.trim().to_string()).filter(|a| !a.is_empty()).collect())
+                    .unwrap_or_default(),
+            )),
+            "webhook" => Ok(AlertTarget::Webhook(rest.map(str::to_string))),
+            "chat" => rest
+                .map(|name| AlertTarget::Chat(name.to_string()))
+                .ok_or_else(|| anyhow!("chat needs the target's name, e.g. chat:discord")),
+            other => Err(anyhow!("Unknown alert channel {} (expected slack, email, webhook or chat)", other)),
+        }
+    }
+}
+
+impl fmt::Display for AlertTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertTarget::Slack(None) => f.write_str("slack"),
+            AlertTarget::Slack(Some(channel)) => write!(f, "slack:{}", channel),
+            AlertTarget::Email(recipients) if recipients.is_empty() => f.write_str("email"),
+            AlertTarget::Email(recipients) => write!(f, "email:{}", recipients.join(",")),
+            AlertTarget::Webhook(None) => f.write_str("webhook"),
+            AlertTarget::Webhook(Some(url)) => write!(f, "webhook:{}", endpoint_label(url)),
+            AlertTarget::Chat(name) => write!(f, "chat:{}", name),
+        }
+    }
+}
+
+impl AlertTarget {
+    /// Channels whose latest attempt in the delivery log failed, for resending without `--to`
+    pub fn failed_in(deliveries: &[DeliveryRecord]) -> Vec<AlertTarget> {
+        let mut latest: Vec<(&str, &str)> = Vec::new();
+        for record in deliveries {
+            match latest.iter_mut().find(|(channel, _)| *channel == record.channel) {
+                Some(entry) => entry.1 = &record.status,
+                None => latest.push((&record.channel, &record.status)),
+            }
+        }
+        latest
+            .into_iter()
+            .filter(|(_, status)| *status == "failed")
+            .filter_map(|(channel, _)| channel.parse().ok())
+            .collect()
+    }
+}
+
+/// Deliver a stored alert to one target, bypassing quiet windows and the minimum severity.
+/// Outcomes are reported per channel, ready for the delivery log.
+pub async fn resend_alert(
+    notifier: &Notifier,
+    monitor: &GitHubEventMonitor,
+    alert: &RealTimeSecretAlert,
+    target: &AlertTarget,
+) -> AlertDelivery {
+    let mut delivery = AlertDelivery::default();
+    let mut record = |channel: String, result: Result<()>| match result {
+        Ok(()) => {
+            delivery.delivered += 1;
+            delivery.delivered_to.push(channel);
+        }
+        Err(e) => delivery.errors.push(format!("{}: {}", channel, e)),
+    };
+
+    match target {
+        AlertTarget::Slack(channel) => {
+            record(target.to_string(), notifier.send_slack_to(alert, channel.as_deref()).await);
+        }
+        AlertTarget::Email(recipients) if recipients.is_empty() => {
+            record(target.to_string(), notifier.send_email(alert).await);
+        }
+        AlertTarget::Email(recipients) => {
+            record(target.to_string(), notifier.send_email_to(alert, recipients).await);
+        }
+        AlertTarget::Webhook(filter) => {
+            let endpoints = webhook_endpoints(monitor, filter.as_deref()).await;
+            if endpoints.is_empty() {
+                record(target.to_string(), Err(anyhow!("no matching webhook is configured")));
+            }
+            for endpoint in endpoints {
+                let channel = format!("webhook:{}", endpoint_label(&endpoint.url));
+                record(channel, monitor.send_webhook(alert, &endpoint).await);
+            }
+        }
+        AlertTarget::Chat(name) => {
+            let result = match monitor.chat_notifier(&target.to_string()) {
+                Some(chat) => chat.send(notifier, alert).await,
+                None => Err(anyhow!("no chat target named {} is configured", name)),
+            };
+            record(target.to_string(), result);
+        }
+    }
+
+    delivery
+}
+
+/// Configured webhooks matching `filter` by URL or endpoint label. A full URL that matches
+/// none is used as a one-off, unsigned endpoint.
+async fn webhook_endpoints(monitor: &GitHubEventMonitor, filter: Option<&str>) -> Vec<WebhookEndpoint> {
+    let configured = monitor.active_webhooks().await;
+    let Some(filter) = filter else {
+        return configured;
+    };
+
+    let matching: Vec<WebhookEndpoint> = configured
+        .into_iter()
+        .filter(|endpoint| endpoint.url == filter || endpoint_label(&endpoint.url) == filter)
+        .collect();
+    // Labels elide the path with `…`, so they never name a one-off endpoint
+    if matching.is_empty() && !filter.contains('…') && Url::parse(filter).is_ok() {
+        return vec![WebhookEndpoint {
+            id: Uuid::new_v4(),
+            url: filter.to_string(),
+            secret: None,
+            events: Vec::new(),
+            active: true,
+            created_at: Utc::now(),
+        }];
+    }
+    matching
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(channel: &str, status: &str) -> DeliveryRecord {
+        DeliveryRecord {
+            channel: channel.to_string(),
+            status: status.to_string(),
+            error: None,
+            resend: false,
+            attempted_at: "2024-01-01 00:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_targets() {
+        assert_eq!("slack:sec-ops".parse::<AlertTarget>().unwrap(), AlertTarget::Slack(Some("#sec-ops".to_string())));
+        assert_eq!("slack".parse::<AlertTarget>().unwrap(), AlertTarget::Slack(None));
+        assert_eq!(
+            "email:a@acme.test, b@acme.test".parse::<AlertTarget>().unwrap(),
+            AlertTarget::Email(vec!["a@acme.test".to_string(), "b@acme.test".to_string()])
+        );
+        assert_eq!(
+            "webhook:https://hooks.acme.test/alerts".parse::<AlertTarget>().unwrap(),
+            AlertTarget::Webhook(Some("https://hooks.acme.test/alerts".to_string()))
+        );
+        assert_eq!("chat:discord".parse::<AlertTarget>().unwrap(), AlertTarget::Chat("discord".to_string()));
+        assert!("chat".parse::<AlertTarget>().is_err());
+        assert!("pager:oncall".parse::<AlertTarget>().is_err());
+        assert_eq!(AlertTarget::Slack(Some("#sec-ops".to_string())).to_string(), "slack:#sec-ops");
+    }
+
+    #[test]
+    fn test_failed_in_uses_latest_attempt() {
+        let deliveries = [
+            record("slack", "failed"),
+            record("email", "delivered"),
+            record("webhook:https://hooks.acme.test/…", "failed"),
+            record("slack", "delivered"),
+            record("chat:teams", "failed"),
+        ];
+        assert_eq!(
+            AlertTarget::failed_in(&deliveries),
+            vec![AlertTarget::Webhook(Some("https://hooks.acme.test/…".to_string())), AlertTarget::Chat("teams".to_string())]
+        );
+    }
+}
@@ -0,0 +1,387 @@
+use anyhow::{anyhow, Context as _, Result};
+use chrono::Utc;
+use std::collections::HashMap;
+use std::path::Path;
+use tera::{Context, Tera, Value};
+use tracing::info;
+
+use super::i18n;
+use crate::core::{output, DigestSchedule, Locale};
+use crate::integration::ScanningReport;
+use crate::realtime::RealTimeSecretAlert;
+
+pub const SLACK: &str = "slack";
+pub const EMAIL_SUBJECT: &str = "email_subject";
+pub const EMAIL_BODY: &str = "email_body";
+pub const WEBHOOK: &str = "webhook";
+pub const SLACK_DIGEST: &str = "slack_digest";
+pub const EMAIL_DIGEST_SUBJECT: &str = "email_digest_subject";
+pub const EMAIL_DIGEST_BODY: &str = "email_digest_body";
+pub const WEBHOOK_DIGEST: &str = "webhook_digest";
+pub const EMAIL_HTML: &str = "email_html";
+pub const EMAIL_DIGEST_HTML: &str = "email_digest_html";
+pub const EMAIL_REPORT_SUBJECT: &str = "email_report_subject";
+pub const EMAIL_REPORT_BODY: &str = "email_report_body";
+pub const EMAIL_REPORT_HTML: &str = "email_report_html";
+
+/// Findings listed in a scan report email; the rest are only counted
+const REPORT_FINDINGS_LIMIT: usize = 50;
+
+/// Built-in templates; a `<name>.tera` file in the template directory replaces one
+const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    (SLACK, include_str!("templates/slack.tera")),
+    (EMAIL_SUBJECT, include_str!("templates/email_subject.tera")),
+    (EMAIL_BODY, include_str!("templates/email_body.tera")),
+    (WEBHOOK, include_str!("templates/webhook.tera")),
+    (SLACK_DIGEST, include_str!("templates/slack_digest.tera")),
+    (EMAIL_DIGEST_SUBJECT, include_str!("templates/email_digest_subject.tera")),
+    (EMAIL_DIGEST_BODY, include_str!("templates/email_digest_body.tera")),
+    (WEBHOOK_DIGEST, include_str!("templates/webhook_digest.tera")),
+    (EMAIL_HTML, include_str!("templates/email_html.tera")),
+    (EMAIL_DIGEST_HTML, include_str!("templates/email_digest_html.tera")),
+    (EMAIL_REPORT_SUBJECT, include_str!("templates/email_report_subject.tera")),
+    (EMAIL_REPORT_BODY, include_str!("templates/email_report_body.tera")),
+    (EMAIL_REPORT_HTML, include_str!("templates/email_report_html.tera")),
+];
+
+/// Per-channel message templates rendered with the fields of one alert
+pub struct NotificationTemplates {
+    tera: Tera,
+}
+
+impl NotificationTemplates {
+    pub fn builtin() -> Self {
+        let mut tera = Tera::default();
+        // Values in the HTML bodies are escaped; the other templates are plain text or JSON
+        tera.autoescape_on(vec!["_html"]);
+        for (name, template) in BUILTIN_TEMPLATES {
+            tera.add_raw_template(name, template).expect("built-in notification template is valid");
+        }
+        Self { tera }.with_locale(i18n::locale())
+    }
+
+    /// Render `t(id="...")` messages in `locale`
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        let translations = i18n::translations(locale);
+        self.tera
+            .register_function("t", move |args: &HashMap<String, Value>| translations.template_function(args));
+        self
+    }
+
+    /// Built-in templates, overridden by any `<name>.tera` files in `dir`.
+    /// A missing directory is not an error; a template that fails to parse is.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut templates = Self::builtin();
+        for (name, _) in BUILTIN_TEMPLATES {
+            let path = dir.join(format!("{}.tera", name));
+            if !path.is_file() {
+                continue;
+            }
+            let template = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            templates
+                .tera
+                .add_raw_template(name, &template)
+                .map_err(|e| anyhow!("Invalid notification template {}: {:?}", path.display(), e))?;
+            info!("Using notification template override {}", path.display());
+        }
+        Ok(templates)
+    }
+
+    /// Variables available to every template. `spread` is null unless a secret in the
+    /// alert has been found before.
+    pub fn context(alert: &RealTimeSecretAlert) -> Context {
+        let mut context = Context::new();
+        context.insert("alert", alert);
+        context.insert("event_id", &alert.event_id);
+        context.insert("repository", &alert.repository);
+        context.insert("repository_url", &format!("https://github.com/{}", alert.repository));
+        context.insert("commit_sha", &alert.commit_sha);
+        context.insert("short_sha", &alert.commit_sha.chars().take(7).collect::<String>());
+        context.insert(
+            "commit_url",
+            &format!("https://github.com/{}/commit/{}", alert.repository, alert.commit_sha),
+        );
+        context.insert("severity", &format!("{:?}", alert.alert_severity));
+        context.insert("detection_time", &alert.detection_time.format("%Y-%m-%d %H:%M:%S UTC").to_string());
+        context.insert("count", &alert.secrets_found.len());
+        context.insert("findings", &alert.secrets_found);
+        context.insert("spread", &alert.spread);
+        context.insert("output", output::style());
+        context
+    }
+
+    /// Variables available to the digest templates: `alerts`, `count`, `finding_count`, `since`, `until`,
+    /// and `schedule`, which is `hourly` or `daily` for the email digest and null for quiet-hours digests
+    pub fn digest_context(alerts: &[RealTimeSecretAlert]) -> Context {
+        Self::scheduled_digest_context(alerts, None)
+    }
+
+    pub fn scheduled_digest_context(alerts: &[RealTimeSecretAlert], schedule: Option<DigestSchedule>) -> Context {
+        let format = "%Y-%m-%d %H:%M:%S UTC";
+        let since = alerts.iter().map(|a| a.detection_time).min().unwrap_or_else(Utc::now);
+        let mut context = Context::new();
+        context.insert("alerts", alerts);
+        context.insert("count", &alerts.len());
+        context.insert("finding_count", &alerts.iter().map(|a| a.secrets_found.len()).sum::<usize>());
+        context.insert("since", &since.format(format).to_string());
+        context.insert("until", &Utc::now().format(format).to_string());
+        context.insert("schedule", &schedule);
+        context.insert("output", output::style());
+        context
+    }
+
+    /// Variables available to the scan report templates. Matched text is left out, and only the
+    /// first findings are listed in `findings`; `omitted` counts the rest.
+    pub fn report_context(report: &ScanningReport) -> Context {
+        let format = "%Y-%m-%d %H:%M:%S UTC";
+        let active: Vec<_> = report.secrets_found.iter().filter(|s| !s.suppressed).collect();
+        let mut by_severity: Vec<(String, usize)> = Vec::new();
+        for secret in &active {
+            let severity = format!("{:?}", secret.severity);
+            match by_severity.iter_mut().find(|(s, _)| *s == severity) {
+                Some((_, count)) => *count += 1,
+                None => by_severity.push((severity, 1)),
+            }
+        }
+        let findings: Vec<_> = active
+            .iter()
+            .take(REPORT_FINDINGS_LIMIT)
+            .map(|s| {
+                serde_json::json!({
+                    "detector_name": s.detector_name,
+                    "severity": s.severity,
+                    "filename": s.filename,
+                    "line_number": s.line_number,
+                    "commit_sha": s.commit_sha,
+                })
+            })
+            .collect();
+
+        let mut context = Context::new();
+        context.insert("scan_id", &report.scan_id.to_string());
+        context.insert("scan_type", &format!("{:?}", report.scan_type));
+        context.insert("target", &report.target);
+        context.insert("status", &format!("{:?}", report.status));
+        context.insert("started_at", &report.started_at.format(format).to_string());
+        context.insert("completed_at", &report.completed_at.map(|at| at.format(format).to_string()));
+        context.insert("count", &active.len());
+        context.insert("suppressed", &(report.secrets_found.len() - active.len()));
+        context.insert(
+            "by_severity",
+            &by_severity
+                .iter()
+                .map(|(severity, count)| serde_json::json!({ "severity": severity, "count": count }))
+                .collect::<Vec<_>>(),
+        );
+        context.insert("omitted", &(active.len() - findings.len()));
+        context.insert("findings", &findings);
+        context.insert("repositories", &report.repositories);
+        context.insert("recommendations", &report.recommendations);
+        context.insert("output", output::style());
+        context
+    }
+
+    pub fn render(&self, name: &str, alert: &RealTimeSecretAlert) -> Result<String> {
+        self.render_with(name, &Self::context(alert))
+    }
+
+    fn render_with(&self, name: &str, context: &Context) -> Result<String> {
+        self.tera
+            .render(name, context)
+            .map_err(|e| anyhow!("Failed to render the {} notification template: {:?}", name, e))
+    }
+
+    pub fn slack_text(&self, alert: &RealTimeSecretAlert) -> Result<String> {
+        Ok(self.render(SLACK, alert)?.trim_end().to_string())
+    }
+
+    /// Subject and plain-text body
+    pub fn email(&self, alert: &RealTimeSecretAlert) -> Result<(String, String)> {
+        let subject = self.render(EMAIL_SUBJECT, alert)?.lines().next().unwrap_or_default().trim().to_string();
+        Ok((subject, self.render(EMAIL_BODY, alert)?))
+    }
+
+    pub fn email_html(&self, alert: &RealTimeSecretAlert) -> Result<String> {
+        self.render(EMAIL_HTML, alert)
+    }
+
+    /// Webhook bodies are JSON, so the rendered template must parse as JSON
+    pub fn webhook_payload(&self, alert: &RealTimeSecretAlert) -> Result<serde_json::Value> {
+        let rendered = self.render(WEBHOOK, alert)?;
+        serde_json::from_str(&rendered).context("The webhook notification template did not render valid JSON")
+    }
+
+    pub fn slack_digest_text(&self, alerts: &[RealTimeSecretAlert]) -> Result<String> {
+        Ok(self.render_with(SLACK_DIGEST, &Self::digest_context(alerts))?.trim_end().to_string())
+    }
+
+    pub fn email_digest(&self, alerts: &[RealTimeSecretAlert]) -> Result<(String, String)> {
+        let (subject, body, _) = self.scheduled_email_digest(alerts, None)?;
+        Ok((subject, body))
+    }
+
+    /// Subject, plain-text and HTML body of a digest
+    pub fn scheduled_email_digest(
+        &self,
+        alerts: &[RealTimeSecretAlert],
+        schedule: Option<DigestSchedule>,
+    ) -> Result<(String, String, String)> {
+        let context = Self::scheduled_digest_context(alerts, schedule);
+        let subject = self.render_with(EMAIL_DIGEST_SUBJECT, &context)?.lines().next().unwrap_or_default().trim().to_string();
+        Ok((
+            subject,
+            self.render_with(EMAIL_DIGEST_BODY, &context)?,
+            self.render_with(EMAIL_DIGEST_HTML, &context)?,
+        ))
+    }
+
+    /// Subject, plain-text and HTML body summarizing a scan
+    pub fn email_report(&self, report: &ScanningReport) -> Result<(String, String, String)> {
+        let context = Self::report_context(report);
+        let subject = self.render_with(EMAIL_REPORT_SUBJECT, &context)?.lines().next().unwrap_or_default().trim().to_string();
+        Ok((
+            subject,
+            self.render_with(EMAIL_REPORT_BODY, &context)?,
+            self.render_with(EMAIL_REPORT_HTML, &context)?,
+        ))
+    }
+
+    pub fn webhook_digest_payload(&self, alerts: &[RealTimeSecretAlert]) -> Result<serde_json::Value> {
+        let rendered = self.render_with(WEBHOOK_DIGEST, &Self::digest_context(alerts))?;
+        serde_json::from_str(&rendered).context("The webhook digest template did not render valid JSON")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::sample_alert;
+
+    #[test]
+    fn test_builtin_templates_render_finding_fields() {
+        let templates = NotificationTemplates::builtin();
+        let alert = sample_alert();
+
+        let slack = templates.slack_text(&alert).unwrap();
+        assert!(slack.contains("*Critical secret alert*"));
+        assert!(slack.contains(&alert.secrets_found[0].detector_name));
+
+        let (subject, body) = templates.email(&alert).unwrap();
+        assert!(subject.starts_with("[Critical]"));
+        assert!(body.contains(&alert.secrets_found[0].filename));
+
+        // The default webhook body is the alert itself
+        assert_eq!(templates.webhook_payload(&alert).unwrap(), serde_json::to_value(&alert).unwrap());
+    }
+
+    #[test]
+    fn test_spread_is_shown_when_present() {
+        let templates = NotificationTemplates::builtin();
+        let mut alert = sample_alert();
+        assert!(!templates.email(&alert).unwrap().1.contains("Spread:"));
+
+        let seen = chrono::DateTime::parse_from_rfc3339("2024-03-01T09:30:00Z").unwrap().with_timezone(&Utc);
+        alert.spread = Some(crate::realtime::SecretSpread {
+            occurrences: 4,
+            files: 3,
+            commits: 4,
+            repositories: 1,
+            first_seen: seen,
+            last_seen: seen,
+        });
+        assert!(templates.slack_text(&alert).unwrap().contains("Seen 4 times in 3 files across 1 repository since 2024-03-01 09:30 UTC"));
+        assert!(templates.email(&alert).unwrap().1.contains("found 4 times in 3 files across 1 repository"));
+    }
+
+    #[test]
+    fn test_digest_templates_list_every_alert() {
+        let templates = NotificationTemplates::builtin();
+        let alerts = vec![sample_alert(), sample_alert()];
+
+        assert!(templates.slack_digest_text(&alerts).unwrap().contains("2 alerts"));
+        let (subject, body) = templates.email_digest(&alerts).unwrap();
+        assert!(subject.contains("2 secret alerts"));
+        assert_eq!(body.matches("AWS Access Key ID").count(), 2);
+        assert_eq!(templates.webhook_digest_payload(&alerts).unwrap()["alerts"].as_array().unwrap().len(), 2);
+
+        let (subject, _, html) = templates.scheduled_email_digest(&alerts, Some(DigestSchedule::Daily)).unwrap();
+        assert_eq!(subject, "[Digest] 2 secret alerts in the last day");
+        assert_eq!(html.matches("AWS Access Key ID").count(), 2);
+    }
+
+    #[test]
+    fn test_html_bodies_escape_values() {
+        let templates = NotificationTemplates::builtin();
+        let mut alert = sample_alert();
+        alert.secrets_found[0].filename = "<script>.yml".to_string();
+
+        let html = templates.email_html(&alert).unwrap();
+        assert!(html.contains("&lt;script&gt;.yml"));
+        assert!(!html.contains("<script>"));
+        // Plain-text bodies are not escaped
+        assert!(templates.email(&alert).unwrap().1.contains("<script>.yml"));
+    }
+
+    #[test]
+    fn test_report_lists_findings_without_matched_text() {
+        use crate::integration::{synthetic_finding, ScanType};
+
+        let templates = NotificationTemplates::builtin();
+        let mut report = ScanningReport::new(uuid::Uuid::new_v4(), ScanType::ManualRepository, "octo/repo");
+        let finding = synthetic_finding();
+        report.secrets_found = vec![finding.clone(); REPORT_FINDINGS_LIMIT + 2];
+
+        let (subject, body, html) = templates.email_report(&report).unwrap();
+        assert_eq!(subject, format!("[Scan Running] {} secrets found in octo/repo", REPORT_FINDINGS_LIMIT + 2));
+        assert_eq!(body.matches(&finding.detector_name).count(), REPORT_FINDINGS_LIMIT);
+        assert!(body.contains("... and 2 more"));
+        assert!(!body.contains(&finding.matched_text) && !html.contains(&finding.matched_text));
+    }
+
+    #[test]
+    fn test_templates_render_in_the_configured_locale() {
+        let alert = sample_alert();
+        let german = NotificationTemplates::builtin().with_locale(Locale::De);
+        let (subject, body) = german.email(&alert).unwrap();
+        assert_eq!(subject, format!("[Kritisch] 1 Secret in {} entdeckt", alert.repository));
+        assert!(body.contains("Schweregrad: Kritisch"));
+        assert!(german.slack_text(&alert).unwrap().contains("*Secret-Alarm (Kritisch)*"));
+
+        let spanish = NotificationTemplates::builtin().with_locale(Locale::Es);
+        let (subject, _, _) = spanish.scheduled_email_digest(&[alert.clone(), alert], Some(DigestSchedule::Hourly)).unwrap();
+        assert_eq!(subject, "[Resumen] 2 alertas de secretos en la última hora");
+    }
+
+    #[test]
+    fn test_slack_text_follows_the_output_profile() {
+        use crate::core::{OutputConfig, OutputProfile, OutputStyle};
+
+        let templates = NotificationTemplates::builtin();
+        let alert = sample_alert();
+        let mut context = NotificationTemplates::context(&alert);
+        let ascii = OutputStyle::new(&OutputConfig { profiles: vec![OutputProfile::Ascii] });
+        context.insert("output", &ascii);
+
+        let slack = templates.render_with(SLACK, &context).unwrap();
+        assert!(slack.starts_with("*"));
+        assert!(!slack.contains(":rotating_light:") && !slack.contains('•'));
+    }
+
+    #[test]
+    fn test_directory_overrides_builtin() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("slack.tera"), "{{ repository }}: {{ count }}").unwrap();
+        std::fs::write(dir.path().join("webhook.tera"), r#"{"text": "{{ severity }}"}"#).unwrap();
+
+        let templates = NotificationTemplates::load(dir.path()).unwrap();
+        let alert = sample_alert();
+        assert_eq!(templates.slack_text(&alert).unwrap(), format!("{}: 1", alert.repository));
+        assert_eq!(templates.webhook_payload(&alert).unwrap()["text"], "Critical");
+        // Templates without an override keep the built-in
+        assert!(templates.email(&alert).unwrap().0.starts_with("[Critical]"));
+
+        std::fs::write(dir.path().join("email_body.tera"), "{% if %}").unwrap();
+        assert!(NotificationTemplates::load(dir.path()).is_err());
+    }
+}
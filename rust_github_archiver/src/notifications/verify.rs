@@ -0,0 +1,94 @@
+use anyhow::Result;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Instant;
+use uuid::Uuid;
+
+use super::{sample_alert, Notifier};
+use crate::realtime::{GitHubEventMonitor, RealTimeSecretAlert};
+
+/// Outcome of one test delivery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryCheck {
+    pub channel: String,
+    /// Endpoint with any credentials in the URL path left out
+    pub endpoint: String,
+    /// Whether the receiver can verify the body (`X-Hub-Signature-256`); only webhooks sign
+    pub signed: bool,
+    pub delivered: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// The sample alert, tagged so receivers can tell verification messages from real findings
+pub fn verification_alert() -> RealTimeSecretAlert {
+    let mut alert = sample_alert();
+    alert.event_id = format!("verify-{}", Uuid::new_v4());
+    alert
+}
+
+/// `scheme://host[:port]/…`, so Slack tokens and other path secrets stay out of reports
+pub fn endpoint_label(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(parsed) => {
+            let host = parsed.host_str().unwrap_or_default();
+            let port = parsed.port().map(|p| format!(":{}", p)).unwrap_or_default();
+            let path = if parsed.path().len() > 1 { "/…" } else { "" };
+            format!("{}://{}{}{}", parsed.scheme(), host, port, path)
+        }
+        Err(_) => "<invalid URL>".to_string(),
+    }
+}
+
+async fn timed(
+    channel: &str,
+    endpoint: String,
+    signed: bool,
+    delivery: impl Future<Output = Result<()>>,
+) -> DeliveryCheck {
+    let started = Instant::now();
+    let result = delivery.await;
+    DeliveryCheck {
+        channel: channel.to_string(),
+        endpoint,
+        signed,
+        delivered: result.is_ok(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: result.err().map(|e| e.to_string()),
+    }
+}
+
+/// Send one verification alert to every configured Slack, email and webhook endpoint
+pub async fn verify_endpoints(notifier: &Notifier, monitor: &GitHubEventMonitor) -> Vec<DeliveryCheck> {
+    let alert = verification_alert();
+    let mut checks = Vec::new();
+
+    if let Some(url) = notifier.slack_webhook_url() {
+        checks.push(timed("slack", endpoint_label(url), false, notifier.send_slack(&alert)).await);
+    }
+    if let Some(endpoint) = notifier.email_endpoint() {
+        checks.push(timed("email", endpoint, false, notifier.send_email(&alert)).await);
+    }
+    for webhook in monitor.active_webhooks().await {
+        let signed = webhook.secret.is_some();
+        checks.push(timed("webhook", endpoint_label(&webhook.url), signed, monitor.send_webhook(&alert, &webhook)).await);
+    }
+
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_label_hides_path() {
+        assert_eq!(
+            endpoint_label("https://hooks.slack.com/services/T000/B000/XXXX"),
+            "https://hooks.slack.com/…"
+        );
+        assert_eq!(endpoint_label("http://127.0.0.1:8080/"), "http://127.0.0.1:8080");
+        assert_eq!(endpoint_label("not a url"), "<invalid URL>");
+    }
+}
@@ -0,0 +1,209 @@
+use anyhow::Result;
+use rusqlite::types::Value;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use super::compression::{self, ALERT_JSON};
+use super::SecretDatabase;
+use crate::realtime::{AlertDelivery, RealTimeSecretAlert};
+
+/// An alert as it was sent (after redaction), with every delivery attempt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredAlert {
+    pub id: i64,
+    pub alert: RealTimeSecretAlert,
+    pub created_at: String,
+    pub deliveries: Vec<DeliveryRecord>,
+}
+
+/// One attempt to deliver an alert to one channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryRecord {
+    /// `slack`, `email` or `webhook`, with the target after a colon when known
+    pub channel: String,
+    /// `delivered`, `failed` or `held`
+    pub status: String,
+    pub error: Option<String>,
+    /// Sent with `alerts resend` rather than when the alert was raised
+    pub resend: bool,
+    pub attempted_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertSummary {
+    pub id: i64,
+    pub event_id: String,
+    pub repository: String,
+    pub severity: String,
+    pub secrets: usize,
+    pub created_at: String,
+    pub delivered: i64,
+    pub failed: i64,
+}
+
+impl SecretDatabase {
+    pub(super) fn initialize_alert_log_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS alert_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                event_id TEXT NOT NULL,
+                repository TEXT NOT NULL,
+                commit_sha TEXT NOT NULL,
+                severity TEXT NOT NULL,
+                secrets INTEGER NOT NULL,
+                alert_json TEXT NOT NULL,
+                created_at DATETIME NOT NULL
+            )",
+            [],
+        )?;
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS alert_deliveries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                alert_id INTEGER NOT NULL REFERENCES alert_log(id) ON DELETE CASCADE,
+                channel TEXT NOT NULL,
+                status TEXT NOT NULL,
+                error TEXT,
+                resend BOOLEAN NOT NULL DEFAULT FALSE,
+                attempted_at DATETIME NOT NULL
+            )",
+            [],
+        )?;
+        self.connection.execute(
+            "CREATE INDEX IF NOT EXISTS idx_alert_deliveries_alert ON alert_deliveries(alert_id)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Store a sent alert and its delivery outcome. Returns the alert id.
+    pub fn record_alert(&self, alert: &RealTimeSecretAlert, delivery: &AlertDelivery) -> Result<i64> {
+        self.connection.execute(
+            "INSERT INTO alert_log (event_id, repository, commit_sha, severity, secrets, alert_json, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, datetime('now'))",
+            params![
+                alert.event_id,
+                alert.repository,
+                alert.commit_sha,
+                format!("{:?}", alert.alert_severity),
+                alert.secrets_found.len() as i64,
+                compression::policy().encode(&ALERT_JSON, serde_json::to_string(alert)?)?,
+            ],
+        )?;
+        let id = self.connection.last_insert_rowid();
+        self.record_deliveries(id, delivery, false)?;
+        Ok(id)
+    }
+
+    /// Log each channel in `delivery` against a stored alert
+    pub fn record_deliveries(&self, alert_id: i64, delivery: &AlertDelivery, resend: bool) -> Result<()> {
+        let tx = self.connection.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO alert_deliveries (alert_id, channel, status, error, resend, attempted_at)
+                VALUES (?, ?, ?, ?, ?, datetime('now'))",
+            )?;
+            for channel in &delivery.delivered_to {
+                stmt.execute(params![alert_id, channel, "delivered", None::<String>, resend])?;
+            }
+            for channel in &delivery.held {
+                stmt.execute(params![alert_id, channel, "held", None::<String>, resend])?;
+            }
+            // Errors read `channel: message`
+            for error in &delivery.errors {
+                let (channel, message) = error.split_once(": ").unwrap_or(("unknown", error.as_str()));
+                stmt.execute(params![alert_id, channel, "failed", message, resend])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn alert(&self, id: i64) -> Result<Option<StoredAlert>> {
+        let row = self
+            .connection
+            .query_row("SELECT alert_json, created_at FROM alert_log WHERE id = ?", params![id], |row| {
+                Ok((row.get::<_, Value>(0)?, row.get::<_, String>(1)?))
+            })
+            .optional()?;
+        let Some((json, created_at)) = row else {
+            return Ok(None);
+        };
+
+        let mut stmt = self.connection.prepare(
+            "SELECT channel, status, error, resend, attempted_at FROM alert_deliveries
+            WHERE alert_id = ? ORDER BY id",
+        )?;
+        let deliveries = stmt
+            .query_map(params![id], |row| {
+                Ok(DeliveryRecord {
+                    channel: row.get(0)?,
+                    status: row.get(1)?,
+                    error: row.get(2)?,
+                    resend: row.get(3)?,
+                    attempted_at: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+
+        Ok(Some(StoredAlert { id, alert: serde_json::from_str(&compression::decode(&json)?)?, created_at, deliveries }))
+    }
+
+    /// Most recent alerts first; `failed_only` keeps alerts with a failed delivery
+    pub fn list_alerts(&self, limit: usize, failed_only: bool) -> Result<Vec<AlertSummary>> {
+        let mut stmt = self.connection.prepare(&format!(
+            "SELECT a.id, a.event_id, a.repository, a.severity, a.secrets, a.created_at,
+                COUNT(CASE WHEN d.status = 'delivered' THEN 1 END),
+                COUNT(CASE WHEN d.status = 'failed' THEN 1 END)
+            FROM alert_log a LEFT JOIN alert_deliveries d ON d.alert_id = a.id
+            GROUP BY a.id {}
+            ORDER BY a.id DESC LIMIT ?",
+            if failed_only { "HAVING COUNT(CASE WHEN d.status = 'failed' THEN 1 END) > 0" } else { "" }
+        ))?;
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            Ok(AlertSummary {
+                id: row.get(0)?,
+                event_id: row.get(1)?,
+                repository: row.get(2)?,
+                severity: row.get(3)?,
+                secrets: row.get::<_, i64>(4)? as usize,
+                created_at: row.get(5)?,
+                delivered: row.get(6)?,
+                failed: row.get(7)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::sample_alert;
+
+    #[test]
+    fn test_alert_and_deliveries_round_trip() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let delivery = AlertDelivery {
+            delivered: 1,
+            delivered_to: vec!["email".to_string()],
+            errors: vec!["slack: Slack returned 404 Not Found: no_team".to_string()],
+            ..Default::default()
+        };
+        let id = db.record_alert(&sample_alert(), &delivery).unwrap();
+
+        let summaries = db.list_alerts(10, true).unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!((summaries[0].delivered, summaries[0].failed), (1, 1));
+
+        let resent = AlertDelivery { delivered: 1, delivered_to: vec!["slack:#sec-ops".to_string()], ..Default::default() };
+        db.record_deliveries(id, &resent, true).unwrap();
+        let stored = db.alert(id).unwrap().unwrap();
+        assert_eq!(stored.alert.repository, sample_alert().repository);
+        assert_eq!(stored.deliveries.len(), 3);
+        let failed = stored.deliveries.iter().find(|d| d.status == "failed").unwrap();
+        assert_eq!(failed.channel, "slack");
+        assert_eq!(failed.error.as_deref(), Some("Slack returned 404 Not Found: no_team"));
+        assert!(stored.deliveries.last().unwrap().resend);
+        assert!(db.alert(id + 1).unwrap().is_none());
+    }
+}
@@ -0,0 +1,1079 @@
+use anyhow::{anyhow, Context, Result};
+use aws_config::BehaviorVersion;
+use aws_sdk_s3::primitives::ByteStream;
+use chrono::{DateTime, Utc};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, MAX_TAG_LEN, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use rusqlite::{params, types::Value as SqlValue, Connection};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{postgres::PgPoolOptions, Postgres, QueryBuilder, Transaction};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokio::io::AsyncWriteExt;
+use tracing::info;
+
+use super::{AttachmentStore, SecretDatabase, SCHEMA_VERSION};
+use crate::core::migrations::postgres_version;
+use crate::core::{build_client_with_timeout, offline, HttpConfig};
+
+/// Every backup file starts with this line, followed by the JSON manifest on one line
+const MAGIC: &[u8] = b"GITARCHIVER-BACKUP-2\n";
+
+/// Passphrase for encrypted backups. Read from the environment so it never shows up
+/// in shell history or the process list.
+pub const BACKUP_KEY_ENV: &str = "BACKUP_ENCRYPTION_KEY";
+
+const KDF_ITERATIONS: u32 = 600_000;
+
+/// Plaintext bytes per encrypted chunk; neither backup nor restore holds more than one in memory
+const CHUNK_SIZE: usize = 1 << 20;
+
+const ENTRY_DATABASE: u8 = 0;
+const ENTRY_ATTACHMENT: u8 = 1;
+
+/// Schema of a Postgres database that restored findings tables are created in, apart
+/// from the archive's own tables
+pub const POSTGRES_FINDINGS_SCHEMA: &str = "findings";
+
+/// Store name the restored schema version is recorded under in `schema_migrations`
+const FINDINGS_STORE: &str = "findings";
+
+/// Postgres accepts at most this many bind parameters per statement
+const MAX_BIND_PARAMETERS: usize = 65_535;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// `SCHEMA_VERSION` of the binary that took the backup
+    pub schema_version: i32,
+    pub created_at: DateTime<Utc>,
+    pub source: String,
+    /// Row count per table at snapshot time
+    pub tables: BTreeMap<String, i64>,
+    pub attachments: usize,
+    /// SHA-256 of the unencrypted payload
+    pub sha256: String,
+    pub encryption: Option<BackupEncryption>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupEncryption {
+    pub algorithm: String,
+    pub kdf: String,
+    pub iterations: u32,
+    pub salt: String,
+    /// Chunk `n` is sealed with this nonce, its last four bytes XORed with `n`
+    pub nonce: String,
+    /// Plaintext bytes per sealed chunk; the last chunk is shorter
+    pub chunk_size: usize,
+}
+
+/// A backup unpacked into a staging directory and checked against its manifest.
+/// The directory is removed when this is dropped.
+#[derive(Debug)]
+pub struct BackupContents {
+    staging: PathBuf,
+    /// The database snapshot
+    pub database: PathBuf,
+    /// One file per attachment blob
+    pub attachments: Vec<PathBuf>,
+}
+
+impl Drop for BackupContents {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.staging).ok();
+    }
+}
+
+impl SecretDatabase {
+    pub fn schema_version(&self) -> Result<i32> {
+        Ok(self.connection.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+    }
+
+    pub fn table_row_counts(&self) -> Result<BTreeMap<String, i64>> {
+        let mut stmt = self
+            .connection
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")?;
+        let tables: Vec<String> = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+
+        let mut counts = BTreeMap::new();
+        for table in tables {
+            let count = self
+                .connection
+                .query_row(&format!("SELECT COUNT(*) FROM \"{}\"", table), [], |row| row.get(0))?;
+            counts.insert(table, count);
+        }
+        Ok(counts)
+    }
+
+    /// Write a transactionally consistent copy of the database to `path`, which must not exist.
+    /// Writers can keep going while the copy is taken.
+    pub fn snapshot_to(&self, path: &Path) -> Result<()> {
+        self.connection
+            .execute("VACUUM INTO ?", params![path.to_string_lossy()])
+            .with_context(|| format!("Failed to snapshot database to {}", path.display()))?;
+        Ok(())
+    }
+}
+
+/// Snapshot the findings database at `db_path` (and its attachments) into the backup file
+/// `out`, encrypted when a passphrase is given. The snapshot and blobs are streamed from
+/// disk, so memory use does not grow with the database.
+pub fn create_backup(db_path: &str, passphrase: Option<&str>, out: &Path) -> Result<BackupManifest> {
+    let database = SecretDatabase::new(db_path)?;
+    let snapshot = PathBuf::from(format!("{}.snapshot-{}", db_path, uuid::Uuid::new_v4().simple()));
+    database.snapshot_to(&snapshot)?;
+    let written = write_backup(&database, db_path, &snapshot, passphrase, out);
+    std::fs::remove_file(&snapshot).ok();
+    if written.is_err() {
+        std::fs::remove_file(out).ok();
+    }
+    written
+}
+
+fn write_backup(
+    database: &SecretDatabase,
+    db_path: &str,
+    snapshot: &Path,
+    passphrase: Option<&str>,
+    out: &Path,
+) -> Result<BackupManifest> {
+    let store = AttachmentStore::beside_database(db_path);
+    let mut entries = vec![(ENTRY_DATABASE, snapshot.to_path_buf())];
+    let mut digests: Vec<String> = database.referenced_attachments()?.into_iter().collect();
+    digests.sort();
+    for digest in digests {
+        entries.push((ENTRY_ATTACHMENT, store.path_for(&digest)?));
+    }
+
+    // The manifest leads the file and carries the payload's checksum, so the payload is read twice
+    let mut hasher = Sha256::new();
+    write_payload(&mut hasher, &entries)?;
+
+    let mut manifest = BackupManifest {
+        schema_version: database.schema_version()?,
+        created_at: Utc::now(),
+        source: db_path.to_string(),
+        tables: database.table_row_counts()?,
+        attachments: entries.len() - 1,
+        sha256: hex::encode(hasher.finalize()),
+        encryption: None,
+    };
+    let sealing = match passphrase {
+        Some(passphrase) => {
+            let (key, nonce, encryption) = new_encryption(passphrase)?;
+            manifest.encryption = Some(encryption);
+            Some((key, nonce))
+        }
+        None => None,
+    };
+
+    let mut file = BufWriter::new(
+        File::create(out).with_context(|| format!("Failed to write backup to {}", out.display()))?,
+    );
+    file.write_all(MAGIC)?;
+    serde_json::to_writer(&mut file, &manifest)?;
+    file.write_all(b"\n")?;
+    match sealing {
+        Some((key, nonce)) => {
+            let mut sealed = SealingWriter::new(&mut file, key, nonce, CHUNK_SIZE);
+            write_payload(&mut sealed, &entries)?;
+            sealed.finish()?;
+        }
+        None => write_payload(&mut file, &entries)?,
+    }
+    file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    Ok(manifest)
+}
+
+/// Each entry is its type, its length as a big-endian u64, then its bytes
+fn write_payload<W: Write>(out: &mut W, entries: &[(u8, PathBuf)]) -> Result<()> {
+    for (kind, path) in entries {
+        let file = File::open(path).with_context(|| match *kind {
+            ENTRY_ATTACHMENT => format!("Attachment {} is missing from the store", path.display()),
+            _ => format!("Failed to read snapshot {}", path.display()),
+        })?;
+        let len = file.metadata()?.len();
+        out.write_all(&[*kind])?;
+        out.write_all(&len.to_be_bytes())?;
+        if io::copy(&mut file.take(len), out)? != len {
+            return Err(anyhow!("{} shrank while it was being backed up", path.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Only the manifest of a backup file; needs no passphrase
+pub fn read_manifest(file: &Path) -> Result<BackupManifest> {
+    read_header(&mut open_backup_file(file)?)
+}
+
+/// Verify and unpack the backup file `file` into `staging`, a directory on the same
+/// filesystem as the restore target (see [`restore_staging`])
+pub fn read_backup(file: &Path, passphrase: Option<&str>, staging: &Path) -> Result<(BackupManifest, BackupContents)> {
+    let mut input = open_backup_file(file)?;
+    let manifest = read_header(&mut input)?;
+    let payload: Box<dyn Read> = match &manifest.encryption {
+        Some(encryption) => {
+            let passphrase =
+                passphrase.ok_or_else(|| anyhow!("Backup is encrypted; set {} to restore it", BACKUP_KEY_ENV))?;
+            Box::new(OpeningReader::new(input, passphrase, encryption)?)
+        }
+        None => Box::new(input),
+    };
+
+    // A leftover from an interrupted restore is replaced; `contents` removes the directory on failure
+    std::fs::remove_dir_all(staging).ok();
+    std::fs::create_dir_all(staging)
+        .with_context(|| format!("Failed to create staging directory {}", staging.display()))?;
+    let mut contents =
+        BackupContents { staging: staging.to_path_buf(), database: staging.join("database"), attachments: Vec::new() };
+
+    let mut payload = HashingReader { inner: payload, hasher: Sha256::new() };
+    let mut has_database = false;
+    loop {
+        let mut kind = [0u8; 1];
+        match payload.read_exact(&mut kind) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            read => read?,
+        }
+        let mut len = [0u8; 8];
+        payload.read_exact(&mut len).map_err(truncated)?;
+        let len = u64::from_be_bytes(len);
+
+        let path = match kind[0] {
+            ENTRY_DATABASE => {
+                has_database = true;
+                contents.database.clone()
+            }
+            ENTRY_ATTACHMENT => {
+                let path = staging.join(format!("attachment-{}", contents.attachments.len()));
+                contents.attachments.push(path.clone());
+                path
+            }
+            other => return Err(anyhow!("Unknown backup entry type {}", other)),
+        };
+        let mut out = BufWriter::new(File::create(&path)?);
+        if io::copy(&mut (&mut payload).take(len), &mut out)? != len {
+            return Err(anyhow!("Backup payload is truncated"));
+        }
+        out.flush()?;
+    }
+
+    if hex::encode(payload.hasher.finalize()) != manifest.sha256 {
+        return Err(anyhow!("Backup is corrupted: checksum mismatch"));
+    }
+    if !has_database {
+        return Err(anyhow!("Backup contains no database"));
+    }
+    Ok((manifest, contents))
+}
+
+fn truncated(error: io::Error) -> anyhow::Error {
+    if error.kind() == io::ErrorKind::UnexpectedEof {
+        anyhow!("Backup payload is truncated")
+    } else {
+        error.into()
+    }
+}
+
+fn open_backup_file(path: &Path) -> Result<BufReader<File>> {
+    let file = File::open(path).with_context(|| format!("Failed to read backup {}", path.display()))?;
+    Ok(BufReader::new(file))
+}
+
+fn read_header<R: BufRead>(input: &mut R) -> Result<BackupManifest> {
+    let mut magic = [0u8; MAGIC.len()];
+    if input.read_exact(&mut magic).is_err() || magic != MAGIC {
+        return Err(anyhow!("Not a GitArchiver backup file"));
+    }
+    let mut line = Vec::new();
+    input.read_until(b'\n', &mut line)?;
+    if line.pop() != Some(b'\n') {
+        return Err(anyhow!("Backup manifest is truncated"));
+    }
+    serde_json::from_slice(&line).context("Backup manifest is not valid JSON")
+}
+
+fn is_postgres_url(target: &str) -> bool {
+    target.starts_with("postgres://") || target.starts_with("postgresql://")
+}
+
+/// Where a backup restored to `target` is unpacked: beside a SQLite target, so the
+/// database can be moved into place, or in the temporary directory for Postgres
+pub fn restore_staging(target: &str) -> PathBuf {
+    if is_postgres_url(target) {
+        std::env::temp_dir().join(format!("gitarchiver-restore-{}", uuid::Uuid::new_v4().simple()))
+    } else {
+        PathBuf::from(format!("{}.restoring", target))
+    }
+}
+
+/// Restore into `target`: the path of a SQLite findings database, or a `postgres://` URL
+/// whose `findings` schema gets a copy of every table. An existing database (or schema)
+/// is only replaced with `force`. The snapshot is checked before anything is replaced.
+pub async fn restore_backup(manifest: &BackupManifest, contents: &BackupContents, target: &str, force: bool) -> Result<()> {
+    if manifest.schema_version > SCHEMA_VERSION {
+        return Err(anyhow!(
+            "Backup has schema version {}, this build supports up to {}; upgrade before restoring",
+            manifest.schema_version,
+            SCHEMA_VERSION
+        ));
+    }
+    let check: String = open_snapshot(&contents.database)?.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+    if check != "ok" {
+        return Err(anyhow!("Restored database failed the integrity check: {}", check));
+    }
+
+    if is_postgres_url(target) {
+        restore_into_postgres(manifest, contents, target, force).await?;
+    } else {
+        restore_into_sqlite(contents, target, force)?;
+    }
+
+    info!(
+        "Restored backup from {} ({} tables, {} attachments)",
+        manifest.created_at,
+        manifest.tables.len(),
+        contents.attachments.len()
+    );
+    Ok(())
+}
+
+fn open_snapshot(path: &Path) -> Result<Connection> {
+    super::encryption::open_keyed(&path.to_string_lossy())
+}
+
+fn restore_into_sqlite(contents: &BackupContents, target: &str, force: bool) -> Result<()> {
+    if Path::new(target).exists() && !force {
+        return Err(anyhow!("{} already exists; pass --force to replace it", target));
+    }
+    for suffix in ["-wal", "-shm"] {
+        std::fs::remove_file(format!("{}{}", target, suffix)).ok();
+    }
+    std::fs::rename(&contents.database, target)?;
+
+    // Opening applies schema additions made since the backup was taken
+    SecretDatabase::new(target)?;
+    let store = AttachmentStore::beside_database(target);
+    for blob in &contents.attachments {
+        store.put_file(blob)?;
+    }
+    Ok(())
+}
+
+/// Copy every table of the snapshot into the `findings` schema in one transaction, and the
+/// attachment blobs into `findings.attachment_blobs`. Rows are read and inserted in batches.
+async fn restore_into_postgres(manifest: &BackupManifest, contents: &BackupContents, url: &str, force: bool) -> Result<()> {
+    offline::policy().check_url(url)?;
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(url)
+        .await
+        .context("Failed to connect to the Postgres restore target")?;
+    // Creates `schema_migrations` if this database has none yet
+    postgres_version(&pool, FINDINGS_STORE).await?;
+
+    let mut tx = pool.begin().await?;
+    let existing: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM information_schema.tables WHERE table_schema = $1")
+        .bind(POSTGRES_FINDINGS_SCHEMA)
+        .fetch_one(&mut *tx)
+        .await?;
+    if existing > 0 && !force {
+        return Err(anyhow!(
+            "The Postgres database already has a {} schema; pass --force to replace it",
+            POSTGRES_FINDINGS_SCHEMA
+        ));
+    }
+    sqlx::query(&format!("DROP SCHEMA IF EXISTS {} CASCADE", POSTGRES_FINDINGS_SCHEMA)).execute(&mut *tx).await?;
+    sqlx::query(&format!("CREATE SCHEMA {}", POSTGRES_FINDINGS_SCHEMA)).execute(&mut *tx).await?;
+
+    let tables = table_layouts(&open_snapshot(&contents.database)?)?;
+    for table in &tables {
+        sqlx::query(&table.create_statement()).execute(&mut *tx).await?;
+        let rows = copy_table(&contents.database, table, &mut tx).await?;
+        info!("Copied {} rows of {} to Postgres", rows, table.name);
+    }
+
+    sqlx::query(&format!(
+        "CREATE TABLE {}.attachment_blobs (sha256 TEXT PRIMARY KEY, contents BYTEA NOT NULL)",
+        POSTGRES_FINDINGS_SCHEMA
+    ))
+    .execute(&mut *tx)
+    .await?;
+    for blob in &contents.attachments {
+        // Attachments are bounded by the upload limit, so one at a time fits in memory
+        let bytes = std::fs::read(blob)?;
+        sqlx::query(&format!(
+            "INSERT INTO {}.attachment_blobs (sha256, contents) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            POSTGRES_FINDINGS_SCHEMA
+        ))
+        .bind(hex::encode(Sha256::digest(&bytes)))
+        .bind(bytes)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query("DELETE FROM schema_migrations WHERE store = $1")
+        .bind(FINDINGS_STORE)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("INSERT INTO schema_migrations (store, version, description) VALUES ($1, $2, $3)")
+        .bind(FINDINGS_STORE)
+        .bind(manifest.schema_version)
+        .bind(format!("restored from a backup taken {}", manifest.created_at.to_rfc3339()))
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Column storage in Postgres, from the SQLite declared type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnKind {
+    Integer,
+    Real,
+    Text,
+    Blob,
+}
+
+impl ColumnKind {
+    /// Follows SQLite's type affinity rules. Booleans are stored as integers; timestamps,
+    /// numerics and untyped columns keep their text form.
+    fn from_declared(declared: &str) -> Self {
+        let declared = declared.to_uppercase();
+        let has = |names: &[&str]| names.iter().any(|name| declared.contains(name));
+        if has(&["INT", "BOOL"]) {
+            ColumnKind::Integer
+        } else if has(&["CHAR", "CLOB", "TEXT"]) {
+            ColumnKind::Text
+        } else if has(&["BLOB"]) {
+            ColumnKind::Blob
+        } else if has(&["REAL", "FLOA", "DOUB"]) {
+            ColumnKind::Real
+        } else {
+            ColumnKind::Text
+        }
+    }
+
+    fn postgres_type(self) -> &'static str {
+        match self {
+            ColumnKind::Integer => "BIGINT",
+            ColumnKind::Real => "DOUBLE PRECISION",
+            ColumnKind::Text => "TEXT",
+            ColumnKind::Blob => "BYTEA",
+        }
+    }
+
+    /// SQLite values are dynamically typed; convert one to this column's type
+    fn cell(self, value: SqlValue) -> Result<Cell> {
+        Ok(match (self, value) {
+            (ColumnKind::Integer, SqlValue::Null) => Cell::Integer(None),
+            (ColumnKind::Integer, SqlValue::Integer(v)) => Cell::Integer(Some(v)),
+            (ColumnKind::Integer, SqlValue::Real(v)) if v.fract() == 0.0 => Cell::Integer(Some(v as i64)),
+            (ColumnKind::Integer, SqlValue::Text(v)) if v.trim().parse::<i64>().is_ok() => {
+                Cell::Integer(v.trim().parse().ok())
+            }
+            (ColumnKind::Real, SqlValue::Null) => Cell::Real(None),
+            (ColumnKind::Real, SqlValue::Integer(v)) => Cell::Real(Some(v as f64)),
+            (ColumnKind::Real, SqlValue::Real(v)) => Cell::Real(Some(v)),
+            (ColumnKind::Real, SqlValue::Text(v)) if v.trim().parse::<f64>().is_ok() => Cell::Real(v.trim().parse().ok()),
+            (ColumnKind::Text, SqlValue::Null) => Cell::Text(None),
+            (ColumnKind::Text, SqlValue::Integer(v)) => Cell::Text(Some(v.to_string())),
+            (ColumnKind::Text, SqlValue::Real(v)) => Cell::Text(Some(v.to_string())),
+            (ColumnKind::Text, SqlValue::Text(v)) => Cell::Text(Some(v)),
+            (ColumnKind::Text, SqlValue::Blob(v)) => {
+                Cell::Text(Some(String::from_utf8(v).map_err(|_| anyhow!("Cannot copy binary data into a TEXT column"))?))
+            }
+            (ColumnKind::Blob, SqlValue::Null) => Cell::Bytes(None),
+            (ColumnKind::Blob, SqlValue::Blob(v)) => Cell::Bytes(Some(v)),
+            (ColumnKind::Blob, SqlValue::Text(v)) => Cell::Bytes(Some(v.into_bytes())),
+            (kind, value) => return Err(anyhow!("Cannot copy {:?} into a {} column", value, kind.postgres_type())),
+        })
+    }
+}
+
+/// One value bound into a Postgres insert
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Integer(Option<i64>),
+    Real(Option<f64>),
+    Text(Option<String>),
+    Bytes(Option<Vec<u8>>),
+}
+
+#[derive(Debug, Clone)]
+struct TableLayout {
+    name: String,
+    columns: Vec<(String, ColumnKind)>,
+    primary_key: Vec<String>,
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+impl TableLayout {
+    fn column_list(&self) -> String {
+        self.columns.iter().map(|(name, _)| quote(name)).collect::<Vec<_>>().join(", ")
+    }
+
+    fn create_statement(&self) -> String {
+        let mut columns: Vec<String> =
+            self.columns.iter().map(|(name, kind)| format!("{} {}", quote(name), kind.postgres_type())).collect();
+        if !self.primary_key.is_empty() {
+            let key: Vec<String> = self.primary_key.iter().map(|name| quote(name)).collect();
+            columns.push(format!("PRIMARY KEY ({})", key.join(", ")));
+        }
+        format!("CREATE TABLE {}.{} ({})", POSTGRES_FINDINGS_SCHEMA, quote(&self.name), columns.join(", "))
+    }
+}
+
+fn table_layouts(connection: &Connection) -> Result<Vec<TableLayout>> {
+    let mut stmt = connection
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")?;
+    let names: Vec<String> = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+
+    let mut tables = Vec::new();
+    for name in names {
+        let mut info = connection.prepare(&format!("PRAGMA table_info({})", quote(&name)))?;
+        // (name, declared type, position in the primary key or 0)
+        let columns: Vec<(String, String, i64)> = info
+            .query_map([], |row| Ok((row.get(1)?, row.get(2)?, row.get(5)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        let mut key: Vec<&(String, String, i64)> = columns.iter().filter(|c| c.2 > 0).collect();
+        key.sort_by_key(|c| c.2);
+        tables.push(TableLayout {
+            primary_key: key.into_iter().map(|c| c.0.clone()).collect(),
+            columns: columns.iter().map(|(column, declared, _)| (column.clone(), ColumnKind::from_declared(declared))).collect(),
+            name,
+        });
+    }
+    Ok(tables)
+}
+
+/// Stream one table into Postgres: a blocking task reads batches from the snapshot while
+/// they are inserted
+async fn copy_table(snapshot: &Path, table: &TableLayout, tx: &mut Transaction<'_, Postgres>) -> Result<u64> {
+    let (sender, mut batches) = tokio::sync::mpsc::channel::<Vec<Vec<Cell>>>(2);
+    let reader = {
+        let (snapshot, table) = (snapshot.to_path_buf(), table.clone());
+        tokio::task::spawn_blocking(move || read_rows(&snapshot, &table, sender))
+    };
+
+    let mut copied = 0;
+    while let Some(rows) = batches.recv().await {
+        copied += rows.len() as u64;
+        let mut insert = QueryBuilder::<Postgres>::new(format!(
+            "INSERT INTO {}.{} ({}) ",
+            POSTGRES_FINDINGS_SCHEMA,
+            quote(&table.name),
+            table.column_list()
+        ));
+        insert.push_values(rows, |mut values, row| {
+            for cell in row {
+                match cell {
+                    Cell::Integer(v) => values.push_bind(v),
+                    Cell::Real(v) => values.push_bind(v),
+                    Cell::Text(v) => values.push_bind(v),
+                    Cell::Bytes(v) => values.push_bind(v),
+                };
+            }
+        });
+        insert
+            .build()
+            .execute(&mut **tx)
+            .await
+            .with_context(|| format!("Failed to copy {} to Postgres", table.name))?;
+    }
+    reader.await??;
+    Ok(copied)
+}
+
+fn read_rows(snapshot: &Path, table: &TableLayout, sender: tokio::sync::mpsc::Sender<Vec<Vec<Cell>>>) -> Result<()> {
+    if table.columns.is_empty() {
+        return Ok(());
+    }
+    let connection = open_snapshot(snapshot)?;
+    let mut stmt = connection.prepare(&format!("SELECT {} FROM {}", table.column_list(), quote(&table.name)))?;
+    let mut rows = stmt.query([])?;
+    let batch_size = (MAX_BIND_PARAMETERS / table.columns.len()).clamp(1, 1000);
+    let mut batch = Vec::with_capacity(batch_size);
+    while let Some(row) = rows.next()? {
+        let mut cells = Vec::with_capacity(table.columns.len());
+        for (i, (_, kind)) in table.columns.iter().enumerate() {
+            cells.push(kind.cell(row.get::<_, SqlValue>(i)?).with_context(|| format!("In table {}", table.name))?);
+        }
+        batch.push(cells);
+        if batch.len() == batch_size {
+            sender.blocking_send(std::mem::take(&mut batch)).map_err(|_| anyhow!("Postgres restore stopped"))?;
+        }
+    }
+    if !batch.is_empty() {
+        sender.blocking_send(batch).map_err(|_| anyhow!("Postgres restore stopped"))?;
+    }
+    Ok(())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<LessSafeKey> {
+    let iterations = NonZeroU32::new(iterations).ok_or_else(|| anyhow!("Invalid key derivation iterations"))?;
+    let mut key = [0u8; 32];
+    pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, iterations, salt, passphrase.as_bytes(), &mut key);
+    let key = UnboundKey::new(&AES_256_GCM, &key).map_err(|_| anyhow!("Failed to build encryption key"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+fn new_encryption(passphrase: &str) -> Result<(LessSafeKey, [u8; NONCE_LEN], BackupEncryption)> {
+    if passphrase.is_empty() {
+        return Err(anyhow!("{} is empty", BACKUP_KEY_ENV));
+    }
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt).map_err(|_| anyhow!("Failed to generate salt"))?;
+    rng.fill(&mut nonce).map_err(|_| anyhow!("Failed to generate nonce"))?;
+
+    let encryption = BackupEncryption {
+        algorithm: "AES-256-GCM".to_string(),
+        kdf: "PBKDF2-HMAC-SHA256".to_string(),
+        iterations: KDF_ITERATIONS,
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce),
+        chunk_size: CHUNK_SIZE,
+    };
+    Ok((derive_key(passphrase, &salt, KDF_ITERATIONS)?, nonce, encryption))
+}
+
+fn chunk_nonce(nonce: &[u8; NONCE_LEN], chunk: u32) -> Nonce {
+    let mut nonce = *nonce;
+    for (byte, counter) in nonce[NONCE_LEN - 4..].iter_mut().zip(chunk.to_be_bytes()) {
+        *byte ^= counter;
+    }
+    Nonce::assume_unique_for_key(nonce)
+}
+
+/// The last chunk is authenticated as last, so cutting a file at a chunk boundary is detected
+fn chunk_aad(last: bool) -> Aad<Vec<u8>> {
+    let mut aad = MAGIC.to_vec();
+    aad.push(last as u8);
+    Aad::from(aad)
+}
+
+fn crypto_error(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Encrypts a payload in `chunk_size` pieces. Every piece but the last is full, so the
+/// reader knows the last one by its length.
+struct SealingWriter<W: Write> {
+    out: W,
+    key: LessSafeKey,
+    nonce: [u8; NONCE_LEN],
+    chunk_size: usize,
+    chunk: u32,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> SealingWriter<W> {
+    fn new(out: W, key: LessSafeKey, nonce: [u8; NONCE_LEN], chunk_size: usize) -> Self {
+        Self { out, key, nonce, chunk_size, chunk: 0, buffer: Vec::with_capacity(chunk_size + MAX_TAG_LEN) }
+    }
+
+    fn seal(&mut self, last: bool) -> io::Result<()> {
+        self.key
+            .seal_in_place_append_tag(chunk_nonce(&self.nonce, self.chunk), chunk_aad(last), &mut self.buffer)
+            .map_err(|_| crypto_error("Failed to encrypt backup".to_string()))?;
+        self.out.write_all(&self.buffer)?;
+        self.buffer.clear();
+        self.chunk = self.chunk.checked_add(1).ok_or_else(|| crypto_error("Backup is too large to encrypt".to_string()))?;
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<W> {
+        self.seal(true)?;
+        Ok(self.out)
+    }
+}
+
+impl<W: Write> Write for SealingWriter<W> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let taken = bytes.len().min(self.chunk_size - self.buffer.len());
+        self.buffer.extend_from_slice(&bytes[..taken]);
+        if self.buffer.len() == self.chunk_size {
+            self.seal(false)?;
+        }
+        Ok(taken)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Decrypts what `SealingWriter` wrote, one chunk at a time
+struct OpeningReader<R: Read> {
+    input: R,
+    key: LessSafeKey,
+    nonce: [u8; NONCE_LEN],
+    chunk_size: usize,
+    chunk: u32,
+    plaintext: Vec<u8>,
+    position: usize,
+    done: bool,
+}
+
+impl<R: Read> OpeningReader<R> {
+    fn new(input: R, passphrase: &str, encryption: &BackupEncryption) -> Result<Self> {
+        if encryption.algorithm != "AES-256-GCM" || encryption.kdf != "PBKDF2-HMAC-SHA256" {
+            return Err(anyhow!("Unsupported backup encryption {} / {}", encryption.algorithm, encryption.kdf));
+        }
+        if encryption.chunk_size == 0 || encryption.chunk_size > 64 * CHUNK_SIZE {
+            return Err(anyhow!("Invalid backup chunk size {}", encryption.chunk_size));
+        }
+        let salt = hex::decode(&encryption.salt)?;
+        let nonce = hex::decode(&encryption.nonce)?
+            .try_into()
+            .map_err(|_| anyhow!("Invalid backup nonce"))?;
+        Ok(Self {
+            input,
+            key: derive_key(passphrase, &salt, encryption.iterations)?,
+            nonce,
+            chunk_size: encryption.chunk_size,
+            chunk: 0,
+            plaintext: Vec::with_capacity(encryption.chunk_size + MAX_TAG_LEN),
+            position: 0,
+            done: false,
+        })
+    }
+
+    fn open_next(&mut self) -> io::Result<()> {
+        let sealed_size = self.chunk_size + MAX_TAG_LEN;
+        self.plaintext.clear();
+        self.position = 0;
+        (&mut self.input).take(sealed_size as u64).read_to_end(&mut self.plaintext)?;
+        let last = self.plaintext.len() < sealed_size;
+        let len = self
+            .key
+            .open_in_place(chunk_nonce(&self.nonce, self.chunk), chunk_aad(last), &mut self.plaintext)
+            .map_err(|_| crypto_error(format!("Cannot decrypt backup: wrong {} or corrupted file", BACKUP_KEY_ENV)))?
+            .len();
+        self.plaintext.truncate(len);
+        self.chunk = self.chunk.wrapping_add(1);
+        self.done = last;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for OpeningReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.plaintext.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.open_next()?;
+        }
+        let n = buf.len().min(self.plaintext.len() - self.position);
+        buf[..n].copy_from_slice(&self.plaintext[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+/// Checksums the plaintext payload as it is unpacked
+struct HashingReader<R: Read> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Where a backup is written to or read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackupLocation {
+    File(PathBuf),
+    S3 { bucket: String, key: String },
+    Gcs { bucket: String, object: String },
+}
+
+impl FromStr for BackupLocation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let split = |rest: &str| -> Result<(String, String)> {
+            match rest.split_once('/') {
+                Some((bucket, object)) if !bucket.is_empty() && !object.is_empty() => {
+                    Ok((bucket.to_string(), object.to_string()))
+                }
+                _ => Err(anyhow!("Expected <bucket>/<object> in {}", s)),
+            }
+        };
+
+        if let Some(rest) = s.strip_prefix("s3://") {
+            let (bucket, key) = split(rest)?;
+            Ok(BackupLocation::S3 { bucket, key })
+        } else if let Some(rest) = s.strip_prefix("gs://") {
+            let (bucket, object) = split(rest)?;
+            Ok(BackupLocation::Gcs { bucket, object })
+        } else {
+            Ok(BackupLocation::File(PathBuf::from(s)))
+        }
+    }
+}
+
+impl BackupLocation {
+    /// Copy the backup file at `file` here; remote uploads stream from disk
+    pub async fn upload(&self, file: &Path) -> Result<()> {
+        match self {
+            BackupLocation::File(path) => {
+                if path != file {
+                    std::fs::copy(file, path).with_context(|| format!("Failed to write backup to {}", path.display()))?;
+                }
+            }
+            BackupLocation::S3 { bucket, key } => {
+                offline::policy().ensure_online("S3 backup upload")?;
+                let client = aws_sdk_s3::Client::new(&aws_config::load_defaults(BehaviorVersion::latest()).await);
+                client
+                    .put_object()
+                    .bucket(bucket)
+                    .key(key)
+                    .body(ByteStream::from_path(file).await?)
+                    .send()
+                    .await
+                    .map_err(|e| anyhow!("S3 upload to s3://{}/{} failed: {}", bucket, key, e))?;
+            }
+            BackupLocation::Gcs { bucket, object } => {
+                offline::policy().ensure_online("GCS backup upload")?;
+                let url = reqwest::Url::parse_with_params(
+                    &format!("https://storage.googleapis.com/upload/storage/v1/b/{}/o", bucket),
+                    &[("uploadType", "media"), ("name", object.as_str())],
+                )?;
+                let body = tokio::fs::File::open(file).await?;
+                let size = body.metadata().await?.len();
+                build_client_with_timeout(&HttpConfig::default(), 600)?
+                    .post(url)
+                    .bearer_auth(gcs_token().await?)
+                    .header("Content-Type", "application/octet-stream")
+                    .header("Content-Length", size)
+                    .body(body)
+                    .send()
+                    .await?
+                    .error_for_status()
+                    .map_err(|e| anyhow!("GCS upload to gs://{}/{} failed: {}", bucket, object, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The local backup file, downloading it to `scratch` first when it is remote
+    pub async fn download(&self, scratch: &Path) -> Result<PathBuf> {
+        let mut out = match self {
+            BackupLocation::File(path) => return Ok(path.clone()),
+            _ => tokio::fs::File::create(scratch)
+                .await
+                .with_context(|| format!("Failed to create {}", scratch.display()))?,
+        };
+        match self {
+            BackupLocation::File(_) => {}
+            BackupLocation::S3 { bucket, key } => {
+                offline::policy().ensure_online("S3 backup download")?;
+                let client = aws_sdk_s3::Client::new(&aws_config::load_defaults(BehaviorVersion::latest()).await);
+                let object = client
+                    .get_object()
+                    .bucket(bucket)
+                    .key(key)
+                    .send()
+                    .await
+                    .map_err(|e| anyhow!("S3 download of s3://{}/{} failed: {}", bucket, key, e))?;
+                let mut body = object.body;
+                while let Some(bytes) = body.try_next().await.map_err(|e| anyhow!("S3 download failed: {}", e))? {
+                    out.write_all(&bytes).await?;
+                }
+            }
+            BackupLocation::Gcs { bucket, object } => {
+                offline::policy().ensure_online("GCS backup download")?;
+                let mut url = reqwest::Url::parse(&format!("https://storage.googleapis.com/storage/v1/b/{}/o", bucket))?;
+                url.path_segments_mut()
+                    .map_err(|_| anyhow!("Invalid GCS URL"))?
+                    .push(object);
+                url.set_query(Some("alt=media"));
+                let mut response = build_client_with_timeout(&HttpConfig::default(), 600)?
+                    .get(url)
+                    .bearer_auth(gcs_token().await?)
+                    .send()
+                    .await?
+                    .error_for_status()
+                    .map_err(|e| anyhow!("GCS download of gs://{}/{} failed: {}", bucket, object, e))?;
+                while let Some(bytes) = response.chunk().await? {
+                    out.write_all(&bytes).await?;
+                }
+            }
+        }
+        out.sync_all().await?;
+        Ok(scratch.to_path_buf())
+    }
+}
+
+/// Access token from the service account in GOOGLE_APPLICATION_CREDENTIALS
+async fn gcs_token() -> Result<String> {
+    let key_path = std::env::var("GOOGLE_APPLICATION_CREDENTIALS")
+        .map_err(|_| anyhow!("Set GOOGLE_APPLICATION_CREDENTIALS to a service account key for GCS backups"))?;
+    let key = yup_oauth2::read_service_account_key(&key_path).await?;
+    let auth = yup_oauth2::ServiceAccountAuthenticator::builder(key).build().await?;
+    let token = auth
+        .token(&["https://www.googleapis.com/auth/devstorage.read_write"])
+        .await?;
+    token
+        .token()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("GCS authentication returned no access token"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_backup_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("secrets.db").to_string_lossy().to_string();
+        let db = SecretDatabase::new(&source).unwrap();
+        db.record_heartbeat("hunter", &serde_json::json!({"is_running": true})).unwrap();
+        drop(db);
+
+        let file = dir.path().join("secrets.bak");
+        let manifest = create_backup(&source, Some("correct horse"), &file).unwrap();
+        assert_eq!(manifest.schema_version, SCHEMA_VERSION);
+        assert_eq!(manifest.tables["component_heartbeats"], 1);
+        assert_eq!(read_manifest(&file).unwrap().sha256, manifest.sha256);
+
+        let target = dir.path().join("restored.db").to_string_lossy().to_string();
+        let staging = restore_staging(&target);
+        assert!(read_backup(&file, None, &staging).is_err());
+        assert!(read_backup(&file, Some("wrong"), &staging).is_err());
+        assert!(!staging.exists());
+        let (manifest, contents) = read_backup(&file, Some("correct horse"), &staging).unwrap();
+
+        restore_backup(&manifest, &contents, &target, false).await.unwrap();
+        assert_eq!(SecretDatabase::new(&target).unwrap().heartbeats().unwrap().len(), 1);
+        drop(contents);
+        assert!(!staging.exists());
+
+        let (manifest, contents) = read_backup(&file, Some("correct horse"), &staging).unwrap();
+        assert!(restore_backup(&manifest, &contents, &target, false).await.is_err());
+        restore_backup(&manifest, &contents, &target, true).await.unwrap();
+    }
+
+    #[test]
+    fn test_corrupted_plain_backup_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("secrets.db").to_string_lossy().to_string();
+        SecretDatabase::new(&source).unwrap();
+        let file = dir.path().join("secrets.bak");
+        create_backup(&source, None, &file).unwrap();
+
+        let mut bytes = std::fs::read(&file).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        std::fs::write(&file, &bytes).unwrap();
+        let error = read_backup(&file, None, &dir.path().join("staging")).unwrap_err();
+        assert!(error.to_string().contains("checksum"));
+
+        std::fs::write(&file, &bytes[..bytes.len() / 2]).unwrap();
+        assert!(read_backup(&file, None, &dir.path().join("staging")).is_err());
+        std::fs::write(&file, b"not a backup").unwrap();
+        assert!(read_manifest(&file).is_err());
+    }
+
+    fn seal(plaintext: &[u8], chunk_size: usize) -> (Vec<u8>, BackupEncryption) {
+        let (key, nonce, mut encryption) = new_encryption("correct horse").unwrap();
+        encryption.chunk_size = chunk_size;
+        let mut writer = SealingWriter::new(Vec::new(), key, nonce, chunk_size);
+        writer.write_all(plaintext).unwrap();
+        (writer.finish().unwrap(), encryption)
+    }
+
+    fn open(sealed: &[u8], encryption: &BackupEncryption) -> io::Result<Vec<u8>> {
+        let mut plaintext = Vec::new();
+        OpeningReader::new(sealed, "correct horse", encryption).unwrap().read_to_end(&mut plaintext)?;
+        Ok(plaintext)
+    }
+
+    #[test]
+    fn test_chunked_encryption_detects_truncation_and_tampering() {
+        let plaintext: Vec<u8> = (0..40u8).collect();
+        let (sealed, encryption) = seal(&plaintext, 16);
+        // Two full chunks and a short last one, each with its tag
+        assert_eq!(sealed.len(), 40 + 3 * MAX_TAG_LEN);
+        assert_eq!(open(&sealed, &encryption).unwrap(), plaintext);
+
+        // A whole number of chunks still ends with an empty, authenticated last chunk
+        let (sealed_even, encryption_even) = seal(&plaintext[..32], 16);
+        assert_eq!(sealed_even.len(), 32 + 3 * MAX_TAG_LEN);
+        assert_eq!(open(&sealed_even, &encryption_even).unwrap(), &plaintext[..32]);
+        assert!(open(&sealed_even[..2 * (16 + MAX_TAG_LEN)], &encryption_even).is_err());
+
+        let mut tampered = sealed.clone();
+        tampered[20] ^= 1;
+        assert!(open(&tampered, &encryption).is_err());
+        let mut swapped = sealed[16 + MAX_TAG_LEN..2 * (16 + MAX_TAG_LEN)].to_vec();
+        swapped.extend_from_slice(&sealed[..16 + MAX_TAG_LEN]);
+        swapped.extend_from_slice(&sealed[2 * (16 + MAX_TAG_LEN)..]);
+        assert!(open(&swapped, &encryption).is_err());
+    }
+
+    #[test]
+    fn test_sqlite_values_convert_to_postgres_columns() {
+        assert_eq!(ColumnKind::from_declared("INTEGER"), ColumnKind::Integer);
+        assert_eq!(ColumnKind::from_declared("BOOLEAN"), ColumnKind::Integer);
+        assert_eq!(ColumnKind::from_declared("varchar(64)"), ColumnKind::Text);
+        assert_eq!(ColumnKind::from_declared("REAL"), ColumnKind::Real);
+        assert_eq!(ColumnKind::from_declared("BLOB"), ColumnKind::Blob);
+        assert_eq!(ColumnKind::from_declared("DATETIME"), ColumnKind::Text);
+        assert_eq!(ColumnKind::from_declared(""), ColumnKind::Text);
+
+        assert_eq!(ColumnKind::Integer.cell(SqlValue::Text(" 42".to_string())).unwrap(), Cell::Integer(Some(42)));
+        assert_eq!(ColumnKind::Integer.cell(SqlValue::Null).unwrap(), Cell::Integer(None));
+        assert!(ColumnKind::Integer.cell(SqlValue::Text("yes".to_string())).is_err());
+        assert_eq!(ColumnKind::Real.cell(SqlValue::Integer(3)).unwrap(), Cell::Real(Some(3.0)));
+        assert_eq!(ColumnKind::Text.cell(SqlValue::Real(0.5)).unwrap(), Cell::Text(Some("0.5".to_string())));
+        assert!(ColumnKind::Text.cell(SqlValue::Blob(vec![0xff, 0xfe])).is_err());
+        assert_eq!(ColumnKind::Blob.cell(SqlValue::Text("ab".to_string())).unwrap(), Cell::Bytes(Some(b"ab".to_vec())));
+    }
+
+    #[test]
+    fn test_findings_tables_map_to_postgres() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let tables = table_layouts(&db.connection).unwrap();
+        assert_eq!(tables.len(), db.table_row_counts().unwrap().len());
+
+        let secrets = tables.iter().find(|t| t.name == "secrets").unwrap();
+        assert_eq!(secrets.primary_key, vec!["id".to_string()]);
+        let create = secrets.create_statement();
+        assert!(create.starts_with("CREATE TABLE findings.\"secrets\" (\"id\" BIGINT"));
+        assert!(create.contains("\"secret_hash\" TEXT"));
+        assert!(create.ends_with("PRIMARY KEY (\"id\"))"));
+        assert_eq!(quote("odd\"name"), "\"odd\"\"name\"");
+    }
+
+    #[test]
+    fn test_backup_location() {
+        assert_eq!(
+            "s3://bucket/nightly/secrets.bak".parse::<BackupLocation>().unwrap(),
+            BackupLocation::S3 { bucket: "bucket".to_string(), key: "nightly/secrets.bak".to_string() }
+        );
+        assert!(matches!("gs://b/o".parse::<BackupLocation>().unwrap(), BackupLocation::Gcs { .. }));
+        assert!("s3://bucket".parse::<BackupLocation>().is_err());
+        assert!(matches!("backup.bak".parse::<BackupLocation>().unwrap(), BackupLocation::File(_)));
+    }
+}
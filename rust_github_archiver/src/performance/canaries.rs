@@ -0,0 +1,254 @@
+use anyhow::{anyhow, Result};
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use super::SecretDatabase;
+use crate::canary::CanaryToken;
+
+/// One time the scanner found a canary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryTrip {
+    pub canary_id: String,
+    pub repository: String,
+    pub commit_sha: Option<String>,
+    pub filename: Option<String>,
+    pub detected_at: String,
+}
+
+const CANARY_COLUMNS: &str = "c.id, c.kind, c.provider, c.label, c.key_id, c.secret, c.external_id, c.created_at, c.retired_at,
+    (SELECT COUNT(*) FROM canary_trips t WHERE t.canary_id = c.id),
+    (SELECT MAX(detected_at) FROM canary_trips t WHERE t.canary_id = c.id)";
+
+fn parsed<T: std::str::FromStr<Err = anyhow::Error>>(row: &Row, index: usize) -> rusqlite::Result<T> {
+    row.get::<_, String>(index)?
+        .parse()
+        .map_err(|e: anyhow::Error| rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, e.into()))
+}
+
+fn canary_from_row(row: &Row) -> rusqlite::Result<CanaryToken> {
+    Ok(CanaryToken {
+        id: row.get(0)?,
+        kind: parsed(row, 1)?,
+        provider: parsed(row, 2)?,
+        label: row.get(3)?,
+        key_id: row.get(4)?,
+        secret: row.get(5)?,
+        external_id: row.get(6)?,
+        created_at: row.get(7)?,
+        retired_at: row.get(8)?,
+        locations: Vec::new(),
+        trips: row.get(9)?,
+        last_tripped_at: row.get(10)?,
+    })
+}
+
+impl SecretDatabase {
+    pub(super) fn initialize_canary_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS canaries (
+                id TEXT PRIMARY KEY,
+                kind TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                label TEXT NOT NULL,
+                key_id TEXT,
+                secret TEXT NOT NULL,
+                external_id TEXT,
+                created_at DATETIME NOT NULL,
+                retired_at DATETIME
+            )",
+            [],
+        )?;
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS canary_locations (
+                canary_id TEXT NOT NULL REFERENCES canaries(id) ON DELETE CASCADE,
+                location TEXT NOT NULL,
+                seeded_at DATETIME NOT NULL,
+                PRIMARY KEY (canary_id, location)
+            )",
+            [],
+        )?;
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS canary_trips (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                canary_id TEXT NOT NULL REFERENCES canaries(id) ON DELETE CASCADE,
+                repository TEXT NOT NULL,
+                commit_sha TEXT,
+                filename TEXT,
+                detected_at DATETIME NOT NULL
+            )",
+            [],
+        )?;
+        self.connection.execute(
+            "CREATE INDEX IF NOT EXISTS idx_canary_trips_canary ON canary_trips(canary_id)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    pub fn save_canary(&self, canary: &CanaryToken) -> Result<()> {
+        self.connection.execute(
+            "INSERT INTO canaries (id, kind, provider, label, key_id, secret, external_id, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                canary.id,
+                canary.kind.as_str(),
+                canary.provider.as_str(),
+                canary.label,
+                canary.key_id,
+                canary.secret,
+                canary.external_id,
+                canary.created_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Canaries that still raise alerts, without their locations
+    pub fn active_canaries(&self) -> Result<Vec<CanaryToken>> {
+        let mut stmt = self.connection.prepare_cached(&format!(
+            "SELECT {} FROM canaries c WHERE c.retired_at IS NULL",
+            CANARY_COLUMNS
+        ))?;
+        let canaries = stmt.query_map([], canary_from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(canaries)
+    }
+
+    /// Every canary with its locations, newest first
+    pub fn list_canaries(&self) -> Result<Vec<CanaryToken>> {
+        let mut stmt = self.connection.prepare(&format!(
+            "SELECT {} FROM canaries c ORDER BY c.created_at DESC, c.id",
+            CANARY_COLUMNS
+        ))?;
+        let mut canaries = stmt.query_map([], canary_from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+        for canary in &mut canaries {
+            canary.locations = self.canary_locations(&canary.id)?;
+        }
+        Ok(canaries)
+    }
+
+    pub fn canary(&self, id: &str) -> Result<Option<CanaryToken>> {
+        let canary = self
+            .connection
+            .query_row(&format!("SELECT {} FROM canaries c WHERE c.id = ?", CANARY_COLUMNS), [id], canary_from_row)
+            .optional()?;
+        match canary {
+            Some(mut canary) => {
+                canary.locations = self.canary_locations(id)?;
+                Ok(Some(canary))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn canary_locations(&self, id: &str) -> Result<Vec<String>> {
+        let mut stmt = self
+            .connection
+            .prepare_cached("SELECT location FROM canary_locations WHERE canary_id = ? ORDER BY seeded_at, location")?;
+        let locations = stmt.query_map([id], |row| row.get(0))?.collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(locations)
+    }
+
+    /// Remember where a canary was planted
+    pub fn add_canary_location(&self, id: &str, location: &str) -> Result<()> {
+        self.connection.execute(
+            "INSERT OR IGNORE INTO canary_locations (canary_id, location, seeded_at) VALUES (?, ?, datetime('now'))",
+            params![id, location],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_canary_trip(&self, id: &str, repository: &str, commit_sha: Option<&str>, filename: Option<&str>) -> Result<()> {
+        self.connection.execute(
+            "INSERT INTO canary_trips (canary_id, repository, commit_sha, filename, detected_at)
+            VALUES (?, ?, ?, ?, datetime('now'))",
+            params![id, repository, commit_sha, filename],
+        )?;
+        Ok(())
+    }
+
+    pub fn canary_trips(&self, id: &str) -> Result<Vec<CanaryTrip>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT canary_id, repository, commit_sha, filename, detected_at FROM canary_trips
+            WHERE canary_id = ? ORDER BY id DESC",
+        )?;
+        let trips = stmt
+            .query_map([id], |row| {
+                Ok(CanaryTrip {
+                    canary_id: row.get(0)?,
+                    repository: row.get(1)?,
+                    commit_sha: row.get(2)?,
+                    filename: row.get(3)?,
+                    detected_at: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(trips)
+    }
+
+    /// Stop alerting on a canary, e.g. once it has been removed from where it was seeded.
+    /// Its sightings are kept.
+    pub fn retire_canary(&self, id: &str) -> Result<()> {
+        let changed = self.connection.execute(
+            "UPDATE canaries SET retired_at = datetime('now') WHERE id = ? AND retired_at IS NULL",
+            [id],
+        )?;
+        if changed == 0 {
+            return Err(anyhow!("No active canary {}", id));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canary::{generate_local, CanaryKind, CanaryProvider};
+
+    #[test]
+    fn test_canaries_round_trip_with_locations_and_trips() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let aws = generate_local(CanaryKind::AwsKeys, "deploy bucket");
+        let github = generate_local(CanaryKind::GithubToken, "ci");
+        db.save_canary(&aws).unwrap();
+        db.save_canary(&github).unwrap();
+        assert!(db.save_canary(&aws).is_err());
+
+        db.add_canary_location(&aws.id, "acme/infra:.env").unwrap();
+        db.add_canary_location(&aws.id, "acme/infra:.env").unwrap();
+        db.record_canary_trip(&aws.id, "someone/fork", Some("abc123"), Some(".env")).unwrap();
+        db.record_canary_trip(&aws.id, "other/fork", None, None).unwrap();
+
+        let stored = db.canary(&aws.id).unwrap().unwrap();
+        assert_eq!(stored.kind, CanaryKind::AwsKeys);
+        assert_eq!(stored.provider, CanaryProvider::Local);
+        assert_eq!(stored.key_id, aws.key_id);
+        assert_eq!(stored.secret, aws.secret);
+        assert_eq!(stored.locations, vec!["acme/infra:.env".to_string()]);
+        assert_eq!(stored.trips, 2);
+        assert!(stored.last_tripped_at.is_some());
+        assert!(db.canary("missing").unwrap().is_none());
+
+        let trips = db.canary_trips(&aws.id).unwrap();
+        assert_eq!(trips[0].repository, "other/fork");
+        assert_eq!(trips[1].commit_sha.as_deref(), Some("abc123"));
+        assert_eq!(db.list_canaries().unwrap().len(), 2);
+        assert_eq!(db.canary(&github.id).unwrap().unwrap().trips, 0);
+    }
+
+    #[test]
+    fn test_retired_canaries_stop_alerting_but_keep_trips() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let canary = generate_local(CanaryKind::GithubToken, "ci");
+        db.save_canary(&canary).unwrap();
+        db.record_canary_trip(&canary.id, "someone/fork", None, None).unwrap();
+        assert_eq!(db.active_canaries().unwrap().len(), 1);
+
+        db.retire_canary(&canary.id).unwrap();
+        assert!(db.active_canaries().unwrap().is_empty());
+        assert!(db.retire_canary(&canary.id).is_err());
+        assert!(db.retire_canary("missing").is_err());
+        let retired = db.canary(&canary.id).unwrap().unwrap();
+        assert!(retired.retired_at.is_some());
+        assert_eq!(retired.trips, 1);
+    }
+}
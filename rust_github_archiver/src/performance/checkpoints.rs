@@ -0,0 +1,261 @@
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use super::SecretDatabase;
+
+/// Cursor of a historical scan, saved after every event batch so an interrupted
+/// scan can continue where it stopped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanCheckpoint {
+    pub scan_id: String,
+    /// Every organization the scan covers, in scan order
+    pub organizations: Vec<String>,
+    pub completed_organizations: Vec<String>,
+    pub current_organization: Option<String>,
+    /// Date range of the current organization's BigQuery query, inclusive
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    /// Last event of the current organization whose batch was fully processed
+    pub last_event_id: Option<String>,
+    pub events_processed: u64,
+    pub findings: u64,
+    pub completed: bool,
+    pub started_at: String,
+    pub updated_at: String,
+}
+
+impl ScanCheckpoint {
+    pub fn new(scan_id: &str, organizations: Vec<String>) -> Self {
+        Self {
+            scan_id: scan_id.to_string(),
+            organizations,
+            completed_organizations: Vec::new(),
+            current_organization: None,
+            start_date: None,
+            end_date: None,
+            last_event_id: None,
+            events_processed: 0,
+            findings: 0,
+            completed: false,
+            started_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    /// Organizations not finished yet, in scan order
+    pub fn remaining_organizations(&self) -> Vec<String> {
+        self.organizations
+            .iter()
+            .filter(|org| !self.completed_organizations.contains(org))
+            .cloned()
+            .collect()
+    }
+
+    /// Start (or, on resume, continue) an organization. Returns the stored date range when
+    /// resuming the organization that was interrupted, otherwise records `range`.
+    pub fn begin_organization(&mut self, organization: &str, range: (NaiveDate, NaiveDate)) -> (NaiveDate, NaiveDate) {
+        if self.current_organization.as_deref() == Some(organization) {
+            if let (Some(start), Some(end)) = (self.start_date, self.end_date) {
+                return (start, end);
+            }
+        }
+        self.current_organization = Some(organization.to_string());
+        self.start_date = Some(range.0);
+        self.end_date = Some(range.1);
+        self.last_event_id = None;
+        self.events_processed = 0;
+        range
+    }
+
+    pub fn finish_organization(&mut self, organization: &str) {
+        if !self.completed_organizations.iter().any(|org| org == organization) {
+            self.completed_organizations.push(organization.to_string());
+        }
+        self.current_organization = None;
+        self.start_date = None;
+        self.end_date = None;
+        self.last_event_id = None;
+        self.events_processed = 0;
+    }
+
+    /// Index of the first event still to process in `event_ids`. Uses the last processed
+    /// event when it is still in the result set, otherwise the processed count.
+    pub fn resume_position<'a>(&self, event_ids: impl IntoIterator<Item = &'a str>) -> usize {
+        match &self.last_event_id {
+            None => 0,
+            Some(last) => event_ids
+                .into_iter()
+                .position(|id| id == last)
+                .map(|i| i + 1)
+                .unwrap_or(self.events_processed as usize),
+        }
+    }
+}
+
+fn parse_date(value: Option<String>) -> Option<NaiveDate> {
+    value.and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+}
+
+fn checkpoint_from_row(row: &Row) -> rusqlite::Result<ScanCheckpoint> {
+    let organizations: String = row.get(1)?;
+    let completed_organizations: String = row.get(2)?;
+    Ok(ScanCheckpoint {
+        scan_id: row.get(0)?,
+        organizations: serde_json::from_str(&organizations).unwrap_or_default(),
+        completed_organizations: serde_json::from_str(&completed_organizations).unwrap_or_default(),
+        current_organization: row.get(3)?,
+        start_date: parse_date(row.get(4)?),
+        end_date: parse_date(row.get(5)?),
+        last_event_id: row.get(6)?,
+        events_processed: row.get::<_, i64>(7)? as u64,
+        findings: row.get::<_, i64>(8)? as u64,
+        completed: row.get(9)?,
+        started_at: row.get(10)?,
+        updated_at: row.get(11)?,
+    })
+}
+
+const CHECKPOINT_COLUMNS: &str = "scan_id, organizations, completed_organizations, current_organization,
+    start_date, end_date, last_event_id, events_processed, findings, completed, started_at, updated_at";
+
+impl SecretDatabase {
+    pub(super) fn initialize_checkpoints_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS scan_checkpoints (
+                scan_id TEXT PRIMARY KEY,
+                organizations TEXT NOT NULL,           -- JSON array
+                completed_organizations TEXT NOT NULL, -- JSON array
+                current_organization TEXT,
+                start_date TEXT,
+                end_date TEXT,
+                last_event_id TEXT,
+                events_processed INTEGER NOT NULL DEFAULT 0,
+                findings INTEGER NOT NULL DEFAULT 0,
+                completed BOOLEAN NOT NULL DEFAULT FALSE,
+                started_at DATETIME NOT NULL,
+                updated_at DATETIME NOT NULL
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Insert or update a checkpoint; `started_at` is kept from the first save
+    pub fn save_checkpoint(&self, checkpoint: &ScanCheckpoint) -> Result<()> {
+        self.connection.execute(
+            "INSERT INTO scan_checkpoints
+                (scan_id, organizations, completed_organizations, current_organization, start_date, end_date,
+                 last_event_id, events_processed, findings, completed, started_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, datetime('now'), datetime('now'))
+            ON CONFLICT(scan_id) DO UPDATE SET
+                organizations = excluded.organizations,
+                completed_organizations = excluded.completed_organizations,
+                current_organization = excluded.current_organization,
+                start_date = excluded.start_date,
+                end_date = excluded.end_date,
+                last_event_id = excluded.last_event_id,
+                events_processed = excluded.events_processed,
+                findings = excluded.findings,
+                completed = excluded.completed,
+                updated_at = excluded.updated_at",
+            params![
+                checkpoint.scan_id,
+                serde_json::to_string(&checkpoint.organizations)?,
+                serde_json::to_string(&checkpoint.completed_organizations)?,
+                checkpoint.current_organization,
+                checkpoint.start_date.map(|d| d.to_string()),
+                checkpoint.end_date.map(|d| d.to_string()),
+                checkpoint.last_event_id,
+                checkpoint.events_processed as i64,
+                checkpoint.findings as i64,
+                checkpoint.completed,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn load_checkpoint(&self, scan_id: &str) -> Result<Option<ScanCheckpoint>> {
+        let checkpoint = self
+            .connection
+            .query_row(
+                &format!("SELECT {} FROM scan_checkpoints WHERE scan_id = ?", CHECKPOINT_COLUMNS),
+                params![scan_id],
+                checkpoint_from_row,
+            )
+            .optional()?;
+        Ok(checkpoint)
+    }
+
+    /// Checkpoint of an interrupted scan, or an error explaining why it cannot be resumed
+    pub fn resumable_checkpoint(&self, scan_id: &str) -> Result<ScanCheckpoint> {
+        match self.load_checkpoint(scan_id)? {
+            None => Err(anyhow!("No checkpoint for scan {}", scan_id)),
+            Some(checkpoint) if checkpoint.completed => Err(anyhow!("Scan {} already completed", scan_id)),
+            Some(checkpoint) => Ok(checkpoint),
+        }
+    }
+
+    /// The most recently updated scan, finished or not
+    pub fn latest_checkpoint(&self) -> Result<Option<ScanCheckpoint>> {
+        let checkpoint = self
+            .connection
+            .query_row(
+                &format!("SELECT {} FROM scan_checkpoints ORDER BY updated_at DESC LIMIT 1", CHECKPOINT_COLUMNS),
+                [],
+                checkpoint_from_row,
+            )
+            .optional()?;
+        Ok(checkpoint)
+    }
+
+    /// Interrupted scans, most recently updated first
+    pub fn incomplete_checkpoints(&self) -> Result<Vec<ScanCheckpoint>> {
+        let mut stmt = self.connection.prepare(&format!(
+            "SELECT {} FROM scan_checkpoints WHERE completed = FALSE ORDER BY updated_at DESC",
+            CHECKPOINT_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], checkpoint_from_row)?;
+
+        let mut checkpoints = Vec::new();
+        for row in rows {
+            checkpoints.push(row?);
+        }
+        Ok(checkpoints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, d).unwrap()
+    }
+
+    #[test]
+    fn test_checkpoint_round_trip_and_resume() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let mut checkpoint = ScanCheckpoint::new("scan-1", vec!["acme".to_string(), "globex".to_string()]);
+        checkpoint.begin_organization("acme", (day(1), day(30)));
+        checkpoint.finish_organization("acme");
+        checkpoint.begin_organization("globex", (day(2), day(30)));
+        checkpoint.last_event_id = Some("e2".to_string());
+        checkpoint.events_processed = 2;
+        db.save_checkpoint(&checkpoint).unwrap();
+
+        let mut resumed = db.resumable_checkpoint("scan-1").unwrap();
+        assert_eq!(resumed.remaining_organizations(), vec!["globex".to_string()]);
+        // The interrupted organization keeps its original date range and cursor
+        assert_eq!(resumed.begin_organization("globex", (day(9), day(30))), (day(2), day(30)));
+        assert_eq!(resumed.resume_position(["e1", "e2", "e3"]), 2);
+        assert_eq!(resumed.resume_position(["x", "y", "z"]), 2);
+        assert_eq!(db.incomplete_checkpoints().unwrap().len(), 1);
+
+        resumed.completed = true;
+        db.save_checkpoint(&resumed).unwrap();
+        assert!(db.resumable_checkpoint("scan-1").is_err());
+        assert!(db.resumable_checkpoint("missing").is_err());
+    }
+}
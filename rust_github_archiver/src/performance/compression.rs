@@ -0,0 +1,276 @@
+use anyhow::{anyhow, Context, Result};
+use rusqlite::params;
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+use super::SecretDatabase;
+use crate::core::{StorageCodec, StorageCompressionConfig};
+
+/// First byte of a compressed value. It never starts a UTF-8 string, so a compressed
+/// value cannot be mistaken for text even after a cast.
+const MAGIC: u8 = 0xC0;
+const LZ4: u8 = 1;
+const ZSTD: u8 = 2;
+
+static POLICY: OnceLock<CompressionPolicy> = OnceLock::new();
+
+/// A payload column whose values may be stored compressed, and the codec it gets unless
+/// configured otherwise. Plain-text values stay readable, so rows written before
+/// compression, or with it turned off, need no conversion.
+#[derive(Debug)]
+pub struct CompressedColumn {
+    pub table: &'static str,
+    pub column: &'static str,
+    pub default_codec: StorageCodec,
+}
+
+impl CompressedColumn {
+    /// `table.column`, as used in the configuration
+    pub fn key(&self) -> String {
+        format!("{}.{}", self.table, self.column)
+    }
+}
+
+/// Leased again within seconds, so decompression speed matters more than size
+pub const EVENT_QUEUE_JSON: CompressedColumn =
+    CompressedColumn { table: "event_queue", column: "event_json", default_codec: StorageCodec::Lz4 };
+pub const DEAD_LETTER_JSON: CompressedColumn =
+    CompressedColumn { table: "event_dead_letters", column: "event_json", default_codec: StorageCodec::Zstd };
+pub const ALERT_JSON: CompressedColumn =
+    CompressedColumn { table: "alert_log", column: "alert_json", default_codec: StorageCodec::Zstd };
+
+pub const COMPRESSED_COLUMNS: [&CompressedColumn; 3] = [&EVENT_QUEUE_JSON, &DEAD_LETTER_JSON, &ALERT_JSON];
+
+/// Which codec each compressed column is written with
+#[derive(Debug, Clone)]
+pub struct CompressionPolicy {
+    enabled: bool,
+    min_bytes: usize,
+    zstd_level: i32,
+    columns: BTreeMap<String, StorageCodec>,
+}
+
+impl CompressionPolicy {
+    pub fn new(config: &StorageCompressionConfig) -> Self {
+        for key in config.columns.keys() {
+            if !COMPRESSED_COLUMNS.iter().any(|column| column.key() == *key) {
+                warn!("Storage compression is not supported for {}; setting ignored", key);
+            }
+        }
+        Self {
+            enabled: config.enabled,
+            min_bytes: config.min_bytes,
+            zstd_level: config.zstd_level,
+            columns: config.columns.clone(),
+        }
+    }
+
+    pub fn codec(&self, column: &CompressedColumn) -> StorageCodec {
+        if !self.enabled {
+            return StorageCodec::None;
+        }
+        self.columns.get(&column.key()).copied().unwrap_or(column.default_codec)
+    }
+
+    /// The value to store in `column`: compressed with its codec, or the text itself when
+    /// it is short or compression would not make it smaller
+    pub fn encode(&self, column: &CompressedColumn, text: String) -> Result<Value> {
+        if text.len() < self.min_bytes {
+            return Ok(Value::Text(text));
+        }
+        let (codec, compressed) = match self.codec(column) {
+            StorageCodec::None => return Ok(Value::Text(text)),
+            StorageCodec::Lz4 => (LZ4, lz4_flex::compress_prepend_size(text.as_bytes())),
+            StorageCodec::Zstd => (ZSTD, zstd::encode_all(text.as_bytes(), self.zstd_level)?),
+        };
+        if compressed.len() + 2 >= text.len() {
+            return Ok(Value::Text(text));
+        }
+
+        let mut blob = Vec::with_capacity(compressed.len() + 2);
+        blob.extend([MAGIC, codec]);
+        blob.extend(compressed);
+        Ok(Value::Blob(blob))
+    }
+}
+
+/// Read a compressed column's value, whichever way it was stored
+pub fn decode(value: &Value) -> Result<String> {
+    match value {
+        Value::Text(text) => Ok(text.clone()),
+        Value::Blob(blob) => {
+            let bytes = match blob.as_slice() {
+                [MAGIC, LZ4, compressed @ ..] => lz4_flex::decompress_size_prepended(compressed)
+                    .map_err(|e| anyhow!("Corrupt lz4 value: {}", e))?,
+                [MAGIC, ZSTD, compressed @ ..] => zstd::decode_all(compressed).context("Corrupt zstd value")?,
+                [MAGIC, codec, ..] => return Err(anyhow!("Value compressed with unknown codec {}", codec)),
+                _ => return Err(anyhow!("Blob of {} bytes is not a compressed value", blob.len())),
+            };
+            Ok(String::from_utf8(bytes)?)
+        }
+        other => Err(anyhow!("Expected text or a compressed value, found {:?}", other.data_type())),
+    }
+}
+
+/// Install the process-wide policy from the configuration
+pub fn install(config: &StorageCompressionConfig) {
+    if POLICY.set(CompressionPolicy::new(config)).is_err() {
+        warn!("Storage compression was already configured; ignoring the new settings");
+    }
+}
+
+/// The active policy, configured from the environment if `install` was never called
+pub fn policy() -> &'static CompressionPolicy {
+    POLICY.get_or_init(|| CompressionPolicy::new(&StorageCompressionConfig::default()))
+}
+
+/// What compressing existing rows did to one column
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnCompression {
+    /// `table.column`
+    pub column: String,
+    pub codec: StorageCodec,
+    pub rows: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+impl SecretDatabase {
+    /// Compress rows stored as plain text, e.g. before compression was turned on, in batches
+    /// of `batch_size` rows per transaction so writers never wait long. Stops after
+    /// `max_batches` batches per column when given. Rows that are short or do not compress
+    /// stay as they are.
+    pub fn compress_existing_rows(&self, batch_size: usize, max_batches: Option<usize>) -> Result<Vec<ColumnCompression>> {
+        let policy = policy();
+        let mut reports = Vec::new();
+
+        for column in COMPRESSED_COLUMNS {
+            let codec = policy.codec(column);
+            let mut report = ColumnCompression { column: column.key(), codec, rows: 0, bytes_before: 0, bytes_after: 0 };
+            if codec == StorageCodec::None {
+                reports.push(report);
+                continue;
+            }
+
+            let select = format!(
+                "SELECT rowid, {column} FROM {table}
+                WHERE rowid > ? AND typeof({column}) = 'text' AND length(CAST({column} AS BLOB)) >= ?
+                ORDER BY rowid LIMIT ?",
+                table = column.table,
+                column = column.column
+            );
+            let update = format!("UPDATE {} SET {} = ? WHERE rowid = ?", column.table, column.column);
+
+            let mut after = 0i64;
+            let mut batches = 0;
+            loop {
+                if max_batches.is_some_and(|max| batches >= max) {
+                    break;
+                }
+                let tx = self.connection.unchecked_transaction()?;
+                let rows = tx
+                    .prepare(&select)?
+                    .query_map(params![after, policy.min_bytes as i64, batch_size as i64], |row| {
+                        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                    })?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                let Some(&(last, _)) = rows.last() else {
+                    break;
+                };
+                after = last;
+
+                for (rowid, text) in rows {
+                    let before = text.len() as u64;
+                    if let Value::Blob(blob) = policy.encode(column, text)? {
+                        report.rows += 1;
+                        report.bytes_before += before;
+                        report.bytes_after += blob.len() as u64;
+                        tx.execute(&update, params![blob, rowid])?;
+                    }
+                }
+                tx.commit()?;
+                batches += 1;
+            }
+
+            if report.rows > 0 {
+                info!(
+                    "Compressed {} rows of {} with {:?}: {} KB to {} KB",
+                    report.rows,
+                    report.column,
+                    codec,
+                    report.bytes_before / 1024,
+                    report.bytes_after / 1024
+                );
+            }
+            reports.push(report);
+        }
+        Ok(reports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy_with(columns: &[(&str, StorageCodec)]) -> CompressionPolicy {
+        CompressionPolicy::new(&StorageCompressionConfig {
+            enabled: true,
+            min_bytes: 64,
+            zstd_level: 3,
+            columns: columns.iter().map(|(key, codec)| (key.to_string(), *codec)).collect(),
+        })
+    }
+
+    fn payload() -> String {
+        serde_json::json!({ "commits": vec!["a0f3 update deploy script"; 50] }).to_string()
+    }
+
+    #[test]
+    fn test_codecs_round_trip_and_small_values_stay_text() {
+        let zstd = policy_with(&[]);
+        let lz4 = policy_with(&[("alert_log.alert_json", StorageCodec::Lz4)]);
+        for (policy, codec) in [(&zstd, ZSTD), (&lz4, LZ4)] {
+            let stored = policy.encode(&ALERT_JSON, payload()).unwrap();
+            let Value::Blob(blob) = &stored else { panic!("not compressed") };
+            assert_eq!(&blob[..2], &[MAGIC, codec]);
+            assert!(blob.len() < payload().len() / 4);
+            assert_eq!(decode(&stored).unwrap(), payload());
+        }
+
+        assert_eq!(zstd.encode(&ALERT_JSON, "{}".to_string()).unwrap(), Value::Text("{}".to_string()));
+        let off = policy_with(&[("alert_log.alert_json", StorageCodec::None)]);
+        assert!(matches!(off.encode(&ALERT_JSON, payload()).unwrap(), Value::Text(_)));
+        assert!(decode(&Value::Blob(vec![MAGIC, 9, 0])).is_err());
+    }
+
+    #[test]
+    fn test_existing_rows_are_compressed_in_batches() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        for i in 0..5 {
+            db.connection
+                .execute(
+                    "INSERT INTO event_dead_letters (event_id, event_type, repository, event_json, attempts, failed_at)
+                    VALUES (?, 'PushEvent', 'acme/api', ?, 3, datetime('now'))",
+                    params![format!("event-{}", i), payload()],
+                )
+                .unwrap();
+        }
+
+        let first = db.compress_existing_rows(2, Some(1)).unwrap();
+        let dead_letters = first.iter().find(|r| r.column == "event_dead_letters.event_json").unwrap();
+        assert_eq!(dead_letters.rows, 2);
+        let rest = db.compress_existing_rows(2, None).unwrap();
+        assert_eq!(rest.iter().find(|r| r.column == "event_dead_letters.event_json").unwrap().rows, 3);
+
+        let mut stmt = db.connection.prepare("SELECT event_json FROM event_dead_letters").unwrap();
+        let values = stmt.query_map([], |row| row.get::<_, Value>(0)).unwrap();
+        for value in values {
+            let value = value.unwrap();
+            assert!(matches!(value, Value::Blob(_)));
+            assert_eq!(decode(&value).unwrap(), payload());
+        }
+    }
+}
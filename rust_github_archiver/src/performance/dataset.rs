@@ -0,0 +1,459 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::io::Write;
+
+use super::export::ExportFormat;
+use super::synthetic::NOT_SYNTHETIC;
+use super::SecretDatabase;
+
+/// Columns of `export dataset`, in order
+pub const DATASET_COLUMNS: [&str; 12] = [
+    "example_id",
+    "detector_name",
+    "category",
+    "severity",
+    "entropy",
+    "line_number",
+    "file_extension",
+    "path_depth",
+    "test_path",
+    "label",
+    "label_source",
+    "split",
+];
+
+/// Path components and file name words that mark test data, fixtures and examples
+const TEST_PATH_WORDS: &[&str] = &[
+    "test", "tests", "__tests__", "spec", "specs", "testdata", "fixture", "fixtures", "example", "examples", "sample",
+    "samples", "mock", "mocks",
+];
+
+/// Whether a finding was a real secret. Verified findings and findings marked valid,
+/// resolved or revoked are true positives; allowlisted findings are false positives.
+/// Everything else has not been reviewed and is left out of the dataset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatasetLabel {
+    Tp,
+    Fp,
+}
+
+impl DatasetLabel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DatasetLabel::Tp => "tp",
+            DatasetLabel::Fp => "fp",
+        }
+    }
+
+    /// The label of a finding, and what it was taken from
+    fn of(verified: bool, validation_status: Option<&str>, suppressed: bool) -> Option<(Self, &'static str)> {
+        if verified {
+            Some((DatasetLabel::Tp, "verified"))
+        } else if matches!(validation_status, Some("valid" | "resolved" | "revoked")) {
+            Some((DatasetLabel::Tp, "validation"))
+        } else if suppressed {
+            Some((DatasetLabel::Fp, "allowlist"))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatasetSplit {
+    Train,
+    Test,
+}
+
+impl DatasetSplit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DatasetSplit::Train => "train",
+            DatasetSplit::Test => "test",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DatasetOptions {
+    /// Share of the examples put in the test split
+    pub test_fraction: f64,
+    /// Mixed into every example ID and split; the same seed gives the same split
+    pub seed: String,
+}
+
+impl Default for DatasetOptions {
+    fn default() -> Self {
+        Self { test_fraction: 0.2, seed: "gitarchiver".to_string() }
+    }
+}
+
+/// One labeled finding. Nothing in it identifies the secret, its repository or its file:
+/// the ID is a seeded hash, and the path is reduced to its extension and shape.
+#[derive(Debug, Clone, Serialize)]
+pub struct DatasetExample {
+    pub example_id: String,
+    pub detector_name: String,
+    pub category: String,
+    pub severity: String,
+    pub entropy: f64,
+    pub line_number: Option<i32>,
+    pub file_extension: Option<String>,
+    pub path_depth: i32,
+    pub test_path: bool,
+    pub label: DatasetLabel,
+    pub label_source: &'static str,
+    pub split: DatasetSplit,
+}
+
+/// What `export dataset` wrote
+#[derive(Debug, Default, Serialize)]
+pub struct DatasetReport {
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub train: usize,
+    pub test: usize,
+    /// Findings not written because nobody has confirmed or dismissed them yet
+    pub unlabeled: usize,
+}
+
+impl DatasetReport {
+    pub fn examples(&self) -> usize {
+        self.train + self.test
+    }
+}
+
+/// The example ID and split of `secret_hash`. Both hash the seed with the finding, so a
+/// finding stays on the same side of the split as the dataset grows.
+fn assign(seed: &str, secret_hash: &str, test_fraction: f64) -> (String, DatasetSplit) {
+    let digest = Sha256::new().chain_update(seed).chain_update([0u8]).chain_update(secret_hash).finalize();
+    let position = u64::from_be_bytes(digest[..8].try_into().expect("8 bytes")) as f64 / u64::MAX as f64;
+    let split = if position < test_fraction { DatasetSplit::Test } else { DatasetSplit::Train };
+    (hex::encode(&digest[..8]), split)
+}
+
+/// Lowercased extension of the file, if it is a short alphanumeric one
+fn file_extension(filename: &str) -> Option<String> {
+    let name = filename.rsplit('/').next().unwrap_or(filename);
+    let (stem, extension) = name.rsplit_once('.')?;
+    (!stem.is_empty() && (1..=8).contains(&extension.len()) && extension.chars().all(|c| c.is_ascii_alphanumeric()))
+        .then(|| extension.to_ascii_lowercase())
+}
+
+fn is_test_path(filename: &str) -> bool {
+    let lowercase = filename.to_ascii_lowercase();
+    let mut components = lowercase.split('/').collect::<Vec<_>>();
+    let name = components.pop().unwrap_or_default();
+    components.iter().any(|component| TEST_PATH_WORDS.contains(component))
+        || name.split(|c: char| !c.is_ascii_alphanumeric()).any(|word| TEST_PATH_WORDS.contains(&word))
+}
+
+impl SecretDatabase {
+    /// Call `f` with every labeled finding as a dataset example, in insertion order.
+    /// Synthetic and erased findings are skipped.
+    pub fn for_each_dataset_example(
+        &self,
+        options: &DatasetOptions,
+        mut f: impl FnMut(DatasetExample) -> Result<()>,
+    ) -> Result<DatasetReport> {
+        if !(0.0..=1.0).contains(&options.test_fraction) {
+            return Err(anyhow!("The test fraction must be between 0 and 1, not {}", options.test_fraction));
+        }
+
+        let query = format!(
+            "SELECT secret_hash, detector_name, category, severity, COALESCE(entropy, 0.0), line_number, filename,
+                COALESCE(verified, FALSE), validation_status, suppressed
+             FROM secrets
+             WHERE {} AND validation_status IS NOT 'erased'
+             ORDER BY id",
+            NOT_SYNTHETIC
+        );
+        let mut stmt = self.connection.prepare(&query)?;
+        let mut rows = stmt.query([])?;
+
+        let mut report = DatasetReport::default();
+        while let Some(row) = rows.next()? {
+            let validation_status: Option<String> = row.get(8)?;
+            let Some((label, label_source)) = DatasetLabel::of(row.get(7)?, validation_status.as_deref(), row.get(9)?) else {
+                report.unlabeled += 1;
+                continue;
+            };
+
+            let secret_hash: String = row.get(0)?;
+            let (example_id, split) = assign(&options.seed, &secret_hash, options.test_fraction);
+            let filename: Option<String> = row.get(6)?;
+            let example = DatasetExample {
+                example_id,
+                detector_name: row.get(1)?,
+                category: row.get(2)?,
+                severity: row.get(3)?,
+                entropy: row.get(4)?,
+                line_number: row.get::<_, Option<i64>>(5)?.map(|line| i32::try_from(line).unwrap_or(i32::MAX)),
+                file_extension: filename.as_deref().and_then(file_extension),
+                path_depth: filename.as_deref().map_or(0, |name| name.matches('/').count() as i32),
+                test_path: filename.as_deref().is_some_and(is_test_path),
+                label,
+                label_source,
+                split,
+            };
+
+            match label {
+                DatasetLabel::Tp => report.true_positives += 1,
+                DatasetLabel::Fp => report.false_positives += 1,
+            }
+            match split {
+                DatasetSplit::Train => report.train += 1,
+                DatasetSplit::Test => report.test += 1,
+            }
+            f(example)?;
+        }
+        Ok(report)
+    }
+
+    /// Write the labeled dataset to `out` as Parquet, CSV or JSON lines
+    pub fn export_dataset<W: Write + Send>(&self, options: &DatasetOptions, format: ExportFormat, mut out: W) -> Result<DatasetReport> {
+        match format {
+            ExportFormat::Parquet => self.export_dataset_parquet(options, out),
+            ExportFormat::JsonLines => self.for_each_dataset_example(options, |example| {
+                serde_json::to_writer(&mut out, &example)?;
+                out.write_all(b"\n")?;
+                Ok(())
+            }),
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(&mut out);
+                writer.write_record(DATASET_COLUMNS)?;
+                let report = self.for_each_dataset_example(options, |example| {
+                    writer.write_record([
+                        example.example_id,
+                        example.detector_name,
+                        example.category,
+                        example.severity,
+                        example.entropy.to_string(),
+                        example.line_number.map(|line| line.to_string()).unwrap_or_default(),
+                        example.file_extension.unwrap_or_default(),
+                        example.path_depth.to_string(),
+                        example.test_path.to_string(),
+                        example.label.as_str().to_string(),
+                        example.label_source.to_string(),
+                        example.split.as_str().to_string(),
+                    ])?;
+                    Ok(())
+                })?;
+                writer.flush()?;
+                Ok(report)
+            }
+            ExportFormat::Json => Err(anyhow!("Datasets are written as parquet, csv or jsonl")),
+        }
+    }
+
+    #[cfg(feature = "parquet")]
+    fn export_dataset_parquet<W: Write + Send>(&self, options: &DatasetOptions, out: W) -> Result<DatasetReport> {
+        let mut writer = parquet_dataset::DatasetParquetWriter::new(out)?;
+        let report = self.for_each_dataset_example(options, |example| writer.push(example))?;
+        writer.finish()?;
+        Ok(report)
+    }
+
+    #[cfg(not(feature = "parquet"))]
+    fn export_dataset_parquet<W: Write + Send>(&self, _options: &DatasetOptions, _out: W) -> Result<DatasetReport> {
+        Err(anyhow!("This build has no Parquet support; rebuild with --features parquet, or use --format csv"))
+    }
+}
+
+#[cfg(feature = "parquet")]
+mod parquet_dataset {
+    use anyhow::Result;
+    use parquet::basic::Compression;
+    use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, Int32Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use super::DatasetExample;
+
+    /// Examples buffered per row group
+    const ROW_GROUP_ROWS: usize = 65_536;
+
+    const SCHEMA: &str = "message example {
+        REQUIRED BYTE_ARRAY example_id (UTF8);
+        REQUIRED BYTE_ARRAY detector_name (UTF8);
+        REQUIRED BYTE_ARRAY category (UTF8);
+        REQUIRED BYTE_ARRAY severity (UTF8);
+        REQUIRED DOUBLE entropy;
+        OPTIONAL INT32 line_number;
+        OPTIONAL BYTE_ARRAY file_extension (UTF8);
+        REQUIRED INT32 path_depth;
+        REQUIRED BOOLEAN test_path;
+        REQUIRED BYTE_ARRAY label (UTF8);
+        REQUIRED BYTE_ARRAY label_source (UTF8);
+        REQUIRED BYTE_ARRAY split (UTF8);
+    }";
+
+    #[derive(Default)]
+    struct Columns {
+        ids: Vec<ByteArray>,
+        detectors: Vec<ByteArray>,
+        categories: Vec<ByteArray>,
+        severities: Vec<ByteArray>,
+        entropies: Vec<f64>,
+        lines: Vec<i32>,
+        line_levels: Vec<i16>,
+        extensions: Vec<ByteArray>,
+        extension_levels: Vec<i16>,
+        depths: Vec<i32>,
+        test_paths: Vec<bool>,
+        labels: Vec<ByteArray>,
+        label_sources: Vec<ByteArray>,
+        splits: Vec<ByteArray>,
+    }
+
+    pub struct DatasetParquetWriter<W: Write + Send> {
+        writer: SerializedFileWriter<W>,
+        columns: Columns,
+    }
+
+    impl<W: Write + Send> DatasetParquetWriter<W> {
+        pub fn new(out: W) -> Result<Self> {
+            let schema = Arc::new(parse_message_type(SCHEMA)?);
+            let properties = Arc::new(WriterProperties::builder().set_compression(Compression::SNAPPY).build());
+            Ok(Self { writer: SerializedFileWriter::new(out, schema, properties)?, columns: Columns::default() })
+        }
+
+        pub fn push(&mut self, example: DatasetExample) -> Result<()> {
+            let columns = &mut self.columns;
+            columns.ids.push(example.example_id.as_str().into());
+            columns.detectors.push(example.detector_name.as_str().into());
+            columns.categories.push(example.category.as_str().into());
+            columns.severities.push(example.severity.as_str().into());
+            columns.entropies.push(example.entropy);
+            match example.line_number {
+                Some(line) => {
+                    columns.lines.push(line);
+                    columns.line_levels.push(1);
+                }
+                None => columns.line_levels.push(0),
+            }
+            match example.file_extension {
+                Some(extension) => {
+                    columns.extensions.push(extension.as_str().into());
+                    columns.extension_levels.push(1);
+                }
+                None => columns.extension_levels.push(0),
+            }
+            columns.depths.push(example.path_depth);
+            columns.test_paths.push(example.test_path);
+            columns.labels.push(example.label.as_str().into());
+            columns.label_sources.push(example.label_source.into());
+            columns.splits.push(example.split.as_str().into());
+            if columns.ids.len() >= ROW_GROUP_ROWS {
+                self.flush()?;
+            }
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            if self.columns.ids.is_empty() {
+                return Ok(());
+            }
+            let columns = std::mem::take(&mut self.columns);
+            let mut row_group = self.writer.next_row_group()?;
+            let mut index = 0;
+            while let Some(mut column) = row_group.next_column()? {
+                match index {
+                    0 => column.typed::<ByteArrayType>().write_batch(&columns.ids, None, None)?,
+                    1 => column.typed::<ByteArrayType>().write_batch(&columns.detectors, None, None)?,
+                    2 => column.typed::<ByteArrayType>().write_batch(&columns.categories, None, None)?,
+                    3 => column.typed::<ByteArrayType>().write_batch(&columns.severities, None, None)?,
+                    4 => column.typed::<DoubleType>().write_batch(&columns.entropies, None, None)?,
+                    5 => column.typed::<Int32Type>().write_batch(&columns.lines, Some(&columns.line_levels), None)?,
+                    6 => column.typed::<ByteArrayType>().write_batch(&columns.extensions, Some(&columns.extension_levels), None)?,
+                    7 => column.typed::<Int32Type>().write_batch(&columns.depths, None, None)?,
+                    8 => column.typed::<BoolType>().write_batch(&columns.test_paths, None, None)?,
+                    9 => column.typed::<ByteArrayType>().write_batch(&columns.labels, None, None)?,
+                    10 => column.typed::<ByteArrayType>().write_batch(&columns.label_sources, None, None)?,
+                    _ => column.typed::<ByteArrayType>().write_batch(&columns.splits, None, None)?,
+                };
+                column.close()?;
+                index += 1;
+            }
+            row_group.close()?;
+            Ok(())
+        }
+
+        pub fn finish(mut self) -> Result<()> {
+            self.flush()?;
+            self.writer.close()?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dataset_is_labeled_scrubbed_and_split_deterministically() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let insert = "INSERT INTO secrets (secret_hash, detector_name, matched_text_hash, filename, severity, category,
+                verified, validation_status, suppressed, created_at)
+            VALUES (?, ?, '', ?, 'High', 'CloudProvider', ?, ?, ?, datetime('now'))";
+        let rows: [(&str, &str, Option<&str>, bool, Option<&str>, bool); 4] = [
+            ("hash-a", "AWS Access Key ID", Some("acme/deploy/prod.env"), true, None, false),
+            ("hash-b", "Slack Token", Some("tests/fixtures/chat.json"), false, None, true),
+            ("hash-c", "Generic API Key", Some("src/main.rs"), false, Some("revoked"), false),
+            ("hash-d", "Generic API Key", None, false, None, false),
+        ];
+        for (hash, detector, filename, verified, status, suppressed) in rows {
+            db.connection
+                .execute(insert, rusqlite::params![hash, detector, filename, verified, status, suppressed])
+                .unwrap();
+        }
+
+        let options = DatasetOptions { test_fraction: 0.5, seed: "s1".to_string() };
+        let mut examples = Vec::new();
+        let report = db.for_each_dataset_example(&options, |example| {
+            examples.push(example);
+            Ok(())
+        }).unwrap();
+        assert_eq!((report.true_positives, report.false_positives, report.unlabeled), (2, 1, 1));
+        assert_eq!(report.examples(), 3);
+
+        assert_eq!(examples[0].label_source, "verified");
+        assert_eq!(examples[0].file_extension.as_deref(), Some("env"));
+        assert_eq!(examples[0].path_depth, 2);
+        assert!(!examples[0].test_path);
+        assert_eq!((examples[1].label, examples[1].test_path), (DatasetLabel::Fp, true));
+        assert_eq!(examples[2].label_source, "validation");
+
+        let csv = {
+            let mut out = Vec::new();
+            db.export_dataset(&options, ExportFormat::Csv, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(csv.lines().next().unwrap(), DATASET_COLUMNS.join(","));
+        assert!(!csv.contains("hash-") && !csv.contains("acme") && !csv.contains("prod"), "{}", csv);
+
+        // The same seed gives the same IDs and split; another seed reshuffles the IDs
+        assert_eq!(assign("s1", "hash-a", 0.5), (examples[0].example_id.clone(), examples[0].split));
+        assert_ne!(assign("s2", "hash-a", 0.5).0, examples[0].example_id);
+        assert_eq!(assign("s1", "hash-a", 0.0).1, DatasetSplit::Train);
+        assert_eq!(assign("s1", "hash-a", 1.0).1, DatasetSplit::Test);
+        assert!(db.for_each_dataset_example(&DatasetOptions { test_fraction: 1.5, ..options }, |_| Ok(())).is_err());
+    }
+
+    #[test]
+    fn test_path_features() {
+        assert_eq!(file_extension("config/.env"), None);
+        assert_eq!(file_extension("src/App.TSX"), Some("tsx".to_string()));
+        assert_eq!(file_extension("notes.a-b"), None);
+        assert!(is_test_path("src/__tests__/client.ts"));
+        assert!(is_test_path("docs/example_config.yml"));
+        assert!(!is_test_path("src/latest/contest.rs"));
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use super::{run_purge, AttachmentStore, SecretDatabase};
+use crate::core::{paths, AuditLog, DiskForecast, DiskForecastConfig, MaintenanceConfig, ResourceLimits, ResourceMonitor, RetentionConfig};
+
+/// Minimum time between two purges triggered by the forecast
+const AUTO_PURGE_COOLDOWN: Duration = Duration::from_secs(24 * 3600);
+
+/// The findings database with its WAL and attachments, and the cache directory
+pub fn tracked_paths(database_path: &str) -> Vec<PathBuf> {
+    vec![
+        PathBuf::from(database_path),
+        PathBuf::from(format!("{}-wal", database_path)),
+        PathBuf::from(format!("{}-shm", database_path)),
+        AttachmentStore::beside_database(database_path).root().to_path_buf(),
+        paths::cache_dir(),
+    ]
+}
+
+/// Spawn the disk forecast job: sample the tracked paths, warn when the disk is projected
+/// to fill within `warn_days` and, with `auto_purge`, apply the retention policy and an
+/// incremental vacuum. Returns `None` when the sample interval is zero.
+pub fn spawn_disk_forecast_job(
+    database_path: String,
+    config: DiskForecastConfig,
+    retention: RetentionConfig,
+    maintenance: MaintenanceConfig,
+    audit: AuditLog,
+) -> Option<JoinHandle<()>> {
+    if config.sample_interval_minutes == 0 {
+        return None;
+    }
+
+    Some(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(config.sample_interval_minutes * 60));
+        let window = Duration::from_secs(config.window_hours.max(1) * 3600);
+        let tracked = tracked_paths(&database_path);
+        let mut monitor = ResourceMonitor::new(ResourceLimits::default());
+        let mut last_purge: Option<Instant> = None;
+        loop {
+            ticker.tick().await;
+
+            // Walking the cache can take a while, keep it off the async workers
+            let paths = tracked.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                let sample = monitor.sample_disk_usage(&paths, window);
+                (monitor, sample)
+            })
+            .await;
+            let sample = match result {
+                Ok((returned, sample)) => {
+                    monitor = returned;
+                    sample
+                }
+                Err(e) => {
+                    error!("Disk forecast task panicked: {}", e);
+                    return;
+                }
+            };
+            if let Err(e) = sample {
+                warn!("Could not sample disk usage: {}", e);
+                continue;
+            }
+
+            let Some(forecast) = monitor.disk_forecast(config.warn_days) else {
+                continue;
+            };
+            let mut purged = false;
+            if forecast.warning {
+                warn!(
+                    "Disk projected to fill in {:.1} days: {} MB free, tracked files growing {:.1} MB/day",
+                    forecast.days_remaining.unwrap_or_default(),
+                    forecast.free_bytes / (1024 * 1024),
+                    forecast.growth_bytes_per_day / (1024.0 * 1024.0)
+                );
+                if config.auto_purge && !last_purge.is_some_and(|at| at.elapsed() < AUTO_PURGE_COOLDOWN) {
+                    last_purge = Some(Instant::now());
+                    purged = true;
+                    let path = database_path.clone();
+                    let policy = retention.clone();
+                    let vacuum = maintenance.clone();
+                    let audit = audit.clone();
+                    let result = tokio::task::spawn_blocking(move || -> Result<usize> {
+                        let report = run_purge(&path, &policy, false, &audit, "disk-forecast")?;
+                        SecretDatabase::new(&path)?.run_maintenance(&vacuum, false)?;
+                        Ok(report.total())
+                    })
+                    .await;
+                    match result {
+                        Ok(Ok(rows)) => info!("Disk forecast purge removed or scrubbed {} rows", rows),
+                        Ok(Err(e)) => error!("Disk forecast purge failed: {}", e),
+                        Err(e) => error!("Disk forecast purge task panicked: {}", e),
+                    }
+                }
+            }
+
+            let path = database_path.clone();
+            let recorded = tokio::task::spawn_blocking(move || record_forecast(&path, &forecast, purged)).await;
+            if let Ok(Err(e)) = recorded {
+                warn!("Could not record disk forecast: {}", e);
+            }
+        }
+    }))
+}
+
+fn record_forecast(database_path: &str, forecast: &DiskForecast, purged: bool) -> Result<()> {
+    let mut snapshot = serde_json::to_value(forecast)?;
+    snapshot["auto_purged"] = serde_json::json!(purged);
+    SecretDatabase::new(database_path)?.record_heartbeat("disk", &snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_tracked_paths_cover_the_database_files() {
+        let paths = tracked_paths("/data/secrets.db");
+        let database_files = ["/data/secrets.db", "/data/secrets.db-wal", "/data/secrets.db-shm"].map(PathBuf::from);
+        assert_eq!(&paths[..3], database_files);
+        assert!(paths.contains(&AttachmentStore::beside_database("/data/secrets.db").root().to_path_buf()));
+        assert!(paths.contains(&paths::cache_dir()));
+    }
+
+    #[test]
+    fn test_record_forecast_keeps_the_latest_projection() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = dir.path().join("secrets.db").display().to_string();
+        let forecast = |growth_bytes_per_day: f64, days_remaining: Option<f64>| DiskForecast {
+            tracked_bytes: 2_000,
+            free_bytes: 10_000,
+            growth_bytes_per_day,
+            days_remaining,
+            samples: 3,
+            warning: days_remaining.is_some_and(|days| days < 7.0),
+            timestamp: Utc::now(),
+        };
+
+        record_forecast(&database, &forecast(2_000.0, Some(5.0)), true).unwrap();
+        // Not growing: no fill date, and the snapshot says so
+        record_forecast(&database, &forecast(-500.0, None), false).unwrap();
+
+        let heartbeats = SecretDatabase::new(&database).unwrap().heartbeats().unwrap();
+        let disk = heartbeats.iter().find(|h| h.component == "disk").unwrap();
+        assert_eq!(disk.snapshot["growth_bytes_per_day"], -500.0);
+        assert!(disk.snapshot["days_remaining"].is_null());
+        assert_eq!(disk.snapshot["warning"], false);
+        assert_eq!(disk.snapshot["auto_purged"], false);
+    }
+}
@@ -0,0 +1,211 @@
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, Connection, DatabaseName};
+use std::path::Path;
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+use super::SecretDatabase;
+
+/// Key for the findings database: a passphrase, or 64 hex digits used as the raw 256-bit key
+pub const DB_KEY_ENV: &str = "FINDINGS_DB_KEY";
+/// File holding the key, e.g. a mounted Kubernetes or Docker secret
+pub const DB_KEY_FILE_ENV: &str = "FINDINGS_DB_KEY_FILE";
+/// Shell command printing the key, e.g. `aws kms decrypt ...` or `secret-tool lookup ...`
+pub const DB_KEY_COMMAND_ENV: &str = "FINDINGS_DB_KEY_COMMAND";
+
+const RAW_KEY_HEX_LEN: usize = 64;
+
+static DB_KEY: OnceLock<Option<String>> = OnceLock::new();
+
+/// The value `PRAGMA key` takes: a raw key as `x'<hex>'`, anything else as a passphrase
+fn key_pragma_value(key: &str) -> String {
+    let key = key.trim_end_matches(['\r', '\n']);
+    if key.len() == RAW_KEY_HEX_LEN && key.bytes().all(|b| b.is_ascii_hexdigit()) {
+        format!("x'{}'", key)
+    } else {
+        key.to_string()
+    }
+}
+
+fn load_key() -> Result<Option<String>> {
+    let key = if let Ok(key) = std::env::var(DB_KEY_ENV) {
+        key
+    } else if let Ok(path) = std::env::var(DB_KEY_FILE_ENV) {
+        std::fs::read_to_string(&path).with_context(|| format!("Failed to read findings database key {}", path))?
+    } else if let Ok(command) = std::env::var(DB_KEY_COMMAND_ENV) {
+        let output = std::process::Command::new(if cfg!(windows) { "cmd" } else { "sh" })
+            .args([if cfg!(windows) { "/C" } else { "-c" }, &command])
+            .output()
+            .with_context(|| format!("Failed to run {}", DB_KEY_COMMAND_ENV))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "{} exited with {}: {}",
+                DB_KEY_COMMAND_ENV,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        String::from_utf8(output.stdout).map_err(|_| anyhow!("{} printed a key that is not UTF-8", DB_KEY_COMMAND_ENV))?
+    } else {
+        return Ok(None);
+    };
+    let key = key_pragma_value(&key);
+    if key.is_empty() {
+        return Err(anyhow!("The findings database key is empty"));
+    }
+    Ok(Some(key))
+}
+
+/// The configured key, read once per process so a KMS or keyring command runs only once
+pub fn database_key() -> Result<Option<&'static str>> {
+    if let Some(key) = DB_KEY.get() {
+        return Ok(key.as_deref());
+    }
+    let key = load_key()?;
+    Ok(DB_KEY.get_or_init(|| key).as_deref())
+}
+
+fn ensure_sqlcipher(connection: &Connection) -> Result<()> {
+    let version: Option<String> = connection.query_row("PRAGMA cipher_version", [], |row| row.get(0)).ok();
+    if version.is_none() {
+        return Err(anyhow!(
+            "{} is set but this build has no SQLCipher support; rebuild with --features sqlcipher",
+            DB_KEY_ENV
+        ));
+    }
+    Ok(())
+}
+
+/// Unlock `connection` with the configured key. Must run before anything else touches the
+/// database. Without a key this does nothing, so plaintext databases keep working.
+pub(super) fn apply_key(connection: &Connection, db_path: &str) -> Result<()> {
+    if db_path == ":memory:" {
+        return Ok(());
+    }
+    let Some(key) = database_key()? else {
+        return Ok(());
+    };
+    unlock(connection, db_path, key)
+}
+
+/// Unlock `connection` with `key`, failing unless the key opens the database
+fn unlock(connection: &Connection, db_path: &str, key: &str) -> Result<()> {
+    ensure_sqlcipher(connection)?;
+    connection.pragma_update(None, "key", key)?;
+    // The key is only checked when the first page is read
+    connection
+        .query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+        .map_err(|_| {
+            anyhow!(
+                "Cannot open {} with the configured key: the key is wrong or the database is not encrypted \
+                 (encrypt it with `database migrate-encrypt`)",
+                db_path
+            )
+        })?;
+    Ok(())
+}
+
+/// Open a database file that is not managed through `SecretDatabase`, such as a staged
+/// restore, with the configured key
+pub(super) fn open_keyed(db_path: &str) -> Result<Connection> {
+    let connection = Connection::open(db_path)?;
+    apply_key(&connection, db_path)?;
+    Ok(connection)
+}
+
+impl SecretDatabase {
+    /// Encrypt the plaintext database at `db_path` in place with the configured key. The
+    /// copy is written beside it and verified before it replaces the original.
+    pub fn migrate_encrypt(db_path: &str) -> Result<()> {
+        let key = database_key()?
+            .ok_or_else(|| anyhow!("Set {}, {} or {} to the key to encrypt with", DB_KEY_ENV, DB_KEY_FILE_ENV, DB_KEY_COMMAND_ENV))?;
+        encrypt_with_key(db_path, key)
+    }
+}
+
+/// `migrate_encrypt` with an explicit key
+fn encrypt_with_key(db_path: &str, key: &str) -> Result<()> {
+    if !Path::new(db_path).exists() {
+        return Err(anyhow!("{} does not exist", db_path));
+    }
+
+    // Opened unkeyed: if it is already encrypted, reading it fails here
+    let plaintext = Connection::open(db_path)?;
+    ensure_sqlcipher(&plaintext)?;
+    let version: i64 = plaintext
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|_| anyhow!("{} is not a plaintext database; it may already be encrypted", db_path))?;
+    plaintext.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+
+    let encrypted = format!("{}.encrypting", db_path);
+    std::fs::remove_file(&encrypted).ok();
+    plaintext.execute("ATTACH DATABASE ?1 AS encrypted KEY ?2", params![encrypted, key])?;
+    plaintext.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))?;
+    plaintext.pragma_update(Some(DatabaseName::Attached("encrypted")), "user_version", version)?;
+    plaintext.execute("DETACH DATABASE encrypted", [])?;
+    drop(plaintext);
+
+    let check = Connection::open(&encrypted)
+        .map_err(anyhow::Error::from)
+        .and_then(|connection| {
+            unlock(&connection, &encrypted, key)?;
+            Ok(connection.query_row("PRAGMA integrity_check", [], |row| row.get::<_, String>(0))?)
+        })
+        .unwrap_or_else(|e| e.to_string());
+    if check != "ok" {
+        std::fs::remove_file(&encrypted).ok();
+        return Err(anyhow!("Encrypted copy failed the integrity check: {}", check));
+    }
+
+    for suffix in ["-wal", "-shm"] {
+        std::fs::remove_file(format!("{}{}", db_path, suffix)).ok();
+    }
+    std::fs::rename(&encrypted, db_path)?;
+    info!("Encrypted {}", db_path);
+    warn!("Backups and replicas taken before now are still plaintext; replace them and delete the old copies");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_pragma_value() {
+        let raw = "2dd29ca851e7b56e4697b0e1f08507293d761a05ce4d1b628663f411a8086d99";
+        assert_eq!(key_pragma_value(&format!("{}\n", raw)), format!("x'{}'", raw));
+        assert_eq!(key_pragma_value("correct horse battery staple\n"), "correct horse battery staple");
+        assert_eq!(key_pragma_value("deadbeef"), "deadbeef");
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[test]
+    fn test_migrate_encrypt_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.db").to_string_lossy().to_string();
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch("CREATE TABLE secrets (secret_hash TEXT); INSERT INTO secrets VALUES ('aaa'); PRAGMA user_version = 7;")
+            .unwrap();
+
+        let key = key_pragma_value("correct horse battery staple");
+        encrypt_with_key(&path, &key).unwrap();
+        assert!(!Path::new(&format!("{}.encrypting", path)).exists());
+
+        // Unreadable without the key, or with another one
+        let unkeyed = Connection::open(&path).unwrap();
+        assert!(unkeyed.query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0)).is_err());
+        assert!(unlock(&Connection::open(&path).unwrap(), &path, "wrong horse").is_err());
+
+        let keyed = Connection::open(&path).unwrap();
+        unlock(&keyed, &path, &key).unwrap();
+        let hash: String = keyed.query_row("SELECT secret_hash FROM secrets", [], |row| row.get(0)).unwrap();
+        let version: i64 = keyed.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+        assert_eq!((hash.as_str(), version), ("aaa", 7));
+        drop(keyed);
+
+        // Already encrypted: refused, and the database is left as it was
+        assert!(encrypt_with_key(&path, &key).is_err());
+        unlock(&Connection::open(&path).unwrap(), &path, &key).unwrap();
+    }
+}
@@ -0,0 +1,312 @@
+use anyhow::Result;
+use rusqlite::types::Value;
+use rusqlite::{params, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::compression::{self, DEAD_LETTER_JSON, EVENT_QUEUE_JSON};
+use super::SecretDatabase;
+use crate::realtime::GitHubEvent;
+
+/// An event leased from the queue for processing
+#[derive(Debug, Clone)]
+pub struct QueuedEvent {
+    pub event: GitHubEvent,
+    /// Leases so far, this one included
+    pub attempts: u32,
+}
+
+/// Events waiting, being processed and given up on
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueueDepth {
+    pub pending: u64,
+    /// Leased and not yet acknowledged; they return to `pending` when the lease expires
+    pub in_flight: u64,
+    pub dead_letters: u64,
+}
+
+/// An event that failed processing `max_attempts` times
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub id: i64,
+    pub event_id: String,
+    pub event_type: String,
+    pub repository: String,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub failed_at: String,
+}
+
+impl SecretDatabase {
+    pub(super) fn initialize_event_queue_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS event_queue (
+                event_id TEXT PRIMARY KEY,
+                event_json TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT,
+                enqueued_at DATETIME NOT NULL,
+                visible_at DATETIME NOT NULL
+            )",
+            [],
+        )?;
+        self.connection.execute(
+            "CREATE INDEX IF NOT EXISTS idx_event_queue_visible ON event_queue(visible_at)",
+            [],
+        )?;
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS event_dead_letters (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                event_id TEXT NOT NULL,
+                event_type TEXT NOT NULL,
+                repository TEXT NOT NULL,
+                event_json TEXT NOT NULL,
+                attempts INTEGER NOT NULL,
+                last_error TEXT,
+                failed_at DATETIME NOT NULL
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Add events to the queue; events already queued are left alone. Returns how many were added.
+    pub fn enqueue_events(&self, events: &[GitHubEvent]) -> Result<usize> {
+        let tx = self.connection.unchecked_transaction()?;
+        let mut added = 0;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT OR IGNORE INTO event_queue (event_id, event_json, enqueued_at, visible_at)
+                VALUES (?, ?, datetime('now'), datetime('now'))",
+            )?;
+            let policy = compression::policy();
+            for event in events {
+                let json = policy.encode(&EVENT_QUEUE_JSON, serde_json::to_string(event)?)?;
+                added += stmt.execute(params![event.id, json])?;
+            }
+        }
+        tx.commit()?;
+        Ok(added)
+    }
+
+    /// Lease up to `limit` visible events, oldest first, hiding them from other consumers
+    /// for `visibility_secs`. An event not acknowledged by then is leased again, unless its
+    /// lease has already expired `max_attempts` times; it is dead-lettered instead.
+    pub fn lease_events(&self, limit: usize, visibility_secs: u64, max_attempts: u32) -> Result<Vec<QueuedEvent>> {
+        let tx = self.connection.unchecked_transaction()?;
+        // An event whose every lease expired most likely crashes or hangs its consumer
+        let expired = {
+            let mut stmt = tx.prepare_cached(
+                "SELECT event_id, event_json, attempts FROM event_queue
+                WHERE visible_at <= datetime('now') AND attempts >= ?",
+            )?;
+            let rows = stmt.query_map(params![max_attempts.max(1)], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Value>(1)?, row.get::<_, u32>(2)?))
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        for (event_id, json, attempts) in expired {
+            warn!("Dead-lettering event {}: its lease expired {} times", event_id, attempts);
+            let error = format!("Lease expired {} times without an acknowledgement", attempts);
+            dead_letter(&tx, &event_id, &json, attempts, &error)?;
+        }
+
+        let mut leased = Vec::new();
+        {
+            let mut stmt = tx.prepare_cached(
+                "UPDATE event_queue SET attempts = attempts + 1, visible_at = datetime('now', ?)
+                WHERE event_id IN (
+                    SELECT event_id FROM event_queue WHERE visible_at <= datetime('now')
+                    ORDER BY enqueued_at, event_id LIMIT ?
+                )
+                RETURNING event_json, attempts",
+            )?;
+            let rows = stmt.query_map(params![format!("+{} seconds", visibility_secs), limit as i64], |row| {
+                Ok((row.get::<_, Value>(0)?, row.get::<_, u32>(1)?))
+            })?;
+            for row in rows {
+                let (json, attempts) = row?;
+                leased.push(QueuedEvent { event: serde_json::from_str(&compression::decode(&json)?)?, attempts });
+            }
+        }
+        tx.commit()?;
+        leased.sort_by(|a, b| a.event.created_at.cmp(&b.event.created_at));
+        Ok(leased)
+    }
+
+    /// Remove a processed event from the queue
+    pub fn ack_event(&self, event_id: &str) -> Result<()> {
+        self.connection.execute("DELETE FROM event_queue WHERE event_id = ?", params![event_id])?;
+        Ok(())
+    }
+
+    /// Record a failed attempt. Below `max_attempts` the event is retried after
+    /// `retry_after_secs`; at `max_attempts` it moves to the dead-letter table. Returns
+    /// whether it was dead-lettered.
+    pub fn fail_event(&self, event_id: &str, error: &str, max_attempts: u32, retry_after_secs: u64) -> Result<bool> {
+        let tx = self.connection.unchecked_transaction()?;
+        let entry: Option<(Value, u32)> = tx
+            .query_row(
+                "SELECT event_json, attempts FROM event_queue WHERE event_id = ?",
+                params![event_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((json, attempts)) = entry else {
+            return Ok(false);
+        };
+
+        let dead = attempts >= max_attempts.max(1);
+        if dead {
+            dead_letter(&tx, event_id, &json, attempts, error)?;
+        } else {
+            tx.execute(
+                "UPDATE event_queue SET last_error = ?, visible_at = datetime('now', ?) WHERE event_id = ?",
+                params![error, format!("+{} seconds", retry_after_secs), event_id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(dead)
+    }
+
+    pub fn event_queue_depth(&self) -> Result<QueueDepth> {
+        let (pending, in_flight): (i64, i64) = self.connection.query_row(
+            "SELECT COALESCE(SUM(visible_at <= datetime('now')), 0), COALESCE(SUM(visible_at > datetime('now')), 0)
+            FROM event_queue",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let dead_letters: i64 = self.connection.query_row("SELECT COUNT(*) FROM event_dead_letters", [], |row| row.get(0))?;
+        Ok(QueueDepth { pending: pending as u64, in_flight: in_flight as u64, dead_letters: dead_letters as u64 })
+    }
+
+    /// Dead-lettered events, most recent first
+    pub fn dead_letters(&self, limit: usize) -> Result<Vec<DeadLetter>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, event_id, event_type, repository, attempts, last_error, failed_at
+            FROM event_dead_letters ORDER BY failed_at DESC, id DESC LIMIT ?",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            Ok(DeadLetter {
+                id: row.get(0)?,
+                event_id: row.get(1)?,
+                event_type: row.get(2)?,
+                repository: row.get(3)?,
+                attempts: row.get(4)?,
+                last_error: row.get(5)?,
+                failed_at: row.get(6)?,
+            })
+        })?;
+
+        let mut letters = Vec::new();
+        for row in rows {
+            letters.push(row?);
+        }
+        Ok(letters)
+    }
+
+    /// Put a dead-lettered event back on the queue with its attempts reset. Returns
+    /// whether `id` was a dead letter.
+    pub fn requeue_dead_letter(&self, id: i64) -> Result<bool> {
+        let tx = self.connection.unchecked_transaction()?;
+        let queued = tx.execute(
+            "INSERT OR IGNORE INTO event_queue (event_id, event_json, enqueued_at, visible_at)
+            SELECT event_id, event_json, datetime('now'), datetime('now') FROM event_dead_letters WHERE id = ?",
+            params![id],
+        )?;
+        let removed = tx.execute("DELETE FROM event_dead_letters WHERE id = ?", params![id])?;
+        tx.commit()?;
+        Ok(queued > 0 || removed > 0)
+    }
+}
+
+/// Move a queued event to the dead-letter table
+fn dead_letter(tx: &Transaction, event_id: &str, json: &Value, attempts: u32, error: &str) -> Result<()> {
+    let json = compression::decode(json)?;
+    let event: GitHubEvent = serde_json::from_str(&json)?;
+    tx.execute(
+        "INSERT INTO event_dead_letters (event_id, event_type, repository, event_json, attempts, last_error, failed_at)
+        VALUES (?, ?, ?, ?, ?, ?, datetime('now'))",
+        params![
+            event_id,
+            event.event_type,
+            event.repo.name,
+            compression::policy().encode(&DEAD_LETTER_JSON, json)?,
+            attempts,
+            error
+        ],
+    )?;
+    tx.execute("DELETE FROM event_queue WHERE event_id = ?", params![event_id])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::realtime::{Actor, Repository};
+    use chrono::Utc;
+
+    fn event(id: &str) -> GitHubEvent {
+        GitHubEvent {
+            id: id.to_string(),
+            event_type: "PushEvent".to_string(),
+            created_at: Utc::now(),
+            actor: Actor {
+                id: 1,
+                login: "octocat".to_string(),
+                display_login: None,
+                gravatar_id: None,
+                url: String::new(),
+                avatar_url: String::new(),
+            },
+            repo: Repository { id: 1, name: "acme/api".to_string(), url: String::new() },
+            payload: serde_json::json!({}),
+            public: true,
+        }
+    }
+
+    #[test]
+    fn test_leased_events_are_retried_then_dead_lettered() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        assert_eq!(db.enqueue_events(&[event("1"), event("2")]).unwrap(), 2);
+        // Replayed events are not queued twice
+        assert_eq!(db.enqueue_events(&[event("1")]).unwrap(), 0);
+
+        let leased = db.lease_events(10, 300, 2).unwrap();
+        assert_eq!(leased.len(), 2);
+        // Hidden while leased
+        assert!(db.lease_events(10, 300, 2).unwrap().is_empty());
+        assert_eq!(db.event_queue_depth().unwrap().in_flight, 2);
+
+        db.ack_event("1").unwrap();
+        assert!(!db.fail_event("2", "scan failed", 2, 0).unwrap());
+        let retried = db.lease_events(10, 300, 2).unwrap();
+        assert_eq!((retried[0].event.id.as_str(), retried[0].attempts), ("2", 2));
+        assert!(db.fail_event("2", "scan failed again", 2, 0).unwrap());
+
+        let depth = db.event_queue_depth().unwrap();
+        assert_eq!((depth.pending, depth.in_flight, depth.dead_letters), (0, 0, 1));
+        let letters = db.dead_letters(10).unwrap();
+        assert_eq!(letters[0].last_error.as_deref(), Some("scan failed again"));
+
+        assert!(db.requeue_dead_letter(letters[0].id).unwrap());
+        assert_eq!(db.lease_events(10, 300, 2).unwrap()[0].attempts, 1);
+    }
+
+    #[test]
+    fn test_expired_leases_count_towards_dead_lettering() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        db.enqueue_events(&[event("1")]).unwrap();
+
+        // A consumer that never acknowledges or fails the event, e.g. because it crashes on it
+        assert_eq!(db.lease_events(10, 0, 2).unwrap()[0].attempts, 1);
+        assert_eq!(db.lease_events(10, 0, 2).unwrap()[0].attempts, 2);
+        assert!(db.lease_events(10, 0, 2).unwrap().is_empty());
+
+        let depth = db.event_queue_depth().unwrap();
+        assert_eq!((depth.pending, depth.in_flight, depth.dead_letters), (0, 0, 1));
+        let letters = db.dead_letters(10).unwrap();
+        assert_eq!((letters[0].event_id.as_str(), letters[0].attempts), ("1", 2));
+        assert_eq!(letters[0].last_error.as_deref(), Some("Lease expired 2 times without an acknowledgement"));
+    }
+}
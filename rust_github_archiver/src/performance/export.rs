@@ -0,0 +1,305 @@
+use anyhow::{anyhow, Result};
+use std::io::Write;
+
+use super::{SecretDatabase, SecretQueryFilters, SecretRecord};
+
+/// File formats `database export` writes findings in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON array, each finding with its notes, attachments and tags
+    Json,
+    /// One JSON object per line, as in `Json`
+    JsonLines,
+    /// One row per finding; tags joined with `;`
+    Csv,
+    /// Columnar, for Spark, DuckDB or BigQuery; the same columns as `Csv`
+    Parquet,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "jsonl" | "ndjson" | "json-lines" => Ok(Self::JsonLines),
+            "csv" => Ok(Self::Csv),
+            "parquet" => Ok(Self::Parquet),
+            other => Err(anyhow!("Unsupported export format: {}", other)),
+        }
+    }
+}
+
+/// Columns of the flat (CSV and Parquet) exports, in order
+pub const FLAT_COLUMNS: [&str; 12] = [
+    "id",
+    "secret_hash",
+    "detector_name",
+    "filename",
+    "line_number",
+    "entropy",
+    "severity",
+    "category",
+    "verified",
+    "suppressed",
+    "created_at",
+    "tags",
+];
+
+impl SecretDatabase {
+    /// The finding as exported to JSON: the record with its notes, attachments and tags
+    fn export_document(&self, record: &SecretRecord) -> Result<serde_json::Value> {
+        let mut document = serde_json::to_value(record)?;
+        let annotations = self.annotations(&record.secret_hash)?;
+        document["notes"] = serde_json::to_value(&annotations.notes)?;
+        document["attachments"] = serde_json::to_value(&annotations.attachments)?;
+        document["tags"] = serde_json::to_value(self.finding_tags(&record.secret_hash)?)?;
+        Ok(document)
+    }
+
+    /// Write the findings matching `filters` to `out`, one at a time as they are read from
+    /// the database. Returns how many were written.
+    pub fn export_findings<W: Write + Send>(&self, filters: &SecretQueryFilters, format: ExportFormat, mut out: W) -> Result<usize> {
+        let count = match format {
+            ExportFormat::Json => {
+                // Written by hand so the findings never have to be held at once
+                out.write_all(b"[")?;
+                let mut first = true;
+                let count = self.for_each_secret(filters, |record| {
+                    out.write_all(if first { b"\n".as_slice() } else { b",\n".as_slice() })?;
+                    first = false;
+                    serde_json::to_writer_pretty(&mut out, &self.export_document(&record)?)?;
+                    Ok(())
+                })?;
+                out.write_all(if count == 0 { b"]\n".as_slice() } else { b"\n]\n".as_slice() })?;
+                count
+            }
+            ExportFormat::JsonLines => self.for_each_secret(filters, |record| {
+                serde_json::to_writer(&mut out, &self.export_document(&record)?)?;
+                out.write_all(b"\n")?;
+                Ok(())
+            })?,
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(&mut out);
+                writer.write_record(FLAT_COLUMNS)?;
+                let count = self.for_each_secret(filters, |record| {
+                    let tags = self.finding_tags(&record.secret_hash)?.join(";");
+                    writer.write_record([
+                        record.id.to_string(),
+                        record.secret_hash,
+                        record.detector_name,
+                        record.filename.unwrap_or_default(),
+                        record.line_number.map(|line| line.to_string()).unwrap_or_default(),
+                        record.entropy.to_string(),
+                        record.severity.to_string(),
+                        record.category.to_string(),
+                        record.verified.to_string(),
+                        record.suppressed.to_string(),
+                        record.created_at,
+                        tags,
+                    ])?;
+                    Ok(())
+                })?;
+                writer.flush()?;
+                count
+            }
+            ExportFormat::Parquet => self.export_parquet(filters, out)?,
+        };
+        Ok(count)
+    }
+
+    #[cfg(feature = "parquet")]
+    fn export_parquet<W: Write + Send>(&self, filters: &SecretQueryFilters, out: W) -> Result<usize> {
+        let mut writer = parquet_export::FindingParquetWriter::new(out)?;
+        let count = self.for_each_secret(filters, |record| {
+            let tags = self.finding_tags(&record.secret_hash)?;
+            writer.push(record, tags)
+        })?;
+        writer.finish()?;
+        Ok(count)
+    }
+
+    #[cfg(not(feature = "parquet"))]
+    fn export_parquet<W: Write + Send>(&self, _filters: &SecretQueryFilters, _out: W) -> Result<usize> {
+        Err(anyhow!("This build has no Parquet support; rebuild with --features parquet"))
+    }
+}
+
+#[cfg(feature = "parquet")]
+mod parquet_export {
+    use anyhow::Result;
+    use parquet::basic::Compression;
+    use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, Int32Type, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use crate::performance::SecretRecord;
+
+    /// Findings buffered per row group; bounds memory use whatever the export's size
+    const ROW_GROUP_ROWS: usize = 65_536;
+
+    const SCHEMA: &str = "message finding {
+        REQUIRED INT64 id;
+        REQUIRED BYTE_ARRAY secret_hash (UTF8);
+        REQUIRED BYTE_ARRAY detector_name (UTF8);
+        OPTIONAL BYTE_ARRAY filename (UTF8);
+        OPTIONAL INT32 line_number;
+        REQUIRED DOUBLE entropy;
+        REQUIRED BYTE_ARRAY severity (UTF8);
+        REQUIRED BYTE_ARRAY category (UTF8);
+        REQUIRED BOOLEAN verified;
+        REQUIRED BOOLEAN suppressed;
+        REQUIRED BYTE_ARRAY created_at (UTF8);
+        REQUIRED BYTE_ARRAY tags (UTF8);
+    }";
+
+    #[derive(Default)]
+    struct Columns {
+        ids: Vec<i64>,
+        hashes: Vec<ByteArray>,
+        detectors: Vec<ByteArray>,
+        filenames: Vec<ByteArray>,
+        filename_levels: Vec<i16>,
+        lines: Vec<i32>,
+        line_levels: Vec<i16>,
+        entropies: Vec<f64>,
+        severities: Vec<ByteArray>,
+        categories: Vec<ByteArray>,
+        verified: Vec<bool>,
+        suppressed: Vec<bool>,
+        created_at: Vec<ByteArray>,
+        tags: Vec<ByteArray>,
+    }
+
+    pub struct FindingParquetWriter<W: Write + Send> {
+        writer: SerializedFileWriter<W>,
+        columns: Columns,
+    }
+
+    impl<W: Write + Send> FindingParquetWriter<W> {
+        pub fn new(out: W) -> Result<Self> {
+            let schema = Arc::new(parse_message_type(SCHEMA)?);
+            let properties = Arc::new(WriterProperties::builder().set_compression(Compression::SNAPPY).build());
+            Ok(Self { writer: SerializedFileWriter::new(out, schema, properties)?, columns: Columns::default() })
+        }
+
+        pub fn push(&mut self, record: SecretRecord, tags: Vec<String>) -> Result<()> {
+            let columns = &mut self.columns;
+            columns.ids.push(record.id);
+            columns.hashes.push(record.secret_hash.as_str().into());
+            columns.detectors.push(record.detector_name.as_str().into());
+            match record.filename {
+                Some(filename) => {
+                    columns.filenames.push(filename.as_str().into());
+                    columns.filename_levels.push(1);
+                }
+                None => columns.filename_levels.push(0),
+            }
+            match record.line_number {
+                Some(line) => {
+                    columns.lines.push(i32::try_from(line).unwrap_or(i32::MAX));
+                    columns.line_levels.push(1);
+                }
+                None => columns.line_levels.push(0),
+            }
+            columns.entropies.push(record.entropy);
+            columns.severities.push(record.severity.to_string().as_str().into());
+            columns.categories.push(record.category.to_string().as_str().into());
+            columns.verified.push(record.verified);
+            columns.suppressed.push(record.suppressed);
+            columns.created_at.push(record.created_at.as_str().into());
+            columns.tags.push(tags.join(";").as_str().into());
+            if columns.ids.len() >= ROW_GROUP_ROWS {
+                self.flush()?;
+            }
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            if self.columns.ids.is_empty() {
+                return Ok(());
+            }
+            let columns = std::mem::take(&mut self.columns);
+            let mut row_group = self.writer.next_row_group()?;
+            let mut index = 0;
+            while let Some(mut column) = row_group.next_column()? {
+                match index {
+                    0 => column.typed::<Int64Type>().write_batch(&columns.ids, None, None)?,
+                    1 => column.typed::<ByteArrayType>().write_batch(&columns.hashes, None, None)?,
+                    2 => column.typed::<ByteArrayType>().write_batch(&columns.detectors, None, None)?,
+                    3 => column.typed::<ByteArrayType>().write_batch(&columns.filenames, Some(&columns.filename_levels), None)?,
+                    4 => column.typed::<Int32Type>().write_batch(&columns.lines, Some(&columns.line_levels), None)?,
+                    5 => column.typed::<DoubleType>().write_batch(&columns.entropies, None, None)?,
+                    6 => column.typed::<ByteArrayType>().write_batch(&columns.severities, None, None)?,
+                    7 => column.typed::<ByteArrayType>().write_batch(&columns.categories, None, None)?,
+                    8 => column.typed::<BoolType>().write_batch(&columns.verified, None, None)?,
+                    9 => column.typed::<BoolType>().write_batch(&columns.suppressed, None, None)?,
+                    10 => column.typed::<ByteArrayType>().write_batch(&columns.created_at, None, None)?,
+                    _ => column.typed::<ByteArrayType>().write_batch(&columns.tags, None, None)?,
+                };
+                column.close()?;
+                index += 1;
+            }
+            row_group.close()?;
+            Ok(())
+        }
+
+        pub fn finish(mut self) -> Result<()> {
+            self.flush()?;
+            self.writer.close()?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::SecretSeverity;
+
+    fn database() -> SecretDatabase {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let insert = "INSERT INTO secrets (secret_hash, detector_name, matched_text_hash, filename, severity, category, created_at)
+            VALUES (?, ?, '', ?, ?, 'CloudProvider', datetime('now'))";
+        db.connection.execute(insert, ["aaa", "AWS Access Key ID", "deploy/prod.env", "Critical"]).unwrap();
+        db.connection.execute(insert, ["bbb", "Slack Token", "src/chat.rs", "Low"]).unwrap();
+        db
+    }
+
+    fn export(db: &SecretDatabase, filters: &SecretQueryFilters, format: ExportFormat) -> String {
+        let mut out = Vec::new();
+        db.export_findings(filters, format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_export_formats_stream_every_finding() {
+        let db = database();
+        let all = SecretQueryFilters::default();
+
+        let json: serde_json::Value = serde_json::from_str(&export(&db, &all, ExportFormat::Json)).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert!(json[0]["tags"].is_array());
+
+        let lines = export(&db, &all, ExportFormat::JsonLines);
+        assert_eq!(lines.lines().count(), 2);
+        assert!(lines.lines().all(|line| serde_json::from_str::<serde_json::Value>(line).is_ok()));
+
+        let csv = export(&db, &all, ExportFormat::Csv);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], FLAT_COLUMNS.join(","));
+        assert_eq!(rows.len(), 3);
+
+        let critical = SecretQueryFilters { min_severity: Some(SecretSeverity::High), ..Default::default() };
+        let csv = export(&db, &critical, ExportFormat::Csv);
+        assert_eq!(csv.lines().count(), 2);
+        assert!(csv.contains("deploy/prod.env"));
+
+        let none = SecretQueryFilters { detector_name: Some("GitHub Token".to_string()), ..Default::default() };
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&export(&db, &none, ExportFormat::Json)).unwrap(), serde_json::json!([]));
+        assert!("xlsx".parse::<ExportFormat>().is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{SecretDatabase, NOT_SYNTHETIC};
+
+/// Latest state a long-running component (hunter, real-time monitor) wrote to the
+/// findings database, so `status` can report on it from another process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentHeartbeat {
+    pub component: String,
+    pub snapshot: Value,
+    pub updated_at: String,
+    /// Seconds since `updated_at`
+    pub age_seconds: i64,
+}
+
+impl SecretDatabase {
+    pub(super) fn initialize_heartbeats_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS component_heartbeats (
+                component TEXT PRIMARY KEY,
+                snapshot TEXT NOT NULL, -- JSON
+                updated_at DATETIME NOT NULL
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_heartbeat(&self, component: &str, snapshot: &Value) -> Result<()> {
+        self.connection.execute(
+            "INSERT INTO component_heartbeats (component, snapshot, updated_at) VALUES (?, ?, datetime('now'))
+            ON CONFLICT(component) DO UPDATE SET snapshot = excluded.snapshot, updated_at = excluded.updated_at",
+            params![component, serde_json::to_string(snapshot)?],
+        )?;
+        Ok(())
+    }
+
+    pub fn heartbeats(&self) -> Result<Vec<ComponentHeartbeat>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT component, snapshot, updated_at,
+                CAST(strftime('%s', 'now') - strftime('%s', updated_at) AS INTEGER)
+            FROM component_heartbeats ORDER BY component",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let snapshot: String = row.get(1)?;
+            Ok(ComponentHeartbeat {
+                component: row.get(0)?,
+                snapshot: serde_json::from_str(&snapshot).unwrap_or(Value::Null),
+                updated_at: row.get(2)?,
+                age_seconds: row.get(3)?,
+            })
+        })?;
+
+        let mut heartbeats = Vec::new();
+        for row in rows {
+            heartbeats.push(row?);
+        }
+        Ok(heartbeats)
+    }
+
+    /// Active (not suppressed) findings of `severity` nobody has resolved or revoked yet
+    pub fn unresolved_count(&self, severity: &str) -> Result<i64> {
+        let count = self.connection.query_row(
+            &format!(
+                "SELECT COUNT(*) FROM secrets
+                WHERE severity = ? AND COALESCE(validation_status, '') NOT IN ('resolved', 'revoked')
+                AND NOT suppressed AND {}",
+                NOT_SYNTHETIC
+            ),
+            params![severity],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Saved-search alerts that matched but have not been delivered yet
+    pub fn pending_search_alert_count(&self) -> Result<i64> {
+        let count = self.connection.query_row(
+            "SELECT COUNT(*) FROM saved_search_matches WHERE notified_at IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_heartbeat_is_replaced() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        db.record_heartbeat("hunter", &json!({"is_running": true})).unwrap();
+        db.record_heartbeat("hunter", &json!({"is_running": false})).unwrap();
+        db.record_heartbeat("monitor", &json!({"status": "Healthy"})).unwrap();
+
+        let heartbeats = db.heartbeats().unwrap();
+        assert_eq!(heartbeats.len(), 2);
+        assert_eq!(heartbeats[0].component, "hunter");
+        assert_eq!(heartbeats[0].snapshot, json!({"is_running": false}));
+        assert!(heartbeats[0].age_seconds < 5);
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+
+use super::SecretDatabase;
+
+/// `YYYY-MM-DD` (the end of that day, UTC) or an RFC 3339 timestamp
+pub fn parse_as_of(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(day) = value.parse::<NaiveDate>() {
+        let end = day.succ_opt().ok_or_else(|| anyhow!("Invalid as-of date {}", value))?;
+        return Ok(end.and_time(chrono::NaiveTime::MIN).and_utc() - chrono::Duration::seconds(1));
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|at| at.with_timezone(&Utc))
+        .map_err(|_| anyhow!("Invalid as-of time {}; use YYYY-MM-DD or an RFC 3339 timestamp", value))
+}
+
+/// Rows to query findings from: `secrets` itself, or when `as_of` is given, each finding's
+/// last version from before then under the same name and columns. Findings deleted by then,
+/// or not yet found, are left out.
+pub(super) fn findings_source(as_of: Option<DateTime<Utc>>) -> (String, Vec<Value>) {
+    let Some(as_of) = as_of else {
+        return ("secrets".to_string(), Vec::new());
+    };
+    let source = "(SELECT v.finding_id AS id, v.secret_hash, v.commit_id, v.detector_name, v.filename, v.line_number,
+            v.entropy, v.severity, v.category, v.verified, v.created_at, v.validation_status, v.suppressed
+        FROM finding_versions v
+        WHERE v.id IN (SELECT MAX(id) FROM finding_versions WHERE valid_from <= ? GROUP BY secret_hash)
+            AND NOT v.deleted) AS secrets";
+    (source.to_string(), vec![Value::Text(as_of.format("%Y-%m-%d %H:%M:%S").to_string())])
+}
+
+/// One recorded state of a finding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindingVersion {
+    pub severity: String,
+    /// `open` until a validation status is set
+    pub status: String,
+    pub suppressed: bool,
+    pub deleted: bool,
+    pub valid_from: String,
+}
+
+/// Findings per severity and status at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotCount {
+    pub severity: String,
+    /// `open`, a validation status such as `resolved` or `revoked`, or `suppressed`
+    pub status: String,
+    pub findings: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindingSnapshot {
+    pub as_of: String,
+    pub total: i64,
+    pub counts: Vec<SnapshotCount>,
+}
+
+impl SecretDatabase {
+    /// Every recorded severity and status change of a finding, oldest first
+    pub fn finding_history(&self, secret_hash: &str) -> Result<Vec<FindingVersion>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT severity, COALESCE(validation_status, 'open'), suppressed, deleted, valid_from
+            FROM finding_versions WHERE secret_hash = ? ORDER BY id",
+        )?;
+        let versions = stmt
+            .query_map([secret_hash], |row| {
+                Ok(FindingVersion {
+                    severity: row.get(0)?,
+                    status: row.get(1)?,
+                    suppressed: row.get(2)?,
+                    deleted: row.get(3)?,
+                    valid_from: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        if versions.is_empty() {
+            return Err(anyhow!("No finding {}", secret_hash));
+        }
+        Ok(versions)
+    }
+
+    /// How many findings there were per severity and status at `as_of`, e.g. at the end of
+    /// a quarter for compliance reporting
+    pub fn snapshot(&self, as_of: DateTime<Utc>) -> Result<FindingSnapshot> {
+        let (source, params) = findings_source(Some(as_of));
+        let mut stmt = self.connection.prepare(&format!(
+            "SELECT severity, CASE WHEN suppressed THEN 'suppressed' ELSE COALESCE(validation_status, 'open') END AS status,
+                COUNT(*)
+            FROM {} GROUP BY 1, 2 ORDER BY 1, 2",
+            source
+        ))?;
+        let counts = stmt
+            .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+                Ok(SnapshotCount { severity: row.get(0)?, status: row.get(1)?, findings: row.get(2)? })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(FindingSnapshot {
+            as_of: as_of.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            total: counts.iter().map(|c| c.findings).sum(),
+            counts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::performance::SecretQueryFilters;
+    use crate::secrets::SecretSeverity;
+
+    #[test]
+    fn test_parse_as_of() {
+        assert_eq!(parse_as_of("2024-03-31").unwrap().to_rfc3339(), "2024-03-31T23:59:59+00:00");
+        assert_eq!(parse_as_of("2024-03-31T12:00:00+02:00").unwrap().to_rfc3339(), "2024-03-31T10:00:00+00:00");
+        assert!(parse_as_of("last quarter").is_err());
+    }
+
+    #[test]
+    fn test_query_as_of_sees_earlier_severity_and_status() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let insert = "INSERT INTO secrets (secret_hash, detector_name, matched_text_hash, severity, category, created_at)
+            VALUES (?, 'AWS Access Key ID', '', ?, 'CloudProvider', '2024-01-10 09:00:00')";
+        db.connection.execute(insert, ["aaa", "High"]).unwrap();
+        db.connection.execute(insert, ["bbb", "Medium"]).unwrap();
+        // Pretend the findings were stored in January and changed in April
+        db.connection.execute("UPDATE finding_versions SET valid_from = '2024-01-10 09:00:00'", []).unwrap();
+        db.connection
+            .execute("UPDATE secrets SET severity = 'Critical', validation_status = 'revoked' WHERE secret_hash = 'aaa'", [])
+            .unwrap();
+        db.connection.execute("DELETE FROM secrets WHERE secret_hash = 'bbb'", []).unwrap();
+        db.connection.execute("UPDATE finding_versions SET valid_from = '2024-04-02 10:00:00' WHERE valid_from > '2024-04-01'", []).unwrap();
+
+        let march = SecretQueryFilters { as_of: Some(parse_as_of("2024-03-31").unwrap()), ..Default::default() };
+        let found = db.query_secrets(&march).unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().any(|f| f.secret_hash == "aaa" && matches!(f.severity, SecretSeverity::High)));
+        let snapshot = db.snapshot(parse_as_of("2024-03-31").unwrap()).unwrap();
+        assert_eq!(snapshot.total, 2);
+        assert!(snapshot.counts.iter().all(|c| c.status == "open"));
+
+        let now = db.query_secrets(&SecretQueryFilters { as_of: Some(Utc::now()), ..Default::default() }).unwrap();
+        assert_eq!(now.len(), 1);
+        assert!(matches!(now[0].severity, SecretSeverity::Critical));
+        assert!(db.query_secrets(&SecretQueryFilters { as_of: parse_as_of("2023-12-31").ok(), ..Default::default() }).unwrap().is_empty());
+
+        let history = db.finding_history("aaa").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].status, "revoked");
+    }
+}
@@ -0,0 +1,301 @@
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::SecretDatabase;
+
+/// SQL predicate that excludes findings under legal hold. Every retention or cleanup
+/// query that deletes or rewrites `secrets` rows must include it.
+pub const NOT_ON_LEGAL_HOLD: &str = "secret_hash NOT IN (SELECT secret_hash FROM legal_holds)";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegalHold {
+    pub secret_hash: String,
+    pub reason: String,
+    pub placed_by: String,
+    pub placed_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ErasureReport {
+    pub erased: usize,
+    pub not_found: Vec<String>,
+    pub on_legal_hold: Vec<String>,
+}
+
+impl SecretDatabase {
+    pub(super) fn initialize_legal_hold_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS legal_holds (
+                secret_hash TEXT PRIMARY KEY,
+                reason TEXT NOT NULL,
+                placed_by TEXT NOT NULL,
+                placed_at DATETIME NOT NULL
+            )",
+            [],
+        )?;
+
+        // Erasure requests are kept (without secret material) so the workflow is auditable
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS erasure_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                requested_by TEXT NOT NULL,
+                erased_count INTEGER NOT NULL,
+                skipped_on_hold INTEGER NOT NULL,
+                erased_at DATETIME NOT NULL
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    /// Exempt a finding from retention, cleanup and erasure until the hold is released
+    pub fn place_legal_hold(&self, secret_hash: &str, reason: &str, placed_by: &str) -> Result<()> {
+        let exists: bool = self.connection.query_row(
+            "SELECT EXISTS(SELECT 1 FROM secrets WHERE secret_hash = ?)",
+            params![secret_hash],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Err(anyhow!("No finding with hash {}", secret_hash));
+        }
+
+        self.connection.execute(
+            "INSERT OR REPLACE INTO legal_holds (secret_hash, reason, placed_by, placed_at)
+            VALUES (?, ?, ?, datetime('now'))",
+            params![secret_hash, reason, placed_by],
+        )?;
+        info!("Placed legal hold on {} ({})", secret_hash, reason);
+        Ok(())
+    }
+
+    /// Release a hold; returns false if the finding was not held
+    pub fn release_legal_hold(&self, secret_hash: &str) -> Result<bool> {
+        let removed = self.connection.execute(
+            "DELETE FROM legal_holds WHERE secret_hash = ?",
+            params![secret_hash],
+        )?;
+        if removed > 0 {
+            info!("Released legal hold on {}", secret_hash);
+        }
+        Ok(removed > 0)
+    }
+
+    pub fn is_under_legal_hold(&self, secret_hash: &str) -> Result<bool> {
+        Ok(self.connection.query_row(
+            "SELECT EXISTS(SELECT 1 FROM legal_holds WHERE secret_hash = ?)",
+            params![secret_hash],
+            |row| row.get(0),
+        )?)
+    }
+
+    pub fn list_legal_holds(&self) -> Result<Vec<LegalHold>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT secret_hash, reason, placed_by, placed_at FROM legal_holds ORDER BY placed_at DESC"
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(LegalHold {
+                secret_hash: row.get(0)?,
+                reason: row.get(1)?,
+                placed_by: row.get(2)?,
+                placed_at: row.get(3)?,
+            })
+        })?;
+
+        let mut holds = Vec::new();
+        for row in rows {
+            holds.push(row?);
+        }
+        Ok(holds)
+    }
+
+    /// Right-to-erasure: scrub everything that could identify a secret or where it was found,
+    /// keeping detector, severity, category and timestamps so aggregate statistics still add up.
+    /// Findings under legal hold are left untouched and reported back.
+    pub fn erase_findings(&self, secret_hashes: &[String], requested_by: &str) -> Result<ErasureReport> {
+        let mut report = ErasureReport::default();
+        let tx = self.connection.unchecked_transaction()?;
+
+        for hash in secret_hashes {
+            let held: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM legal_holds WHERE secret_hash = ?)",
+                params![hash],
+                |row| row.get(0),
+            )?;
+            if held {
+                warn!("Refusing to erase {}: finding is under legal hold", hash);
+                report.on_legal_hold.push(hash.clone());
+                continue;
+            }
+
+            let updated = scrub_finding(&tx, hash)?;
+            if updated == 0 {
+                report.not_found.push(hash.clone());
+            } else {
+                report.erased += updated;
+            }
+        }
+
+        tx.execute(
+            "INSERT INTO erasure_log (requested_by, erased_count, skipped_on_hold, erased_at)
+            VALUES (?, ?, ?, datetime('now'))",
+            params![requested_by, report.erased as i64, report.on_legal_hold.len() as i64],
+        )?;
+        tx.commit()?;
+
+        info!(
+            "Erased {} findings ({} on legal hold, {} not found)",
+            report.erased,
+            report.on_legal_hold.len(),
+            report.not_found.len()
+        );
+        Ok(report)
+    }
+}
+
+/// Scrub everything that could identify one finding or where it was found; returns the
+/// number of findings scrubbed. The caller checks legal holds and owns the transaction.
+pub(super) fn scrub_finding(connection: &Connection, hash: &str) -> Result<usize> {
+    // Analyst notes and evidence describe the secret; the blobs go on the next prune
+    connection.execute("DELETE FROM finding_notes WHERE secret_hash = ?", params![hash])?;
+    connection.execute("DELETE FROM finding_attachments WHERE secret_hash = ?", params![hash])?;
+    connection.execute("DELETE FROM finding_tags WHERE secret_hash = ?", params![hash])?;
+    connection.execute("DELETE FROM saved_search_matches WHERE secret_hash = ?", params![hash])?;
+
+    connection.execute(
+        "UPDATE triage_results SET analysis = NULL, suggested_actions = NULL, risk_factors = NULL
+        WHERE secret_id = (SELECT id FROM secrets WHERE secret_hash = ?)",
+        params![hash],
+    )?;
+
+    // Earlier versions take the placeholder hash the row is about to get
+    connection.execute(
+        "UPDATE finding_versions SET
+            secret_hash = 'erased:' || COALESCE((SELECT id FROM secrets WHERE secret_hash = ?1), finding_id),
+            filename = NULL,
+            line_number = NULL
+        WHERE secret_hash = ?1",
+        params![hash],
+    )?;
+
+    // The row keeps a placeholder hash so the UNIQUE constraint and statistics survive
+    let updated = connection.execute(
+        "UPDATE secrets SET
+            secret_hash = 'erased:' || id,
+            matched_text_hash = '',
+            context_hash = NULL,
+            filename = NULL,
+            line_number = NULL,
+            validation_status = 'erased',
+            updated_at = datetime('now')
+        WHERE secret_hash = ?",
+        params![hash],
+    )?;
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{RetentionAction, RetentionConfig};
+    use crate::secrets::{SecretCategory, SecretMatch, SecretSeverity};
+
+    fn finding(hash: &str) -> SecretMatch {
+        SecretMatch {
+            detector_name: "Generic Secret".to_string(),
+            matched_text: format!("secret-{}", hash),
+            start_position: 0,
+            end_position: 10,
+            line_number: Some(1),
+            filename: Some("config.yml".to_string()),
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
+            entropy: 4.0,
+            severity: SecretSeverity::High,
+            category: SecretCategory::Token,
+            context: String::new(),
+            verified: false,
+            hash: hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_place_and_release_hold() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        db.bulk_insert_secrets(&[finding("aaa")]).unwrap();
+        assert!(db.place_legal_hold("missing", "litigation", "legal").is_err());
+
+        db.place_legal_hold("aaa", "litigation", "legal").unwrap();
+        // Placing it again replaces the reason
+        db.place_legal_hold("aaa", "subpoena 42", "counsel").unwrap();
+        assert!(db.is_under_legal_hold("aaa").unwrap());
+        let holds = db.list_legal_holds().unwrap();
+        assert_eq!(holds.len(), 1);
+        assert_eq!((holds[0].reason.as_str(), holds[0].placed_by.as_str()), ("subpoena 42", "counsel"));
+
+        assert!(db.release_legal_hold("aaa").unwrap());
+        assert!(!db.release_legal_hold("aaa").unwrap());
+        assert!(!db.is_under_legal_hold("aaa").unwrap());
+        assert!(db.list_legal_holds().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_hold_blocks_erasure_until_released() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        db.bulk_insert_secrets(&[finding("held"), finding("free")]).unwrap();
+        db.place_legal_hold("held", "litigation", "legal").unwrap();
+
+        let hashes = ["held".to_string(), "free".to_string(), "missing".to_string()];
+        let report = db.erase_findings(&hashes, "dpo").unwrap();
+        assert_eq!(report.erased, 1);
+        assert_eq!(report.on_legal_hold, ["held"]);
+        assert_eq!(report.not_found, ["missing"]);
+        let status: Option<String> = db
+            .connection
+            .query_row("SELECT validation_status FROM secrets WHERE secret_hash = 'held'", [], |row| row.get(0))
+            .unwrap();
+        assert_ne!(status.as_deref(), Some("erased"));
+
+        db.release_legal_hold("held").unwrap();
+        let report = db.erase_findings(&["held".to_string()], "dpo").unwrap();
+        assert_eq!((report.erased, report.on_legal_hold.len()), (1, 0));
+        let logged: i64 = db.connection.query_row("SELECT COUNT(*) FROM erasure_log", [], |row| row.get(0)).unwrap();
+        assert_eq!(logged, 2);
+    }
+
+    #[test]
+    fn test_hold_blocks_retention_until_released() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        db.bulk_insert_secrets(&[finding("held")]).unwrap();
+        db.connection.execute("UPDATE secrets SET created_at = datetime('now', '-200 days')", []).unwrap();
+        db.place_legal_hold("held", "litigation", "legal").unwrap();
+
+        let policy = RetentionConfig {
+            context_days: 0,
+            validated_secret_days: 0,
+            resolved_finding_days: 0,
+            purge_interval_hours: 0,
+            finding_days: 180,
+            finding_action: RetentionAction::Anonymize,
+            low_unverified_days: 0,
+            orphans: false,
+            categories: Default::default(),
+        };
+        let report = db.purge(&policy, false).unwrap();
+        assert_eq!((report.expired_findings_anonymized, report.held_skipped), (0, 1));
+        assert!(db.is_under_legal_hold("held").unwrap());
+
+        db.release_legal_hold("held").unwrap();
+        let report = db.purge(&policy, false).unwrap();
+        assert_eq!((report.expired_findings_anonymized, report.held_skipped), (1, 0));
+        let remaining: i64 = db
+            .connection
+            .query_row("SELECT COUNT(*) FROM secrets WHERE secret_hash = 'held'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+}
@@ -0,0 +1,210 @@
+use anyhow::Result;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use super::SecretDatabase;
+use crate::core::MaintenanceConfig;
+
+/// How long a deferred run waits before checking again
+const DEFER_RETRY: Duration = Duration::from_secs(600);
+
+/// A scan checkpoint updated this recently is taken to belong to a running scan
+const ACTIVE_SCAN_MINUTES: i64 = 30;
+
+/// `PRAGMA auto_vacuum` value that lets `incremental_vacuum` return free pages
+const AUTO_VACUUM_INCREMENTAL: i64 = 2;
+
+/// Plain-text payload rows compressed per transaction, and batches per column per run
+const COMPRESS_BATCH_SIZE: usize = 500;
+const COMPRESS_BATCHES_PER_RUN: usize = 20;
+
+/// What one maintenance run did
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    /// Why the run was put off, when it was
+    pub deferred: Option<String>,
+    /// Free pages before the run
+    pub free_pages: i64,
+    /// Pages returned to the file system by `incremental_vacuum`
+    pub vacuumed_pages: i64,
+    /// Incremental vacuum needs `auto_vacuum = INCREMENTAL`, which takes one full
+    /// `database optimize` to switch on for a database created before it was the default
+    pub incremental_vacuum_enabled: bool,
+    /// Findings inserted since the last ANALYZE
+    pub rows_since_analyze: i64,
+    /// Whether a full ANALYZE ran; otherwise only `PRAGMA optimize`
+    pub analyzed: bool,
+    /// Payload rows stored as plain text that were compressed
+    pub compressed_rows: usize,
+}
+
+impl SecretDatabase {
+    pub(super) fn initialize_maintenance_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS maintenance_state (
+                key TEXT PRIMARY KEY,
+                value INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Why maintenance should wait: a historical scan saving checkpoints, or monitor events
+    /// leased and still being processed
+    pub fn maintenance_blocker(&self) -> Result<Option<String>> {
+        let scan: Option<String> = self
+            .connection
+            .query_row(
+                "SELECT scan_id FROM scan_checkpoints
+                WHERE NOT completed AND updated_at > datetime('now', ?)
+                ORDER BY updated_at DESC LIMIT 1",
+                params![format!("-{} minutes", ACTIVE_SCAN_MINUTES)],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(scan_id) = scan {
+            return Ok(Some(format!("scan {} is in progress", scan_id)));
+        }
+
+        let depth = self.event_queue_depth()?;
+        if depth.in_flight > 0 {
+            return Ok(Some(format!("{} queued events are being processed", depth.in_flight)));
+        }
+        Ok(None)
+    }
+
+    /// Online maintenance: compress a few batches of payload rows still stored as plain
+    /// text, return up to `vacuum_pages` free pages to the file system, ANALYZE
+    /// once `analyze_after_rows` findings were added since the last one (`PRAGMA optimize`
+    /// otherwise), and checkpoint the WAL without waiting for readers. Nothing here takes
+    /// the database offline the way `VACUUM` does. Unless `force`, the run is deferred while
+    /// `maintenance_blocker` reports work in progress.
+    pub fn run_maintenance(&self, config: &MaintenanceConfig, force: bool) -> Result<MaintenanceReport> {
+        let mut report = MaintenanceReport::default();
+        if !force {
+            if let Some(reason) = self.maintenance_blocker()? {
+                report.deferred = Some(reason);
+                return Ok(report);
+            }
+        }
+
+        // Before the vacuum, so the pages compression frees are returned in the same run
+        report.compressed_rows = self
+            .compress_existing_rows(COMPRESS_BATCH_SIZE, Some(COMPRESS_BATCHES_PER_RUN))?
+            .iter()
+            .map(|column| column.rows)
+            .sum();
+
+        report.free_pages = self.connection.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+        let auto_vacuum: i64 = self.connection.query_row("PRAGMA auto_vacuum", [], |row| row.get(0))?;
+        report.incremental_vacuum_enabled = auto_vacuum == AUTO_VACUUM_INCREMENTAL;
+        if report.incremental_vacuum_enabled && report.free_pages > 0 && config.vacuum_pages > 0 {
+            let pages = report.free_pages.min(config.vacuum_pages as i64);
+            // Every step of the pragma returns a row; they must be read for it to run
+            let mut stmt = self.connection.prepare(&format!("PRAGMA incremental_vacuum({})", pages))?;
+            let mut rows = stmt.query([])?;
+            while rows.next()?.is_some() {}
+            let remaining: i64 = self.connection.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+            report.vacuumed_pages = report.free_pages - remaining;
+        }
+
+        let newest: i64 = self.connection.query_row("SELECT COALESCE(MAX(id), 0) FROM secrets", [], |row| row.get(0))?;
+        let analyzed_at: i64 = self
+            .connection
+            .query_row("SELECT value FROM maintenance_state WHERE key = 'analyzed_secret_id'", [], |row| row.get(0))
+            .optional()?
+            .unwrap_or(0);
+        report.rows_since_analyze = (newest - analyzed_at).max(0);
+        if config.analyze_after_rows > 0 && report.rows_since_analyze >= config.analyze_after_rows as i64 {
+            self.connection.execute("ANALYZE", [])?;
+            self.connection.execute(
+                "INSERT INTO maintenance_state (key, value) VALUES ('analyzed_secret_id', ?)
+                ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![newest],
+            )?;
+            report.analyzed = true;
+        } else {
+            self.connection.execute_batch("PRAGMA optimize")?;
+        }
+
+        self.connection.query_row("PRAGMA wal_checkpoint(PASSIVE)", [], |_| Ok(()))?;
+        Ok(report)
+    }
+}
+
+/// Spawn the scheduled maintenance job. Returns `None` when the interval is zero.
+pub fn spawn_maintenance_job(database_path: String, config: MaintenanceConfig) -> Option<JoinHandle<()>> {
+    if config.interval_hours == 0 {
+        return None;
+    }
+
+    Some(tokio::spawn(async move {
+        let interval = Duration::from_secs(config.interval_hours * 3600);
+        let mut delay = interval;
+        loop {
+            tokio::time::sleep(delay).await;
+
+            let path = database_path.clone();
+            let policy = config.clone();
+            let result = tokio::task::spawn_blocking(move || -> Result<MaintenanceReport> {
+                let db = SecretDatabase::new(&path)?;
+                let report = db.run_maintenance(&policy, false)?;
+                db.record_heartbeat("maintenance", &serde_json::to_value(&report)?)?;
+                Ok(report)
+            })
+            .await;
+
+            delay = interval;
+            match result {
+                Ok(Ok(report)) => match &report.deferred {
+                    Some(reason) => {
+                        info!("Deferring database maintenance: {}", reason);
+                        delay = DEFER_RETRY;
+                    }
+                    None => {
+                        if !report.incremental_vacuum_enabled && report.free_pages > 0 {
+                            warn!(
+                                "{} free pages cannot be reclaimed online; run `database optimize` once to enable incremental vacuum",
+                                report.free_pages
+                            );
+                        }
+                        info!(
+                            "Database maintenance reclaimed {} pages{}",
+                            report.vacuumed_pages,
+                            if report.analyzed { " and refreshed statistics" } else { "" }
+                        );
+                    }
+                },
+                Ok(Err(e)) => error!("Scheduled maintenance failed: {}", e),
+                Err(e) => error!("Scheduled maintenance task panicked: {}", e),
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maintenance_waits_for_running_scans() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("maintenance.db");
+        let db = SecretDatabase::new(path.to_str().unwrap()).unwrap();
+        let config = MaintenanceConfig { interval_hours: 1, vacuum_pages: 100, analyze_after_rows: 1 };
+
+        let report = db.run_maintenance(&config, false).unwrap();
+        assert!(report.deferred.is_none());
+        assert!(report.incremental_vacuum_enabled);
+        assert!(!report.analyzed);
+
+        db.save_checkpoint(&crate::performance::ScanCheckpoint::new("scan-1", vec!["acme".to_string()])).unwrap();
+        let report = db.run_maintenance(&config, false).unwrap();
+        assert_eq!(report.deferred.as_deref(), Some("scan scan-1 is in progress"));
+        assert!(db.run_maintenance(&config, true).unwrap().deferred.is_none());
+    }
+}
@@ -0,0 +1,241 @@
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::{error, info};
+
+use super::SecretDatabase;
+
+/// Placeholders left by erasure and synthetic test findings are local to their instance
+const IMPORTABLE_SECRET: &str = "o.secret_hash NOT LIKE 'erased:%' AND o.secret_hash NOT LIKE 'synthetic-%'";
+
+/// Every `secrets` column an import copies, besides `commit_id`, which is remapped by SHA
+const SECRET_COLUMNS: &str = "secret_hash, detector_name, matched_text_hash, filename, line_number, entropy, severity, \
+    category, context_hash, verified, validation_status, validation_method, created_at, updated_at, suppressed, \
+    suppressed_by, ticket_url";
+
+const TRIAGE_COLUMNS: &str =
+    "impact_score, bounty_potential, revocation_priority, analysis, suggested_actions, risk_factors, confidence, created_at";
+
+/// Rows of one table an import looked at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeCounts {
+    /// Not here before; copied
+    pub new: usize,
+    /// Here already, but the imported row was newer and replaced it
+    pub updated: usize,
+    /// Here already and at least as recent; left alone
+    pub duplicates: usize,
+}
+
+impl MergeCounts {
+    fn tally(total: usize, new: usize, updated: usize) -> Self {
+        Self { new, updated, duplicates: total.saturating_sub(new + updated) }
+    }
+}
+
+/// What `database import` merged from another hunter's database
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeReport {
+    pub source: String,
+    pub commits: MergeCounts,
+    pub secrets: MergeCounts,
+    pub triage_results: MergeCounts,
+}
+
+fn count(connection: &Connection, query: &str) -> Result<usize> {
+    Ok(connection.query_row(query, [], |row| row.get::<_, i64>(0))? as usize)
+}
+
+impl SecretDatabase {
+    /// Merge the commits, findings and triage results of another instance's database at
+    /// `other_path` into this one, matching findings on their secret hash. When both hold
+    /// a finding, its verification state is taken from whichever changed it last; a
+    /// triage result likewise from the later analysis. Everything is merged in one
+    /// transaction, so a failed import changes nothing.
+    pub fn merge_from(&self, other_path: &str) -> Result<MergeReport> {
+        if !Path::new(other_path).exists() {
+            return Err(anyhow!("{} does not exist", other_path));
+        }
+        // Checks the key and schema version without writing to the other database
+        drop(SecretDatabase::open_read_only(other_path)?);
+
+        let main_file: Option<String> = self.connection.query_row("PRAGMA database_list", [], |row| row.get(2))?;
+        if let Some(main_file) = main_file.filter(|f| !f.is_empty()) {
+            if Path::new(&main_file).canonicalize()? == Path::new(other_path).canonicalize()? {
+                return Err(anyhow!("Cannot import {} into itself", other_path));
+            }
+        }
+
+        // Without a KEY clause, SQLCipher opens the attachment with the main database's key
+        self.connection.execute("ATTACH DATABASE ?1 AS other", params![other_path])?;
+        let result = self.merge_attached();
+        self.connection.execute("DETACH DATABASE other", [])?;
+        let mut report = result?;
+        report.source = other_path.to_string();
+
+        // Recent dashboard counts include the imported findings
+        if let Err(e) = self.refresh_rollups(super::ROLLUP_WINDOW_DAYS) {
+            error!("Failed to update finding rollups: {}", e);
+        }
+        info!(
+            "Imported {}: {} new and {} updated findings, {} duplicates",
+            other_path, report.secrets.new, report.secrets.updated, report.secrets.duplicates
+        );
+        Ok(report)
+    }
+
+    fn merge_attached(&self) -> Result<MergeReport> {
+        let tx = rusqlite::Transaction::new_unchecked(&self.connection, TransactionBehavior::Immediate)?;
+        let mut report = MergeReport::default();
+
+        // Commits are facts about a SHA; the first copy recorded is kept
+        let total = count(&tx, "SELECT COUNT(*) FROM other.commits")?;
+        let new = tx.execute(
+            "INSERT OR IGNORE INTO main.commits
+                (commit_sha, repository_name, author_email, author_name, message, is_dangling, created_at, processed_at)
+            SELECT commit_sha, repository_name, author_email, author_name, message, is_dangling, created_at, processed_at
+            FROM other.commits",
+            [],
+        )?;
+        report.commits = MergeCounts::tally(total, new, 0);
+
+        // Conflicting verification states: the later change wins. Updated before the
+        // insert, so newly copied findings are not compared with themselves.
+        let total = count(&tx, &format!("SELECT COUNT(*) FROM other.secrets o WHERE {}", IMPORTABLE_SECRET))?;
+        let updated = tx.execute(
+            &format!(
+                "UPDATE main.secrets SET
+                    verified = o.verified,
+                    validation_status = o.validation_status,
+                    validation_method = o.validation_method,
+                    updated_at = COALESCE(o.updated_at, o.created_at)
+                FROM other.secrets o
+                WHERE main.secrets.secret_hash = o.secret_hash AND {}
+                AND COALESCE(o.updated_at, o.created_at) > COALESCE(main.secrets.updated_at, main.secrets.created_at)
+                AND (main.secrets.verified IS NOT o.verified
+                    OR main.secrets.validation_status IS NOT o.validation_status
+                    OR main.secrets.validation_method IS NOT o.validation_method)",
+                IMPORTABLE_SECRET
+            ),
+            [],
+        )?;
+        let new = tx.execute(
+            &format!(
+                "INSERT INTO main.secrets (commit_id, {columns})
+                SELECT
+                    (SELECT c.id FROM main.commits c
+                     WHERE c.commit_sha = (SELECT oc.commit_sha FROM other.commits oc WHERE oc.id = o.commit_id)),
+                    {prefixed}
+                FROM other.secrets o
+                WHERE {importable}
+                AND NOT EXISTS (SELECT 1 FROM main.secrets s WHERE s.secret_hash = o.secret_hash)",
+                columns = SECRET_COLUMNS,
+                prefixed = prefixed("o", SECRET_COLUMNS),
+                importable = IMPORTABLE_SECRET
+            ),
+            [],
+        )?;
+        report.secrets = MergeCounts::tally(total, new, updated);
+
+        // Triage results follow their finding by hash; a later analysis replaces an earlier one
+        let matched = "FROM other.triage_results t
+            JOIN other.secrets o ON o.id = t.secret_id
+            JOIN main.secrets s ON s.secret_hash = o.secret_hash";
+        let total = count(&tx, &format!("SELECT COUNT(*) {} WHERE {}", matched, IMPORTABLE_SECRET))?;
+        let updated = tx.execute(
+            &format!(
+                "UPDATE main.triage_results SET {assignments}
+                FROM (SELECT s.id AS secret_id, {prefixed} {matched} WHERE {importable}) AS t
+                WHERE main.triage_results.secret_id = t.secret_id AND t.created_at > main.triage_results.created_at",
+                assignments = TRIAGE_COLUMNS.split(", ").map(|c| format!("{0} = t.{0}", c)).collect::<Vec<_>>().join(", "),
+                prefixed = prefixed("t", TRIAGE_COLUMNS),
+                matched = matched,
+                importable = IMPORTABLE_SECRET
+            ),
+            [],
+        )?;
+        let new = tx.execute(
+            &format!(
+                "INSERT INTO main.triage_results (secret_id, {columns})
+                SELECT s.id, {prefixed} {matched}
+                WHERE {importable} AND NOT EXISTS (SELECT 1 FROM main.triage_results r WHERE r.secret_id = s.id)",
+                columns = TRIAGE_COLUMNS,
+                prefixed = prefixed("t", TRIAGE_COLUMNS),
+                matched = matched,
+                importable = IMPORTABLE_SECRET
+            ),
+            [],
+        )?;
+        report.triage_results = MergeCounts::tally(total, new, updated);
+
+        tx.commit()?;
+        Ok(report)
+    }
+}
+
+/// `a, b` as `t.a, t.b`
+fn prefixed(table: &str, columns: &str) -> String {
+    columns.split(", ").map(|c| format!("{}.{}", table, c)).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::{SecretCategory, SecretMatch, SecretSeverity};
+
+    fn finding(hash: &str) -> SecretMatch {
+        SecretMatch {
+            detector_name: "GitHub Token".to_string(),
+            matched_text: format!("ghp_{}", hash),
+            start_position: 0,
+            end_position: 40,
+            line_number: Some(3),
+            filename: Some("ci/deploy.sh".to_string()),
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
+            entropy: 4.4,
+            severity: SecretSeverity::High,
+            category: SecretCategory::Token,
+            context: String::new(),
+            verified: false,
+            hash: hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_merge_deduplicates_and_takes_later_verification() {
+        let dir = tempfile::tempdir().unwrap();
+        let here_path = dir.path().join("here.db");
+        let there_path = dir.path().join("there.db");
+        let here = SecretDatabase::new(here_path.to_str().unwrap()).unwrap();
+        let there = SecretDatabase::new(there_path.to_str().unwrap()).unwrap();
+
+        here.bulk_insert_secrets(&[finding("shared"), finding("stale")]).unwrap();
+        there.bulk_insert_secrets(&[finding("shared"), finding("stale"), finding("theirs")]).unwrap();
+        there
+            .connection
+            .execute(
+                "UPDATE secrets SET verified = TRUE, validation_status = 'valid', updated_at = datetime('now', '+1 hour')
+                WHERE secret_hash = 'stale'",
+                [],
+            )
+            .unwrap();
+        drop(there);
+
+        let report = here.merge_from(there_path.to_str().unwrap()).unwrap();
+        assert_eq!(report.secrets, MergeCounts { new: 1, updated: 1, duplicates: 1 });
+        let verified: bool = here
+            .connection
+            .query_row("SELECT verified FROM secrets WHERE secret_hash = 'stale'", [], |row| row.get(0))
+            .unwrap();
+        assert!(verified);
+
+        // A second import finds nothing new
+        let again = here.merge_from(there_path.to_str().unwrap()).unwrap();
+        assert_eq!(again.secrets, MergeCounts { new: 0, updated: 0, duplicates: 3 });
+        assert!(here.merge_from(here_path.to_str().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,277 @@
+use anyhow::{anyhow, Result};
+use tracing::info;
+
+use super::{SecretDatabase, SCHEMA_VERSION};
+use crate::core::migrations::{self, Migration};
+
+/// Store name used in messages and reported versions
+pub const FINDINGS_STORE: &str = "findings";
+
+/// The baseline is the schema `initialize_schema` creates; later changes are added here and
+/// `SCHEMA_VERSION` moved to the last one
+pub const FINDINGS_MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, description: "baseline schema", statements: &[] },
+    Migration {
+        version: 2,
+        description: "allowlist suppression",
+        statements: &[
+            "ALTER TABLE secrets ADD COLUMN suppressed BOOLEAN NOT NULL DEFAULT FALSE",
+            "ALTER TABLE secrets ADD COLUMN suppressed_by TEXT",
+            "ALTER TABLE finding_rollups ADD COLUMN suppressed INTEGER NOT NULL DEFAULT 0",
+        ],
+    },
+    Migration {
+        version: 3,
+        description: "repository metadata sync",
+        statements: &[
+            "ALTER TABLE repositories ADD COLUMN visibility TEXT",
+            "ALTER TABLE repositories ADD COLUMN language TEXT",
+            "ALTER TABLE repositories ADD COLUMN archived BOOLEAN NOT NULL DEFAULT FALSE",
+            "ALTER TABLE repositories ADD COLUMN synced_at DATETIME",
+            "CREATE INDEX IF NOT EXISTS idx_repositories_synced ON repositories(synced_at)",
+        ],
+    },
+    Migration {
+        version: 4,
+        description: "repository forks",
+        statements: &["ALTER TABLE repositories ADD COLUMN is_fork BOOLEAN"],
+    },
+    Migration {
+        version: 5,
+        description: "finding history for as-of queries",
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS finding_versions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                finding_id INTEGER NOT NULL,
+                secret_hash TEXT NOT NULL,
+                commit_id INTEGER,
+                detector_name TEXT NOT NULL,
+                filename TEXT,
+                line_number INTEGER,
+                entropy REAL,
+                severity TEXT NOT NULL,
+                category TEXT NOT NULL,
+                verified BOOLEAN,
+                created_at DATETIME NOT NULL,
+                validation_status TEXT,
+                suppressed BOOLEAN NOT NULL DEFAULT FALSE,
+                deleted BOOLEAN NOT NULL DEFAULT FALSE,
+                valid_from DATETIME NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_finding_versions_hash ON finding_versions(secret_hash, id)",
+            "CREATE INDEX IF NOT EXISTS idx_finding_versions_from ON finding_versions(valid_from)",
+            // Findings stored before the history existed start out as they are now
+            "INSERT INTO finding_versions
+                (finding_id, secret_hash, commit_id, detector_name, filename, line_number, entropy, severity, category,
+                 verified, created_at, validation_status, suppressed, valid_from)
+            SELECT id, secret_hash, commit_id, detector_name, filename, line_number, entropy, severity, category,
+                verified, created_at, validation_status, suppressed, created_at
+            FROM secrets",
+            // Re-detections replace the row; only a changed severity or status is a new version
+            "CREATE TRIGGER IF NOT EXISTS finding_versions_insert AFTER INSERT ON secrets
+            WHEN NOT EXISTS (
+                SELECT 1 FROM finding_versions v
+                WHERE v.id = (SELECT MAX(id) FROM finding_versions WHERE secret_hash = NEW.secret_hash)
+                    AND NOT v.deleted
+                    AND v.severity = NEW.severity
+                    AND v.validation_status IS NEW.validation_status
+                    AND v.suppressed = NEW.suppressed
+            )
+            BEGIN
+                INSERT INTO finding_versions
+                    (finding_id, secret_hash, commit_id, detector_name, filename, line_number, entropy, severity, category,
+                     verified, created_at, validation_status, suppressed, valid_from)
+                VALUES (NEW.id, NEW.secret_hash, NEW.commit_id, NEW.detector_name, NEW.filename, NEW.line_number,
+                    NEW.entropy, NEW.severity, NEW.category, NEW.verified, NEW.created_at, NEW.validation_status,
+                    NEW.suppressed, datetime('now'));
+            END",
+            "CREATE TRIGGER IF NOT EXISTS finding_versions_update AFTER UPDATE OF severity, validation_status, suppressed ON secrets
+            WHEN OLD.severity IS NOT NEW.severity
+                OR OLD.validation_status IS NOT NEW.validation_status
+                OR OLD.suppressed IS NOT NEW.suppressed
+            BEGIN
+                INSERT INTO finding_versions
+                    (finding_id, secret_hash, commit_id, detector_name, filename, line_number, entropy, severity, category,
+                     verified, created_at, validation_status, suppressed, valid_from)
+                VALUES (NEW.id, NEW.secret_hash, NEW.commit_id, NEW.detector_name, NEW.filename, NEW.line_number,
+                    NEW.entropy, NEW.severity, NEW.category, NEW.verified, NEW.created_at, NEW.validation_status,
+                    NEW.suppressed, datetime('now'));
+            END",
+            "CREATE TRIGGER IF NOT EXISTS finding_versions_delete AFTER DELETE ON secrets
+            BEGIN
+                INSERT INTO finding_versions
+                    (finding_id, secret_hash, commit_id, detector_name, filename, line_number, entropy, severity, category,
+                     verified, created_at, validation_status, suppressed, deleted, valid_from)
+                VALUES (OLD.id, OLD.secret_hash, OLD.commit_id, OLD.detector_name, OLD.filename, OLD.line_number,
+                    OLD.entropy, OLD.severity, OLD.category, OLD.verified, OLD.created_at, OLD.validation_status,
+                    OLD.suppressed, TRUE, datetime('now'));
+            END",
+        ],
+    },
+    Migration {
+        version: 6,
+        description: "ticket back-links",
+        statements: &["ALTER TABLE secrets ADD COLUMN ticket_url TEXT"],
+    },
+    // Nothing to alter: payload columns may now hold compressed blobs, which older builds
+    // cannot read, so the version bump keeps them out
+    Migration { version: 7, description: "compressed payload columns", statements: &[] },
+    // Cached dashboard results are checked against these counters, which every write to the
+    // tables behind them bumps, from this process or another
+    Migration {
+        version: 8,
+        description: "query cache generations",
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS cache_generations (
+                scope TEXT PRIMARY KEY,
+                generation INTEGER NOT NULL DEFAULT 0
+            )",
+            "INSERT OR IGNORE INTO cache_generations (scope) VALUES ('stats'), ('tags'), ('repositories')",
+            "CREATE TRIGGER IF NOT EXISTS cache_stats_finding_rollups_insert AFTER INSERT ON finding_rollups
+            BEGIN UPDATE cache_generations SET generation = generation + 1 WHERE scope = 'stats'; END",
+            "CREATE TRIGGER IF NOT EXISTS cache_stats_finding_rollups_update AFTER UPDATE ON finding_rollups
+            BEGIN UPDATE cache_generations SET generation = generation + 1 WHERE scope = 'stats'; END",
+            "CREATE TRIGGER IF NOT EXISTS cache_stats_finding_rollups_delete AFTER DELETE ON finding_rollups
+            BEGIN UPDATE cache_generations SET generation = generation + 1 WHERE scope = 'stats'; END",
+            "CREATE TRIGGER IF NOT EXISTS cache_tags_finding_tags_insert AFTER INSERT ON finding_tags
+            BEGIN UPDATE cache_generations SET generation = generation + 1 WHERE scope = 'tags'; END",
+            "CREATE TRIGGER IF NOT EXISTS cache_tags_finding_tags_delete AFTER DELETE ON finding_tags
+            BEGIN UPDATE cache_generations SET generation = generation + 1 WHERE scope = 'tags'; END",
+            "CREATE TRIGGER IF NOT EXISTS cache_tags_repository_tags_insert AFTER INSERT ON repository_tags
+            BEGIN UPDATE cache_generations SET generation = generation + 1 WHERE scope = 'tags'; END",
+            "CREATE TRIGGER IF NOT EXISTS cache_tags_repository_tags_delete AFTER DELETE ON repository_tags
+            BEGIN UPDATE cache_generations SET generation = generation + 1 WHERE scope = 'tags'; END",
+            "CREATE TRIGGER IF NOT EXISTS cache_tags_secrets_insert AFTER INSERT ON secrets
+            BEGIN UPDATE cache_generations SET generation = generation + 1 WHERE scope = 'tags'; END",
+            "CREATE TRIGGER IF NOT EXISTS cache_tags_secrets_delete AFTER DELETE ON secrets
+            BEGIN UPDATE cache_generations SET generation = generation + 1 WHERE scope = 'tags'; END",
+            "CREATE TRIGGER IF NOT EXISTS cache_repositories_repositories_insert AFTER INSERT ON repositories
+            BEGIN UPDATE cache_generations SET generation = generation + 1 WHERE scope = 'repositories'; END",
+            "CREATE TRIGGER IF NOT EXISTS cache_repositories_repositories_update AFTER UPDATE ON repositories
+            BEGIN UPDATE cache_generations SET generation = generation + 1 WHERE scope = 'repositories'; END",
+            "CREATE TRIGGER IF NOT EXISTS cache_repositories_repositories_delete AFTER DELETE ON repositories
+            BEGIN UPDATE cache_generations SET generation = generation + 1 WHERE scope = 'repositories'; END",
+        ],
+    },
+    // SQLite has no inline INDEX clause in CREATE TABLE, so the baseline's indexes were never
+    // created. UNIQUE columns (secret_hash, commit_sha) are indexed by their constraint already.
+    Migration {
+        version: 9,
+        description: "findings table indexes",
+        statements: &[
+            "CREATE INDEX IF NOT EXISTS idx_events_repository ON events(repository_name, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_events_type ON events(event_type, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_events_processed ON events(processed, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_commits_repository ON commits(repository_name, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_commits_dangling ON commits(is_dangling, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_secrets_detector ON secrets(detector_name, severity)",
+            "CREATE INDEX IF NOT EXISTS idx_secrets_commit ON secrets(commit_id, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_secrets_validation ON secrets(verified, validation_status)",
+            "CREATE INDEX IF NOT EXISTS idx_secrets_matched_text ON secrets(matched_text_hash)",
+            "CREATE INDEX IF NOT EXISTS idx_triage_impact ON triage_results(impact_score DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_triage_bounty ON triage_results(bounty_potential DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_triage_priority ON triage_results(revocation_priority, created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_repositories_organization ON repositories(organization, name)",
+            "CREATE INDEX IF NOT EXISTS idx_repositories_risk ON repositories(risk_score DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_repositories_activity ON repositories(last_activity DESC)",
+        ],
+    },
+];
+
+impl SecretDatabase {
+    /// Refuse a database written by a newer build before any table is created in it
+    pub(super) fn check_schema_version(&self) -> Result<i32> {
+        let stored = self.schema_version()?;
+        migrations::pending(FINDINGS_STORE, stored, FINDINGS_MIGRATIONS)?;
+        Ok(stored)
+    }
+
+    /// Apply the migrations newer than `stored` in one transaction and record the new
+    /// version in `PRAGMA user_version`
+    pub(super) fn apply_migrations(&self, stored: i32) -> Result<()> {
+        let pending = migrations::pending(FINDINGS_STORE, stored, FINDINGS_MIGRATIONS)?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let tx = self.connection.unchecked_transaction()?;
+        for migration in pending {
+            info!("Migrating {} schema to version {}: {}", FINDINGS_STORE, migration.version, migration.description);
+            for statement in migration.statements {
+                tx.execute_batch(statement).map_err(|e| {
+                    anyhow!("Migration {} of the {} schema failed: {}", migration.version, FINDINGS_STORE, e)
+                })?;
+            }
+        }
+        tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_version_matches_migrations() {
+        assert_eq!(migrations::latest_version(FINDINGS_MIGRATIONS), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_baseline_indexes_are_created() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let indexes: i64 = db
+            .connection
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index'
+                AND name IN ('idx_secrets_detector', 'idx_commits_repository', 'idx_events_processed')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexes, 3);
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_indexes_are_added_to_existing_databases() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("findings.db");
+        let path = path.to_str().unwrap();
+
+        // A database from a release at version 8, before the indexes existed
+        let db = SecretDatabase::new(path).unwrap();
+        db.connection.execute_batch("DROP INDEX idx_secrets_detector; DROP INDEX idx_events_processed;").unwrap();
+        db.connection.pragma_update(None, "user_version", 8).unwrap();
+        drop(db);
+
+        let db = SecretDatabase::new(path).unwrap();
+        let indexes: i64 = db
+            .connection
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index'
+                AND name IN ('idx_secrets_detector', 'idx_events_processed')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexes, 2);
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_newer_database_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("findings.db");
+        let path = path.to_str().unwrap();
+
+        let db = SecretDatabase::new(path).unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+        db.connection.pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+        drop(db);
+
+        let error = SecretDatabase::new(path).err().unwrap().to_string();
+        assert!(error.contains("only supports up to version"));
+    }
+}
@@ -1,22 +1,106 @@
 use anyhow::{anyhow, Result};
 use lru::LruCache;
 use rayon::prelude::*;
-use rusqlite::{Connection, params};
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::types::{Type, Value};
+use rusqlite::{Connection, OpenFlags, Transaction, TransactionBehavior, params};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::num::NonZeroUsize;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::RwLock;
 use tracing::{info, warn, error, debug};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::secrets::{SecretMatch, SecretSeverity, SecretCategory};
 use crate::ai::TriageResult;
+use crate::github::CommitCheckStatus;
+use crate::sinks::RedactionProfile;
+
+pub mod alert_log;
+pub mod allowlist;
+pub mod backup;
+pub mod canaries;
+pub mod checkpoints;
+pub mod compression;
+pub mod dataset;
+pub mod disk_forecast;
+pub mod encryption;
+pub mod event_queue;
+pub mod export;
+pub mod fingerprint;
+pub mod heartbeats;
+pub mod history;
+pub mod legal_hold;
+pub mod maintenance;
+pub mod merge;
+pub mod migrations;
+pub mod notes;
+pub mod organizations;
+pub mod pool;
+pub mod query_cache;
+pub mod quotas;
+pub mod repositories;
+pub mod retention;
+pub mod saved_searches;
+pub mod scan_requests;
+pub mod scorecard;
+pub mod similarity;
+pub mod slow_queries;
+pub mod stats;
+pub mod status;
+pub mod synthetic;
+pub mod tags;
+
+pub use alert_log::{AlertSummary, DeliveryRecord, StoredAlert};
+pub use allowlist::StoredAllowlistEntry;
+pub use backup::{create_backup, read_backup, read_manifest, restore_backup, BackupLocation, BackupManifest, BACKUP_KEY_ENV};
+pub use canaries::CanaryTrip;
+pub use checkpoints::ScanCheckpoint;
+pub use compression::{ColumnCompression, CompressionPolicy};
+pub use dataset::{DatasetExample, DatasetLabel, DatasetOptions, DatasetReport, DatasetSplit, DATASET_COLUMNS};
+pub use disk_forecast::spawn_disk_forecast_job;
+pub use encryption::{DB_KEY_COMMAND_ENV, DB_KEY_ENV, DB_KEY_FILE_ENV};
+pub use event_queue::{DeadLetter, QueueDepth, QueuedEvent};
+pub use export::ExportFormat;
+pub use fingerprint::{
+    default_pepper_path, FingerprintScheme, Fingerprinter, FINGERPRINT_HASH_ENV, FINGERPRINT_PEPPER_ENV,
+    FINGERPRINT_PEPPER_FILE_ENV,
+};
+pub use heartbeats::ComponentHeartbeat;
+pub use history::{parse_as_of, FindingSnapshot, FindingVersion, SnapshotCount};
+pub use legal_hold::{ErasureReport, LegalHold, NOT_ON_LEGAL_HOLD};
+pub use maintenance::{spawn_maintenance_job, MaintenanceReport};
+pub use merge::{MergeCounts, MergeReport};
+pub use migrations::{FINDINGS_MIGRATIONS, FINDINGS_STORE};
+pub use notes::{guess_content_type, AttachmentStore, FindingAnnotations, FindingAttachment, FindingNote, MAX_ATTACHMENT_BYTES};
+pub use organizations::{normalize_organization, MonitoredOrganization, OrganizationHealth, OrganizationSettings};
+pub use pool::{SecretDatabasePool, DEFAULT_POOL_SIZE};
+pub use query_cache::{CacheScope, QueryCache};
+pub use quotas::{QuotaBudget, QuotaReport, QuotaResource, WorkspaceQuota};
+pub use repositories::{
+    risk_score, FacetCount, RepositoryExposure, RepositoryFacets, RepositoryFilters, RepositoryMetadata, RepositoryRecord,
+    UNAVAILABLE,
+};
+pub use retention::{run_purge, spawn_purge_job, PurgeReport};
+pub use saved_searches::{deliver_search_alerts, SavedSearch, SavedSearchAlert};
+pub use scan_requests::{NewScanRequest, ScanRequest};
+pub use scorecard::{
+    render_scorecards_html, OrganizationScorecard, RevocationTimes, ScorecardPeriod, ScorecardRepository, ScorecardTeam,
+    ScorecardTotals, TEAM_TAG_PREFIX,
+};
+pub use similarity::{trigram_similarity, SimilarFinding};
+pub use stats::{Mttr, StatCount, StatsSummary, TrendPoint, ValidationRatio, ROLLUP_WINDOW_DAYS};
+pub use status::FindingStatus;
+pub use synthetic::NOT_SYNTHETIC;
+pub use tags::{normalize_tag, TagCount};
 
 /// High-performance secret processing engine with parallel processing
 pub struct PerformanceEngine {
     cache: Arc<Mutex<LruCache<String, CacheEntry>>>,
-    db_pool: Arc<RwLock<Vec<Connection>>>,
+    database: Option<SecretDatabasePool>,
     deduplication_store: Arc<RwLock<HashSet<String>>>,
     metrics_collector: MetricsCollector,
 }
@@ -89,17 +173,79 @@ pub struct ProcessingMetrics {
     pub memory_usage_mb: f64,
 }
 
+/// Version of the findings schema, kept in `PRAGMA user_version` and recorded in backups.
+/// Bump it together with a new entry in `FINDINGS_MIGRATIONS`.
+pub const SCHEMA_VERSION: i32 = 9;
+
+/// How long a connection waits for another writer before failing with `database is locked`
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Prepared statements each connection keeps for reuse
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
 /// Database schema for efficient secret storage
 pub struct SecretDatabase {
     connection: Connection,
+    fingerprinter: std::cell::OnceCell<Fingerprinter>,
 }
 
 impl SecretDatabase {
     /// Create new database with optimized schema
     pub fn new(db_path: &str) -> Result<Self> {
-        let connection = Connection::open(db_path)?;
-        let db = Self { connection };
+        let mut connection = Connection::open(db_path)?;
+        configure_connection(&mut connection, db_path, false)?;
+        let db = Self { connection, fingerprinter: Default::default() };
+        if db_path == ":memory:" {
+            // Nothing outlives an in-memory database, so its pepper need not either
+            let _ = db.fingerprinter.set(Fingerprinter::ephemeral()?);
+        }
+        let stored = db.check_schema_version()?;
         db.initialize_schema()?;
+        db.apply_migrations(stored)?;
+        Ok(db)
+    }
+
+    /// Open an existing database without creating, migrating or writing anything, e.g. a
+    /// replica served to analysts
+    pub fn open_read_only(db_path: &str) -> Result<Self> {
+        let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        let mut connection = Connection::open_with_flags(db_path, flags)?;
+        configure_connection(&mut connection, db_path, true)?;
+        let db = Self { connection, fingerprinter: Default::default() };
+        let stored = db.check_schema_version()?;
+        if stored < SCHEMA_VERSION {
+            return Err(anyhow!(
+                "The findings database is at schema version {} and needs migrating to {}; \
+                 open it read-write once before serving it read-only",
+                stored,
+                SCHEMA_VERSION
+            ));
+        }
+        Ok(db)
+    }
+
+    /// `open_read_only` or `new`, for callers that honour a read-only setting
+    pub fn open(db_path: &str, read_only: bool) -> Result<Self> {
+        if read_only {
+            Self::open_read_only(db_path)
+        } else {
+            Self::new(db_path)
+        }
+    }
+
+    /// Open another connection to a database that `new` has already created and migrated,
+    /// as the connection pool does
+    pub fn connect(db_path: &str, read_only: bool) -> Result<Self> {
+        if read_only {
+            return Self::open_read_only(db_path);
+        }
+        let mut connection = Connection::open(db_path)?;
+        configure_connection(&mut connection, db_path, false)?;
+        let db = Self { connection, fingerprinter: Default::default() };
+        let stored = db.check_schema_version()?;
+        if stored < SCHEMA_VERSION {
+            return Err(anyhow!("The findings database is at schema version {} but {} is required", stored, SCHEMA_VERSION));
+        }
         Ok(db)
     }
 
@@ -115,10 +261,7 @@ impl SecretDatabase {
                 actor_login TEXT NOT NULL,
                 created_at DATETIME NOT NULL,
                 payload_hash TEXT NOT NULL,
-                processed BOOLEAN DEFAULT FALSE,
-                INDEX(repository_name, created_at),
-                INDEX(event_type, created_at),
-                INDEX(processed, created_at)
+                processed BOOLEAN DEFAULT FALSE
             )",
             [],
         )?;
@@ -136,10 +279,7 @@ impl SecretDatabase {
                 is_dangling BOOLEAN DEFAULT FALSE,
                 created_at DATETIME NOT NULL,
                 processed_at DATETIME,
-                FOREIGN KEY(event_id) REFERENCES events(id),
-                INDEX(repository_name, created_at),
-                INDEX(is_dangling, created_at),
-                INDEX(commit_sha)
+                FOREIGN KEY(event_id) REFERENCES events(id)
             )",
             [],
         )?;
@@ -163,12 +303,7 @@ impl SecretDatabase {
                 validation_method TEXT,
                 created_at DATETIME NOT NULL,
                 updated_at DATETIME,
-                FOREIGN KEY(commit_id) REFERENCES commits(id),
-                INDEX(detector_name, severity),
-                INDEX(repository_name, created_at),
-                INDEX(verified, validation_status),
-                INDEX(secret_hash),
-                INDEX(matched_text_hash)
+                FOREIGN KEY(commit_id) REFERENCES commits(id)
             )",
             [],
         )?;
@@ -186,10 +321,7 @@ impl SecretDatabase {
                 risk_factors TEXT,      -- JSON array
                 confidence REAL NOT NULL,
                 created_at DATETIME NOT NULL,
-                FOREIGN KEY(secret_id) REFERENCES secrets(id),
-                INDEX(impact_score DESC),
-                INDEX(bounty_potential DESC),
-                INDEX(revocation_priority, created_at)
+                FOREIGN KEY(secret_id) REFERENCES secrets(id)
             )",
             [],
         )?;
@@ -206,14 +338,42 @@ impl SecretDatabase {
                 last_activity DATETIME,
                 risk_score REAL,
                 created_at DATETIME NOT NULL,
-                updated_at DATETIME,
-                INDEX(organization, name),
-                INDEX(risk_score DESC),
-                INDEX(last_activity DESC)
+                updated_at DATETIME
+            )",
+            [],
+        )?;
+
+        // Commits already checked against the API, so replayed events skip the lookup
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS checked_commits (
+                repository_name TEXT NOT NULL,
+                commit_sha TEXT NOT NULL,
+                status TEXT NOT NULL,
+                checked_at DATETIME NOT NULL,
+                PRIMARY KEY(repository_name, commit_sha)
             )",
             [],
         )?;
 
+        self.initialize_legal_hold_schema()?;
+        self.initialize_notes_schema()?;
+        self.initialize_tags_schema()?;
+        self.initialize_saved_searches_schema()?;
+        self.initialize_stats_schema()?;
+        self.initialize_organizations_schema()?;
+        self.initialize_synthetic_schema()?;
+        self.initialize_checkpoints_schema()?;
+        self.initialize_heartbeats_schema()?;
+        self.initialize_allowlist_schema()?;
+        self.initialize_repositories_schema()?;
+        self.initialize_alert_log_schema()?;
+        self.initialize_slow_queries_schema()?;
+        self.initialize_event_queue_schema()?;
+        self.initialize_maintenance_schema()?;
+        self.initialize_quota_schema()?;
+        self.initialize_canary_schema()?;
+        self.initialize_scan_requests_schema()?;
+
         // Performance optimization: Create materialized views
         self.connection.execute(
             "CREATE VIEW IF NOT EXISTS high_priority_secrets AS
@@ -237,21 +397,31 @@ impl SecretDatabase {
         Ok(())
     }
 
-    /// Bulk insert secrets with optimized performance
+    /// Bulk insert secrets with optimized performance.
+    /// Findings not seen before are matched against alerting saved searches.
     pub fn bulk_insert_secrets(&self, secrets: &[SecretMatch]) -> Result<()> {
-        let tx = self.connection.unchecked_transaction()?;
+        let fingerprinter = self.fingerprinter()?;
+        // Take the write lock up front: a deferred transaction that reads first cannot wait
+        // for a concurrent writer and fails with `database is locked` instead
+        let tx = Transaction::new_unchecked(&self.connection, TransactionBehavior::Immediate)?;
+        let mut new_hashes = Vec::new();
         
         {
-            let mut stmt = tx.prepare(
+            let mut exists = tx.prepare_cached("SELECT EXISTS(SELECT 1 FROM secrets WHERE secret_hash = ?)")?;
+            let mut stmt = tx.prepare_cached(
                 "INSERT OR REPLACE INTO secrets 
                 (secret_hash, detector_name, matched_text_hash, filename, line_number, 
-                 entropy, severity, category, context_hash, verified, created_at)
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, datetime('now'))"
+                 entropy, severity, category, context_hash, verified, suppressed, suppressed_by, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, datetime('now'))"
             )?;
 
             for secret in secrets {
-                let matched_text_hash = format!("{:x}", md5::compute(&secret.matched_text));
-                let context_hash = format!("{:x}", md5::compute(&secret.context));
+                if !exists.query_row(params![secret.hash], |row| row.get::<_, bool>(0))? {
+                    new_hashes.push(secret.hash.clone());
+                }
+
+                let matched_text_hash = fingerprinter.fingerprint(&secret.matched_text);
+                let context_hash = fingerprinter.fingerprint(&secret.context);
 
                 stmt.execute(params![
                     secret.hash,
@@ -264,86 +434,286 @@ impl SecretDatabase {
                     format!("{:?}", secret.category),
                     context_hash,
                     secret.verified,
+                    secret.suppressed,
+                    secret.suppressed_by,
                 ])?;
             }
         }
 
         tx.commit()?;
         info!("Bulk inserted {} secrets", secrets.len());
+
+        // A broken saved search or rollup must not lose findings that are already stored.
+        // Suppressed findings never trigger alerts.
+        new_hashes.retain(|hash| secrets.iter().any(|s| &s.hash == hash && !s.suppressed));
+        if let Err(e) = self.evaluate_saved_searches(&new_hashes) {
+            error!("Failed to evaluate saved searches: {}", e);
+        }
+        if let Err(e) = self.refresh_rollups(0) {
+            error!("Failed to update finding rollups: {}", e);
+        }
         Ok(())
     }
 
     /// Query secrets with advanced filtering
     pub fn query_secrets(&self, filters: &SecretQueryFilters) -> Result<Vec<SecretRecord>> {
-        let mut query = "SELECT * FROM secrets WHERE 1=1".to_string();
-        let mut params = Vec::new();
-
-        if let Some(severity) = &filters.min_severity {
-            query.push_str(" AND severity IN ");
-            match severity {
-                SecretSeverity::Critical => query.push_str("('Critical')"),
-                SecretSeverity::High => query.push_str("('Critical', 'High')"),
-                SecretSeverity::Medium => query.push_str("('Critical', 'High', 'Medium')"),
-                SecretSeverity::Low => query.push_str("('Critical', 'High', 'Medium', 'Low')"),
-            }
-        }
+        let mut results = Vec::new();
+        self.for_each_secret(filters, |record| {
+            results.push(record);
+            Ok(())
+        })?;
+        Ok(results)
+    }
 
-        if let Some(detector) = &filters.detector_name {
-            query.push_str(" AND detector_name = ?");
-            params.push(detector.clone());
+    /// `query_secrets`, handing each finding to `f` as it is read instead of collecting
+    /// them, so exports of millions of findings run in constant memory. Returns the count.
+    pub fn for_each_secret(&self, filters: &SecretQueryFilters, mut f: impl FnMut(SecretRecord) -> Result<()>) -> Result<usize> {
+        let (source, mut params) = history::findings_source(filters.as_of);
+        let (conditions, condition_params) = filter_conditions(filters)?;
+        params.extend(condition_params);
+        let mut query = format!("SELECT {} FROM {} WHERE 1=1{}", SECRET_RECORD_COLUMNS, source, conditions);
+
+        query.push_str(" ORDER BY created_at DESC, id DESC");
+
+        if let Some(limit) = filters.limit {
+            query.push_str(" LIMIT ?");
+            params.push(Value::Integer(limit.into()));
         }
 
-        if filters.verified_only {
-            query.push_str(" AND verified = TRUE");
+        let mut stmt = self.connection.prepare_cached(&query)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), SecretRecord::from_row)?;
+
+        let mut count = 0;
+        for row in rows {
+            f(row?)?;
+            count += 1;
         }
 
-        if let Some(days) = filters.last_n_days {
-            query.push_str(" AND created_at >= datetime('now', '-? days')");
-            params.push(days.to_string());
+        Ok(count)
+    }
+
+    /// Findings matching `filters`, ignoring its limit and position, counted up to `cap`
+    pub fn count_secrets(&self, filters: &SecretQueryFilters, cap: u32) -> Result<i64> {
+        let filters = SecretQueryFilters { after: None, ..filters.clone() };
+        let (source, mut params) = history::findings_source(filters.as_of);
+        let (conditions, condition_params) = filter_conditions(&filters)?;
+        params.extend(condition_params);
+        params.push(Value::Integer(cap.into()));
+        let query = format!("SELECT COUNT(*) FROM (SELECT 1 FROM {} WHERE 1=1{} LIMIT ?)", source, conditions);
+        Ok(self.connection.query_row(&query, rusqlite::params_from_iter(params.iter()), |row| row.get(0))?)
+    }
+}
+
+/// Columns read into a `SecretRecord`, in `SecretRecord::from_row` order
+const SECRET_RECORD_COLUMNS: &str =
+    "id, secret_hash, detector_name, filename, line_number, entropy, severity, category, verified, created_at, suppressed";
+
+/// Severities from most to least severe; a minimum severity keeps a prefix of this list
+const SEVERITY_ORDER: [SecretSeverity; 4] =
+    [SecretSeverity::Critical, SecretSeverity::High, SecretSeverity::Medium, SecretSeverity::Low];
+
+/// `AND ...` conditions on `secrets` for a filter set, with their bound parameters (limit excluded).
+/// Every value is bound, never spliced into the SQL.
+fn filter_conditions(filters: &SecretQueryFilters) -> Result<(String, Vec<Value>)> {
+    let mut query = String::new();
+    let mut params = Vec::new();
+
+    if let Some(severity) = &filters.min_severity {
+        let included = match severity {
+            SecretSeverity::Critical => 1,
+            SecretSeverity::High => 2,
+            SecretSeverity::Medium => 3,
+            SecretSeverity::Low => 4,
+        };
+        query.push_str(&format!(" AND severity IN ({})", vec!["?"; included].join(", ")));
+        params.extend(SEVERITY_ORDER[..included].iter().map(|s| Value::Text(format!("{:?}", s))));
+    }
+
+    if let Some(category) = &filters.category {
+        query.push_str(" AND category = ?");
+        params.push(Value::Text(format!("{:?}", category)));
+    }
+
+    if let Some(detector) = &filters.detector_name {
+        query.push_str(" AND detector_name = ?");
+        params.push(Value::Text(detector.clone()));
+    }
+
+    // SQLite GLOB: `*`, `?` and `[...]`, case-sensitive like paths
+    if let Some(pattern) = &filters.filename {
+        query.push_str(" AND filename GLOB ?");
+        params.push(Value::Text(pattern.clone()));
+    }
+
+    if filters.verified_only {
+        query.push_str(" AND verified = TRUE");
+    }
+
+    if !filters.include_suppressed {
+        query.push_str(" AND NOT suppressed");
+    }
+
+    if let Some(days) = filters.last_n_days {
+        query.push_str(" AND created_at >= datetime('now', ?)");
+        params.push(Value::Text(format!("-{} days", days)));
+    }
+
+    // Both ends are whole days, inclusive
+    if let Some(since) = filters.since {
+        query.push_str(" AND created_at >= ?");
+        params.push(Value::Text(since.format("%Y-%m-%d 00:00:00").to_string()));
+    }
+    if let Some(until) = filters.until {
+        let next_day = until.succ_opt().ok_or_else(|| anyhow!("Invalid until date {}", until))?;
+        query.push_str(" AND created_at < ?");
+        params.push(Value::Text(next_day.format("%Y-%m-%d 00:00:00").to_string()));
+    }
+
+    if let Some((created_at, id)) = &filters.after {
+        query.push_str(" AND (created_at < ? OR (created_at = ? AND id < ?))");
+        params.push(Value::Text(created_at.clone()));
+        params.push(Value::Text(created_at.clone()));
+        params.push(Value::Integer(*id));
+    }
+
+    // An owner matches all of its repositories, `owner/name` just that one
+    if let Some(repository) = &filters.repository {
+        let repository = repository.to_lowercase();
+        query.push_str(" AND commit_id IN (SELECT id FROM commits WHERE ");
+        // Commits keep GitHub's casing; LIKE already ignores ASCII case
+        if repository.contains('/') {
+            query.push_str("LOWER(repository_name) = ?)");
+            params.push(Value::Text(repository));
+        } else {
+            query.push_str("repository_name LIKE ? ESCAPE '\\')");
+            params.push(Value::Text(format!("{}/%", repository.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"))));
         }
+    }
 
-        query.push_str(" ORDER BY created_at DESC");
+    // Every requested tag must match, on the finding itself or its repository
+    for tag in &filters.tags {
+        let tag = normalize_tag(tag)?;
+        query.push_str(" AND ");
+        query.push_str(tags::TAGGED);
+        params.push(Value::Text(tag.clone()));
+        params.push(Value::Text(tag));
+    }
 
-        if let Some(limit) = filters.limit {
-            query.push_str(" LIMIT ?");
-            params.push(limit.to_string());
+    Ok((query, params))
+}
+
+impl SecretDatabase {
+    /// Findings of a severity nobody has triaged yet (no validation status); synthetic and suppressed ones excluded
+    pub fn unreviewed_count(&self, severity: &SecretSeverity) -> Result<i64> {
+        let count = self.connection.query_row(
+            &format!(
+                "SELECT COUNT(*) FROM secrets WHERE severity = ? AND validation_status IS NULL AND NOT suppressed AND {}",
+                NOT_SYNTHETIC
+            ),
+            params![format!("{:?}", severity)],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Record the outcome of a commit existence check
+    pub fn record_commit_check(
+        &self,
+        repository: &str,
+        commit_sha: &str,
+        status: CommitCheckStatus,
+    ) -> Result<()> {
+        self.connection.execute(
+            "INSERT OR REPLACE INTO checked_commits (repository_name, commit_sha, status, checked_at)
+            VALUES (?, ?, ?, datetime('now'))",
+            params![repository.to_lowercase(), commit_sha.to_lowercase(), status.as_str()],
+        )?;
+        Ok(())
+    }
+
+    /// Look up a previous commit check
+    pub fn get_commit_check(&self, repository: &str, commit_sha: &str) -> Result<Option<CommitCheckStatus>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT status FROM checked_commits WHERE repository_name = ? AND commit_sha = ?"
+        )?;
+        let mut rows = stmt.query(params![repository.to_lowercase(), commit_sha.to_lowercase()])?;
+
+        match rows.next()? {
+            Some(row) => {
+                let status: String = row.get(0)?;
+                Ok(Some(CommitCheckStatus::parse(&status)?))
+            }
+            None => Ok(None),
         }
+    }
 
-        let mut stmt = self.connection.prepare(&query)?;
-        let rows = stmt.query_map(params.as_slice(), |row| {
-            Ok(SecretRecord {
-                id: row.get(0)?,
-                secret_hash: row.get(1)?,
-                detector_name: row.get(2)?,
-                filename: row.get(3)?,
-                line_number: row.get(4)?,
-                entropy: row.get(5)?,
-                severity: row.get(6)?,
-                category: row.get(7)?,
-                verified: row.get(8)?,
-                created_at: row.get(9)?,
-            })
-        })?;
+    /// All checked `(repository, commit_sha)` pairs, used to seed the bloom filter
+    pub fn load_checked_commit_keys(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT repository_name, commit_sha FROM checked_commits"
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
 
-        let mut results = Vec::new();
+        let mut keys = Vec::new();
         for row in rows {
-            results.push(row?);
+            keys.push(row?);
         }
+        Ok(keys)
+    }
+}
 
-        Ok(results)
+/// Busy timeout and statement cache for every connection. Writable file databases switch
+/// to WAL, so readers no longer block the writer or each other, and new ones are created
+/// with incremental auto-vacuum for the maintenance job. Writable connections also time
+/// their statements for the slow query log.
+fn configure_connection(connection: &mut Connection, db_path: &str, read_only: bool) -> Result<()> {
+    encryption::apply_key(connection, db_path)?;
+    connection.busy_timeout(BUSY_TIMEOUT)?;
+    connection.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+    if !read_only && db_path != ":memory:" {
+        // Only takes effect before the first table is created, or at the next VACUUM
+        connection.pragma_update(None, "auto_vacuum", "INCREMENTAL")?;
+        connection.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+        connection.pragma_update(None, "synchronous", "NORMAL")?;
     }
+    if !read_only && crate::core::slow_query_threshold().is_some() {
+        connection.profile(Some(slow_queries::profile_statement));
+    }
+    Ok(())
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
 pub struct SecretQueryFilters {
     pub min_severity: Option<SecretSeverity>,
+    pub category: Option<SecretCategory>,
     pub detector_name: Option<String>,
+    /// Glob over the finding's file path, e.g. `*.env` or `config/*`
+    pub filename: Option<String>,
     pub verified_only: bool,
     pub last_n_days: Option<u32>,
+    /// First day of findings to return, inclusive
+    pub since: Option<NaiveDate>,
+    /// Last day of findings to return, inclusive
+    pub until: Option<NaiveDate>,
     pub limit: Option<u32>,
+    /// Findings must carry all of these tags, directly or through their repository
+    pub tags: Vec<String>,
+    /// `owner` or `owner/name`
+    pub repository: Option<String>,
+    /// Also return findings suppressed by the allowlist
+    pub include_suppressed: bool,
+    /// Keyset position: only findings after this `(created_at, id)` in newest-first order.
+    /// Never stored with a saved search.
+    #[serde(skip)]
+    pub after: Option<(String, i64)>,
+    /// Findings as they stood at this time, from their recorded history. Never stored with
+    /// a saved search.
+    #[serde(skip)]
+    pub as_of: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SecretRecord {
     pub id: i64,
     pub secret_hash: String,
@@ -351,10 +721,39 @@ pub struct SecretRecord {
     pub filename: Option<String>,
     pub line_number: Option<u32>,
     pub entropy: f64,
-    pub severity: String,
-    pub category: String,
+    pub severity: SecretSeverity,
+    pub category: SecretCategory,
     pub verified: bool,
     pub created_at: String,
+    pub suppressed: bool,
+}
+
+impl SecretRecord {
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            secret_hash: row.get(1)?,
+            detector_name: row.get(2)?,
+            filename: row.get(3)?,
+            line_number: row.get(4)?,
+            entropy: row.get(5)?,
+            severity: parse_column(row, 6)?,
+            category: parse_column(row, 7)?,
+            verified: row.get(8)?,
+            created_at: row.get(9)?,
+            suppressed: row.get(10)?,
+        })
+    }
+}
+
+/// A text column holding an enum stored as its variant name
+fn parse_column<T>(row: &rusqlite::Row<'_>, index: usize) -> rusqlite::Result<T>
+where
+    T: std::str::FromStr<Err = anyhow::Error>,
+{
+    row.get::<_, String>(index)?
+        .parse()
+        .map_err(|e: anyhow::Error| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, e.into()))
 }
 
 impl PerformanceEngine {
@@ -362,12 +761,18 @@ impl PerformanceEngine {
     pub fn new() -> Self {
         Self {
             cache: Arc::new(Mutex::new(LruCache::new(NonZeroUsize::new(10000).unwrap()))),
-            db_pool: Arc::new(RwLock::new(Vec::new())),
+            database: None,
             deduplication_store: Arc::new(RwLock::new(HashSet::new())),
             metrics_collector: MetricsCollector::new(),
         }
     }
 
+    /// Store processed findings through `pool`, one concurrent write per worker chunk
+    pub fn with_database(mut self, pool: SecretDatabasePool) -> Self {
+        self.database = Some(pool);
+        self
+    }
+
     /// Process secrets in parallel batches
     pub async fn process_secrets_parallel(&self, request: BatchProcessingRequest) -> Result<BatchProcessingResult> {
         let start_time = std::time::Instant::now();
@@ -409,6 +814,14 @@ impl PerformanceEngine {
             .flatten()
             .collect();
 
+        if let Some(database) = &self.database {
+            let writes = processed_secrets.chunks(chunk_size).map(|chunk| {
+                let batch: Vec<SecretMatch> = chunk.iter().map(|processed| processed.secret.clone()).collect();
+                database.interact(move |db| db.bulk_insert_secrets(&batch))
+            });
+            futures::future::try_join_all(writes).await?;
+        }
+
         let processing_time = start_time.elapsed().as_millis() as u64;
 
         // Update metrics
@@ -453,8 +866,10 @@ impl PerformanceEngine {
                 let mut cache_hits = self.metrics_collector.cache_hits.lock().unwrap();
                 *cache_hits += 1;
                 
-                // Deserialize cached result
-                serde_json::from_str(&cached.data)?
+                // Cached results hold only the secret's hash; the stored fingerprints need the value
+                let mut processed: ProcessedSecret = serde_json::from_str(&cached.data)?;
+                processed.secret = secret;
+                processed
             } else {
                 // Cache miss - process secret
                 let mut cache_misses = self.metrics_collector.cache_misses.lock().unwrap();
@@ -493,7 +908,11 @@ impl PerformanceEngine {
 
                 // Cache the result
                 if options.cache_results {
-                    let serialized = serde_json::to_string(&processed)?;
+                    let cached = ProcessedSecret {
+                        secret: RedactionProfile::HashOnly.redact_match(&processed.secret),
+                        ..processed.clone()
+                    };
+                    let serialized = serde_json::to_string(&cached)?;
                     self.cache_result(&cache_key, serialized);
                 }
 
@@ -588,7 +1007,9 @@ impl PerformanceEngine {
     pub async fn optimize_database(&self, db_path: &str) -> Result<()> {
         let db = SecretDatabase::new(db_path)?;
         
-        // Run VACUUM to reclaim space
+        // Run VACUUM to reclaim space; it also switches on incremental auto-vacuum for the
+        // scheduled maintenance of databases created before it was the default
+        db.connection.pragma_update(None, "auto_vacuum", "INCREMENTAL")?;
         db.connection.execute("VACUUM", [])?;
         
         // Analyze tables for query optimization
@@ -666,6 +1087,10 @@ mod tests {
             end_position: 10,
             line_number: Some(1),
             filename: Some("test.env".to_string()),
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
             entropy: 4.5,
             severity: SecretSeverity::High,
             category: SecretCategory::ApiKey,
@@ -730,6 +1155,52 @@ mod tests {
         // Database should be created successfully
     }
 
+    #[test]
+    fn test_read_only_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("findings.db");
+        let path = path.to_str().unwrap();
+        assert!(SecretDatabase::open_read_only(path).is_err());
+
+        SecretDatabase::new(path).unwrap().record_heartbeat("hunter", &serde_json::json!({})).unwrap();
+        let db = SecretDatabase::open_read_only(path).unwrap();
+        assert_eq!(db.heartbeats().unwrap().len(), 1);
+        assert!(db.record_heartbeat("monitor", &serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn test_synthetic_findings_excluded_from_counts() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        db.bulk_insert_secrets(&[create_test_secret("real"), create_test_secret("synthetic")]).unwrap();
+        db.mark_synthetic("hash_synthetic").unwrap();
+
+        assert!(db.is_synthetic("hash_synthetic").unwrap());
+        assert!(!db.is_synthetic("hash_real").unwrap());
+        assert_eq!(db.unreviewed_count(&SecretSeverity::High).unwrap(), 1);
+        // Freshly injected findings are kept
+        assert_eq!(db.purge_synthetic(7).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_keyset_pages_cover_every_finding_once() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let secrets: Vec<_> = (0..5).map(|i| create_test_secret(&i.to_string())).collect();
+        db.bulk_insert_secrets(&secrets).unwrap();
+
+        // Rows inserted within the same second share created_at; the id breaks the tie
+        let mut filters = SecretQueryFilters { limit: Some(2), ..Default::default() };
+        let mut seen = Vec::new();
+        loop {
+            let page = db.query_secrets(&filters).unwrap();
+            let Some(last) = page.last() else { break };
+            filters.after = Some((last.created_at.clone(), last.id));
+            seen.extend(page.iter().map(|record| record.id));
+        }
+        assert_eq!(seen, vec![5, 4, 3, 2, 1]);
+        assert_eq!(db.count_secrets(&filters, 100).unwrap(), 5);
+        assert_eq!(db.count_secrets(&filters, 3).unwrap(), 3);
+    }
+
     #[tokio::test]
     async fn test_metrics_collection() {
         let engine = PerformanceEngine::new();
@@ -761,4 +1232,37 @@ mod tests {
         assert!(!report.recommendations.is_empty());
         assert_eq!(report.metrics.total_processed, 0);
     }
+
+    #[test]
+    fn test_query_filters_are_bound() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let mut aws = create_test_secret("aws");
+        aws.severity = SecretSeverity::Critical;
+        aws.category = SecretCategory::CloudProvider;
+        aws.filename = Some("config/prod.env".to_string());
+        let mut quoted = create_test_secret("quoted");
+        quoted.severity = SecretSeverity::Low;
+        quoted.filename = Some("src/it's.rs".to_string());
+        db.bulk_insert_secrets(&[aws, quoted, create_test_secret("plain")]).unwrap();
+        db.connection
+            .execute("UPDATE secrets SET created_at = '2024-03-10 23:59:59' WHERE filename = 'config/prod.env'", [])
+            .unwrap();
+        db.connection.execute("UPDATE secrets SET created_at = '2024-03-11 00:00:00' WHERE filename = 'test.env'", []).unwrap();
+
+        let query = |filters: SecretQueryFilters| db.query_secrets(&filters).unwrap();
+        let cloud = query(SecretQueryFilters { category: Some(SecretCategory::CloudProvider), ..Default::default() });
+        assert_eq!(cloud.len(), 1);
+        assert!(matches!(cloud[0].severity, SecretSeverity::Critical));
+        assert!(matches!(cloud[0].category, SecretCategory::CloudProvider));
+
+        assert_eq!(query(SecretQueryFilters { filename: Some("*.env".to_string()), ..Default::default() }).len(), 2);
+        assert_eq!(query(SecretQueryFilters { filename: Some("src/it's.rs".to_string()), ..Default::default() }).len(), 1);
+        assert_eq!(query(SecretQueryFilters { min_severity: Some(SecretSeverity::High), ..Default::default() }).len(), 2);
+
+        let march_10 = NaiveDate::from_ymd_opt(2024, 3, 10);
+        let on_the_10th = query(SecretQueryFilters { since: march_10, until: march_10, ..Default::default() });
+        assert_eq!(on_the_10th.len(), 1);
+        assert_eq!(on_the_10th[0].filename.as_deref(), Some("config/prod.env"));
+        assert_eq!(query(SecretQueryFilters { since: NaiveDate::from_ymd_opt(2024, 3, 11), ..Default::default() }).len(), 2);
+    }
 }
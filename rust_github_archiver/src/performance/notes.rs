@@ -0,0 +1,518 @@
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+use utoipa::ToSchema;
+
+use super::SecretDatabase;
+
+/// Largest evidence file accepted; keeps the blob store and API uploads bounded
+pub const MAX_ATTACHMENT_BYTES: usize = 25 * 1024 * 1024;
+
+/// Markdown note written by an analyst on a finding
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FindingNote {
+    pub id: i64,
+    pub secret_hash: String,
+    pub author: String,
+    pub body: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
+
+/// Evidence file metadata; the contents live in the [`AttachmentStore`] under `sha256`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FindingAttachment {
+    pub id: i64,
+    pub secret_hash: String,
+    pub sha256: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub uploaded_by: String,
+    pub uploaded_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct FindingAnnotations {
+    pub notes: Vec<FindingNote>,
+    pub attachments: Vec<FindingAttachment>,
+    /// Jira ticket or GitHub issue opened for the finding
+    #[serde(default)]
+    pub ticket_url: Option<String>,
+}
+
+impl FindingAnnotations {
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty() && self.attachments.is_empty()
+    }
+}
+
+/// Content-addressed evidence files, stored as `<root>/<first two hex chars>/<sha256>`.
+/// The same file attached to several findings is kept once.
+#[derive(Debug, Clone)]
+pub struct AttachmentStore {
+    root: PathBuf,
+}
+
+impl AttachmentStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The store kept next to a findings database: `secrets.db` -> `secrets.db.attachments/`
+    pub fn beside_database(db_path: &str) -> Self {
+        Self::new(format!("{}.attachments", db_path))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Blob path for a digest. Only well-formed digests are accepted, so a digest taken
+    /// from a request can never point outside the store.
+    pub fn path_for(&self, sha256: &str) -> Result<PathBuf> {
+        if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit() && !b.is_ascii_uppercase()) {
+            return Err(anyhow!("Invalid attachment digest: {}", sha256));
+        }
+        Ok(self.root.join(&sha256[..2]).join(sha256))
+    }
+
+    /// Store contents and return their SHA-256 digest
+    pub fn put(&self, contents: &[u8]) -> Result<String> {
+        let sha256 = hex::encode(Sha256::digest(contents));
+        let path = self.path_for(&sha256)?;
+        if path.exists() {
+            debug!("Attachment {} already stored", sha256);
+            return Ok(sha256);
+        }
+
+        let dir = path.parent().expect("blob paths always have a parent");
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create attachment directory {}", dir.display()))?;
+
+        // Write then rename so a crash never leaves a truncated blob under a valid digest
+        let staged = path.with_extension("partial");
+        std::fs::write(&staged, contents)?;
+        std::fs::rename(&staged, &path)?;
+        Ok(sha256)
+    }
+
+    /// Store the contents of `source` without reading it into memory, as restores do
+    pub fn put_file(&self, source: &Path) -> Result<String> {
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut std::fs::File::open(source)?, &mut hasher)?;
+        let sha256 = hex::encode(hasher.finalize());
+        let path = self.path_for(&sha256)?;
+        if path.exists() {
+            return Ok(sha256);
+        }
+
+        let dir = path.parent().expect("blob paths always have a parent");
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create attachment directory {}", dir.display()))?;
+        let staged = path.with_extension("partial");
+        std::fs::copy(source, &staged)?;
+        std::fs::rename(&staged, &path)?;
+        Ok(sha256)
+    }
+
+    pub fn get(&self, sha256: &str) -> Result<Vec<u8>> {
+        let path = self.path_for(sha256)?;
+        std::fs::read(&path).with_context(|| format!("Attachment {} is missing from the store", sha256))
+    }
+
+    /// Delete blobs that no attachment references any more; returns how many were removed
+    pub fn prune(&self, referenced: &HashSet<String>) -> Result<usize> {
+        if !self.root.exists() {
+            return Ok(0);
+        }
+
+        let mut removed = 0;
+        for shard in std::fs::read_dir(&self.root)? {
+            let shard = shard?.path();
+            if !shard.is_dir() {
+                continue;
+            }
+            for blob in std::fs::read_dir(&shard)? {
+                let blob = blob?.path();
+                let name = blob.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+                if !referenced.contains(name) {
+                    std::fs::remove_file(&blob)?;
+                    removed += 1;
+                }
+            }
+        }
+
+        if removed > 0 {
+            info!("Pruned {} unreferenced attachments from {}", removed, self.root.display());
+        }
+        Ok(removed)
+    }
+}
+
+/// Best-effort content type from the file extension, for uploads that do not declare one
+pub fn guess_content_type(filename: &str) -> &'static str {
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "pdf" => "application/pdf",
+        "json" => "application/json",
+        "md" => "text/markdown",
+        "txt" | "log" => "text/plain",
+        "html" | "htm" => "text/html",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Keep only the final path component of an uploaded file name
+fn sanitize_filename(filename: &str) -> String {
+    let name = Path::new(filename.trim())
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    if name.is_empty() {
+        "attachment".to_string()
+    } else {
+        // Quotes would break the Content-Disposition header on download
+        name.replace('"', "_")
+    }
+}
+
+fn note_from_row(row: &Row) -> rusqlite::Result<FindingNote> {
+    Ok(FindingNote {
+        id: row.get(0)?,
+        secret_hash: row.get(1)?,
+        author: row.get(2)?,
+        body: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+fn attachment_from_row(row: &Row) -> rusqlite::Result<FindingAttachment> {
+    Ok(FindingAttachment {
+        id: row.get(0)?,
+        secret_hash: row.get(1)?,
+        sha256: row.get(2)?,
+        filename: row.get(3)?,
+        content_type: row.get(4)?,
+        size_bytes: row.get(5)?,
+        uploaded_by: row.get(6)?,
+        uploaded_at: row.get(7)?,
+    })
+}
+
+const NOTE_COLUMNS: &str = "id, secret_hash, author, body, created_at, updated_at";
+const ATTACHMENT_COLUMNS: &str =
+    "id, secret_hash, sha256, filename, content_type, size_bytes, uploaded_by, uploaded_at";
+
+impl SecretDatabase {
+    pub(super) fn initialize_notes_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS finding_notes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                secret_hash TEXT NOT NULL,
+                author TEXT NOT NULL,
+                body TEXT NOT NULL,
+                created_at DATETIME NOT NULL,
+                updated_at DATETIME
+            )",
+            [],
+        )?;
+        self.connection.execute(
+            "CREATE INDEX IF NOT EXISTS idx_finding_notes_hash ON finding_notes(secret_hash)",
+            [],
+        )?;
+
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS finding_attachments (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                secret_hash TEXT NOT NULL,
+                sha256 TEXT NOT NULL,
+                filename TEXT NOT NULL,
+                content_type TEXT NOT NULL,
+                size_bytes INTEGER NOT NULL,
+                uploaded_by TEXT NOT NULL,
+                uploaded_at DATETIME NOT NULL,
+                UNIQUE(secret_hash, sha256)
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub(super) fn require_finding(&self, secret_hash: &str) -> Result<()> {
+        let exists: bool = self.connection.query_row(
+            "SELECT EXISTS(SELECT 1 FROM secrets WHERE secret_hash = ?)",
+            params![secret_hash],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Err(anyhow!("No finding with hash {}", secret_hash));
+        }
+        Ok(())
+    }
+
+    pub fn add_note(&self, secret_hash: &str, author: &str, body: &str) -> Result<FindingNote> {
+        if body.trim().is_empty() {
+            return Err(anyhow!("Note body must not be empty"));
+        }
+        self.require_finding(secret_hash)?;
+
+        self.connection.execute(
+            "INSERT INTO finding_notes (secret_hash, author, body, created_at) VALUES (?, ?, ?, datetime('now'))",
+            params![secret_hash, author, body],
+        )?;
+        let id = self.connection.last_insert_rowid();
+
+        Ok(self.connection.query_row(
+            &format!("SELECT {} FROM finding_notes WHERE id = ?", NOTE_COLUMNS),
+            params![id],
+            note_from_row,
+        )?)
+    }
+
+    /// Replace a note's body; returns false if the note does not belong to the finding
+    pub fn update_note(&self, secret_hash: &str, note_id: i64, body: &str) -> Result<bool> {
+        if body.trim().is_empty() {
+            return Err(anyhow!("Note body must not be empty"));
+        }
+        let updated = self.connection.execute(
+            "UPDATE finding_notes SET body = ?, updated_at = datetime('now') WHERE id = ? AND secret_hash = ?",
+            params![body, note_id, secret_hash],
+        )?;
+        Ok(updated > 0)
+    }
+
+    pub fn delete_note(&self, secret_hash: &str, note_id: i64) -> Result<bool> {
+        let deleted = self.connection.execute(
+            "DELETE FROM finding_notes WHERE id = ? AND secret_hash = ?",
+            params![note_id, secret_hash],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    pub fn list_notes(&self, secret_hash: &str) -> Result<Vec<FindingNote>> {
+        let mut stmt = self.connection.prepare(&format!(
+            "SELECT {} FROM finding_notes WHERE secret_hash = ? ORDER BY created_at, id",
+            NOTE_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![secret_hash], note_from_row)?;
+
+        let mut notes = Vec::new();
+        for row in rows {
+            notes.push(row?);
+        }
+        Ok(notes)
+    }
+
+    /// Store an evidence file and attach it to a finding. Attaching the same file twice
+    /// returns the existing attachment.
+    pub fn add_attachment(
+        &self,
+        store: &AttachmentStore,
+        secret_hash: &str,
+        filename: &str,
+        content_type: &str,
+        contents: &[u8],
+        uploaded_by: &str,
+    ) -> Result<FindingAttachment> {
+        if contents.len() > MAX_ATTACHMENT_BYTES {
+            return Err(anyhow!(
+                "Attachment is {} bytes; the limit is {} bytes",
+                contents.len(),
+                MAX_ATTACHMENT_BYTES
+            ));
+        }
+        self.require_finding(secret_hash)?;
+
+        let sha256 = store.put(contents)?;
+        self.connection.execute(
+            "INSERT OR IGNORE INTO finding_attachments
+            (secret_hash, sha256, filename, content_type, size_bytes, uploaded_by, uploaded_at)
+            VALUES (?, ?, ?, ?, ?, ?, datetime('now'))",
+            params![
+                secret_hash,
+                sha256,
+                sanitize_filename(filename),
+                content_type,
+                contents.len() as i64,
+                uploaded_by
+            ],
+        )?;
+
+        self.get_attachment(secret_hash, &sha256)?
+            .ok_or_else(|| anyhow!("Attachment {} was not recorded", sha256))
+    }
+
+    pub fn get_attachment(&self, secret_hash: &str, sha256: &str) -> Result<Option<FindingAttachment>> {
+        Ok(self.connection
+            .query_row(
+                &format!(
+                    "SELECT {} FROM finding_attachments WHERE secret_hash = ? AND sha256 = ?",
+                    ATTACHMENT_COLUMNS
+                ),
+                params![secret_hash, sha256],
+                attachment_from_row,
+            )
+            .optional()?)
+    }
+
+    pub fn list_attachments(&self, secret_hash: &str) -> Result<Vec<FindingAttachment>> {
+        let mut stmt = self.connection.prepare(&format!(
+            "SELECT {} FROM finding_attachments WHERE secret_hash = ? ORDER BY uploaded_at, id",
+            ATTACHMENT_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![secret_hash], attachment_from_row)?;
+
+        let mut attachments = Vec::new();
+        for row in rows {
+            attachments.push(row?);
+        }
+        Ok(attachments)
+    }
+
+    /// Detach a file from a finding; the blob itself is removed by [`AttachmentStore::prune`]
+    pub fn delete_attachment(&self, secret_hash: &str, sha256: &str) -> Result<bool> {
+        let deleted = self.connection.execute(
+            "DELETE FROM finding_attachments WHERE secret_hash = ? AND sha256 = ?",
+            params![secret_hash, sha256],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// Link to the ticket opened for a finding, if any
+    pub fn ticket_url(&self, secret_hash: &str) -> Result<Option<String>> {
+        let url = self
+            .connection
+            .query_row("SELECT ticket_url FROM secrets WHERE secret_hash = ?", [secret_hash], |row| row.get(0))
+            .optional()?;
+        Ok(url.flatten())
+    }
+
+    /// Remember the ticket opened for a finding, so no second one is opened for it
+    pub fn set_ticket_url(&self, secret_hash: &str, url: &str) -> Result<()> {
+        let changed = self
+            .connection
+            .execute("UPDATE secrets SET ticket_url = ? WHERE secret_hash = ?", params![url, secret_hash])?;
+        if changed == 0 {
+            return Err(anyhow!("No finding {}", secret_hash));
+        }
+        Ok(())
+    }
+
+    pub fn annotations(&self, secret_hash: &str) -> Result<FindingAnnotations> {
+        Ok(FindingAnnotations {
+            notes: self.list_notes(secret_hash)?,
+            attachments: self.list_attachments(secret_hash)?,
+            ticket_url: self.ticket_url(secret_hash)?,
+        })
+    }
+
+    /// Digests still referenced by any finding, used to prune the attachment store
+    pub fn referenced_attachments(&self) -> Result<HashSet<String>> {
+        let mut stmt = self.connection.prepare("SELECT DISTINCT sha256 FROM finding_attachments")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+
+        let mut digests = HashSet::new();
+        for row in rows {
+            digests.insert(row?);
+        }
+        Ok(digests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> AttachmentStore {
+        AttachmentStore::new(std::env::temp_dir().join(format!("attachments-{}", uuid::Uuid::new_v4())))
+    }
+
+    #[test]
+    fn test_store_is_content_addressed() {
+        let store = temp_store();
+        let first = store.put(b"evidence").unwrap();
+        let second = store.put(b"evidence").unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(store.get(&first).unwrap(), b"evidence");
+        assert_eq!(store.prune(&HashSet::from([first.clone()])).unwrap(), 0);
+        assert_eq!(store.prune(&HashSet::new()).unwrap(), 1);
+        assert!(store.get(&first).is_err());
+
+        std::fs::remove_dir_all(&store.root).ok();
+    }
+
+    #[test]
+    fn test_digest_cannot_escape_store() {
+        let store = temp_store();
+        assert!(store.path_for("../../etc/passwd").is_err());
+        assert!(store.path_for(&"A".repeat(64)).is_err());
+        assert!(store.path_for(&"a".repeat(64)).is_ok());
+        assert_eq!(sanitize_filename("../../secret.txt"), "secret.txt");
+        assert_eq!(sanitize_filename(""), "attachment");
+    }
+
+    fn database_with_finding(hash: &str) -> SecretDatabase {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        db.bulk_insert_secrets(&[crate::secrets::SecretMatch {
+            detector_name: "Generic Secret".to_string(),
+            matched_text: format!("secret-{}", hash),
+            start_position: 0,
+            end_position: 10,
+            line_number: Some(1),
+            filename: Some("config.yml".to_string()),
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
+            entropy: 4.0,
+            severity: crate::secrets::SecretSeverity::High,
+            category: crate::secrets::SecretCategory::Token,
+            context: String::new(),
+            verified: false,
+            hash: hash.to_string(),
+        }])
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_add_list_update_and_delete_notes() {
+        let db = database_with_finding("aaa");
+        assert!(db.add_note("missing", "anna", "rotated").is_err());
+        assert!(db.add_note("aaa", "anna", "   ").is_err());
+
+        let first = db.add_note("aaa", "anna", "Owner contacted").unwrap();
+        let second = db.add_note("aaa", "bob", "Key rotated").unwrap();
+        assert_eq!((first.author.as_str(), first.updated_at.as_deref()), ("anna", None));
+        let bodies: Vec<_> = db.list_notes("aaa").unwrap().into_iter().map(|n| n.body).collect();
+        assert_eq!(bodies, ["Owner contacted", "Key rotated"]);
+
+        assert!(db.update_note("aaa", first.id, "Owner contacted by email").unwrap());
+        assert!(!db.update_note("other", first.id, "moved").unwrap());
+        assert!(db.list_notes("aaa").unwrap()[0].updated_at.is_some());
+
+        // Notes are only deleted through the finding they belong to
+        assert!(!db.delete_note("other", second.id).unwrap());
+        assert!(db.delete_note("aaa", second.id).unwrap());
+        assert!(!db.delete_note("aaa", second.id).unwrap());
+        let notes = db.list_notes("aaa").unwrap();
+        assert_eq!((notes.len(), notes[0].body.as_str()), (1, "Owner contacted by email"));
+        assert_eq!(db.annotations("aaa").unwrap().notes.len(), 1);
+    }
+}
@@ -0,0 +1,176 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use super::SecretDatabase;
+
+/// An organization the hunter watches, with its per-org options
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MonitoredOrganization {
+    pub name: String,
+    /// Disabled organizations keep their settings but are not scanned
+    pub enabled: bool,
+    /// Overrides the hunter's `historical_days_back` for BigQuery scans
+    pub historical_days_back: Option<u32>,
+    pub added_by: String,
+    pub added_at: String,
+    /// Last real-time event seen for one of the organization's repositories
+    pub last_event_at: Option<String>,
+}
+
+/// Settings accepted when adding or updating an organization
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrganizationSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub historical_days_back: Option<u32>,
+}
+
+impl Default for OrganizationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            historical_days_back: None,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrganizationHealth {
+    #[serde(flatten)]
+    pub organization: MonitoredOrganization,
+    pub findings_this_week: i64,
+}
+
+/// GitHub logins: letters, digits and single inner dashes, at most 39 characters
+pub fn normalize_organization(name: &str) -> Result<String> {
+    let name = name.trim().to_lowercase();
+    let valid = !name.is_empty()
+        && name.len() <= 39
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+        && !name.contains("--");
+    if !valid {
+        return Err(anyhow!("Invalid organization name {:?}", name));
+    }
+    Ok(name)
+}
+
+impl SecretDatabase {
+    pub(super) fn initialize_organizations_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS monitored_organizations (
+                name TEXT PRIMARY KEY,
+                enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                historical_days_back INTEGER,
+                added_by TEXT NOT NULL,
+                added_at DATETIME NOT NULL,
+                last_event_at DATETIME
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Add an organization, or update the settings of one already monitored
+    pub fn save_organization(&self, name: &str, settings: &OrganizationSettings, added_by: &str) -> Result<MonitoredOrganization> {
+        let name = normalize_organization(name)?;
+        self.connection.execute(
+            "INSERT INTO monitored_organizations (name, enabled, historical_days_back, added_by, added_at)
+            VALUES (?, ?, ?, ?, datetime('now'))
+            ON CONFLICT(name) DO UPDATE SET
+                enabled = excluded.enabled, historical_days_back = excluded.historical_days_back",
+            params![name, settings.enabled, settings.historical_days_back, added_by],
+        )?;
+
+        self.list_organizations()?
+            .into_iter()
+            .find(|org| org.name == name)
+            .ok_or_else(|| anyhow!("Organization {} vanished after saving", name))
+    }
+
+    pub fn remove_organization(&self, name: &str) -> Result<bool> {
+        let removed = self.connection.execute(
+            "DELETE FROM monitored_organizations WHERE name = ?",
+            params![name.trim().to_lowercase()],
+        )?;
+        Ok(removed > 0)
+    }
+
+    pub fn list_organizations(&self) -> Result<Vec<MonitoredOrganization>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT name, enabled, historical_days_back, added_by, added_at, last_event_at
+            FROM monitored_organizations ORDER BY name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(MonitoredOrganization {
+                name: row.get(0)?,
+                enabled: row.get(1)?,
+                historical_days_back: row.get(2)?,
+                added_by: row.get(3)?,
+                added_at: row.get(4)?,
+                last_event_at: row.get(5)?,
+            })
+        })?;
+
+        let mut organizations = Vec::new();
+        for row in rows {
+            organizations.push(row?);
+        }
+        Ok(organizations)
+    }
+
+    /// Organizations with their last event and findings over the past 7 days (from the rollups)
+    pub fn organization_health(&self) -> Result<Vec<OrganizationHealth>> {
+        let mut health = Vec::new();
+        for organization in self.list_organizations()? {
+            let findings_this_week: i64 = self.connection.query_row(
+                "SELECT COALESCE(SUM(findings), 0) FROM finding_rollups
+                WHERE day >= date('now', '-7 days') AND repository_name LIKE ? || '/%'",
+                params![organization.name],
+                |row| row.get(0),
+            )?;
+            health.push(OrganizationHealth { organization, findings_this_week });
+        }
+        Ok(health)
+    }
+
+    /// Store the latest event time per organization; only monitored organizations are updated
+    pub fn record_organization_activity(&self, activity: &HashMap<String, DateTime<Utc>>) -> Result<()> {
+        let tx = self.connection.unchecked_transaction()?;
+        for (organization, seen_at) in activity {
+            let seen_at = seen_at.format("%Y-%m-%d %H:%M:%S").to_string();
+            tx.execute(
+                "UPDATE monitored_organizations SET last_event_at = ?1
+                WHERE name = ?2 AND (last_event_at IS NULL OR last_event_at < ?1)",
+                params![seen_at, organization.to_lowercase()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_organization_names() {
+        assert_eq!(normalize_organization(" Acme-Corp ").unwrap(), "acme-corp");
+        assert!(normalize_organization("").is_err());
+        assert!(normalize_organization("-acme").is_err());
+        assert!(normalize_organization("acme--corp").is_err());
+        assert!(normalize_organization("acme/corp").is_err());
+        assert!(normalize_organization(&"a".repeat(40)).is_err());
+    }
+}
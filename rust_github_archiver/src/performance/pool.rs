@@ -0,0 +1,158 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use deadpool::managed::{self, Metrics, RecycleError, RecycleResult};
+use deadpool::Runtime;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tracing::debug;
+
+use super::SecretDatabase;
+
+/// Connections kept by a pool unless the caller asks for another size
+pub const DEFAULT_POOL_SIZE: usize = 8;
+
+/// How long a caller waits for a free connection before giving up
+const WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Opens findings database connections for the pool. The schema is created and migrated
+/// once, before the first connection; pooled connections only check its version, so they
+/// never race a migration.
+pub struct SecretDatabaseManager {
+    path: String,
+    read_only: bool,
+    migrated: OnceCell<()>,
+}
+
+#[async_trait]
+impl managed::Manager for SecretDatabaseManager {
+    type Type = SecretDatabase;
+    type Error = anyhow::Error;
+
+    async fn create(&self) -> Result<SecretDatabase> {
+        let path = self.path.clone();
+        let read_only = self.read_only;
+        self.migrated
+            .get_or_try_init(|| {
+                let path = path.clone();
+                async move { tokio::task::spawn_blocking(move || SecretDatabase::open(&path, read_only).map(drop)).await? }
+            })
+            .await?;
+        tokio::task::spawn_blocking(move || SecretDatabase::connect(&path, read_only)).await?
+    }
+
+    async fn recycle(&self, database: &mut SecretDatabase, _: &Metrics) -> RecycleResult<anyhow::Error> {
+        // A connection left inside a transaction by a panicking caller must not be reused
+        if database.connection.is_autocommit() {
+            Ok(())
+        } else {
+            Err(RecycleError::StaticMessage("connection was returned inside a transaction"))
+        }
+    }
+}
+
+/// Pool of findings database connections for async callers. Each connection runs its
+/// queries on the blocking thread pool, so several workers can read and write at once
+/// without stalling the runtime; SQLite's WAL mode lets readers proceed while one writes.
+#[derive(Clone)]
+pub struct SecretDatabasePool {
+    pool: managed::Pool<SecretDatabaseManager>,
+}
+
+impl SecretDatabasePool {
+    /// Connections are opened lazily, up to `max_size`. An in-memory database is private to
+    /// its connection, so it is always pooled as one.
+    pub fn new(path: &str, read_only: bool, max_size: usize) -> Result<Self> {
+        let max_size = if path == ":memory:" { 1 } else { max_size.max(1) };
+        let manager = SecretDatabaseManager { path: path.to_string(), read_only, migrated: OnceCell::new() };
+        let pool = managed::Pool::builder(manager)
+            .max_size(max_size)
+            .wait_timeout(Some(WAIT_TIMEOUT))
+            .runtime(Runtime::Tokio1)
+            .build()
+            .map_err(|e| anyhow!("Failed to build the findings database pool: {}", e))?;
+        Ok(Self { pool })
+    }
+
+    /// Run `f` on a pooled connection off the async workers
+    pub async fn interact<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&SecretDatabase) -> Result<T> + Send + 'static,
+    {
+        let database = self.pool.get().await.map_err(|e| match e {
+            managed::PoolError::Backend(e) => e,
+            other => anyhow!("No findings database connection available: {}", other),
+        })?;
+        tokio::task::spawn_blocking(move || {
+            let result = f(&database);
+            if let Err(e) = database.record_slow_queries() {
+                debug!("Failed to record slow queries: {}", e);
+            }
+            result
+        })
+        .await?
+    }
+
+    /// Open, idle and maximum connections
+    pub fn status(&self) -> managed::Status {
+        self.pool.status()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::{SecretCategory, SecretMatch, SecretSeverity};
+    use crate::performance::SecretQueryFilters;
+
+    fn finding(id: usize) -> SecretMatch {
+        SecretMatch {
+            detector_name: "Test Detector".to_string(),
+            matched_text: format!("secret_{}", id),
+            start_position: 0,
+            end_position: 10,
+            line_number: Some(1),
+            filename: Some("test.env".to_string()),
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
+            entropy: 4.5,
+            severity: SecretSeverity::High,
+            category: SecretCategory::ApiKey,
+            context: "test context".to_string(),
+            verified: false,
+            hash: format!("hash_{}", id),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_writers_share_the_pool() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.db").to_string_lossy().into_owned();
+        let pool = SecretDatabasePool::new(&path, false, 4).unwrap();
+
+        let writers = (0..8).map(|worker| {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                let batch: Vec<_> = (0..25).map(|i| finding(worker * 100 + i)).collect();
+                pool.interact(move |db| db.bulk_insert_secrets(&batch)).await
+            })
+        });
+        for writer in futures::future::join_all(writers).await {
+            writer.unwrap().unwrap();
+        }
+
+        let stored = pool
+            .interact(|db| db.query_secrets(&SecretQueryFilters { include_suppressed: true, ..Default::default() }))
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 200);
+        let journal: String = pool
+            .interact(|db| Ok(db.connection.query_row("PRAGMA journal_mode", [], |row| row.get(0))?))
+            .await
+            .unwrap();
+        assert_eq!(journal, "wal");
+        assert!(pool.status().size <= 4);
+    }
+}
@@ -0,0 +1,197 @@
+use anyhow::Result;
+use rusqlite::{params, OptionalExtension};
+use std::any::Any;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use super::SecretDatabase;
+use crate::core::QueryCacheConfig;
+
+static CACHE: OnceLock<QueryCache> = OnceLock::new();
+
+/// The tables a cached result is computed from. Triggers bump the scope's generation on
+/// every write to them, whichever process makes it, so a changed result is never served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheScope {
+    /// `finding_rollups`, behind the dashboard stats
+    Stats,
+    /// Finding and repository tags, and the findings they count
+    Tags,
+    /// `repositories`, behind the repository facets
+    Repositories,
+}
+
+impl CacheScope {
+    fn name(self) -> &'static str {
+        match self {
+            CacheScope::Stats => "stats",
+            CacheScope::Tags => "tags",
+            CacheScope::Repositories => "repositories",
+        }
+    }
+}
+
+struct CachedResult {
+    generation: i64,
+    stored_at: Instant,
+    value: Arc<dyn Any + Send + Sync>,
+}
+
+/// Results of expensive aggregate queries, keyed by database and a hash of the query's
+/// filters, each served until it is `ttl` old or its scope's tables change
+pub struct QueryCache {
+    ttl: Duration,
+    max_entries: usize,
+    results: Mutex<HashMap<(String, u64), CachedResult>>,
+}
+
+impl QueryCache {
+    pub fn new(config: &QueryCacheConfig) -> Self {
+        Self {
+            ttl: Duration::from_secs(config.ttl_seconds),
+            max_entries: config.max_entries,
+            results: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The cached result of the query `key` names, or `compute`'s, which is then cached.
+    /// `key` holds everything that changes the result, e.g. the query and its filters.
+    /// In-memory databases are never cached: two of them can share a name.
+    pub fn get_or_compute<T, K, F>(&self, db: &SecretDatabase, scope: CacheScope, key: &K, compute: F) -> Result<T>
+    where
+        T: Clone + Send + Sync + 'static,
+        K: Hash + ?Sized,
+        F: FnOnce(&SecretDatabase) -> Result<T>,
+    {
+        let database = match db.connection.path() {
+            Some(path) if !path.is_empty() && !self.ttl.is_zero() && self.max_entries > 0 => path.to_string(),
+            _ => return compute(db),
+        };
+        let mut hasher = DefaultHasher::new();
+        (scope, key).hash(&mut hasher);
+        let key = (database, hasher.finish());
+
+        // Read before computing: a write that lands meanwhile leaves the stored result one
+        // generation behind, so it is recomputed on the next call. A read-only database from
+        // an older build has no counters and is not cached.
+        let Ok(generation) = db.cache_generation(scope) else {
+            return compute(db);
+        };
+        if let Some(cached) = self.results.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+            if cached.generation == generation && cached.stored_at.elapsed() < self.ttl {
+                if let Some(value) = cached.value.downcast_ref::<T>() {
+                    return Ok(value.clone());
+                }
+            }
+        }
+
+        let value = compute(db)?;
+        let mut results = self.results.lock().unwrap_or_else(|e| e.into_inner());
+        if results.len() >= self.max_entries && !results.contains_key(&key) {
+            let ttl = self.ttl;
+            results.retain(|_, cached| cached.stored_at.elapsed() < ttl);
+            if results.len() >= self.max_entries {
+                if let Some(oldest) = results.iter().min_by_key(|(_, cached)| cached.stored_at).map(|(k, _)| k.clone()) {
+                    results.remove(&oldest);
+                }
+            }
+        }
+        results.insert(key, CachedResult { generation, stored_at: Instant::now(), value: Arc::new(value.clone()) });
+        Ok(value)
+    }
+}
+
+/// Install the process-wide cache from the configuration
+pub fn install(config: &QueryCacheConfig) {
+    if CACHE.set(QueryCache::new(config)).is_err() {
+        warn!("The query cache was already configured; ignoring the new settings");
+    }
+}
+
+/// The active cache, configured from the environment if `install` was never called
+pub fn cache() -> &'static QueryCache {
+    CACHE.get_or_init(|| QueryCache::new(&QueryCacheConfig::default()))
+}
+
+impl SecretDatabase {
+    /// Times the tables behind `scope` have been written to
+    fn cache_generation(&self, scope: CacheScope) -> Result<i64> {
+        Ok(self
+            .connection
+            .query_row("SELECT generation FROM cache_generations WHERE scope = ?", params![scope.name()], |row| row.get(0))
+            .optional()?
+            .unwrap_or(0))
+    }
+
+    /// `compute`, served from the process-wide query cache while its scope is unchanged
+    pub fn cached<T, K, F>(&self, scope: CacheScope, key: &K, compute: F) -> Result<T>
+    where
+        T: Clone + Send + Sync + 'static,
+        K: Hash + ?Sized,
+        F: FnOnce(&SecretDatabase) -> Result<T>,
+    {
+        cache().get_or_compute(self, scope, key, compute)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::{SecretCategory, SecretMatch, SecretSeverity};
+    use std::cell::Cell;
+
+    fn finding(hash: &str) -> SecretMatch {
+        SecretMatch {
+            detector_name: "AWS Access Key".to_string(),
+            matched_text: format!("AKIA{}", hash),
+            start_position: 0,
+            end_position: 20,
+            line_number: Some(1),
+            filename: Some(".env".to_string()),
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
+            entropy: 4.1,
+            severity: SecretSeverity::Critical,
+            category: SecretCategory::CloudProvider,
+            context: String::new(),
+            verified: false,
+            hash: hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_results_are_cached_until_their_tables_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("findings.db");
+        let db = SecretDatabase::new(path.to_str().unwrap()).unwrap();
+        let cache = QueryCache::new(&QueryCacheConfig { ttl_seconds: 60, max_entries: 8 });
+        let computed = Cell::new(0);
+        let summary = |db: &SecretDatabase| {
+            cache.get_or_compute(db, CacheScope::Stats, &("summary", 30u32, 10u32), |db| {
+                computed.set(computed.get() + 1);
+                db.validation_ratio(30)
+            })
+        };
+
+        assert_eq!(summary(&db).unwrap().findings, 0);
+        assert_eq!(summary(&db).unwrap().findings, 0);
+        assert_eq!(computed.get(), 1);
+
+        // Written through another connection, as the hunter process would
+        let writer = SecretDatabase::new(path.to_str().unwrap()).unwrap();
+        writer.bulk_insert_secrets(&[finding("new")]).unwrap();
+        assert_eq!(summary(&db).unwrap().findings, 1);
+        assert_eq!(computed.get(), 2);
+
+        let in_memory = SecretDatabase::new(":memory:").unwrap();
+        summary(&in_memory).unwrap();
+        summary(&in_memory).unwrap();
+        assert_eq!(computed.get(), 4);
+    }
+}
@@ -0,0 +1,307 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use utoipa::ToSchema;
+
+use super::{SecretDatabase, SecretDatabasePool};
+use crate::core::{QuotaConfig, QuotaLimits};
+use crate::secrets::SecretMatch;
+
+/// A resource a workspace has a budget for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaResource {
+    GithubApiCalls,
+    BigqueryBytes,
+    AiTokens,
+    FindingsStored,
+}
+
+impl QuotaResource {
+    pub const ALL: [QuotaResource; 4] =
+        [Self::GithubApiCalls, Self::BigqueryBytes, Self::AiTokens, Self::FindingsStored];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GithubApiCalls => "github_api_calls",
+            Self::BigqueryBytes => "bigquery_bytes",
+            Self::AiTokens => "ai_tokens",
+            Self::FindingsStored => "findings_stored",
+        }
+    }
+
+    pub fn limit(&self, limits: &QuotaLimits) -> u64 {
+        match self {
+            Self::GithubApiCalls => limits.github_api_calls_per_day,
+            Self::BigqueryBytes => limits.bigquery_bytes_per_month,
+            Self::AiTokens => limits.ai_tokens_per_day,
+            Self::FindingsStored => limits.findings_stored,
+        }
+    }
+
+    /// The usage period `now` falls in and when the next one starts: a UTC day, a UTC
+    /// calendar month, or no period for stored findings, which are counted rather than metered
+    pub fn period(&self, now: DateTime<Utc>) -> (String, Option<DateTime<Utc>>) {
+        let today = now.date_naive();
+        match self {
+            Self::GithubApiCalls | Self::AiTokens => {
+                let next = Utc.from_utc_datetime(&(today + Duration::days(1)).and_hms_opt(0, 0, 0).expect("midnight exists"));
+                (today.format("%Y-%m-%d").to_string(), Some(next))
+            }
+            Self::BigqueryBytes => {
+                let (year, month) = if today.month() == 12 { (today.year() + 1, 1) } else { (today.year(), today.month() + 1) };
+                let next = NaiveDate::from_ymd_opt(year, month, 1)
+                    .and_then(|day| day.and_hms_opt(0, 0, 0))
+                    .map(|start| Utc.from_utc_datetime(&start));
+                (today.format("%Y-%m").to_string(), next)
+            }
+            Self::FindingsStored => ("total".to_string(), None),
+        }
+    }
+}
+
+/// One resource's budget for the current period
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QuotaBudget {
+    pub resource: QuotaResource,
+    pub period: String,
+    /// 0 when unlimited
+    pub limit: u64,
+    pub used: u64,
+    /// `None` when unlimited
+    pub remaining: Option<u64>,
+    /// When the period ends and usage starts over; `None` for stored findings
+    pub resets_at: Option<DateTime<Utc>>,
+}
+
+/// Every budget of one workspace
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QuotaReport {
+    pub workspace: String,
+    pub budgets: Vec<QuotaBudget>,
+}
+
+impl SecretDatabase {
+    pub(super) fn initialize_quota_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS quota_usage (
+                workspace TEXT NOT NULL,
+                resource TEXT NOT NULL,
+                period TEXT NOT NULL,
+                used INTEGER NOT NULL DEFAULT 0,
+                updated_at DATETIME NOT NULL,
+                PRIMARY KEY (workspace, resource, period)
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Usage of a metered resource in the current period; the finding count for stored findings
+    pub fn quota_used(&self, workspace: &str, resource: QuotaResource) -> Result<u64> {
+        if resource == QuotaResource::FindingsStored {
+            let count: i64 = self.connection.query_row(
+                &format!("SELECT COUNT(*) FROM secrets WHERE {}", super::NOT_SYNTHETIC),
+                [],
+                |row| row.get(0),
+            )?;
+            return Ok(count as u64);
+        }
+        let (period, _) = resource.period(Utc::now());
+        let used: Option<i64> = self
+            .connection
+            .query_row(
+                "SELECT used FROM quota_usage WHERE workspace = ? AND resource = ? AND period = ?",
+                params![workspace, resource.as_str(), period],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(used.unwrap_or(0) as u64)
+    }
+
+    /// Add `amount` to the current period's usage if that stays within `limit` (0 is
+    /// unlimited). Returns whether it was added; the check and the update are one statement,
+    /// so concurrent consumers cannot overshoot.
+    pub fn consume_quota(&self, workspace: &str, resource: QuotaResource, amount: u64, limit: u64) -> Result<bool> {
+        let (period, _) = resource.period(Utc::now());
+        let limit = if limit == 0 { i64::MAX } else { limit.min(i64::MAX as u64) as i64 };
+        let amount = amount.min(i64::MAX as u64) as i64;
+        if amount > limit {
+            return Ok(false);
+        }
+        let changed = self.connection.execute(
+            "INSERT INTO quota_usage (workspace, resource, period, used, updated_at)
+            VALUES (?1, ?2, ?3, ?4, datetime('now'))
+            ON CONFLICT(workspace, resource, period) DO UPDATE
+            SET used = used + excluded.used, updated_at = excluded.updated_at
+            WHERE used <= ?5 - excluded.used",
+            params![workspace, resource.as_str(), period, amount, limit],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Add usage measured after the fact, such as the bytes a BigQuery job processed.
+    /// This may take the period past its limit; later `consume_quota` calls then fail.
+    pub fn record_quota_usage(&self, workspace: &str, resource: QuotaResource, amount: u64) -> Result<()> {
+        let (period, _) = resource.period(Utc::now());
+        self.connection.execute(
+            "INSERT INTO quota_usage (workspace, resource, period, used, updated_at)
+            VALUES (?, ?, ?, ?, datetime('now'))
+            ON CONFLICT(workspace, resource, period) DO UPDATE
+            SET used = used + excluded.used, updated_at = excluded.updated_at",
+            params![workspace, resource.as_str(), period, amount.min(i64::MAX as u64) as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Distinct findings of `secrets` not stored yet; what storing them adds to the finding count
+    pub fn new_findings(&self, secrets: &[SecretMatch]) -> Result<usize> {
+        let mut exists = self.connection.prepare_cached("SELECT EXISTS(SELECT 1 FROM secrets WHERE secret_hash = ?)")?;
+        let mut seen = HashSet::new();
+        let mut new = 0;
+        for secret in secrets {
+            if seen.insert(secret.hash.as_str()) && !exists.query_row(params![secret.hash], |row| row.get::<_, bool>(0))? {
+                new += 1;
+            }
+        }
+        Ok(new)
+    }
+
+    pub fn quota_report(&self, workspace: &str, limits: &QuotaLimits) -> Result<QuotaReport> {
+        let now = Utc::now();
+        let mut budgets = Vec::new();
+        for resource in QuotaResource::ALL {
+            let (period, resets_at) = resource.period(now);
+            let limit = resource.limit(limits);
+            let used = self.quota_used(workspace, resource)?;
+            budgets.push(QuotaBudget {
+                resource,
+                period,
+                limit,
+                used,
+                remaining: (limit > 0).then(|| limit.saturating_sub(used)),
+                resets_at,
+            });
+        }
+        Ok(QuotaReport { workspace: workspace.to_string(), budgets })
+    }
+}
+
+/// The budgets of the workspace this process works for, kept in the findings database so
+/// every process of the workspace draws on the same usage
+#[derive(Clone)]
+pub struct WorkspaceQuota {
+    workspace: String,
+    limits: QuotaLimits,
+    pool: SecretDatabasePool,
+}
+
+impl WorkspaceQuota {
+    pub fn new(config: &QuotaConfig, database_path: &str) -> Result<Self> {
+        Ok(Self {
+            workspace: config.workspace.clone(),
+            limits: config.limits_for(&config.workspace).clone(),
+            pool: SecretDatabasePool::new(database_path, false, 2)?,
+        })
+    }
+
+    pub fn workspace(&self) -> &str {
+        &self.workspace
+    }
+
+    pub fn limit(&self, resource: QuotaResource) -> u64 {
+        resource.limit(&self.limits)
+    }
+
+    /// Take `amount` from the budget, or fail without taking anything when it would be exceeded
+    pub async fn consume(&self, resource: QuotaResource, amount: u64) -> Result<()> {
+        let limit = self.limit(resource);
+        if limit == 0 {
+            return Ok(());
+        }
+        let workspace = self.workspace.clone();
+        let taken = self.pool.interact(move |db| db.consume_quota(&workspace, resource, amount, limit)).await?;
+        if taken {
+            Ok(())
+        } else {
+            Err(self.exceeded(resource))
+        }
+    }
+
+    /// Record usage measured after the fact
+    pub async fn record(&self, resource: QuotaResource, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let workspace = self.workspace.clone();
+        self.pool.interact(move |db| db.record_quota_usage(&workspace, resource, amount)).await
+    }
+
+    /// What is left of a resource's budget; `None` when unlimited
+    pub async fn remaining(&self, resource: QuotaResource) -> Result<Option<u64>> {
+        let limit = self.limit(resource);
+        if limit == 0 {
+            return Ok(None);
+        }
+        let workspace = self.workspace.clone();
+        let used = self.pool.interact(move |db| db.quota_used(&workspace, resource)).await?;
+        Ok(Some(limit.saturating_sub(used)))
+    }
+
+    pub async fn report(&self) -> Result<QuotaReport> {
+        let (workspace, limits) = (self.workspace.clone(), self.limits.clone());
+        self.pool.interact(move |db| db.quota_report(&workspace, &limits)).await
+    }
+
+    pub fn exceeded(&self, resource: QuotaResource) -> anyhow::Error {
+        anyhow!(
+            "Workspace {} has used its {} quota of {} for {}",
+            self.workspace,
+            resource.as_str(),
+            self.limit(resource),
+            resource.period(Utc::now()).0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_consumption_stops_at_the_limit() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        assert!(db.consume_quota("acme", QuotaResource::GithubApiCalls, 3, 5).unwrap());
+        assert!(db.consume_quota("acme", QuotaResource::GithubApiCalls, 2, 5).unwrap());
+        assert!(!db.consume_quota("acme", QuotaResource::GithubApiCalls, 1, 5).unwrap());
+        // Workspaces and unlimited resources are metered apart
+        assert!(db.consume_quota("other", QuotaResource::GithubApiCalls, 5, 5).unwrap());
+        assert!(db.consume_quota("acme", QuotaResource::AiTokens, 1_000_000, 0).unwrap());
+
+        db.record_quota_usage("acme", QuotaResource::BigqueryBytes, 700).unwrap();
+        let limits = QuotaLimits {
+            github_api_calls_per_day: 5,
+            bigquery_bytes_per_month: 500,
+            ai_tokens_per_day: 0,
+            findings_stored: 10,
+        };
+        let report = db.quota_report("acme", &limits).unwrap();
+        let budget = |resource| report.budgets.iter().find(|b| b.resource == resource).unwrap().clone();
+        assert_eq!(budget(QuotaResource::GithubApiCalls).remaining, Some(0));
+        assert_eq!((budget(QuotaResource::BigqueryBytes).used, budget(QuotaResource::BigqueryBytes).remaining), (700, Some(0)));
+        assert_eq!(budget(QuotaResource::AiTokens).remaining, None);
+        assert_eq!(budget(QuotaResource::FindingsStored).remaining, Some(10));
+    }
+
+    #[test]
+    fn test_periods_reset_at_utc_boundaries() {
+        let now = Utc.with_ymd_and_hms(2026, 12, 31, 18, 30, 0).unwrap();
+        let (day, next_day) = QuotaResource::GithubApiCalls.period(now);
+        assert_eq!((day.as_str(), next_day), ("2026-12-31", Some(Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap())));
+        let (month, next_month) = QuotaResource::BigqueryBytes.period(now);
+        assert_eq!((month.as_str(), next_month), ("2026-12", Some(Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap())));
+        assert_eq!(QuotaResource::FindingsStored.period(now).1, None);
+    }
+}
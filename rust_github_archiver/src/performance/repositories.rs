@@ -0,0 +1,440 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
+
+use super::{SecretDatabase, NOT_SYNTHETIC};
+
+/// Timestamp format of `repositories.last_activity` and `synced_at`
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Visibility recorded for a repository GitHub no longer returns (deleted, renamed or made private)
+pub const UNAVAILABLE: &str = "unavailable";
+
+/// Metadata fetched from `GET /repos/{owner}/{repo}` or a GraphQL batch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepositoryMetadata {
+    /// `owner/name`
+    pub name: String,
+    /// `public`, `private`, `internal` or `unavailable`
+    pub visibility: Option<String>,
+    pub star_count: Option<i64>,
+    pub language: Option<String>,
+    pub archived: bool,
+    /// Last push
+    pub last_activity: Option<DateTime<Utc>>,
+    /// From the contributors endpoint, fetched on demand by `refresh_repository`; neither sync sets it
+    pub contributor_count: Option<i64>,
+    pub is_fork: Option<bool>,
+}
+
+impl RepositoryMetadata {
+    pub fn from_github(name: &str, repository: &serde_json::Value) -> Self {
+        let visibility = repository["visibility"].as_str().map(str::to_string).or_else(|| {
+            repository["private"].as_bool().map(|private| if private { "private" } else { "public" }.to_string())
+        });
+        Self {
+            name: repository["full_name"].as_str().unwrap_or(name).to_string(),
+            visibility,
+            star_count: repository["stargazers_count"].as_i64(),
+            language: repository["language"].as_str().map(str::to_string),
+            archived: repository["archived"].as_bool().unwrap_or(false),
+            last_activity: repository["pushed_at"]
+                .as_str()
+                .and_then(|pushed| DateTime::parse_from_rfc3339(pushed).ok())
+                .map(|pushed| pushed.with_timezone(&Utc)),
+            contributor_count: None,
+            is_fork: repository["fork"].as_bool(),
+        }
+    }
+
+    /// GitHub answered 404: keep what is known and record that the repository is gone
+    pub fn unavailable(name: &str) -> Self {
+        Self { name: name.to_string(), visibility: Some(UNAVAILABLE.to_string()), ..Default::default() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RepositoryRecord {
+    pub name: String,
+    pub organization: Option<String>,
+    pub visibility: Option<String>,
+    pub star_count: Option<i64>,
+    pub language: Option<String>,
+    pub archived: bool,
+    pub last_activity: Option<String>,
+    pub risk_score: Option<f64>,
+    pub contributor_count: Option<i64>,
+    pub is_fork: Option<bool>,
+    /// Last metadata sync; `None` for a repository only seen in events so far
+    pub synced_at: Option<String>,
+}
+
+impl RepositoryRecord {
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            name: row.get(0)?,
+            organization: row.get(1)?,
+            visibility: row.get(2)?,
+            star_count: row.get(3)?,
+            language: row.get(4)?,
+            archived: row.get(5)?,
+            last_activity: row.get(6)?,
+            risk_score: row.get(7)?,
+            synced_at: row.get(8)?,
+            contributor_count: row.get(9)?,
+            is_fork: row.get(10)?,
+        })
+    }
+
+    pub fn is_public(&self) -> bool {
+        self.visibility.as_deref() == Some("public")
+    }
+
+    /// Pushed to or seen in an event within the last 30 days
+    pub fn recently_active(&self, now: DateTime<Utc>) -> bool {
+        self.last_activity
+            .as_deref()
+            .and_then(|seen| NaiveDateTime::parse_from_str(seen, TIMESTAMP_FORMAT).ok())
+            .map_or(false, |seen| now - seen.and_utc() <= chrono::Duration::days(30))
+    }
+}
+
+const REPOSITORY_COLUMNS: &str =
+    "name, organization, visibility, star_count, language, archived, last_activity, risk_score, synced_at, \
+    contributor_count, is_fork";
+
+/// Open findings of one repository, the part of the risk score that comes from this database
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepositoryExposure {
+    /// Not suppressed, synthetic, resolved or revoked
+    pub active_findings: i64,
+    pub verified_findings: i64,
+}
+
+/// 0 to 1. Visibility weighs most, then what the repository has already leaked, then
+/// its audience (stars) and whether anyone still pushes to it.
+pub fn risk_score(record: &RepositoryRecord, exposure: &RepositoryExposure, now: DateTime<Utc>) -> f64 {
+    let visibility = match record.visibility.as_deref() {
+        Some("public") => 0.35,
+        Some("internal") => 0.15,
+        Some("private") | Some(UNAVAILABLE) => 0.05,
+        _ => 0.2,
+    };
+    let stars = record.star_count.unwrap_or(0).max(0) as f64;
+    let audience = 0.15 * ((1.0 + stars).ln() / (1.0 + 10_000f64).ln()).min(1.0);
+    let activity = if record.recently_active(now) && !record.archived { 0.1 } else { 0.0 };
+    let findings = 0.25 * (exposure.active_findings as f64 / 10.0).min(1.0);
+    let verified = if exposure.verified_findings > 0 { 0.15 } else { 0.0 };
+
+    ((visibility + audience + activity + findings + verified) * 1000.0).round() / 1000.0
+}
+
+/// `?organization=acme&visibility=public&language=Go&min_risk=0.5&limit=50`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(default)]
+pub struct RepositoryFilters {
+    pub organization: Option<String>,
+    pub visibility: Option<String>,
+    pub language: Option<String>,
+    pub min_risk: Option<f64>,
+    pub limit: Option<u32>,
+    /// Keyset position: only repositories after this `(risk_score, name)` in listing order
+    #[serde(skip)]
+    pub after: Option<(Option<f64>, String)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FacetCount {
+    pub value: String,
+    pub repositories: i64,
+}
+
+/// Repository counts per value of each facet, for dashboard filters
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RepositoryFacets {
+    pub visibility: Vec<FacetCount>,
+    pub language: Vec<FacetCount>,
+    pub organization: Vec<FacetCount>,
+}
+
+impl SecretDatabase {
+    pub(super) fn initialize_repositories_schema(&self) -> Result<()> {
+        // Commits pushed per repository and day, counted from real-time push events
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS commit_activity (
+                day DATE NOT NULL,
+                repository_name TEXT NOT NULL,
+                commits INTEGER NOT NULL,
+                PRIMARY KEY(day, repository_name)
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Add pushed commit counts to the per-day totals
+    pub fn record_commit_activity(&self, activity: &HashMap<(String, NaiveDate), u64>) -> Result<()> {
+        let tx = self.connection.unchecked_transaction()?;
+        for ((repository, day), commits) in activity {
+            tx.execute(
+                "INSERT INTO commit_activity (day, repository_name, commits) VALUES (?, ?, ?)
+                ON CONFLICT(day, repository_name) DO UPDATE SET commits = commits + excluded.commits",
+                params![day.to_string(), repository, *commits as i64],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Record repositories seen in real-time events, keeping the latest time per repository.
+    /// Their metadata is filled in by the next sync.
+    pub fn record_repository_activity(&self, activity: &HashMap<String, DateTime<Utc>>) -> Result<()> {
+        let tx = self.connection.unchecked_transaction()?;
+        for (name, seen_at) in activity {
+            let Some((owner, _)) = name.split_once('/') else { continue };
+            tx.execute(
+                "INSERT INTO repositories (name, organization, last_activity, created_at)
+                VALUES (?, ?, ?, datetime('now'))
+                ON CONFLICT(name) DO UPDATE SET
+                    last_activity = MAX(COALESCE(repositories.last_activity, ''), excluded.last_activity)",
+                params![name, owner.to_lowercase(), seen_at.format(TIMESTAMP_FORMAT).to_string()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Repositories seen in events or findings whose metadata is missing or older than
+    /// `stale_hours`, never-synced ones first
+    pub fn repositories_due_for_sync(&self, stale_hours: u32, limit: u32) -> Result<Vec<String>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT seen.name FROM (
+                SELECT c.repository_name AS name FROM secrets s JOIN commits c ON c.id = s.commit_id
+                UNION SELECT name FROM repositories
+            ) seen
+            LEFT JOIN repositories r ON r.name = seen.name
+            WHERE seen.name LIKE '%_/_%' AND (r.synced_at IS NULL OR r.synced_at < datetime('now', ?))
+            ORDER BY r.synced_at IS NOT NULL, r.synced_at
+            LIMIT ?",
+        )?;
+        let rows = stmt.query_map(params![format!("-{} hours", stale_hours), limit], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn repository_exposure(&self, name: &str) -> Result<RepositoryExposure> {
+        let exposure = self.connection.query_row(
+            &format!(
+                "SELECT COUNT(*), COALESCE(SUM(CASE WHEN s.verified THEN 1 ELSE 0 END), 0)
+                FROM secrets s JOIN commits c ON c.id = s.commit_id
+                WHERE c.repository_name = ? AND NOT s.suppressed
+                AND (s.validation_status IS NULL OR s.validation_status NOT IN ('resolved', 'revoked'))
+                AND {}",
+                NOT_SYNTHETIC
+            ),
+            params![name],
+            |row| Ok(RepositoryExposure { active_findings: row.get(0)?, verified_findings: row.get(1)? }),
+        )?;
+        Ok(exposure)
+    }
+
+    /// Merge freshly fetched metadata into the stored row and recompute its risk score.
+    /// Fields GitHub did not return keep their stored values.
+    pub fn save_repository_metadata(&self, metadata: &RepositoryMetadata) -> Result<RepositoryRecord> {
+        let stored = self.repository(&metadata.name)?;
+        let fetched_activity = metadata.last_activity.map(|at| at.format(TIMESTAMP_FORMAT).to_string());
+        let now = Utc::now();
+
+        let mut record = RepositoryRecord {
+            name: metadata.name.clone(),
+            organization: metadata.name.split_once('/').map(|(owner, _)| owner.to_lowercase()),
+            visibility: metadata.visibility.clone().or_else(|| stored.as_ref().and_then(|r| r.visibility.clone())),
+            star_count: metadata.star_count.or_else(|| stored.as_ref().and_then(|r| r.star_count)),
+            language: metadata.language.clone().or_else(|| stored.as_ref().and_then(|r| r.language.clone())),
+            archived: metadata.archived,
+            last_activity: fetched_activity.max(stored.as_ref().and_then(|r| r.last_activity.clone())),
+            risk_score: None,
+            contributor_count: metadata.contributor_count.or_else(|| stored.as_ref().and_then(|r| r.contributor_count)),
+            is_fork: metadata.is_fork.or_else(|| stored.as_ref().and_then(|r| r.is_fork)),
+            synced_at: Some(now.format(TIMESTAMP_FORMAT).to_string()),
+        };
+        record.risk_score = Some(risk_score(&record, &self.repository_exposure(&record.name)?, now));
+
+        self.connection.execute(
+            "INSERT INTO repositories
+                (name, organization, is_public, visibility, star_count, language, archived, last_activity,
+                 risk_score, contributor_count, is_fork, created_at, updated_at, synced_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, datetime('now'), datetime('now'), ?)
+            ON CONFLICT(name) DO UPDATE SET
+                organization = excluded.organization, is_public = excluded.is_public,
+                visibility = excluded.visibility, star_count = excluded.star_count,
+                language = excluded.language, archived = excluded.archived,
+                last_activity = excluded.last_activity, risk_score = excluded.risk_score,
+                contributor_count = excluded.contributor_count, is_fork = excluded.is_fork,
+                updated_at = excluded.updated_at, synced_at = excluded.synced_at",
+            params![
+                record.name,
+                record.organization,
+                record.is_public(),
+                record.visibility,
+                record.star_count,
+                record.language,
+                record.archived,
+                record.last_activity,
+                record.risk_score,
+                record.contributor_count,
+                record.is_fork,
+                record.synced_at,
+            ],
+        )?;
+        Ok(record)
+    }
+
+    pub fn repository(&self, name: &str) -> Result<Option<RepositoryRecord>> {
+        let record = self
+            .connection
+            .query_row(
+                &format!("SELECT {} FROM repositories WHERE name = ? COLLATE NOCASE", REPOSITORY_COLUMNS),
+                params![name],
+                RepositoryRecord::from_row,
+            )
+            .optional()?;
+        Ok(record)
+    }
+
+    /// Repositories matching `filters`, riskiest first
+    pub fn list_repositories(&self, filters: &RepositoryFilters) -> Result<Vec<RepositoryRecord>> {
+        let mut query = format!("SELECT {} FROM repositories WHERE 1=1", REPOSITORY_COLUMNS);
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(organization) = &filters.organization {
+            query.push_str(" AND organization = ?");
+            params.push(Box::new(organization.to_lowercase()));
+        }
+        if let Some(visibility) = &filters.visibility {
+            query.push_str(" AND visibility = ?");
+            params.push(Box::new(visibility.to_lowercase()));
+        }
+        if let Some(language) = &filters.language {
+            query.push_str(" AND language = ? COLLATE NOCASE");
+            params.push(Box::new(language.clone()));
+        }
+        if let Some(min_risk) = filters.min_risk {
+            query.push_str(" AND risk_score >= ?");
+            params.push(Box::new(min_risk));
+        }
+        // Unscored repositories sort last, so they follow every scored one
+        match &filters.after {
+            Some((Some(risk), name)) => {
+                query.push_str(" AND (risk_score IS NULL OR risk_score < ? OR (risk_score = ? AND name > ?))");
+                params.push(Box::new(*risk));
+                params.push(Box::new(*risk));
+                params.push(Box::new(name.clone()));
+            }
+            Some((None, name)) => {
+                query.push_str(" AND risk_score IS NULL AND name > ?");
+                params.push(Box::new(name.clone()));
+            }
+            None => {}
+        }
+        query.push_str(" ORDER BY risk_score IS NULL, risk_score DESC, name");
+        if let Some(limit) = filters.limit {
+            query.push_str(" LIMIT ?");
+            params.push(Box::new(limit));
+        }
+
+        let mut stmt = self.connection.prepare(&query)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), RepositoryRecord::from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Counts per visibility, and per language and organization (top `limit` of each)
+    pub fn repository_facets(&self, limit: u32) -> Result<RepositoryFacets> {
+        let facet = |expression: &str, limit: u32| -> Result<Vec<FacetCount>> {
+            let mut stmt = self.connection.prepare(&format!(
+                "SELECT {expression} AS value, COUNT(*) FROM repositories
+                WHERE {expression} IS NOT NULL GROUP BY value ORDER BY 2 DESC, value LIMIT ?"
+            ))?;
+            let rows = stmt.query_map(params![limit], |row| {
+                Ok(FacetCount { value: row.get(0)?, repositories: row.get(1)? })
+            })?;
+            Ok(rows.collect::<rusqlite::Result<_>>()?)
+        };
+
+        Ok(RepositoryFacets {
+            visibility: facet("COALESCE(visibility, 'unknown')", u32::MAX)?,
+            language: facet("language", limit)?,
+            organization: facet("organization", limit)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seen_repositories_are_synced() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let seen = HashMap::from([("Acme/Payments".to_string(), Utc::now())]);
+        db.record_repository_activity(&seen).unwrap();
+        assert_eq!(db.repositories_due_for_sync(24, 10).unwrap(), vec!["Acme/Payments"]);
+
+        let github = serde_json::json!({
+            "full_name": "Acme/Payments",
+            "visibility": "public",
+            "stargazers_count": 1200,
+            "language": "Go",
+            "archived": false,
+            "fork": false,
+            "pushed_at": "2020-01-01T00:00:00Z"
+        });
+        let record = db.save_repository_metadata(&RepositoryMetadata::from_github("Acme/Payments", &github)).unwrap();
+        assert!(record.is_public());
+        assert_eq!(record.organization.as_deref(), Some("acme"));
+        // The event seen just now is newer than the last push
+        assert!(record.recently_active(Utc::now()));
+        assert!(record.risk_score.unwrap() > 0.5);
+        assert!(db.repositories_due_for_sync(24, 10).unwrap().is_empty());
+
+        // A repository that disappeared keeps its last known metadata
+        let gone = db.save_repository_metadata(&RepositoryMetadata::unavailable("Acme/Payments")).unwrap();
+        assert_eq!(gone.visibility.as_deref(), Some(UNAVAILABLE));
+        assert_eq!(gone.star_count, Some(1200));
+        assert_eq!(gone.is_fork, Some(false));
+
+        let graphql = RepositoryMetadata { contributor_count: Some(12), ..RepositoryMetadata::unavailable("Acme/Payments") };
+        assert_eq!(db.save_repository_metadata(&graphql).unwrap().contributor_count, Some(12));
+        assert!(gone.risk_score < record.risk_score);
+
+        let facets = db.repository_facets(10).unwrap();
+        assert_eq!(facets.language[0].value, "Go");
+        let filters = RepositoryFilters { organization: Some("ACME".to_string()), ..Default::default() };
+        assert_eq!(db.list_repositories(&filters).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_risk_score_weighs_exposure() {
+        let now = Utc::now();
+        let record = RepositoryRecord {
+            name: "acme/api".to_string(),
+            organization: Some("acme".to_string()),
+            visibility: Some("private".to_string()),
+            star_count: None,
+            language: None,
+            archived: false,
+            last_activity: None,
+            risk_score: None,
+            contributor_count: None,
+            is_fork: None,
+            synced_at: None,
+        };
+        let quiet = risk_score(&record, &RepositoryExposure::default(), now);
+        let leaking = risk_score(&record, &RepositoryExposure { active_findings: 20, verified_findings: 1 }, now);
+        assert_eq!(quiet, 0.05);
+        assert_eq!(leaking, 0.45);
+    }
+}
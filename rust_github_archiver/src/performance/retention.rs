@@ -0,0 +1,432 @@
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Duration};
+use tracing::{error, info};
+
+use super::legal_hold::scrub_finding;
+use super::{AttachmentStore, SecretDatabase, NOT_ON_LEGAL_HOLD, ROLLUP_WINDOW_DAYS};
+use crate::core::{AuditLog, RetentionAction, RetentionConfig};
+use crate::secrets::SecretCategory;
+
+/// One retention rule: which rows it selects and what it does to them
+struct RetentionRule {
+    name: &'static str,
+    /// Predicate on `secrets`, with a single `?` bound to the age cutoff
+    selector: &'static str,
+    /// Statement applied to the selected rows; `{}` is replaced with the full predicate
+    action: &'static str,
+}
+
+const RULES: [RetentionRule; 3] = [
+    RetentionRule {
+        name: "contexts",
+        selector: "context_hash IS NOT NULL AND created_at < datetime('now', ?)",
+        action: "UPDATE secrets SET context_hash = NULL, updated_at = datetime('now') WHERE {}",
+    },
+    RetentionRule {
+        name: "validated_secrets",
+        selector: "verified = TRUE AND matched_text_hash != '' AND created_at < datetime('now', ?)",
+        action: "UPDATE secrets SET matched_text_hash = '', updated_at = datetime('now') WHERE {}",
+    },
+    RetentionRule {
+        name: "resolved_findings",
+        selector: "validation_status IN ('resolved', 'revoked') AND COALESCE(updated_at, created_at) < datetime('now', ?)",
+        action: "DELETE FROM secrets WHERE {}",
+    },
+];
+
+/// Findings age-based rules may expire. Erased findings are already scrubbed and only
+/// count towards statistics; synthetic ones are purged by the synthetic monitor.
+const EXPIRABLE: &str = "secret_hash NOT LIKE 'erased:%' AND secret_hash NOT LIKE 'synthetic-%'";
+
+const LOW_UNVERIFIED: &str =
+    "severity = 'Low' AND NOT COALESCE(verified, FALSE) AND validation_status IS NOT 'valid' AND created_at < datetime('now', ?)";
+
+/// Tables holding analyst data keyed by a finding's hash
+const FINDING_CHILDREN: [&str; 4] = ["finding_notes", "finding_attachments", "finding_tags", "saved_search_matches"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PurgeReport {
+    pub dry_run: bool,
+    pub contexts_cleared: usize,
+    pub validated_secrets_cleared: usize,
+    pub resolved_findings_deleted: usize,
+    /// Findings past `finding_days` or their category's retention
+    pub expired_findings_deleted: usize,
+    pub expired_findings_anonymized: usize,
+    /// Expired findings per category, deleted or anonymized
+    pub expired_by_category: BTreeMap<String, usize>,
+    pub low_unverified_deleted: usize,
+    /// Triage results, notes, attachments, tags and search matches of findings that no
+    /// longer exist
+    pub orphans_deleted: usize,
+    /// Rows that matched a rule but were kept because of a legal hold
+    pub held_skipped: usize,
+}
+
+impl PurgeReport {
+    pub fn total(&self) -> usize {
+        self.contexts_cleared
+            + self.validated_secrets_cleared
+            + self.resolved_findings_deleted
+            + self.expired_findings_deleted
+            + self.expired_findings_anonymized
+            + self.low_unverified_deleted
+            + self.orphans_deleted
+    }
+
+    fn set(&mut self, rule: &str, count: usize) {
+        match rule {
+            "contexts" => self.contexts_cleared = count,
+            "validated_secrets" => self.validated_secrets_cleared = count,
+            _ => self.resolved_findings_deleted = count,
+        }
+    }
+}
+
+fn retention_days(config: &RetentionConfig, rule: &str) -> u32 {
+    match rule {
+        "contexts" => config.context_days,
+        "validated_secrets" => config.validated_secret_days,
+        _ => config.resolved_finding_days,
+    }
+}
+
+/// An age-based rule over all findings, or those of one category
+struct ExpiryRule {
+    /// Predicate on `secrets` without the age, e.g. `category = 'Token'`
+    scope: String,
+    days: u32,
+    action: RetentionAction,
+}
+
+/// The configured age-based rules. A category with its own rule is left out of the
+/// general one, so its retention may be longer as well as shorter.
+fn expiry_rules(config: &RetentionConfig) -> Result<Vec<ExpiryRule>> {
+    let mut rules = Vec::new();
+    let mut overridden = Vec::new();
+    for (name, retention) in &config.categories {
+        let category: SecretCategory =
+            name.parse().map_err(|e| anyhow!("Invalid retention category {}: {}", name, e))?;
+        let scope = format!("category = '{:?}'", category);
+        overridden.push(format!("'{:?}'", category));
+        if retention.days > 0 {
+            rules.push(ExpiryRule { scope, days: retention.days, action: retention.action.unwrap_or(config.finding_action) });
+        }
+    }
+    if config.finding_days > 0 {
+        let scope = if overridden.is_empty() {
+            "TRUE".to_string()
+        } else {
+            format!("category NOT IN ({})", overridden.join(", "))
+        };
+        rules.push(ExpiryRule { scope, days: config.finding_days, action: config.finding_action });
+    }
+    Ok(rules)
+}
+
+/// Days after which the policy deletes a finding at the earliest, or 0 if it never does
+fn shortest_deletion_days(config: &RetentionConfig) -> u32 {
+    let expiring = config
+        .categories
+        .values()
+        .filter(|c| c.action.unwrap_or(config.finding_action) == RetentionAction::Delete)
+        .map(|c| c.days);
+    let general = match config.finding_action {
+        RetentionAction::Delete => config.finding_days,
+        RetentionAction::Anonymize => 0,
+    };
+    [config.resolved_finding_days, config.low_unverified_days, general]
+        .into_iter()
+        .chain(expiring)
+        .filter(|days| *days > 0)
+        .min()
+        .unwrap_or(0)
+}
+
+fn cutoff(days: u32) -> String {
+    format!("-{} days", days)
+}
+
+/// Findings matching `selector` that a legal hold keeps
+fn count_held(connection: &Connection, selector: &str, cutoff: &str) -> Result<usize> {
+    let held: i64 = connection.query_row(
+        &format!("SELECT COUNT(*) FROM secrets WHERE {} AND NOT ({})", selector, NOT_ON_LEGAL_HOLD),
+        params![cutoff],
+        |row| row.get(0),
+    )?;
+    Ok(held as usize)
+}
+
+/// Delete the findings matching `predicate`, with their triage results and analyst data
+fn delete_findings(connection: &Connection, predicate: &str, cutoff: &str) -> Result<usize> {
+    connection.execute(
+        &format!("DELETE FROM triage_results WHERE secret_id IN (SELECT id FROM secrets WHERE {})", predicate),
+        params![cutoff],
+    )?;
+    for table in FINDING_CHILDREN {
+        connection.execute(
+            &format!("DELETE FROM {} WHERE secret_hash IN (SELECT secret_hash FROM secrets WHERE {})", table, predicate),
+            params![cutoff],
+        )?;
+    }
+    Ok(connection.execute(&format!("DELETE FROM secrets WHERE {}", predicate), params![cutoff])?)
+}
+
+/// Delete analyst data and triage results whose finding is gone, e.g. removed by an
+/// older version that did not clean up after itself
+fn delete_orphans(connection: &Connection) -> Result<usize> {
+    let mut deleted = connection.execute(
+        "DELETE FROM triage_results WHERE secret_id IS NULL OR secret_id NOT IN (SELECT id FROM secrets)",
+        [],
+    )?;
+    for table in FINDING_CHILDREN {
+        deleted += connection.execute(
+            &format!(
+                "DELETE FROM {} WHERE secret_hash NOT IN (SELECT secret_hash FROM secrets) AND {}",
+                table, NOT_ON_LEGAL_HOLD
+            ),
+            [],
+        )?;
+    }
+    Ok(deleted)
+}
+
+impl SecretDatabase {
+    /// Apply the retention policy. With `dry_run` the purge runs and is rolled back, so
+    /// the report shows exactly what would have been purged.
+    pub fn purge(&self, config: &RetentionConfig, dry_run: bool) -> Result<PurgeReport> {
+        let expiry = expiry_rules(config)?;
+        let mut report = PurgeReport { dry_run, ..Default::default() };
+        let tx = self.connection.unchecked_transaction()?;
+
+        for rule in &RULES {
+            let days = retention_days(config, rule.name);
+            if days == 0 {
+                continue;
+            }
+            let cutoff = cutoff(days);
+            let predicate = format!("{} AND {}", rule.selector, NOT_ON_LEGAL_HOLD);
+            report.held_skipped += count_held(&tx, rule.selector, &cutoff)?;
+
+            let count = if rule.name == "resolved_findings" {
+                delete_findings(&tx, &predicate, &cutoff)?
+            } else {
+                tx.execute(&rule.action.replace("{}", &predicate), params![cutoff])?
+            };
+            report.set(rule.name, count);
+        }
+
+        if config.low_unverified_days > 0 {
+            let cutoff = cutoff(config.low_unverified_days);
+            let selector = format!("{} AND {}", LOW_UNVERIFIED, EXPIRABLE);
+            report.held_skipped += count_held(&tx, &selector, &cutoff)?;
+            report.low_unverified_deleted =
+                delete_findings(&tx, &format!("{} AND {}", selector, NOT_ON_LEGAL_HOLD), &cutoff)?;
+        }
+
+        for rule in &expiry {
+            let cutoff = cutoff(rule.days);
+            let selector = format!("{} AND created_at < datetime('now', ?) AND {}", rule.scope, EXPIRABLE);
+            let predicate = format!("{} AND {}", selector, NOT_ON_LEGAL_HOLD);
+            report.held_skipped += count_held(&tx, &selector, &cutoff)?;
+
+            let counts = tx
+                .prepare(&format!("SELECT category, COUNT(*) FROM secrets WHERE {} GROUP BY category", predicate))?
+                .query_map(params![cutoff], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            for (category, count) in counts {
+                *report.expired_by_category.entry(category).or_insert(0) += count as usize;
+            }
+
+            match rule.action {
+                RetentionAction::Delete => report.expired_findings_deleted += delete_findings(&tx, &predicate, &cutoff)?,
+                RetentionAction::Anonymize => {
+                    let hashes = tx
+                        .prepare(&format!("SELECT secret_hash FROM secrets WHERE {}", predicate))?
+                        .query_map(params![cutoff], |row| row.get::<_, String>(0))?
+                        .collect::<rusqlite::Result<Vec<_>>>()?;
+                    for hash in &hashes {
+                        report.expired_findings_anonymized += scrub_finding(&tx, hash)?;
+                    }
+                }
+            }
+        }
+
+        if config.orphans {
+            report.orphans_deleted = delete_orphans(&tx)?;
+        }
+
+        if dry_run {
+            tx.rollback()?;
+        } else {
+            tx.commit()?;
+        }
+
+        info!(
+            "{}Retention purge: {} contexts, {} validated secrets, {} resolved, {} expired ({} anonymized), \
+            {} low unverified findings, {} orphans ({} held)",
+            if dry_run { "[dry run] " } else { "" },
+            report.contexts_cleared,
+            report.validated_secrets_cleared,
+            report.resolved_findings_deleted,
+            report.expired_findings_deleted + report.expired_findings_anonymized,
+            report.expired_findings_anonymized,
+            report.low_unverified_deleted,
+            report.orphans_deleted,
+            report.held_skipped
+        );
+        Ok(report)
+    }
+}
+
+/// Run a purge and record it in the audit log. Dry runs are audited too.
+pub fn run_purge(
+    database_path: &str,
+    config: &RetentionConfig,
+    dry_run: bool,
+    audit: &AuditLog,
+    actor: &str,
+) -> Result<PurgeReport> {
+    let db = SecretDatabase::new(database_path)?;
+    if !dry_run {
+        // Count findings into the rollups before they are deleted. Never rebuild a day old
+        // enough to have lost findings to an earlier purge.
+        let window = match shortest_deletion_days(config) {
+            0 => ROLLUP_WINDOW_DAYS,
+            days => ROLLUP_WINDOW_DAYS.min(days.saturating_sub(1)),
+        };
+        db.refresh_rollups(window)?;
+    }
+    let report = db.purge(config, dry_run)?;
+    if !dry_run {
+        AttachmentStore::beside_database(database_path).prune(&db.referenced_attachments()?)?;
+    }
+
+    audit.record(
+        if dry_run { "retention_purge_preview" } else { "retention_purge" },
+        actor,
+        json!({
+            "database": database_path,
+            "policy": config,
+            "report": report,
+        }),
+    )?;
+    Ok(report)
+}
+
+/// Spawn the scheduled purge job. Returns `None` when the interval is zero.
+pub fn spawn_purge_job(database_path: String, config: RetentionConfig, audit: AuditLog) -> Option<JoinHandle<()>> {
+    if config.purge_interval_hours == 0 {
+        return None;
+    }
+
+    Some(tokio::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(config.purge_interval_hours * 3600));
+        loop {
+            ticker.tick().await;
+
+            let path = database_path.clone();
+            let policy = config.clone();
+            let audit = audit.clone();
+            // rusqlite is blocking, keep it off the async workers
+            let result = tokio::task::spawn_blocking(move || run_purge(&path, &policy, false, &audit, "scheduler")).await;
+
+            match result {
+                Ok(Ok(report)) => info!("Scheduled purge removed or scrubbed {} rows", report.total()),
+                Ok(Err(e)) => error!("Scheduled purge failed: {}", e),
+                Err(e) => error!("Scheduled purge task panicked: {}", e),
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::CategoryRetention;
+    use crate::secrets::{SecretMatch, SecretSeverity};
+
+    fn finding(hash: &str, severity: SecretSeverity, category: SecretCategory) -> SecretMatch {
+        SecretMatch {
+            detector_name: "Generic Secret".to_string(),
+            matched_text: format!("secret-{}", hash),
+            start_position: 0,
+            end_position: 20,
+            line_number: Some(1),
+            filename: Some("config.yml".to_string()),
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
+            entropy: 4.0,
+            severity,
+            category,
+            context: String::new(),
+            verified: false,
+            hash: hash.to_string(),
+        }
+    }
+
+    fn policy() -> RetentionConfig {
+        RetentionConfig {
+            context_days: 0,
+            validated_secret_days: 0,
+            resolved_finding_days: 0,
+            purge_interval_hours: 0,
+            finding_days: 180,
+            finding_action: RetentionAction::Anonymize,
+            low_unverified_days: 30,
+            orphans: true,
+            categories: BTreeMap::from([("cloud".to_string(), CategoryRetention { days: 365, action: None })]),
+        }
+    }
+
+    #[test]
+    fn test_purge_expires_by_category_and_dry_run_changes_nothing() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        db.bulk_insert_secrets(&[
+            finding("old-token", SecretSeverity::High, SecretCategory::Token),
+            finding("old-cloud", SecretSeverity::High, SecretCategory::CloudProvider),
+            finding("old-low", SecretSeverity::Low, SecretCategory::Other),
+            finding("held", SecretSeverity::High, SecretCategory::Token),
+            finding("fresh", SecretSeverity::Low, SecretCategory::Token),
+        ])
+        .unwrap();
+        db.connection
+            .execute("UPDATE secrets SET created_at = datetime('now', '-200 days') WHERE secret_hash != 'fresh'", [])
+            .unwrap();
+        db.place_legal_hold("held", "litigation", "legal").unwrap();
+        db.tag_finding("old-low", &["noise".to_string()], "analyst").unwrap();
+        db.connection
+            .execute(
+                "INSERT INTO finding_tags (secret_hash, tag, added_by, added_at) VALUES ('gone', 'stale', 'analyst', datetime('now'))",
+                [],
+            )
+            .unwrap();
+
+        let preview = db.purge(&policy(), true).unwrap();
+        let count = |sql: &str| db.connection.query_row(sql, [], |row| row.get::<_, i64>(0)).unwrap();
+        assert_eq!(count("SELECT COUNT(*) FROM secrets WHERE secret_hash NOT LIKE 'erased:%'"), 5);
+        assert_eq!(count("SELECT COUNT(*) FROM finding_tags"), 2);
+
+        let report = db.purge(&policy(), false).unwrap();
+        for report in [&preview, &report] {
+            assert_eq!(report.expired_findings_anonymized, 1);
+            assert_eq!(report.expired_by_category, BTreeMap::from([("Token".to_string(), 1)]));
+            assert_eq!(report.low_unverified_deleted, 1);
+            assert_eq!(report.orphans_deleted, 1);
+            assert_eq!(report.held_skipped, 1);
+        }
+        assert_eq!(count("SELECT COUNT(*) FROM secrets WHERE secret_hash IN ('old-cloud', 'held', 'fresh')"), 3);
+        assert_eq!(count("SELECT COUNT(*) FROM secrets WHERE secret_hash LIKE 'erased:%'"), 1);
+        assert_eq!(count("SELECT COUNT(*) FROM finding_tags"), 0);
+
+        let unknown = BTreeMap::from([("bogus".to_string(), CategoryRetention { days: 1, action: None })]);
+        assert!(db.purge(&RetentionConfig { categories: unknown, ..policy() }, true).is_err());
+    }
+}
@@ -0,0 +1,431 @@
+use anyhow::{anyhow, Result};
+use rusqlite::types::Value;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info, warn};
+use utoipa::ToSchema;
+
+use crate::core::{build_client, send_with_retry, HttpConfig, RetryPolicy};
+use super::{filter_conditions, SecretDatabase, SecretQueryFilters, SecretRecord, SECRET_RECORD_COLUMNS};
+
+const MATCH_CHUNK: usize = 500;
+
+/// A named filter query, optionally alerting whenever a newly inserted finding matches it
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SavedSearch {
+    pub id: i64,
+    pub name: String,
+    pub owner: String,
+    pub filters: SecretQueryFilters,
+    pub alert: bool,
+    /// Where alerts go; the hunter's webhook endpoints when unset
+    pub webhook_url: Option<String>,
+    pub created_at: String,
+    pub last_matched_at: Option<String>,
+}
+
+/// New findings matching one alerting search that have not been notified yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearchAlert {
+    pub search_id: i64,
+    pub search: String,
+    pub webhook_url: Option<String>,
+    pub findings: Vec<SecretRecord>,
+}
+
+fn validate_search(name: &str, filters: &SecretQueryFilters, webhook_url: Option<&str>) -> Result<()> {
+    if name.trim().is_empty() || name.len() > 128 {
+        return Err(anyhow!("Invalid search name: must be 1-128 characters"));
+    }
+    // Reject bad tags now rather than on every insert
+    filter_conditions(filters)?;
+    if let Some(url) = webhook_url {
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return Err(anyhow!("Invalid search webhook {}: must be an http(s) URL", url));
+        }
+    }
+    Ok(())
+}
+
+impl SecretDatabase {
+    pub(super) fn initialize_saved_searches_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS saved_searches (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT UNIQUE NOT NULL,
+                owner TEXT NOT NULL,
+                filters TEXT NOT NULL,
+                alert BOOLEAN NOT NULL DEFAULT FALSE,
+                webhook_url TEXT,
+                created_at DATETIME NOT NULL,
+                last_matched_at DATETIME
+            )",
+            [],
+        )?;
+
+        // One row per (search, new finding); notified_at is set once the alert went out
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS saved_search_matches (
+                search_id INTEGER NOT NULL,
+                secret_hash TEXT NOT NULL,
+                matched_at DATETIME NOT NULL,
+                notified_at DATETIME,
+                PRIMARY KEY(search_id, secret_hash)
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    /// Create a saved search, or replace the one with the same name
+    pub fn save_search(
+        &self,
+        name: &str,
+        owner: &str,
+        filters: &SecretQueryFilters,
+        alert: bool,
+        webhook_url: Option<&str>,
+    ) -> Result<SavedSearch> {
+        let name = name.trim();
+        validate_search(name, filters, webhook_url)?;
+
+        // The stored query is unbounded; limits only apply when it is run
+        let mut filters = filters.clone();
+        filters.limit = None;
+
+        self.connection.execute(
+            "INSERT INTO saved_searches (name, owner, filters, alert, webhook_url, created_at)
+            VALUES (?, ?, ?, ?, ?, datetime('now'))
+            ON CONFLICT(name) DO UPDATE SET
+                owner = excluded.owner, filters = excluded.filters,
+                alert = excluded.alert, webhook_url = excluded.webhook_url",
+            params![name, owner, serde_json::to_string(&filters)?, alert, webhook_url],
+        )?;
+
+        self.get_saved_search(name)?
+            .ok_or_else(|| anyhow!("Saved search {} vanished after saving", name))
+    }
+
+    pub fn get_saved_search(&self, name: &str) -> Result<Option<SavedSearch>> {
+        let row = self
+            .connection
+            .query_row(
+                "SELECT id, name, owner, filters, alert, webhook_url, created_at, last_matched_at
+                FROM saved_searches WHERE name = ?",
+                params![name],
+                saved_search_row,
+            )
+            .optional()?;
+        row.map(into_saved_search).transpose()
+    }
+
+    pub fn list_saved_searches(&self) -> Result<Vec<SavedSearch>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, name, owner, filters, alert, webhook_url, created_at, last_matched_at
+            FROM saved_searches ORDER BY name",
+        )?;
+        let rows = stmt.query_map([], saved_search_row)?;
+
+        let mut searches = Vec::new();
+        for row in rows {
+            searches.push(into_saved_search(row?)?);
+        }
+        Ok(searches)
+    }
+
+    pub fn delete_saved_search(&self, name: &str) -> Result<bool> {
+        let tx = self.connection.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM saved_search_matches WHERE search_id IN (SELECT id FROM saved_searches WHERE name = ?)",
+            params![name],
+        )?;
+        let deleted = tx.execute("DELETE FROM saved_searches WHERE name = ?", params![name])?;
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+
+    /// Run a saved search over the whole database
+    pub fn run_saved_search(&self, name: &str, limit: Option<u32>) -> Result<Vec<SecretRecord>> {
+        self.run_saved_search_after(name, limit, None)
+    }
+
+    /// A page of a saved search's results, after a `(created_at, id)` keyset position
+    pub fn run_saved_search_after(
+        &self,
+        name: &str,
+        limit: Option<u32>,
+        after: Option<(String, i64)>,
+    ) -> Result<Vec<SecretRecord>> {
+        let search = self
+            .get_saved_search(name)?
+            .ok_or_else(|| anyhow!("No saved search named {}", name))?;
+        let filters = SecretQueryFilters { limit, after, ..search.filters };
+        self.query_secrets(&filters)
+    }
+
+    /// Match newly inserted findings against every alerting search and queue the hits.
+    /// Returns the number of (search, finding) matches recorded.
+    pub(super) fn evaluate_saved_searches(&self, new_hashes: &[String]) -> Result<usize> {
+        if new_hashes.is_empty() {
+            return Ok(0);
+        }
+
+        let mut recorded = 0;
+        for search in self.list_saved_searches()?.into_iter().filter(|s| s.alert) {
+            let (conditions, params) = filter_conditions(&search.filters)?;
+
+            // Chunked to stay under SQLite's bound parameter limit
+            let mut matches: Vec<String> = Vec::new();
+            for chunk in new_hashes.chunks(MATCH_CHUNK) {
+                let placeholders = vec!["?"; chunk.len()].join(", ");
+                let query = format!(
+                    "SELECT secret_hash FROM secrets WHERE secret_hash IN ({}){}",
+                    placeholders, conditions
+                );
+                let mut stmt = self.connection.prepare(&query)?;
+                let bound = chunk.iter().map(|hash| Value::Text(hash.clone())).chain(params.iter().cloned());
+                for hash in stmt.query_map(rusqlite::params_from_iter(bound), |row| row.get(0))? {
+                    matches.push(hash?);
+                }
+            }
+            if matches.is_empty() {
+                continue;
+            }
+
+            for hash in &matches {
+                recorded += self.connection.execute(
+                    "INSERT OR IGNORE INTO saved_search_matches (search_id, secret_hash, matched_at)
+                    VALUES (?, ?, datetime('now'))",
+                    params![search.id, hash],
+                )?;
+            }
+            self.connection.execute(
+                "UPDATE saved_searches SET last_matched_at = datetime('now') WHERE id = ?",
+                params![search.id],
+            )?;
+            warn!("🔎 Saved search {:?} matched {} new findings", search.name, matches.len());
+        }
+
+        debug!("Saved searches recorded {} new matches", recorded);
+        Ok(recorded)
+    }
+
+    /// Matches waiting to be notified, grouped by search
+    pub fn pending_search_alerts(&self) -> Result<Vec<SavedSearchAlert>> {
+        let mut alerts = Vec::new();
+        for search in self.list_saved_searches()?.into_iter().filter(|s| s.alert) {
+            let mut stmt = self.connection.prepare(&format!(
+                "SELECT {} FROM secrets WHERE secret_hash IN (
+                    SELECT secret_hash FROM saved_search_matches WHERE search_id = ? AND notified_at IS NULL
+                ) ORDER BY created_at",
+                SECRET_RECORD_COLUMNS
+            ))?;
+            let findings: Vec<SecretRecord> = stmt
+                .query_map(params![search.id], SecretRecord::from_row)?
+                .collect::<rusqlite::Result<_>>()?;
+
+            if !findings.is_empty() {
+                alerts.push(SavedSearchAlert {
+                    search_id: search.id,
+                    search: search.name,
+                    webhook_url: search.webhook_url,
+                    findings,
+                });
+            }
+        }
+        Ok(alerts)
+    }
+
+    pub fn mark_search_alert_sent(&self, alert: &SavedSearchAlert) -> Result<()> {
+        let tx = self.connection.unchecked_transaction()?;
+        for finding in &alert.findings {
+            tx.execute(
+                "UPDATE saved_search_matches SET notified_at = datetime('now')
+                WHERE search_id = ? AND secret_hash = ?",
+                params![alert.search_id, finding.secret_hash],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// POST pending saved-search alerts to each search's webhook, or to `default_webhooks` when it has none.
+/// Alerts that fail to deliver stay pending and are retried after the next insert.
+pub async fn deliver_search_alerts(db: &SecretDatabase, default_webhooks: &[String]) -> Result<usize> {
+    let alerts = db.pending_search_alerts()?;
+    if alerts.is_empty() {
+        return Ok(0);
+    }
+
+    let client = build_client(&HttpConfig::default())?;
+    let mut delivered = 0;
+    for alert in &alerts {
+        let destinations: Vec<&str> = match &alert.webhook_url {
+            Some(url) => vec![url.as_str()],
+            None => default_webhooks.iter().map(String::as_str).collect(),
+        };
+        let payload = serde_json::json!({
+            "type": "saved_search_match",
+            "search": alert.search,
+            "count": alert.findings.len(),
+            "findings": alert.findings,
+        });
+
+        let mut failed = false;
+        for url in &destinations {
+            let request = client.post(*url).header("User-Agent", "GitHubArchiver/2.0").json(&payload);
+            match send_with_retry(request, &RetryPolicy::default()).await {
+                Ok(response) if response.status().is_success() => {}
+                Ok(response) => {
+                    error!("Saved search webhook {} returned {}", url, response.status());
+                    failed = true;
+                }
+                Err(e) => {
+                    error!("Saved search webhook {} failed: {}", url, e);
+                    failed = true;
+                }
+            }
+        }
+
+        // With no destination the warning logged on match is the notification
+        if !failed {
+            db.mark_search_alert_sent(alert)?;
+            delivered += 1;
+        }
+    }
+
+    info!("Delivered {} of {} saved search alerts", delivered, alerts.len());
+    Ok(delivered)
+}
+
+type SavedSearchRow = (i64, String, String, String, bool, Option<String>, String, Option<String>);
+
+fn saved_search_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SavedSearchRow> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+        row.get(6)?,
+        row.get(7)?,
+    ))
+}
+
+fn into_saved_search(row: SavedSearchRow) -> Result<SavedSearch> {
+    let (id, name, owner, filters, alert, webhook_url, created_at, last_matched_at) = row;
+    Ok(SavedSearch {
+        id,
+        filters: serde_json::from_str(&filters)
+            .map_err(|e| anyhow!("Saved search {} has unreadable filters: {}", name, e))?,
+        name,
+        owner,
+        alert,
+        webhook_url,
+        created_at,
+        last_matched_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::SecretSeverity;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_search_validation() {
+        let filters = SecretQueryFilters {
+            tags: vec!["customer-impact".to_string()],
+            ..Default::default()
+        };
+        assert!(validate_search("aws critical", &filters, Some("https://hooks.example.com/x")).is_ok());
+        assert!(validate_search("", &filters, None).is_err());
+        assert!(validate_search("aws", &filters, Some("ftp://hooks.example.com")).is_err());
+
+        let bad_tag = SecretQueryFilters {
+            tags: vec!["no spaces; allowed!".to_string()],
+            ..Default::default()
+        };
+        assert!(validate_search("aws", &bad_tag, None).is_err());
+    }
+
+    /// A finding in `repository`, stored the way scans store commits
+    fn seed(db: &SecretDatabase, hash: &str, repository: &str, severity: &str) {
+        db.connection
+            .execute(
+                "INSERT INTO commits (commit_sha, repository_name, created_at) VALUES (?, ?, datetime('now'))",
+                params![format!("sha-{}", hash), repository],
+            )
+            .unwrap();
+        db.connection
+            .execute(
+                "INSERT INTO secrets (secret_hash, commit_id, detector_name, matched_text_hash, severity, category, created_at)
+                VALUES (?, last_insert_rowid(), 'AWS Access Key ID', 'x', ?, 'CloudProvider', datetime('now'))",
+                params![hash, severity],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_new_findings_are_matched_against_alerting_searches() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let filters = SecretQueryFilters {
+            repository: Some("Acme/Widgets".to_string()),
+            min_severity: Some(SecretSeverity::High),
+            ..Default::default()
+        };
+        db.save_search("widgets", "alice", &filters, true, None).unwrap();
+        db.save_search("quiet", "alice", &SecretQueryFilters::default(), false, None).unwrap();
+
+        seed(&db, "critical", "Acme/Widgets", "Critical");
+        seed(&db, "low", "Acme/Widgets", "Low");
+        seed(&db, "elsewhere", "acme/gadgets", "Critical");
+        let hashes: Vec<String> = ["critical", "low", "elsewhere"].iter().map(|h| h.to_string()).collect();
+
+        assert_eq!(db.evaluate_saved_searches(&hashes).unwrap(), 1);
+        // A finding is only queued once per search
+        assert_eq!(db.evaluate_saved_searches(&hashes).unwrap(), 0);
+
+        let alerts = db.pending_search_alerts().unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].search, "widgets");
+        assert_eq!(alerts[0].findings.iter().map(|f| f.secret_hash.as_str()).collect::<Vec<_>>(), vec!["critical"]);
+        assert!(db.get_saved_search("widgets").unwrap().unwrap().last_matched_at.is_some());
+        assert_eq!(db.run_saved_search("widgets", None).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delivered_alerts_are_no_longer_pending() {
+        let received = Arc::new(Mutex::new(Vec::<serde_json::Value>::new()));
+        let app = {
+            let received = received.clone();
+            axum::Router::new().route(
+                "/hook",
+                axum::routing::post(move |axum::Json(body): axum::Json<serde_json::Value>| async move {
+                    received.lock().unwrap().push(body);
+                }),
+            )
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let db = SecretDatabase::new(":memory:").unwrap();
+        db.save_search("hooked", "alice", &SecretQueryFilters::default(), true, Some(&url)).unwrap();
+        seed(&db, "h1", "acme/widgets", "High");
+        db.evaluate_saved_searches(&["h1".to_string()]).unwrap();
+
+        assert_eq!(deliver_search_alerts(&db, &[]).await.unwrap(), 1);
+        assert!(db.pending_search_alerts().unwrap().is_empty());
+        assert_eq!(deliver_search_alerts(&db, &[]).await.unwrap(), 0);
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0]["search"], "hooked");
+        assert_eq!(received[0]["findings"][0]["secret_hash"], "h1");
+    }
+}
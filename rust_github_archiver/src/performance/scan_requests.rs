@@ -0,0 +1,245 @@
+use anyhow::{anyhow, Result};
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::SecretDatabase;
+
+/// A running request whose hunter has not finished it after this long is assumed lost
+/// with its hunter and handed out again
+const STALE_RUNNING_HOURS: u32 = 6;
+
+const SCAN_REQUEST_COLUMNS: &str = "id, repository, commit_sha, callback_url, source, requested_by, status, findings,
+    error, created_at, started_at, finished_at, callback_status";
+
+/// A scan of one repository, or one commit of it, asked for by CI, a SOAR playbook or
+/// an analyst, and run by the next hunter to poll the queue
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ScanRequest {
+    pub id: i64,
+    /// `owner/repo` or a clone URL
+    pub repository: String,
+    /// Only the changes this commit introduced are scanned; `None` scans HEAD's history
+    pub commit_sha: Option<String>,
+    /// Notified with a signed JSON summary when the scan finishes
+    pub callback_url: Option<String>,
+    /// System that asked for the scan, e.g. `webhook`, `ci` or `soar`
+    pub source: String,
+    pub requested_by: String,
+    /// `queued`, `running`, `completed` or `failed`
+    pub status: String,
+    /// Active findings, once completed
+    pub findings: Option<i64>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    /// `delivered`, or why the callback could not be delivered
+    pub callback_status: Option<String>,
+}
+
+impl ScanRequest {
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status.as_str(), "completed" | "failed")
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            repository: row.get(1)?,
+            commit_sha: row.get(2)?,
+            callback_url: row.get(3)?,
+            source: row.get(4)?,
+            requested_by: row.get(5)?,
+            status: row.get(6)?,
+            findings: row.get(7)?,
+            error: row.get(8)?,
+            created_at: row.get(9)?,
+            started_at: row.get(10)?,
+            finished_at: row.get(11)?,
+            callback_status: row.get(12)?,
+        })
+    }
+}
+
+/// What a scan trigger asks for
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewScanRequest {
+    pub repository: String,
+    #[serde(default)]
+    pub commit_sha: Option<String>,
+    #[serde(default)]
+    pub callback_url: Option<String>,
+    #[serde(default = "default_source")]
+    pub source: String,
+}
+
+fn default_source() -> String {
+    "api".to_string()
+}
+
+impl NewScanRequest {
+    fn validate(&self) -> Result<()> {
+        let repository = self.repository.trim();
+        if repository.is_empty() || repository.chars().any(char::is_whitespace) {
+            return Err(anyhow!("Invalid repository {:?}", self.repository));
+        }
+        if let Some(commit) = &self.commit_sha {
+            if commit.len() < 7 || commit.len() > 64 || !commit.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(anyhow!("Invalid commit {:?}; give its SHA", commit));
+            }
+        }
+        if let Some(url) = &self.callback_url {
+            if !(url.starts_with("https://") || url.starts_with("http://")) {
+                return Err(anyhow!("Invalid callback URL {}; it must be http(s)", url));
+            }
+        }
+        if self.source.is_empty() || self.source.len() > 64 {
+            return Err(anyhow!("Invalid source {:?}", self.source));
+        }
+        Ok(())
+    }
+}
+
+impl SecretDatabase {
+    pub(super) fn initialize_scan_requests_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS scan_requests (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                repository TEXT NOT NULL,
+                commit_sha TEXT,
+                callback_url TEXT,
+                source TEXT NOT NULL,
+                requested_by TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'queued',
+                findings INTEGER,
+                error TEXT,
+                created_at DATETIME NOT NULL,
+                started_at DATETIME,
+                finished_at DATETIME,
+                callback_status TEXT
+            )",
+            [],
+        )?;
+        self.connection.execute(
+            "CREATE INDEX IF NOT EXISTS idx_scan_requests_status ON scan_requests(status, id)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    pub fn queue_scan_request(&self, request: &NewScanRequest, requested_by: &str) -> Result<ScanRequest> {
+        request.validate()?;
+        self.connection.execute(
+            "INSERT INTO scan_requests (repository, commit_sha, callback_url, source, requested_by, created_at)
+            VALUES (?, ?, ?, ?, ?, datetime('now'))",
+            params![
+                request.repository.trim(),
+                request.commit_sha.as_deref().map(str::to_lowercase),
+                request.callback_url,
+                request.source,
+                requested_by
+            ],
+        )?;
+        let id = self.connection.last_insert_rowid();
+        self.scan_request(id)?.ok_or_else(|| anyhow!("Scan request {} vanished after queueing", id))
+    }
+
+    pub fn scan_request(&self, id: i64) -> Result<Option<ScanRequest>> {
+        Ok(self
+            .connection
+            .query_row(
+                &format!("SELECT {} FROM scan_requests WHERE id = ?", SCAN_REQUEST_COLUMNS),
+                params![id],
+                ScanRequest::from_row,
+            )
+            .optional()?)
+    }
+
+    /// Mark the oldest queued request running and return it
+    pub fn claim_scan_request(&self) -> Result<Option<ScanRequest>> {
+        let claimed = self
+            .connection
+            .query_row(
+                &format!(
+                    "UPDATE scan_requests SET status = 'running', started_at = datetime('now')
+                    WHERE id = (
+                        SELECT id FROM scan_requests
+                        WHERE status = 'queued' OR (status = 'running' AND started_at < datetime('now', ?))
+                        ORDER BY id LIMIT 1
+                    )
+                    RETURNING {}",
+                    SCAN_REQUEST_COLUMNS
+                ),
+                params![format!("-{} hours", STALE_RUNNING_HOURS)],
+                ScanRequest::from_row,
+            )
+            .optional()?;
+        Ok(claimed)
+    }
+
+    /// Record how a claimed request ended: its active findings, or why it failed
+    pub fn finish_scan_request(&self, id: i64, outcome: std::result::Result<usize, String>) -> Result<ScanRequest> {
+        let (status, findings, error) = match outcome {
+            Ok(findings) => ("completed", Some(findings as i64), None),
+            Err(error) => ("failed", None, Some(error)),
+        };
+        self.connection.execute(
+            "UPDATE scan_requests SET status = ?, findings = ?, error = ?, finished_at = datetime('now') WHERE id = ?",
+            params![status, findings, error, id],
+        )?;
+        self.scan_request(id)?.ok_or_else(|| anyhow!("Scan request {} not found", id))
+    }
+
+    pub fn set_scan_callback_status(&self, id: i64, callback_status: &str) -> Result<()> {
+        self.connection.execute(
+            "UPDATE scan_requests SET callback_status = ? WHERE id = ?",
+            params![callback_status, id],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(repository: &str) -> NewScanRequest {
+        NewScanRequest {
+            repository: repository.to_string(),
+            commit_sha: Some("A1B2C3D4E5F6".to_string()),
+            callback_url: Some("https://soar.example.com/hooks/scan".to_string()),
+            source: "webhook".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_requests_are_claimed_in_order_once() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let first = db.queue_scan_request(&request("acme/api"), "ci-bot").unwrap();
+        db.queue_scan_request(&request("acme/web"), "ci-bot").unwrap();
+        assert_eq!(first.status, "queued");
+        assert_eq!(first.commit_sha.as_deref(), Some("a1b2c3d4e5f6"));
+
+        let claimed = db.claim_scan_request().unwrap().unwrap();
+        assert_eq!((claimed.id, claimed.status.as_str()), (first.id, "running"));
+        assert_eq!(db.claim_scan_request().unwrap().unwrap().repository, "acme/web");
+        assert!(db.claim_scan_request().unwrap().is_none());
+
+        let finished = db.finish_scan_request(first.id, Ok(2)).unwrap();
+        assert!(finished.is_finished());
+        assert_eq!(finished.findings, Some(2));
+    }
+
+    #[test]
+    fn test_invalid_requests_are_refused() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let mut bad = request("acme/api");
+        bad.commit_sha = Some("main".to_string());
+        assert!(db.queue_scan_request(&bad, "ci-bot").is_err());
+        let mut bad = request("acme/api");
+        bad.callback_url = Some("file:///etc/passwd".to_string());
+        assert!(db.queue_scan_request(&bad, "ci-bot").is_err());
+        assert!(db.queue_scan_request(&request("not a repo"), "ci-bot").is_err());
+    }
+}
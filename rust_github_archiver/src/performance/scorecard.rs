@@ -0,0 +1,289 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use tera::{Context, Tera};
+use utoipa::ToSchema;
+
+use super::{normalize_organization, SecretDatabase, NOT_SYNTHETIC};
+
+/// Repository tags of this form attribute a repository to a team, e.g. `team:payments`
+pub const TEAM_TAG_PREFIX: &str = "team:";
+
+const SCORECARD_TEMPLATE: &str = include_str!("templates/scorecard.html");
+
+/// Days covered by a scorecard, both ends included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ScorecardPeriod {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl ScorecardPeriod {
+    pub fn month(year: i32, month: u32) -> Result<Self> {
+        let start = NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(|| anyhow!("Invalid month {}-{}", year, month))?;
+        let next = if month == 12 { NaiveDate::from_ymd_opt(year + 1, 1, 1) } else { NaiveDate::from_ymd_opt(year, month + 1, 1) };
+        Ok(Self { start, end: next.expect("first of a month is a valid date") - Duration::days(1) })
+    }
+
+    /// `YYYY-MM`
+    pub fn parse_month(value: &str) -> Result<Self> {
+        let (year, month) = value
+            .split_once('-')
+            .and_then(|(year, month)| Some((year.parse().ok()?, month.parse().ok()?)))
+            .ok_or_else(|| anyhow!("Expected a month as YYYY-MM, got {:?}", value))?;
+        Self::month(year, month)
+    }
+
+    /// The last complete month before `today`, the usual monthly reporting period
+    pub fn last_month(today: NaiveDate) -> Self {
+        let first = today.with_day(1).expect("day 1 exists in every month");
+        let previous = first - Duration::days(1);
+        Self::month(previous.year(), previous.month()).expect("valid month")
+    }
+
+    /// The month before this one, for the period-over-period comparison
+    pub fn previous(&self) -> Self {
+        Self::last_month(self.start)
+    }
+
+    fn bounds(&self) -> (String, String) {
+        (self.start.to_string(), self.end.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ScorecardTotals {
+    /// Active findings; suppressed and synthetic ones are left out
+    pub findings: i64,
+    pub verified: i64,
+    pub suppressed: i64,
+    /// Distinct commits pushed, counted from real-time push events
+    pub commits: i64,
+    /// `None` when no commits were counted for the period
+    pub findings_per_1k_commits: Option<f64>,
+    pub verified_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct RevocationTimes {
+    /// Findings marked `revoked` during the period
+    pub revoked: i64,
+    /// From detection to revocation
+    pub mean_hours_to_revoke: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ScorecardRepository {
+    pub repository: String,
+    pub findings: i64,
+    pub verified: i64,
+    pub commits: i64,
+    /// From the repository metadata sync
+    pub risk_score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ScorecardTeam {
+    pub team: String,
+    pub repositories: i64,
+    pub findings: i64,
+    pub verified: i64,
+}
+
+/// Monthly exposure of one organization, for reporting to security leadership
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrganizationScorecard {
+    pub organization: String,
+    pub period: ScorecardPeriod,
+    pub generated_at: DateTime<Utc>,
+    pub totals: ScorecardTotals,
+    /// The same totals for the month before
+    pub previous: ScorecardTotals,
+    pub revocation: RevocationTimes,
+    pub top_repositories: Vec<ScorecardRepository>,
+    /// Repositories tagged `team:<name>`, grouped by team
+    pub top_teams: Vec<ScorecardTeam>,
+}
+
+fn per_1k(count: i64, commits: i64) -> Option<f64> {
+    (commits > 0).then(|| count as f64 * 1000.0 / commits as f64)
+}
+
+impl SecretDatabase {
+    /// Counts come from the finding rollups, so findings purged since still count
+    pub fn organization_scorecard(
+        &self,
+        organization: &str,
+        period: ScorecardPeriod,
+        limit: u32,
+    ) -> Result<OrganizationScorecard> {
+        let organization = normalize_organization(organization)?;
+        // Organization names cannot contain LIKE wildcards
+        let pattern = format!("{}/%", organization);
+
+        Ok(OrganizationScorecard {
+            totals: self.scorecard_totals(&pattern, period)?,
+            previous: self.scorecard_totals(&pattern, period.previous())?,
+            revocation: self.revocation_times(&pattern, period)?,
+            top_repositories: self.scorecard_repositories(&pattern, period, limit)?,
+            top_teams: self.scorecard_teams(&pattern, period, limit)?,
+            organization,
+            period,
+            generated_at: Utc::now(),
+        })
+    }
+
+    fn scorecard_totals(&self, pattern: &str, period: ScorecardPeriod) -> Result<ScorecardTotals> {
+        let (start, end) = period.bounds();
+        let (findings, verified, suppressed): (i64, i64, i64) = self.connection.query_row(
+            "SELECT COALESCE(SUM(findings), 0), COALESCE(SUM(verified), 0), COALESCE(SUM(suppressed), 0)
+            FROM finding_rollups WHERE day BETWEEN ?1 AND ?2 AND LOWER(repository_name) LIKE ?3",
+            params![start, end, pattern],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        let commits: i64 = self.connection.query_row(
+            "SELECT COALESCE(SUM(commits), 0) FROM commit_activity
+            WHERE day BETWEEN ?1 AND ?2 AND LOWER(repository_name) LIKE ?3",
+            params![start, end, pattern],
+            |row| row.get(0),
+        )?;
+
+        Ok(ScorecardTotals {
+            findings,
+            verified,
+            suppressed,
+            commits,
+            findings_per_1k_commits: per_1k(findings, commits),
+            verified_rate: (findings > 0).then(|| verified as f64 / findings as f64),
+        })
+    }
+
+    fn revocation_times(&self, pattern: &str, period: ScorecardPeriod) -> Result<RevocationTimes> {
+        let (start, end) = period.bounds();
+        let times = self.connection.query_row(
+            &format!(
+                "SELECT COUNT(*), AVG((julianday(s.updated_at) - julianday(s.created_at)) * 24)
+                FROM secrets s JOIN commits c ON c.id = s.commit_id
+                WHERE s.validation_status = 'revoked' AND s.updated_at IS NOT NULL AND NOT s.suppressed
+                AND date(s.updated_at) BETWEEN ?1 AND ?2 AND LOWER(c.repository_name) LIKE ?3 AND {}",
+                NOT_SYNTHETIC
+            ),
+            params![start, end, pattern],
+            |row| Ok(RevocationTimes { revoked: row.get(0)?, mean_hours_to_revoke: row.get(1)? }),
+        )?;
+        Ok(times)
+    }
+
+    fn scorecard_repositories(&self, pattern: &str, period: ScorecardPeriod, limit: u32) -> Result<Vec<ScorecardRepository>> {
+        let (start, end) = period.bounds();
+        let mut stmt = self.connection.prepare(
+            "SELECT r.repository_name, SUM(r.findings) AS total, SUM(r.verified),
+                (SELECT COALESCE(SUM(ca.commits), 0) FROM commit_activity ca
+                    WHERE ca.repository_name = r.repository_name AND ca.day BETWEEN ?1 AND ?2),
+                (SELECT risk_score FROM repositories WHERE name = r.repository_name)
+            FROM finding_rollups r
+            WHERE r.day BETWEEN ?1 AND ?2 AND LOWER(r.repository_name) LIKE ?3
+            GROUP BY r.repository_name HAVING total > 0
+            ORDER BY total DESC, r.repository_name LIMIT ?4",
+        )?;
+        let rows = stmt.query_map(params![start, end, pattern, limit], |row| {
+            Ok(ScorecardRepository {
+                repository: row.get(0)?,
+                findings: row.get(1)?,
+                verified: row.get(2)?,
+                commits: row.get(3)?,
+                risk_score: row.get(4)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    fn scorecard_teams(&self, pattern: &str, period: ScorecardPeriod, limit: u32) -> Result<Vec<ScorecardTeam>> {
+        let (start, end) = period.bounds();
+        let mut stmt = self.connection.prepare(
+            "SELECT SUBSTR(rt.tag, ?5), COUNT(DISTINCT r.repository_name), SUM(r.findings) AS total, SUM(r.verified)
+            FROM finding_rollups r
+            JOIN repository_tags rt ON rt.repository_name = LOWER(r.repository_name)
+            WHERE rt.tag LIKE ?6 AND r.day BETWEEN ?1 AND ?2 AND LOWER(r.repository_name) LIKE ?3
+            GROUP BY rt.tag HAVING total > 0
+            ORDER BY total DESC, rt.tag LIMIT ?4",
+        )?;
+        let rows = stmt.query_map(
+            params![start, end, pattern, limit, TEAM_TAG_PREFIX.len() as i64 + 1, format!("{}%", TEAM_TAG_PREFIX)],
+            |row| {
+                Ok(ScorecardTeam {
+                    team: row.get(0)?,
+                    repositories: row.get(1)?,
+                    findings: row.get(2)?,
+                    verified: row.get(3)?,
+                })
+            },
+        )?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+/// One HTML page with a section per organization, self-contained so it can be mailed or archived
+pub fn render_scorecards_html(scorecards: &[OrganizationScorecard]) -> Result<String> {
+    let mut tera = Tera::default();
+    // The `.html` name turns on autoescaping of repository and team names
+    tera.add_raw_template("scorecard.html", SCORECARD_TEMPLATE)
+        .map_err(|e| anyhow!("Invalid scorecard template: {:?}", e))?;
+
+    let mut context = Context::new();
+    context.insert("scorecards", scorecards);
+    context.insert("generated_at", &Utc::now().format("%Y-%m-%d %H:%M UTC").to_string());
+    tera.render("scorecard.html", &context)
+        .map_err(|e| anyhow!("Failed to render scorecard: {:?}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_periods() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 15).unwrap();
+        let period = ScorecardPeriod::last_month(today);
+        assert_eq!(period, ScorecardPeriod::parse_month("2026-02").unwrap());
+        assert_eq!(period.end, NaiveDate::from_ymd_opt(2026, 2, 28).unwrap());
+        assert_eq!(period.previous(), ScorecardPeriod::parse_month("2026-01").unwrap());
+        assert_eq!(ScorecardPeriod::parse_month("2025-12").unwrap().end.to_string(), "2025-12-31");
+        assert!(ScorecardPeriod::parse_month("2026-13").is_err());
+    }
+
+    #[test]
+    fn test_scorecard_from_rollups() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let period = ScorecardPeriod::parse_month("2026-02").unwrap();
+        for (day, repository, findings, verified) in
+            [("2026-02-03", "Acme/api", 3, 1), ("2026-02-10", "acme/web", 1, 0), ("2026-02-11", "other/api", 5, 5)]
+        {
+            db.connection
+                .execute(
+                    "INSERT INTO finding_rollups
+                    (day, detector_name, repository_name, severity, findings, verified, resolved, resolution_seconds, suppressed)
+                    VALUES (?, 'AWS Access Key ID', ?, 'Critical', ?, ?, 0, 0, 0)",
+                    params![day, repository, findings, verified],
+                )
+                .unwrap();
+        }
+        let commits = [(("Acme/api".to_string(), NaiveDate::from_ymd_opt(2026, 2, 3).unwrap()), 2000)];
+        db.record_commit_activity(&commits.into_iter().collect()).unwrap();
+        db.tag_repository("acme/api", &["team:platform".to_string()], "alice").unwrap();
+
+        let scorecard = db.organization_scorecard("ACME", period, 10).unwrap();
+        assert_eq!(scorecard.totals.findings, 4);
+        assert_eq!(scorecard.totals.findings_per_1k_commits, Some(2.0));
+        assert_eq!(scorecard.totals.verified_rate, Some(0.25));
+        assert_eq!(scorecard.previous.findings, 0);
+        assert_eq!(scorecard.top_repositories[0].repository, "Acme/api");
+        assert_eq!(scorecard.top_repositories[0].commits, 2000);
+        assert_eq!(scorecard.top_teams[0].team, "platform");
+
+        let html = render_scorecards_html(&[scorecard]).unwrap();
+        assert!(html.contains("Acme/api"));
+        assert!(html.contains("platform"));
+    }
+}
@@ -0,0 +1,194 @@
+use anyhow::{anyhow, Result};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use utoipa::ToSchema;
+
+use super::{SecretDatabase, SecretRecord, NOT_SYNTHETIC};
+
+/// Most recent findings compared against; older ones are not considered
+const CANDIDATE_LIMIT: u32 = 5000;
+/// Findings scoring less than this are not similar enough to list
+const MIN_SCORE: f64 = 0.25;
+/// Path similarity worth naming as a reason
+const SIMILAR_PATH: f64 = 0.5;
+
+const CONTEXT_WEIGHT: f64 = 0.4;
+const PATH_WEIGHT: f64 = 0.3;
+const DETECTOR_WEIGHT: f64 = 0.2;
+const REPOSITORY_WEIGHT: f64 = 0.05;
+const CATEGORY_WEIGHT: f64 = 0.05;
+
+/// `SECRET_RECORD_COLUMNS` of `secrets s`, followed by the compared columns
+const CANDIDATE_COLUMNS: &str = "s.id, s.secret_hash, s.detector_name, s.filename, s.line_number, s.entropy, s.severity,
+    s.category, s.verified, s.created_at, s.suppressed, s.context_hash, c.repository_name";
+
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    let padded: Vec<char> = format!("  {} ", text.to_lowercase()).chars().collect();
+    padded.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+/// Share of trigrams two strings have in common (Jaccard), from 0 to 1
+pub fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (trigrams(a), trigrams(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// A finding like another one, with why
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SimilarFinding {
+    pub finding: SecretRecord,
+    /// 0 to 1; identical context and path with the same detector scores 1
+    pub score: f64,
+    /// e.g. `same context`, `similar path (83%)`, `same detector`
+    pub reasons: Vec<String>,
+}
+
+struct Compared {
+    detector_name: String,
+    filename: Option<String>,
+    category: String,
+    context_hash: Option<String>,
+    repository: Option<String>,
+}
+
+fn compare(target: &Compared, other: &Compared) -> (f64, Vec<String>) {
+    let mut score = 0.0;
+    let mut reasons = Vec::new();
+    if target.context_hash.is_some() && target.context_hash == other.context_hash {
+        score += CONTEXT_WEIGHT;
+        reasons.push("same context".to_string());
+    }
+    if let (Some(a), Some(b)) = (&target.filename, &other.filename) {
+        let similarity = trigram_similarity(a, b);
+        score += PATH_WEIGHT * similarity;
+        if a == b {
+            reasons.push("same path".to_string());
+        } else if similarity >= SIMILAR_PATH {
+            reasons.push(format!("similar path ({:.0}%)", similarity * 100.0));
+        }
+    }
+    if target.detector_name == other.detector_name {
+        score += DETECTOR_WEIGHT;
+        reasons.push("same detector".to_string());
+    }
+    if target.repository.is_some() && target.repository == other.repository {
+        score += REPOSITORY_WEIGHT;
+        reasons.push("same repository".to_string());
+    }
+    if target.category == other.category {
+        score += CATEGORY_WEIGHT;
+    }
+    (score, reasons)
+}
+
+/// `%/name.ext`-style pattern matching files with the same name in any directory
+fn same_name_pattern(filename: &str) -> String {
+    let name = filename.rsplit('/').next().unwrap_or(filename);
+    format!("%{}", name.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"))
+}
+
+impl SecretDatabase {
+    /// Findings most like the one with `secret_hash`, best first: same surrounding code
+    /// (by context hash), similar file path (trigram similarity), same detector, same
+    /// repository. Meant for triage, e.g. to find every instance of a new kind of false positive.
+    pub fn similar_findings(&self, secret_hash: &str, limit: usize) -> Result<Vec<SimilarFinding>> {
+        let target = self
+            .connection
+            .query_row(
+                "SELECT s.detector_name, s.filename, s.category, s.context_hash, c.repository_name
+                FROM secrets s LEFT JOIN commits c ON c.id = s.commit_id WHERE s.secret_hash = ?",
+                [secret_hash],
+                |row| {
+                    Ok(Compared {
+                        detector_name: row.get(0)?,
+                        filename: row.get(1)?,
+                        category: row.get(2)?,
+                        context_hash: row.get(3)?,
+                        repository: row.get(4)?,
+                    })
+                },
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("No finding {}", secret_hash))?;
+
+        let mut stmt = self.connection.prepare(&format!(
+            "SELECT {} FROM secrets s LEFT JOIN commits c ON c.id = s.commit_id
+            WHERE s.secret_hash != ?1 AND s.{}
+                AND (s.detector_name = ?2 OR s.category = ?3 OR s.context_hash = ?4 OR s.filename LIKE ?5 ESCAPE '\\')
+            ORDER BY s.created_at DESC, s.id DESC LIMIT ?6",
+            CANDIDATE_COLUMNS, NOT_SYNTHETIC
+        ))?;
+        let name_pattern = target.filename.as_deref().map(same_name_pattern);
+        let candidates = stmt
+            .query_map(
+                params![secret_hash, target.detector_name, target.category, target.context_hash, name_pattern, CANDIDATE_LIMIT],
+                |row| {
+                    let finding = SecretRecord::from_row(row)?;
+                    let compared = Compared {
+                        detector_name: finding.detector_name.clone(),
+                        filename: finding.filename.clone(),
+                        category: row.get(7)?,
+                        context_hash: row.get(11)?,
+                        repository: row.get(12)?,
+                    };
+                    Ok((finding, compared))
+                },
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut similar: Vec<SimilarFinding> = candidates
+            .into_iter()
+            .filter_map(|(finding, compared)| {
+                let (score, reasons) = compare(&target, &compared);
+                (score >= MIN_SCORE).then(|| SimilarFinding { finding, score: (score * 100.0).round() / 100.0, reasons })
+            })
+            .collect();
+        similar.sort_by(|a, b| b.score.total_cmp(&a.score));
+        similar.truncate(limit);
+        Ok(similar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigram_similarity() {
+        assert_eq!(trigram_similarity("config/.env", "config/.env"), 1.0);
+        let near = trigram_similarity("tests/fixtures/aws.json", "tests/fixtures/gcp.json");
+        let far = trigram_similarity("tests/fixtures/aws.json", "src/main.rs");
+        assert!(near > 0.5 && far < 0.2, "{} {}", near, far);
+        assert_eq!(trigram_similarity("", ""), 0.0);
+    }
+
+    #[test]
+    fn test_similar_findings_rank_by_context_and_path() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let insert = "INSERT INTO secrets (secret_hash, detector_name, matched_text_hash, filename, severity, category, context_hash, created_at)
+            VALUES (?, ?, '', ?, 'High', ?, ?, datetime('now'))";
+        let rows = [
+            ("target", "AWS Access Key ID", "tests/fixtures/aws.json", "CloudProvider", Some("ctx")),
+            ("twin", "AWS Access Key ID", "tests/fixtures/aws_eu.json", "CloudProvider", Some("ctx")),
+            ("cousin", "AWS Access Key ID", "deploy/prod.env", "CloudProvider", None),
+            ("stranger", "Slack Token", "src/chat.rs", "Communication", None),
+        ];
+        for (hash, detector, filename, category, context) in rows {
+            db.connection.execute(insert, params![hash, detector, filename, category, context]).unwrap();
+        }
+
+        let similar = db.similar_findings("target", 10).unwrap();
+        let hashes: Vec<&str> = similar.iter().map(|s| s.finding.secret_hash.as_str()).collect();
+        assert_eq!(hashes, ["twin", "cousin"]);
+        assert!(similar[0].reasons.contains(&"same context".to_string()));
+        assert!(similar[0].reasons.iter().any(|r| r.starts_with("similar path")));
+
+        assert_eq!(db.similar_findings("target", 1).unwrap().len(), 1);
+        assert!(db.similar_findings("missing", 10).unwrap_err().to_string().starts_with("No finding"));
+    }
+}
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use rusqlite::{params, DatabaseName};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use super::SecretDatabase;
+use crate::core::slow_queries::{explainable, slow_query_threshold, SlowQuery};
+
+/// Statements waiting to be explained and written, at most this many
+const MAX_PENDING: usize = 1000;
+
+/// Slow statements seen by the profile hook. The hook cannot reach its connection, so they
+/// are written by the next `record_slow_queries` on a writable one.
+static PENDING: Mutex<Vec<(String, Duration)>> = Mutex::new(Vec::new());
+
+/// Profile hook installed on writable connections; runs after every statement
+pub(super) fn profile_statement(sql: &str, elapsed: Duration) {
+    let Some(threshold) = slow_query_threshold() else {
+        return;
+    };
+    if elapsed < threshold || !explainable(sql) {
+        return;
+    }
+    if let Ok(mut pending) = PENDING.lock() {
+        if pending.len() < MAX_PENDING {
+            pending.push((sql.to_string(), elapsed));
+        }
+    }
+}
+
+impl SecretDatabase {
+    pub(super) fn initialize_slow_queries_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS slow_queries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                backend TEXT NOT NULL,
+                sql TEXT NOT NULL,
+                duration_ms REAL NOT NULL,
+                plan TEXT,
+                recorded_at DATETIME NOT NULL
+            )",
+            [],
+        )?;
+        self.connection.execute(
+            "CREATE INDEX IF NOT EXISTS idx_slow_queries_recorded_at ON slow_queries(recorded_at)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Explain the slow statements seen since the last call and write them to the slow
+    /// query log. Read-only connections leave them for a writable one.
+    pub fn record_slow_queries(&self) -> Result<usize> {
+        if self.connection.is_readonly(DatabaseName::Main)? {
+            return Ok(0);
+        }
+        let pending = match PENDING.lock() {
+            Ok(mut pending) if !pending.is_empty() => std::mem::take(&mut *pending),
+            _ => return Ok(0),
+        };
+
+        for (sql, elapsed) in &pending {
+            let plan = match self.query_plan(sql) {
+                Ok(plan) => Some(plan),
+                Err(e) => {
+                    debug!("Could not explain slow query: {}", e);
+                    None
+                }
+            };
+            warn!("Slow query ({:.0} ms): {}", elapsed.as_secs_f64() * 1000.0, sql);
+            self.record_slow_query("sqlite", sql, elapsed.as_secs_f64() * 1000.0, plan.as_deref())?;
+        }
+        Ok(pending.len())
+    }
+
+    /// Add one statement to the slow query log, e.g. one timed against the archive database
+    pub fn record_slow_query(&self, backend: &str, sql: &str, duration_ms: f64, plan: Option<&str>) -> Result<()> {
+        self.connection.execute(
+            "INSERT INTO slow_queries (backend, sql, duration_ms, plan, recorded_at)
+            VALUES (?, ?, ?, ?, datetime('now'))",
+            params![backend, sql, duration_ms, plan],
+        )?;
+        Ok(())
+    }
+
+    /// `EXPLAIN QUERY PLAN` output, one `detail` per line indented by depth. Unbound
+    /// parameters are explained as NULL.
+    fn query_plan(&self, sql: &str) -> Result<String> {
+        let mut stmt = self.connection.prepare(&format!("EXPLAIN QUERY PLAN {}", sql))?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(3)?)))?;
+
+        let mut depths: Vec<(i64, usize)> = Vec::new();
+        let mut lines = Vec::new();
+        for row in rows {
+            let (id, parent, detail) = row?;
+            let depth = depths.iter().find(|(node, _)| *node == parent).map_or(0, |(_, depth)| depth + 1);
+            depths.push((id, depth));
+            lines.push(format!("{}{}", "  ".repeat(depth), detail));
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// Most recent slow statements, newest first
+    pub fn slow_queries(&self, limit: usize) -> Result<Vec<SlowQuery>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, backend, sql, duration_ms, plan, recorded_at FROM slow_queries
+            ORDER BY recorded_at DESC, id DESC LIMIT ?",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            Ok(SlowQuery {
+                id: row.get(0)?,
+                backend: row.get(1)?,
+                sql: row.get(2)?,
+                duration_ms: row.get(3)?,
+                plan: row.get(4)?,
+                recorded_at: row.get(5)?,
+            })
+        })?;
+
+        let mut queries = Vec::new();
+        for row in rows {
+            queries.push(row?);
+        }
+        Ok(queries)
+    }
+
+    /// `(table, leading column)` of every index in the findings database
+    pub fn indexed_columns(&self) -> Result<HashSet<(String, String)>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT m.name, ii.name FROM sqlite_master m
+            JOIN pragma_index_list(m.name) il
+            JOIN pragma_index_info(il.name) ii
+            WHERE m.type = 'table' AND ii.seqno = 0",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?.to_lowercase(), row.get::<_, String>(1)?.to_lowercase())))?;
+
+        let mut indexed = HashSet::new();
+        for row in rows {
+            indexed.insert(row?);
+        }
+        Ok(indexed)
+    }
+}
+
+impl Drop for SecretDatabase {
+    fn drop(&mut self) {
+        if let Err(e) = self.record_slow_queries() {
+            debug!("Failed to record slow queries: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slow_statements_are_recorded_with_their_plan() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        let sql = "SELECT * FROM secrets WHERE detector_name = ?";
+        let plan = db.query_plan(sql).unwrap();
+        assert!(plan.contains("secrets"));
+
+        db.record_slow_query("sqlite", sql, 900.0, Some(&plan)).unwrap();
+        let recorded = db.slow_queries(10).unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!((recorded[0].backend.as_str(), recorded[0].duration_ms), ("sqlite", 900.0));
+        assert_eq!(recorded[0].plan.as_deref(), Some(plan.as_str()));
+
+        assert!(db.indexed_columns().unwrap().contains(&("slow_queries".to_string(), "recorded_at".to_string())));
+        // The log's own statements are never queued
+        assert!(!explainable("INSERT INTO slow_queries (sql) VALUES (?)"));
+    }
+}
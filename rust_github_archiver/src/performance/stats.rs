@@ -0,0 +1,279 @@
+use anyhow::Result;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use utoipa::ToSchema;
+
+use super::{SecretDatabase, NOT_SYNTHETIC};
+
+/// Days of rollups rebuilt from live findings on a full refresh. Older days are frozen,
+/// so findings removed by the retention purge still count towards historical stats.
+pub const ROLLUP_WINDOW_DAYS: u32 = 30;
+
+/// Rollup key used for findings whose commit (and so repository) is unknown
+const UNKNOWN_REPOSITORY: &str = "";
+
+/// Finding and verification counts for one detector or repository. `findings` and
+/// `verified` count active findings; allowlisted ones are only in `suppressed`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StatCount {
+    pub key: String,
+    pub findings: i64,
+    pub verified: i64,
+    pub suppressed: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrendPoint {
+    pub day: String,
+    pub findings: i64,
+    pub verified: i64,
+    pub suppressed: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ValidationRatio {
+    pub findings: i64,
+    pub verified: i64,
+    /// `verified / findings`, 0 when there are no findings
+    pub ratio: f64,
+}
+
+/// Mean time to remediation: creation to `resolved`/`revoked`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct Mttr {
+    pub resolved: i64,
+    pub mean_hours: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StatsSummary {
+    pub days: u32,
+    pub top_detectors: Vec<StatCount>,
+    pub top_repositories: Vec<StatCount>,
+    pub trend: Vec<TrendPoint>,
+    pub validation: ValidationRatio,
+    pub mttr: Mttr,
+    /// Findings in the window suppressed by the allowlist
+    pub suppressed: i64,
+}
+
+fn since(days: u32) -> String {
+    format!("-{} days", days)
+}
+
+impl SecretDatabase {
+    pub(super) fn initialize_stats_schema(&self) -> Result<()> {
+        // Daily counts per detector, repository and severity; dashboards read only this table
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS finding_rollups (
+                day DATE NOT NULL,
+                detector_name TEXT NOT NULL,
+                repository_name TEXT NOT NULL,
+                severity TEXT NOT NULL,
+                findings INTEGER NOT NULL,
+                verified INTEGER NOT NULL,
+                resolved INTEGER NOT NULL,
+                resolution_seconds REAL NOT NULL,
+                PRIMARY KEY(day, detector_name, repository_name, severity)
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Rebuild the rollups for the last `days` days (0 = today only) from the findings tables
+    pub fn refresh_rollups(&self, days: u32) -> Result<usize> {
+        let cutoff = since(days);
+        let tx = self.connection.unchecked_transaction()?;
+
+        tx.execute("DELETE FROM finding_rollups WHERE day >= date('now', ?)", params![cutoff])?;
+        let rows = tx.execute(
+            &format!("INSERT INTO finding_rollups
+                (day, detector_name, repository_name, severity, findings, verified, resolved, resolution_seconds, suppressed)
+            SELECT
+                date(s.created_at),
+                s.detector_name,
+                COALESCE(c.repository_name, ?),
+                s.severity,
+                SUM(CASE WHEN NOT s.suppressed THEN 1 ELSE 0 END),
+                SUM(CASE WHEN s.verified AND NOT s.suppressed THEN 1 ELSE 0 END),
+                SUM(CASE WHEN s.validation_status IN ('resolved', 'revoked') AND NOT s.suppressed THEN 1 ELSE 0 END),
+                COALESCE(SUM(CASE WHEN s.validation_status IN ('resolved', 'revoked') AND NOT s.suppressed
+                    AND s.updated_at IS NOT NULL
+                    THEN (julianday(s.updated_at) - julianday(s.created_at)) * 86400 END), 0),
+                SUM(CASE WHEN s.suppressed THEN 1 ELSE 0 END)
+            FROM secrets s
+            LEFT JOIN commits c ON c.id = s.commit_id
+            WHERE date(s.created_at) >= date('now', ?) AND {}
+            GROUP BY 1, 2, 3, 4", NOT_SYNTHETIC),
+            params![UNKNOWN_REPOSITORY, cutoff],
+        )?;
+        tx.commit()?;
+
+        debug!("Rebuilt {} rollup rows for the last {} days", rows, days);
+        Ok(rows)
+    }
+
+    pub fn top_detectors(&self, days: u32, limit: u32) -> Result<Vec<StatCount>> {
+        self.top_by("detector_name", days, limit)
+    }
+
+    /// Findings with no known repository are left out
+    pub fn top_repositories(&self, days: u32, limit: u32) -> Result<Vec<StatCount>> {
+        self.top_by("repository_name", days, limit)
+    }
+
+    fn top_by(&self, column: &str, days: u32, limit: u32) -> Result<Vec<StatCount>> {
+        let mut stmt = self.connection.prepare(&format!(
+            "SELECT {column}, SUM(findings) AS total, SUM(verified), SUM(suppressed)
+            FROM finding_rollups
+            WHERE day >= date('now', ?) AND {column} != ?
+            GROUP BY {column}
+            ORDER BY total DESC, {column}
+            LIMIT ?",
+            column = column
+        ))?;
+        let rows = stmt.query_map(params![since(days), UNKNOWN_REPOSITORY, limit], |row| {
+            Ok(StatCount {
+                key: row.get(0)?,
+                findings: row.get(1)?,
+                verified: row.get(2)?,
+                suppressed: row.get(3)?,
+            })
+        })?;
+
+        let mut counts = Vec::new();
+        for row in rows {
+            counts.push(row?);
+        }
+        Ok(counts)
+    }
+
+    /// Findings per day, oldest first; days without findings are omitted
+    pub fn findings_trend(&self, days: u32) -> Result<Vec<TrendPoint>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT day, SUM(findings), SUM(verified), SUM(suppressed)
+            FROM finding_rollups
+            WHERE day >= date('now', ?)
+            GROUP BY day
+            ORDER BY day",
+        )?;
+        let rows = stmt.query_map(params![since(days)], |row| {
+            Ok(TrendPoint {
+                day: row.get(0)?,
+                findings: row.get(1)?,
+                verified: row.get(2)?,
+                suppressed: row.get(3)?,
+            })
+        })?;
+
+        let mut trend = Vec::new();
+        for row in rows {
+            trend.push(row?);
+        }
+        Ok(trend)
+    }
+
+    pub fn validation_ratio(&self, days: u32) -> Result<ValidationRatio> {
+        let (findings, verified): (i64, i64) = self.connection.query_row(
+            "SELECT COALESCE(SUM(findings), 0), COALESCE(SUM(verified), 0)
+            FROM finding_rollups WHERE day >= date('now', ?)",
+            params![since(days)],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let ratio = if findings > 0 { verified as f64 / findings as f64 } else { 0.0 };
+        Ok(ValidationRatio { findings, verified, ratio })
+    }
+
+    /// MTTR for findings created in the window
+    pub fn mttr(&self, days: u32) -> Result<Mttr> {
+        let (resolved, seconds): (i64, f64) = self.connection.query_row(
+            "SELECT COALESCE(SUM(resolved), 0), COALESCE(SUM(resolution_seconds), 0)
+            FROM finding_rollups WHERE day >= date('now', ?)",
+            params![since(days)],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let mean_hours = (resolved > 0).then(|| seconds / resolved as f64 / 3600.0);
+        Ok(Mttr { resolved, mean_hours })
+    }
+
+    pub fn suppressed_count(&self, days: u32) -> Result<i64> {
+        let suppressed = self.connection.query_row(
+            "SELECT COALESCE(SUM(suppressed), 0) FROM finding_rollups WHERE day >= date('now', ?)",
+            params![since(days)],
+            |row| row.get(0),
+        )?;
+        Ok(suppressed)
+    }
+
+    pub fn stats_summary(&self, days: u32, limit: u32) -> Result<StatsSummary> {
+        Ok(StatsSummary {
+            days,
+            top_detectors: self.top_detectors(days, limit)?,
+            top_repositories: self.top_repositories(days, limit)?,
+            trend: self.findings_trend(days)?,
+            validation: self.validation_ratio(days)?,
+            mttr: self.mttr(days)?,
+            suppressed: self.suppressed_count(days)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::performance::FindingStatus;
+
+    fn seed(db: &SecretDatabase, hash: &str, repository: &str, detector: &str) {
+        db.connection
+            .execute(
+                "INSERT INTO commits (commit_sha, repository_name, created_at) VALUES (?, ?, datetime('now'))",
+                params![format!("sha-{}", hash), repository],
+            )
+            .unwrap();
+        db.connection
+            .execute(
+                "INSERT INTO secrets (secret_hash, commit_id, detector_name, matched_text_hash, severity, category, created_at)
+                VALUES (?, ?, ?, 'x', 'High', 'CloudProvider', datetime('now', '-2 hours'))",
+                params![hash, db.connection.last_insert_rowid(), detector],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_rollups_count_findings_per_detector_and_repository() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        seed(&db, "aaa", "acme/widgets", "AWS Access Key ID");
+        seed(&db, "bbb", "acme/widgets", "GitHub Token");
+        seed(&db, "ccc", "acme/gadgets", "GitHub Token");
+        db.connection.execute("UPDATE secrets SET verified = TRUE WHERE secret_hash = 'ccc'", []).unwrap();
+        db.refresh_rollups(1).unwrap();
+
+        let detectors = db.top_detectors(1, 10).unwrap();
+        assert_eq!((detectors[0].key.as_str(), detectors[0].findings, detectors[0].verified), ("GitHub Token", 2, 1));
+        let repositories = db.top_repositories(1, 1).unwrap();
+        assert_eq!((repositories.len(), repositories[0].key.as_str()), (1, "acme/widgets"));
+        let validation = db.validation_ratio(1).unwrap();
+        assert_eq!((validation.findings, validation.verified), (3, 1));
+        assert_eq!(db.findings_trend(1).unwrap().iter().map(|p| p.findings).sum::<i64>(), 3);
+    }
+
+    #[test]
+    fn test_mttr_counts_resolved_and_revoked_findings() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        seed(&db, "aaa", "acme/widgets", "AWS Access Key ID");
+        seed(&db, "bbb", "acme/widgets", "GitHub Token");
+        seed(&db, "ccc", "acme/widgets", "GitHub Token");
+        db.refresh_rollups(1).unwrap();
+        assert_eq!((db.mttr(1).unwrap().resolved, db.mttr(1).unwrap().mean_hours), (0, None));
+
+        db.set_finding_status("aaa", FindingStatus::Revoked).unwrap();
+        db.set_finding_status("bbb", FindingStatus::Resolved).unwrap();
+
+        let mttr = db.mttr(1).unwrap();
+        assert_eq!(mttr.resolved, 2);
+        let hours = mttr.mean_hours.unwrap();
+        assert!((1.9..2.1).contains(&hours), "{}", hours);
+    }
+}
@@ -0,0 +1,101 @@
+use anyhow::{anyhow, Result};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use utoipa::ToSchema;
+
+use super::{SecretDatabase, ROLLUP_WINDOW_DAYS};
+
+/// Remediation state an analyst records on a finding. Stats, heartbeats, repository
+/// exposure and scorecards count `resolved` and `revoked` findings as remediated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FindingStatus {
+    /// Not remediated; clears an earlier `resolved` or `revoked`
+    Open,
+    /// Dealt with without revoking the credential, e.g. removed from history or a false positive
+    Resolved,
+    /// The credential was revoked or rotated
+    Revoked,
+}
+
+impl FindingStatus {
+    /// Stored `validation_status`; open findings have none
+    fn stored(self) -> Option<&'static str> {
+        match self {
+            Self::Open => None,
+            Self::Resolved => Some("resolved"),
+            Self::Revoked => Some("revoked"),
+        }
+    }
+}
+
+impl std::str::FromStr for FindingStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "open" | "reopen" => Ok(Self::Open),
+            "resolved" | "resolve" => Ok(Self::Resolved),
+            "revoked" | "revoke" | "rotated" => Ok(Self::Revoked),
+            other => Err(anyhow!("Invalid finding status: {} (open, resolved or revoked)", other)),
+        }
+    }
+}
+
+impl SecretDatabase {
+    /// Record a remediation status; `updated_at` becomes the remediation time that MTTR and
+    /// mean time to revoke are measured to. Returns false if no finding has the hash.
+    /// Erased findings keep their `erased` status.
+    pub fn set_finding_status(&self, secret_hash: &str, status: FindingStatus) -> Result<bool> {
+        let updated = self.connection.execute(
+            "UPDATE secrets SET validation_status = ?, updated_at = datetime('now')
+            WHERE secret_hash = ? AND validation_status IS NOT 'erased'",
+            params![status.stored(), secret_hash],
+        )?;
+        if updated > 0 {
+            info!("Marked finding {} {:?}", secret_hash, status);
+            // Resolution counts live in the rollups of the day the finding was created
+            self.refresh_rollups(ROLLUP_WINDOW_DAYS)?;
+        }
+        Ok(updated > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seed(db: &SecretDatabase, hash: &str) {
+        db.connection
+            .execute(
+                "INSERT INTO commits (commit_sha, repository_name, created_at) VALUES (?, 'acme/widgets', datetime('now'))",
+                params![format!("sha-{}", hash)],
+            )
+            .unwrap();
+        db.connection
+            .execute(
+                "INSERT INTO secrets (secret_hash, commit_id, detector_name, matched_text_hash, severity, category, created_at)
+                VALUES (?, ?, 'AWS Access Key ID', 'x', 'Critical', 'CloudProvider', datetime('now', '-2 hours'))",
+                params![hash, db.connection.last_insert_rowid()],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_status_transitions_feed_remediation_counts() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        seed(&db, "aaa");
+        seed(&db, "bbb");
+        assert_eq!(db.unresolved_count("Critical").unwrap(), 2);
+
+        assert!(db.set_finding_status("aaa", "revoke".parse().unwrap()).unwrap());
+        assert!(!db.set_finding_status("missing", FindingStatus::Resolved).unwrap());
+        assert_eq!(db.unresolved_count("Critical").unwrap(), 1);
+        assert_eq!(db.repository_exposure("acme/widgets").unwrap().active_findings, 1);
+
+        assert!(db.set_finding_status("aaa", FindingStatus::Open).unwrap());
+        assert_eq!(db.unresolved_count("Critical").unwrap(), 2);
+        assert!("fixed".parse::<FindingStatus>().is_err());
+    }
+}
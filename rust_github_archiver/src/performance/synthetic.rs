@@ -0,0 +1,66 @@
+use anyhow::Result;
+use rusqlite::params;
+
+use super::{SecretDatabase, NOT_ON_LEGAL_HOLD};
+
+/// SQL predicate that excludes synthetic findings. Statistics, rollups and review
+/// counters must include it so test injections never show up in the numbers.
+pub const NOT_SYNTHETIC: &str = "secret_hash NOT IN (SELECT secret_hash FROM synthetic_findings)";
+
+impl SecretDatabase {
+    pub(super) fn initialize_synthetic_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS synthetic_findings (
+                secret_hash TEXT PRIMARY KEY,
+                injected_at DATETIME NOT NULL
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Record a stored finding as a synthetic test injection
+    pub fn mark_synthetic(&self, secret_hash: &str) -> Result<()> {
+        self.connection.execute(
+            "INSERT OR IGNORE INTO synthetic_findings (secret_hash, injected_at) VALUES (?, datetime('now'))",
+            params![secret_hash],
+        )?;
+        // Rollups are rebuilt on insert, before the finding was known to be synthetic
+        self.refresh_rollups(0)?;
+        Ok(())
+    }
+
+    pub fn is_synthetic(&self, secret_hash: &str) -> Result<bool> {
+        let synthetic = self.connection.query_row(
+            "SELECT EXISTS(SELECT 1 FROM synthetic_findings WHERE secret_hash = ?)",
+            params![secret_hash],
+            |row| row.get(0),
+        )?;
+        Ok(synthetic)
+    }
+
+    /// Delete synthetic findings injected more than `days` days ago
+    pub fn purge_synthetic(&self, days: u32) -> Result<usize> {
+        let selector = format!(
+            "secret_hash IN (SELECT secret_hash FROM synthetic_findings WHERE injected_at < datetime('now', ?)) AND {}",
+            NOT_ON_LEGAL_HOLD
+        );
+        let cutoff = format!("-{} days", days);
+
+        let tx = self.connection.unchecked_transaction()?;
+        tx.execute(
+            &format!("DELETE FROM triage_results WHERE secret_id IN (SELECT id FROM secrets WHERE {})", selector),
+            params![cutoff],
+        )?;
+        for table in ["finding_notes", "finding_attachments", "finding_tags", "saved_search_matches"] {
+            tx.execute(&format!("DELETE FROM {} WHERE {}", table, selector), params![cutoff])?;
+        }
+        let deleted = tx.execute(&format!("DELETE FROM secrets WHERE {}", selector), params![cutoff])?;
+        tx.execute(
+            "DELETE FROM synthetic_findings WHERE secret_hash NOT IN (SELECT secret_hash FROM secrets)",
+            [],
+        )?;
+        tx.commit()?;
+        Ok(deleted)
+    }
+}
@@ -0,0 +1,218 @@
+use anyhow::{anyhow, Result};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::SecretDatabase;
+
+const MAX_TAG_LENGTH: usize = 64;
+
+/// Number of findings carrying a tag, directly or through their repository
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TagCount {
+    pub tag: String,
+    pub findings: i64,
+}
+
+/// Canonical form of a free-form tag: trimmed, lowercase, spaces as dashes.
+/// Letters, digits and `-_:.` are allowed, e.g. `customer-impact` or `bounty:submitted`.
+pub fn normalize_tag(tag: &str) -> Result<String> {
+    let tag = tag.trim().to_lowercase().replace(' ', "-");
+    if tag.is_empty() || tag.len() > MAX_TAG_LENGTH {
+        return Err(anyhow!("Tags must be 1-{} characters", MAX_TAG_LENGTH));
+    }
+    if !tag.chars().all(|c| c.is_ascii_alphanumeric() || "-_:.".contains(c)) {
+        return Err(anyhow!("Invalid tag {:?}: use letters, digits and -_:.", tag));
+    }
+    Ok(tag)
+}
+
+fn normalize_all(tags: &[String]) -> Result<Vec<String>> {
+    tags.iter().map(|t| normalize_tag(t)).collect()
+}
+
+/// SQL predicate on `secrets` matching findings tagged `?`, either directly or via their repository.
+/// Binds the tag twice. Repository tags are stored lowercase; commits keep GitHub's casing.
+pub(super) const TAGGED: &str = "(secret_hash IN (SELECT secret_hash FROM finding_tags WHERE tag = ?)
+    OR commit_id IN (SELECT c.id FROM commits c
+        JOIN repository_tags rt ON rt.repository_name = LOWER(c.repository_name) WHERE rt.tag = ?))";
+
+impl SecretDatabase {
+    pub(super) fn initialize_tags_schema(&self) -> Result<()> {
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS finding_tags (
+                secret_hash TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                added_by TEXT NOT NULL,
+                added_at DATETIME NOT NULL,
+                PRIMARY KEY(secret_hash, tag)
+            )",
+            [],
+        )?;
+        self.connection.execute(
+            "CREATE INDEX IF NOT EXISTS idx_finding_tags_tag ON finding_tags(tag)",
+            [],
+        )?;
+
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS repository_tags (
+                repository_name TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                added_by TEXT NOT NULL,
+                added_at DATETIME NOT NULL,
+                PRIMARY KEY(repository_name, tag)
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    /// Add tags to a finding; tags it already has are left alone
+    pub fn tag_finding(&self, secret_hash: &str, tags: &[String], added_by: &str) -> Result<Vec<String>> {
+        let tags = normalize_all(tags)?;
+        self.require_finding(secret_hash)?;
+
+        let tx = self.connection.unchecked_transaction()?;
+        for tag in &tags {
+            tx.execute(
+                "INSERT OR IGNORE INTO finding_tags (secret_hash, tag, added_by, added_at)
+                VALUES (?, ?, ?, datetime('now'))",
+                params![secret_hash, tag, added_by],
+            )?;
+        }
+        tx.commit()?;
+
+        self.finding_tags(secret_hash)
+    }
+
+    pub fn untag_finding(&self, secret_hash: &str, tags: &[String]) -> Result<Vec<String>> {
+        for tag in normalize_all(tags)? {
+            self.connection.execute(
+                "DELETE FROM finding_tags WHERE secret_hash = ? AND tag = ?",
+                params![secret_hash, tag],
+            )?;
+        }
+        self.finding_tags(secret_hash)
+    }
+
+    pub fn finding_tags(&self, secret_hash: &str) -> Result<Vec<String>> {
+        self.collect_tags("SELECT tag FROM finding_tags WHERE secret_hash = ? ORDER BY tag", secret_hash)
+    }
+
+    /// Tag a repository (`owner/name`); every finding in it matches the tag
+    pub fn tag_repository(&self, repository: &str, tags: &[String], added_by: &str) -> Result<Vec<String>> {
+        let tags = normalize_all(tags)?;
+        let repository = repository.to_lowercase();
+
+        let tx = self.connection.unchecked_transaction()?;
+        for tag in &tags {
+            tx.execute(
+                "INSERT OR IGNORE INTO repository_tags (repository_name, tag, added_by, added_at)
+                VALUES (?, ?, ?, datetime('now'))",
+                params![repository, tag, added_by],
+            )?;
+        }
+        tx.commit()?;
+
+        self.repository_tags(&repository)
+    }
+
+    pub fn untag_repository(&self, repository: &str, tags: &[String]) -> Result<Vec<String>> {
+        let repository = repository.to_lowercase();
+        for tag in normalize_all(tags)? {
+            self.connection.execute(
+                "DELETE FROM repository_tags WHERE repository_name = ? AND tag = ?",
+                params![repository, tag],
+            )?;
+        }
+        self.repository_tags(&repository)
+    }
+
+    pub fn repository_tags(&self, repository: &str) -> Result<Vec<String>> {
+        self.collect_tags(
+            "SELECT tag FROM repository_tags WHERE repository_name = ? ORDER BY tag",
+            &repository.to_lowercase(),
+        )
+    }
+
+    /// Tag facets: every tag in use with the number of findings it matches, most used first
+    pub fn tag_counts(&self) -> Result<Vec<TagCount>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT tag, COUNT(DISTINCT secret_hash) AS findings FROM (
+                SELECT tag, secret_hash FROM finding_tags
+                UNION ALL
+                SELECT rt.tag, s.secret_hash FROM repository_tags rt
+                JOIN commits c ON LOWER(c.repository_name) = rt.repository_name
+                JOIN secrets s ON s.commit_id = c.id
+            )
+            GROUP BY tag
+            ORDER BY findings DESC, tag",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(TagCount {
+                tag: row.get(0)?,
+                findings: row.get(1)?,
+            })
+        })?;
+
+        let mut counts = Vec::new();
+        for row in rows {
+            counts.push(row?);
+        }
+        Ok(counts)
+    }
+
+    fn collect_tags(&self, query: &str, key: &str) -> Result<Vec<String>> {
+        let mut stmt = self.connection.prepare(query)?;
+        let rows = stmt.query_map(params![key], |row| row.get(0))?;
+
+        let mut tags = Vec::new();
+        for row in rows {
+            tags.push(row?);
+        }
+        Ok(tags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::performance::SecretQueryFilters;
+
+    #[test]
+    fn test_tag_normalization() {
+        assert_eq!(normalize_tag(" Customer Impact ").unwrap(), "customer-impact");
+        assert_eq!(normalize_tag("bounty:submitted").unwrap(), "bounty:submitted");
+        assert!(normalize_tag("").is_err());
+        assert!(normalize_tag("rotated; DROP TABLE").is_err());
+        assert!(normalize_tag(&"x".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_repository_tags_match_mixed_case_repositories() {
+        let db = SecretDatabase::new(":memory:").unwrap();
+        db.connection
+            .execute(
+                "INSERT INTO commits (commit_sha, repository_name, created_at) VALUES ('abc123', 'Acme/Widgets', datetime('now'))",
+                [],
+            )
+            .unwrap();
+        db.connection
+            .execute(
+                "INSERT INTO secrets (secret_hash, commit_id, detector_name, matched_text_hash, severity, category, created_at)
+                VALUES ('h1', 1, 'AWS Access Key ID', 'x', 'High', 'CloudProvider', datetime('now'))",
+                [],
+            )
+            .unwrap();
+
+        assert_eq!(db.tag_repository("Acme/Widgets", &["Customer Impact".to_string()], "alice").unwrap(), vec!["customer-impact"]);
+        let counts = db.tag_counts().unwrap();
+        assert_eq!((counts[0].tag.as_str(), counts[0].findings), ("customer-impact", 1));
+
+        let filters = SecretQueryFilters { tags: vec!["customer-impact".to_string()], ..Default::default() };
+        let found = db.query_secrets(&filters).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].secret_hash, "h1");
+    }
+}
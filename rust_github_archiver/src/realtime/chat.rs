@@ -0,0 +1,497 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::{AlertSeverity, RealTimeSecretAlert};
+use crate::core::{output, ChatPlatform, ChatTarget};
+use crate::notifications::{i18n, Notifier};
+use crate::secrets::SecretSeverity;
+use crate::sinks::severity_rank;
+
+/// Alerts listed in one batched message; the rest are counted
+const MAX_LISTED_ALERTS: usize = 10;
+/// Findings listed per alert
+const MAX_LISTED_FINDINGS: usize = 5;
+
+/// Renders alerts as one chat platform's message body
+pub trait AlertFormatter: Send + Sync {
+    fn format_alert(&self, alert: &RealTimeSecretAlert) -> Value;
+
+    /// One message for the alerts batched during a storm
+    fn format_batch(&self, alerts: &[RealTimeSecretAlert]) -> Value;
+}
+
+pub fn formatter(platform: ChatPlatform) -> Box<dyn AlertFormatter> {
+    match platform {
+        ChatPlatform::Slack => Box::new(SlackBlockKit),
+        ChatPlatform::Discord => Box::new(DiscordEmbeds),
+        ChatPlatform::Teams => Box::new(TeamsAdaptiveCard),
+    }
+}
+
+/// `severity_rank` of the alert's most severe finding
+fn worst_rank(alert: &RealTimeSecretAlert) -> u8 {
+    alert.secrets_found.iter().map(|finding| severity_rank(&finding.severity)).max().unwrap_or(0)
+}
+
+/// The severity's emoji, and the space after it, unless the output profile leaves emoji out
+fn emoji(severity: &AlertSeverity) -> &'static str {
+    if !output::style().emoji() {
+        return "";
+    }
+    match severity {
+        AlertSeverity::Critical => "🚨 ",
+        AlertSeverity::High => "⚠️ ",
+        AlertSeverity::Medium => "⚡ ",
+        AlertSeverity::Low => "📝 ",
+    }
+}
+
+fn title(alert: &RealTimeSecretAlert) -> String {
+    let text = i18n::text(
+        "chat-alert-title",
+        &[
+            ("severity", i18n::severity(&alert.alert_severity).into()),
+            ("count", alert.secrets_found.len().into()),
+            ("repository", alert.repository.as_str().into()),
+        ],
+    );
+    format!("{}{}", emoji(&alert.alert_severity), text)
+}
+
+fn batch_title(alerts: &[RealTimeSecretAlert]) -> String {
+    let worst = alerts.iter().max_by_key(|a| worst_rank(a)).map(|a| &a.alert_severity);
+    let secrets: usize = alerts.iter().map(|a| a.secrets_found.len()).sum();
+    let text = i18n::text(
+        "chat-batch-title",
+        &[
+            ("alerts", alerts.len().into()),
+            ("secrets", secrets.into()),
+            ("repositories", distinct_repositories(alerts).into()),
+        ],
+    );
+    format!("{}{}", worst.map(emoji).unwrap_or_default(), text)
+}
+
+fn distinct_repositories(alerts: &[RealTimeSecretAlert]) -> usize {
+    let mut repositories: Vec<&str> = alerts.iter().map(|a| a.repository.as_str()).collect();
+    repositories.sort_unstable();
+    repositories.dedup();
+    repositories.len()
+}
+
+fn repository_url(alert: &RealTimeSecretAlert) -> String {
+    format!("https://github.com/{}", alert.repository)
+}
+
+fn commit_url(alert: &RealTimeSecretAlert) -> String {
+    format!("https://github.com/{}/commit/{}", alert.repository, alert.commit_sha)
+}
+
+fn short_sha(alert: &RealTimeSecretAlert) -> String {
+    alert.commit_sha.chars().take(7).collect()
+}
+
+/// `Detector (Severity) in path:line`, one per finding; `code` wraps names and paths
+fn finding_lines(alert: &RealTimeSecretAlert, code: fn(&str) -> String) -> Vec<String> {
+    let mut lines: Vec<String> = alert
+        .secrets_found
+        .iter()
+        .take(MAX_LISTED_FINDINGS)
+        .map(|finding| {
+            let location = match finding.line_number {
+                Some(line) => format!("{}:{}", finding.filename, line),
+                None => finding.filename.clone(),
+            };
+            i18n::text(
+                "finding-in-file",
+                &[
+                    ("detector", code(&finding.detector_name).into()),
+                    ("severity", i18n::severity(&finding.severity).into()),
+                    ("file", code(&location).into()),
+                ],
+            )
+        })
+        .collect();
+    if alert.secrets_found.len() > MAX_LISTED_FINDINGS {
+        let more = i18n::text("and-more", &[("count", (alert.secrets_found.len() - MAX_LISTED_FINDINGS).into())]);
+        lines.push(output::style().text(&format!("…{}", more)).into_owned());
+    }
+    lines
+}
+
+fn backticks(text: &str) -> String {
+    format!("`{}`", text.replace('`', "'"))
+}
+
+fn plain(text: &str) -> String {
+    text.to_string()
+}
+
+fn unlisted(alerts: &[RealTimeSecretAlert]) -> Option<String> {
+    (alerts.len() > MAX_LISTED_ALERTS).then(|| {
+        let more = i18n::text("and-more-alerts", &[("count", (alerts.len() - MAX_LISTED_ALERTS).into())]);
+        output::style().text(&format!("…{}", more)).into_owned()
+    })
+}
+
+fn detection_time(alert: &RealTimeSecretAlert) -> String {
+    alert.detection_time.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+fn detected(alert: &RealTimeSecretAlert) -> String {
+    i18n::text("detected-at", &[("time", detection_time(alert).into())])
+}
+
+pub struct SlackBlockKit;
+
+impl SlackBlockKit {
+    fn alert_section(alert: &RealTimeSecretAlert) -> Value {
+        let bullet = output::style().text("•");
+        let lines: Vec<String> = finding_lines(alert, backticks).into_iter().map(|line| format!("{} {}", bullet, line)).collect();
+        let heading = i18n::text(
+            "chat-alert-section",
+            &[
+                ("repository", format!("*<{}|{}>*", repository_url(alert), alert.repository).into()),
+                ("severity", i18n::severity(&alert.alert_severity).into()),
+                ("commit", format!("<{}|{}>", commit_url(alert), short_sha(alert)).into()),
+            ],
+        );
+        json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": format!("{}\n{}", heading, lines.join("\n")) }
+        })
+    }
+}
+
+impl AlertFormatter for SlackBlockKit {
+    fn format_alert(&self, alert: &RealTimeSecretAlert) -> Value {
+        let mut blocks = vec![
+            json!({ "type": "header", "text": { "type": "plain_text", "text": title(alert), "emoji": true } }),
+            Self::alert_section(alert),
+        ];
+        if let Some(spread) = &alert.spread {
+            blocks.push(json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": format!(
+                    "{}{}",
+                    output::style().slack_emoji(":globe_with_meridians: "),
+                    i18n::text(
+                        "chat-alert-spread",
+                        &[("occurrences", spread.occurrences.into()), ("repositories", spread.repositories.into())],
+                    )
+                ) }
+            }));
+        }
+        blocks.push(json!({ "type": "context", "elements": [{ "type": "mrkdwn", "text": detected(alert) }] }));
+        // `text` is the notification preview and the fallback for clients without blocks
+        json!({ "text": title(alert), "blocks": blocks })
+    }
+
+    fn format_batch(&self, alerts: &[RealTimeSecretAlert]) -> Value {
+        let title = batch_title(alerts);
+        let mut blocks = vec![json!({ "type": "header", "text": { "type": "plain_text", "text": title, "emoji": true } })];
+        for alert in alerts.iter().take(MAX_LISTED_ALERTS) {
+            blocks.push(json!({ "type": "divider" }));
+            blocks.push(Self::alert_section(alert));
+        }
+        if let Some(more) = unlisted(alerts) {
+            blocks.push(json!({ "type": "context", "elements": [{ "type": "mrkdwn", "text": more }] }));
+        }
+        json!({ "text": title, "blocks": blocks })
+    }
+}
+
+pub struct DiscordEmbeds;
+
+impl DiscordEmbeds {
+    fn color(alert: &RealTimeSecretAlert) -> u32 {
+        let [r, g, b] = output::style().severity_color(worst_rank(alert));
+        u32::from_be_bytes([0, r, g, b])
+    }
+
+    fn embed(alert: &RealTimeSecretAlert) -> Value {
+        json!({
+            "title": title(alert),
+            "url": commit_url(alert),
+            "color": Self::color(alert),
+            "description": finding_lines(alert, backticks).join("\n"),
+            "fields": [
+                {
+                    "name": i18n::text("column-repository", &[]),
+                    "value": format!("[{}]({})", alert.repository, repository_url(alert)),
+                    "inline": true,
+                },
+                {
+                    "name": i18n::text("column-commit", &[]),
+                    "value": format!("[{}]({})", short_sha(alert), commit_url(alert)),
+                    "inline": true,
+                },
+            ],
+            "timestamp": alert.detection_time.to_rfc3339(),
+        })
+    }
+}
+
+impl AlertFormatter for DiscordEmbeds {
+    fn format_alert(&self, alert: &RealTimeSecretAlert) -> Value {
+        json!({ "embeds": [Self::embed(alert)] })
+    }
+
+    fn format_batch(&self, alerts: &[RealTimeSecretAlert]) -> Value {
+        // Discord takes at most 10 embeds per message
+        let mut content = format!("**{}**", batch_title(alerts));
+        if let Some(more) = unlisted(alerts) {
+            content = format!("{}\n{}", content, more);
+        }
+        let embeds: Vec<Value> = alerts.iter().take(MAX_LISTED_ALERTS).map(Self::embed).collect();
+        json!({ "content": content, "embeds": embeds })
+    }
+}
+
+pub struct TeamsAdaptiveCard;
+
+impl TeamsAdaptiveCard {
+    fn color(severity: &AlertSeverity) -> &'static str {
+        match severity {
+            AlertSeverity::Critical | AlertSeverity::High => "Attention",
+            AlertSeverity::Medium => "Warning",
+            AlertSeverity::Low => "Default",
+        }
+    }
+
+    fn alert_items(alert: &RealTimeSecretAlert, heading_size: &str) -> Vec<Value> {
+        vec![
+            json!({
+                "type": "TextBlock", "text": title(alert), "weight": "Bolder", "size": heading_size,
+                "color": Self::color(&alert.alert_severity), "wrap": true,
+            }),
+            json!({ "type": "FactSet", "facts": [
+                { "title": i18n::text("column-repository", &[]), "value": format!("[{}]({})", alert.repository, repository_url(alert)) },
+                { "title": i18n::text("column-commit", &[]), "value": format!("[{}]({})", short_sha(alert), commit_url(alert)) },
+                { "title": i18n::text("column-detected", &[]), "value": detection_time(alert) },
+            ]}),
+            json!({
+                "type": "TextBlock", "wrap": true,
+                "text": finding_lines(alert, plain).into_iter().map(|line| format!("- {}", line)).collect::<Vec<_>>().join("\n"),
+            }),
+        ]
+    }
+
+    fn card(body: Vec<Value>, actions: Vec<Value>) -> Value {
+        json!({
+            "type": "message",
+            "attachments": [{
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "content": {
+                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                    "type": "AdaptiveCard",
+                    "version": "1.4",
+                    "msteams": { "width": "Full" },
+                    "body": body,
+                    "actions": actions,
+                },
+            }],
+        })
+    }
+}
+
+impl AlertFormatter for TeamsAdaptiveCard {
+    fn format_alert(&self, alert: &RealTimeSecretAlert) -> Value {
+        let action = json!({ "type": "Action.OpenUrl", "title": i18n::text("view-commit", &[]), "url": commit_url(alert) });
+        Self::card(Self::alert_items(alert, "Large"), vec![action])
+    }
+
+    fn format_batch(&self, alerts: &[RealTimeSecretAlert]) -> Value {
+        let mut body = vec![json!({ "type": "TextBlock", "text": batch_title(alerts), "weight": "Bolder", "size": "Large", "wrap": true })];
+        for alert in alerts.iter().take(MAX_LISTED_ALERTS) {
+            body.push(json!({ "type": "Container", "separator": true, "items": Self::alert_items(alert, "Medium") }));
+        }
+        if let Some(more) = unlisted(alerts) {
+            body.push(json!({ "type": "TextBlock", "text": more, "isSubtle": true }));
+        }
+        Self::card(body, Vec::new())
+    }
+}
+
+/// Batching and rate limiting for one chat target
+pub struct ChatThrottle {
+    batch_window: Duration,
+    per_minute: u32,
+    pending: Vec<RealTimeSecretAlert>,
+    /// When each message of the last minute went out, oldest first
+    sent: VecDeque<Instant>,
+}
+
+impl ChatThrottle {
+    pub fn new(batch_window: Duration, per_minute: u32) -> Self {
+        Self { batch_window, per_minute, pending: Vec::new(), sent: VecDeque::new() }
+    }
+
+    /// Queue an alert and return what should be sent now, if anything
+    pub fn offer(&mut self, alert: RealTimeSecretAlert, now: Instant) -> Option<Vec<RealTimeSecretAlert>> {
+        self.pending.push(alert);
+        self.take_due(now)
+    }
+
+    /// The queued alerts, once the batch window since the last message has passed and the
+    /// per-minute limit allows another message
+    pub fn take_due(&mut self, now: Instant) -> Option<Vec<RealTimeSecretAlert>> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        while self.sent.front().is_some_and(|at| now.duration_since(*at) >= Duration::from_secs(60)) {
+            self.sent.pop_front();
+        }
+        if self.sent.back().is_some_and(|last| now.duration_since(*last) < self.batch_window) {
+            return None;
+        }
+        if self.per_minute > 0 && self.sent.len() >= self.per_minute as usize {
+            return None;
+        }
+        self.sent.push_back(now);
+        Some(std::mem::take(&mut self.pending))
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+/// What happened to an alert offered to a chat target
+pub enum ChatOutcome {
+    /// Below the target's minimum severity
+    Skipped,
+    /// Waiting for the next batched message
+    Batched,
+    /// Sent, alone or with the alerts batched before it
+    Sent,
+}
+
+/// A Slack, Discord or Teams webhook with its formatter, severity threshold and throttle.
+/// Messages are posted through the `Notifier`, like every other alert channel.
+pub struct ChatNotifier {
+    label: String,
+    url: String,
+    min_severity: Option<SecretSeverity>,
+    formatter: Box<dyn AlertFormatter>,
+    throttle: Mutex<ChatThrottle>,
+}
+
+impl ChatNotifier {
+    pub fn from_target(target: &ChatTarget) -> Result<Self> {
+        let min_severity = target.min_severity.as_deref().map(str::parse).transpose()?;
+        let name = target.name.clone().unwrap_or_else(|| format!("{:?}", target.platform).to_lowercase());
+        Ok(Self {
+            label: format!("chat:{}", name),
+            url: target.url.clone(),
+            min_severity,
+            formatter: formatter(target.platform),
+            throttle: Mutex::new(ChatThrottle::new(
+                Duration::from_secs(target.batch_seconds),
+                target.max_messages_per_minute,
+            )),
+        })
+    }
+
+    pub fn from_targets(targets: &[ChatTarget]) -> Result<Vec<Self>> {
+        targets.iter().map(Self::from_target).collect()
+    }
+
+    /// `chat:<name>`, as reported in alert deliveries
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn accepts(&self, alert: &RealTimeSecretAlert) -> bool {
+        match &self.min_severity {
+            Some(min) => worst_rank(alert) >= severity_rank(min),
+            None => true,
+        }
+    }
+
+    /// Send the alert now, or batch it when the target has had a message recently
+    pub async fn offer(&self, notifier: &Notifier, alert: &RealTimeSecretAlert) -> Result<ChatOutcome> {
+        if !self.accepts(alert) {
+            return Ok(ChatOutcome::Skipped);
+        }
+        let due = self.throttle.lock().unwrap().offer(alert.clone(), Instant::now());
+        match due {
+            Some(alerts) => self.deliver(notifier, &alerts).await.map(|_| ChatOutcome::Sent),
+            None => Ok(ChatOutcome::Batched),
+        }
+    }
+
+    /// Send one alert at once, past the severity threshold and the throttle, as resends do
+    pub async fn send(&self, notifier: &Notifier, alert: &RealTimeSecretAlert) -> Result<()> {
+        self.deliver(notifier, std::slice::from_ref(alert)).await
+    }
+
+    /// Send the batched alerts once the target may have another message. Returns how many
+    /// alerts went out.
+    pub async fn flush(&self, notifier: &Notifier) -> Result<usize> {
+        let due = self.throttle.lock().unwrap().take_due(Instant::now());
+        match due {
+            Some(alerts) => self.deliver(notifier, &alerts).await.map(|_| alerts.len()),
+            None => Ok(0),
+        }
+    }
+
+    async fn deliver(&self, notifier: &Notifier, alerts: &[RealTimeSecretAlert]) -> Result<()> {
+        let payload = match alerts {
+            [alert] => self.formatter.format_alert(alert),
+            _ => self.formatter.format_batch(alerts),
+        };
+        notifier.post_json(&self.label, &self.url, &payload).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::sample_alert;
+
+    #[test]
+    fn test_throttle_batches_an_alert_storm() {
+        let start = Instant::now();
+        let mut throttle = ChatThrottle::new(Duration::from_secs(30), 3);
+        assert_eq!(throttle.offer(sample_alert(), start).map(|a| a.len()), Some(1));
+        for second in 1..=5 {
+            assert!(throttle.offer(sample_alert(), start + Duration::from_secs(second)).is_none());
+        }
+        assert!(throttle.take_due(start + Duration::from_secs(29)).is_none());
+        assert_eq!(throttle.take_due(start + Duration::from_secs(30)).map(|a| a.len()), Some(5));
+        assert_eq!(throttle.pending(), 0);
+
+        // Two messages in the last minute already: the third is the last one allowed
+        let mut limited = ChatThrottle::new(Duration::ZERO, 2);
+        assert!(limited.offer(sample_alert(), start).is_some());
+        assert!(limited.offer(sample_alert(), start + Duration::from_secs(1)).is_some());
+        assert!(limited.offer(sample_alert(), start + Duration::from_secs(2)).is_none());
+        assert_eq!(limited.take_due(start + Duration::from_secs(60)).map(|a| a.len()), Some(1));
+    }
+
+    #[test]
+    fn test_platform_formats() {
+        let alert = sample_alert();
+        let storm = vec![alert.clone(); 12];
+
+        let slack = formatter(ChatPlatform::Slack);
+        assert_eq!(slack.format_alert(&alert)["blocks"][0]["type"], "header");
+        let batch = slack.format_batch(&storm);
+        assert!(batch["text"].as_str().unwrap().contains("12 alerts"));
+        assert!(batch["blocks"].as_array().unwrap().len() <= 50);
+
+        let discord = formatter(ChatPlatform::Discord);
+        assert_eq!(discord.format_alert(&alert)["embeds"][0]["color"], 0xE01E5A);
+        assert_eq!(discord.format_batch(&storm)["embeds"].as_array().unwrap().len(), 10);
+
+        let teams = formatter(ChatPlatform::Teams);
+        let card = &teams.format_alert(&alert)["attachments"][0];
+        assert_eq!(card["contentType"], "application/vnd.microsoft.card.adaptive");
+        assert_eq!(card["content"]["type"], "AdaptiveCard");
+        assert!(teams.format_batch(&storm).to_string().contains("and 2 more alerts"));
+    }
+}
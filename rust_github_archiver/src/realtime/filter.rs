@@ -0,0 +1,238 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::secrets::SecretSeverity;
+use crate::sinks::severity_rank;
+
+use super::{GitHubEvent, RealTimeSecretAlert};
+
+/// Event types the monitor has a handler for; everything else is skipped
+pub const HANDLED_EVENT_TYPES: &[&str] = &["PushEvent", "PullRequestEvent", "IssueCommentEvent", "ReleaseEvent"];
+
+/// Which events the monitor processes and which alerts it sends.
+/// The default processes every handled event type and alerts on every finding.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventFilter {
+    /// Only these event types are processed; empty means all handled types
+    pub include_types: Vec<String>,
+    pub exclude_types: Vec<String>,
+    /// Alerts whose findings are all below this severity are dropped
+    pub min_severity: Option<SecretSeverity>,
+    /// `owner/name` patterns where `*` matches any run of characters, e.g. `acme/*` or `*/infra-*`
+    pub repo_patterns: Vec<String>,
+    /// Lowercase organization logins whose repositories are processed
+    #[serde(default)]
+    pub organizations: HashSet<String>,
+    /// Lowercase user logins whose repositories are processed
+    #[serde(default)]
+    pub users: HashSet<String>,
+    /// Lowercase `owner/name` of single repositories to process
+    #[serde(default)]
+    pub repositories: HashSet<String>,
+}
+
+/// `push`, `Push` and `PushEvent` all name `PushEvent`
+pub fn normalize_event_type(name: &str) -> Result<String> {
+    let name = name.trim().to_lowercase();
+    let name = name.strip_suffix("event").unwrap_or(&name).replace(['_', '-'], "");
+    HANDLED_EVENT_TYPES
+        .iter()
+        .find(|handled| handled.to_lowercase().trim_end_matches("event") == name)
+        .map(|handled| handled.to_string())
+        .ok_or_else(|| anyhow!("Unknown event type {:?}; handled types: {}", name, HANDLED_EVENT_TYPES.join(", ")))
+}
+
+/// A login or `owner/name`, lowercased; GitHub names never contain spaces or further slashes
+fn normalize_target(target: &str, kind: &str, with_name: bool) -> Result<String> {
+    let target = target.trim().to_lowercase();
+    let valid = match target.split_once('/') {
+        Some((owner, name)) => with_name && !owner.is_empty() && !name.is_empty() && !name.contains('/'),
+        None => !with_name && !target.is_empty(),
+    } && !target.contains(char::is_whitespace);
+    if valid {
+        Ok(target)
+    } else {
+        Err(anyhow!("Invalid {} {:?}", kind, target))
+    }
+}
+
+/// Case-insensitive match of `text` against a pattern where `*` matches any run of characters
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let text = text.to_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || !text[first.len()..].ends_with(last) {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+impl EventFilter {
+    /// Build a filter from user input, normalizing event type names and rejecting unknown ones
+    pub fn new(
+        include_types: &[String],
+        exclude_types: &[String],
+        min_severity: Option<SecretSeverity>,
+        repo_patterns: Vec<String>,
+    ) -> Result<Self> {
+        let normalize = |types: &[String]| types.iter().map(|t| normalize_event_type(t)).collect::<Result<Vec<_>>>();
+        // Wildcard-free patterns are single repositories, found with a set lookup
+        let (exact, repo_patterns): (Vec<String>, Vec<String>) =
+            repo_patterns.into_iter().partition(|pattern| !pattern.contains('*'));
+        let filter = Self {
+            include_types: normalize(include_types)?,
+            exclude_types: normalize(exclude_types)?,
+            min_severity,
+            repo_patterns,
+            repositories: exact.iter().map(|r| normalize_target(r, "repository", true)).collect::<Result<_>>()?,
+            ..Default::default()
+        };
+        if filter.processed_types().is_empty() {
+            return Err(anyhow!("The event type filters exclude every handled event type"));
+        }
+        Ok(filter)
+    }
+
+    /// Also process the repositories of these organizations and users
+    pub fn with_owners(mut self, organizations: &[String], users: &[String]) -> Result<Self> {
+        for organization in organizations {
+            self.organizations.insert(normalize_target(organization, "organization", false)?);
+        }
+        for user in users {
+            self.users.insert(normalize_target(user, "user", false)?);
+        }
+        Ok(self)
+    }
+
+    /// Whether any organization, user, repository or pattern narrows the repositories processed
+    pub fn has_targets(&self) -> bool {
+        !(self.organizations.is_empty() && self.users.is_empty() && self.repositories.is_empty() && self.repo_patterns.is_empty())
+    }
+
+    /// Events API paths covering every target: `/orgs/{org}/events` and
+    /// `/repos/{owner}/{name}/events`, plus the public `/events` firehose when users or
+    /// wildcard patterns are targeted, since GitHub has no endpoint for those
+    pub fn target_endpoints(&self) -> Vec<String> {
+        let mut endpoints: Vec<String> = self.organizations.iter().map(|org| format!("/orgs/{}/events", org)).collect();
+        endpoints.extend(
+            self.repositories
+                .iter()
+                .filter(|repository| !self.organizations.contains(repository.split('/').next().unwrap_or_default()))
+                .map(|repository| format!("/repos/{}/events", repository)),
+        );
+        endpoints.sort();
+        if !self.has_targets() || !self.users.is_empty() || !self.repo_patterns.is_empty() {
+            endpoints.insert(0, "/events".to_string());
+        }
+        endpoints
+    }
+
+    /// Handled event types that pass the include/exclude lists
+    pub fn processed_types(&self) -> Vec<&'static str> {
+        HANDLED_EVENT_TYPES
+            .iter()
+            .copied()
+            .filter(|t| self.include_types.is_empty() || self.include_types.iter().any(|i| i == t))
+            .filter(|t| !self.exclude_types.iter().any(|e| e == t))
+            .collect()
+    }
+
+    pub fn accepts_event(&self, event: &GitHubEvent) -> bool {
+        self.processed_types().contains(&event.event_type.as_str()) && self.accepts_repository(&event.repo.name)
+    }
+
+    /// Owners and whole repositories are set lookups; only wildcard patterns are matched one by one
+    pub fn accepts_repository(&self, repository: &str) -> bool {
+        if !self.has_targets() {
+            return true;
+        }
+        let repository = repository.to_lowercase();
+        let owner = repository.split('/').next().unwrap_or_default();
+        self.organizations.contains(owner)
+            || self.users.contains(owner)
+            || self.repositories.contains(&repository)
+            || self.repo_patterns.iter().any(|p| wildcard_match(p, &repository))
+    }
+
+    pub fn accepts_alert(&self, alert: &RealTimeSecretAlert) -> bool {
+        match &self.min_severity {
+            None => true,
+            Some(min) => alert
+                .secrets_found
+                .iter()
+                .any(|s| severity_rank(&s.severity) >= severity_rank(min)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_type_names() {
+        assert_eq!(normalize_event_type("push").unwrap(), "PushEvent");
+        assert_eq!(normalize_event_type("PullRequestEvent").unwrap(), "PullRequestEvent");
+        assert_eq!(normalize_event_type("issue_comment").unwrap(), "IssueCommentEvent");
+        assert!(normalize_event_type("watch").is_err());
+    }
+
+    #[test]
+    fn test_include_and_exclude() {
+        let filter = EventFilter::new(&[], &["issue-comment".to_string()], None, Vec::new()).unwrap();
+        assert_eq!(filter.processed_types(), vec!["PushEvent", "PullRequestEvent", "ReleaseEvent"]);
+
+        let push_only = EventFilter::new(&["push".to_string()], &[], None, Vec::new()).unwrap();
+        assert_eq!(push_only.processed_types(), vec!["PushEvent"]);
+
+        assert!(EventFilter::new(&["push".to_string()], &["push".to_string()], None, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_repo_patterns() {
+        let filter = EventFilter {
+            repo_patterns: vec!["acme/*".to_string(), "*/infra-*".to_string()],
+            ..Default::default()
+        };
+        assert!(filter.accepts_repository("Acme/payments"));
+        assert!(filter.accepts_repository("other/infra-terraform"));
+        assert!(!filter.accepts_repository("other/payments"));
+        assert!(!filter.accepts_repository("acme-labs/payments"));
+        assert!(wildcard_match("acme/api", "acme/api"));
+        assert!(!wildcard_match("a*b*c", "acb"));
+    }
+
+    #[test]
+    fn test_owner_and_repository_targets() {
+        let filter = EventFilter::new(&[], &[], None, vec!["Other/API".to_string()])
+            .unwrap()
+            .with_owners(&["Acme".to_string()], &["octocat".to_string()])
+            .unwrap();
+        assert!(filter.accepts_repository("acme/payments"));
+        assert!(filter.accepts_repository("OctoCat/dotfiles"));
+        assert!(filter.accepts_repository("other/api"));
+        assert!(!filter.accepts_repository("other/web"));
+        assert!(!filter.accepts_repository("acme-labs/payments"));
+        assert!(filter.repo_patterns.is_empty());
+        // The user has no endpoint of their own, so the firehose is still polled
+        assert_eq!(filter.target_endpoints(), vec!["/events", "/orgs/acme/events", "/repos/other/api/events"]);
+
+        let org_only = EventFilter::default().with_owners(&["acme".to_string()], &[]).unwrap();
+        assert_eq!(org_only.target_endpoints(), vec!["/orgs/acme/events"]);
+        assert_eq!(EventFilter::default().target_endpoints(), vec!["/events"]);
+        assert!(EventFilter::default().with_owners(&["acme/api".to_string()], &[]).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::env;
+
+/// Thresholds used by the monitor watchdog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    /// Seconds without any new event before the poller is considered stalled
+    pub stall_threshold_secs: i64,
+    /// Consecutive poll failures before the poller is considered degraded
+    pub max_consecutive_failures: u32,
+    /// Minimum seconds between recovery attempts
+    pub recovery_cooldown_secs: i64,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            stall_threshold_secs: env::var("MONITOR_STALL_THRESHOLD")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            max_consecutive_failures: env::var("MONITOR_MAX_CONSECUTIVE_FAILURES")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            recovery_cooldown_secs: env::var("MONITOR_RECOVERY_COOLDOWN")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse()
+                .unwrap_or(600),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum HealthStatus {
+    Healthy,
+    /// Polls are failing but the stall threshold has not been reached yet
+    Degraded { reason: String },
+    /// No events have arrived for longer than the stall threshold
+    Stalled { reason: String },
+}
+
+impl HealthStatus {
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, HealthStatus::Healthy)
+    }
+}
+
+/// Liveness bookkeeping for the event poller
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorHealth {
+    pub started_at: DateTime<Utc>,
+    pub last_successful_poll: Option<DateTime<Utc>>,
+    pub last_event_received: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub consecutive_failures: u32,
+    pub total_events: u64,
+    pub recovery_attempts: u32,
+    pub last_recovery: Option<DateTime<Utc>>,
+    /// Whether a health alert has been sent for the current incident
+    pub alert_active: bool,
+}
+
+impl MonitorHealth {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            started_at: now,
+            last_successful_poll: None,
+            last_event_received: None,
+            last_error: None,
+            consecutive_failures: 0,
+            total_events: 0,
+            recovery_attempts: 0,
+            last_recovery: None,
+            alert_active: false,
+        }
+    }
+
+    pub fn record_success(&mut self, now: DateTime<Utc>, event_count: usize) {
+        self.last_successful_poll = Some(now);
+        self.consecutive_failures = 0;
+        self.last_error = None;
+        if event_count > 0 {
+            self.last_event_received = Some(now);
+            self.total_events += event_count as u64;
+        }
+    }
+
+    pub fn record_failure(&mut self, error: &str) {
+        self.consecutive_failures += 1;
+        self.last_error = Some(error.to_string());
+    }
+
+    pub fn record_recovery(&mut self, now: DateTime<Utc>) {
+        self.recovery_attempts += 1;
+        self.last_recovery = Some(now);
+    }
+
+    /// Evaluate poller health at `now`
+    pub fn evaluate(&self, config: &WatchdogConfig, now: DateTime<Utc>) -> HealthStatus {
+        let stall_threshold = Duration::seconds(config.stall_threshold_secs);
+        let last_activity = self.last_event_received.unwrap_or(self.started_at);
+
+        if now - last_activity > stall_threshold {
+            return HealthStatus::Stalled {
+                reason: format!(
+                    "No events received for {}s{}",
+                    (now - last_activity).num_seconds(),
+                    self.last_error
+                        .as_ref()
+                        .map(|e| format!(" (last error: {})", e))
+                        .unwrap_or_default()
+                ),
+            };
+        }
+
+        if self.consecutive_failures >= config.max_consecutive_failures {
+            return HealthStatus::Degraded {
+                reason: format!(
+                    "{} consecutive poll failures: {}",
+                    self.consecutive_failures,
+                    self.last_error.as_deref().unwrap_or("unknown error")
+                ),
+            };
+        }
+
+        HealthStatus::Healthy
+    }
+
+    /// Whether enough time has passed since the last recovery to try again
+    pub fn can_attempt_recovery(&self, config: &WatchdogConfig, now: DateTime<Utc>) -> bool {
+        self.last_recovery
+            .map(|last| now - last >= Duration::seconds(config.recovery_cooldown_secs))
+            .unwrap_or(true)
+    }
+}
+
+/// Payload sent to webhook endpoints when the monitor's own health changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorHealthAlert {
+    pub status: HealthStatus,
+    pub health: MonitorHealth,
+    pub detected_at: DateTime<Utc>,
+    pub recovery_attempted: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> WatchdogConfig {
+        WatchdogConfig {
+            stall_threshold_secs: 300,
+            max_consecutive_failures: 3,
+            recovery_cooldown_secs: 600,
+        }
+    }
+
+    #[test]
+    fn test_healthy_after_recent_events() {
+        let start = Utc::now();
+        let mut health = MonitorHealth::new(start);
+        health.record_success(start + Duration::seconds(10), 5);
+
+        assert!(health.evaluate(&config(), start + Duration::seconds(60)).is_healthy());
+        assert_eq!(health.total_events, 5);
+    }
+
+    #[test]
+    fn test_stall_detected_without_events() {
+        let start = Utc::now();
+        let mut health = MonitorHealth::new(start);
+        // Successful polls that return nothing still count as a stall
+        health.record_success(start + Duration::seconds(290), 0);
+
+        let status = health.evaluate(&config(), start + Duration::seconds(301));
+        assert!(matches!(status, HealthStatus::Stalled { .. }));
+    }
+
+    #[test]
+    fn test_degraded_after_consecutive_failures() {
+        let start = Utc::now();
+        let mut health = MonitorHealth::new(start);
+        for _ in 0..3 {
+            health.record_failure("401 Bad credentials");
+        }
+
+        let status = health.evaluate(&config(), start + Duration::seconds(30));
+        assert!(matches!(status, HealthStatus::Degraded { .. }));
+
+        health.record_success(start + Duration::seconds(40), 1);
+        assert!(health.evaluate(&config(), start + Duration::seconds(41)).is_healthy());
+    }
+
+    #[test]
+    fn test_recovery_cooldown() {
+        let start = Utc::now();
+        let mut health = MonitorHealth::new(start);
+        assert!(health.can_attempt_recovery(&config(), start));
+
+        health.record_recovery(start);
+        assert!(!health.can_attempt_recovery(&config(), start + Duration::seconds(60)));
+        assert!(health.can_attempt_recovery(&config(), start + Duration::seconds(600)));
+    }
+}
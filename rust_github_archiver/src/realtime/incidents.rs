@@ -0,0 +1,278 @@
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::{AlertSeverity, RealTimeSecretAlert, RealTimeSecretMatch};
+#[cfg(feature = "ai")]
+use crate::ai::RevocationPriority;
+use crate::core::{build_client, send_with_retry, HttpConfig, IncidentProvider, IncidentTarget, RetryPolicy};
+use crate::performance::Fingerprinter;
+use crate::secrets::SecretSeverity;
+
+const PAGERDUTY_API_URL: &str = "https://events.pagerduty.com";
+const OPSGENIE_API_URL: &str = "https://api.opsgenie.com";
+/// Longest summary each provider accepts
+const PAGERDUTY_SUMMARY_LIMIT: usize = 1024;
+const OPSGENIE_MESSAGE_LIMIT: usize = 130;
+
+/// Incident key of a secret: its keyed `secret_hash`, so the same secret found again maps to
+/// the same PagerDuty `dedup_key` or Opsgenie `alias` and updates that incident, while the
+/// provider never holds a digest it could check guesses against without the pepper
+pub fn incident_key(finding: &RealTimeSecretMatch) -> Result<String> {
+    if finding.hash.is_empty() {
+        // Alerts logged before matches carried their hash
+        return Ok(format!("gitarchiver-{}", Fingerprinter::from_env()?.secret_hash(&finding.matched_text)));
+    }
+    Ok(format!("gitarchiver-{}", finding.hash))
+}
+
+/// The secrets of an alert worth paging for, one per distinct secret: every secret when
+/// triage says to revoke immediately, otherwise the Critical ones
+pub fn paging_findings(alert: &RealTimeSecretAlert) -> Result<Vec<(String, &RealTimeSecretMatch)>> {
+    let immediate = revoke_immediately(alert);
+    if !immediate && !matches!(alert.alert_severity, AlertSeverity::Critical) {
+        return Ok(Vec::new());
+    }
+    let mut findings: Vec<(String, &RealTimeSecretMatch)> = Vec::new();
+    for finding in &alert.secrets_found {
+        if !immediate && !matches!(finding.severity, SecretSeverity::Critical) {
+            continue;
+        }
+        let key = incident_key(finding)?;
+        if !findings.iter().any(|(seen, _)| *seen == key) {
+            findings.push((key, finding));
+        }
+    }
+    Ok(findings)
+}
+
+/// Whether AI triage says to revoke the alert's secrets now; never without the `ai` feature
+#[cfg(feature = "ai")]
+fn revoke_immediately(alert: &RealTimeSecretAlert) -> bool {
+    alert
+        .triage_result
+        .as_ref()
+        .is_some_and(|triage| matches!(triage.revocation_priority, RevocationPriority::Immediate))
+}
+
+#[cfg(not(feature = "ai"))]
+fn revoke_immediately(_alert: &RealTimeSecretAlert) -> bool {
+    false
+}
+
+fn summary(alert: &RealTimeSecretAlert, finding: &RealTimeSecretMatch, limit: usize) -> String {
+    let summary = format!("Secret exposed in {}: {} in {}", alert.repository, finding.detector_name, finding.filename);
+    if summary.chars().count() <= limit {
+        return summary;
+    }
+    let mut truncated: String = summary.chars().take(limit - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Incident details. The secret itself is never sent, only its incident key.
+fn details(alert: &RealTimeSecretAlert, finding: &RealTimeSecretMatch, occurrences: u32) -> Value {
+    let mut details = json!({
+        "repository": alert.repository,
+        "commit": format!("https://github.com/{}/commit/{}", alert.repository, alert.commit_sha),
+        "file": finding.filename,
+        "line": finding.line_number,
+        "detector": finding.detector_name,
+        "severity": format!("{:?}", finding.severity),
+        "event_id": alert.event_id,
+        "detected_at": alert.detection_time.to_rfc3339(),
+        "detections": occurrences,
+    });
+    #[cfg(feature = "ai")]
+    if let Some(triage) = &alert.triage_result {
+        details["revocation_priority"] = json!(format!("{:?}", triage.revocation_priority));
+        details["impact_score"] = json!(triage.impact_score);
+        details["suggested_actions"] = json!(triage.suggested_actions);
+    }
+    if let Some(spread) = &alert.spread {
+        details["spread"] = json!(format!(
+            "{} occurrences in {} files across {} repositories",
+            spread.occurrences, spread.files, spread.repositories
+        ));
+    }
+    details
+}
+
+/// A PagerDuty Events API v2 trigger. Triggering an open alert's `dedup_key` again adds the
+/// event to it instead of paging.
+pub fn pagerduty_event(routing_key: &str, key: &str, alert: &RealTimeSecretAlert, finding: &RealTimeSecretMatch, occurrences: u32) -> Value {
+    json!({
+        "routing_key": routing_key,
+        "event_action": "trigger",
+        "dedup_key": key,
+        "payload": {
+            "summary": summary(alert, finding, PAGERDUTY_SUMMARY_LIMIT),
+            "source": alert.repository,
+            "severity": "critical",
+            "timestamp": alert.detection_time.to_rfc3339(),
+            "component": finding.filename,
+            "group": alert.repository,
+            "class": finding.detector_name,
+            "custom_details": details(alert, finding, occurrences),
+        },
+        "links": [{
+            "href": format!("https://github.com/{}/commit/{}", alert.repository, alert.commit_sha),
+            "text": "Commit",
+        }],
+    })
+}
+
+/// An Opsgenie alert. Creating one with the `alias` of an open alert only bumps its count.
+pub fn opsgenie_alert(key: &str, alert: &RealTimeSecretAlert, finding: &RealTimeSecretMatch, occurrences: u32) -> Value {
+    let details: serde_json::Map<String, Value> = details(alert, finding, occurrences)
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(name, value)| {
+            let text = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+            (name.clone(), Value::String(text))
+        })
+        .collect();
+    json!({
+        "message": summary(alert, finding, OPSGENIE_MESSAGE_LIMIT),
+        "alias": key,
+        "description": format!(
+            "{} found a secret in {} at https://github.com/{}/commit/{}. Rotate it before reviewing the finding.",
+            finding.detector_name, finding.filename, alert.repository, alert.commit_sha
+        ),
+        "priority": "P1",
+        "source": "GitArchiver",
+        "entity": alert.repository,
+        "tags": ["secret-exposure", format!("{:?}", finding.severity).to_lowercase()],
+        "details": details,
+    })
+}
+
+/// Incidents one alert opened and updated
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IncidentOutcome {
+    pub opened: usize,
+    pub updated: usize,
+}
+
+/// A PagerDuty service or Opsgenie team paged once per exposed secret
+pub struct IncidentNotifier {
+    label: String,
+    provider: IncidentProvider,
+    key: String,
+    api_url: String,
+    client: Client,
+    retry_policy: RetryPolicy,
+    /// Detections sent per incident key since startup. The provider deduplicates too, so a
+    /// restart does not page again for an incident that is still open.
+    detections: Mutex<HashMap<String, u32>>,
+}
+
+impl IncidentNotifier {
+    pub fn from_target(target: &IncidentTarget, http: &HttpConfig) -> Result<Self> {
+        let name = target.name.clone().unwrap_or_else(|| format!("{:?}", target.provider).to_lowercase());
+        let default_url = match target.provider {
+            IncidentProvider::PagerDuty => PAGERDUTY_API_URL,
+            IncidentProvider::Opsgenie => OPSGENIE_API_URL,
+        };
+        Ok(Self {
+            label: format!("incident:{}", name),
+            provider: target.provider,
+            key: target.key.clone(),
+            api_url: target.api_url.as_deref().unwrap_or(default_url).trim_end_matches('/').to_string(),
+            client: build_client(http)?,
+            retry_policy: RetryPolicy::from_config(http),
+            detections: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn from_targets(targets: &[IncidentTarget], http: &HttpConfig) -> Result<Vec<Self>> {
+        targets.iter().map(|target| Self::from_target(target, http)).collect()
+    }
+
+    /// `incident:<name>`, as reported in alert deliveries
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Open an incident for each secret in the alert worth paging for, or update the one
+    /// already open for it
+    pub async fn notify(&self, alert: &RealTimeSecretAlert) -> Result<IncidentOutcome> {
+        let mut outcome = IncidentOutcome::default();
+        for (key, finding) in paging_findings(alert)? {
+            let occurrences = self.detections.lock().unwrap().get(&key).copied().unwrap_or(0) + 1;
+            let request = match self.provider {
+                IncidentProvider::PagerDuty => self
+                    .client
+                    .post(format!("{}/v2/enqueue", self.api_url))
+                    .json(&pagerduty_event(&self.key, &key, alert, finding, occurrences)),
+                IncidentProvider::Opsgenie => self
+                    .client
+                    .post(format!("{}/v2/alerts", self.api_url))
+                    .header("Authorization", format!("GenieKey {}", self.key))
+                    .json(&opsgenie_alert(&key, alert, finding, occurrences)),
+            };
+            let response = send_with_retry(request, &self.retry_policy).await?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                return Err(anyhow!("{} returned {}: {}", self.label, status, body));
+            }
+            self.detections.lock().unwrap().insert(key, occurrences);
+            if occurrences == 1 {
+                outcome.opened += 1;
+            } else {
+                outcome.updated += 1;
+            }
+        }
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::sample_alert;
+
+    #[test]
+    fn test_one_incident_per_critical_secret() {
+        let mut alert = sample_alert();
+        let mut medium = alert.secrets_found[0].clone();
+        medium.matched_text = "AKIAI44QH8DHBEXAMPLE".to_string();
+        medium.hash = "hmac-sha256:medium".to_string();
+        medium.severity = SecretSeverity::Medium;
+        alert.secrets_found.push(alert.secrets_found[0].clone());
+        alert.secrets_found.push(medium);
+
+        let findings = paging_findings(&alert).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].0, "gitarchiver-hmac-sha256:sample");
+
+        alert.alert_severity = AlertSeverity::High;
+        assert!(paging_findings(&alert).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_payloads_leave_out_the_secret() {
+        let alert = sample_alert();
+        let finding = &alert.secrets_found[0];
+        let key = incident_key(finding).unwrap();
+        assert_eq!(key, format!("gitarchiver-{}", finding.hash));
+
+        let event = pagerduty_event("routing", &key, &alert, finding, 2);
+        assert_eq!(event["dedup_key"], key.as_str());
+        assert_eq!(event["payload"]["custom_details"]["detections"], 2);
+
+        let opsgenie = opsgenie_alert(&key, &alert, finding, 1);
+        assert_eq!(opsgenie["alias"], key.as_str());
+        assert!(opsgenie["message"].as_str().unwrap().chars().count() <= OPSGENIE_MESSAGE_LIMIT);
+        assert_eq!(opsgenie["details"]["line"], "42");
+
+        for payload in [event, opsgenie] {
+            assert!(!payload.to_string().contains(&finding.matched_text));
+        }
+    }
+}
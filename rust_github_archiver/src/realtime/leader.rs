@@ -0,0 +1,224 @@
+use anyhow::{anyhow, Result};
+use redis::{Client as RedisClient, Script};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Extend the lease only if we still own it
+const RENEW_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("PEXPIRE", KEYS[1], ARGV[2])
+else
+    return 0
+end
+"#;
+
+/// Delete the lease only if we still own it
+const RELEASE_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+else
+    return 0
+end
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LeaderRole {
+    Leader,
+    Follower,
+}
+
+/// A lease shared by electors in one process: the holder and when its lease expires
+#[derive(Debug, Default)]
+pub struct MemoryLease(Mutex<Option<(String, Instant)>>);
+
+enum LeaseStore {
+    Redis(RedisClient),
+    /// Lost on restart; for tests and instances without Redis
+    Memory(Arc<MemoryLease>),
+}
+
+/// Lease-based leader election over Redis. Each instance tries to hold a key with a TTL;
+/// the holder renews it on every poll cycle and a follower takes over once it expires.
+pub struct LeaderElector {
+    store: LeaseStore,
+    lease_key: String,
+    instance_id: String,
+    lease_ttl: Duration,
+    role: LeaderRole,
+}
+
+impl LeaderElector {
+    pub fn new(redis_url: &str, lease_key: &str, lease_ttl: Duration) -> Result<Self> {
+        let redis = RedisClient::open(redis_url)
+            .map_err(|e| anyhow!("Failed to connect to Redis: {}", e))?;
+        Ok(Self::with_store(LeaseStore::Redis(redis), lease_key, lease_ttl))
+    }
+
+    /// Elect among the electors sharing `lease` in this process
+    pub fn memory(lease: Arc<MemoryLease>, lease_key: &str, lease_ttl: Duration) -> Self {
+        Self::with_store(LeaseStore::Memory(lease), lease_key, lease_ttl)
+    }
+
+    fn with_store(store: LeaseStore, lease_key: &str, lease_ttl: Duration) -> Self {
+        let host = env::var("HOSTNAME").unwrap_or_else(|_| "unknown-host".to_string());
+        let instance_id = format!("{}-{}", host, Uuid::new_v4());
+        info!("Leader election enabled for {} as {}", lease_key, instance_id);
+
+        Self {
+            store,
+            lease_key: lease_key.to_string(),
+            instance_id,
+            lease_ttl,
+            role: LeaderRole::Follower,
+        }
+    }
+
+    /// Build an elector from `LEADER_LEASE_KEY`/`LEADER_LEASE_TTL`
+    pub fn from_env(redis_url: &str) -> Result<Self> {
+        let lease_key = env::var("LEADER_LEASE_KEY")
+            .unwrap_or_else(|_| "github_archiver:monitor:leader".to_string());
+        let lease_ttl = env::var("LEADER_LEASE_TTL")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .unwrap_or(30);
+
+        Self::new(redis_url, &lease_key, Duration::from_secs(lease_ttl))
+    }
+
+    /// Acquire the lease if free, or renew it if already held. Returns the resulting role.
+    pub fn acquire_or_renew(&mut self) -> Result<LeaderRole> {
+        let is_leader = match &self.store {
+            LeaseStore::Redis(redis) => {
+                let mut conn = redis.get_connection()
+                    .map_err(|e| anyhow!("Redis connection failed: {}", e))?;
+                let ttl_ms = self.lease_ttl.as_millis() as u64;
+
+                if self.role == LeaderRole::Leader {
+                    let renewed: i64 = Script::new(RENEW_SCRIPT)
+                        .key(&self.lease_key)
+                        .arg(&self.instance_id)
+                        .arg(ttl_ms)
+                        .invoke(&mut conn)?;
+                    renewed == 1
+                } else {
+                    let acquired: Option<String> = redis::cmd("SET")
+                        .arg(&self.lease_key)
+                        .arg(&self.instance_id)
+                        .arg("NX")
+                        .arg("PX")
+                        .arg(ttl_ms)
+                        .query(&mut conn)?;
+                    acquired.is_some()
+                }
+            }
+            LeaseStore::Memory(lease) => {
+                let mut held = lease.0.lock().map_err(|_| anyhow!("Leader lease lock poisoned"))?;
+                let now = Instant::now();
+                // Like the Redis key: a leader renews only a live lease it holds, anyone takes an expired one
+                let live = held.as_ref().filter(|(_, expires_at)| *expires_at > now);
+                let acquired = if self.role == LeaderRole::Leader {
+                    live.is_some_and(|(holder, _)| *holder == self.instance_id)
+                } else {
+                    live.is_none()
+                };
+                if acquired {
+                    *held = Some((self.instance_id.clone(), now + self.lease_ttl));
+                    true
+                } else {
+                    false
+                }
+            }
+        };
+
+        let new_role = if is_leader { LeaderRole::Leader } else { LeaderRole::Follower };
+        if new_role != self.role {
+            match new_role {
+                LeaderRole::Leader => info!("Instance {} became leader", self.instance_id),
+                LeaderRole::Follower => warn!("Instance {} lost leadership", self.instance_id),
+            }
+        }
+        self.role = new_role;
+        Ok(new_role)
+    }
+
+    /// Give up the lease so a follower can take over immediately
+    pub fn release(&mut self) -> Result<()> {
+        if self.role != LeaderRole::Leader {
+            return Ok(());
+        }
+
+        match &self.store {
+            LeaseStore::Redis(redis) => {
+                let mut conn = redis.get_connection()
+                    .map_err(|e| anyhow!("Redis connection failed: {}", e))?;
+                let _: i64 = Script::new(RELEASE_SCRIPT)
+                    .key(&self.lease_key)
+                    .arg(&self.instance_id)
+                    .invoke(&mut conn)?;
+            }
+            LeaseStore::Memory(lease) => {
+                let mut held = lease.0.lock().map_err(|_| anyhow!("Leader lease lock poisoned"))?;
+                if held.as_ref().is_some_and(|(holder, _)| *holder == self.instance_id) {
+                    *held = None;
+                }
+            }
+        }
+
+        self.role = LeaderRole::Follower;
+        info!("Instance {} released leadership", self.instance_id);
+        Ok(())
+    }
+
+    pub fn role(&self) -> LeaderRole {
+        self.role
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_contenders_never_both_lead() {
+        let lease = Arc::new(MemoryLease::default());
+        let mut first = LeaderElector::memory(lease.clone(), "leader", Duration::from_secs(30));
+        let mut second = LeaderElector::memory(lease, "leader", Duration::from_secs(30));
+        assert_ne!(first.instance_id(), second.instance_id());
+
+        assert_eq!(first.acquire_or_renew().unwrap(), LeaderRole::Leader);
+        for _ in 0..3 {
+            assert_eq!(second.acquire_or_renew().unwrap(), LeaderRole::Follower);
+            assert_eq!(first.acquire_or_renew().unwrap(), LeaderRole::Leader);
+        }
+
+        // A released lease is taken over at the next attempt
+        first.release().unwrap();
+        assert_eq!(first.role(), LeaderRole::Follower);
+        assert_eq!(second.acquire_or_renew().unwrap(), LeaderRole::Leader);
+        assert_eq!(first.acquire_or_renew().unwrap(), LeaderRole::Follower);
+    }
+
+    #[test]
+    fn test_expired_lease_passes_to_a_follower() {
+        let lease = Arc::new(MemoryLease::default());
+        let mut first = LeaderElector::memory(lease.clone(), "leader", Duration::from_millis(50));
+        let mut second = LeaderElector::memory(lease, "leader", Duration::from_millis(50));
+
+        assert_eq!(first.acquire_or_renew().unwrap(), LeaderRole::Leader);
+        assert_eq!(second.acquire_or_renew().unwrap(), LeaderRole::Follower);
+
+        // The leader stalls past its TTL without renewing
+        std::thread::sleep(Duration::from_millis(80));
+        assert_eq!(second.acquire_or_renew().unwrap(), LeaderRole::Leader);
+        assert_eq!(first.acquire_or_renew().unwrap(), LeaderRole::Follower);
+        assert_eq!(first.role(), LeaderRole::Follower);
+    }
+}
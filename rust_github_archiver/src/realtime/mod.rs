@@ -1,36 +1,118 @@
 use anyhow::{anyhow, Result};
-use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::Json,
-    routing::{get, post},
-    Router,
-};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tokio::time::{interval, Duration};
+use tokio::sync::{broadcast, RwLock};
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn, error, debug};
 use uuid::Uuid;
 
-use crate::github::DanglingCommitFetcher;
+use crate::auth::UserManager;
+use crate::canary::trip_alert;
+use crate::core::{HttpConfig, RetryPolicy, WebhookTarget, build_client, offline, send_with_retry};
+use crate::github::{DanglingCommitFetcher, GitHubCredentials};
+use crate::notifications::verify::endpoint_label;
+use crate::notifications::Notifier;
+use crate::performance::{QueueDepth, QuotaResource, SecretDatabase, WorkspaceQuota};
 use crate::secrets::SecretScanner;
+use crate::sinks::{ElasticsearchSink, RedactionProfile};
 use crate::ai::AITriageAgent;
 
+pub mod chat;
+pub mod filter;
+pub mod health;
+pub mod incidents;
+pub mod leader;
+pub mod pacing;
+pub mod queue;
+pub mod spread;
+pub mod toasts;
+pub mod webhook;
+
+pub use chat::{AlertFormatter, ChatNotifier, ChatOutcome, ChatThrottle};
+pub use filter::{EventFilter, HANDLED_EVENT_TYPES};
+pub use health::{HealthStatus, MonitorHealth, MonitorHealthAlert, WatchdogConfig};
+pub use incidents::{IncidentNotifier, IncidentOutcome};
+pub use leader::{LeaderElector, LeaderRole, MemoryLease};
+pub use pacing::RateLimitState;
+pub use queue::{EventQueue, QueueConfig};
+pub use spread::{SecretSpread, SpreadConfig, SpreadTracker};
+pub use toasts::{AlertToast, QuietHours, SeverityToast, ToastPreferences};
+pub use webhook::{delivery_to_event, verify_signature, WEBHOOK_SECRET_ENV};
+
+/// Alerts buffered per subscriber before slow subscribers start skipping
+const ALERT_STREAM_CAPACITY: usize = 256;
+
 /// Real-time GitHub event monitor
 pub struct GitHubEventMonitor {
-    client: Client,
+    client: Arc<RwLock<Client>>,
+    /// A GitHub App or token raises the Events API rate limit from 60 to 5,000+ an hour
+    credentials: GitHubCredentials,
     secret_scanner: SecretScanner,
     commit_fetcher: DanglingCommitFetcher,
     ai_agent: Option<AITriageAgent>,
-    last_event_id: Arc<RwLock<Option<String>>>,
+    /// ETag and newest event seen per Events API path
+    cursors: Arc<RwLock<HashMap<String, EventCursor>>>,
+    /// Poll each target's own events endpoint instead of only the public firehose
+    poll_target_endpoints: bool,
+    /// Shortest time between polls; GitHub's rate limit and `X-Poll-Interval` can stretch it
+    poll_interval: Duration,
+    /// Quota left as of the last Events API response
+    rate_limit: Arc<RwLock<RateLimitState>>,
+    /// Where each alerted secret has been seen, to escalate repeats instead of re-sending them
+    spread: Arc<RwLock<SpreadTracker>>,
     webhook_endpoints: Arc<RwLock<Vec<WebhookEndpoint>>>,
-    processing_queue: Arc<RwLock<Vec<GitHubEvent>>>,
+    /// Events waiting to be scanned; in memory unless `with_event_queue` gives a durable one
+    processing_queue: Arc<EventQueue>,
+    health: Arc<RwLock<MonitorHealth>>,
+    watchdog_config: WatchdogConfig,
+    leader: Option<Arc<tokio::sync::Mutex<LeaderElector>>>,
+    alert_indexer: Option<Arc<ElasticsearchSink>>,
+    /// Redaction of alerts on shared channels, which have no signed-in reader
+    redaction: RedactionProfile,
+    /// Scanner-wide redaction; alerts to individual users are redacted by their role above it
+    redaction_floor: RedactionProfile,
+    /// Latest event time per repository owner, drained by the hunter into the database
+    org_activity: OrgActivity,
+    /// Latest accepted event time per `owner/name`, drained into the repositories table
+    repository_activity: OrgActivity,
+    /// Distinct commits pushed per repository and day, drained into `commit_activity`
+    commit_activity: CommitActivity,
+    /// Redacted alerts fanned out to in-process subscribers such as the desktop app
+    alert_stream: broadcast::Sender<RealTimeSecretAlert>,
+    /// While set, polling is skipped; the loop keeps running so resuming is immediate
+    paused: Arc<AtomicBool>,
+    event_filter: EventFilter,
+    /// Slack/email delivery and the webhook body template; webhooks get the raw alert without it
+    notifier: Option<Arc<Notifier>>,
+    /// Slack, Discord and Teams webhooks getting formatted, batched messages
+    chat_notifiers: Vec<ChatNotifier>,
+    /// PagerDuty and Opsgenie, paged once per exposed secret of a Critical alert
+    incident_notifiers: Vec<IncidentNotifier>,
+    /// Findings database that sent alerts and their deliveries are logged to, for `alerts resend`
+    alert_log: Option<String>,
+    /// Findings database whose canaries turn an alert Critical when it contains one
+    canaries: Option<String>,
+    /// Users whose notification preferences are applied to each alert through `notifier`
+    users: Option<Arc<UserManager>>,
+    /// Daily GitHub API budget that polls and commit fetches draw on
+    quota: Option<WorkspaceQuota>,
+}
+
+/// Where polling of one Events API path left off
+#[derive(Debug, Clone, Default)]
+struct EventCursor {
+    etag: Option<String>,
+    last_event_id: Option<String>,
 }
 
+pub type OrgActivity = Arc<RwLock<HashMap<String, DateTime<Utc>>>>;
+pub type CommitActivity = Arc<RwLock<HashMap<(String, NaiveDate), u64>>>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GitHubEvent {
     pub id: String,
@@ -104,17 +186,38 @@ pub struct RealTimeSecretAlert {
     pub alert_severity: AlertSeverity,
     pub detection_time: DateTime<Utc>,
     pub triage_result: Option<crate::ai::TriageResult>,
+    /// Where else its most widespread secret has been found, once it has been found more than once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spread: Option<SecretSpread>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RealTimeSecretMatch {
     pub detector_name: String,
     pub matched_text: String,
+    /// The finding's keyed `secret_hash`, as stored in the findings database
+    #[serde(default)]
+    pub hash: String,
     pub line_number: Option<u32>,
     pub filename: String,
     pub severity: crate::secrets::SecretSeverity,
 }
 
+/// Where one alert went: delivery counts and errors across webhooks, Slack and email
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertDelivery {
+    /// Dropped by the event filter's minimum severity
+    pub filtered: bool,
+    pub delivered: usize,
+    /// Channels that took the alert: `slack`, `email`, `webhook:<endpoint label>`, `chat:<name>`
+    /// or `incident:<name>`
+    #[serde(default)]
+    pub delivered_to: Vec<String>,
+    /// Channels holding the alert for a quiet-window digest or a chat batch
+    pub held: Vec<String>,
+    pub errors: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AlertSeverity {
     Critical,  // Immediate action required
@@ -124,130 +227,563 @@ pub enum AlertSeverity {
 }
 
 impl GitHubEventMonitor {
-    /// Create a new real-time monitor
-    pub fn new() -> Self {
-        Self {
-            client: Client::new(),
+    /// Create a new real-time monitor; fails when the shared HTTP configuration does not
+    /// build a client, such as an unreadable CA bundle
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            client: Arc::new(RwLock::new(build_client(&HttpConfig::default())?)),
+            credentials: GitHubCredentials::from_env(),
             secret_scanner: SecretScanner::new(),
             commit_fetcher: DanglingCommitFetcher::new("github_token".to_string()),
             ai_agent: None,
-            last_event_id: Arc::new(RwLock::new(None)),
+            cursors: Arc::new(RwLock::new(HashMap::new())),
+            poll_target_endpoints: false,
+            poll_interval: Duration::from_secs(10),
+            rate_limit: Arc::new(RwLock::new(RateLimitState::default())),
+            spread: Arc::new(RwLock::new(SpreadTracker::new(SpreadConfig::default()))),
             webhook_endpoints: Arc::new(RwLock::new(Vec::new())),
-            processing_queue: Arc::new(RwLock::new(Vec::new())),
+            processing_queue: Arc::new(EventQueue::memory(QueueConfig::default())),
+            health: Arc::new(RwLock::new(MonitorHealth::new(Utc::now()))),
+            watchdog_config: WatchdogConfig::default(),
+            leader: None,
+            alert_indexer: None,
+            redaction: RedactionProfile::for_role_name(
+                &std::env::var("ALERT_REDACTION_ROLE").unwrap_or_else(|_| "analyst".to_string()),
+            ),
+            redaction_floor: RedactionProfile::from_env(),
+            org_activity: Arc::new(RwLock::new(HashMap::new())),
+            repository_activity: Arc::new(RwLock::new(HashMap::new())),
+            commit_activity: Arc::new(RwLock::new(HashMap::new())),
+            alert_stream: broadcast::channel(ALERT_STREAM_CAPACITY).0,
+            paused: Arc::new(AtomicBool::new(false)),
+            event_filter: EventFilter::default(),
+            notifier: None,
+            chat_notifiers: Vec::new(),
+            incident_notifiers: Vec::new(),
+            alert_log: None,
+            canaries: None,
+            users: None,
+            quota: None,
+        })
+    }
+
+    /// Charge GitHub API requests to a workspace budget; once it is spent, polls and commit
+    /// fetches fail until the next UTC day
+    pub fn with_quota(mut self, quota: WorkspaceQuota) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    async fn consume_api_call(&self) -> Result<()> {
+        match &self.quota {
+            Some(quota) => quota.consume(QuotaResource::GithubApiCalls, 1).await,
+            None => Ok(()),
         }
     }
 
+    /// Restrict which events are processed and which alerts are sent
+    pub fn with_event_filter(mut self, filter: EventFilter) -> Self {
+        info!("Processing event types: {}", filter.processed_types().join(", "));
+        self.event_filter = filter;
+        self
+    }
+
+    /// Poll `/orgs/{org}/events` and `/repos/{owner}/{name}/events` for the filter's targets
+    /// instead of filtering the public firehose, which carries only a sample of all events
+    pub fn with_target_endpoints(mut self, enabled: bool) -> Self {
+        self.poll_target_endpoints = enabled;
+        self
+    }
+
+    /// Poll no more often than every `interval`
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval.max(Duration::from_secs(1));
+        self
+    }
+
+    /// Queue events in a database or Redis, so events fetched but not yet scanned survive a restart
+    pub fn with_event_queue(mut self, queue: EventQueue) -> Self {
+        info!("Queueing events in {}", queue.kind());
+        self.processing_queue = Arc::new(queue);
+        self
+    }
+
+    /// Send alerts to Slack and email, and render webhook bodies from the notification templates
+    pub fn with_notifier(mut self, notifier: Arc<Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Send alerts to Slack Block Kit, Discord and Teams webhooks, batched during alert storms;
+    /// needs `with_notifier`, which posts them
+    pub fn with_chat_notifiers(mut self, notifiers: Vec<ChatNotifier>) -> Self {
+        self.chat_notifiers = notifiers;
+        self
+    }
+
+    /// Open PagerDuty or Opsgenie incidents for Critical alerts and immediate-revocation triage
+    pub fn with_incident_notifiers(mut self, notifiers: Vec<IncidentNotifier>) -> Self {
+        self.incident_notifiers = notifiers;
+        self
+    }
+
+    /// Log every sent alert and its deliveries to the findings database at `database_path`
+    pub fn with_alert_log(mut self, database_path: impl Into<String>) -> Self {
+        self.alert_log = Some(database_path.into());
+        self
+    }
+
+    /// Check each alert's findings against the canaries in the findings database at
+    /// `database_path`, recording every sighting
+    pub fn with_canaries(mut self, database_path: impl Into<String>) -> Self {
+        self.canaries = Some(database_path.into());
+        self
+    }
+
+    /// Also alert each user whose notification preferences ask for it; needs `with_notifier`
+    pub fn with_user_alerts(mut self, users: Arc<UserManager>) -> Self {
+        self.users = Some(users);
+        self
+    }
+
+    /// Stop polling until `resume` is called
+    pub fn pause(&self) {
+        info!("Pausing GitHub Events API monitoring");
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        info!("Resuming GitHub Events API monitoring");
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Receive every alert the monitor sends, after redaction
+    pub fn subscribe_alerts(&self) -> broadcast::Receiver<RealTimeSecretAlert> {
+        self.alert_stream.subscribe()
+    }
+
+    /// Override the redaction applied to alerts before they leave the process
+    pub fn with_redaction(mut self, profile: RedactionProfile) -> Self {
+        self.redaction = profile;
+        self
+    }
+
+    /// Redact alerts at least as much as `floor`, whatever `ALERT_REDACTION_ROLE` allows
+    pub fn with_redaction_floor(mut self, floor: RedactionProfile) -> Self {
+        self.redaction = self.redaction.at_least(floor);
+        self.redaction_floor = floor;
+        self
+    }
+
+    /// Mirror alerts into an Elasticsearch/OpenSearch index
+    pub fn with_alert_indexer(mut self, indexer: Arc<ElasticsearchSink>) -> Self {
+        self.alert_indexer = Some(indexer);
+        self
+    }
+
+    /// Only poll while holding the leader lease; followers stay warm and take over on expiry
+    pub fn with_leader_election(mut self, elector: LeaderElector) -> Self {
+        self.leader = Some(Arc::new(tokio::sync::Mutex::new(elector)));
+        self
+    }
+
+    /// Override the watchdog thresholds
+    pub fn with_watchdog_config(mut self, watchdog_config: WatchdogConfig) -> Self {
+        self.watchdog_config = watchdog_config;
+        self
+    }
+
     /// Initialize with AI triage capabilities
     pub async fn with_ai_triage(mut self, ai_agent: AITriageAgent) -> Self {
         self.ai_agent = Some(ai_agent);
         self
     }
 
-    /// Start monitoring GitHub Events API
-    pub async fn start_monitoring(&self) -> Result<()> {
+    /// Poll the GitHub Events API until `shutdown` is cancelled. Events already fetched are
+    /// scanned before returning, and the leader lease, if held, is released.
+    pub async fn start_monitoring(&self, shutdown: CancellationToken) -> Result<()> {
         info!("Starting GitHub Events API monitoring");
 
-        let mut poll_interval = interval(Duration::from_secs(10)); // Poll every 10 seconds
+        let mut delay = Duration::ZERO;
+        let mut was_paused = false;
+        let mut waited_for_quota = false;
 
         loop {
-            poll_interval.tick().await;
+            tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => break,
+                _ = tokio::time::sleep(delay) => {}
+            }
+            delay = self.poll_interval;
+            self.send_due_digests().await;
+            self.flush_chat_batches().await;
+
+            if self.is_paused() {
+                was_paused = true;
+                continue;
+            }
+            if was_paused {
+                // A pause is not a stall: start a fresh health window
+                *self.health.write().await = MonitorHealth::new(Utc::now());
+                was_paused = false;
+            }
+
+            if !self.ensure_leadership().await {
+                continue;
+            }
+            if waited_for_quota {
+                // Neither is waiting for the rate limit to reset
+                *self.health.write().await = MonitorHealth::new(Utc::now());
+                waited_for_quota = false;
+            }
 
             match self.poll_events().await {
                 Ok(events) => {
+                    self.health.write().await.record_success(Utc::now(), events.len());
                     if !events.is_empty() {
                         info!("Received {} new events", events.len());
-                        self.process_events(events).await?;
                     }
+                    // Also picks up events left over from before a restart and retries failures
+                    self.process_events(events).await;
                 }
                 Err(e) => {
                     error!("Error polling events: {}", e);
+                    self.health.write().await.record_failure(&e.to_string());
                     // Implement exponential backoff on errors
-                    tokio::time::sleep(Duration::from_secs(30)).await;
+                    tokio::select! {
+                        _ = shutdown.cancelled() => break,
+                        _ = tokio::time::sleep(Duration::from_secs(30)) => {}
+                    }
                 }
             }
+
+            self.run_watchdog().await;
+            delay = self.pace().await;
+            waited_for_quota = self.rate_limit.read().await.exhausted(Utc::now());
         }
+
+        info!("Stopped GitHub Events API monitoring");
+        if let Err(e) = self.step_down().await {
+            warn!("Failed to release the leader lease: {}", e);
+        }
+        Ok(())
     }
 
-    /// Poll GitHub Events API for new events
-    async fn poll_events(&self) -> Result<Vec<GitHubEvent>> {
-        let url = "https://api.github.com/events";
-        
-        let mut request_builder = self.client.get(url);
-        
-        // Add conditional request based on last event ID
-        if let Some(last_id) = self.last_event_id.read().await.as_ref() {
-            // GitHub Events API doesn't support If-Modified-Since, so we filter client-side
-            debug!("Polling for events after ID: {}", last_id);
+    /// Time until the next poll, slowed down as the rate limit runs low
+    async fn pace(&self) -> Duration {
+        let requests = if self.poll_target_endpoints { self.event_filter.target_endpoints().len() } else { 1 };
+        let now = Utc::now();
+        let mut rate_limit = self.rate_limit.write().await;
+        let delay = rate_limit.next_delay(self.poll_interval, requests, now);
+        rate_limit.next_poll_secs = delay.as_secs();
+        if delay > self.poll_interval {
+            debug!(
+                "Next poll in {}s ({:?} of {:?} requests left)",
+                delay.as_secs(),
+                rate_limit.remaining,
+                rate_limit.limit
+            );
+        }
+        if rate_limit.exhausted(now) {
+            warn!("GitHub rate limit exhausted, polling again in {}s", delay.as_secs());
         }
+        delay
+    }
 
-        let response = request_builder
-            .header("User-Agent", "GitHubArchiver/2.0")
-            .header("Accept", "application/vnd.github.v3+json")
-            .send()
-            .await?;
+    /// GitHub's rate limit as of the last poll, and when the next poll is due
+    pub async fn rate_limit(&self) -> RateLimitState {
+        self.rate_limit.read().await.clone()
+    }
 
-        if !response.status().is_success() {
-            return Err(anyhow!("GitHub API returned status: {}", response.status()));
+    /// Renew or acquire the leader lease. Returns whether this instance should poll.
+    async fn ensure_leadership(&self) -> bool {
+        let Some(leader) = &self.leader else {
+            return true;
+        };
+
+        let mut elector = leader.lock().await;
+        let previous = elector.role();
+        let role = match elector.acquire_or_renew() {
+            Ok(role) => role,
+            Err(e) => {
+                // Without Redis we cannot prove the lease is still ours, so stand down
+                error!("Leader election failed, standing down: {}", e);
+                return false;
+            }
+        };
+
+        if role == LeaderRole::Leader && previous == LeaderRole::Follower {
+            // Start a fresh health window so the takeover is not reported as a stall
+            *self.health.write().await = MonitorHealth::new(Utc::now());
+            self.cursors.write().await.clear();
         }
 
-        let events: Vec<GitHubEvent> = response.json().await?;
-        
-        // Filter for new events only
-        let last_id = self.last_event_id.read().await.clone();
-        let new_events = if let Some(last_id) = last_id {
-            events.into_iter()
-                .take_while(|event| event.id != last_id)
-                .collect()
-        } else {
-            events
+        role == LeaderRole::Leader
+    }
+
+    /// Release the leader lease, if held, so a follower can take over immediately
+    pub async fn step_down(&self) -> Result<()> {
+        if let Some(leader) = &self.leader {
+            leader.lock().await.release()?;
+        }
+        Ok(())
+    }
+
+    /// Current health snapshot of the poller
+    pub async fn health_report(&self) -> (HealthStatus, MonitorHealth) {
+        let health = self.health.read().await.clone();
+        (health.evaluate(&self.watchdog_config, Utc::now()), health)
+    }
+
+    /// Check for stalls, alert once per incident and attempt recovery
+    async fn run_watchdog(&self) {
+        let now = Utc::now();
+        let (status, should_recover) = {
+            let health = self.health.read().await;
+            let status = health.evaluate(&self.watchdog_config, now);
+            let should_recover = !status.is_healthy() && health.can_attempt_recovery(&self.watchdog_config, now);
+            (status, should_recover)
         };
 
-        // Update last event ID
-        if let Some(first_event) = new_events.first() {
-            *self.last_event_id.write().await = Some(first_event.id.clone());
+        if status.is_healthy() {
+            let mut health = self.health.write().await;
+            if health.alert_active {
+                info!("Event monitor recovered");
+                health.alert_active = false;
+                let snapshot = health.clone();
+                drop(health);
+                self.send_health_alert(HealthStatus::Healthy, snapshot, false).await;
+            }
+            return;
         }
 
-        Ok(new_events)
+        if should_recover {
+            warn!("Event monitor unhealthy ({:?}), rebuilding HTTP client", status);
+            self.recover().await;
+        }
+
+        let snapshot = {
+            let mut health = self.health.write().await;
+            if health.alert_active && !should_recover {
+                return;
+            }
+            health.alert_active = true;
+            health.clone()
+        };
+        self.send_health_alert(status, snapshot, should_recover).await;
     }
 
-    /// Process incoming events for secret detection
-    async fn process_events(&self, events: Vec<GitHubEvent>) -> Result<()> {
-        // Add events to processing queue
-        {
-            let mut queue = self.processing_queue.write().await;
-            queue.extend(events);
+    /// Replace the HTTP client and reset polling state
+    async fn recover(&self) {
+        match build_client(&HttpConfig::default()) {
+            Ok(client) => *self.client.write().await = client,
+            Err(e) => error!("Failed to rebuild HTTP client during recovery: {}", e),
         }
+        self.cursors.write().await.clear();
+        self.health.write().await.record_recovery(Utc::now());
+    }
 
-        // Process events from queue
-        self.process_queue().await?;
+    /// Send a monitor health alert to webhook endpoints subscribed to `health`
+    async fn send_health_alert(&self, status: HealthStatus, health: MonitorHealth, recovery_attempted: bool) {
+        match &status {
+            HealthStatus::Healthy => info!("🩺 Event monitor healthy again"),
+            HealthStatus::Degraded { reason } => warn!("🩺 Event monitor degraded: {}", reason),
+            HealthStatus::Stalled { reason } => error!("🩺 Event monitor stalled: {}", reason),
+        }
 
-        Ok(())
+        let alert = MonitorHealthAlert {
+            status,
+            health,
+            detected_at: Utc::now(),
+            recovery_attempted,
+        };
+        let payload = match serde_json::to_value(&alert) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Failed to serialize health alert: {}", e);
+                return;
+            }
+        };
+
+        let endpoints = self.webhook_endpoints.read().await;
+        for endpoint in endpoints.iter().filter(|e| e.active && e.events.iter().any(|ev| ev == "health")) {
+            if let Err(e) = self.post_webhook_payload(&payload, endpoint).await {
+                error!("Failed to send health alert to {}: {}", endpoint.url, e);
+            }
+        }
     }
 
-    /// Process events from the queue
-    async fn process_queue(&self) -> Result<()> {
-        let events = {
-            let mut queue = self.processing_queue.write().await;
-            let events = queue.clone();
-            queue.clear();
-            events
+    /// Poll GitHub Events API for new events, from the firehose or each target's endpoint.
+    /// Fails only when every endpoint does.
+    async fn poll_events(&self) -> Result<Vec<GitHubEvent>> {
+        let endpoints = if self.poll_target_endpoints {
+            self.event_filter.target_endpoints()
+        } else {
+            vec!["/events".to_string()]
         };
 
-        for event in events {
-            match self.process_single_event(event).await {
-                Ok(_) => {}
+        let mut events = Vec::new();
+        let mut seen = HashSet::new();
+        let mut failures = Vec::new();
+        for endpoint in &endpoints {
+            match self.poll_endpoint(endpoint).await {
+                // The firehose and a target's endpoint can both carry the same event
+                Ok(polled) => events.extend(polled.into_iter().filter(|event| seen.insert(event.id.clone()))),
                 Err(e) => {
-                    error!("Error processing event: {}", e);
-                    // Continue processing other events
+                    warn!("Polling {} failed: {}", endpoint, e);
+                    failures.push(e);
                 }
             }
         }
+        if failures.len() == endpoints.len() {
+            if let Some(e) = failures.pop() {
+                return Err(e);
+            }
+        }
+        Ok(events)
+    }
 
-        Ok(())
+    /// New events from one Events API path. Each path keeps its own ETag, so an unchanged
+    /// endpoint answers `304 Not Modified` without using rate limit.
+    async fn poll_endpoint(&self, endpoint: &str) -> Result<Vec<GitHubEvent>> {
+        let url = format!("{}{}", offline::policy().github_api_base()?, endpoint);
+        let cursor = self.cursors.read().await.get(endpoint).cloned().unwrap_or_default();
+        self.consume_api_call().await?;
+
+        let mut request = self.client.read().await.get(&url).header("Accept", "application/vnd.github.v3+json");
+        if let Some(token) = self.credentials.token().await? {
+            request = request.bearer_auth(token);
+        }
+        if let Some(etag) = &cursor.etag {
+            request = request.header("If-None-Match", etag);
+        }
+        let response = send_with_retry(request, &RetryPolicy::default()).await?;
+        self.rate_limit.write().await.observe(response.headers(), Utc::now());
+
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            debug!("No new events at {}", endpoint);
+            return Ok(Vec::new());
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("GitHub API returned status: {}", response.status()));
+        }
+        let etag = response.headers().get("etag").and_then(|v| v.to_str().ok()).map(str::to_string);
+        let events: Vec<GitHubEvent> = response.json().await?;
+
+        // Events come newest first; keep those after the last one seen here
+        let new_events: Vec<GitHubEvent> = match &cursor.last_event_id {
+            Some(last_id) => events.into_iter().take_while(|event| &event.id != last_id).collect(),
+            None => events,
+        };
+        let last_event_id = new_events.first().map(|event| event.id.clone()).or(cursor.last_event_id);
+        self.cursors.write().await.insert(endpoint.to_string(), EventCursor { etag, last_event_id });
+        Ok(new_events)
+    }
+
+    /// Queue incoming events, then scan everything due in the queue. Events the queue cannot
+    /// take are scanned straight away rather than dropped.
+    async fn process_events(&self, events: Vec<GitHubEvent>) {
+        if !events.is_empty() {
+            if let Err(e) = self.processing_queue.enqueue(events.clone()).await {
+                error!("Failed to queue {} events, processing them unqueued: {}", events.len(), e);
+                for event in events {
+                    if let Err(e) = self.process_single_event(event).await {
+                        error!("Error processing event: {}", e);
+                    }
+                }
+            }
+        }
+        if let Err(e) = self.process_queue().await {
+            error!("Failed to process the event queue: {}", e);
+        }
+    }
+
+    /// Lease batches until none is due. Each event is acknowledged once processed; one that
+    /// fails is retried later and dead-lettered after the queue's `max_attempts`.
+    async fn process_queue(&self) -> Result<()> {
+        loop {
+            let batch = self.processing_queue.lease().await?;
+            let full = batch.len() >= self.processing_queue.config().batch_size.max(1);
+            for queued in batch {
+                let event_id = queued.event.id.clone();
+                match self.process_single_event(queued.event.clone()).await {
+                    Ok(()) => self.processing_queue.ack(&event_id).await?,
+                    Err(e) => {
+                        error!("Error processing event {}: {}", event_id, e);
+                        if self.processing_queue.fail(&queued, &e.to_string()).await? {
+                            error!(
+                                "Event {} ({} for {}) failed {} times and was dead-lettered",
+                                event_id, queued.event.event_type, queued.event.repo.name, queued.attempts
+                            );
+                        }
+                    }
+                }
+            }
+            if !full {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Queue events received from outside the poll loop, such as webhook deliveries
+    pub async fn enqueue_events(&self, events: Vec<GitHubEvent>) -> Result<usize> {
+        self.processing_queue.enqueue(events).await
+    }
+
+    /// Events waiting in, leased from and dead-lettered by the processing queue
+    pub async fn queue_depth(&self) -> Result<QueueDepth> {
+        self.processing_queue.depth().await
+    }
+
+    /// Shared map of the latest event time per lowercase repository owner
+    pub fn org_activity(&self) -> OrgActivity {
+        self.org_activity.clone()
+    }
+
+    /// Shared map of the latest accepted event time per repository
+    pub fn repository_activity(&self) -> OrgActivity {
+        self.repository_activity.clone()
+    }
+
+    /// Shared map of distinct commits pushed per repository and day
+    pub fn commit_activity(&self) -> CommitActivity {
+        self.commit_activity.clone()
     }
 
     /// Process a single GitHub event
     async fn process_single_event(&self, event: GitHubEvent) -> Result<()> {
+        if let Some((owner, _)) = event.repo.name.split_once('/') {
+            let mut activity = self.org_activity.write().await;
+            let seen = activity.entry(owner.to_lowercase()).or_insert(event.created_at);
+            if event.created_at > *seen {
+                *seen = event.created_at;
+            }
+        }
+
+        if !self.event_filter.accepts_event(&event) {
+            debug!("Filtered out {} for {}", event.event_type, event.repo.name);
+            return Ok(());
+        }
+
+        {
+            let mut activity = self.repository_activity.write().await;
+            let seen = activity.entry(event.repo.name.clone()).or_insert(event.created_at);
+            if event.created_at > *seen {
+                *seen = event.created_at;
+            }
+        }
+        // Commit volume is the denominator of the findings-per-1k-commits scorecard metric
+        if event.event_type == "PushEvent" {
+            if let Some(commits) = event.payload["distinct_size"].as_u64() {
+                let key = (event.repo.name.clone(), event.created_at.date_naive());
+                *self.commit_activity.write().await.entry(key).or_insert(0) += commits;
+            }
+        }
+
         match event.event_type.as_str() {
             "PushEvent" => self.process_push_event(event).await,
             "PullRequestEvent" => self.process_pull_request_event(event).await,
@@ -269,6 +805,8 @@ impl GitHubEventMonitor {
 
         // Check if this is a zero-commit push (before hash with no corresponding commit)
         if payload.before != "0000000000000000000000000000000000000000" {
+            // Out of API budget the event fails, so the queue retries it later instead of skipping it
+            self.consume_api_call().await?;
             match self.check_for_dangling_commit(&event.repo.name, &payload.before).await {
                 Ok(Some(commit_data)) => {
                     info!("Found dangling commit: {} in {}", payload.before, event.repo.name);
@@ -283,7 +821,7 @@ impl GitHubEventMonitor {
                             secrets,
                         ).await?;
                         
-                        self.send_alert(alert).await?;
+                        self.raise_alert(alert).await?;
                     }
                 }
                 Ok(None) => {
@@ -318,6 +856,7 @@ impl GitHubEventMonitor {
                     .map(|s| RealTimeSecretMatch {
                         detector_name: s.detector_name,
                         matched_text: s.matched_text,
+                        hash: s.hash,
                         line_number: None,
                         filename: "PR_METADATA".to_string(),
                         severity: s.severity,
@@ -332,9 +871,10 @@ impl GitHubEventMonitor {
                     alert_severity: AlertSeverity::Medium,
                     detection_time: Utc::now(),
                     triage_result: None,
+                    spread: None,
                 };
 
-                self.send_alert(alert).await?;
+                self.raise_alert(alert).await?;
             }
         }
 
@@ -356,6 +896,7 @@ impl GitHubEventMonitor {
                         .map(|s| RealTimeSecretMatch {
                             detector_name: s.detector_name,
                             matched_text: s.matched_text,
+                            hash: s.hash,
                             line_number: None,
                             filename: "ISSUE_COMMENT".to_string(),
                             severity: s.severity,
@@ -370,9 +911,10 @@ impl GitHubEventMonitor {
                         alert_severity: AlertSeverity::Low,
                         detection_time: Utc::now(),
                         triage_result: None,
+                        spread: None,
                     };
 
-                    self.send_alert(alert).await?;
+                    self.raise_alert(alert).await?;
                 }
             }
         }
@@ -399,6 +941,7 @@ impl GitHubEventMonitor {
                     .map(|s| RealTimeSecretMatch {
                         detector_name: s.detector_name,
                         matched_text: s.matched_text,
+                        hash: s.hash,
                         line_number: None,
                         filename: "RELEASE_METADATA".to_string(),
                         severity: s.severity,
@@ -413,9 +956,10 @@ impl GitHubEventMonitor {
                     alert_severity: AlertSeverity::Medium,
                     detection_time: Utc::now(),
                     triage_result: None,
+                    spread: None,
                 };
 
-                self.send_alert(alert).await?;
+                self.raise_alert(alert).await?;
             }
         }
 
@@ -455,6 +999,7 @@ impl GitHubEventMonitor {
             .map(|s| RealTimeSecretMatch {
                 detector_name: s.detector_name.clone(),
                 matched_text: s.matched_text.clone(),
+                hash: s.hash.clone(),
                 line_number: s.line_number,
                 filename: s.filename.clone().unwrap_or("UNKNOWN".to_string()),
                 severity: s.severity.clone(),
@@ -502,11 +1047,74 @@ impl GitHubEventMonitor {
             alert_severity,
             detection_time: Utc::now(),
             triage_result,
+            spread: None,
         })
     }
 
+    /// Send an alert for newly found secrets, unless each was already alerted on and has not
+    /// spread far enough since to raise the severity. Canaries are alerted on every time.
+    async fn raise_alert(&self, mut alert: RealTimeSecretAlert) -> Result<()> {
+        let mut canary = false;
+        if let Some(path) = self.canaries.clone() {
+            // A failed check still sends the alert, just not flagged
+            let mut checked = alert.clone();
+            let result = tokio::task::spawn_blocking(move || -> Result<(RealTimeSecretAlert, usize)> {
+                let tripped = trip_alert(&SecretDatabase::new(&path)?, &mut checked)?;
+                Ok((checked, tripped))
+            })
+            .await;
+            match result {
+                Ok(Ok((flagged, tripped))) if tripped > 0 => {
+                    error!("🐤 CANARY TRIPPED: {} canary credentials seen in {}", tripped, flagged.repository);
+                    alert = flagged;
+                    canary = true;
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => error!("Failed to check the alert for {} against canaries: {}", alert.repository, e),
+                Err(e) => error!("Failed to check the alert for {} against canaries: {}", alert.repository, e),
+            }
+        }
+        let repository = alert.repository.clone();
+        let canary_alert = canary.then(|| alert.clone());
+        let escalated = self.spread.write().await.observe(alert, Utc::now());
+        match escalated.or(canary_alert) {
+            Some(alert) => {
+                self.send_alert(alert).await?;
+            }
+            None => debug!("Secrets in {} were already alerted on; counted the repeat", repository),
+        }
+        Ok(())
+    }
+
     /// Send alert to configured endpoints
-    async fn send_alert(&self, alert: RealTimeSecretAlert) -> Result<()> {
+    pub async fn send_alert(&self, alert: RealTimeSecretAlert) -> Result<AlertDelivery> {
+        let mut delivery = AlertDelivery::default();
+        if !self.event_filter.accepts_alert(&alert) {
+            debug!("Alert for {} is below the minimum severity", alert.repository);
+            delivery.filtered = true;
+            return Ok(delivery);
+        }
+        // Incident keys come from the stored finding hash; the secrets themselves are never sent
+        for incidents in &self.incident_notifiers {
+            match incidents.notify(&alert).await {
+                Ok(IncidentOutcome { opened: 0, updated: 0 }) => {}
+                Ok(outcome) => {
+                    info!(
+                        "{}: opened {} and updated {} incidents for {}",
+                        incidents.label(), outcome.opened, outcome.updated, alert.repository
+                    );
+                    delivery.delivered += 1;
+                    delivery.delivered_to.push(incidents.label().to_string());
+                }
+                Err(e) => {
+                    error!("Failed to page {} about {}: {}", incidents.label(), alert.repository, e);
+                    delivery.errors.push(format!("{}: {}", incidents.label(), e));
+                }
+            }
+        }
+
+        let unredacted = alert;
+        let alert = self.redaction.redact_alert(&unredacted);
         info!("Sending alert for {} secrets in repo: {}", 
               alert.secrets_found.len(), alert.repository);
 
@@ -530,30 +1138,169 @@ impl GitHubEventMonitor {
             }
         }
 
-        // Send to webhook endpoints
+        let has_webhooks = !self.active_webhooks().await.is_empty();
+        if has_webhooks && self.notifier.as_ref().is_some_and(|n| n.hold("webhook", &alert)) {
+            delivery.held.push("webhook".to_string());
+        } else if has_webhooks {
+            for (url, result) in self.send_webhooks(&alert).await {
+                match result {
+                    Ok(_) => {
+                        debug!("Sent alert to webhook: {}", url);
+                        delivery.delivered += 1;
+                        delivery.delivered_to.push(format!("webhook:{}", endpoint_label(&url)));
+                    }
+                    Err(e) => {
+                        error!("Failed to send webhook to {}: {}", url, e);
+                        delivery.errors.push(format!("webhook:{}: {}", endpoint_label(&url), e));
+                    }
+                }
+            }
+        }
+
+        if let Some(notifier) = &self.notifier {
+            let mut deliveries = vec![notifier.notify(&alert).await];
+            if let Some(users) = &self.users {
+                match users.alert_recipients().await {
+                    Ok(recipients) => deliveries.push(notifier.notify_users(&unredacted, &recipients, self.redaction_floor).await),
+                    Err(e) => error!("Failed to load user notification preferences: {}", e),
+                }
+            }
+            for notified in deliveries {
+                delivery.delivered += notified.delivered;
+                delivery.delivered_to.extend(notified.delivered_to);
+                delivery.held.extend(notified.held);
+                delivery.errors.extend(notified.errors);
+            }
+        }
+
+        // Chat targets post through the notifier
+        if let Some(notifier) = &self.notifier {
+            for chat in &self.chat_notifiers {
+                match chat.offer(notifier, &alert).await {
+                    Ok(ChatOutcome::Skipped) => {}
+                    Ok(ChatOutcome::Batched) => delivery.held.push(chat.label().to_string()),
+                    Ok(ChatOutcome::Sent) => {
+                        delivery.delivered += 1;
+                        delivery.delivered_to.push(chat.label().to_string());
+                    }
+                    Err(e) => {
+                        error!("Failed to send {} alert for {}: {}", chat.label(), alert.repository, e);
+                        delivery.errors.push(format!("{}: {}", chat.label(), e));
+                    }
+                }
+            }
+        }
+
+        if let Some(path) = self.alert_log.clone() {
+            let (logged, outcome) = (alert.clone(), delivery.clone());
+            let recorded =
+                tokio::task::spawn_blocking(move || SecretDatabase::new(&path)?.record_alert(&logged, &outcome)).await;
+            match recorded {
+                Ok(Ok(id)) => debug!("Logged alert {} for {}", id, alert.repository),
+                Ok(Err(e)) => error!("Failed to log alert for {}: {}", alert.repository, e),
+                Err(e) => error!("Failed to log alert for {}: {}", alert.repository, e),
+            }
+        }
+
+        // Having no subscribers is not an error
+        let _ = self.alert_stream.send(alert.clone());
+
+        if let Some(indexer) = &self.alert_indexer {
+            if let Err(e) = indexer.index_alert(&alert).await {
+                error!("Failed to index alert for {}: {}", alert.repository, e);
+            }
+        }
+
+        Ok(delivery)
+    }
+
+    /// Send an alert to every active webhook endpoint, returning each endpoint's outcome
+    pub async fn send_webhooks(&self, alert: &RealTimeSecretAlert) -> Vec<(String, Result<()>)> {
         let endpoints = self.webhook_endpoints.read().await;
+        let mut results = Vec::new();
         for endpoint in endpoints.iter().filter(|e| e.active) {
-            match self.send_webhook(&alert, endpoint).await {
-                Ok(_) => debug!("Sent alert to webhook: {}", endpoint.url),
-                Err(e) => error!("Failed to send webhook to {}: {}", endpoint.url, e),
+            results.push((endpoint.url.clone(), self.send_webhook(alert, endpoint).await));
+        }
+        results
+    }
+
+    /// Send alerts held during quiet windows that have since ended, and the email digest when due
+    async fn send_due_digests(&self) {
+        let Some(notifier) = &self.notifier else {
+            return;
+        };
+
+        for (channel, alerts) in notifier.take_due_digests() {
+            info!("Quiet window over: sending a digest of {} alerts to {}", alerts.len(), channel);
+            if channel != "webhook" {
+                if let Err(e) = notifier.send_digest(&channel, &alerts).await {
+                    error!("Failed to send {} digest: {}", channel, e);
+                }
+                continue;
+            }
+
+            let payload = match notifier.templates().webhook_digest_payload(&alerts) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    error!("Failed to render webhook digest: {}", e);
+                    continue;
+                }
+            };
+            for endpoint in self.active_webhooks().await {
+                if let Err(e) = self.post_webhook_payload(&payload, &endpoint).await {
+                    error!("Failed to send webhook digest to {}: {}", endpoint.url, e);
+                }
             }
         }
 
-        Ok(())
+        if let Err(e) = notifier.send_due_email_digest().await {
+            error!("Failed to send the email digest: {}", e);
+        }
+    }
+
+    /// Send the alerts chat targets batched while they were throttled
+    async fn flush_chat_batches(&self) {
+        let Some(notifier) = &self.notifier else {
+            return;
+        };
+        for chat in &self.chat_notifiers {
+            match chat.flush(notifier).await {
+                Ok(0) => {}
+                Ok(sent) => info!("Sent a batch of {} alerts to {}", sent, chat.label()),
+                Err(e) => error!("Failed to send batched alerts to {}: {}", chat.label(), e),
+            }
+        }
+    }
+
+    /// The chat target reported in deliveries as `label` (`chat:<name>`)
+    pub fn chat_notifier(&self, label: &str) -> Option<&ChatNotifier> {
+        self.chat_notifiers.iter().find(|chat| chat.label() == label)
+    }
+
+    /// Endpoints that currently receive alerts
+    pub async fn active_webhooks(&self) -> Vec<WebhookEndpoint> {
+        self.webhook_endpoints.read().await.iter().filter(|e| e.active).cloned().collect()
     }
 
     /// Send webhook notification
-    async fn send_webhook(&self, alert: &RealTimeSecretAlert, endpoint: &WebhookEndpoint) -> Result<()> {
-        let payload = serde_json::to_value(alert)?;
-        
-        let mut request = self.client.post(&endpoint.url)
+    pub async fn send_webhook(&self, alert: &RealTimeSecretAlert, endpoint: &WebhookEndpoint) -> Result<()> {
+        let payload = match &self.notifier {
+            Some(notifier) => notifier.templates().webhook_payload(alert)?,
+            None => serde_json::to_value(alert)?,
+        };
+        self.post_webhook_payload(&payload, endpoint).await
+    }
+
+    /// POST a JSON payload to a webhook endpoint, signing it when a secret is configured
+    async fn post_webhook_payload(&self, payload: &serde_json::Value, endpoint: &WebhookEndpoint) -> Result<()> {
+        let mut request = self.client.read().await.post(&endpoint.url)
             .header("Content-Type", "application/json")
             .header("User-Agent", "GitHubArchiver/2.0")
-            .json(&payload);
+            .json(payload);
 
         // Add webhook signature if secret is configured
         if let Some(secret) = &endpoint.secret {
-            let signature = self.generate_webhook_signature(&payload, secret)?;
+            let signature = self.generate_webhook_signature(payload, secret)?;
             request = request.header("X-Hub-Signature-256", signature);
         }
 
@@ -597,52 +1344,20 @@ impl GitHubEventMonitor {
         Ok(id)
     }
 
+    /// Register the webhooks from the `notifications` config section
+    pub async fn add_webhook_targets(&self, targets: &[WebhookTarget]) -> Result<()> {
+        for target in targets {
+            self.add_webhook_endpoint(target.url.clone(), target.secret.clone(), target.events.clone()).await?;
+        }
+        Ok(())
+    }
+
     /// Remove webhook endpoint
     pub async fn remove_webhook_endpoint(&self, id: Uuid) -> Result<()> {
         let mut endpoints = self.webhook_endpoints.write().await;
         endpoints.retain(|e| e.id != id);
         Ok(())
     }
-
-    /// Create webhook server
-    pub fn create_webhook_server() -> Router {
-        Router::new()
-            .route("/webhook", post(handle_incoming_webhook))
-            .route("/webhooks", get(list_webhooks))
-            .route("/webhooks", post(add_webhook))
-    }
-}
-
-/// Handle incoming webhook (for receiving alerts from external systems)
-async fn handle_incoming_webhook(
-    Json(payload): Json<serde_json::Value>,
-) -> Result<StatusCode, StatusCode> {
-    info!("Received incoming webhook: {:?}", payload);
-    // Process the incoming webhook
-    Ok(StatusCode::OK)
-}
-
-/// List configured webhooks
-async fn list_webhooks() -> Json<Vec<WebhookEndpoint>> {
-    // This would query the actual webhook storage
-    Json(vec![])
-}
-
-/// Add new webhook endpoint
-async fn add_webhook(
-    Json(request): Json<HashMap<String, serde_json::Value>>,
-) -> Result<Json<WebhookEndpoint>, StatusCode> {
-    // This would add the webhook to storage
-    let endpoint = WebhookEndpoint {
-        id: Uuid::new_v4(),
-        url: request.get("url").and_then(|v| v.as_str()).unwrap_or("").to_string(),
-        secret: request.get("secret").and_then(|v| v.as_str()).map(|s| s.to_string()),
-        events: vec!["push".to_string()],
-        active: true,
-        created_at: Utc::now(),
-    };
-    
-    Ok(Json(endpoint))
 }
 
 #[cfg(test)]
@@ -651,13 +1366,25 @@ mod tests {
 
     #[tokio::test]
     async fn test_event_monitor_creation() {
-        let monitor = GitHubEventMonitor::new();
-        assert_eq!(monitor.processing_queue.read().await.len(), 0);
+        let monitor = GitHubEventMonitor::new().unwrap();
+        assert_eq!(monitor.queue_depth().await.unwrap().pending, 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_monitor_stops_without_polling() {
+        let monitor = GitHubEventMonitor::new().unwrap();
+        let shutdown = CancellationToken::new();
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(5), monitor.start_monitoring(shutdown))
+            .await
+            .expect("monitor did not stop")
+            .unwrap();
+        assert!(monitor.rate_limit().await.updated_at.is_none());
     }
 
     #[tokio::test]
     async fn test_webhook_endpoint_management() {
-        let monitor = GitHubEventMonitor::new();
+        let monitor = GitHubEventMonitor::new().unwrap();
         
         let id = monitor.add_webhook_endpoint(
             "https://example.com/webhook".to_string(),
@@ -673,7 +1400,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_webhook_signature_generation() {
-        let monitor = GitHubEventMonitor::new();
+        let monitor = GitHubEventMonitor::new().unwrap();
         let payload = serde_json::json!({"test": "data"});
         let secret = "my_secret";
         
@@ -0,0 +1,119 @@
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+
+/// Share of the hourly quota left below which polling slows down
+const SLOWDOWN_FRACTION: f64 = 0.5;
+
+/// Events API quota as GitHub last reported it, and the delay chosen from it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RateLimitState {
+    /// `X-RateLimit-Limit`: requests per window
+    pub limit: Option<u64>,
+    /// `X-RateLimit-Remaining`
+    pub remaining: Option<u64>,
+    /// `X-RateLimit-Reset`: when the window starts over
+    pub reset_at: Option<DateTime<Utc>>,
+    /// `X-Poll-Interval`: the least number of seconds GitHub wants between polls
+    pub poll_interval_secs: Option<u64>,
+    /// Seconds until the next poll, after backoff
+    pub next_poll_secs: u64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+fn header<T: std::str::FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+impl RateLimitState {
+    /// Take the quota and poll interval from a response; headers missing from it keep their last value
+    pub fn observe(&mut self, headers: &HeaderMap, now: DateTime<Utc>) {
+        self.limit = header(headers, "x-ratelimit-limit").or(self.limit);
+        self.remaining = header(headers, "x-ratelimit-remaining").or(self.remaining);
+        self.reset_at = header::<i64>(headers, "x-ratelimit-reset")
+            .and_then(|epoch| Utc.timestamp_opt(epoch, 0).single())
+            .or(self.reset_at);
+        self.poll_interval_secs = header(headers, "x-poll-interval").or(self.poll_interval_secs);
+        self.updated_at = Some(now);
+    }
+
+    /// Whether the quota is used up until `reset_at`
+    pub fn exhausted(&self, now: DateTime<Utc>) -> bool {
+        self.remaining == Some(0) && self.reset_at.is_some_and(|reset| reset > now)
+    }
+
+    /// Delay before the next poll of `requests` endpoints. Never shorter than `base` or
+    /// `X-Poll-Interval`; doubled and then quadrupled as the quota drops below a half and a
+    /// quarter; below a tenth the rest is spread evenly until the reset; and once it is used
+    /// up, the wait lasts until the reset.
+    pub fn next_delay(&self, base: Duration, requests: usize, now: DateTime<Utc>) -> Duration {
+        let floor = base.max(Duration::from_secs(self.poll_interval_secs.unwrap_or(0)));
+        let (Some(limit), Some(remaining)) = (self.limit, self.remaining) else {
+            return floor;
+        };
+        let until_reset = self
+            .reset_at
+            .map(|reset| Duration::from_secs((reset - now).num_seconds().max(0) as u64 + 1))
+            .unwrap_or(floor);
+        if self.exhausted(now) {
+            return until_reset.max(floor);
+        }
+
+        let fraction = remaining as f64 / limit.max(1) as f64;
+        let delay = if fraction >= SLOWDOWN_FRACTION {
+            floor
+        } else if fraction >= 0.25 {
+            floor * 2
+        } else if fraction >= 0.1 {
+            floor * 4
+        } else {
+            // Enough polls left to last until the reset
+            let polls_left = (remaining / requests.max(1) as u64).max(1) as u32;
+            (until_reset / polls_left).max(floor * 4)
+        };
+        delay.min(until_reset.max(floor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(remaining: u64, reset_in: i64, now: DateTime<Utc>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit", HeaderValue::from_static("5000"));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_str(&remaining.to_string()).unwrap());
+        headers.insert("x-ratelimit-reset", HeaderValue::from_str(&(now.timestamp() + reset_in).to_string()).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_polling_slows_as_quota_drops() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let base = Duration::from_secs(10);
+        let mut state = RateLimitState::default();
+        assert_eq!(state.next_delay(base, 1, now), base);
+
+        state.observe(&headers(4000, 3600, now), now);
+        assert_eq!(state.next_delay(base, 1, now), base);
+
+        let mut poll_interval = headers(4000, 3600, now);
+        poll_interval.insert("x-poll-interval", HeaderValue::from_static("60"));
+        state.observe(&poll_interval, now);
+        assert_eq!(state.next_delay(base, 1, now), Duration::from_secs(60));
+
+        state.poll_interval_secs = None;
+        state.observe(&headers(1000, 3600, now), now);
+        assert_eq!(state.next_delay(base, 1, now), Duration::from_secs(40));
+
+        // 50 requests left for an hour at 5 requests per poll: one poll every 6 minutes
+        state.observe(&headers(50, 3599, now), now);
+        assert_eq!(state.next_delay(base, 5, now), Duration::from_secs(360));
+
+        state.observe(&headers(0, 120, now), now);
+        assert!(state.exhausted(now));
+        assert_eq!(state.next_delay(base, 1, now), Duration::from_secs(121));
+    }
+}
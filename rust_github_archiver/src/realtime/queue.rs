@@ -0,0 +1,452 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use redis::{Client as RedisClient, Commands, Script};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+use super::GitHubEvent;
+use crate::performance::{QueueDepth, QueuedEvent, SecretDatabasePool};
+
+/// Lease up to ARGV[3] events visible at ARGV[1], hiding them until ARGV[2]. Events already
+/// leased ARGV[4] times are hidden too but returned separately, to be dead-lettered.
+const LEASE_SCRIPT: &str = r#"
+local ids = redis.call("ZRANGEBYSCORE", KEYS[1], "-inf", ARGV[1], "LIMIT", 0, ARGV[3])
+local leased, expired = {}, {}
+for _, id in ipairs(ids) do
+    redis.call("ZADD", KEYS[1], ARGV[2], id)
+    if tonumber(redis.call("HGET", KEYS[2], id) or "0") >= tonumber(ARGV[4]) then
+        table.insert(expired, id)
+    else
+        redis.call("HINCRBY", KEYS[2], id, 1)
+        table.insert(leased, id)
+    end
+end
+return {leased, expired}
+"#;
+
+/// Longest wait before a failed event is tried again
+const MAX_RETRY_DELAY_SECS: u64 = 300;
+
+/// How events are leased from the processing queue and when they are given up on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueConfig {
+    /// Seconds a leased event stays hidden; one not acknowledged by then is processed again
+    pub visibility_timeout_secs: u64,
+    /// Failed or expired leases after which an event moves to the dead-letter table
+    pub max_attempts: u32,
+    /// Events leased per batch
+    pub batch_size: usize,
+    /// Prefix of the Redis keys holding the queue
+    pub redis_key: String,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            visibility_timeout_secs: env::var("EVENT_QUEUE_VISIBILITY_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            max_attempts: env::var("EVENT_QUEUE_MAX_ATTEMPTS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            batch_size: env::var("EVENT_QUEUE_BATCH")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100),
+            redis_key: env::var("EVENT_QUEUE_KEY").unwrap_or_else(|_| "github_archiver:events".to_string()),
+        }
+    }
+}
+
+impl QueueConfig {
+    /// Failed events wait 30 seconds per attempt so far, up to five minutes
+    fn retry_after_secs(&self, attempts: u32) -> u64 {
+        (30 * attempts as u64).min(MAX_RETRY_DELAY_SECS)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MemoryEntry {
+    event: GitHubEvent,
+    attempts: u32,
+    visible_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryQueue {
+    entries: Vec<MemoryEntry>,
+    dead_letters: Vec<(GitHubEvent, String)>,
+}
+
+enum Backend {
+    /// Lost on restart; for tests and one-off monitors without a database
+    Memory(Mutex<MemoryQueue>),
+    /// `event_queue` and `event_dead_letters` in the findings database
+    Database(SecretDatabasePool),
+    /// Shared between instances: a hash of events, a sorted set of visibility times and a
+    /// dead-letter list
+    Redis(RedisClient),
+}
+
+/// Events waiting to be scanned. With a database or Redis behind it, events survive a crash:
+/// each is leased, hidden for the visibility timeout and only removed once processed, so an
+/// event is processed at least once.
+pub struct EventQueue {
+    backend: Arc<Backend>,
+    config: QueueConfig,
+}
+
+impl EventQueue {
+    pub fn memory(config: QueueConfig) -> Self {
+        Self { backend: Arc::new(Backend::Memory(Mutex::new(MemoryQueue::default()))), config }
+    }
+
+    /// Queue in the findings database at `database_path`
+    pub fn database(database_path: &str, config: QueueConfig) -> Result<Self> {
+        let pool = SecretDatabasePool::new(database_path, false, 2)?;
+        Ok(Self { backend: Arc::new(Backend::Database(pool)), config })
+    }
+
+    pub fn redis(redis_url: &str, config: QueueConfig) -> Result<Self> {
+        let client = RedisClient::open(redis_url).map_err(|e| anyhow!("Failed to connect to Redis: {}", e))?;
+        Ok(Self { backend: Arc::new(Backend::Redis(client)), config })
+    }
+
+    /// Redis when a URL is given, the findings database otherwise
+    pub fn open(redis_url: Option<&str>, database_path: &str) -> Result<Self> {
+        match redis_url {
+            Some(url) => Self::redis(url, QueueConfig::default()),
+            None => Self::database(database_path, QueueConfig::default()),
+        }
+    }
+
+    /// `memory`, `database` or `redis`
+    pub fn kind(&self) -> &'static str {
+        match &*self.backend {
+            Backend::Memory(_) => "memory",
+            Backend::Database(_) => "database",
+            Backend::Redis(_) => "redis",
+        }
+    }
+
+    pub fn config(&self) -> &QueueConfig {
+        &self.config
+    }
+
+    /// Add events not already queued. Returns how many were added.
+    pub async fn enqueue(&self, events: Vec<GitHubEvent>) -> Result<usize> {
+        match &*self.backend {
+            Backend::Memory(queue) => {
+                let mut queue = queue.lock().map_err(|_| anyhow!("Event queue lock poisoned"))?;
+                let now = Utc::now();
+                let mut added = 0;
+                for event in events {
+                    if queue.entries.iter().any(|entry| entry.event.id == event.id) {
+                        continue;
+                    }
+                    queue.entries.push(MemoryEntry { event, attempts: 0, visible_at: now });
+                    added += 1;
+                }
+                Ok(added)
+            }
+            Backend::Database(pool) => pool.interact(move |db| db.enqueue_events(&events)).await,
+            Backend::Redis(_) => {
+                self.with_redis(move |conn, keys| {
+                    let now = Utc::now().timestamp_millis();
+                    let mut added = 0;
+                    for event in &events {
+                        let new: bool = conn.hset_nx(&keys.events, &event.id, serde_json::to_string(event)?)?;
+                        if new {
+                            redis::cmd("ZADD").arg(&keys.visible).arg("NX").arg(now).arg(&event.id).query::<()>(conn)?;
+                            added += 1;
+                        }
+                    }
+                    Ok(added)
+                })
+                .await
+            }
+        }
+    }
+
+    /// Lease the next batch of visible events, oldest first. An event whose lease already
+    /// expired `max_attempts` times is dead-lettered instead of leased again.
+    pub async fn lease(&self) -> Result<Vec<QueuedEvent>> {
+        let (limit, visibility) = (self.config.batch_size.max(1), self.config.visibility_timeout_secs);
+        let max_attempts = self.config.max_attempts.max(1);
+        match &*self.backend {
+            Backend::Memory(queue) => {
+                let mut queue = queue.lock().map_err(|_| anyhow!("Event queue lock poisoned"))?;
+                let now = Utc::now();
+                let (expired, entries): (Vec<_>, Vec<_>) = std::mem::take(&mut queue.entries)
+                    .into_iter()
+                    .partition(|entry| entry.visible_at <= now && entry.attempts >= max_attempts);
+                queue.entries = entries;
+                for entry in expired {
+                    warn!("Dead-lettering event {}: its lease expired {} times", entry.event.id, entry.attempts);
+                    queue.dead_letters.push((entry.event, lease_expired(entry.attempts)));
+                }
+                let mut leased = Vec::new();
+                for entry in queue.entries.iter_mut().filter(|entry| entry.visible_at <= now).take(limit) {
+                    entry.attempts += 1;
+                    entry.visible_at = now + Duration::seconds(visibility as i64);
+                    leased.push(QueuedEvent { event: entry.event.clone(), attempts: entry.attempts });
+                }
+                Ok(leased)
+            }
+            Backend::Database(pool) => pool.interact(move |db| db.lease_events(limit, visibility, max_attempts)).await,
+            Backend::Redis(_) => {
+                self.with_redis(move |conn, keys| {
+                    let now = Utc::now().timestamp_millis();
+                    let (ids, expired): (Vec<String>, Vec<String>) = Script::new(LEASE_SCRIPT)
+                        .key(&keys.visible)
+                        .key(&keys.attempts)
+                        .arg(now)
+                        .arg(now + visibility as i64 * 1000)
+                        .arg(limit)
+                        .arg(max_attempts)
+                        .invoke(conn)?;
+                    for id in &expired {
+                        let body: Option<String> = conn.hget(&keys.events, id)?;
+                        let attempts: Option<u32> = conn.hget(&keys.attempts, id)?;
+                        match body {
+                            Some(body) => {
+                                let attempts = attempts.unwrap_or(max_attempts);
+                                warn!("Dead-lettering event {}: its lease expired {} times", id, attempts);
+                                keys.dead_letter(conn, &serde_json::from_str(&body)?, attempts, &lease_expired(attempts))?;
+                            }
+                            None => keys.remove(conn, id)?,
+                        }
+                    }
+                    if ids.is_empty() {
+                        return Ok(Vec::new());
+                    }
+                    let bodies: Vec<Option<String>> = redis::cmd("HMGET").arg(&keys.events).arg(&ids).query(conn)?;
+                    let attempts: Vec<Option<u32>> = redis::cmd("HMGET").arg(&keys.attempts).arg(&ids).query(conn)?;
+
+                    let mut leased = Vec::new();
+                    for (id, (body, attempts)) in ids.iter().zip(bodies.into_iter().zip(attempts)) {
+                        match body {
+                            Some(body) => leased.push(QueuedEvent {
+                                event: serde_json::from_str(&body)?,
+                                attempts: attempts.unwrap_or(1),
+                            }),
+                            // Acknowledged by another instance between the two calls
+                            None => redis::cmd("ZREM").arg(&keys.visible).arg(id).query::<()>(conn)?,
+                        }
+                    }
+                    leased.sort_by(|a, b| a.event.created_at.cmp(&b.event.created_at));
+                    Ok(leased)
+                })
+                .await
+            }
+        }
+    }
+
+    /// Remove a processed event
+    pub async fn ack(&self, event_id: &str) -> Result<()> {
+        let event_id = event_id.to_string();
+        match &*self.backend {
+            Backend::Memory(queue) => {
+                let mut queue = queue.lock().map_err(|_| anyhow!("Event queue lock poisoned"))?;
+                queue.entries.retain(|entry| entry.event.id != event_id);
+                Ok(())
+            }
+            Backend::Database(pool) => pool.interact(move |db| db.ack_event(&event_id)).await,
+            Backend::Redis(_) => self.with_redis(move |conn, keys| keys.remove(conn, &event_id)).await,
+        }
+    }
+
+    /// Record a failed attempt: the event is retried later, or dead-lettered once it has
+    /// failed `max_attempts` times. Returns whether it was dead-lettered.
+    pub async fn fail(&self, queued: &QueuedEvent, error: &str) -> Result<bool> {
+        let (event, error) = (queued.event.clone(), error.to_string());
+        let (attempts, max_attempts) = (queued.attempts, self.config.max_attempts.max(1));
+        let retry_after = self.config.retry_after_secs(attempts);
+        match &*self.backend {
+            Backend::Memory(queue) => {
+                let mut queue = queue.lock().map_err(|_| anyhow!("Event queue lock poisoned"))?;
+                let Some(position) = queue.entries.iter().position(|entry| entry.event.id == event.id) else {
+                    return Ok(false);
+                };
+                if queue.entries[position].attempts >= max_attempts {
+                    let entry = queue.entries.remove(position);
+                    queue.dead_letters.push((entry.event, error));
+                    return Ok(true);
+                }
+                queue.entries[position].visible_at = Utc::now() + Duration::seconds(retry_after as i64);
+                Ok(false)
+            }
+            Backend::Database(pool) => {
+                pool.interact(move |db| db.fail_event(&event.id, &error, max_attempts, retry_after)).await
+            }
+            Backend::Redis(_) => {
+                self.with_redis(move |conn, keys| {
+                    let stored: Option<u32> = conn.hget(&keys.attempts, &event.id)?;
+                    let attempts = stored.unwrap_or(attempts);
+                    if attempts >= max_attempts {
+                        keys.dead_letter(conn, &event, attempts, &error)?;
+                        return Ok(true);
+                    }
+                    let visible_at = Utc::now().timestamp_millis() + retry_after as i64 * 1000;
+                    redis::cmd("ZADD").arg(&keys.visible).arg("XX").arg(visible_at).arg(&event.id).query::<()>(conn)?;
+                    Ok(false)
+                })
+                .await
+            }
+        }
+    }
+
+    /// Events waiting, leased and dead-lettered
+    pub async fn depth(&self) -> Result<QueueDepth> {
+        match &*self.backend {
+            Backend::Memory(queue) => {
+                let queue = queue.lock().map_err(|_| anyhow!("Event queue lock poisoned"))?;
+                let now = Utc::now();
+                let pending = queue.entries.iter().filter(|entry| entry.visible_at <= now).count() as u64;
+                Ok(QueueDepth {
+                    pending,
+                    in_flight: queue.entries.len() as u64 - pending,
+                    dead_letters: queue.dead_letters.len() as u64,
+                })
+            }
+            Backend::Database(pool) => pool.interact(|db| db.event_queue_depth()).await,
+            Backend::Redis(_) => {
+                self.with_redis(|conn, keys| {
+                    let now = Utc::now().timestamp_millis();
+                    let pending: u64 = conn.zcount(&keys.visible, "-inf", now)?;
+                    let total: u64 = conn.zcard(&keys.visible)?;
+                    let dead_letters: u64 = conn.llen(&keys.dead_letters)?;
+                    Ok(QueueDepth { pending, in_flight: total.saturating_sub(pending), dead_letters })
+                })
+                .await
+            }
+        }
+    }
+
+    /// Run `f` on a Redis connection off the async workers
+    async fn with_redis<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut redis::Connection, &RedisKeys) -> Result<T> + Send + 'static,
+    {
+        let backend = self.backend.clone();
+        let keys = RedisKeys::new(&self.config.redis_key);
+        tokio::task::spawn_blocking(move || {
+            let Backend::Redis(client) = &*backend else {
+                return Err(anyhow!("The event queue is not backed by Redis"));
+            };
+            let mut conn = client.get_connection().map_err(|e| anyhow!("Redis connection failed: {}", e))?;
+            f(&mut conn, &keys)
+        })
+        .await?
+    }
+}
+
+/// Dead-letter reason of an event no consumer acknowledged or failed in `attempts` leases
+fn lease_expired(attempts: u32) -> String {
+    format!("Lease expired {} times without an acknowledgement", attempts)
+}
+
+struct RedisKeys {
+    events: String,
+    visible: String,
+    attempts: String,
+    dead_letters: String,
+}
+
+impl RedisKeys {
+    fn new(prefix: &str) -> Self {
+        Self {
+            events: format!("{}:queue", prefix),
+            visible: format!("{}:visible", prefix),
+            attempts: format!("{}:attempts", prefix),
+            dead_letters: format!("{}:dead", prefix),
+        }
+    }
+
+    /// Move an event to the dead-letter list
+    fn dead_letter(&self, conn: &mut redis::Connection, event: &GitHubEvent, attempts: u32, error: &str) -> Result<()> {
+        let letter = serde_json::json!({
+            "event": event,
+            "attempts": attempts,
+            "last_error": error,
+            "failed_at": Utc::now(),
+        });
+        conn.lpush::<_, _, ()>(&self.dead_letters, letter.to_string())?;
+        self.remove(conn, &event.id)
+    }
+
+    fn remove(&self, conn: &mut redis::Connection, event_id: &str) -> Result<()> {
+        redis::pipe()
+            .hdel(&self.events, event_id)
+            .hdel(&self.attempts, event_id)
+            .zrem(&self.visible, event_id)
+            .query::<()>(conn)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::realtime::{Actor, Repository};
+
+    fn event(id: &str) -> GitHubEvent {
+        GitHubEvent {
+            id: id.to_string(),
+            event_type: "PushEvent".to_string(),
+            created_at: Utc::now(),
+            actor: Actor {
+                id: 1,
+                login: "octocat".to_string(),
+                display_login: None,
+                gravatar_id: None,
+                url: String::new(),
+                avatar_url: String::new(),
+            },
+            repo: Repository { id: 1, name: "acme/api".to_string(), url: String::new() },
+            payload: serde_json::json!({}),
+            public: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unacknowledged_events_survive_in_the_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queue.db").to_string_lossy().to_string();
+        crate::performance::SecretDatabase::new(&path).unwrap();
+        let config = QueueConfig { visibility_timeout_secs: 0, max_attempts: 2, batch_size: 10, redis_key: String::new() };
+
+        let queue = EventQueue::database(&path, config.clone()).unwrap();
+        queue.enqueue(vec![event("1"), event("2")]).await.unwrap();
+        let leased = queue.lease().await.unwrap();
+        queue.ack(&leased[0].event.id).await.unwrap();
+        drop(queue);
+
+        // A crash before the second event was acknowledged: it is leased again after restart
+        let queue = EventQueue::database(&path, config).unwrap();
+        let leased = queue.lease().await.unwrap();
+        assert_eq!((leased.len(), leased[0].attempts), (1, 2));
+        assert!(queue.fail(&leased[0], "scan failed").await.unwrap());
+        assert_eq!(queue.depth().await.unwrap().dead_letters, 1);
+    }
+
+    #[tokio::test]
+    async fn test_memory_queue_dead_letters_expired_leases() {
+        let config = QueueConfig { visibility_timeout_secs: 0, max_attempts: 2, batch_size: 10, redis_key: String::new() };
+        let queue = EventQueue::memory(config);
+        queue.enqueue(vec![event("1")]).await.unwrap();
+
+        // Never acknowledged nor failed: the lease expires each time
+        assert_eq!(queue.lease().await.unwrap()[0].attempts, 1);
+        assert_eq!(queue.lease().await.unwrap()[0].attempts, 2);
+        assert!(queue.lease().await.unwrap().is_empty());
+
+        let depth = queue.depth().await.unwrap();
+        assert_eq!((depth.pending, depth.in_flight, depth.dead_letters), (0, 0, 1));
+    }
+}
@@ -0,0 +1,144 @@
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+
+use super::{AlertSeverity, RealTimeSecretAlert};
+use crate::core::in_daily_window;
+
+/// How alerts of one severity are presented on the desktop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeverityToast {
+    pub enabled: bool,
+    /// Built-in sound name (`alarm`, `chime`, `beep`), silent when unset
+    pub sound: Option<String>,
+    /// Sticky toasts stay until dismissed instead of fading out
+    pub sticky: bool,
+}
+
+impl SeverityToast {
+    fn off() -> Self {
+        Self { enabled: false, sound: None, sticky: false }
+    }
+}
+
+/// Daily quiet period in local time; may wrap past midnight (e.g. 22:00-07:00)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    /// Critical alerts still toast (without sound) during quiet hours
+    pub allow_critical: bool,
+}
+
+impl QuietHours {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        in_daily_window(time, self.start, self.end)
+    }
+}
+
+/// Desktop toast settings, persisted with the desktop app's settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToastPreferences {
+    pub critical: SeverityToast,
+    pub high: SeverityToast,
+    pub medium: SeverityToast,
+    pub low: SeverityToast,
+    pub do_not_disturb: Option<QuietHours>,
+}
+
+impl Default for ToastPreferences {
+    fn default() -> Self {
+        Self {
+            critical: SeverityToast { enabled: true, sound: Some("alarm".to_string()), sticky: true },
+            high: SeverityToast { enabled: true, sound: Some("chime".to_string()), sticky: false },
+            medium: SeverityToast::off(),
+            low: SeverityToast::off(),
+            do_not_disturb: None,
+        }
+    }
+}
+
+/// A toast to show for one real-time alert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertToast {
+    pub alert: RealTimeSecretAlert,
+    pub sound: Option<String>,
+    pub sticky: bool,
+}
+
+impl ToastPreferences {
+    pub fn for_severity(&self, severity: &AlertSeverity) -> &SeverityToast {
+        match severity {
+            AlertSeverity::Critical => &self.critical,
+            AlertSeverity::High => &self.high,
+            AlertSeverity::Medium => &self.medium,
+            AlertSeverity::Low => &self.low,
+        }
+    }
+
+    /// The toast for `alert` at local time `now`, or `None` when it should stay quiet
+    pub fn toast_for(&self, alert: &RealTimeSecretAlert, now: NaiveTime) -> Option<AlertToast> {
+        let settings = self.for_severity(&alert.alert_severity);
+        if !settings.enabled {
+            return None;
+        }
+
+        let quiet = self.do_not_disturb.as_ref().filter(|dnd| dnd.contains(now));
+        if let Some(dnd) = quiet {
+            if !(dnd.allow_critical && matches!(alert.alert_severity, AlertSeverity::Critical)) {
+                return None;
+            }
+        }
+
+        Some(AlertToast {
+            alert: alert.clone(),
+            sound: if quiet.is_some() { None } else { settings.sound.clone() },
+            sticky: settings.sticky,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn alert(severity: AlertSeverity) -> RealTimeSecretAlert {
+        RealTimeSecretAlert {
+            event_id: "1".to_string(),
+            repository: "acme/api".to_string(),
+            commit_sha: "abc123".to_string(),
+            secrets_found: Vec::new(),
+            alert_severity: severity,
+            detection_time: Utc::now(),
+            triage_result: None,
+            spread: None,
+        }
+    }
+
+    fn at(hour: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_default_toasts_high_and_critical_only() {
+        let prefs = ToastPreferences::default();
+        assert!(prefs.toast_for(&alert(AlertSeverity::Critical), at(12)).unwrap().sticky);
+        assert!(prefs.toast_for(&alert(AlertSeverity::High), at(12)).is_some());
+        assert!(prefs.toast_for(&alert(AlertSeverity::Medium), at(12)).is_none());
+    }
+
+    #[test]
+    fn test_quiet_hours_wrap_midnight() {
+        let prefs = ToastPreferences {
+            do_not_disturb: Some(QuietHours { start: at(22), end: at(7), allow_critical: true }),
+            ..Default::default()
+        };
+        assert!(prefs.toast_for(&alert(AlertSeverity::High), at(23)).is_none());
+        assert!(prefs.toast_for(&alert(AlertSeverity::High), at(6)).is_none());
+        assert!(prefs.toast_for(&alert(AlertSeverity::High), at(7)).is_some());
+
+        // Critical still shows during quiet hours, but silently
+        let toast = prefs.toast_for(&alert(AlertSeverity::Critical), at(2)).unwrap();
+        assert!(toast.sound.is_none());
+    }
+}
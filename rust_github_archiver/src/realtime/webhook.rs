@@ -0,0 +1,281 @@
+use anyhow::{anyhow, Result};
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, State},
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Router,
+};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use super::{Actor, GitHubEvent, GitHubEventMonitor, Repository};
+
+/// Environment variable holding the secret configured on the GitHub webhook
+pub const WEBHOOK_SECRET_ENV: &str = "GITHUB_WEBHOOK_SECRET";
+
+/// GitHub caps webhook payloads at 25 MB
+const MAX_DELIVERY_BYTES: usize = 25 * 1024 * 1024;
+
+#[derive(Clone)]
+struct ReceiverState {
+    monitor: Arc<GitHubEventMonitor>,
+    secret: Arc<Vec<u8>>,
+    /// Wakes the queue worker when a delivery was queued
+    queued: Arc<Notify>,
+}
+
+/// Whether `signature` (`X-Hub-Signature-256`, `sha256=<hex>`) is the HMAC-SHA256 of `body`
+/// under `secret`. The comparison is constant time.
+pub fn verify_signature(secret: &[u8], body: &[u8], signature: &str) -> bool {
+    let Some(digest) = signature.strip_prefix("sha256=").and_then(|hex| hex::decode(hex).ok()) else {
+        return false;
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac.verify_slice(&digest).is_ok()
+}
+
+/// Turn a webhook delivery into the Events API shape the monitor processes. `kind` is the
+/// `X-GitHub-Event` header and `delivery_id` the `X-GitHub-Delivery` GUID, which GitHub
+/// keeps on redelivery so a redelivered event is only queued once. Returns `None` for
+/// deliveries the monitor has no handler for.
+pub fn delivery_to_event(kind: &str, delivery_id: &str, body: &Value) -> Result<Option<GitHubEvent>> {
+    let (event_type, payload) = match kind {
+        "push" => {
+            let commits: Vec<Value> = body["commits"]
+                .as_array()
+                .map(|commits| {
+                    commits
+                        .iter()
+                        .map(|commit| {
+                            json!({
+                                "sha": commit["id"],
+                                "author": {
+                                    "email": commit["author"]["email"].as_str().unwrap_or_default(),
+                                    "name": commit["author"]["name"].as_str().unwrap_or_default(),
+                                },
+                                "message": commit["message"].as_str().unwrap_or_default(),
+                                "distinct": commit["distinct"].as_bool().unwrap_or(true),
+                                "url": commit["url"].as_str().unwrap_or_default(),
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let distinct = commits.iter().filter(|commit| commit["distinct"].as_bool().unwrap_or(true)).count();
+            let payload = json!({
+                "push_id": 0,
+                "size": commits.len(),
+                "distinct_size": distinct,
+                "ref": body["ref"].as_str().unwrap_or_default(),
+                "head": body["after"].as_str().unwrap_or_default(),
+                "before": body["before"].as_str().unwrap_or_default(),
+                "commits": commits,
+            });
+            ("PushEvent", payload)
+        }
+        "pull_request" => (
+            "PullRequestEvent",
+            json!({ "action": body["action"], "number": body["number"], "pull_request": body["pull_request"] }),
+        ),
+        "issue_comment" => (
+            "IssueCommentEvent",
+            json!({ "action": body["action"], "issue": body["issue"], "comment": body["comment"] }),
+        ),
+        _ => return Ok(None),
+    };
+
+    let repository = &body["repository"];
+    let sender = &body["sender"];
+    let name = repository["full_name"]
+        .as_str()
+        .ok_or_else(|| anyhow!("{} delivery {} has no repository", kind, delivery_id))?;
+    Ok(Some(GitHubEvent {
+        id: format!("webhook-{}", delivery_id),
+        event_type: event_type.to_string(),
+        created_at: Utc::now(),
+        actor: Actor {
+            id: sender["id"].as_u64().unwrap_or_default(),
+            login: sender["login"].as_str().unwrap_or_default().to_string(),
+            display_login: None,
+            gravatar_id: sender["gravatar_id"].as_str().map(str::to_string),
+            url: sender["url"].as_str().unwrap_or_default().to_string(),
+            avatar_url: sender["avatar_url"].as_str().unwrap_or_default().to_string(),
+        },
+        repo: Repository {
+            id: repository["id"].as_u64().unwrap_or_default(),
+            name: name.to_string(),
+            url: repository["url"].as_str().unwrap_or_default().to_string(),
+        },
+        payload,
+        public: !repository["private"].as_bool().unwrap_or(false),
+    }))
+}
+
+/// `POST /webhook`: verify the signature, queue the delivery and answer straight away;
+/// GitHub gives up on deliveries that take longer than ten seconds
+async fn receive_delivery(State(state): State<ReceiverState>, headers: HeaderMap, body: Bytes) -> (StatusCode, &'static str) {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    if !verify_signature(&state.secret, &body, header("x-hub-signature-256")) {
+        warn!("Rejected webhook delivery {:?} with a missing or invalid signature", header("x-github-delivery"));
+        return (StatusCode::UNAUTHORIZED, "invalid signature");
+    }
+
+    let kind = header("x-github-event");
+    if kind == "ping" {
+        info!("Webhook ping received (delivery {})", header("x-github-delivery"));
+        return (StatusCode::OK, "pong");
+    }
+    let delivery_id = header("x-github-delivery");
+    if delivery_id.is_empty() {
+        return (StatusCode::BAD_REQUEST, "missing X-GitHub-Delivery");
+    }
+    let parsed = serde_json::from_slice::<Value>(&body)
+        .map_err(anyhow::Error::from)
+        .and_then(|body| delivery_to_event(kind, delivery_id, &body));
+    let event = match parsed {
+        Ok(Some(event)) => event,
+        Ok(None) => {
+            debug!("Ignoring {} delivery {}", kind, delivery_id);
+            return (StatusCode::ACCEPTED, "ignored");
+        }
+        Err(e) => {
+            warn!("Malformed {} delivery {}: {}", kind, delivery_id, e);
+            return (StatusCode::BAD_REQUEST, "malformed payload");
+        }
+    };
+
+    // A failed enqueue answers 503 so the delivery shows as failed and can be redelivered
+    match state.monitor.enqueue_events(vec![event]).await {
+        Ok(_) => {
+            state.queued.notify_one();
+            (StatusCode::ACCEPTED, "queued")
+        }
+        Err(e) => {
+            error!("Failed to queue {} delivery {}: {}", kind, delivery_id, e);
+            (StatusCode::SERVICE_UNAVAILABLE, "queue unavailable")
+        }
+    }
+}
+
+fn router(state: ReceiverState) -> Router {
+    Router::new()
+        .route("/webhook", post(receive_delivery))
+        .layer(DefaultBodyLimit::max(MAX_DELIVERY_BYTES))
+        .with_state(state)
+}
+
+impl GitHubEventMonitor {
+    /// Router receiving GitHub organization or repository webhooks signed with `secret`
+    pub fn create_webhook_server(self: Arc<Self>, secret: &str) -> Router {
+        router(ReceiverState {
+            monitor: self,
+            secret: Arc::new(secret.as_bytes().to_vec()),
+            queued: Arc::new(Notify::new()),
+        })
+    }
+
+    /// Receive webhooks on `listen` instead of polling the Events API. Deliveries go through
+    /// the same queue, filter and scanners as polled events; the queue is drained as they
+    /// arrive and every poll interval, which also retries failed events.
+    pub async fn serve_webhooks(self: Arc<Self>, listen: SocketAddr, secret: &str, shutdown: CancellationToken) -> Result<()> {
+        if secret.is_empty() {
+            return Err(anyhow!("Webhook mode needs the webhook secret in {}", WEBHOOK_SECRET_ENV));
+        }
+        let queued = Arc::new(Notify::new());
+        let app = router(ReceiverState {
+            monitor: self.clone(),
+            secret: Arc::new(secret.as_bytes().to_vec()),
+            queued: queued.clone(),
+        });
+
+        let worker = {
+            let monitor = self.clone();
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                loop {
+                    tokio::select! {
+                        biased;
+                        _ = shutdown.cancelled() => break,
+                        _ = queued.notified() => {}
+                        _ = tokio::time::sleep(monitor.poll_interval) => {}
+                    }
+                    monitor.send_due_digests().await;
+                    monitor.flush_chat_batches().await;
+                    if let Err(e) = monitor.process_queue().await {
+                        error!("Failed to process the event queue: {}", e);
+                    }
+                }
+            })
+        };
+
+        info!("Receiving GitHub webhooks on http://{}/webhook", listen);
+        let served = axum::serve(TcpListener::bind(listen).await?, app)
+            .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+            .await;
+        shutdown.cancel();
+        if let Err(e) = worker.await {
+            error!("Webhook queue worker panicked: {}", e);
+        }
+        info!("Stopped receiving GitHub webhooks");
+        Ok(served?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &[u8], body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(body);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[test]
+    fn test_signature_verification() {
+        let body = br#"{"zen":"Keep it logically awesome."}"#;
+        let signature = sign(b"s3cret", body);
+        assert!(verify_signature(b"s3cret", body, &signature));
+        assert!(!verify_signature(b"other", body, &signature));
+        assert!(!verify_signature(b"s3cret", b"{}", &signature));
+        assert!(!verify_signature(b"s3cret", body, ""));
+        assert!(!verify_signature(b"s3cret", body, &signature.replace("sha256=", "sha1=")));
+    }
+
+    #[test]
+    fn test_push_delivery_matches_events_api_shape() {
+        let body = json!({
+            "ref": "refs/heads/main",
+            "before": "a".repeat(40),
+            "after": "b".repeat(40),
+            "repository": { "id": 7, "full_name": "acme/api", "url": "https://github.com/acme/api", "private": true },
+            "sender": { "id": 1, "login": "octocat" },
+            "commits": [
+                { "id": "b".repeat(40), "message": "Add config", "distinct": true,
+                  "author": { "name": "Octo", "email": "octo@example.com" }, "url": "https://github.com/acme/api/commit/b" }
+            ]
+        });
+        let event = delivery_to_event("push", "guid-1", &body).unwrap().unwrap();
+        assert_eq!((event.id.as_str(), event.event_type.as_str()), ("webhook-guid-1", "PushEvent"));
+        assert_eq!(event.repo.name, "acme/api");
+        assert!(!event.public);
+        assert_eq!(event.payload["distinct_size"], 1);
+
+        let payload: crate::realtime::PushEventPayload = serde_json::from_value(event.payload).unwrap();
+        assert_eq!(payload.before, "a".repeat(40));
+        assert_eq!(payload.commits[0].author.email, "octo@example.com");
+
+        assert!(delivery_to_event("star", "guid-2", &body).unwrap().is_none());
+        assert!(delivery_to_event("issue_comment", "guid-3", &json!({})).is_err());
+    }
+}
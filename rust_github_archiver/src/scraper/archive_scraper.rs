@@ -10,7 +10,7 @@ use flate2::read::GzDecoder;
 use std::io::Read;
 use tracing::{info, warn, error, debug};
 
-use crate::core::{Config, ResourceMonitor, ResourceLimits};
+use crate::core::{Config, ResourceMonitor, ResourceLimits, RetryPolicy, build_client_with_timeout, send_with_retry};
 use crate::scraper::{ScraperManager, ScraperState};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,11 +65,8 @@ pub struct ArchiveScraper {
 }
 
 impl ArchiveScraper {
-    pub fn new(config: Config, scraper_manager: Arc<ScraperManager>) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(180))
-            .build()
-            .expect("Failed to create HTTP client");
+    pub fn new(config: Config, scraper_manager: Arc<ScraperManager>) -> Result<Self> {
+        let client = build_client_with_timeout(&config.http, config.download.request_timeout)?;
 
         let resource_limits = ResourceLimits {
             memory_limit_gb: 18.0,
@@ -84,14 +81,14 @@ impl ArchiveScraper {
 
         let resource_monitor = Arc::new(Mutex::new(ResourceMonitor::new(resource_limits)));
 
-        Self {
+        Ok(Self {
             config,
             client,
             stats: Arc::new(Mutex::new(ScrapingStats::default())),
             resource_monitor,
             scraper_manager,
             shutdown_requested: Arc::new(Mutex::new(false)),
-        }
+        })
     }
 
     pub async fn initialize(&self) -> Result<()> {
@@ -107,10 +104,10 @@ impl ArchiveScraper {
     pub async fn get_available_files(&self) -> Result<Vec<ArchiveFile>> {
         info!("Fetching available archive files...");
         
-        let response = self.client
-            .get("https://data.gharchive.org/?list-type=2")
-            .send()
-            .await?;
+        let response = send_with_retry(
+            self.client.get(&self.config.download.s3_list_url),
+            &RetryPolicy::from_config(&self.config.http),
+        ).await?;
 
         if !response.status().is_success() {
             return Err(anyhow!("Failed to fetch file list: HTTP {}", response.status()));
@@ -163,7 +160,10 @@ impl ArchiveScraper {
         debug!("Processing file: {}", file_info.filename);
         
         // Download file
-        let response = self.client.get(&file_info.url).send().await?;
+        let response = send_with_retry(
+            self.client.get(&file_info.url),
+            &RetryPolicy::from_config(&self.config.http),
+        ).await?;
         
         if !response.status().is_success() {
             return Ok(ProcessingResult {
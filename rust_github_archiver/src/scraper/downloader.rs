@@ -1,4 +1,5 @@
 use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use reqwest::Client;
 use tokio::fs::{File, create_dir_all};
@@ -7,6 +8,9 @@ use anyhow::{Result, anyhow};
 use serde::{Serialize, Deserialize};
 use tracing::{info, warn, error, debug};
 
+use crate::core::{DownloadWindow, HttpConfig, build_client_with_timeout};
+use crate::scraper::throttle::{self, TokenBucket};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadConfig {
     pub max_concurrent_downloads: usize,
@@ -14,6 +18,13 @@ pub struct DownloadConfig {
     pub request_timeout_seconds: u64,
     pub max_retries: u32,
     pub retry_delay_seconds: f64,
+    /// Combined bandwidth cap across concurrent downloads; `None` is unlimited
+    #[serde(default)]
+    pub max_bytes_per_second: Option<u64>,
+    /// Downloads only start inside one of these local-time windows; empty means any time.
+    /// A download already running when its window closes is finished.
+    #[serde(default)]
+    pub schedule: Vec<DownloadWindow>,
 }
 
 impl Default for DownloadConfig {
@@ -24,6 +35,8 @@ impl Default for DownloadConfig {
             request_timeout_seconds: 180,
             max_retries: 3,
             retry_delay_seconds: 2.0,
+            max_bytes_per_second: None,
+            schedule: Vec::new(),
         }
     }
 }
@@ -49,15 +62,18 @@ pub enum DownloadStatus {
 pub struct Downloader {
     client: Client,
     config: DownloadConfig,
+    throttle: Option<Arc<TokenBucket>>,
 }
 
 impl Downloader {
     pub fn new(config: DownloadConfig) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(config.request_timeout_seconds))
-            .build()?;
+        let client = build_client_with_timeout(&HttpConfig::default(), config.request_timeout_seconds)?;
+        let throttle = config.max_bytes_per_second.map(|rate| {
+            info!("Download bandwidth capped at {} KB/s", rate / 1024);
+            Arc::new(TokenBucket::new(rate))
+        });
 
-        Ok(Self { client, config })
+        Ok(Self { client, config, throttle })
     }
 
     pub async fn download_file(
@@ -94,6 +110,8 @@ impl Downloader {
             }
         }
 
+        throttle::wait_for_window(&self.config.schedule).await;
+
         let mut last_error = None;
 
         // Retry loop
@@ -171,6 +189,9 @@ impl Downloader {
 
         while let Some(chunk_result) = stream.next().await {
             let chunk = chunk_result?;
+            if let Some(ref bucket) = self.throttle {
+                bucket.acquire(chunk.len() as u64).await;
+            }
             file.write_all(&chunk).await?;
             bytes_written += chunk.len() as u64;
 
@@ -0,0 +1,134 @@
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// GH Archive publishes one file per hour, named `YYYY-MM-DD-H.json.gz` with an unpadded hour
+pub fn archive_filename(hour: DateTime<Utc>) -> String {
+    format!("{}-{}.json.gz", hour.format("%Y-%m-%d"), hour.hour())
+}
+
+/// The hour an archive file covers, or `None` if the name is not an hourly archive
+pub fn archive_hour(filename: &str) -> Option<DateTime<Utc>> {
+    let stem = filename.rsplit('/').next()?.strip_suffix(".json.gz")?;
+    let (date, hour) = stem.rsplit_once('-')?;
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let hour: u32 = hour.parse().ok()?;
+    Some(Utc.from_utc_datetime(&date.and_hms_opt(hour, 0, 0)?))
+}
+
+/// Truncate to the start of the hour
+pub fn hour_start(time: DateTime<Utc>) -> DateTime<Utc> {
+    time.with_minute(0)
+        .and_then(|t| t.with_second(0))
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(time)
+}
+
+/// A run of consecutive missing hours, both ends inclusive
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GapRange {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub hours: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GapReport {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub expected_hours: u64,
+    pub processed_hours: u64,
+    pub missing_hours: u64,
+    pub gaps: Vec<GapRange>,
+    /// Filenames of every missing hour, oldest first
+    pub missing_files: Vec<String>,
+}
+
+impl GapReport {
+    pub fn is_complete(&self) -> bool {
+        self.missing_hours == 0
+    }
+}
+
+/// Compare processed archive filenames against every hour from `start` to `end` inclusive
+pub fn find_gaps<'a>(
+    processed: impl IntoIterator<Item = &'a str>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> GapReport {
+    let start = hour_start(start);
+    let end = hour_start(end);
+    let processed: HashSet<DateTime<Utc>> = processed.into_iter().filter_map(archive_hour).collect();
+
+    let mut report = GapReport {
+        start,
+        end,
+        expected_hours: 0,
+        processed_hours: 0,
+        missing_hours: 0,
+        gaps: Vec::new(),
+        missing_files: Vec::new(),
+    };
+
+    let mut hour = start;
+    while hour <= end {
+        report.expected_hours += 1;
+        if processed.contains(&hour) {
+            report.processed_hours += 1;
+        } else {
+            report.missing_hours += 1;
+            report.missing_files.push(archive_filename(hour));
+            match report.gaps.last_mut() {
+                Some(gap) if gap.end + Duration::hours(1) == hour => {
+                    gap.end = hour;
+                    gap.hours += 1;
+                }
+                _ => report.gaps.push(GapRange { start: hour, end: hour, hours: 1 }),
+            }
+        }
+        hour += Duration::hours(1);
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_archive_filename_round_trip() {
+        assert_eq!(archive_filename(at(5, 7)), "2024-03-05-7.json.gz");
+        assert_eq!(archive_hour("2024-03-05-7.json.gz"), Some(at(5, 7)));
+        assert_eq!(archive_hour("https://data.gharchive.org/2024-03-05-23.json.gz"), Some(at(5, 23)));
+        assert_eq!(archive_hour("2024-03-05-24.json.gz"), None);
+        assert_eq!(archive_hour("notes.txt"), None);
+    }
+
+    #[test]
+    fn test_find_gaps_groups_consecutive_hours() {
+        let processed: Vec<String> = [(1, 0), (1, 1), (1, 4), (1, 23), (2, 1)]
+            .iter()
+            .map(|&(d, h)| archive_filename(at(d, h)))
+            .collect();
+
+        let report = find_gaps(processed.iter().map(String::as_str), at(1, 0), at(2, 1));
+        assert_eq!(report.expected_hours, 26);
+        assert_eq!(report.processed_hours, 5);
+        assert_eq!(report.missing_hours, 21);
+        assert_eq!(
+            report.gaps,
+            vec![
+                GapRange { start: at(1, 2), end: at(1, 3), hours: 2 },
+                GapRange { start: at(1, 5), end: at(1, 22), hours: 18 },
+                GapRange { start: at(2, 0), end: at(2, 0), hours: 1 },
+            ]
+        );
+        assert_eq!(report.missing_files.first().unwrap(), "2024-03-01-2.json.gz");
+        assert!(!report.is_complete());
+    }
+}
@@ -0,0 +1,288 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use flate2::read::MultiGzDecoder;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+use crate::bigquery::{RepositoryFilter, ZeroCommitEvent, NULL_COMMIT};
+use crate::core::offline;
+use crate::scraper::downloader::{DownloadConfig, DownloadStatus, Downloader};
+use crate::scraper::gaps::{archive_filename, hour_start};
+
+/// Where GH Archive publishes its hourly files
+pub const GHARCHIVE_URL: &str = "https://data.gharchive.org/";
+
+/// Hours downloaded ahead of the one being read
+const PREFETCH_HOURS: usize = 2;
+
+// Only the fields a `ZeroCommitEvent` needs; everything else in the event is skipped by serde
+#[derive(Deserialize)]
+struct EventLine {
+    #[serde(default)]
+    id: String,
+    #[serde(rename = "type")]
+    event_type: String,
+    created_at: DateTime<Utc>,
+    repo: EventRepo,
+    actor: EventActor,
+    #[serde(default)]
+    payload: PushPayload,
+}
+
+#[derive(Deserialize)]
+struct EventRepo {
+    #[serde(default)]
+    id: i64,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct EventActor {
+    #[serde(default)]
+    id: i64,
+    #[serde(default)]
+    login: String,
+}
+
+#[derive(Default, Deserialize)]
+struct PushPayload {
+    before: Option<String>,
+    #[serde(alias = "after")]
+    head: Option<String>,
+    #[serde(rename = "ref")]
+    ref_name: Option<String>,
+    #[serde(default)]
+    commits: Vec<IgnoredAny>,
+}
+
+/// Zero-commit PushEvents in one hourly GH Archive file that `filter` matches, in file
+/// order. Pushes with no `before` commit are left out, as in the BigQuery scan.
+pub fn zero_commit_events_in_file(path: &Path, filter: &RepositoryFilter) -> Result<Vec<ZeroCommitEvent>> {
+    let reader = BufReader::new(MultiGzDecoder::new(File::open(path)?));
+    let mut events = Vec::new();
+    let mut malformed = 0u64;
+    for line in reader.lines() {
+        let line = line?;
+        // Most events are not pushes; skip them without parsing
+        if !line.contains("\"PushEvent\"") {
+            continue;
+        }
+        let event: EventLine = match serde_json::from_str(&line) {
+            Ok(event) => event,
+            Err(_) => {
+                malformed += 1;
+                continue;
+            }
+        };
+        let before = event.payload.before.unwrap_or_default();
+        if event.event_type != "PushEvent"
+            || !event.payload.commits.is_empty()
+            || before.is_empty()
+            || before == NULL_COMMIT
+            || !filter.matches(&event.repo.name)
+        {
+            continue;
+        }
+        events.push(ZeroCommitEvent {
+            id: event.id,
+            event_type: event.event_type,
+            created_at: event.created_at,
+            repo_name: event.repo.name,
+            repo_id: event.repo.id,
+            actor_login: event.actor.login,
+            actor_id: event.actor.id,
+            before_commit: before,
+            after_commit: event.payload.head.unwrap_or_default(),
+            ref_name: event.payload.ref_name.unwrap_or_default(),
+        });
+    }
+    if malformed > 0 {
+        warn!("Skipped {} malformed push events in {}", malformed, path.display());
+    }
+    Ok(events)
+}
+
+/// The hours of `start..=end`, newest first, leaving out hours not over by `now`
+fn hours(start: NaiveDate, end: NaiveDate, now: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let first = Utc.from_utc_datetime(&start.and_hms_opt(0, 0, 0).expect("midnight is valid"));
+    let last = Utc
+        .from_utc_datetime(&end.and_hms_opt(23, 0, 0).expect("23:00 is valid"))
+        .min(hour_start(now) - Duration::hours(1));
+    let mut hours = Vec::new();
+    let mut hour = last;
+    while hour >= first {
+        hours.push(hour);
+        hour -= Duration::hours(1);
+    }
+    hours
+}
+
+/// Finds zero-commit PushEvents in GH Archive hourly files instead of BigQuery, so no
+/// Google Cloud project is needed. Files are kept in `archive_dir`; missing hours are
+/// downloaded from data.gharchive.org and read one hour at a time.
+pub struct LocalArchiveScanner {
+    downloader: Downloader,
+    archive_dir: PathBuf,
+    base_url: String,
+}
+
+impl LocalArchiveScanner {
+    pub fn new(archive_dir: impl Into<PathBuf>, config: DownloadConfig) -> Result<Self> {
+        let archive_dir = archive_dir.into();
+        info!("Reading GH Archive hourly files from {}", archive_dir.display());
+        Ok(Self {
+            downloader: Downloader::new(config)?,
+            archive_dir,
+            base_url: GHARCHIVE_URL.to_string(),
+        })
+    }
+
+    /// Download missing hours from a mirror instead of data.gharchive.org
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+        self.base_url = base_url;
+        self
+    }
+
+    /// The local copy of the file for `hour`, downloaded first if it is missing. An hour
+    /// that cannot be downloaded, e.g. one GH Archive has not published yet or any hour in
+    /// offline mode, is skipped with a warning.
+    async fn archive_file(&self, hour: DateTime<Utc>) -> Result<Option<PathBuf>> {
+        let filename = archive_filename(hour);
+        let path = self.archive_dir.join(&filename);
+        if path.exists() {
+            return Ok(Some(path));
+        }
+
+        let url = format!("{}{}", self.base_url, filename);
+        if let Err(e) = offline::policy().check_url(&url) {
+            warn!("Skipping {}, which is not in {}: {}", filename, self.archive_dir.display(), e);
+            return Ok(None);
+        }
+        // Downloaded under another name first, so an interrupted download is never read
+        let partial = path.with_extension("gz.part");
+        let result = self.downloader.download_file(&url, &partial, None).await?;
+        if let DownloadStatus::Failed = result.status {
+            warn!("Skipping {}: {}", filename, result.error.unwrap_or_default());
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Ok(None);
+        }
+        tokio::fs::rename(&partial, &path).await?;
+        Ok(Some(path))
+    }
+
+    async fn hour_events(&self, hour: DateTime<Utc>, filter: RepositoryFilter) -> Result<Vec<ZeroCommitEvent>> {
+        let Some(path) = self.archive_file(hour).await? else {
+            return Ok(Vec::new());
+        };
+        let events = tokio::task::spawn_blocking(move || zero_commit_events_in_file(&path, &filter)).await??;
+        debug!("{} zero-commit events in {}", events.len(), archive_filename(hour));
+        Ok(events)
+    }
+
+    /// Every matching zero-commit PushEvent of `start_date..=end_date`, held in memory at once
+    pub async fn scan_zero_commit_events(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        filter: &RepositoryFilter,
+        limit: Option<i64>,
+    ) -> Result<Vec<ZeroCommitEvent>> {
+        info!("Scanning GH Archive files for zero-commit events from {} to {}", start_date, end_date);
+        let events: Vec<ZeroCommitEvent> =
+            self.stream_zero_commit_events(start_date, end_date, filter, limit).try_collect().await?;
+        info!("Found {} zero-commit events", events.len());
+        Ok(events)
+    }
+
+    /// Zero-commit PushEvents, newest hour first, read one hour at a time while the next
+    /// hours download. An error ends the stream.
+    pub fn stream_zero_commit_events<'a>(
+        &'a self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        filter: &RepositoryFilter,
+        limit: Option<i64>,
+    ) -> impl Stream<Item = Result<ZeroCommitEvent>> + Send + 'a {
+        let filter = filter.clone();
+        stream::iter(hours(start_date, end_date, Utc::now()))
+            .map(move |hour| self.hour_events(hour, filter.clone()))
+            .buffered(PREFETCH_HOURS)
+            .map_ok(|events| stream::iter(events.into_iter().map(Ok)))
+            .try_flatten()
+            .take(limit.map_or(usize::MAX, |limit| limit.max(0) as usize))
+    }
+
+    /// `stream_zero_commit_events` for the repositories of `organization`
+    pub fn stream_organization_zero_commits<'a>(
+        &'a self,
+        organization: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        limit: Option<i64>,
+    ) -> impl Stream<Item = Result<ZeroCommitEvent>> + Send + 'a {
+        let filter = RepositoryFilter {
+            organizations: vec![organization.to_string()],
+            ..Default::default()
+        };
+
+        self.stream_zero_commit_events(start_date, end_date, &filter, limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn test_reads_zero_commit_pushes_from_hourly_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("2024-01-01-0.json.gz");
+        let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+        for line in [
+            r#"{"id":"1","type":"PushEvent","created_at":"2024-01-01T00:05:00Z","actor":{"id":3,"login":"dev"},"repo":{"id":7,"name":"Acme/api"},"payload":{"ref":"refs/heads/main","head":"def456","before":"abc123","size":0,"commits":[]}}"#,
+            r#"{"id":"2","type":"PushEvent","created_at":"2024-01-01T00:06:00Z","actor":{"id":3,"login":"dev"},"repo":{"id":7,"name":"acme/api"},"payload":{"ref":"refs/heads/main","head":"aaa111","before":"def456","commits":[{"sha":"aaa111"}]}}"#,
+            r#"{"id":"3","type":"PushEvent","created_at":"2024-01-01T00:07:00Z","actor":{"id":4,"login":"bot"},"repo":{"id":8,"name":"other/web"},"payload":{"ref":"refs/heads/main","head":"bbb222","before":"ccc333","commits":[]}}"#,
+            r#"{"id":"4","type":"PushEvent","created_at":"2024-01-01T00:08:00Z","actor":{"id":3,"login":"dev"},"repo":{"id":7,"name":"acme/api"},"payload":{"ref":"refs/heads/new","head":"ddd444","before":"0000000000000000000000000000000000000000","commits":[]}}"#,
+            r#"{"id":"5","type":"WatchEvent","created_at":"2024-01-01T00:09:00Z","actor":{"id":3,"login":"dev"},"repo":{"id":7,"name":"acme/api"},"payload":{"action":"started"}}"#,
+            r#"{"type":"PushEvent","repo":"#,
+        ] {
+            writeln!(encoder, "{}", line).unwrap();
+        }
+        encoder.finish().unwrap();
+
+        let filter = RepositoryFilter { organizations: vec!["acme".to_string()], ..Default::default() };
+        let events = zero_commit_events_in_file(&path, &filter).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, "1");
+        assert_eq!(events[0].repo_name, "Acme/api");
+        assert_eq!((events[0].before_commit.as_str(), events[0].after_commit.as_str()), ("abc123", "def456"));
+        assert_eq!(events[0].ref_name, "refs/heads/main");
+        assert_eq!(zero_commit_events_in_file(&path, &RepositoryFilter::default()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_hours_are_newest_first_and_finished() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let at = |d, h| Utc.with_ymd_and_hms(2024, 1, d, h, 0, 0).unwrap();
+
+        let all = hours(date(1), date(2), at(10, 0));
+        assert_eq!(all.len(), 48);
+        assert_eq!((all[0], all[47]), (at(2, 23), at(1, 0)));
+
+        // Today's hours stop before the one in progress
+        let today = hours(date(2), date(2), at(2, 5) + Duration::minutes(30));
+        assert_eq!(today, vec![at(2, 4), at(2, 3), at(2, 2), at(2, 1), at(2, 0)]);
+    }
+}
@@ -8,8 +8,18 @@ use serde::{Serialize, Deserialize};
 use crate::core::{Config, DatabaseManager, ResourceMonitor, ResourceLimits};
 use crate::scraper::{
     ScraperManager, ArchiveScraper, FileProcessor, Downloader,
-    DownloadConfig, ProcessingConfig, ScrapingStats
+    DownloadConfig, ProcessingConfig, ScrapingStats, DownloadStatus
 };
+use crate::scraper::gaps::{self, GapReport};
+
+/// A queued backfill is dropped after failing this many times
+pub const MAX_BACKFILL_ATTEMPTS: i32 = 5;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BackfillSummary {
+    pub processed: u64,
+    pub failed: u64,
+}
 
 #[derive(Debug, Clone, Serialize)]
 pub struct MainScraperStatus {
@@ -46,6 +56,8 @@ impl MainScraper {
             request_timeout_seconds: 180,
             max_retries: 3,
             retry_delay_seconds: 2.0,
+            max_bytes_per_second: config.download.max_bytes_per_second,
+            schedule: config.download.download_windows.clone(),
         };
 
         let processing_config = ProcessingConfig {
@@ -70,7 +82,7 @@ impl MainScraper {
         Ok(Self {
             config: config.clone(),
             scraper_manager: scraper_manager.clone(),
-            archive_scraper: Some(ArchiveScraper::new(config.clone(), scraper_manager)),
+            archive_scraper: Some(ArchiveScraper::new(config.clone(), scraper_manager)?),
             file_processor: FileProcessor::new(processing_config),
             downloader: Downloader::new(download_config)?,
             database_manager: Some(DatabaseManager::new(config)),
@@ -246,6 +258,11 @@ impl MainScraper {
                 }
             }
 
+            // Fill hours found missing by `scraper gaps` before following new files
+            if let Err(e) = self.run_backfills(50).await {
+                error!("Backfill error: {}", e);
+            }
+
             // Run archive scraping
             if let Some(ref scraper) = self.archive_scraper {
                 match scraper.run_continuous_scraping().await {
@@ -308,6 +325,78 @@ impl MainScraper {
         Ok(result)
     }
 
+    /// Compare processed files against the hourly GH Archive timeline. `start` defaults to the
+    /// earliest processed hour; `end` defaults to two hours ago, the newest hour reliably published.
+    pub async fn find_gaps(
+        &self,
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        end: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<GapReport> {
+        let db = self.database_manager.as_ref().ok_or_else(|| anyhow::anyhow!("Database not initialized"))?;
+        let processed = db.processed_filenames().await?;
+
+        let end = end.unwrap_or_else(|| chrono::Utc::now() - chrono::Duration::hours(2));
+        let start = match start {
+            Some(start) => start,
+            None => processed
+                .iter()
+                .filter_map(|f| gaps::archive_hour(f))
+                .min()
+                .ok_or_else(|| anyhow::anyhow!("No processed files yet; pass a start date"))?,
+        };
+        if start > end {
+            return Err(anyhow::anyhow!("Gap check start {} is after end {}", start, end));
+        }
+
+        Ok(gaps::find_gaps(processed.iter().map(String::as_str), start, end))
+    }
+
+    /// Queue every missing hour of `report` for download; returns how many were newly queued
+    pub async fn queue_backfills(&self, report: &GapReport) -> Result<u64> {
+        let db = self.database_manager.as_ref().ok_or_else(|| anyhow::anyhow!("Database not initialized"))?;
+        let queued = db.queue_backfills(&report.missing_files).await?;
+        info!("Queued {} missing hours for backfill", queued);
+        Ok(queued)
+    }
+
+    /// Download and process up to `limit` queued backfills
+    pub async fn run_backfills(&self, limit: i64) -> Result<BackfillSummary> {
+        let db = self.database_manager.as_ref().ok_or_else(|| anyhow::anyhow!("Database not initialized"))?;
+        let mut summary = BackfillSummary::default();
+
+        for filename in db.pending_backfills(limit, MAX_BACKFILL_ATTEMPTS).await? {
+            if self.shutdown_requested {
+                break;
+            }
+
+            let url = format!("{}{}", self.config.download.base_url, filename);
+            let outcome = match self.download_file(&url, &filename).await {
+                Ok(download) if matches!(download.status, DownloadStatus::Failed) => {
+                    Err(anyhow::anyhow!(download.error.unwrap_or_else(|| "download failed".to_string())))
+                }
+                Ok(_) => self.process_single_file(&filename).await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+
+            match outcome {
+                Ok(()) => {
+                    summary.processed += 1;
+                    db.finish_backfill(&filename, None).await?;
+                }
+                Err(e) => {
+                    warn!("Backfill of {} failed: {}", filename, e);
+                    summary.failed += 1;
+                    db.finish_backfill(&filename, Some(&e.to_string())).await?;
+                }
+            }
+        }
+
+        if summary.processed + summary.failed > 0 {
+            info!("Backfill: {} hours processed, {} failed", summary.processed, summary.failed);
+        }
+        Ok(summary)
+    }
+
     pub async fn get_available_files(&self) -> Result<Vec<crate::scraper::ArchiveFile>> {
         if let Some(ref scraper) = self.archive_scraper {
             scraper.get_available_files().await
@@ -1,14 +1,22 @@
 // GitHub Archive scraper module placeholder
 // This will contain the main scraping logic, file processing, and data extraction
 
+pub mod archive_scan;
 pub mod archive_scraper;
 pub mod file_processor;
 pub mod downloader;
 pub mod state;
 pub mod main_scraper;
+pub mod gaps;
+pub mod local_archive;
+pub mod throttle;
 
 pub use state::{ScraperManager, ScraperState, ScraperStatus};
+pub use archive_scan::{ArchiveScanStats, ArchiveSecretScanner};
 pub use archive_scraper::{ArchiveScraper, ArchiveFile, ProcessingResult as ArchiveProcessingResult, ScrapingStats};
 pub use file_processor::{FileProcessor, ProcessingResult as FileProcessingResult, GitHubEvent, EventBatch, RepositoryInfo, ActorInfo, ProcessingConfig};
 pub use downloader::{Downloader, DownloadResult, DownloadStatus, DownloadConfig};
-pub use main_scraper::{MainScraper, MainScraperStatus};
+pub use main_scraper::{BackfillSummary, MainScraper, MainScraperStatus, MAX_BACKFILL_ATTEMPTS};
+pub use gaps::{GapRange, GapReport};
+pub use local_archive::{LocalArchiveScanner, GHARCHIVE_URL};
+pub use throttle::TokenBucket;
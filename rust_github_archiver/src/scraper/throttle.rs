@@ -0,0 +1,129 @@
+use chrono::{Local, NaiveTime};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::core::{in_daily_window, DownloadWindow};
+
+/// Token bucket measured in bytes. One bucket is shared by every concurrent download
+/// of a `Downloader`, so the cap applies to their combined bandwidth.
+pub struct TokenBucket {
+    bytes_per_second: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Holds at most one second of bandwidth, so bursts stay short
+    pub fn new(bytes_per_second: u64) -> Self {
+        let bytes_per_second = bytes_per_second.max(1) as f64;
+        Self {
+            bytes_per_second,
+            state: Mutex::new(BucketState {
+                tokens: bytes_per_second,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn bytes_per_second(&self) -> u64 {
+        self.bytes_per_second as u64
+    }
+
+    /// Take `bytes` from the bucket, sleeping until they are paid for. The bucket may go
+    /// into debt; later callers then wait behind it, which keeps the combined rate at the cap.
+    pub async fn acquire(&self, bytes: u64) {
+        let wait = {
+            let mut state = self.state.lock().await;
+            let now = Instant::now();
+            let refill = now.duration_since(state.last_refill).as_secs_f64() * self.bytes_per_second;
+            state.tokens = (state.tokens + refill).min(self.bytes_per_second);
+            state.last_refill = now;
+            state.tokens -= bytes as f64;
+            if state.tokens < 0.0 {
+                Duration::from_secs_f64(-state.tokens / self.bytes_per_second)
+            } else {
+                Duration::ZERO
+            }
+        };
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+impl DownloadWindow {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        in_daily_window(time, self.start, self.end)
+    }
+
+    /// How long from `time` until this window next opens
+    pub fn until_open(&self, time: NaiveTime) -> Duration {
+        let seconds = (self.start - time).num_seconds().rem_euclid(24 * 3600);
+        Duration::from_secs(seconds as u64)
+    }
+}
+
+/// Time to wait before a download may start; zero when there are no windows or one is open
+pub fn until_next_window(windows: &[DownloadWindow], now: NaiveTime) -> Duration {
+    if windows.is_empty() || windows.iter().any(|w| w.contains(now)) {
+        return Duration::ZERO;
+    }
+    windows.iter().map(|w| w.until_open(now)).min().unwrap_or(Duration::ZERO)
+}
+
+/// Sleep until one of `windows` is open (local time)
+pub async fn wait_for_window(windows: &[DownloadWindow]) {
+    loop {
+        let wait = until_next_window(windows, Local::now().time());
+        if wait.is_zero() {
+            return;
+        }
+        info!("Outside the download schedule, waiting {} minutes", wait.as_secs().div_ceil(60));
+        // Re-check at least every 15 minutes in case the clock jumps
+        tokio::time::sleep(wait.min(Duration::from_secs(900))).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_windows_gate_start_time() {
+        let night = DownloadWindow { start: at(22, 0), end: at(6, 0) };
+        assert!(night.contains(at(23, 30)));
+        assert!(night.contains(at(2, 0)));
+        assert!(!night.contains(at(6, 0)));
+
+        assert_eq!(until_next_window(&[night], at(3, 0)), Duration::ZERO);
+        assert_eq!(until_next_window(&[night], at(20, 30)), Duration::from_secs(90 * 60));
+        let lunch = DownloadWindow { start: at(12, 0), end: at(13, 0) };
+        assert_eq!(until_next_window(&[night, lunch], at(7, 0)), Duration::from_secs(5 * 3600));
+        assert_eq!(until_next_window(&[], at(7, 0)), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_bucket_is_shared_across_callers() {
+        let bucket = std::sync::Arc::new(TokenBucket::new(10_000));
+        let start = Instant::now();
+
+        // The first second's worth is available immediately
+        bucket.acquire(10_000).await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        // Two concurrent callers split the refill: 4 KB total needs ~400 ms
+        let (a, b) = (bucket.clone(), bucket.clone());
+        tokio::join!(a.acquire(2_000), b.acquire(2_000));
+        assert!(start.elapsed() >= Duration::from_millis(350));
+    }
+}
@@ -1,5 +1,15 @@
+pub mod allowlist;
+pub mod annotations;
+pub mod import;
+pub mod rotation;
+pub mod rules;
 pub mod scanner;
 pub mod validator;
 
 pub use scanner::{SecretScanner, SecretMatch, SecretDetector, SecretSeverity, SecretCategory, ScanResult};
+pub use annotations::{check_run_annotations, check_run_level, rdjson_report, rdjson_severity};
+pub use allowlist::{Allowlist, AllowlistEntry, AllowlistKind, DEFAULT_IGNORE_FILE};
+pub use rotation::{rotation_actions, rotation_snippets, RotationSnippet, SnippetLanguage};
+pub use rules::{RuleError, RuleLoadReport};
+pub use import::{FindingImporter, ImportFormat, ImportReport};
 pub use validator::{SecretValidator, ValidationResult};
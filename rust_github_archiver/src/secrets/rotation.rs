@@ -0,0 +1,277 @@
+use serde::{Deserialize, Serialize};
+
+use super::SecretMatch;
+
+/// What a rotation snippet is written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnippetLanguage {
+    Shell,
+    Terraform,
+}
+
+/// A script that replaces one leaked credential with its provider: it creates the new
+/// credential, then deactivates or deletes the leaked one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationSnippet {
+    pub provider: String,
+    pub language: SnippetLanguage,
+    pub title: String,
+    pub script: String,
+}
+
+impl RotationSnippet {
+    fn new(provider: &str, language: SnippetLanguage, title: &str, header: &str, body: &str) -> Self {
+        let mut script = header.to_string();
+        if language == SnippetLanguage::Shell {
+            script.push_str("set -euo pipefail\n");
+        }
+        script.push_str(body);
+        Self { provider: provider.to_string(), language, title: title.to_string(), script }
+    }
+
+    /// The snippet as a suggested action: the title on the first line, the script below it
+    pub fn suggested_action(&self) -> String {
+        format!("🔄 {}\n{}", self.title, self.script.trim_end())
+    }
+}
+
+/// Comment lines naming where the credential leaked, valid in shell and Terraform alike.
+/// The leaked value itself is never written into a snippet, since suggested actions are
+/// stored and sent unredacted.
+fn header(finding: &SecretMatch, repository: Option<&str>) -> String {
+    let file = finding.filename.as_deref().unwrap_or("unknown file");
+    let location = match (repository, finding.line_number) {
+        (Some(repository), Some(line)) => format!("{}/{}:{}", repository, file, line),
+        (Some(repository), None) => format!("{}/{}", repository, file),
+        (None, Some(line)) => format!("{}:{}", file, line),
+        (None, None) => file.to_string(),
+    };
+    let mut lines = vec![format!("# Rotates the {} leaked in {}", finding.detector_name, location)];
+    if let Some(commit) = &finding.commit_sha {
+        lines.push(format!("# Commit {}, GitArchiver finding {}", commit, finding.hash));
+    } else {
+        lines.push(format!("# GitArchiver finding {}", finding.hash));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Short name for the replacement credential, so it can be traced back to the finding
+fn replacement_name(finding: &SecretMatch) -> String {
+    let id: String = finding.hash.chars().filter(char::is_ascii_alphanumeric).take(8).collect();
+    format!("rotated-{}", id.to_ascii_lowercase())
+}
+
+/// Rotation snippets for the provider of `finding`'s detector; empty for detectors with
+/// no provider-side rotation, such as generic secrets and JWTs
+pub fn rotation_snippets(finding: &SecretMatch, repository: Option<&str>) -> Vec<RotationSnippet> {
+    use SnippetLanguage::{Shell, Terraform};
+
+    let header = header(finding, repository);
+    let name = replacement_name(finding);
+    let resource = name.replace('-', "_");
+    match finding.detector_name.as_str() {
+        "AWS Access Key ID" | "AWS Secret Access Key" => vec![
+            RotationSnippet::new("aws", Shell, "Rotate the AWS access key with the AWS CLI", &header, &format!(
+                "KEY_ID=\"${{LEAKED_ACCESS_KEY_ID:?set to the ID of the leaked access key}}\"\n\
+                 USER_NAME=$(aws iam get-access-key-last-used --access-key-id \"$KEY_ID\" --query UserName --output text)\n\
+                 aws iam create-access-key --user-name \"$USER_NAME\" > {name}.json\n\
+                 aws iam update-access-key --user-name \"$USER_NAME\" --access-key-id \"$KEY_ID\" --status Inactive\n\
+                 # Once everything uses the key in {name}.json:\n\
+                 # aws iam delete-access-key --user-name \"$USER_NAME\" --access-key-id \"$KEY_ID\"\n"
+            )),
+            RotationSnippet::new("aws", Terraform, "Rotate the AWS access key with Terraform", &header, &format!(
+                "# A key Terraform already manages is replaced with: terraform apply -replace=aws_iam_access_key.<name>\n\
+                 # Otherwise this creates the new key; deactivate the leaked one with the CLI snippet\n\
+                 variable \"leaked_key_user\" {{\n  \
+                   description = \"IAM user that owns the leaked access key\"\n  \
+                   type        = string\n\
+                 }}\n\n\
+                 resource \"aws_iam_access_key\" \"{resource}\" {{\n  \
+                   user   = var.leaked_key_user\n  \
+                   status = \"Active\"\n\
+                 }}\n"
+            )),
+        ],
+        "AWS Session Token" => vec![RotationSnippet::new("aws", Shell, "Revoke the AWS role's active sessions", &header,
+            "ROLE_NAME=\"${LEAKED_ROLE_NAME:?set to the role the session token was issued for}\"\n\
+             # Denies every session of the role issued before now; new sessions are unaffected\n\
+             aws iam put-role-policy --role-name \"$ROLE_NAME\" --policy-name AWSRevokeOlderSessions --policy-document \"$(cat <<EOF\n\
+             {\"Version\":\"2012-10-17\",\"Statement\":[{\"Effect\":\"Deny\",\"Action\":\"*\",\"Resource\":\"*\",\
+             \"Condition\":{\"DateLessThan\":{\"aws:TokenIssueTime\":\"$(date -u +%Y-%m-%dT%H:%M:%SZ)\"}}}]}\n\
+             EOF\n)\"\n",
+        )],
+        "GitHub Personal Access Token" | "GitHub Fine-grained PAT" | "GitHub OAuth Token" => {
+            vec![RotationSnippet::new("github", Shell, "Find and replace the GitHub token", &header, &format!(
+                ": \"${{LEAKED_TOKEN:?export the leaked token; it is not written into this script}}\"\n\
+                 # The account the token acts for, and the scopes of a classic token\n\
+                 GH_TOKEN=\"$LEAKED_TOKEN\" gh api user --jq .login\n\
+                 GH_TOKEN=\"$LEAKED_TOKEN\" gh api --include user | grep -i '^x-oauth-scopes' || true\n\
+                 # As that account, create \"{name}\" with the same scopes, then delete the leaked token:\n\
+                 #   https://github.com/settings/tokens\n\
+                 # OAuth app tokens are revoked by the app instead:\n\
+                 # gh api --method DELETE \"/applications/$CLIENT_ID/token\" -u \"$CLIENT_ID:$CLIENT_SECRET\" -f access_token=\"$LEAKED_TOKEN\"\n"
+            ))]
+        }
+        "GitHub App Token" => vec![RotationSnippet::new("github", Shell, "Revoke the GitHub App installation token", &header,
+            ": \"${LEAKED_TOKEN:?export the leaked token; it is not written into this script}\"\n\
+             # An installation token can revoke itself; the app requests a new one when it next needs it\n\
+             GH_TOKEN=\"$LEAKED_TOKEN\" gh api --method DELETE /installation/token\n",
+        )],
+        "Google API Key" => vec![RotationSnippet::new("gcp", Shell, "Replace the Google API key", &header, &format!(
+            ": \"${{LEAKED_KEY:?export the leaked API key; it is not written into this script}}\"\n\
+             KEY_NAME=$(gcloud services api-keys lookup \"$LEAKED_KEY\" --format='value(name)')\n\
+             # Copy the leaked key's API and application restrictions to the new one\n\
+             gcloud services api-keys describe \"$KEY_NAME\"\n\
+             gcloud services api-keys create --display-name=\"{name}\"\n\
+             gcloud services api-keys delete \"$KEY_NAME\"\n"
+        ))],
+        "Google Service Account" => vec![
+            RotationSnippet::new("gcp", Shell, "Rotate the Google service account key with gcloud", &header, &format!(
+                "SERVICE_ACCOUNT=\"${{LEAKED_CLIENT_EMAIL:?set to client_email from the leaked key file}}\"\n\
+                 KEY_ID=\"${{LEAKED_PRIVATE_KEY_ID:?set to private_key_id from the leaked key file}}\"\n\
+                 gcloud iam service-accounts keys create {name}.json --iam-account=\"$SERVICE_ACCOUNT\"\n\
+                 gcloud iam service-accounts keys disable \"$KEY_ID\" --iam-account=\"$SERVICE_ACCOUNT\"\n\
+                 # Once everything uses {name}.json:\n\
+                 # gcloud iam service-accounts keys delete \"$KEY_ID\" --iam-account=\"$SERVICE_ACCOUNT\"\n"
+            )),
+            RotationSnippet::new("gcp", Terraform, "Rotate the Google service account key with Terraform", &header, &format!(
+                "variable \"leaked_key_service_account\" {{\n  \
+                   description = \"client_email from the leaked key file\"\n  \
+                   type        = string\n\
+                 }}\n\n\
+                 resource \"google_service_account_key\" \"{resource}\" {{\n  \
+                   service_account_id = var.leaked_key_service_account\n\
+                 }}\n"
+            )),
+        ],
+        "Slack Bot Token" => vec![RotationSnippet::new("slack", Shell, "Revoke the Slack bot token", &header,
+            ": \"${LEAKED_TOKEN:?export the leaked token; it is not written into this script}\"\n\
+             curl -sS -X POST https://slack.com/api/auth.revoke -H \"Authorization: Bearer $LEAKED_TOKEN\"\n\
+             # Reinstall the app to the workspace to issue its new bot token\n",
+        )],
+        "SendGrid API Key" => vec![RotationSnippet::new("sendgrid", Shell, "Replace the SendGrid API key", &header, &format!(
+            ": \"${{LEAKED_KEY:?export the leaked API key; it is not written into this script}}\"\n\
+             : \"${{SENDGRID_ADMIN_KEY:?export a SendGrid key allowed to manage API keys}}\"\n\
+             # The key's ID is the part between SG. and the second dot\n\
+             KEY_ID=$(echo \"$LEAKED_KEY\" | cut -d. -f2)\n\
+             curl -sS -X POST https://api.sendgrid.com/v3/api_keys -H \"Authorization: Bearer $SENDGRID_ADMIN_KEY\" \
+             -H 'Content-Type: application/json' -d '{{\"name\": \"{name}\", \"scopes\": [\"mail.send\"]}}'\n\
+             curl -sS -X DELETE \"https://api.sendgrid.com/v3/api_keys/$KEY_ID\" -H \"Authorization: Bearer $SENDGRID_ADMIN_KEY\"\n"
+        ))],
+        "MongoDB Connection String" => vec![RotationSnippet::new("mongodb", Shell, "Change the MongoDB user's password", &header,
+            "DB_USER=\"${LEAKED_DB_USER:?set to the user in the leaked connection string}\"\n\
+             : \"${ADMIN_URI:?export a connection string for a user administrator}\"\n\
+             NEW_PASSWORD=$(openssl rand -base64 24)\n\
+             mongosh \"$ADMIN_URI\" --quiet --eval \"db.getSiblingDB('admin').changeUserPassword('$DB_USER', '$NEW_PASSWORD')\"\n\
+             echo \"New password for $DB_USER: $NEW_PASSWORD\"\n",
+        )],
+        "MongoDB Atlas Connection" => vec![
+            RotationSnippet::new("mongodb-atlas", Shell, "Change the Atlas database user's password", &header,
+                "DB_USER=\"${LEAKED_DB_USER:?set to the user in the leaked connection string}\"\n\
+                 PROJECT_ID=\"${ATLAS_PROJECT_ID:?set to the Atlas project of the cluster}\"\n\
+                 NEW_PASSWORD=$(openssl rand -base64 24)\n\
+                 atlas dbusers update \"$DB_USER\" --projectId \"$PROJECT_ID\" --password \"$NEW_PASSWORD\"\n\
+                 echo \"New password for $DB_USER: $NEW_PASSWORD\"\n",
+            ),
+            RotationSnippet::new("mongodb-atlas", Terraform, "Change the Atlas database user's password with Terraform", &header, &format!(
+                "# Import the leaked user first: terraform import mongodbatlas_database_user.{resource} <project>-<user>-admin\n\
+                 variable \"atlas_project_id\" {{\n  type = string\n}}\n\n\
+                 variable \"leaked_db_user\" {{\n  type = string\n}}\n\n\
+                 resource \"random_password\" \"{resource}\" {{\n  \
+                   length  = 32\n  \
+                   special = false\n\
+                 }}\n\n\
+                 resource \"mongodbatlas_database_user\" \"{resource}\" {{\n  \
+                   project_id         = var.atlas_project_id\n  \
+                   username           = var.leaked_db_user\n  \
+                   password           = random_password.{resource}.result\n  \
+                   auth_database_name = \"admin\"\n\n  \
+                   roles {{\n    \
+                     role_name     = \"readWriteAnyDatabase\"\n    \
+                     database_name = \"admin\"\n  \
+                   }}\n\
+                 }}\n"
+            )),
+        ],
+        "SSH Private Key" => {
+            let deploy_keys = match repository {
+                Some(repository) => format!(
+                    "gh repo deploy-key add {name}.pub --repo {repository} --title \"{name}\"\n\
+                     # Delete the leaked key's deploy key by its ID:\n\
+                     gh repo deploy-key list --repo {repository}\n\
+                     # gh repo deploy-key delete <id> --repo {repository}\n"
+                ),
+                None => "# Replace the leaked public key wherever it is trusted: deploy keys, user keys, servers\n".to_string(),
+            };
+            vec![RotationSnippet::new("ssh", Shell, "Replace the SSH key pair", &header, &format!(
+                "ssh-keygen -t ed25519 -N '' -C \"{name}\" -f {name}\n\
+                 OLD_KEY=\"${{LEAKED_PUBLIC_KEY:?set to the public half of the leaked key}}\"\n\
+                 # On every host that trusts the leaked key:\n\
+                 # grep -vF \"$OLD_KEY\" ~/.ssh/authorized_keys > authorized_keys.new && cat {name}.pub >> authorized_keys.new \
+                 && mv authorized_keys.new ~/.ssh/authorized_keys\n\
+                 {deploy_keys}"
+            ))]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// `rotation_snippets` as suggested actions
+pub fn rotation_actions(finding: &SecretMatch, repository: Option<&str>) -> Vec<String> {
+    rotation_snippets(finding, repository).iter().map(RotationSnippet::suggested_action).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::{SecretCategory, SecretSeverity};
+
+    fn finding(detector: &str, matched_text: &str) -> SecretMatch {
+        SecretMatch {
+            detector_name: detector.to_string(),
+            matched_text: matched_text.to_string(),
+            start_position: 0,
+            end_position: matched_text.len(),
+            line_number: Some(12),
+            filename: Some("config/prod.env".to_string()),
+            commit_sha: Some("abc123".to_string()),
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
+            entropy: 4.5,
+            severity: SecretSeverity::Critical,
+            category: SecretCategory::CloudProvider,
+            context: matched_text.to_string(),
+            verified: true,
+            hash: "F00DCAFE1234".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_snippets_are_personalized_without_the_secret() {
+        let secret = "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY";
+        let snippets = rotation_snippets(&finding("AWS Secret Access Key", secret), Some("acme/api"));
+        assert_eq!(snippets.len(), 2);
+        assert_eq!((snippets[0].language, snippets[1].language), (SnippetLanguage::Shell, SnippetLanguage::Terraform));
+        for snippet in &snippets {
+            assert!(snippet.script.contains("leaked in acme/api/config/prod.env:12"));
+            assert!(snippet.script.contains("Commit abc123, GitArchiver finding F00DCAFE1234"));
+            assert!(!snippet.script.contains(secret));
+        }
+        assert!(snippets[0].script.contains("create-access-key") && snippets[0].script.contains("--status Inactive"));
+        assert!(snippets[1].script.contains("resource \"aws_iam_access_key\" \"rotated_f00dcafe\""));
+
+        let action = rotation_actions(&finding("GitHub App Token", "ghs_abc"), None).remove(0);
+        assert!(action.starts_with("🔄 Revoke the GitHub App installation token\n# Rotates the GitHub App Token leaked in config/prod.env:12"));
+    }
+
+    #[test]
+    fn test_ssh_snippet_uses_the_repository_deploy_keys() {
+        let with_repository = rotation_snippets(&finding("SSH Private Key", "-----BEGIN"), Some("acme/api"));
+        assert!(with_repository[0].script.contains("gh repo deploy-key add rotated-f00dcafe.pub --repo acme/api"));
+        let without = rotation_snippets(&finding("SSH Private Key", "-----BEGIN"), None);
+        assert!(!without[0].script.contains("deploy-key"));
+        assert!(rotation_snippets(&finding("Generic Secret", "hunter2"), None).is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use super::scanner::{SecretCategory, SecretDetector, SecretScanner, SecretSeverity};
+
+/// One rule in a gitleaks-style rule file. `severity` and `category` extend the gitleaks
+/// format; other gitleaks keys (`allowlist`, `secretGroup`, ...) are ignored.
+#[derive(Debug, Deserialize)]
+struct RuleDefinition {
+    id: String,
+    #[serde(default)]
+    description: String,
+    #[serde(alias = "pattern")]
+    regex: String,
+    #[serde(default)]
+    keywords: Vec<String>,
+    entropy: Option<f64>,
+    severity: Option<String>,
+    category: Option<String>,
+}
+
+impl RuleDefinition {
+    fn into_detector(self) -> Result<SecretDetector> {
+        let severity = match &self.severity {
+            Some(severity) => severity.parse()?,
+            None => SecretSeverity::Medium,
+        };
+        let category = match &self.category {
+            Some(category) => category.parse()?,
+            None => SecretCategory::Other,
+        };
+        Ok(SecretDetector {
+            description: if self.description.is_empty() { self.id.clone() } else { self.description },
+            name: self.id,
+            pattern: self.regex,
+            keywords: self.keywords,
+            entropy_threshold: self.entropy,
+            verify_func: None,
+            severity,
+            category,
+        })
+    }
+}
+
+/// A rule that could not be loaded; the other rules of its file still are
+#[derive(Debug, Clone)]
+pub struct RuleError {
+    pub file: PathBuf,
+    /// Rule id, or its position in the file when it has none
+    pub rule: String,
+    pub message: String,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: rule {}: {}", self.file.display(), self.rule, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RuleLoadReport {
+    pub loaded: Vec<String>,
+    /// Loaded rules that replaced a built-in or earlier detector of the same name
+    pub replaced: Vec<String>,
+    pub errors: Vec<RuleError>,
+}
+
+impl RuleLoadReport {
+    fn merge(&mut self, other: RuleLoadReport) {
+        self.loaded.extend(other.loaded);
+        self.replaced.extend(other.replaced);
+        self.errors.extend(other.errors);
+    }
+
+    pub fn log(&self) {
+        info!("Loaded {} custom detector rules ({} replacing built-ins)", self.loaded.len(), self.replaced.len());
+        for error in &self.errors {
+            warn!("Skipped detector rule {}", error);
+        }
+    }
+}
+
+/// Parse a rule file into one value per rule, so a bad rule does not hide the others
+fn parse_rules(path: &Path, content: &str) -> Result<Vec<(String, Result<RuleDefinition>)>> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let label = |index: usize, id: Option<&str>| id.map_or(format!("#{}", index + 1), str::to_string);
+
+    match extension.as_str() {
+        "toml" => {
+            let document: toml::Table = toml::from_str(content)?;
+            let rules = match document.get("rules") {
+                Some(toml::Value::Array(rules)) => rules.clone(),
+                Some(_) => return Err(anyhow!("`rules` must be an array of tables")),
+                None => Vec::new(),
+            };
+            Ok(rules
+                .into_iter()
+                .enumerate()
+                .map(|(i, rule)| {
+                    let name = label(i, rule.get("id").and_then(|id| id.as_str()));
+                    (name, rule.try_into::<RuleDefinition>().map_err(Into::into))
+                })
+                .collect())
+        }
+        "yaml" | "yml" => {
+            let document: serde_yaml::Value = serde_yaml::from_str(content)?;
+            let rules = match document.get("rules") {
+                Some(serde_yaml::Value::Sequence(rules)) => rules.clone(),
+                Some(_) => return Err(anyhow!("`rules` must be a list")),
+                None => Vec::new(),
+            };
+            Ok(rules
+                .into_iter()
+                .enumerate()
+                .map(|(i, rule)| {
+                    let name = label(i, rule.get("id").and_then(|id| id.as_str()));
+                    (name, serde_yaml::from_value::<RuleDefinition>(rule).map_err(Into::into))
+                })
+                .collect())
+        }
+        other => Err(anyhow!("Unsupported rule file extension '{}' (expected .toml, .yaml or .yml)", other)),
+    }
+}
+
+impl SecretScanner {
+    /// Load detectors from a gitleaks-style TOML or YAML file and merge them with the
+    /// current ones. Rules that fail to parse or compile are reported, not fatal; an
+    /// unreadable or malformed file is.
+    pub fn load_detectors_from_file(&mut self, path: impl AsRef<Path>) -> Result<RuleLoadReport> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read rule file {}: {}", path.display(), e))?;
+        let rules = parse_rules(path, &content).map_err(|e| anyhow!("Invalid rule file {}: {}", path.display(), e))?;
+
+        let mut report = RuleLoadReport::default();
+        for (name, rule) in rules {
+            let result = rule.and_then(RuleDefinition::into_detector).and_then(|d| self.upsert_detector(d));
+            match result {
+                Ok(replaced) => {
+                    if replaced {
+                        report.replaced.push(name.clone());
+                    }
+                    report.loaded.push(name);
+                }
+                Err(e) => report.errors.push(RuleError { file: path.to_path_buf(), rule: name, message: e.to_string() }),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Load every `.toml`, `.yaml` and `.yml` file in `dir` in name order, so a later file
+    /// can override a rule from an earlier one. A single file is loaded as is.
+    pub fn load_detectors_from_dir(&mut self, dir: impl AsRef<Path>) -> Result<RuleLoadReport> {
+        let dir = dir.as_ref();
+        if dir.is_file() {
+            return self.load_detectors_from_file(dir);
+        }
+
+        let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map_err(|e| anyhow!("Failed to read rules directory {}: {}", dir.display(), e))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file()
+                    && matches!(
+                        path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref(),
+                        Some("toml" | "yaml" | "yml")
+                    )
+            })
+            .collect();
+        files.sort();
+
+        let mut report = RuleLoadReport::default();
+        for file in files {
+            report.merge(self.load_detectors_from_file(&file)?);
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_rules_merge_with_built_ins() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("acme.toml"),
+            r#"
+[[rules]]
+id = "acme-api-key"
+description = "Acme API key"
+regex = '''acme_[a-z0-9]{24}'''
+keywords = ["acme_"]
+severity = "high"
+category = "api_key"
+
+[[rules]]
+id = "broken"
+regex = '''acme_(unclosed'''
+"#,
+        )
+        .unwrap();
+
+        let mut scanner = SecretScanner::new();
+        let built_in = scanner.get_detector_names().len();
+        let report = scanner.load_detectors_from_dir(dir.path()).unwrap();
+
+        assert_eq!(report.loaded, vec!["acme-api-key"]);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].rule, "broken");
+        assert_eq!(scanner.get_detector_names().len(), built_in + 1);
+
+        let matches = scanner.scan_text("key = acme_abcdefghijklmnopqrstuvwx", None);
+        assert!(matches.iter().any(|m| m.detector_name == "acme-api-key"));
+    }
+
+    #[test]
+    fn test_yaml_rules_report_each_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rules.yaml");
+        std::fs::write(
+            &path,
+            r#"
+rules:
+  - id: AWS Access Key ID
+    pattern: "AKIA[0-9A-Z]{16}"
+    severity: critical
+  - description: no id or regex
+  - id: bad-severity
+    regex: "x{3}"
+    severity: catastrophic
+"#,
+        )
+        .unwrap();
+
+        let mut scanner = SecretScanner::new();
+        let before = scanner.get_detector_names().len();
+        let report = scanner.load_detectors_from_file(&path).unwrap();
+
+        assert_eq!(report.replaced, vec!["AWS Access Key ID"]);
+        assert_eq!(scanner.get_detector_names().len(), before);
+        assert!(matches!(scanner.get_detector("AWS Access Key ID").unwrap().severity, SecretSeverity::Critical));
+        let rules: Vec<_> = report.errors.iter().map(|e| e.rule.as_str()).collect();
+        assert_eq!(rules, vec!["#2", "bad-severity"]);
+    }
+}
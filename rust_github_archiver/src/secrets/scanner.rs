@@ -1,17 +1,23 @@
 use anyhow::{anyhow, Result};
 use fancy_regex::Regex;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use std::collections::HashMap;
 use tracing::{info, warn, error, debug};
 use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
-use sha2::{Sha256, Digest};
 use entropy::shannon_entropy;
 
+use super::allowlist::Allowlist;
+use crate::performance::Fingerprinter;
+
 /// Secret scanner with 50+ built-in detectors
+#[derive(Clone)]
 pub struct SecretScanner {
     detectors: Vec<SecretDetector>,
     patterns: HashMap<String, Regex>,
     entropy_threshold: f64,
+    allowlist: Allowlist,
+    fingerprinter: Fingerprinter,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,7 +32,7 @@ pub struct SecretDetector {
     pub category: SecretCategory,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub enum SecretSeverity {
     Low,
     Medium,
@@ -34,7 +40,28 @@ pub enum SecretSeverity {
     Critical,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl std::str::FromStr for SecretSeverity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(SecretSeverity::Low),
+            "medium" => Ok(SecretSeverity::Medium),
+            "high" => Ok(SecretSeverity::High),
+            "critical" => Ok(SecretSeverity::Critical),
+            other => Err(anyhow!("Unknown severity {}", other)),
+        }
+    }
+}
+
+// The variant name, as stored in the database and shown in the GUI's pick lists
+impl std::fmt::Display for SecretSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(&format!("{:?}", self))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub enum SecretCategory {
     CloudProvider,
     Database,
@@ -46,6 +73,31 @@ pub enum SecretCategory {
     Other,
 }
 
+impl std::str::FromStr for SecretCategory {
+    type Err = anyhow::Error;
+
+    /// Accepts the variant name in any case, with or without `_`/`-` separators
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().replace(['_', '-', ' '], "").as_str() {
+            "cloudprovider" | "cloud" => Ok(SecretCategory::CloudProvider),
+            "database" => Ok(SecretCategory::Database),
+            "apikey" => Ok(SecretCategory::ApiKey),
+            "certificate" => Ok(SecretCategory::Certificate),
+            "password" => Ok(SecretCategory::Password),
+            "token" => Ok(SecretCategory::Token),
+            "webhook" => Ok(SecretCategory::Webhook),
+            "other" => Ok(SecretCategory::Other),
+            _ => Err(anyhow!("Unknown category {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for SecretCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(&format!("{:?}", self))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecretMatch {
     pub detector_name: String,
@@ -54,6 +106,18 @@ pub struct SecretMatch {
     pub end_position: usize,
     pub line_number: Option<usize>,
     pub filename: Option<String>,
+    /// Commit the match was found in, when scanning git history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_sha: Option<String>,
+    /// `Name <email>` of that commit's author
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_author: Option<String>,
+    /// Matched an allowlist rule: still recorded, but kept out of alerts and active counts
+    #[serde(default)]
+    pub suppressed: bool,
+    /// `kind:pattern` of the allowlist rule that suppressed it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suppressed_by: Option<String>,
     pub entropy: f64,
     pub severity: SecretSeverity,
     pub category: SecretCategory,
@@ -84,6 +148,11 @@ impl SecretScanner {
             detectors: Vec::new(),
             patterns: HashMap::new(),
             entropy_threshold: 4.5,
+            allowlist: Allowlist::default(),
+            fingerprinter: Fingerprinter::from_env().unwrap_or_else(|e| {
+                warn!("Failed to load the fingerprint pepper, finding hashes will not match other runs: {}", e);
+                Fingerprinter::ephemeral().expect("the system random generator is available")
+            }),
         };
         
         scanner.load_built_in_detectors();
@@ -408,25 +477,31 @@ impl SecretScanner {
                             }
                         }
 
-                        // Create hash of the match
-                        let mut hasher = Sha256::new();
-                        hasher.update(&matched_text);
-                        let hash = hex::encode(hasher.finalize());
+                        let hash = self.fingerprinter.secret_hash(&matched_text);
 
-                        matches.push(SecretMatch {
+                        let mut secret = SecretMatch {
                             detector_name: detector.name.clone(),
                             matched_text,
                             start_position: start,
                             end_position: end,
                             line_number: Some(line_number),
                             filename: filename.map(|s| s.to_string()),
+                            commit_sha: None,
+                            commit_author: None,
+                            suppressed: false,
+                            suppressed_by: None,
                             entropy,
                             severity: detector.severity.clone(),
                             category: detector.category.clone(),
                             context,
                             verified: false,
                             hash,
-                        });
+                        };
+                        if let Some(rule) = self.allowlist.find(&secret) {
+                            secret.suppressed = true;
+                            secret.suppressed_by = Some(rule.label());
+                        }
+                        matches.push(secret);
                     }
                 }
             }
@@ -519,11 +594,58 @@ impl SecretScanner {
         Ok(())
     }
 
+    /// Add a detector, replacing any detector with the same name. Returns whether one was replaced.
+    pub fn upsert_detector(&mut self, detector: SecretDetector) -> Result<bool> {
+        let regex = Regex::new(&detector.pattern)
+            .map_err(|e| anyhow!("Invalid regex pattern: {}", e))?;
+
+        self.patterns.insert(detector.name.clone(), regex);
+        match self.detectors.iter_mut().find(|d| d.name == detector.name) {
+            Some(existing) => {
+                *existing = detector;
+                Ok(true)
+            }
+            None => {
+                self.detectors.push(detector);
+                Ok(false)
+            }
+        }
+    }
+
     /// Get all detector names
     pub fn get_detector_names(&self) -> Vec<String> {
         self.detectors.iter().map(|d| d.name.clone()).collect()
     }
 
+    /// Look up a detector by name
+    pub fn get_detector(&self, name: &str) -> Option<&SecretDetector> {
+        self.detectors.iter().find(|d| d.name == name)
+    }
+
+    /// Flag matches of these rules as suppressed instead of active
+    pub fn set_allowlist(&mut self, allowlist: Allowlist) {
+        self.allowlist = allowlist;
+    }
+
+    pub fn allowlist(&self) -> &Allowlist {
+        &self.allowlist
+    }
+
+    /// Key finding hashes with this fingerprinter instead of the one `new` loads
+    pub fn set_fingerprinter(&mut self, fingerprinter: Fingerprinter) {
+        self.fingerprinter = fingerprinter;
+    }
+
+    /// The keyed `secret_hash` of a matched value
+    pub fn secret_hash(&self, matched_text: &str) -> String {
+        self.fingerprinter.secret_hash(matched_text)
+    }
+
+    /// Rewrap a SHA-256 `secret_hash` from an earlier release; other values are unchanged
+    pub fn upgrade_secret_hash(&self, hash: &str) -> String {
+        self.fingerprinter.rewrap_legacy_secret_hash(hash).unwrap_or_else(|| hash.to_string())
+    }
+
     /// Set entropy threshold
     pub fn set_entropy_threshold(&mut self, threshold: f64) {
         self.entropy_threshold = threshold;
@@ -652,6 +774,10 @@ index 1234567..abcdefg 100644
                 end_position: 9,
                 line_number: Some(1),
                 filename: None,
+                commit_sha: None,
+                commit_author: None,
+                suppressed: false,
+                suppressed_by: None,
                 entropy: 3.5,
                 severity: SecretSeverity::Medium,
                 category: SecretCategory::ApiKey,
@@ -666,6 +792,10 @@ index 1234567..abcdefg 100644
                 end_position: 19,
                 line_number: Some(2),
                 filename: None,
+                commit_sha: None,
+                commit_author: None,
+                suppressed: false,
+                suppressed_by: None,
                 entropy: 3.5,
                 severity: SecretSeverity::Medium,
                 category: SecretCategory::ApiKey,
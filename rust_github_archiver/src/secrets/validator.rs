@@ -6,6 +6,7 @@ use serde_json::Value;
 use std::time::Duration;
 use tracing::{info, warn, error, debug};
 use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use crate::core::{HttpConfig, http_client::build_client_with, offline};
 use crate::secrets::scanner::{SecretMatch, SecretSeverity};
 
 /// Secret validator for verifying if secrets are active
@@ -27,15 +28,20 @@ pub struct ValidationResult {
 impl SecretValidator {
     /// Create a new secret validator
     pub async fn new() -> Result<Self> {
-        let http_client = HttpClient::builder()
-            .timeout(Duration::from_secs(30))
-            .user_agent("GitArchiver-SecretValidator/1.0")
-            .build()
-            .map_err(|e| anyhow!("Failed to create HTTP client: {}", e))?;
-
-        // Try to load AWS config (may fail if not configured)
-        let aws_config = match aws_config::load_defaults(BehaviorVersion::latest()).await {
-            config => Some(config),
+        let http_client = build_client_with(
+            &HttpConfig::default(),
+            Some(Duration::from_secs(30)),
+            Some("GitArchiver-SecretValidator/1.0"),
+        )?;
+
+        // Try to load AWS config (may fail if not configured). Loading probes the instance
+        // metadata endpoint, so it is skipped entirely in offline mode.
+        let aws_config = if offline::policy().enabled() {
+            None
+        } else {
+            match aws_config::load_defaults(BehaviorVersion::latest()).await {
+                config => Some(config),
+            }
         };
 
         Ok(Self {
@@ -46,6 +52,8 @@ impl SecretValidator {
 
     /// Validate a secret match
     pub async fn validate_secret(&self, secret_match: &SecretMatch) -> Result<ValidationResult> {
+        // Every validator calls the issuing provider's public API
+        offline::policy().ensure_online("Live secret validation")?;
         info!("Validating secret: {}", secret_match.detector_name);
 
         let result = match secret_match.detector_name.as_str() {
@@ -576,6 +584,10 @@ mod tests {
             end_position: matched_text.len(),
             line_number: Some(1),
             filename: Some("test.txt".to_string()),
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
             entropy: 4.0,
             severity: SecretSeverity::High,
             category: SecretCategory::ApiKey,
@@ -0,0 +1,237 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::multipart::{Form, Part};
+use reqwest::Client;
+use serde_json::json;
+use tracing::debug;
+
+use super::{severity_label, AnnotationUpdate, FindingSink};
+use crate::core::{build_client, HttpConfig};
+use crate::secrets::SecretMatch;
+
+/// Pushes findings to DefectDojo's `import-scan` endpoint as a Generic Findings Import report
+pub struct DefectDojoSink {
+    client: Client,
+    api_url: String,
+    import_url: String,
+    api_key: String,
+    engagement_id: u64,
+    test_title: String,
+    minimum_severity: String,
+}
+
+impl DefectDojoSink {
+    pub fn new(
+        http: &HttpConfig,
+        url: &str,
+        api_key: &str,
+        engagement_id: u64,
+        test_title: &str,
+        minimum_severity: Option<&str>,
+    ) -> Result<Self> {
+        let api_url = format!("{}/api/v2", url.trim_end_matches('/'));
+        Ok(Self {
+            client: build_client(http)?,
+            import_url: format!("{}/import-scan/", api_url),
+            api_url,
+            api_key: api_key.to_string(),
+            engagement_id,
+            test_title: test_title.to_string(),
+            minimum_severity: minimum_severity.unwrap_or("Info").to_string(),
+        })
+    }
+
+    /// Map findings onto DefectDojo's generic findings schema. Secrets arrive already redacted.
+    pub fn build_report(findings: &[SecretMatch]) -> serde_json::Value {
+        let date = Utc::now().format("%Y-%m-%d").to_string();
+
+        let findings: Vec<_> = findings
+            .iter()
+            .map(|f| {
+                json!({
+                    "title": format!("{} in {}", f.detector_name, f.filename.as_deref().unwrap_or("unknown file")),
+                    "description": format!(
+                        "Detector: {}\nCategory: {:?}\nSecret: {}\nEntropy: {:.2}",
+                        f.detector_name, f.category, f.matched_text, f.entropy
+                    ),
+                    "severity": severity_label(&f.severity),
+                    "date": date,
+                    "file_path": f.filename,
+                    "line": f.line_number,
+                    "unique_id_from_tool": f.hash,
+                    "vuln_id_from_tool": f.detector_name,
+                    "verified": f.verified,
+                    "static_finding": true,
+                    "dynamic_finding": false,
+                })
+            })
+            .collect();
+
+        json!({ "findings": findings })
+    }
+
+    /// Note entry as shown on the DefectDojo finding
+    pub fn note_entry(author: &str, created_at: &str, body: &str) -> String {
+        format!("**{}** ({}):\n\n{}", author, created_at, body)
+    }
+
+    /// DefectDojo id of the finding imported for `secret_hash` into our engagement
+    async fn find_finding_id(&self, secret_hash: &str) -> Result<u64> {
+        let response = self.client
+            .get(format!("{}/findings/", self.api_url))
+            .header("Authorization", format!("Token {}", self.api_key))
+            .query(&[
+                ("unique_id_from_tool", secret_hash.to_string()),
+                ("test__engagement", self.engagement_id.to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let body: serde_json::Value = response.json().await?;
+        body["results"]
+            .as_array()
+            .and_then(|results| results.first())
+            .and_then(|finding| finding["id"].as_u64())
+            .ok_or_else(|| anyhow!("Finding {} has not been imported into DefectDojo", secret_hash))
+    }
+}
+
+#[async_trait]
+impl FindingSink for DefectDojoSink {
+    fn name(&self) -> &str {
+        "defectdojo"
+    }
+
+    async fn send_batch(&self, findings: &[SecretMatch]) -> Result<()> {
+        let report = serde_json::to_vec(&Self::build_report(findings))?;
+
+        let form = Form::new()
+            .text("scan_type", "Generic Findings Import")
+            .text("engagement", self.engagement_id.to_string())
+            .text("test_title", self.test_title.clone())
+            .text("minimum_severity", self.minimum_severity.clone())
+            .text("active", "true")
+            .text("verified", "false")
+            .text("close_old_findings", "false")
+            .part("file", Part::bytes(report).file_name("findings.json").mime_str("application/json")?);
+
+        let response = self.client
+            .post(&self.import_url)
+            .header("Authorization", format!("Token {}", self.api_key))
+            .multipart(form)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("DefectDojo import returned {}: {}", status, body));
+        }
+
+        debug!("Imported {} findings into DefectDojo engagement {}", findings.len(), self.engagement_id);
+        Ok(())
+    }
+
+    async fn send_annotations(&self, update: &AnnotationUpdate) -> Result<()> {
+        let finding_id = self.find_finding_id(&update.secret_hash).await?;
+        let finding_url = format!("{}/findings/{}", self.api_url, finding_id);
+
+        for note in &update.notes {
+            self.client
+                .post(format!("{}/notes/", finding_url))
+                .header("Authorization", format!("Token {}", self.api_key))
+                .json(&json!({
+                    "entry": Self::note_entry(&note.author, &note.created_at, &note.body),
+                    "private": false,
+                }))
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+
+        for (attachment, contents) in &update.files {
+            let form = Form::new()
+                .text("title", attachment.filename.clone())
+                .part(
+                    "file",
+                    Part::bytes(contents.clone())
+                        .file_name(attachment.filename.clone())
+                        .mime_str(&attachment.content_type)?,
+                );
+            self.client
+                .post(format!("{}/files/", finding_url))
+                .header("Authorization", format!("Token {}", self.api_key))
+                .multipart(form)
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+
+        debug!(
+            "Added {} notes and {} files to DefectDojo finding {}",
+            update.notes.len(),
+            update.files.len(),
+            finding_id
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::{SecretCategory, SecretSeverity};
+
+    fn finding(hash: &str, filename: Option<&str>) -> SecretMatch {
+        SecretMatch {
+            detector_name: "AWS Access Key ID".to_string(),
+            matched_text: "AKIA********".to_string(),
+            start_position: 0,
+            end_position: 20,
+            line_number: filename.map(|_| 4),
+            filename: filename.map(str::to_string),
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
+            entropy: 3.7,
+            severity: SecretSeverity::Critical,
+            category: SecretCategory::CloudProvider,
+            context: String::new(),
+            verified: true,
+            hash: hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_report_uses_generic_findings_schema() {
+        let report = DefectDojoSink::build_report(&[finding("abc", Some(".env")), finding("def", None)]);
+        let findings = report["findings"].as_array().unwrap();
+        assert_eq!(findings.len(), 2);
+
+        let first = &findings[0];
+        assert_eq!(first["title"], "AWS Access Key ID in .env");
+        assert_eq!(first["severity"], severity_label(&SecretSeverity::Critical));
+        assert_eq!(first["file_path"], ".env");
+        assert_eq!(first["line"], 4);
+        assert_eq!(first["unique_id_from_tool"], "abc");
+        assert_eq!(first["vuln_id_from_tool"], "AWS Access Key ID");
+        assert_eq!((first["verified"].as_bool(), first["static_finding"].as_bool()), (Some(true), Some(true)));
+        assert!(first["description"].as_str().unwrap().contains("Secret: AKIA********"));
+        assert_eq!(first["date"], Utc::now().format("%Y-%m-%d").to_string());
+
+        assert_eq!(findings[1]["title"], "AWS Access Key ID in unknown file");
+        assert!(findings[1]["file_path"].is_null() && findings[1]["line"].is_null());
+        assert_eq!(DefectDojoSink::build_report(&[]), json!({ "findings": [] }));
+    }
+
+    #[test]
+    fn test_note_entry_names_author_and_time() {
+        assert_eq!(
+            DefectDojoSink::note_entry("anna", "2024-03-01 10:00:00", "Key rotated"),
+            "**anna** (2024-03-01 10:00:00):\n\nKey rotated"
+        );
+    }
+}
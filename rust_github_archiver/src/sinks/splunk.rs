@@ -0,0 +1,168 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::Client;
+use serde_json::json;
+use tracing::debug;
+
+use super::{severity_label, FindingSink};
+use crate::core::{build_client, send_with_retry, HttpConfig, RetryPolicy};
+use crate::secrets::SecretMatch;
+
+/// Sends findings to a Splunk HTTP Event Collector, one HEC event per finding
+pub struct SplunkHecSink {
+    client: Client,
+    endpoint: String,
+    token: String,
+    index: Option<String>,
+    source: String,
+    sourcetype: String,
+    retry_policy: RetryPolicy,
+}
+
+impl SplunkHecSink {
+    pub fn new(
+        http: &HttpConfig,
+        url: &str,
+        token: &str,
+        index: Option<String>,
+        source: &str,
+        sourcetype: &str,
+    ) -> Result<Self> {
+        Ok(Self {
+            client: build_client(http)?,
+            endpoint: format!("{}/services/collector/event", url.trim_end_matches('/')),
+            token: token.to_string(),
+            index,
+            source: source.to_string(),
+            sourcetype: sourcetype.to_string(),
+            retry_policy: RetryPolicy::from_config(http),
+        })
+    }
+
+    /// HEC accepts a batch as concatenated JSON event objects
+    pub fn build_payload(&self, findings: &[SecretMatch]) -> Result<String> {
+        let time = Utc::now().timestamp();
+        let mut payload = String::new();
+
+        for finding in findings {
+            let mut event = json!({
+                "time": time,
+                "source": self.source,
+                "sourcetype": self.sourcetype,
+                "event": {
+                    "finding_hash": finding.hash,
+                    "detector": finding.detector_name,
+                    "severity": severity_label(&finding.severity),
+                    "category": format!("{:?}", finding.category),
+                    "file": finding.filename,
+                    "line": finding.line_number,
+                    "entropy": finding.entropy,
+                    "verified": finding.verified,
+                    "secret_preview": finding.matched_text,
+                },
+            });
+            if let Some(index) = &self.index {
+                event["index"] = json!(index);
+            }
+            payload.push_str(&serde_json::to_string(&event)?);
+        }
+
+        Ok(payload)
+    }
+}
+
+#[async_trait]
+impl FindingSink for SplunkHecSink {
+    fn name(&self) -> &str {
+        "splunk_hec"
+    }
+
+    async fn send_batch(&self, findings: &[SecretMatch]) -> Result<()> {
+        let request = self.client
+            .post(&self.endpoint)
+            .header("Authorization", format!("Splunk {}", self.token))
+            .header("Content-Type", "application/json")
+            .body(self.build_payload(findings)?);
+
+        let response = send_with_retry(request, &self.retry_policy).await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Splunk HEC returned {}: {}", status, body));
+        }
+
+        debug!("Sent {} findings to Splunk HEC", findings.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::{SecretCategory, SecretSeverity};
+
+    fn finding(hash: &str) -> SecretMatch {
+        SecretMatch {
+            detector_name: "GitHub Personal Access Token".to_string(),
+            matched_text: "ghp_****".to_string(),
+            start_position: 0,
+            end_position: 40,
+            line_number: Some(7),
+            filename: Some("deploy.sh".to_string()),
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
+            entropy: 4.5,
+            severity: SecretSeverity::High,
+            category: SecretCategory::Token,
+            context: String::new(),
+            verified: false,
+            hash: hash.to_string(),
+        }
+    }
+
+    fn sink(index: Option<&str>) -> SplunkHecSink {
+        SplunkHecSink::new(
+            &HttpConfig::default(),
+            "https://splunk.example.com:8088/",
+            "token",
+            index.map(str::to_string),
+            "gitarchiver",
+            "gitarchiver:finding",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_payload_is_one_hec_event_per_finding() {
+        let sink = sink(Some("security"));
+        assert_eq!(sink.endpoint, "https://splunk.example.com:8088/services/collector/event");
+
+        let payload = sink.build_payload(&[finding("abc"), finding("def")]).unwrap();
+        let events: Vec<serde_json::Value> =
+            serde_json::Deserializer::from_str(&payload).into_iter().collect::<Result<_, _>>().unwrap();
+        assert_eq!(events.len(), 2);
+
+        let event = &events[0];
+        assert_eq!((event["source"].as_str(), event["sourcetype"].as_str()), (Some("gitarchiver"), Some("gitarchiver:finding")));
+        assert_eq!(event["index"], "security");
+        assert!(event["time"].is_i64());
+        assert_eq!(event["event"]["finding_hash"], "abc");
+        assert_eq!(event["event"]["severity"], severity_label(&SecretSeverity::High));
+        assert_eq!(event["event"]["category"], "Token");
+        assert_eq!((event["event"]["file"].as_str(), event["event"]["line"].as_u64()), (Some("deploy.sh"), Some(7)));
+        assert_eq!(event["event"]["secret_preview"], "ghp_****");
+        assert_eq!(events[1]["event"]["finding_hash"], "def");
+    }
+
+    #[test]
+    fn test_payload_without_index_or_findings() {
+        let sink = sink(None);
+        let payload = sink.build_payload(&[finding("abc")]).unwrap();
+        let event: serde_json::Value = serde_json::from_str(&payload).unwrap();
+        assert!(event.get("index").is_none());
+        assert!(sink.build_payload(&[]).unwrap().is_empty());
+    }
+}
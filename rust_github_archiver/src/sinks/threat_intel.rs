@@ -0,0 +1,279 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tracing::{debug, info};
+use uuid::{Builder, Uuid};
+
+use super::{severity_label, FindingSink};
+use crate::core::{build_client, send_with_retry, HttpConfig, RetryPolicy};
+use crate::performance::SecretRecord;
+use crate::secrets::SecretMatch;
+
+/// Fixed identity used as `created_by_ref` on every STIX object we produce
+const PRODUCER_IDENTITY: &str = "identity--6f1c3f4e-2b7a-5c3e-9d1a-4a8b2c0e7f10";
+
+/// Minimal view of a validated finding shared by the STIX and MISP exporters.
+/// Only the secret hash leaves the system, never the secret itself.
+#[derive(Debug, Clone)]
+pub struct IntelFinding {
+    pub secret_hash: String,
+    pub detector_name: String,
+    pub severity: String,
+    pub filename: Option<String>,
+    pub first_seen: DateTime<Utc>,
+}
+
+impl From<&SecretMatch> for IntelFinding {
+    fn from(finding: &SecretMatch) -> Self {
+        Self {
+            secret_hash: finding.hash.clone(),
+            detector_name: finding.detector_name.clone(),
+            severity: severity_label(&finding.severity).to_string(),
+            filename: finding.filename.clone(),
+            first_seen: Utc::now(),
+        }
+    }
+}
+
+impl From<&SecretRecord> for IntelFinding {
+    fn from(record: &SecretRecord) -> Self {
+        Self {
+            secret_hash: record.secret_hash.clone(),
+            detector_name: record.detector_name.clone(),
+            severity: severity_label(&record.severity).to_string(),
+            filename: record.filename.clone(),
+            first_seen: chrono::NaiveDateTime::parse_from_str(&record.created_at, "%Y-%m-%d %H:%M:%S")
+                .map(|dt| dt.and_utc())
+                .unwrap_or_else(|_| Utc::now()),
+        }
+    }
+}
+
+/// Deterministic STIX id so re-exports of the same finding produce the same object
+fn stix_id(object_type: &str, seed: &str) -> String {
+    let digest = Sha256::digest(format!("{}:{}", object_type, seed).as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    let uuid: Uuid = Builder::from_sha1_bytes(bytes).into_uuid();
+    format!("{}--{}", object_type, uuid)
+}
+
+/// Build a STIX 2.1 bundle with one `indicator` per validated finding
+pub fn build_stix_bundle(findings: &[IntelFinding]) -> serde_json::Value {
+    let now = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+
+    let mut objects = vec![json!({
+        "type": "identity",
+        "spec_version": "2.1",
+        "id": PRODUCER_IDENTITY,
+        "created": now,
+        "modified": now,
+        "name": "GitHub Secret Hunter",
+        "identity_class": "system",
+    })];
+
+    for finding in findings {
+        let valid_from = finding.first_seen.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+        objects.push(json!({
+            "type": "indicator",
+            "spec_version": "2.1",
+            "id": stix_id("indicator", &finding.secret_hash),
+            "created_by_ref": PRODUCER_IDENTITY,
+            "created": now,
+            "modified": now,
+            "name": format!("Leaked {}", finding.detector_name),
+            "description": format!(
+                "Validated {} credential exposed in a public GitHub commit{}",
+                finding.detector_name,
+                finding.filename.as_ref().map(|f| format!(" ({})", f)).unwrap_or_default()
+            ),
+            "indicator_types": ["compromised"],
+            "pattern": format!(
+                "[x-leaked-secret:sha256 = '{}' AND x-leaked-secret:detector = '{}']",
+                finding.secret_hash,
+                finding.detector_name.replace('\'', "\\'")
+            ),
+            "pattern_type": "stix",
+            "valid_from": valid_from,
+            "labels": [format!("severity:{}", finding.severity.to_lowercase())],
+        }));
+    }
+
+    json!({
+        "type": "bundle",
+        "id": format!("bundle--{}", Uuid::new_v4()),
+        "objects": objects,
+    })
+}
+
+fn misp_threat_level(severity: &str) -> u8 {
+    // MISP: 1 = High, 2 = Medium, 3 = Low, 4 = Undefined
+    match severity {
+        "Critical" | "High" => 1,
+        "Medium" => 2,
+        "Low" => 3,
+        _ => 4,
+    }
+}
+
+/// Build a MISP event carrying one attribute per validated finding
+pub fn build_misp_event(findings: &[IntelFinding], distribution: u8) -> serde_json::Value {
+    let threat_level = findings
+        .iter()
+        .map(|f| misp_threat_level(&f.severity))
+        .min()
+        .unwrap_or(4);
+
+    let attributes: Vec<_> = findings
+        .iter()
+        .map(|f| {
+            json!({
+                "type": "sha256",
+                "category": "Other",
+                "value": f.secret_hash,
+                "comment": format!(
+                    "Leaked {}{}",
+                    f.detector_name,
+                    f.filename.as_ref().map(|file| format!(" in {}", file)).unwrap_or_default()
+                ),
+                "to_ids": false,
+            })
+        })
+        .collect();
+
+    json!({
+        "Event": {
+            "info": format!("GitHub Secret Hunter: {} leaked credentials", findings.len()),
+            "date": Utc::now().format("%Y-%m-%d").to_string(),
+            "distribution": distribution,
+            "threat_level_id": threat_level,
+            "analysis": 2,
+            "Attribute": attributes,
+            "Tag": [{ "name": "tlp:amber" }, { "name": "leaked-credentials" }],
+        }
+    })
+}
+
+/// Pushes validated findings to a MISP instance as events
+pub struct MispSink {
+    client: Client,
+    events_url: String,
+    api_key: String,
+    distribution: u8,
+    retry_policy: RetryPolicy,
+}
+
+impl MispSink {
+    pub fn new(http: &HttpConfig, url: &str, api_key: &str, distribution: u8) -> Result<Self> {
+        Ok(Self {
+            client: build_client(http)?,
+            events_url: format!("{}/events", url.trim_end_matches('/')),
+            api_key: api_key.to_string(),
+            distribution,
+            retry_policy: RetryPolicy::from_config(http),
+        })
+    }
+}
+
+#[async_trait]
+impl FindingSink for MispSink {
+    fn name(&self) -> &str {
+        "misp"
+    }
+
+    async fn send_batch(&self, findings: &[SecretMatch]) -> Result<()> {
+        let validated = validated_only(findings);
+        if validated.is_empty() {
+            debug!("No validated findings in batch, skipping MISP event");
+            return Ok(());
+        }
+
+        let request = self.client
+            .post(&self.events_url)
+            .header("Authorization", &self.api_key)
+            .header("Accept", "application/json")
+            .json(&build_misp_event(&validated, self.distribution));
+
+        let response = send_with_retry(request, &self.retry_policy).await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("MISP returned {}: {}", status, body));
+        }
+
+        info!("Published MISP event with {} validated findings", validated.len());
+        Ok(())
+    }
+}
+
+/// Only validated findings are worth sharing as intelligence
+pub fn validated_only(findings: &[SecretMatch]) -> Vec<IntelFinding> {
+    findings.iter().filter(|f| f.verified).map(IntelFinding::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::SecretSeverity;
+
+    fn finding(hash: &str, severity: &str) -> IntelFinding {
+        IntelFinding {
+            secret_hash: hash.to_string(),
+            detector_name: "AWS Access Key ID".to_string(),
+            severity: severity.to_string(),
+            filename: Some("deploy.sh".to_string()),
+            first_seen: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_stix_bundle_structure() {
+        let bundle = build_stix_bundle(&[finding("aa11", "High"), finding("bb22", "Critical")]);
+        let objects = bundle["objects"].as_array().unwrap();
+
+        assert_eq!(bundle["type"], "bundle");
+        assert_eq!(objects.len(), 3);
+        assert_eq!(objects[1]["type"], "indicator");
+        assert_eq!(objects[1]["pattern_type"], "stix");
+        assert!(objects[1]["pattern"].as_str().unwrap().contains("aa11"));
+    }
+
+    #[test]
+    fn test_stix_ids_are_deterministic() {
+        assert_eq!(stix_id("indicator", "aa11"), stix_id("indicator", "aa11"));
+        assert_ne!(stix_id("indicator", "aa11"), stix_id("indicator", "bb22"));
+    }
+
+    #[test]
+    fn test_misp_event_threat_level() {
+        let event = build_misp_event(&[finding("aa11", "Medium"), finding("bb22", "Critical")], 0);
+        assert_eq!(event["Event"]["threat_level_id"], 1);
+        assert_eq!(event["Event"]["Attribute"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_unverified_findings_are_excluded() {
+        let unverified = SecretMatch {
+            detector_name: "JWT Token".to_string(),
+            matched_text: "eyJ...".to_string(),
+            start_position: 0,
+            end_position: 6,
+            line_number: None,
+            filename: None,
+            commit_sha: None,
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
+            entropy: 3.0,
+            severity: SecretSeverity::Medium,
+            category: crate::secrets::SecretCategory::Token,
+            context: String::new(),
+            verified: false,
+            hash: "cc33".to_string(),
+        };
+        assert!(validated_only(&[unverified]).is_empty());
+    }
+}
@@ -0,0 +1,381 @@
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use super::{severity_label, severity_rank, RedactionProfile};
+#[cfg(feature = "ai")]
+use crate::ai::TriageResult;
+use crate::core::{build_client, send_with_retry, HttpConfig, RetryPolicy, SinksConfig, TicketTarget};
+use crate::performance::SecretDatabase;
+use crate::secrets::{rotation_actions, SecretMatch, SecretSeverity};
+
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// How a ticket body is marked up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TicketMarkup {
+    /// Jira wiki markup
+    Jira,
+    /// GitHub Flavored Markdown
+    Markdown,
+}
+
+pub fn ticket_title(finding: &SecretMatch) -> String {
+    format!(
+        "[{}] {} in {}",
+        severity_label(&finding.severity),
+        finding.detector_name,
+        finding.filename.as_deref().unwrap_or("unknown file")
+    )
+}
+
+/// What AI triage said about a finding, as far as its ticket shows it
+#[derive(Debug, Clone, Default)]
+pub struct TicketTriage {
+    pub secret_hash: String,
+    /// `Immediate`, `High`, `Medium`, `Low` or `Monitor`
+    pub revocation_priority: String,
+    pub impact_score: f64,
+    pub analysis: String,
+    pub suggested_actions: Vec<String>,
+}
+
+#[cfg(feature = "ai")]
+impl From<&TriageResult> for TicketTriage {
+    fn from(triage: &TriageResult) -> Self {
+        Self {
+            secret_hash: triage.secret_hash.clone(),
+            revocation_priority: format!("{:?}", triage.revocation_priority),
+            impact_score: triage.impact_score,
+            analysis: triage.analysis.clone(),
+            suggested_actions: triage.suggested_actions.clone(),
+        }
+    }
+}
+
+/// Ticket description for an already redacted finding, with the triage's suggested
+/// remediation steps when there was one
+pub fn ticket_body(finding: &SecretMatch, triage: Option<&TicketTriage>, markup: TicketMarkup) -> String {
+    let (heading, code_open, code_close) = match markup {
+        TicketMarkup::Jira => ("h3. ", "{noformat}", "{noformat}"),
+        TicketMarkup::Markdown => ("### ", "```", "```"),
+    };
+    let mut lines = vec![
+        format!("A verified {} secret was found by GitArchiver.", severity_label(&finding.severity)),
+        String::new(),
+        format!("* Detector: {}", finding.detector_name),
+        format!("* Category: {:?}", finding.category),
+        format!(
+            "* File: {}{}",
+            finding.filename.as_deref().unwrap_or("unknown"),
+            finding.line_number.map(|line| format!(":{}", line)).unwrap_or_default()
+        ),
+    ];
+    if let Some(commit) = &finding.commit_sha {
+        lines.push(format!("* Commit: {}", commit));
+    }
+    lines.push(format!("* Secret: {}", finding.matched_text));
+    lines.push(format!("* Finding: {}", finding.hash));
+    if !finding.context.trim().is_empty() {
+        lines.extend([String::new(), format!("{}Context", heading), code_open.to_string()]);
+        lines.push(finding.context.trim_end().to_string());
+        lines.push(code_close.to_string());
+    }
+
+    lines.extend([String::new(), format!("{}Remediation", heading)]);
+    let suggested = triage.map(|t| t.suggested_actions.clone()).unwrap_or_default();
+    let steps: Vec<String> = if suggested.is_empty() {
+        [
+            "Revoke or rotate the secret with its provider.",
+            "Check the provider's access logs for use since the commit.",
+            "Remove the secret from the repository history.",
+        ]
+        .into_iter()
+        .map(String::from)
+        .chain(rotation_actions(finding, None))
+        .collect()
+    } else {
+        suggested
+    };
+    for (number, step) in steps.iter().enumerate() {
+        // A step with more lines, such as a rotation script, shows them as a code block
+        let (step, code) = step.split_once('\n').unwrap_or((step.as_str(), ""));
+        lines.push(match markup {
+            TicketMarkup::Jira => format!("# {}", step),
+            TicketMarkup::Markdown => format!("{}. {}", number + 1, step),
+        });
+        if !code.is_empty() {
+            lines.extend([code_open.to_string(), code.to_string(), code_close.to_string()]);
+        }
+    }
+    if let Some(triage) = triage {
+        lines.push(String::new());
+        lines.push(format!(
+            "Triage: {} revocation priority, impact {:.2}.",
+            triage.revocation_priority, triage.impact_score
+        ));
+        if !triage.analysis.is_empty() {
+            lines.push(triage.analysis.clone());
+        }
+    }
+    lines.join("\n")
+}
+
+enum Tracker {
+    Jira { url: String, email: String, api_token: String, project: String, issue_type: String },
+    GitHub { api_url: String, repository: String, token: String, labels: Vec<String> },
+}
+
+/// One Jira project or GitHub repository tickets are opened in
+struct TicketTracker {
+    tracker: Tracker,
+    minimum_severity: SecretSeverity,
+    client: Client,
+    retry_policy: RetryPolicy,
+}
+
+impl TicketTracker {
+    fn from_target(target: &TicketTarget, http: &HttpConfig) -> Result<Self> {
+        let (tracker, minimum_severity) = match target {
+            TicketTarget::Jira { url, email, api_token, project, issue_type, minimum_severity } => (
+                Tracker::Jira {
+                    url: url.trim_end_matches('/').to_string(),
+                    email: email.clone(),
+                    api_token: api_token.clone(),
+                    project: project.clone(),
+                    issue_type: issue_type.clone(),
+                },
+                minimum_severity,
+            ),
+            TicketTarget::GithubIssues { repository, token, labels, api_url, minimum_severity } => (
+                Tracker::GitHub {
+                    api_url: api_url.as_deref().unwrap_or(GITHUB_API_URL).trim_end_matches('/').to_string(),
+                    repository: repository.clone(),
+                    token: token.clone(),
+                    labels: labels.clone(),
+                },
+                minimum_severity,
+            ),
+        };
+        Ok(Self {
+            tracker,
+            minimum_severity: minimum_severity.as_deref().unwrap_or("high").parse()?,
+            client: build_client(http)?,
+            retry_policy: RetryPolicy::from_config(http),
+        })
+    }
+
+    fn name(&self) -> String {
+        match &self.tracker {
+            Tracker::Jira { project, .. } => format!("jira:{}", project),
+            Tracker::GitHub { repository, .. } => format!("github:{}", repository),
+        }
+    }
+
+    fn wants(&self, finding: &SecretMatch) -> bool {
+        finding.verified && !finding.suppressed && severity_rank(&finding.severity) >= severity_rank(&self.minimum_severity)
+    }
+
+    /// Open a ticket and return its URL
+    async fn open(&self, finding: &SecretMatch, triage: Option<&TicketTriage>) -> Result<String> {
+        let labels = vec!["gitarchiver".to_string(), severity_label(&finding.severity).to_lowercase()];
+        let (request, name) = match &self.tracker {
+            Tracker::Jira { url, email, api_token, project, issue_type } => {
+                let body = json!({
+                    "fields": {
+                        "project": {"key": project},
+                        "issuetype": {"name": issue_type},
+                        "summary": ticket_title(finding),
+                        "description": ticket_body(finding, triage, TicketMarkup::Jira),
+                        "labels": labels,
+                    }
+                });
+                (self.client.post(format!("{}/rest/api/2/issue", url)).basic_auth(email, Some(api_token)).json(&body), "Jira")
+            }
+            Tracker::GitHub { api_url, repository, token, labels: extra } => {
+                let body = json!({
+                    "title": ticket_title(finding),
+                    "body": ticket_body(finding, triage, TicketMarkup::Markdown),
+                    "labels": labels.iter().chain(extra).collect::<Vec<_>>(),
+                });
+                let request = self
+                    .client
+                    .post(format!("{}/repos/{}/issues", api_url, repository))
+                    .bearer_auth(token)
+                    .header("Accept", "application/vnd.github+json")
+                    .json(&body);
+                (request, "GitHub")
+            }
+        };
+        let response = send_with_retry(request, &self.retry_policy).await?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(anyhow!("{} returned {}: {}", name, status, body));
+        }
+        match &self.tracker {
+            Tracker::Jira { url, .. } => body["key"]
+                .as_str()
+                .map(|key| format!("{}/browse/{}", url, key))
+                .ok_or_else(|| anyhow!("Jira did not return an issue key")),
+            Tracker::GitHub { .. } => {
+                body["html_url"].as_str().map(str::to_string).ok_or_else(|| anyhow!("GitHub did not return an issue URL"))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TicketReport {
+    pub opened: usize,
+    pub errors: Vec<String>,
+}
+
+/// Opens one ticket per verified finding at or above each tracker's severity threshold and
+/// stores its URL with the finding. Findings that already have a ticket are skipped.
+pub struct TicketFiler {
+    trackers: Vec<TicketTracker>,
+    redaction: RedactionProfile,
+}
+
+impl TicketFiler {
+    /// Trackers from the `sinks.tickets` section; ticket bodies use the sinks' redaction role
+    pub fn from_config(config: &SinksConfig, http: &HttpConfig) -> Result<Self> {
+        let trackers = config
+            .tickets
+            .iter()
+            .map(|target| TicketTracker::from_target(target, http))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { trackers, redaction: RedactionProfile::for_role_name(&config.redaction_role) })
+    }
+
+    /// Redact ticket bodies at least as much as `floor`
+    pub fn with_redaction_floor(mut self, floor: RedactionProfile) -> Self {
+        self.redaction = self.redaction.at_least(floor);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trackers.is_empty()
+    }
+
+    /// Open tickets for `findings`, which must already be stored. The first ticket opened for
+    /// a finding is its back-link; other trackers still get theirs in the same run.
+    pub async fn file(&self, db: &SecretDatabase, findings: &[SecretMatch], triage: &[TicketTriage]) -> TicketReport {
+        let mut report = TicketReport::default();
+        for finding in findings {
+            let wanted: Vec<&TicketTracker> = self.trackers.iter().filter(|t| t.wants(finding)).collect();
+            if wanted.is_empty() {
+                continue;
+            }
+            match db.ticket_url(&finding.hash) {
+                Ok(None) => {}
+                Ok(Some(_)) => continue,
+                Err(e) => {
+                    report.errors.push(format!("{}: {}", finding.hash, e));
+                    continue;
+                }
+            }
+            let redacted = self.redaction.redact_match(finding);
+            let triage = triage.iter().find(|t| t.secret_hash == finding.hash);
+            let mut linked = false;
+            for tracker in wanted {
+                match tracker.open(&redacted, triage).await {
+                    Ok(url) => {
+                        info!("Opened {} for finding {}", url, finding.hash);
+                        report.opened += 1;
+                        if !linked {
+                            linked = true;
+                            if let Err(e) = db.set_ticket_url(&finding.hash, &url) {
+                                report.errors.push(format!("{}: {}", finding.hash, e));
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        error!("{} did not open a ticket for {}: {}", tracker.name(), finding.hash, e);
+                        report.errors.push(format!("{}: {}", tracker.name(), e));
+                    }
+                }
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::SecretCategory;
+
+    fn finding() -> SecretMatch {
+        SecretMatch {
+            detector_name: "AWS Access Key ID".to_string(),
+            matched_text: "AKIA********".to_string(),
+            start_position: 0,
+            end_position: 20,
+            line_number: Some(7),
+            filename: Some("deploy/prod.env".to_string()),
+            commit_sha: Some("abc123".to_string()),
+            commit_author: None,
+            suppressed: false,
+            suppressed_by: None,
+            entropy: 4.2,
+            severity: SecretSeverity::Critical,
+            category: SecretCategory::CloudProvider,
+            context: "AWS_ACCESS_KEY_ID=AKIA********".to_string(),
+            verified: true,
+            hash: "f00d".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_ticket_body_markup() {
+        let finding = finding();
+        let markdown = ticket_body(&finding, None, TicketMarkup::Markdown);
+        assert!(markdown.contains("### Context\n```\nAWS_ACCESS_KEY_ID=AKIA********\n```"));
+        assert!(markdown.contains("1. Revoke or rotate"));
+        assert!(markdown.contains("* File: deploy/prod.env:7"));
+
+        let jira = ticket_body(&finding, None, TicketMarkup::Jira);
+        assert!(jira.contains("{noformat}") && jira.contains("h3. Remediation\n# Revoke"));
+        // Untriaged findings still get the provider's rotation script
+        assert!(markdown.contains("4. 🔄 Rotate the AWS access key with the AWS CLI\n```\n# Rotates the AWS Access Key ID"));
+        assert!(jira.contains("# 🔄 Rotate the AWS access key with Terraform\n{noformat}\n# Rotates"));
+        assert_eq!(ticket_title(&finding), "[Critical] AWS Access Key ID in deploy/prod.env");
+    }
+
+    #[test]
+    fn test_ticket_body_follows_triage() {
+        let triage = TicketTriage {
+            secret_hash: "f00d".to_string(),
+            revocation_priority: "Immediate".to_string(),
+            impact_score: 0.9,
+            analysis: "Production deploy key".to_string(),
+            suggested_actions: vec!["Deactivate the key in IAM".to_string()],
+        };
+        let body = ticket_body(&finding(), Some(&triage), TicketMarkup::Markdown);
+        assert!(body.contains("### Remediation\n1. Deactivate the key in IAM"));
+        assert!(!body.contains("Revoke or rotate"));
+        assert!(body.contains("Triage: Immediate revocation priority, impact 0.90.\nProduction deploy key"));
+    }
+
+    #[test]
+    fn test_trackers_take_verified_findings_above_threshold() {
+        let target = TicketTarget::GithubIssues {
+            repository: "acme/triage".to_string(),
+            token: "t".to_string(),
+            labels: Vec::new(),
+            api_url: None,
+            minimum_severity: None,
+        };
+        let tracker = TicketTracker::from_target(&target, &HttpConfig::default()).unwrap();
+        let mut finding = finding();
+        assert!(tracker.wants(&finding));
+        finding.severity = SecretSeverity::Medium;
+        assert!(!tracker.wants(&finding));
+        finding.severity = SecretSeverity::High;
+        finding.verified = false;
+        assert!(!tracker.wants(&finding));
+    }
+}
@@ -0,0 +1,382 @@
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use reqwest::Client;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::{debug, info, warn};
+
+use crate::core::{build_client, offline, HttpConfig};
+
+/// Version of the running binary
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const DEFAULT_FEED_URL: &str = "https://api.github.com/repos/Bit-Loop/GitArchiver/releases";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    /// Tagged releases only
+    Stable,
+    /// Tagged releases and pre-releases
+    Beta,
+}
+
+impl FromStr for UpdateChannel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "stable" => Ok(UpdateChannel::Stable),
+            "beta" => Ok(UpdateChannel::Beta),
+            other => Err(anyhow!("Unknown update channel: {} (expected stable or beta)", other)),
+        }
+    }
+}
+
+impl UpdateChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+        }
+    }
+}
+
+/// Release feed and signing key used by `self-update`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateConfig {
+    /// GitHub-style releases API listing
+    pub feed_url: String,
+    pub channel: UpdateChannel,
+    /// Hex-encoded Ed25519 public key that release manifests are signed with
+    pub public_key: Option<String>,
+    /// Log a notice at startup when a newer release is available
+    pub check_on_start: bool,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            feed_url: env::var("UPDATE_FEED_URL").unwrap_or_else(|_| DEFAULT_FEED_URL.to_string()),
+            channel: env::var("UPDATE_CHANNEL")
+                .ok()
+                .and_then(|c| c.parse().ok())
+                .unwrap_or(UpdateChannel::Stable),
+            public_key: env::var("UPDATE_PUBLIC_KEY").ok().filter(|k| !k.is_empty()),
+            check_on_start: env::var("UPDATE_CHECK_ON_START")
+                .unwrap_or_else(|_| "true".to_string())
+                .to_lowercase()
+                == "true",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FeedRelease {
+    tag_name: String,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    assets: Vec<FeedAsset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FeedAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// A release that can be installed on this platform
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleaseInfo {
+    pub version: Version,
+    pub prerelease: bool,
+    pub binary_url: String,
+    pub manifest_url: String,
+    pub signature_url: String,
+}
+
+/// What a release's signature vouches for: the version, the platform asset and its digest.
+/// Signing these together, rather than the binary alone, stops a feed from passing off an
+/// older signed binary under a newer tag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseManifest {
+    pub version: Version,
+    pub asset: String,
+    /// Hex SHA-256 of the asset
+    pub sha256: String,
+}
+
+impl ReleaseManifest {
+    /// Check the manifest describes `release`'s asset for this platform, that it is newer
+    /// than `current`, and that `binary` is that asset
+    pub fn check(&self, release: &ReleaseInfo, asset_name: &str, current: &Version, binary: &[u8]) -> Result<()> {
+        if self.version != release.version {
+            return Err(anyhow!("Release {} carries a manifest signed for version {}", release.version, self.version));
+        }
+        if self.version <= *current {
+            return Err(anyhow!("Refusing to install {}: it is not newer than the running {}", self.version, current));
+        }
+        if self.asset != asset_name {
+            return Err(anyhow!("Release manifest is signed for {}, not {}", self.asset, asset_name));
+        }
+        if !hex::encode(Sha256::digest(binary)).eq_ignore_ascii_case(self.sha256.trim()) {
+            return Err(anyhow!("Downloaded {} does not match the SHA-256 in its signed manifest", asset_name));
+        }
+        Ok(())
+    }
+}
+
+/// Asset name for this platform, e.g. `github_archiver-x86_64-linux`
+pub fn platform_asset_name() -> String {
+    let suffix = if cfg!(windows) { ".exe" } else { "" };
+    format!("github_archiver-{}-{}{}", env::consts::ARCH, env::consts::OS, suffix)
+}
+
+/// Pick the newest release on the channel that ships a binary for this platform with a
+/// signed manifest (`<asset>.manifest` and `<asset>.manifest.sig`)
+fn select_release(releases: &[FeedRelease], channel: UpdateChannel, asset_name: &str) -> Option<ReleaseInfo> {
+    let manifest_name = format!("{}.manifest", asset_name);
+    let signature_name = format!("{}.sig", manifest_name);
+
+    releases
+        .iter()
+        .filter(|r| !r.draft && (channel == UpdateChannel::Beta || !r.prerelease))
+        .filter_map(|r| {
+            let version = Version::parse(r.tag_name.trim_start_matches('v')).ok()?;
+            let binary = r.assets.iter().find(|a| a.name == asset_name)?;
+            let manifest = r.assets.iter().find(|a| a.name == manifest_name)?;
+            let signature = r.assets.iter().find(|a| a.name == signature_name)?;
+            Some(ReleaseInfo {
+                version,
+                prerelease: r.prerelease,
+                binary_url: binary.browser_download_url.clone(),
+                manifest_url: manifest.browser_download_url.clone(),
+                signature_url: signature.browser_download_url.clone(),
+            })
+        })
+        .max_by(|a, b| a.version.cmp(&b.version))
+}
+
+/// Check a detached Ed25519 signature (base64) over `message`
+pub fn verify_signature(message: &[u8], signature_b64: &str, public_key_hex: &str) -> Result<()> {
+    let public_key = hex::decode(public_key_hex.trim()).context("Update public key is not valid hex")?;
+    let signature = base64::engine::general_purpose::STANDARD
+        .decode(signature_b64.trim())
+        .context("Release signature is not valid base64")?;
+
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(message, &signature)
+        .map_err(|_| anyhow!("Release signature verification failed"))
+}
+
+/// Verify a release manifest's signature and parse it
+pub fn verify_manifest(manifest: &[u8], signature_b64: &str, public_key_hex: &str) -> Result<ReleaseManifest> {
+    verify_signature(manifest, signature_b64, public_key_hex)?;
+    serde_json::from_slice(manifest).context("Signed release manifest is not valid JSON")
+}
+
+/// Checks the release feed and replaces the running binary with a verified release
+pub struct SelfUpdater {
+    client: Client,
+    config: UpdateConfig,
+}
+
+impl SelfUpdater {
+    pub fn new(config: UpdateConfig) -> Result<Self> {
+        // Air-gapped installs may still update from an allowed internal mirror
+        offline::policy().check_url(&config.feed_url)?;
+        Ok(Self {
+            client: build_client(&HttpConfig::default())?,
+            config,
+        })
+    }
+
+    /// Newest installable release on the configured channel, if it is newer than this binary
+    pub async fn check(&self) -> Result<Option<ReleaseInfo>> {
+        let releases: Vec<FeedRelease> = self.client
+            .get(&self.config.feed_url)
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse release feed")?;
+
+        let current = Version::parse(CURRENT_VERSION)?;
+        let latest = select_release(&releases, self.config.channel, &platform_asset_name());
+        debug!("Latest {} release: {:?}", self.config.channel.as_str(), latest.as_ref().map(|r| &r.version));
+
+        Ok(latest.filter(|r| r.version > current))
+    }
+
+    /// Download, verify and install a release. The previous binary is kept next to it as `.old`.
+    pub async fn install(&self, release: &ReleaseInfo) -> Result<PathBuf> {
+        let public_key = self.config.public_key.as_deref()
+            .ok_or_else(|| anyhow!("UPDATE_PUBLIC_KEY is not set; refusing to install an unverified binary"))?;
+
+        let manifest = self.client.get(&release.manifest_url).send().await?.error_for_status()?.bytes().await?;
+        let signature = self.client.get(&release.signature_url).send().await?.error_for_status()?.text().await?;
+        let manifest = verify_manifest(&manifest, &signature, public_key)?;
+        info!("Manifest signature verified for release {}", manifest.version);
+
+        info!("Downloading {} from {}", release.version, release.binary_url);
+        let binary = self.client.get(&release.binary_url).send().await?.error_for_status()?.bytes().await?;
+        manifest.check(release, &platform_asset_name(), &Version::parse(CURRENT_VERSION)?, &binary)?;
+
+        let current_exe = env::current_exe().context("Cannot locate the running binary")?;
+        replace_binary(&current_exe, &binary)?;
+        Ok(current_exe)
+    }
+}
+
+/// Swap the binary in place. Renaming the running executable works on both Unix and Windows,
+/// so the old file is moved aside before the new one takes its name.
+fn replace_binary(target: &Path, contents: &[u8]) -> Result<()> {
+    let staged = target.with_extension("new");
+    let backup = target.with_extension("old");
+
+    std::fs::write(&staged, contents).with_context(|| format!("Failed to write {}", staged.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    if backup.exists() {
+        std::fs::remove_file(&backup)?;
+    }
+    std::fs::rename(target, &backup)?;
+    if let Err(e) = std::fs::rename(&staged, target) {
+        // Put the original back so the install is never left without a binary
+        std::fs::rename(&backup, target)?;
+        return Err(anyhow!("Failed to install new binary: {}", e));
+    }
+
+    Ok(())
+}
+
+/// Passive new-version notice; never fails the caller
+pub fn spawn_version_notice(config: UpdateConfig) {
+    if !config.check_on_start || offline::policy().enabled() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let result = match SelfUpdater::new(config) {
+            Ok(updater) => updater.check().await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(Some(release)) => info!(
+                "📦 A new version {} is available (running {}). Run `self-update` to install it.",
+                release.version, CURRENT_VERSION
+            ),
+            Ok(None) => debug!("Running the latest version {}", CURRENT_VERSION),
+            Err(e) => warn!("Update check failed: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    fn release(tag: &str, prerelease: bool, asset: &str) -> FeedRelease {
+        FeedRelease {
+            tag_name: tag.to_string(),
+            prerelease,
+            draft: false,
+            assets: vec![
+                FeedAsset { name: asset.to_string(), browser_download_url: format!("https://example.com/{}/bin", tag) },
+                FeedAsset { name: format!("{}.manifest", asset), browser_download_url: format!("https://example.com/{}/manifest", tag) },
+                FeedAsset { name: format!("{}.manifest.sig", asset), browser_download_url: format!("https://example.com/{}/sig", tag) },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_channel_selection() {
+        let asset = "github_archiver-x86_64-linux";
+        let releases = vec![
+            release("v2.1.0", false, asset),
+            release("v2.2.0-beta.1", true, asset),
+            release("v2.0.5", false, asset),
+        ];
+
+        let stable = select_release(&releases, UpdateChannel::Stable, asset).unwrap();
+        assert_eq!(stable.version, Version::parse("2.1.0").unwrap());
+
+        let beta = select_release(&releases, UpdateChannel::Beta, asset).unwrap();
+        assert_eq!(beta.version, Version::parse("2.2.0-beta.1").unwrap());
+    }
+
+    #[test]
+    fn test_release_without_platform_asset_is_skipped() {
+        let releases = vec![release("v9.0.0", false, "github_archiver-aarch64-macos")];
+        assert!(select_release(&releases, UpdateChannel::Stable, "github_archiver-x86_64-linux").is_none());
+    }
+
+    fn key_pair() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    #[test]
+    fn test_signature_verification() {
+        let key_pair = key_pair();
+        let public_key = hex::encode(key_pair.public_key().as_ref());
+
+        let binary = b"new binary contents";
+        let signature = base64::engine::general_purpose::STANDARD.encode(key_pair.sign(binary).as_ref());
+
+        assert!(verify_signature(binary, &signature, &public_key).is_ok());
+        assert!(verify_signature(b"tampered", &signature, &public_key).is_err());
+    }
+
+    #[test]
+    fn test_signed_manifest_binds_version_asset_and_digest() {
+        let asset = "github_archiver-x86_64-linux";
+        let binary = b"release 2.1.0";
+        let key_pair = key_pair();
+        let public_key = hex::encode(key_pair.public_key().as_ref());
+        let sign = |manifest: &ReleaseManifest| {
+            let bytes = serde_json::to_vec(manifest).unwrap();
+            let signature = base64::engine::general_purpose::STANDARD.encode(key_pair.sign(&bytes).as_ref());
+            verify_manifest(&bytes, &signature, &public_key).unwrap()
+        };
+        let manifest = |version: &str| ReleaseManifest {
+            version: Version::parse(version).unwrap(),
+            asset: asset.to_string(),
+            sha256: hex::encode(Sha256::digest(binary)),
+        };
+        let release = select_release(&[release("v2.1.0", false, asset)], UpdateChannel::Stable, asset).unwrap();
+        assert_eq!(release.manifest_url, "https://example.com/v2.1.0/manifest");
+        let current = Version::parse("2.0.0").unwrap();
+
+        assert!(sign(&manifest("2.1.0")).check(&release, asset, &current, binary).is_ok());
+        assert!(sign(&manifest("2.1.0")).check(&release, asset, &current, b"tampered").is_err());
+        assert!(sign(&manifest("2.1.0")).check(&release, "github_archiver-aarch64-macos", &current, binary).is_err());
+        // An older signed binary served under the newer tag
+        let downgrade = sign(&manifest("1.9.0")).check(&release, asset, &current, binary).unwrap_err();
+        assert!(downgrade.to_string().contains("signed for version 1.9.0"));
+        // A correctly tagged release that is not newer than the running binary
+        let stale = sign(&manifest("2.1.0")).check(&release, asset, &Version::parse("2.1.0").unwrap(), binary).unwrap_err();
+        assert!(stale.to_string().contains("not newer"));
+
+        let bytes = serde_json::to_vec(&manifest("2.1.0")).unwrap();
+        let signature = base64::engine::general_purpose::STANDARD.encode(key_pair.sign(&bytes).as_ref());
+        let forged = serde_json::to_vec(&manifest("9.0.0")).unwrap();
+        assert!(verify_manifest(&forged, &signature, &public_key).is_err());
+    }
+}
@@ -1,17 +1,34 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/tauri';
+import { listen } from '@tauri-apps/api/event';
 import { LavaLampBackground } from './components/LavaLampBackground';
 import { Dashboard } from './components/Dashboard';
 import { SecretScanner } from './components/SecretScanner';
 import { PerformanceReport } from './components/PerformanceReport';
 import { WebhookConfig } from './components/WebhookConfig';
+import { OrganizationMonitor } from './components/OrganizationMonitor';
+import { playAlertSound } from './components/AlertSettings';
+import { Settings } from './components/Settings';
 import './style.css';
 
 interface AppState {
-  currentView: 'dashboard' | 'scanner' | 'performance' | 'webhooks';
+  currentView: 'dashboard' | 'scanner' | 'performance' | 'webhooks' | 'organizations' | 'settings';
   hunterStatus: 'idle' | 'scanning' | 'error';
   systemHealth: 'healthy' | 'warning' | 'critical';
   isInitialized: boolean;
+  theme: string;
+}
+
+// Emitted by the backend for real-time alerts that pass the toast preferences
+interface AlertToast {
+  alert: {
+    repository: string;
+    commit_sha: string;
+    alert_severity: 'Critical' | 'High' | 'Medium' | 'Low';
+    secrets_found: Array<{ detector_name: string; filename: string }>;
+  };
+  sound: string | null;
+  sticky: boolean;
 }
 
 function App() {
@@ -19,7 +36,8 @@ function App() {
     currentView: 'dashboard',
     hunterStatus: 'idle',
     systemHealth: 'healthy',
-    isInitialized: false
+    isInitialized: false,
+    theme: 'lava'
   });
 
   const [notifications, setNotifications] = useState<Array<{
@@ -27,12 +45,48 @@ function App() {
     type: 'info' | 'warning' | 'error' | 'success';
     message: string;
     timestamp: Date;
+    sticky?: boolean;
   }>>([]);
 
   useEffect(() => {
+    invoke<{ theme: string }>('get_settings')
+      .then(settings => setState(prev => ({ ...prev, theme: settings.theme })))
+      .catch(error => console.error('Failed to load settings:', error));
     initializeHunter();
   }, []);
 
+  useEffect(() => {
+    // Tray quick actions
+    const unlistenNavigate = listen<AppState['currentView']>('navigate', ({ payload }) => {
+      setState(prev => ({ ...prev, currentView: payload }));
+    });
+    const unlistenPaused = listen<boolean>('monitoring-paused', ({ payload }) => {
+      addNotification('info', payload ? 'Monitoring paused' : 'Monitoring resumed');
+    });
+    return () => {
+      unlistenNavigate.then(stop => stop());
+      unlistenPaused.then(stop => stop());
+    };
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen<AlertToast>('secret-alert', ({ payload }) => {
+      const { alert } = payload;
+      const detectors = Array.from(new Set(alert.secrets_found.map(s => s.detector_name))).join(', ');
+      const type = alert.alert_severity === 'Critical' ? 'error'
+        : alert.alert_severity === 'High' ? 'warning' : 'info';
+      addNotification(
+        type,
+        `${alert.alert_severity}: ${alert.secrets_found.length} secret(s) in ${alert.repository} @ ${alert.commit_sha.slice(0, 7)} (${detectors})`,
+        payload.sticky
+      );
+      playAlertSound(payload.sound);
+    });
+    return () => {
+      unlisten.then(stop => stop());
+    };
+  }, []);
+
   const initializeHunter = async () => {
     try {
       await invoke('initialize_hunter');
@@ -45,17 +99,21 @@ function App() {
     }
   };
 
-  const addNotification = (type: 'info' | 'warning' | 'error' | 'success', message: string) => {
-    const id = Date.now().toString();
+  const addNotification = (type: 'info' | 'warning' | 'error' | 'success', message: string, sticky = false) => {
+    const id = `${Date.now()}-${Math.random()}`;
     setNotifications(prev => [
       ...prev,
-      { id, type, message, timestamp: new Date() }
+      { id, type, message, timestamp: new Date(), sticky }
     ]);
 
-    // Auto-remove after 5 seconds
-    setTimeout(() => {
-      setNotifications(prev => prev.filter(n => n.id !== id));
-    }, 5000);
+    // Auto-remove after 5 seconds unless it has to be dismissed
+    if (!sticky) {
+      setTimeout(() => dismissNotification(id), 5000);
+    }
+  };
+
+  const dismissNotification = (id: string) => {
+    setNotifications(prev => prev.filter(n => n.id !== id));
   };
 
   const renderCurrentView = () => {
@@ -68,6 +126,10 @@ function App() {
         return <PerformanceReport />;
       case 'webhooks':
         return <WebhookConfig onNotification={addNotification} />;
+      case 'organizations':
+        return <OrganizationMonitor onNotification={addNotification} />;
+      case 'settings':
+        return <Settings onNotification={addNotification} onThemeChange={(theme) => setState(prev => ({ ...prev, theme }))} />;
       default:
         return <Dashboard systemHealth={state.systemHealth} onHealthChange={(health) => setState(prev => ({ ...prev, systemHealth: health }))} />;
     }
@@ -76,10 +138,12 @@ function App() {
   return (
     <div className="relative w-full h-screen overflow-hidden">
       {/* Lava Lamp Background */}
-      <LavaLampBackground 
-        health={state.systemHealth}
-        isScanning={state.hunterStatus === 'scanning'}
-      />
+      {state.theme === 'lava' && (
+        <LavaLampBackground
+          health={state.systemHealth}
+          isScanning={state.hunterStatus === 'scanning'}
+        />
+      )}
 
       {/* Main Application */}
       <div className="relative z-10 flex h-full">
@@ -133,6 +197,22 @@ function App() {
                   <span className="text-lg">🔗 Webhooks</span>
                 </div>
               </li>
+              <li>
+                <div
+                  className={`nav-item ${state.currentView === 'organizations' ? 'active' : ''}`}
+                  onClick={() => setState(prev => ({ ...prev, currentView: 'organizations' }))}
+                >
+                  <span className="text-lg">🏢 Organizations</span>
+                </div>
+              </li>
+              <li>
+                <div
+                  className={`nav-item ${state.currentView === 'settings' ? 'active' : ''}`}
+                  onClick={() => setState(prev => ({ ...prev, currentView: 'settings' }))}
+                >
+                  <span className="text-lg">⚙️ Settings</span>
+                </div>
+              </li>
             </ul>
           </nav>
 
@@ -190,6 +270,14 @@ function App() {
                   {notification.timestamp.toLocaleTimeString()}
                 </p>
               </div>
+              {notification.sticky && (
+                <button
+                  className="text-gray-400 hover:text-white text-sm"
+                  onClick={() => dismissNotification(notification.id)}
+                >
+                  ✗
+                </button>
+              )}
             </div>
           </div>
         ))}
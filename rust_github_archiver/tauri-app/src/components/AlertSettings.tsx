@@ -0,0 +1,237 @@
+import React, { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/tauri';
+
+interface AlertSettingsProps {
+  onNotification: (type: 'info' | 'warning' | 'error' | 'success', message: string) => void;
+}
+
+interface SeverityToast {
+  enabled: boolean;
+  sound: string | null;
+  sticky: boolean;
+}
+
+interface QuietHours {
+  start: string;
+  end: string;
+  allow_critical: boolean;
+}
+
+interface ToastPreferences {
+  critical: SeverityToast;
+  high: SeverityToast;
+  medium: SeverityToast;
+  low: SeverityToast;
+  do_not_disturb: QuietHours | null;
+}
+
+type Severity = 'critical' | 'high' | 'medium' | 'low';
+
+const SEVERITIES: Array<{ key: Severity; label: string; color: string }> = [
+  { key: 'critical', label: 'Critical', color: 'text-lava-red' },
+  { key: 'high', label: 'High', color: 'text-lava-orange' },
+  { key: 'medium', label: 'Medium', color: 'text-lava-yellow' },
+  { key: 'low', label: 'Low', color: 'text-lava-green' }
+];
+
+// Tones are synthesized so the app ships without audio assets: [frequency Hz, duration s]
+const SOUNDS: Record<string, Array<[number, number]>> = {
+  alarm: [[880, 0.15], [660, 0.15], [880, 0.15], [660, 0.15]],
+  chime: [[660, 0.12], [990, 0.25]],
+  beep: [[740, 0.15]]
+};
+
+export function playAlertSound(sound: string | null) {
+  const tones = sound ? SOUNDS[sound] : undefined;
+  if (!tones) {
+    return;
+  }
+  const context = new AudioContext();
+  let at = context.currentTime;
+  for (const [frequency, duration] of tones) {
+    const oscillator = context.createOscillator();
+    const gain = context.createGain();
+    oscillator.frequency.value = frequency;
+    gain.gain.setValueAtTime(0.2, at);
+    gain.gain.exponentialRampToValueAtTime(0.001, at + duration);
+    oscillator.connect(gain).connect(context.destination);
+    oscillator.start(at);
+    oscillator.stop(at + duration);
+    at += duration;
+  }
+  setTimeout(() => context.close(), (at - context.currentTime) * 1000 + 100);
+}
+
+// The backend serializes times as HH:MM:SS
+const toInputTime = (time: string) => time.slice(0, 5);
+const fromInputTime = (time: string) => `${time}:00`;
+
+export function AlertSettings({ onNotification }: AlertSettingsProps) {
+  const [preferences, setPreferences] = useState<ToastPreferences | null>(null);
+  const [saving, setSaving] = useState(false);
+
+  useEffect(() => {
+    loadPreferences();
+  }, []);
+
+  const loadPreferences = async () => {
+    try {
+      setPreferences(await invoke<ToastPreferences>('get_toast_preferences'));
+    } catch (error) {
+      console.error('Failed to load alert settings:', error);
+      onNotification('error', 'Failed to load alert settings');
+    }
+  };
+
+  const savePreferences = async () => {
+    if (!preferences) return;
+    setSaving(true);
+    try {
+      await invoke('save_toast_preferences', { preferences });
+      onNotification('success', 'Alert settings saved');
+    } catch (error) {
+      console.error('Failed to save alert settings:', error);
+      onNotification('error', `Failed to save alert settings: ${error}`);
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const updateSeverity = (severity: Severity, changes: Partial<SeverityToast>) => {
+    setPreferences(prev => prev && { ...prev, [severity]: { ...prev[severity], ...changes } });
+  };
+
+  const updateQuietHours = (changes: Partial<QuietHours> | null) => {
+    setPreferences(prev => {
+      if (!prev) return prev;
+      if (changes === null) return { ...prev, do_not_disturb: null };
+      const current = prev.do_not_disturb ?? { start: '22:00:00', end: '07:00:00', allow_critical: true };
+      return { ...prev, do_not_disturb: { ...current, ...changes } };
+    });
+  };
+
+  if (!preferences) {
+    return (
+      <div className="flex items-center justify-center h-full">
+        <div className="text-center">
+          <div className="animate-spin w-12 h-12 border-4 border-lava-green border-t-transparent rounded-full mx-auto mb-4"></div>
+          <p className="text-gray-300">Loading alert settings...</p>
+        </div>
+      </div>
+    );
+  }
+
+  const quiet = preferences.do_not_disturb;
+
+  return (
+    <div className="p-6 space-y-6">
+      {/* Header */}
+      <div className="flex items-center justify-between">
+        <div>
+          <h1 className="text-3xl font-bold text-white mb-2">Alert Notifications</h1>
+          <p className="text-gray-400">Desktop toasts for real-time findings while the hunter is running</p>
+        </div>
+        <button className="glow-button" onClick={savePreferences} disabled={saving}>
+          {saving ? 'Saving...' : '💾 Save'}
+        </button>
+      </div>
+
+      {/* Per-severity settings */}
+      <div className="glass-panel p-6">
+        <h2 className="text-xl font-semibold text-white mb-4">By Severity</h2>
+        <div className="space-y-3">
+          {SEVERITIES.map(({ key, label, color }) => {
+            const settings = preferences[key];
+            return (
+              <div key={key} className="flex items-center gap-4">
+                <span className={`w-24 font-semibold ${color}`}>{label}</span>
+                <label className="flex items-center gap-2 text-gray-300 text-sm">
+                  <input
+                    type="checkbox"
+                    checked={settings.enabled}
+                    onChange={(e) => updateSeverity(key, { enabled: e.target.checked })}
+                  />
+                  Show toast
+                </label>
+                <select
+                  value={settings.sound ?? ''}
+                  disabled={!settings.enabled}
+                  onChange={(e) => updateSeverity(key, { sound: e.target.value || null })}
+                  className="px-3 py-1 bg-gray-800 border border-gray-600 rounded-lg text-white text-sm"
+                >
+                  <option value="">Silent</option>
+                  {Object.keys(SOUNDS).map(sound => (
+                    <option key={sound} value={sound}>{sound}</option>
+                  ))}
+                </select>
+                <button
+                  className="px-2 py-1 bg-gray-600 hover:bg-gray-700 text-white rounded text-sm"
+                  disabled={!settings.sound}
+                  onClick={() => playAlertSound(settings.sound)}
+                >
+                  ▶
+                </button>
+                <label className="flex items-center gap-2 text-gray-300 text-sm">
+                  <input
+                    type="checkbox"
+                    checked={settings.sticky}
+                    disabled={!settings.enabled}
+                    onChange={(e) => updateSeverity(key, { sticky: e.target.checked })}
+                  />
+                  Keep until dismissed
+                </label>
+              </div>
+            );
+          })}
+        </div>
+      </div>
+
+      {/* Do not disturb */}
+      <div className="glass-panel p-6">
+        <div className="flex items-center justify-between mb-4">
+          <h2 className="text-xl font-semibold text-white">Do Not Disturb</h2>
+          <label className="flex items-center gap-2 text-gray-300 text-sm">
+            <input
+              type="checkbox"
+              checked={quiet !== null}
+              onChange={(e) => updateQuietHours(e.target.checked ? {} : null)}
+            />
+            Quiet hours
+          </label>
+        </div>
+        {quiet ? (
+          <div className="flex items-center gap-4">
+            <label className="text-gray-300 text-sm">
+              From
+              <input
+                type="time"
+                value={toInputTime(quiet.start)}
+                onChange={(e) => updateQuietHours({ start: fromInputTime(e.target.value) })}
+                className="ml-2 px-3 py-1 bg-gray-800 border border-gray-600 rounded-lg text-white"
+              />
+            </label>
+            <label className="text-gray-300 text-sm">
+              to
+              <input
+                type="time"
+                value={toInputTime(quiet.end)}
+                onChange={(e) => updateQuietHours({ end: fromInputTime(e.target.value) })}
+                className="ml-2 px-3 py-1 bg-gray-800 border border-gray-600 rounded-lg text-white"
+              />
+            </label>
+            <label className="flex items-center gap-2 text-gray-300 text-sm">
+              <input
+                type="checkbox"
+                checked={quiet.allow_critical}
+                onChange={(e) => updateQuietHours({ allow_critical: e.target.checked })}
+              />
+              Still show Critical (silently)
+            </label>
+          </div>
+        ) : (
+          <p className="text-sm text-gray-400">Toasts are shown at any time of day.</p>
+        )}
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,230 @@
+import React, { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/tauri';
+
+interface OrganizationMonitorProps {
+  onNotification: (type: 'info' | 'warning' | 'error' | 'success', message: string) => void;
+}
+
+interface OrganizationSettings {
+  enabled: boolean;
+  historical_days_back: number | null;
+}
+
+interface OrganizationHealth {
+  name: string;
+  enabled: boolean;
+  historical_days_back: number | null;
+  added_by: string;
+  added_at: string;
+  last_event_at: string | null;
+  findings_this_week: number;
+}
+
+// An organization that has been silent this long is shown as stale
+const STALE_AFTER_HOURS = 24;
+
+function lastSeen(lastEventAt: string | null): { label: string; stale: boolean } {
+  if (!lastEventAt) {
+    return { label: 'No events yet', stale: true };
+  }
+  // SQLite timestamps are UTC without a zone suffix
+  const seen = new Date(lastEventAt.replace(' ', 'T') + 'Z');
+  const hours = (Date.now() - seen.getTime()) / 3_600_000;
+  const label = hours < 1 ? `${Math.max(1, Math.round(hours * 60))} min ago`
+    : hours < 48 ? `${Math.round(hours)} h ago`
+    : `${Math.round(hours / 24)} days ago`;
+  return { label, stale: hours > STALE_AFTER_HOURS };
+}
+
+export function OrganizationMonitor({ onNotification }: OrganizationMonitorProps) {
+  const [organizations, setOrganizations] = useState<OrganizationHealth[]>([]);
+  const [loading, setLoading] = useState(true);
+  const [newName, setNewName] = useState('');
+  const [newDaysBack, setNewDaysBack] = useState('');
+
+  useEffect(() => {
+    loadOrganizations();
+    const timer = setInterval(loadOrganizations, 60_000);
+    return () => clearInterval(timer);
+  }, []);
+
+  const loadOrganizations = async () => {
+    try {
+      setOrganizations(await invoke<OrganizationHealth[]>('list_organizations'));
+    } catch (error) {
+      console.error('Failed to load organizations:', error);
+      onNotification('error', 'Failed to load monitored organizations');
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const saveOrganization = async (name: string, settings: OrganizationSettings) => {
+    try {
+      await invoke('save_organization', { name, settings });
+      await loadOrganizations();
+      return true;
+    } catch (error) {
+      console.error('Failed to save organization:', error);
+      onNotification('error', `Failed to save ${name}: ${error}`);
+      return false;
+    }
+  };
+
+  const addOrganization = async () => {
+    const name = newName.trim();
+    if (!name) {
+      onNotification('warning', 'Enter an organization name');
+      return;
+    }
+    const days = newDaysBack ? parseInt(newDaysBack, 10) : null;
+    if (await saveOrganization(name, { enabled: true, historical_days_back: days })) {
+      setNewName('');
+      setNewDaysBack('');
+      onNotification('success', `Now monitoring ${name}; restart the hunter to pick it up`);
+    }
+  };
+
+  const toggleOrganization = async (org: OrganizationHealth) => {
+    await saveOrganization(org.name, {
+      enabled: !org.enabled,
+      historical_days_back: org.historical_days_back
+    });
+  };
+
+  const updateDaysBack = async (org: OrganizationHealth, value: string) => {
+    const days = value ? parseInt(value, 10) : null;
+    if (days === org.historical_days_back || (days !== null && isNaN(days))) {
+      return;
+    }
+    await saveOrganization(org.name, { enabled: org.enabled, historical_days_back: days });
+  };
+
+  const removeOrganization = async (name: string) => {
+    if (!confirm(`Stop monitoring ${name}?`)) {
+      return;
+    }
+    try {
+      await invoke('remove_organization', { name });
+      setOrganizations(prev => prev.filter(org => org.name !== name));
+      onNotification('success', `Removed ${name}`);
+    } catch (error) {
+      console.error('Failed to remove organization:', error);
+      onNotification('error', `Failed to remove ${name}`);
+    }
+  };
+
+  if (loading) {
+    return (
+      <div className="flex items-center justify-center h-full">
+        <div className="text-center">
+          <div className="animate-spin w-12 h-12 border-4 border-lava-green border-t-transparent rounded-full mx-auto mb-4"></div>
+          <p className="text-gray-300">Loading monitored organizations...</p>
+        </div>
+      </div>
+    );
+  }
+
+  return (
+    <div className="p-6 space-y-6">
+      {/* Header */}
+      <div>
+        <h1 className="text-3xl font-bold text-white mb-2">Organization Monitoring</h1>
+        <p className="text-gray-400">
+          Organizations scanned by the hunter, when each was last heard from, and what was found this week
+        </p>
+      </div>
+
+      {/* Add Organization */}
+      <div className="glass-panel p-4 flex gap-3 items-end">
+        <div className="flex-1">
+          <label className="block text-gray-300 text-sm mb-2">Organization</label>
+          <input
+            type="text"
+            value={newName}
+            onChange={(e) => setNewName(e.target.value)}
+            onKeyDown={(e) => e.key === 'Enter' && addOrganization()}
+            placeholder="e.g., acme-corp"
+            className="w-full px-3 py-2 bg-gray-800 border border-gray-600 rounded-lg text-white focus:border-lava-green focus:outline-none"
+          />
+        </div>
+        <div className="w-48">
+          <label className="block text-gray-300 text-sm mb-2">History (days)</label>
+          <input
+            type="number"
+            min={1}
+            value={newDaysBack}
+            onChange={(e) => setNewDaysBack(e.target.value)}
+            placeholder="Default"
+            className="w-full px-3 py-2 bg-gray-800 border border-gray-600 rounded-lg text-white focus:border-lava-green focus:outline-none"
+          />
+        </div>
+        <button className="glow-button" onClick={addOrganization}>
+          ➕ Add
+        </button>
+      </div>
+
+      {/* Organization List */}
+      {organizations.length === 0 ? (
+        <div className="text-center py-12 text-gray-400">
+          <p className="text-lg mb-2">No organizations are monitored yet</p>
+          <p className="text-sm">Add one above, or pass --organizations to the hunter</p>
+        </div>
+      ) : (
+        <div className="space-y-3">
+          {organizations.map(org => {
+            const seen = lastSeen(org.last_event_at);
+            return (
+              <div key={org.name} className="glass-panel p-4 flex items-center gap-4">
+                <div className={`status-indicator ${
+                  !org.enabled ? 'bg-gray-500' : seen.stale ? 'status-warning' : 'status-healthy'
+                }`}></div>
+                <div className="flex-1">
+                  <h3 className="text-lg font-semibold text-white">{org.name}</h3>
+                  <p className="text-xs text-gray-400">
+                    Added by {org.added_by} · {org.added_at}
+                  </p>
+                </div>
+                <div className="text-right w-40">
+                  <p className="text-xs text-gray-400">Last event</p>
+                  <p className={`text-sm ${seen.stale ? 'text-lava-yellow' : 'text-gray-200'}`}>{seen.label}</p>
+                </div>
+                <div className="text-right w-32">
+                  <p className="text-xs text-gray-400">Findings (7d)</p>
+                  <p className={`text-lg font-bold ${org.findings_this_week > 0 ? 'text-lava-red' : 'text-lava-green'}`}>
+                    {org.findings_this_week}
+                  </p>
+                </div>
+                <div className="w-28">
+                  <p className="text-xs text-gray-400">History (days)</p>
+                  <input
+                    type="number"
+                    min={1}
+                    defaultValue={org.historical_days_back ?? ''}
+                    placeholder="Default"
+                    onBlur={(e) => updateDaysBack(org, e.target.value)}
+                    className="w-full px-2 py-1 bg-gray-800 border border-gray-600 rounded text-white text-sm"
+                  />
+                </div>
+                <button
+                  className={`px-3 py-2 rounded-lg text-sm text-white ${
+                    org.enabled ? 'bg-gray-600 hover:bg-gray-700' : 'bg-green-700 hover:bg-green-800'
+                  }`}
+                  onClick={() => toggleOrganization(org)}
+                >
+                  {org.enabled ? 'Pause' : 'Resume'}
+                </button>
+                <button
+                  className="px-3 py-2 bg-red-600 hover:bg-red-700 text-white rounded-lg text-sm"
+                  onClick={() => removeOrganization(org.name)}
+                >
+                  ✗
+                </button>
+              </div>
+            );
+          })}
+        </div>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,189 @@
+import React, { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/tauri';
+import { AlertSettings } from './AlertSettings';
+
+interface SettingsProps {
+  onNotification: (type: 'info' | 'warning' | 'error' | 'success', message: string) => void;
+  onThemeChange: (theme: string) => void;
+}
+
+// Mirrors DesktopSettings; notification preferences are edited by AlertSettings
+interface DesktopSettings {
+  database_path: string;
+  gcp_project_id: string;
+  theme: string;
+  notifications: unknown;
+  minimize_to_tray: boolean;
+}
+
+interface SettingsView extends DesktopSettings {
+  has_github_token: boolean;
+}
+
+export function Settings({ onNotification, onThemeChange }: SettingsProps) {
+  const [settings, setSettings] = useState<SettingsView | null>(null);
+  const [token, setToken] = useState('');
+  const [saving, setSaving] = useState(false);
+
+  useEffect(() => {
+    loadSettings();
+  }, []);
+
+  const loadSettings = async () => {
+    try {
+      setSettings(await invoke<SettingsView>('get_settings'));
+    } catch (error) {
+      console.error('Failed to load settings:', error);
+      onNotification('error', 'Failed to load settings');
+    }
+  };
+
+  // `githubToken` undefined keeps the stored token, '' removes it
+  const saveSettings = async (githubToken?: string) => {
+    if (!settings) return;
+    setSaving(true);
+    try {
+      // Re-read notification preferences so changes saved on the alerts panel are kept
+      const current = await invoke<SettingsView>('get_settings');
+      const { has_github_token, ...stored } = settings;
+      const saved = await invoke<SettingsView>('save_settings', {
+        settings: { ...stored, notifications: current.notifications },
+        githubToken: githubToken ?? null
+      });
+      setSettings(saved);
+      setToken('');
+      onThemeChange(saved.theme);
+      onNotification('success', 'Settings saved; re-initialize the hunter to apply them');
+    } catch (error) {
+      console.error('Failed to save settings:', error);
+      onNotification('error', `Failed to save settings: ${error}`);
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const reinitializeHunter = async () => {
+    try {
+      await invoke('initialize_hunter');
+      onNotification('success', 'Hunter re-initialized with the saved settings');
+    } catch (error) {
+      onNotification('error', `Failed to initialize hunter: ${error}`);
+    }
+  };
+
+  if (!settings) {
+    return (
+      <div className="flex items-center justify-center h-full">
+        <div className="text-center">
+          <div className="animate-spin w-12 h-12 border-4 border-lava-green border-t-transparent rounded-full mx-auto mb-4"></div>
+          <p className="text-gray-300">Loading settings...</p>
+        </div>
+      </div>
+    );
+  }
+
+  return (
+    <div className="space-y-6">
+      <div className="p-6 space-y-6">
+        {/* Header */}
+        <div className="flex items-center justify-between">
+          <div>
+            <h1 className="text-3xl font-bold text-white mb-2">Settings</h1>
+            <p className="text-gray-400">Saved locally; the GitHub token is kept in the OS keyring</p>
+          </div>
+          <div className="flex gap-3">
+            <button
+              className="px-4 py-2 bg-gray-600 hover:bg-gray-700 text-white rounded-lg"
+              onClick={reinitializeHunter}
+            >
+              🔄 Re-initialize Hunter
+            </button>
+            <button className="glow-button" onClick={() => saveSettings()} disabled={saving}>
+              {saving ? 'Saving...' : '💾 Save'}
+            </button>
+          </div>
+        </div>
+
+        {/* GitHub Token */}
+        <div className="glass-panel p-6">
+          <h2 className="text-xl font-semibold text-white mb-2">GitHub Token</h2>
+          <p className="text-sm text-gray-400 mb-4">
+            {settings.has_github_token
+              ? '✅ A token is stored in the keyring.'
+              : 'No token stored; the GITHUB_TOKEN environment variable is used if set.'}
+          </p>
+          <div className="flex gap-3">
+            <input
+              type="password"
+              value={token}
+              onChange={(e) => setToken(e.target.value)}
+              placeholder={settings.has_github_token ? 'Enter a new token to replace it' : 'ghp_...'}
+              className="flex-1 px-3 py-2 bg-gray-800 border border-gray-600 rounded-lg text-white focus:border-lava-green focus:outline-none"
+            />
+            <button
+              className="glow-button"
+              disabled={!token.trim() || saving}
+              onClick={() => saveSettings(token)}
+            >
+              Store
+            </button>
+            {settings.has_github_token && (
+              <button
+                className="px-4 py-2 bg-red-600 hover:bg-red-700 text-white rounded-lg"
+                disabled={saving}
+                onClick={() => saveSettings('')}
+              >
+                Remove
+              </button>
+            )}
+          </div>
+        </div>
+
+        {/* General */}
+        <div className="glass-panel p-6 space-y-4">
+          <h2 className="text-xl font-semibold text-white">General</h2>
+          <div>
+            <label className="block text-gray-300 text-sm mb-2">Findings database</label>
+            <input
+              type="text"
+              value={settings.database_path}
+              onChange={(e) => setSettings({ ...settings, database_path: e.target.value })}
+              className="w-full px-3 py-2 bg-gray-800 border border-gray-600 rounded-lg text-white focus:border-lava-green focus:outline-none"
+            />
+          </div>
+          <div>
+            <label className="block text-gray-300 text-sm mb-2">GCP project (BigQuery)</label>
+            <input
+              type="text"
+              value={settings.gcp_project_id}
+              onChange={(e) => setSettings({ ...settings, gcp_project_id: e.target.value })}
+              className="w-full px-3 py-2 bg-gray-800 border border-gray-600 rounded-lg text-white focus:border-lava-green focus:outline-none"
+            />
+          </div>
+          <div>
+            <label className="block text-gray-300 text-sm mb-2">Theme</label>
+            <select
+              value={settings.theme}
+              onChange={(e) => setSettings({ ...settings, theme: e.target.value })}
+              className="px-3 py-2 bg-gray-800 border border-gray-600 rounded-lg text-white"
+            >
+              <option value="lava">Lava lamp (animated)</option>
+              <option value="minimal">Minimal</option>
+            </select>
+          </div>
+          <label className="flex items-center gap-2 text-gray-300 text-sm">
+            <input
+              type="checkbox"
+              checked={settings.minimize_to_tray}
+              onChange={(e) => setSettings({ ...settings, minimize_to_tray: e.target.checked })}
+            />
+            Keep monitoring in the system tray when the window is closed
+          </label>
+        </div>
+      </div>
+
+      {/* Notification preferences */}
+      <AlertSettings onNotification={onNotification} />
+    </div>
+  );
+}
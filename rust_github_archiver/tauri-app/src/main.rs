@@ -4,22 +4,33 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use github_archiver::{
-    GitHubSecretHunter, HunterConfig, SecretDatabase, PerformanceEngine,
+    GitHubSecretHunter, SecretDatabase, PerformanceEngine,
     integration::{ScanningReport, HunterState, DashboardData},
     secrets::{SecretMatch, SecretSeverity},
-    performance::{SecretQueryFilters, ProcessingMetrics},
+    performance::{
+        SecretQueryFilters, ProcessingMetrics, StatsSummary,
+        MonitoredOrganization, OrganizationHealth, OrganizationSettings,
+    },
+    realtime::ToastPreferences,
     ai::TriageResult,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
-use tauri::{Manager, State};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::broadcast::error::RecvError;
 use tracing::{info, error};
 
+mod settings;
+mod tray;
+
+use settings::{DesktopSettings, SettingsView};
+
 // State management for the application
 struct AppState {
     hunter: Arc<Mutex<Option<GitHubSecretHunter>>>,
     database: Arc<Mutex<Option<SecretDatabase>>>,
     performance_engine: Arc<Mutex<PerformanceEngine>>,
+    settings: Arc<Mutex<DesktopSettings>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,14 +61,18 @@ struct TauriDashboardData {
 
 // Tauri commands
 #[tauri::command]
-async fn initialize_hunter(config_path: String, state: State<'_, AppState>) -> Result<String, String> {
-    info!("Initializing GitHub Secret Hunter from config: {}", config_path);
-    
-    // Load configuration (simplified for demo)
-    let config = HunterConfig::default();
-    
+async fn initialize_hunter(app: AppHandle, state: State<'_, AppState>) -> Result<String, String> {
+    let config = state
+        .settings
+        .lock()
+        .unwrap()
+        .hunter_config()
+        .map_err(|e| format!("Failed to load settings: {}", e))?;
+    info!("Initializing GitHub Secret Hunter with database {}", config.database_path);
+
     match GitHubSecretHunter::new(config).await {
         Ok(hunter) => {
+            forward_alert_toasts(app, &hunter, state.settings.clone());
             let mut hunter_state = state.hunter.lock().unwrap();
             *hunter_state = Some(hunter);
             Ok("Hunter initialized successfully".to_string())
@@ -191,6 +206,8 @@ async fn export_secrets(format: String, output_path: String, state: State<'_, Ap
             verified_only: false,
             last_n_days: None,
             limit: None,
+            tags: Vec::new(),
+            repository: None,
         };
 
         match db.query_secrets(&filters) {
@@ -236,6 +253,119 @@ async fn get_performance_report(state: State<'_, AppState>) -> Result<String, St
     }
 }
 
+/// Dashboard aggregations from the database rollups, so the UI does not compute them itself
+#[tauri::command]
+async fn get_finding_stats(days: Option<u32>, state: State<'_, AppState>) -> Result<StatsSummary, String> {
+    let db_mutex = state.database.lock().unwrap();
+    match *db_mutex {
+        Some(ref db) => db.stats_summary(days.unwrap_or(30), 10).map_err(|e| format!("Stats query error: {}", e)),
+        None => Err("Database not initialized".to_string()),
+    }
+}
+
+/// Monitored organizations with last event and findings this week
+#[tauri::command]
+async fn list_organizations(state: State<'_, AppState>) -> Result<Vec<OrganizationHealth>, String> {
+    let db_mutex = state.database.lock().unwrap();
+    match *db_mutex {
+        Some(ref db) => db.organization_health().map_err(|e| format!("Failed to load organizations: {}", e)),
+        None => Err("Database not initialized".to_string()),
+    }
+}
+
+#[tauri::command]
+async fn save_organization(
+    name: String,
+    settings: OrganizationSettings,
+    state: State<'_, AppState>,
+) -> Result<MonitoredOrganization, String> {
+    info!("Saving monitored organization: {}", name);
+    let db_mutex = state.database.lock().unwrap();
+    match *db_mutex {
+        Some(ref db) => db.save_organization(&name, &settings, "desktop").map_err(|e| e.to_string()),
+        None => Err("Database not initialized".to_string()),
+    }
+}
+
+#[tauri::command]
+async fn remove_organization(name: String, state: State<'_, AppState>) -> Result<bool, String> {
+    info!("Removing monitored organization: {}", name);
+    let db_mutex = state.database.lock().unwrap();
+    match *db_mutex {
+        Some(ref db) => db.remove_organization(&name).map_err(|e| e.to_string()),
+        None => Err("Database not initialized".to_string()),
+    }
+}
+
+/// Emit a `secret-alert` event for each real-time alert the toast preferences let through
+fn forward_alert_toasts(app: AppHandle, hunter: &GitHubSecretHunter, settings: Arc<Mutex<DesktopSettings>>) {
+    let mut alerts = hunter.event_monitor.subscribe_alerts();
+    tokio::spawn(async move {
+        loop {
+            let alert = match alerts.recv().await {
+                Ok(alert) => alert,
+                Err(RecvError::Lagged(skipped)) => {
+                    error!("Desktop toasts fell behind, skipped {} alerts", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+
+            let toast = settings.lock().unwrap().notifications.toast_for(&alert, chrono::Local::now().time());
+            if let Some(toast) = toast {
+                if let Err(e) = app.emit_all("secret-alert", toast) {
+                    error!("Failed to emit alert toast: {}", e);
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+async fn get_toast_preferences(state: State<'_, AppState>) -> Result<ToastPreferences, String> {
+    Ok(state.settings.lock().unwrap().notifications.clone())
+}
+
+#[tauri::command]
+async fn save_toast_preferences(preferences: ToastPreferences, state: State<'_, AppState>) -> Result<(), String> {
+    let mut settings = state.settings.lock().unwrap();
+    let mut updated = settings.clone();
+    updated.notifications = preferences;
+    updated.save().map_err(|e| format!("Failed to save toast preferences: {}", e))?;
+    *settings = updated;
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_settings(state: State<'_, AppState>) -> Result<SettingsView, String> {
+    Ok(state.settings.lock().unwrap().clone().view())
+}
+
+/// Persist settings; `github_token` is left unchanged when omitted and removed when empty.
+/// A changed database path is opened right away; the hunter picks up changes when re-initialized.
+#[tauri::command]
+async fn save_settings(
+    settings: DesktopSettings,
+    github_token: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SettingsView, String> {
+    if let Some(token) = github_token {
+        settings::set_github_token(&token).map_err(|e| e.to_string())?;
+    }
+
+    let database_changed = state.settings.lock().unwrap().database_path != settings.database_path;
+    if database_changed {
+        let db = SecretDatabase::new(&settings.database_path)
+            .map_err(|e| format!("Failed to open database {}: {}", settings.database_path, e))?;
+        *state.database.lock().unwrap() = Some(db);
+    }
+
+    settings.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    info!("Saved desktop settings");
+    *state.settings.lock().unwrap() = settings.clone();
+    Ok(settings.view())
+}
+
 #[tauri::command]
 async fn configure_webhooks(endpoints: Vec<String>, state: State<'_, AppState>) -> Result<String, String> {
     info!("Configuring webhooks: {:?}", endpoints);
@@ -271,15 +401,21 @@ async fn main() -> Result<()> {
 
     info!("Starting GitHub Secret Hunter Tauri Application");
 
+    let settings = DesktopSettings::load().unwrap_or_else(|e| {
+        error!("Failed to load settings, using defaults: {}", e);
+        DesktopSettings::default()
+    });
+
     // Initialize application state
     let app_state = AppState {
         hunter: Arc::new(Mutex::new(None)),
         database: Arc::new(Mutex::new(None)),
         performance_engine: Arc::new(Mutex::new(PerformanceEngine::new())),
+        settings: Arc::new(Mutex::new(settings.clone())),
     };
 
     // Initialize database
-    match SecretDatabase::new("secrets.db") {
+    match SecretDatabase::new(&settings.database_path) {
         Ok(db) => {
             let mut db_state = app_state.database.lock().unwrap();
             *db_state = Some(db);
@@ -291,6 +427,18 @@ async fn main() -> Result<()> {
 
     tauri::Builder::default()
         .manage(app_state)
+        .system_tray(tray::system_tray())
+        .on_system_tray_event(tray::handle_tray_event)
+        .on_window_event(|event| {
+            // Closing the window keeps the hunter running in the tray when enabled
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event.event() {
+                let state = event.window().state::<AppState>();
+                if state.settings.lock().unwrap().minimize_to_tray {
+                    api.prevent_close();
+                    let _ = event.window().hide();
+                }
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             initialize_hunter,
             start_hunting,
@@ -299,9 +447,18 @@ async fn main() -> Result<()> {
             validate_secret,
             export_secrets,
             get_performance_report,
+            get_finding_stats,
+            list_organizations,
+            save_organization,
+            remove_organization,
+            get_toast_preferences,
+            save_toast_preferences,
+            get_settings,
+            save_settings,
             configure_webhooks
         ])
         .setup(|app| {
+            tray::spawn_badge_refresh(app.handle());
             info!("Tauri application setup complete");
             Ok(())
         })
@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use github_archiver::{core::paths, realtime::ToastPreferences, HunterConfig};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Keyring service the GitHub token is stored under; never written to the settings file
+const KEYRING_SERVICE: &str = "GitArchiver";
+const KEYRING_TOKEN_ACCOUNT: &str = "github_token";
+
+/// Desktop settings persisted as JSON in the data directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DesktopSettings {
+    pub database_path: String,
+    pub gcp_project_id: String,
+    /// `lava` (animated background) or `minimal`
+    pub theme: String,
+    pub notifications: ToastPreferences,
+    /// Closing the window hides it to the system tray while monitoring continues
+    pub minimize_to_tray: bool,
+}
+
+impl Default for DesktopSettings {
+    fn default() -> Self {
+        let defaults = HunterConfig::default();
+        Self {
+            database_path: paths::default_database_path().to_string_lossy().into_owned(),
+            gcp_project_id: defaults.gcp_project_id,
+            theme: "lava".to_string(),
+            notifications: ToastPreferences::default(),
+            minimize_to_tray: true,
+        }
+    }
+}
+
+/// What the settings screen sees: the stored settings plus whether a token is set, not the token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsView {
+    #[serde(flatten)]
+    pub settings: DesktopSettings,
+    pub has_github_token: bool,
+}
+
+impl DesktopSettings {
+    pub fn path() -> PathBuf {
+        paths::data_dir().join("desktop_settings.json")
+    }
+
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Invalid settings in {}", path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Hunter configuration from these settings, with the token from the keyring when one is stored
+    pub fn hunter_config(&self) -> Result<HunterConfig> {
+        let mut config = HunterConfig::default();
+        config.database_path = self.database_path.clone();
+        if !self.gcp_project_id.trim().is_empty() {
+            config.gcp_project_id = self.gcp_project_id.trim().to_string();
+        }
+        if let Some(token) = github_token()? {
+            config.github_token = token;
+        }
+        Ok(config)
+    }
+
+    pub fn view(self) -> SettingsView {
+        SettingsView {
+            has_github_token: github_token().ok().flatten().is_some(),
+            settings: self,
+        }
+    }
+}
+
+fn token_entry() -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_TOKEN_ACCOUNT).context("OS keyring is unavailable")
+}
+
+/// The stored GitHub token; `None` when none has been saved (the `GITHUB_TOKEN` variable then applies)
+pub fn github_token() -> Result<Option<String>> {
+    match token_entry()?.get_password() {
+        Ok(token) => Ok(Some(token)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e).context("Failed to read the GitHub token from the keyring"),
+    }
+}
+
+/// Store the token in the OS keyring, or remove it when `token` is empty
+pub fn set_github_token(token: &str) -> Result<()> {
+    let entry = token_entry()?;
+    if token.trim().is_empty() {
+        return match entry.delete_password() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e).context("Failed to remove the GitHub token from the keyring"),
+        };
+    }
+    entry
+        .set_password(token.trim())
+        .context("Failed to store the GitHub token in the keyring")
+}
@@ -0,0 +1,109 @@
+use github_archiver::secrets::SecretSeverity;
+use std::time::Duration;
+use tauri::{
+    AppHandle, CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu, SystemTrayMenuItem,
+};
+use tracing::error;
+
+use crate::AppState;
+
+const BADGE_REFRESH: Duration = Duration::from_secs(30);
+
+pub fn system_tray() -> SystemTray {
+    let menu = SystemTrayMenu::new()
+        .add_item(CustomMenuItem::new("critical", "No unreviewed Critical findings").disabled())
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new("open", "Open Dashboard"))
+        .add_item(CustomMenuItem::new("pause", "Pause Monitoring"))
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new("quit", "Quit"));
+    SystemTray::new().with_menu(menu).with_tooltip("GitHub Secret Hunter")
+}
+
+pub fn handle_tray_event(app: &AppHandle, event: SystemTrayEvent) {
+    match event {
+        SystemTrayEvent::LeftClick { .. } => show_view(app, None),
+        SystemTrayEvent::MenuItemClick { id, .. } => match id.as_str() {
+            "open" => show_view(app, Some("dashboard")),
+            "pause" => toggle_monitoring(app),
+            "quit" => app.exit(0),
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+/// Bring the main window back, optionally switching the UI to `view`
+fn show_view(app: &AppHandle, view: Option<&str>) {
+    let Some(window) = app.get_window("main") else {
+        return;
+    };
+    if let Err(e) = window.show().and_then(|_| window.unminimize()).and_then(|_| window.set_focus()) {
+        error!("Failed to show main window: {}", e);
+    }
+    if let Some(view) = view {
+        let _ = window.emit("navigate", view);
+    }
+}
+
+fn toggle_monitoring(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let hunter = state.hunter.lock().unwrap();
+    let Some(hunter) = hunter.as_ref() else {
+        error!("Cannot pause monitoring: hunter not initialized");
+        return;
+    };
+
+    let monitor = &hunter.event_monitor;
+    let title = if monitor.is_paused() {
+        monitor.resume();
+        "Pause Monitoring"
+    } else {
+        monitor.pause();
+        "Resume Monitoring"
+    };
+    if let Err(e) = app.tray_handle().get_item("pause").set_title(title) {
+        error!("Failed to update tray menu: {}", e);
+    }
+    let _ = app.emit_all("monitoring-paused", monitor.is_paused());
+}
+
+/// Keep the tray badge (tooltip, menu entry and, on macOS, the title next to the icon)
+/// in step with the number of unreviewed Critical findings
+pub fn spawn_badge_refresh(app: AppHandle) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(BADGE_REFRESH);
+        loop {
+            ticker.tick().await;
+
+            let count = {
+                let state = app.state::<AppState>();
+                let db = state.database.lock().unwrap();
+                match db.as_ref().map(|db| db.unreviewed_count(&SecretSeverity::Critical)) {
+                    Some(Ok(count)) => count,
+                    Some(Err(e)) => {
+                        error!("Failed to count unreviewed findings: {}", e);
+                        continue;
+                    }
+                    None => continue,
+                }
+            };
+
+            let label = match count {
+                0 => "No unreviewed Critical findings".to_string(),
+                1 => "🚨 1 unreviewed Critical finding".to_string(),
+                n => format!("🚨 {} unreviewed Critical findings", n),
+            };
+            let tray = app.tray_handle();
+            let updated = tray
+                .get_item("critical")
+                .set_title(&label)
+                .and_then(|_| tray.set_tooltip(&format!("GitHub Secret Hunter — {}", label)));
+            #[cfg(target_os = "macos")]
+            let updated = updated.and_then(|_| tray.set_title(&if count > 0 { count.to_string() } else { String::new() }));
+            if let Err(e) = updated {
+                error!("Failed to update tray badge: {}", e);
+            }
+        }
+    });
+}